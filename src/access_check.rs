@@ -0,0 +1,204 @@
+use crate::backend::QueryBackend;
+use crate::error::KqlPanopticonError;
+use crate::workspace::Workspace;
+use serde::Serialize;
+
+/// Query sent to one workspace per subscription; it reads no tables
+pub const PROBE_QUERY: &str = "print access_check = 1";
+
+/// What the identity can do in a subscription
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "access", rename_all = "snake_case")]
+pub enum Access {
+    /// The probe query succeeded
+    Queryable,
+    /// Workspaces are listed but the probe query failed
+    ListOnly {
+        error: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hint: Option<String>,
+    },
+    /// The subscription is visible but none of its workspaces are
+    NoWorkspaces,
+}
+
+impl Access {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Access::Queryable => "query",
+            Access::ListOnly { .. } => "list only",
+            Access::NoWorkspaces => "no workspaces",
+        }
+    }
+}
+
+/// Access check result for one subscription
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionAccess {
+    pub subscription_id: String,
+    pub subscription_name: String,
+    /// Workspaces listed in the subscription
+    pub workspaces: usize,
+    /// Workspace the probe query ran on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probed_workspace: Option<String>,
+    #[serde(flatten)]
+    pub access: Access,
+}
+
+/// Probe one workspace in each subscription with a trivial query
+///
+/// `subscriptions` are (id, display name) pairs; subscriptions that only appear through their
+/// workspaces are included too. Results are sorted by subscription name.
+pub async fn check<B: QueryBackend>(
+    backend: &B,
+    subscriptions: &[(String, String)],
+    workspaces: &[Workspace],
+) -> Vec<SubscriptionAccess> {
+    let mut all: Vec<(String, String)> = subscriptions.to_vec();
+    for workspace in workspaces {
+        if !all.iter().any(|(id, _)| *id == workspace.subscription_id) {
+            all.push((
+                workspace.subscription_id.clone(),
+                workspace.subscription_name.clone(),
+            ));
+        }
+    }
+
+    let probes = all.into_iter().map(|(id, name)| async move {
+        let mut listed: Vec<&Workspace> = workspaces
+            .iter()
+            .filter(|ws| ws.subscription_id == id)
+            .collect();
+        listed.sort_by(|a, b| a.name.cmp(&b.name));
+        let Some(probe) = listed.first() else {
+            return SubscriptionAccess {
+                subscription_id: id,
+                subscription_name: name,
+                workspaces: 0,
+                probed_workspace: None,
+                access: Access::NoWorkspaces,
+            };
+        };
+        let access = match backend
+            .query_workspace(
+                &probe.workspace_id,
+                PROBE_QUERY,
+                None,
+                backend.query_timeout(),
+            )
+            .await
+        {
+            Ok(_) => Access::Queryable,
+            Err(e) => Access::ListOnly {
+                hint: hint(&e, probe.delegated),
+                error: e.to_string(),
+            },
+        };
+        SubscriptionAccess {
+            subscription_id: id,
+            subscription_name: name,
+            workspaces: listed.len(),
+            probed_workspace: Some(probe.name.clone()),
+            access,
+        }
+    });
+
+    let mut results = futures::future::join_all(probes).await;
+    results.sort_by(|a, b| {
+        a.subscription_name
+            .to_lowercase()
+            .cmp(&b.subscription_name.to_lowercase())
+    });
+    results
+}
+
+/// Likely cause of a failed probe, naming the role or permission involved
+fn hint(error: &KqlPanopticonError, delegated: bool) -> Option<String> {
+    let hint = match error {
+        KqlPanopticonError::AzureApiError { status: 403, .. } if delegated => {
+            "The Lighthouse delegation doesn't grant query access: it needs Log Analytics Reader \
+             (or another role with Microsoft.OperationalInsights/workspaces/query/read)"
+        }
+        KqlPanopticonError::AzureApiError { status: 403, .. } => {
+            "Listing workspaces only needs Reader; querying needs Log Analytics Reader (or another \
+             role with Microsoft.OperationalInsights/workspaces/query/read) on the workspace, its \
+             resource group or the subscription"
+        }
+        KqlPanopticonError::AzureApiError { status: 401, .. } => {
+            "The Log Analytics API rejected the token: sign in again, or check that the identity \
+             belongs to the workspace's tenant"
+        }
+        KqlPanopticonError::AzureApiError { status: 404, .. }
+        | KqlPanopticonError::WorkspaceNotFound(_) => {
+            "The query API can't find the workspace: it may have been deleted or moved"
+        }
+        KqlPanopticonError::AuthenticationFailed(_)
+        | KqlPanopticonError::TokenAcquisitionFailed(_) => {
+            "No token for the Log Analytics API: check the credential's tenant and scopes"
+        }
+        _ => return None,
+    };
+    Some(hint.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FakeBackend;
+
+    fn workspace(id: &str, name: &str, subscription: &str) -> Workspace {
+        Workspace {
+            workspace_id: id.to_string(),
+            resource_id: format!(
+                "/subscriptions/{}/resourceGroups/rg/providers/Microsoft.OperationalInsights/workspaces/{}",
+                subscription, name
+            ),
+            subscription_id: subscription.to_string(),
+            subscription_name: subscription.to_uppercase(),
+            ..Workspace::fixture(name)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_each_subscription() {
+        let fake = FakeBackend::new();
+        fake.respond(
+            "access_check",
+            FakeBackend::table(&["access_check"], vec![]),
+        );
+        let workspaces = vec![
+            workspace("ws-prod-b", "prod-b", "prod"),
+            workspace("ws-prod-a", "prod-a", "prod"),
+            workspace("ws-dev", "dev", "dev"),
+        ];
+        fake.fail_workspace(
+            "ws-dev",
+            KqlPanopticonError::AzureApiError {
+                status: 403,
+                message: "The provided credentials have insufficient access".to_string(),
+                code: Some("InsufficientAccessError".to_string()),
+            },
+        );
+        let subscriptions = vec![("empty".to_string(), "EMPTY".to_string())];
+
+        let report = check(&fake, &subscriptions, &workspaces).await;
+        let summary: Vec<(&str, &str, usize)> = report
+            .iter()
+            .map(|s| (s.subscription_name.as_str(), s.access.label(), s.workspaces))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("DEV", "list only", 1),
+                ("EMPTY", "no workspaces", 0),
+                ("PROD", "query", 2)
+            ]
+        );
+        assert_eq!(report[2].probed_workspace.as_deref(), Some("prod-a"));
+        let Access::ListOnly { hint, .. } = &report[0].access else {
+            panic!("expected list-only access");
+        };
+        assert!(hint.as_deref().unwrap().contains("Log Analytics Reader"));
+    }
+}
@@ -0,0 +1,334 @@
+use crate::client::{Client, QueryResponse};
+use crate::config::SubscriptionFilter;
+use crate::error::{KqlPanopticonError, Result};
+use crate::ingestion::IngestionTarget;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The Azure calls query jobs and the TUI's update loop make
+///
+/// Implemented by [`Client`] and by [`FakeBackend`], which serves scripted responses so job
+/// execution and TUI flows can be tested without Azure.
+pub trait QueryBackend: Clone + Send + Sync + 'static {
+    /// Query a single Log Analytics workspace
+    fn query_workspace(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        timespan: Option<&str>,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<QueryResponse>> + Send;
+
+    /// Fetch the next page of a result
+    fn query_next_page(
+        &self,
+        next_link: &str,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<QueryResponse>> + Send;
+
+    /// Upload a result file as a block blob
+    fn upload_blob(
+        &self,
+        url: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Send a batch of result rows to an ingestion target
+    fn ingest_rows(
+        &self,
+        target: &IngestionTarget,
+        rows: &[Value],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Default timeout for queries without their own
+    fn query_timeout(&self) -> Duration;
+
+    /// Default retry count for queries without their own
+    fn retry_count(&self) -> u32;
+
+    /// Subscription filter applied during workspace discovery
+    fn subscription_filter(&self) -> &SubscriptionFilter;
+
+    /// Copy of this backend with new timeout, retry and subscription settings
+    fn reconfigure(
+        &self,
+        validation_interval: Duration,
+        query_timeout: Duration,
+        retry_count: u32,
+        subscription_filter: SubscriptionFilter,
+    ) -> Result<Self>;
+}
+
+impl QueryBackend for Client {
+    fn query_workspace(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        timespan: Option<&str>,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<QueryResponse>> + Send {
+        Client::query_workspace(self, workspace_id, query, timespan, timeout)
+    }
+
+    fn query_next_page(
+        &self,
+        next_link: &str,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<QueryResponse>> + Send {
+        Client::query_next_page(self, next_link, timeout)
+    }
+
+    fn upload_blob(
+        &self,
+        url: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> impl Future<Output = Result<()>> + Send {
+        Client::upload_blob(self, url, content, content_type)
+    }
+
+    fn ingest_rows(
+        &self,
+        target: &IngestionTarget,
+        rows: &[Value],
+    ) -> impl Future<Output = Result<()>> + Send {
+        Client::ingest_rows(self, target, rows)
+    }
+
+    fn query_timeout(&self) -> Duration {
+        Client::query_timeout(self)
+    }
+
+    fn retry_count(&self) -> u32 {
+        Client::retry_count(self)
+    }
+
+    fn subscription_filter(&self) -> &SubscriptionFilter {
+        Client::subscription_filter(self)
+    }
+
+    fn reconfigure(
+        &self,
+        validation_interval: Duration,
+        query_timeout: Duration,
+        retry_count: u32,
+        subscription_filter: SubscriptionFilter,
+    ) -> Result<Self> {
+        Ok(
+            Client::with_config(validation_interval, query_timeout, retry_count)?
+                .with_subscription_filter(subscription_filter),
+        )
+    }
+}
+
+/// A call made to a [`FakeBackend`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FakeCall {
+    Query { workspace_id: String, query: String },
+    NextPage(String),
+    UploadBlob(String),
+    IngestRows { target: String, rows: usize },
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    /// Responses by query text fragment, checked in the order they were added
+    queries: Vec<(String, Result<Value>)>,
+    /// Errors for every query against a workspace, checked before `queries`
+    workspace_failures: HashMap<String, KqlPanopticonError>,
+    /// Pages by next link
+    pages: HashMap<String, Result<Value>>,
+    calls: Vec<FakeCall>,
+}
+
+/// In-memory backend with scripted query responses, for tests
+///
+/// Clones share the script and call log. A query without a matching response fails.
+#[derive(Debug, Clone)]
+pub struct FakeBackend {
+    state: Arc<Mutex<FakeState>>,
+    query_timeout: Duration,
+    retry_count: u32,
+    subscription_filter: SubscriptionFilter,
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self {
+            state: Arc::default(),
+            query_timeout: Duration::from_secs(30),
+            retry_count: 0,
+            subscription_filter: SubscriptionFilter::default(),
+        }
+    }
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer queries containing `fragment` with `response` (a Log Analytics response body,
+    /// see [`FakeBackend::table`])
+    pub fn respond(&self, fragment: &str, response: Value) -> &Self {
+        self.state()
+            .queries
+            .push((fragment.to_string(), Ok(response)));
+        self
+    }
+
+    /// Fail queries containing `fragment` with `error`
+    pub fn fail(&self, fragment: &str, error: KqlPanopticonError) -> &Self {
+        self.state()
+            .queries
+            .push((fragment.to_string(), Err(error)));
+        self
+    }
+
+    /// Fail every query against `workspace_id` with `error`
+    pub fn fail_workspace(&self, workspace_id: &str, error: KqlPanopticonError) -> &Self {
+        self.state()
+            .workspace_failures
+            .insert(workspace_id.to_string(), error);
+        self
+    }
+
+    /// Answer requests for `next_link` with `response`
+    pub fn respond_page(&self, next_link: &str, response: Value) -> &Self {
+        self.state()
+            .pages
+            .insert(next_link.to_string(), Ok(response));
+        self
+    }
+
+    /// Calls made so far, in order
+    pub fn calls(&self) -> Vec<FakeCall> {
+        self.state().calls.clone()
+    }
+
+    /// Response body with one table of string columns
+    pub fn table(columns: &[&str], rows: Vec<Vec<Value>>) -> Value {
+        serde_json::json!({
+            "tables": [{
+                "name": "PrimaryResult",
+                "columns": columns
+                    .iter()
+                    .map(|name| serde_json::json!({ "name": name, "type": "string" }))
+                    .collect::<Vec<_>>(),
+                "rows": rows,
+            }]
+        })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        // A panicking test poisons the lock; later calls still see the script
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn response(scripted: Option<Result<Value>>, what: &str) -> Result<QueryResponse> {
+        let value = scripted.unwrap_or_else(|| {
+            Err(KqlPanopticonError::QueryExecutionFailed(format!(
+                "No scripted response for {}",
+                what
+            )))
+        })?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl QueryBackend for FakeBackend {
+    fn query_workspace(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        _timespan: Option<&str>,
+        _timeout: Duration,
+    ) -> impl Future<Output = Result<QueryResponse>> + Send {
+        let scripted = {
+            let mut state = self.state();
+            state.calls.push(FakeCall::Query {
+                workspace_id: workspace_id.to_string(),
+                query: query.to_string(),
+            });
+            match state.workspace_failures.get(workspace_id) {
+                Some(error) => Some(Err(error.clone())),
+                None => state
+                    .queries
+                    .iter()
+                    .find(|(fragment, _)| query.contains(fragment.as_str()))
+                    .map(|(_, response)| response.clone()),
+            }
+        };
+        let result = Self::response(scripted, &format!("query '{}'", query));
+        async move { result }
+    }
+
+    fn query_next_page(
+        &self,
+        next_link: &str,
+        _timeout: Duration,
+    ) -> impl Future<Output = Result<QueryResponse>> + Send {
+        let scripted = {
+            let mut state = self.state();
+            state.calls.push(FakeCall::NextPage(next_link.to_string()));
+            state.pages.get(next_link).cloned()
+        };
+        let result = Self::response(scripted, &format!("page {}", next_link));
+        async move { result }
+    }
+
+    fn upload_blob(
+        &self,
+        url: &str,
+        _content: Vec<u8>,
+        _content_type: &str,
+    ) -> impl Future<Output = Result<()>> + Send {
+        self.state()
+            .calls
+            .push(FakeCall::UploadBlob(url.to_string()));
+        async { Ok(()) }
+    }
+
+    fn ingest_rows(
+        &self,
+        target: &IngestionTarget,
+        rows: &[Value],
+    ) -> impl Future<Output = Result<()>> + Send {
+        self.state().calls.push(FakeCall::IngestRows {
+            target: target.describe(),
+            rows: rows.len(),
+        });
+        async { Ok(()) }
+    }
+
+    fn query_timeout(&self) -> Duration {
+        self.query_timeout
+    }
+
+    fn retry_count(&self) -> u32 {
+        self.retry_count
+    }
+
+    fn subscription_filter(&self) -> &SubscriptionFilter {
+        &self.subscription_filter
+    }
+
+    fn reconfigure(
+        &self,
+        _validation_interval: Duration,
+        query_timeout: Duration,
+        retry_count: u32,
+        subscription_filter: SubscriptionFilter,
+    ) -> Result<Self> {
+        Ok(Self {
+            state: self.state.clone(),
+            query_timeout,
+            retry_count,
+            subscription_filter,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+use crate::cloud::CloudEndpoints;
+use crate::config::PackOverride;
+use crate::error::{KqlPanopticonError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where finished result files are uploaded (`blob_storage` in config.yaml, or in a pack's
+/// `settings` to send that pack's results to another container or prefix)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlobStorageConfig {
+    /// Storage account name, or its blob endpoint URL (e.g. for sovereign clouds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Container the result files are uploaded to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// Path prepended to every blob name, e.g. `hunts/2024`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Set to false to turn uploads off (e.g. for one pack)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+impl BlobStorageConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combine the global settings with a pack's overrides, for accounts in `cloud`
+    /// Returns None if uploads are off or the account or container is missing.
+    pub fn resolve(
+        global: &BlobStorageConfig,
+        pack: Option<&BlobStorageConfig>,
+        cloud: &CloudEndpoints,
+    ) -> Option<BlobDestination> {
+        let settings = Self::layered(global, pack)?;
+        Some(BlobDestination {
+            account: settings.account?,
+            container: settings.container?,
+            prefix: settings
+                .prefix
+                .unwrap_or_default()
+                .trim_matches('/')
+                .to_string(),
+            storage_suffix: cloud.storage_suffix.clone(),
+        })
+    }
+}
+
+impl PackOverride for BlobStorageConfig {
+    fn or(self, global: &Self) -> Self {
+        Self {
+            account: self.account.or_else(|| global.account.clone()),
+            container: self.container.or_else(|| global.container.clone()),
+            prefix: self.prefix.or_else(|| global.prefix.clone()),
+            enabled: self.enabled.or(global.enabled),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+}
+
+/// An enabled upload destination with its settings resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobDestination {
+    pub account: String,
+    pub container: String,
+    /// Blob name prefix without leading or trailing slashes (may be empty)
+    pub prefix: String,
+    /// Storage endpoint suffix of the cloud the account is in, e.g. `core.windows.net`
+    pub storage_suffix: String,
+}
+
+impl BlobDestination {
+    /// Blob endpoint of the account
+    fn endpoint(&self) -> String {
+        if self.account.contains("://") {
+            self.account.trim_end_matches('/').to_string()
+        } else {
+            format!("https://{}.blob.{}", self.account, self.storage_suffix)
+        }
+    }
+
+    /// URL of the blob a file is uploaded to
+    /// `relative` is the file's path under the output folder, so blobs mirror the local layout.
+    pub fn blob_url(&self, relative: &Path) -> Result<String> {
+        let mut url = reqwest::Url::parse(&self.endpoint()).map_err(|e| {
+            KqlPanopticonError::InvalidConfiguration(format!(
+                "Invalid blob storage account '{}': {}",
+                self.account, e
+            ))
+        })?;
+        let file_segments: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                std::path::Component::Normal(segment) => {
+                    Some(segment.to_string_lossy().into_owned())
+                }
+                _ => None,
+            })
+            .collect();
+        url.path_segments_mut()
+            .map_err(|_| {
+                KqlPanopticonError::InvalidConfiguration(format!(
+                    "Invalid blob storage account '{}'",
+                    self.account
+                ))
+            })?
+            .pop_if_empty()
+            .push(&self.container)
+            .extend(self.prefix.split('/').filter(|segment| !segment.is_empty()))
+            .extend(&file_segments);
+        Ok(url.to_string())
+    }
+}
+
+/// Outcome of uploading one result file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobUpload {
+    /// Local file that was uploaded
+    pub file: PathBuf,
+    pub url: String,
+    /// Why the upload failed (None when it succeeded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Content type sent with an uploaded result file
+pub(crate) fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => "text/csv",
+        Some("json") => "application/json",
+        Some("ndjson") => "application/x-ndjson",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::CloudEnvironment;
+
+    #[test]
+    fn test_resolve_and_blob_url() {
+        let public = CloudEndpoints::for_environment(CloudEnvironment::Public);
+        let global = BlobStorageConfig {
+            account: Some("huntresults".to_string()),
+            container: Some("kql".to_string()),
+            prefix: Some("/team/".to_string()),
+            ..Default::default()
+        };
+        let destination = BlobStorageConfig::resolve(&global, None, &public).unwrap();
+        assert_eq!(
+            destination
+                .blob_url(Path::new("prod/ws_eu/2024-01-01_00-00-00/Failed logons.csv"))
+                .unwrap(),
+            "https://huntresults.blob.core.windows.net/kql/team/prod/ws_eu/2024-01-01_00-00-00/Failed%20logons.csv"
+        );
+
+        let pack = BlobStorageConfig {
+            account: Some("https://huntresults.blob.core.usgovcloudapi.net/".to_string()),
+            prefix: Some(String::new()),
+            ..Default::default()
+        };
+        let destination = BlobStorageConfig::resolve(&global, Some(&pack), &public).unwrap();
+        assert_eq!(
+            destination.blob_url(Path::new("a.json")).unwrap(),
+            "https://huntresults.blob.core.usgovcloudapi.net/kql/a.json"
+        );
+
+        let china = CloudEndpoints::for_environment(CloudEnvironment::China);
+        let destination = BlobStorageConfig::resolve(&global, None, &china).unwrap();
+        assert_eq!(
+            destination.blob_url(Path::new("a.csv")).unwrap(),
+            "https://huntresults.blob.core.chinacloudapi.cn/kql/team/a.csv"
+        );
+
+        let muted = BlobStorageConfig {
+            enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(BlobStorageConfig::resolve(&global, Some(&muted), &public).is_none());
+        assert!(BlobStorageConfig::resolve(&BlobStorageConfig::default(), None, &public).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "kql-panopticon")]
@@ -9,6 +10,34 @@ use clap::{Parser, Subcommand, ValueEnum};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Directory for config, sessions, packs, runs and cache (default: ~/.kql-panopticon)
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        env = "KQL_PANOPTICON_DATA_DIR"
+    )]
+    pub data_dir: Option<PathBuf>,
+
+    /// Save every Azure API response under this directory (fixtures for --replay)
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        env = "KQL_PANOPTICON_RECORD",
+        conflicts_with = "replay"
+    )]
+    pub record: Option<PathBuf>,
+
+    /// Serve Azure API responses saved by --record from this directory instead of calling
+    /// Azure (no login needed)
+    #[arg(long, global = true, value_name = "DIR", env = "KQL_PANOPTICON_REPLAY")]
+    pub replay: Option<PathBuf>,
+
+    /// Launch the TUI with query execution disabled (drafting and browsing only)
+    #[arg(long)]
+    pub read_only: bool,
 }
 
 #[derive(Subcommand)]
@@ -19,13 +48,18 @@ pub enum Commands {
     /// Run a query pack
     RunPack {
         /// Path to query pack file (.yaml, .yml, or .json)
-        /// Can be absolute path or relative to ~/.kql-panopticon/packs/
+        /// Can be absolute path or relative to a pack library (e.g. ~/.kql-panopticon/packs/)
         pack: String,
 
         /// Override workspace selection (comma-separated IDs or 'all')
         #[arg(short, long)]
         workspaces: Option<String>,
 
+        /// Pick workspaces interactively (fuzzy search, multi-select) before executing
+        /// Starts from the --workspaces or pack selection
+        #[arg(short, long)]
+        interactive: bool,
+
         /// Output format
         #[arg(short = 'f', long, value_enum, default_value = "files")]
         format: OutputFormat,
@@ -34,9 +68,80 @@ pub enum Commands {
         #[arg(long)]
         json: bool,
 
+        /// Only run queries with any of these tags (comma-separated; pack tags apply to all queries)
+        #[arg(short, long, value_delimiter = ',')]
+        tags: Vec<String>,
+
         /// Validate pack without executing
         #[arg(long)]
         validate_only: bool,
+
+        /// Bypass the workspace discovery cache and re-enumerate subscriptions
+        #[arg(long)]
+        refresh_workspaces: bool,
+
+        /// Estimate the billed data scanned by each query with a follow-up _BilledSize query
+        #[arg(long)]
+        estimate_cost: bool,
+
+        /// Write a <job_name>.meta.json sidecar with query metadata next to each CSV
+        #[arg(long)]
+        csv_metadata: bool,
+
+        /// Also append results to this SQLite database (one table per query)
+        #[arg(long, value_name = "PATH")]
+        sqlite: Option<PathBuf>,
+
+        /// Keep rows already fetched when pagination fails, marking the output truncated
+        #[arg(long)]
+        keep_partial: bool,
+
+        /// Smoke-test the pack: append `| take N` to each query (overrides `sample_rows`)
+        #[arg(long, value_name = "N")]
+        sample: Option<usize>,
+
+        /// Don't write output files for query/workspace pairs that return no rows
+        #[arg(long)]
+        skip_empty: bool,
+
+        /// Write the IPs, domains, hashes and emails found in the results to IOC files
+        #[arg(long)]
+        extract_iocs: bool,
+
+        /// Confirm runs of packs with `requires_confirmation` or above `confirm_jobs_threshold`
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Resume an interrupted run from its manifest, skipping completed query/workspace pairs
+        #[arg(long, value_name = "MANIFEST")]
+        resume: Option<std::path::PathBuf>,
+    },
+
+    /// Run a single query and print the results to stdout
+    RunQuery {
+        /// KQL query text, or '-' to read it from stdin
+        #[arg(required_unless_present = "query_file", conflicts_with = "query_file")]
+        query: Option<String>,
+
+        /// Read the query from a file ('-' for stdin)
+        #[arg(short, long, value_name = "PATH")]
+        query_file: Option<PathBuf>,
+
+        /// Workspaces to query (comma-separated IDs or names, or 'all')
+        #[arg(short, long, default_value = "all")]
+        workspaces: String,
+
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value = "json")]
+        format: QueryOutputFormat,
+
+        /// Stop after this many rows per workspace (default: config `max_rows`)
+        #[arg(long)]
+        max_rows: Option<usize>,
+
+        /// Bypass the workspace discovery cache and re-enumerate subscriptions
+        #[arg(long)]
+        refresh_workspaces: bool,
     },
 
     /// Export a session as a query pack
@@ -44,13 +149,163 @@ pub enum Commands {
         /// Session name to export
         session: String,
 
-        /// Output path (default: ~/.kql-panopticon/packs/<session-name>.yaml)
+        /// Output path (default: <data-dir>/packs/<session-name>.yaml)
         #[arg(short, long)]
         output: Option<std::path::PathBuf>,
 
         /// Output format
         #[arg(short = 'f', long, value_enum, default_value = "yaml")]
         format: PackFormat,
+
+        /// Only export queries from jobs that completed successfully
+        #[arg(long)]
+        only_successful: bool,
+
+        /// Only export jobs whose query name matches this regex (case-insensitive)
+        #[arg(long, value_name = "REGEX")]
+        query_name_pattern: Option<String>,
+
+        /// Pack author
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Pack description (default: "Exported from session: <session>")
+        #[arg(long)]
+        description: Option<String>,
+
+        /// Pack version
+        #[arg(long)]
+        version: Option<String>,
+    },
+
+    /// Bundle a session and the output files its jobs reference into a .tar.gz archive
+    ExportSession {
+        /// Session name to export
+        session: String,
+
+        /// Archive path (default: ./<session-name>.tar.gz)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Restore a session archive written by export-session, rewriting output paths
+    ImportSession {
+        /// Archive to import
+        archive: std::path::PathBuf,
+
+        /// Folder to extract the output files into
+        #[arg(short, long, default_value = "./output")]
+        output_folder: std::path::PathBuf,
+
+        /// Save the session under this name instead of the one in the archive
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+
+    /// Report MITRE ATT&CK coverage of the pack library as an ATT&CK Navigator layer
+    Coverage {
+        /// Session to check for exercised techniques (completed jobs running a mapped query)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Write the layer JSON to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Delete old sessions and job output (limits default to config `retention`)
+    Clean {
+        /// Delete sessions last saved more than this many days ago
+        #[arg(long, value_name = "DAYS")]
+        max_age_days: Option<u64>,
+
+        /// Keep only this many of the most recently saved sessions
+        #[arg(long, value_name = "COUNT")]
+        max_sessions: Option<usize>,
+
+        /// Delete the oldest run folders once the output folder grows past this size
+        #[arg(long, value_name = "MB")]
+        max_output_mb: Option<u64>,
+
+        /// Output folder to check against --max-output-mb
+        #[arg(short, long, default_value = "./output")]
+        output_folder: std::path::PathBuf,
+
+        /// List what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Search result files for a string or regex (e.g. an IOC) and list the matching files
+    Search {
+        /// Text to search for (case-insensitive)
+        pattern: String,
+
+        /// Treat PATTERN as a regular expression
+        #[arg(short = 'e', long)]
+        regex: bool,
+
+        /// Only search the output of this saved session
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Output folder to search when no session is given
+        #[arg(short, long, default_value = "./output")]
+        output_folder: std::path::PathBuf,
+
+        /// Print the first matching lines of each file
+        #[arg(short = 'l', long)]
+        show_lines: bool,
+    },
+
+    /// Check every pack in the pack libraries (or in PATH) and print a pass/fail table
+    /// Exits with an error if any pack fails, for use in CI.
+    Test {
+        /// Pack library directory to check instead of the configured libraries
+        path: Option<std::path::PathBuf>,
+
+        /// Also run each query with `| take 0` against the test workspace
+        #[arg(long)]
+        online: bool,
+
+        /// Workspace ID or name for --online (default: config `test_workspace`)
+        #[arg(short, long)]
+        workspace: Option<String>,
+
+        /// Bypass the workspace discovery cache and re-enumerate subscriptions
+        #[arg(long)]
+        refresh_workspaces: bool,
+    },
+
+    /// Run a trivial query on one workspace per subscription and report where queries are allowed
+    /// Subscriptions whose workspaces can be listed but not queried are shown with the error.
+    CheckAccess {
+        /// Bypass the workspace discovery cache and re-enumerate subscriptions
+        #[arg(long)]
+        refresh_workspaces: bool,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Sync the personal pack library with its git remote (config `pack_remote`)
+    Packs {
+        #[command(subcommand)]
+        command: PacksCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PacksCommand {
+    /// Pull pack updates (clones the remote into an empty library)
+    Pull,
+
+    /// Commit local pack changes and push them
+    Push {
+        /// Commit message
+        #[arg(short, long, default_value = "Update query packs")]
+        message: String,
     },
 }
 
@@ -60,6 +315,20 @@ pub enum OutputFormat {
     Files,
     /// Print to stdout as JSON
     Stdout,
+    /// Stream rows to stdout as they arrive, one JSON object per line with `Query` and
+    /// `Workspace` keys (no files are written)
+    Ndjson,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+pub enum QueryOutputFormat {
+    /// JSON array with one result object (metadata, columns, rows) per workspace
+    Json,
+    /// CSV rows (with a leading Workspace column when querying several workspaces)
+    Csv,
+    /// One JSON object per row, printed as pages arrive (with a `Workspace` key when querying
+    /// several workspaces)
+    Ndjson,
 }
 
 #[derive(ValueEnum, Clone)]
@@ -0,0 +1,100 @@
+use crate::{
+    access_check::{self, Access, SubscriptionAccess},
+    client::Client,
+    config::Config,
+    error::Result,
+};
+
+/// Options for the check-access command
+pub struct CheckAccessOptions {
+    pub refresh_workspaces: bool,
+    pub json: bool,
+}
+
+pub async fn execute(options: CheckAccessOptions) -> Result<()> {
+    let config = Config::resolve();
+    let client = Client::from_config(&config)?;
+
+    eprintln!("Authenticating with Azure...");
+    client.force_validate_auth().await?;
+
+    eprintln!("Loading subscriptions and workspaces...");
+    let filter = client.subscription_filter();
+    let subscriptions: Vec<(String, String)> = client
+        .list_subscriptions()
+        .await?
+        .into_iter()
+        .filter(|s| filter.allows(&s.subscription_id, &s.display_name))
+        .map(|s| (s.subscription_id, s.display_name))
+        .collect();
+    let cache_ttl = config.workspace_cache_ttl();
+    let workspaces = client
+        .list_workspaces_cached(cache_ttl, options.refresh_workspaces)
+        .await?;
+
+    eprintln!("Probing {} subscription(s)...", subscriptions.len());
+    let report = access_check::check(&client, &subscriptions, &workspaces).await;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if report.is_empty() {
+        eprintln!("No subscriptions found");
+        return Ok(());
+    }
+    print_table(&report);
+
+    let failures: Vec<&SubscriptionAccess> = report
+        .iter()
+        .filter(|s| matches!(s.access, Access::ListOnly { .. }))
+        .collect();
+    for subscription in &failures {
+        if let Access::ListOnly { error, hint } = &subscription.access {
+            println!();
+            println!(
+                "{} ({}):",
+                subscription.subscription_name, subscription.subscription_id
+            );
+            println!("  error: {}", error);
+            if let Some(hint) = hint {
+                println!("  hint:  {}", hint);
+            }
+        }
+    }
+
+    let queryable = report
+        .iter()
+        .filter(|s| s.access == Access::Queryable)
+        .count();
+    println!();
+    println!(
+        "{} of {} subscription(s) queryable, {} list only",
+        queryable,
+        report.len(),
+        failures.len()
+    );
+    Ok(())
+}
+
+fn print_table(report: &[SubscriptionAccess]) {
+    let width = report
+        .iter()
+        .map(|s| s.subscription_name.len())
+        .max()
+        .unwrap_or(0)
+        .max("SUBSCRIPTION".len());
+    println!(
+        "{:<width$}  {:<13}  {:>10}  PROBED",
+        "SUBSCRIPTION", "ACCESS", "WORKSPACES"
+    );
+    for subscription in report {
+        println!(
+            "{:<width$}  {:<13}  {:>10}  {}",
+            subscription.subscription_name,
+            subscription.access.label(),
+            subscription.workspaces,
+            subscription.probed_workspace.as_deref().unwrap_or("-")
+        );
+    }
+}
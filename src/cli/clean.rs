@@ -0,0 +1,48 @@
+use crate::config::RetentionPolicy;
+use crate::error::{KqlPanopticonError, Result};
+use crate::retention::{self, CleanPlan};
+use crate::units::format_bytes;
+use std::path::Path;
+
+/// Delete sessions and run folders beyond the retention limits
+pub fn execute(policy: &RetentionPolicy, output_folder: &Path, dry_run: bool) -> Result<()> {
+    if policy.is_empty() {
+        return Err(KqlPanopticonError::InvalidConfiguration(
+            "No retention limits set (use --max-age-days, --max-sessions, --max-output-mb or config `retention`)"
+                .to_string(),
+        ));
+    }
+
+    let sessions = retention::all_sessions()?;
+    let plan = CleanPlan::for_policy(policy, &sessions, output_folder, chrono::Local::now());
+    if plan.is_empty() {
+        eprintln!("✓ Nothing to clean");
+        return Ok(());
+    }
+
+    for name in &plan.sessions {
+        eprintln!("  session  {}", name);
+    }
+    for dir in &plan.run_dirs {
+        eprintln!("  output   {}", dir.display());
+    }
+
+    if dry_run {
+        eprintln!(
+            "Would delete {} session(s) and {} run folder(s), freeing {}",
+            plan.sessions.len(),
+            plan.run_dirs.len(),
+            format_bytes(plan.bytes)
+        );
+        return Ok(());
+    }
+
+    plan.apply()?;
+    eprintln!(
+        "✓ Deleted {} session(s) and {} run folder(s), freed {}",
+        plan.sessions.len(),
+        plan.run_dirs.len(),
+        format_bytes(plan.bytes)
+    );
+    Ok(())
+}
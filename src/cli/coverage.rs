@@ -0,0 +1,63 @@
+use crate::{coverage::Coverage, error::Result, session::Session};
+use std::path::PathBuf;
+
+/// Print library technique coverage and emit an ATT&CK Navigator layer
+pub fn execute(session_name: Option<String>, output: Option<PathBuf>) -> Result<()> {
+    eprintln!("Scanning pack libraries...");
+    let mut coverage = Coverage::from_libraries()?;
+
+    let (name, description) = match &session_name {
+        Some(session_name) => {
+            eprintln!("Loading session '{}'...", session_name);
+            let session = Session::load(session_name)?;
+            coverage.mark_session(&session);
+            (
+                format!("kql-panopticon coverage ({})", session_name),
+                format!(
+                    "Techniques covered by the pack library; green were exercised in session '{}'",
+                    session_name
+                ),
+            )
+        }
+        None => (
+            "kql-panopticon coverage".to_string(),
+            "Techniques covered by the pack library".to_string(),
+        ),
+    };
+
+    if coverage.techniques.is_empty() {
+        eprintln!("No queries have `mitre` technique mappings");
+    }
+    for (id, technique) in &coverage.techniques {
+        eprintln!(
+            "  {:<10} {} quer{}{}",
+            id,
+            technique.queries.len(),
+            if technique.queries.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            if technique.exercised {
+                " (exercised)"
+            } else {
+                ""
+            }
+        );
+    }
+    eprintln!("✓ {} technique(s) covered", coverage.techniques.len());
+    if session_name.is_some() {
+        eprintln!("  {} exercised in the session", coverage.exercised_count());
+    }
+
+    let layer = serde_json::to_string_pretty(&coverage.navigator_layer(&name, &description))?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, layer)?;
+            eprintln!("  Layer: {}", path.display());
+        }
+        None => println!("{}", layer),
+    }
+
+    Ok(())
+}
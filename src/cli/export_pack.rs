@@ -1,4 +1,8 @@
-use crate::{error::Result, query_pack::QueryPack, session::Session};
+use crate::{
+    error::Result,
+    query_pack::QueryPack,
+    session::{PackExportFilter, Session},
+};
 use std::path::PathBuf;
 
 #[derive(Clone, Copy)]
@@ -7,14 +11,46 @@ pub enum PackFormat {
     Json,
 }
 
-pub fn execute(session_name: String, output: Option<PathBuf>, format: PackFormat) -> Result<()> {
+/// Job filters and pack metadata for the export-pack command
+pub struct ExportPackOptions {
+    pub only_successful: bool,
+    pub query_name_pattern: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+}
+
+pub fn execute(
+    session_name: String,
+    output: Option<PathBuf>,
+    format: PackFormat,
+    options: ExportPackOptions,
+) -> Result<()> {
+    let filter = PackExportFilter {
+        only_successful: options.only_successful,
+        query_name: options
+            .query_name_pattern
+            .as_deref()
+            .map(|pattern| crate::result_search::matcher(pattern, true))
+            .transpose()?,
+    };
+
     // Load session
     eprintln!("Loading session '{}'...", session_name);
     let session = Session::load(&session_name)?;
 
     // Convert to query pack
     eprintln!("Converting session to query pack...");
-    let pack = session.to_query_pack()?;
+    let mut pack = session.to_query_pack_filtered(&filter)?;
+    if options.author.is_some() {
+        pack.author = options.author;
+    }
+    if options.description.is_some() {
+        pack.description = options.description;
+    }
+    if options.version.is_some() {
+        pack.version = options.version;
+    }
 
     // Validate generated pack
     pack.validate()?;
@@ -23,7 +59,7 @@ pub fn execute(session_name: String, output: Option<PathBuf>, format: PackFormat
     let output_path = if let Some(path) = output {
         path
     } else {
-        // Default: ~/.kql-panopticon/packs/<session-name>.yaml
+        // Default: <data_dir>/packs/<session-name>.yaml
         let extension = match format {
             PackFormat::Yaml => "yaml",
             PackFormat::Json => "json",
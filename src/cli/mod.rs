@@ -1,3 +1,13 @@
 pub mod args;
+pub mod check_access;
+pub mod clean;
+pub mod coverage;
 pub mod export_pack;
+pub mod ndjson;
+pub mod packs;
 pub mod run_pack;
+pub mod run_query;
+pub mod search;
+pub mod session_archive;
+pub mod test_packs;
+pub mod workspace_picker;
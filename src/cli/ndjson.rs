@@ -0,0 +1,42 @@
+use crate::error::Result;
+use crate::query_job::RowSink;
+use crate::workspace::Workspace;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Prints rows to stdout as NDJSON (one object per line) as each page arrives
+pub struct NdjsonStdout {
+    /// Added to every row as `Query` (pack runs mix several queries)
+    query: Option<String>,
+    /// Add the workspace name to every row as `Workspace`
+    with_workspace: bool,
+}
+
+impl NdjsonStdout {
+    pub fn new(query: Option<String>, with_workspace: bool) -> Self {
+        Self {
+            query,
+            with_workspace,
+        }
+    }
+}
+
+impl RowSink for NdjsonStdout {
+    fn write_page(&self, workspace: &Workspace, rows: Vec<Map<String, Value>>) -> Result<()> {
+        // Hold the lock for the whole page so concurrent workspaces don't interleave lines
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        for mut row in rows {
+            if self.with_workspace {
+                row.insert("Workspace".to_string(), workspace.name.clone().into());
+            }
+            if let Some(query) = &self.query {
+                row.insert("Query".to_string(), query.clone().into());
+            }
+            serde_json::to_writer(&mut out, &row)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
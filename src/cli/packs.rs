@@ -0,0 +1,41 @@
+use crate::{config::Config, error::Result, pack_sync, query_pack::QueryPack};
+
+/// Pull pack updates from the configured git remote into the personal library
+pub async fn pull() -> Result<()> {
+    let library = QueryPack::get_library_path("")?;
+    let config = Config::load_or_default();
+
+    eprintln!("Pulling packs into {}...", library.display());
+    let changes = pack_sync::pull(&library, config.pack_remote.as_deref()).await?;
+
+    if changes.is_empty() {
+        eprintln!("✓ Pack library is up to date");
+    } else {
+        eprintln!("✓ Pulled {} pack change(s):", changes.len());
+        for change in &changes {
+            eprintln!("  {}", change);
+        }
+    }
+
+    Ok(())
+}
+
+/// Commit local pack changes and push them to the configured git remote
+pub async fn push(message: String) -> Result<()> {
+    let library = QueryPack::get_library_path("")?;
+    let config = Config::load_or_default();
+
+    eprintln!("Pushing packs from {}...", library.display());
+    let changes = pack_sync::push(&library, config.pack_remote.as_deref(), &message).await?;
+
+    if changes.is_empty() {
+        eprintln!("✓ No local pack changes to commit; pushed existing commits");
+    } else {
+        eprintln!("✓ Pushed {} pack change(s):", changes.len());
+        for change in &changes {
+            eprintln!("  {}", change);
+        }
+    }
+
+    Ok(())
+}
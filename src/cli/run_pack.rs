@@ -1,63 +1,184 @@
 use crate::cli::args::OutputFormat;
 use crate::{
     client::Client,
+    config::Config,
     error::Result,
-    query_job::{QueryJobBuilder, QueryJobResult},
-    query_pack::{QueryPack, WorkspaceScope},
+    paths,
+    query_job::{QueryJobBuilder, QueryJobResult, QuerySettings},
+    query_pack::{QueryPack, Severity, WorkspaceScope},
+    run_manifest::RunManifest,
+    webhook::{RunSummary, WebhookConfig},
     workspace::Workspace,
 };
-use std::path::Path;
-
-pub async fn execute(
-    pack_path: String,
-    workspaces_override: Option<String>,
-    format: OutputFormat,
-    json_output: bool,
-    validate_only: bool,
-) -> Result<()> {
+use futures::stream::{self, StreamExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Options for the run-pack command
+pub struct RunPackOptions {
+    pub workspaces_override: Option<String>,
+    pub interactive: bool,
+    pub format: OutputFormat,
+    pub json_output: bool,
+    pub tags: Vec<String>,
+    pub validate_only: bool,
+    pub refresh_workspaces: bool,
+    pub resume: Option<PathBuf>,
+    pub estimate_cost: bool,
+    pub csv_metadata: bool,
+    pub sqlite: Option<PathBuf>,
+    pub keep_partial: bool,
+    pub sample: Option<usize>,
+    pub skip_empty: bool,
+    pub extract_iocs: bool,
+    pub yes: bool,
+}
+
+pub async fn execute(pack_path: String, options: RunPackOptions) -> Result<()> {
+    let RunPackOptions {
+        workspaces_override,
+        interactive,
+        format,
+        json_output,
+        tags,
+        validate_only,
+        refresh_workspaces,
+        resume,
+        estimate_cost,
+        csv_metadata,
+        sqlite,
+        keep_partial,
+        sample,
+        skip_empty,
+        extract_iocs,
+        yes,
+    } = options;
+
     // Load pack
-    let pack = load_pack(&pack_path)?;
+    let (pack, resolved_pack_path) = load_pack(&pack_path)?;
 
     // Validate
     pack.validate()?;
 
+    // Narrow to the requested tags
+    let queries = pack.queries_with_tags(&tags);
+    if queries.is_empty() {
+        return Err(crate::error::KqlPanopticonError::QueryPackValidation(
+            format!("No queries in pack match tags: {}", tags.join(", ")),
+        ));
+    }
+
     if validate_only {
         eprintln!("✓ Query pack is valid");
         eprintln!("  Name: {}", pack.name);
-        eprintln!("  Queries: {}", pack.get_queries().len());
+        eprintln!("  Queries: {}", queries.len());
+        if !pack.all_tags().is_empty() {
+            eprintln!("  Tags: {}", pack.all_tags().join(", "));
+        }
+        if let Some(timespan) = &pack.default_timespan {
+            eprintln!("  Default timespan: {}", timespan);
+        }
+        if let Some(frequency) = &pack.recommended_frequency {
+            eprintln!("  Recommended frequency: {}", frequency);
+        }
         return Ok(());
     }
 
     // Initialize client
-    let client = Client::new()?;
+    let config = Config::resolve();
+    let client = Client::from_config(&config)?;
 
     eprintln!("Authenticating with Azure...");
     client.force_validate_auth().await?;
 
     eprintln!("Loading workspaces...");
-    let all_workspaces = client.list_workspaces().await?;
+    let cache_ttl = config.workspace_cache_ttl();
+    let all_workspaces = client
+        .list_workspaces_cached(cache_ttl, refresh_workspaces)
+        .await?;
 
     // Determine workspace selection
-    let selected_workspaces = select_workspaces(
+    let mut selected_workspaces = select_workspaces(
         &all_workspaces,
         workspaces_override,
         pack.workspaces.as_ref(),
     )?;
 
+    if interactive {
+        selected_workspaces =
+            super::workspace_picker::pick_workspaces(&all_workspaces, &selected_workspaces)?;
+        eprintln!("Selected {} workspace(s)", selected_workspaces.len());
+    }
+
     if selected_workspaces.is_empty() {
         return Err(crate::error::KqlPanopticonError::QueryPackValidation(
             "No workspaces selected for execution".into(),
         ));
     }
 
+    // High-impact runs need --yes
+    if let Some(reason) = pack.confirmation_reason(
+        queries.len(),
+        selected_workspaces.len(),
+        config.confirm_jobs_threshold,
+    ) {
+        if !yes {
+            return Err(crate::error::KqlPanopticonError::Other(format!(
+                "{} ({} queries x {} workspaces); re-run with --yes to execute",
+                reason,
+                queries.len(),
+                selected_workspaces.len()
+            )));
+        }
+        eprintln!("{}; confirmed with --yes", reason);
+    }
+
+    // Load the manifest being resumed, or start a new one
+    let mut manifest = match resume {
+        Some(manifest_path) => {
+            let manifest = RunManifest::load(&manifest_path)?;
+            if manifest.pack_name != pack.name {
+                return Err(crate::error::KqlPanopticonError::InvalidConfiguration(
+                    format!(
+                        "Manifest {} belongs to pack '{}', not '{}'",
+                        manifest_path.display(),
+                        manifest.pack_name,
+                        pack.name
+                    ),
+                ));
+            }
+            manifest
+        }
+        None => RunManifest::new(&pack.name, &resolved_pack_path)?,
+    };
+    for pack_query in &queries {
+        for workspace in &selected_workspaces {
+            manifest.ensure_entry(&pack_query.name, &workspace.workspace_id, &workspace.name);
+        }
+    }
+    manifest.save()?;
+    eprintln!("Run manifest: {}", manifest.path().display());
+
+    let skipped = queries
+        .iter()
+        .flat_map(|q| {
+            selected_workspaces
+                .iter()
+                .filter(|ws| manifest.is_completed(&q.name, &ws.workspace_id))
+        })
+        .count();
+    if skipped > 0 {
+        eprintln!(
+            "Skipping {} query/workspace pair{} completed in the previous run",
+            skipped,
+            if skipped == 1 { "" } else { "s" }
+        );
+    }
+
     eprintln!(
         "Executing {} quer{} across {} workspace{}...",
-        pack.get_queries().len(),
-        if pack.get_queries().len() == 1 {
-            "y"
-        } else {
-            "ies"
-        },
+        queries.len(),
+        if queries.len() == 1 { "y" } else { "ies" },
         selected_workspaces.len(),
         if selected_workspaces.len() == 1 {
             ""
@@ -66,44 +187,140 @@ pub async fn execute(
         }
     );
 
-    // Get base settings from pack or use defaults
-    let base_settings = pack.settings.clone().unwrap_or_default();
+    // Get base settings from pack or use defaults, falling back to the global row limit
+    let mut base_settings = pack.settings.clone().unwrap_or_else(|| QuerySettings {
+        output_folder: config
+            .output_folder
+            .clone()
+            .unwrap_or_else(|| QuerySettings::default().output_folder),
+        ..Default::default()
+    });
+    if base_settings.max_rows.is_none() {
+        base_settings.max_rows = config.max_rows;
+    }
+    base_settings.estimate_cost |= estimate_cost;
+    base_settings.csv_metadata |= csv_metadata;
+    base_settings.keep_partial_results |= keep_partial;
+    base_settings.skip_empty_output |= skip_empty;
+    base_settings.extract_iocs |= extract_iocs;
+    if sample.is_some() {
+        base_settings.sample_rows = sample;
+    }
+    // Paths on the command line are relative to the working directory, not the output folder
+    if let Some(path) = sqlite {
+        base_settings.sqlite_path = Some(std::env::current_dir()?.join(path));
+    }
+
+    // Output results based on format
+    let effective_format = if json_output {
+        OutputFormat::Stdout
+    } else {
+        format
+    };
+    // NDJSON rows go straight to stdout instead of to files
+    let streaming = matches!(effective_format, OutputFormat::Ndjson);
+    if streaming {
+        base_settings.export_csv = false;
+        base_settings.export_json = false;
+        base_settings.export_ndjson = false;
+        base_settings.export_parquet = false;
+    }
+    if let Some(rows) = base_settings.sample_rows {
+        eprintln!("Sampling {} row(s) per query and workspace", rows);
+    }
 
     // Execute all queries across all workspaces
     let mut all_results = Vec::new();
-
-    for pack_query in pack.get_queries() {
+    let mut summary = RunSummary::new(&pack.name);
+    let mut hits = Vec::new();
+    // (query, pairs without rows, pairs completed)
+    let mut no_hits: Vec<(String, usize, usize)> = Vec::new();
+    let concurrency = config
+        .max_concurrency
+        .unwrap_or(selected_workspaces.len())
+        .max(1);
+
+    for pack_query in &queries {
         eprintln!("\nExecuting: {}", pack_query.name);
 
         // Create settings for this query
         let mut settings = base_settings.clone();
-        settings.job_name = sanitize_name(&pack_query.name);
-
-        // Build and execute job
-        let results = QueryJobBuilder::new()
-            .workspaces(selected_workspaces.clone())
-            .queries(vec![pack_query.query.clone()])
-            .settings(settings)
-            .execute(&client)
-            .await?;
+        settings.job_name = paths::sanitize_file_name(&pack_query.name);
+
+        // Resolve per-workspace settings (query and workspace timeout/retry overrides)
+        // and execute each workspace concurrently (up to `max_concurrency` at once),
+        // skipping pairs completed in a resumed run
+        let pending: Vec<&Workspace> = selected_workspaces
+            .iter()
+            .filter(|ws| !manifest.is_completed(&pack_query.name, &ws.workspace_id))
+            .collect();
+        let sink = streaming.then(|| {
+            Arc::new(super::ndjson::NdjsonStdout::new(
+                Some(pack_query.name.clone()),
+                true,
+            ))
+        });
+        let mut executions = stream::iter(pending.into_iter().map(|workspace| {
+            let workspace_settings = pack.resolve_settings(&settings, pack_query, workspace);
+            let mut builder = QueryJobBuilder::new()
+                .workspaces(vec![workspace.clone()])
+                .queries(vec![pack_query.query.clone()])
+                .settings(workspace_settings);
+            if let Some(sink) = &sink {
+                builder = builder.row_sink(sink.clone());
+            }
+            builder.execute(&client)
+        }))
+        .buffer_unordered(concurrency);
+
+        // Record each pair as it finishes so an interrupted run can be resumed
+        let mut query_no_hits = (0, 0);
+        while let Some(results) = executions.next().await {
+            for result in results? {
+                if let Ok(success) = &result.result {
+                    query_no_hits.1 += 1;
+                    if success.row_count == 0 {
+                        query_no_hits.0 += 1;
+                    }
+                }
+                manifest.record(&pack_query.name, &result);
+                summary.add(&pack_query.name, &result);
+                if let (Some(severity), Ok(success)) = (pack_query.severity, &result.result) {
+                    if success.row_count > 0 {
+                        hits.push(SeverityHit {
+                            severity,
+                            query: pack_query.name.clone(),
+                            workspace: result.workspace_name.clone(),
+                            rows: success.row_count,
+                        });
+                    }
+                }
+                all_results.push(result);
+            }
+            manifest.save()?;
+        }
+        if query_no_hits.0 > 0 {
+            no_hits.push((pack_query.name.clone(), query_no_hits.0, query_no_hits.1));
+        }
+    }
 
-        all_results.extend(results);
+    if !manifest.is_complete() {
+        eprintln!(
+            "\n{}/{} pairs completed. Resume with: kql-panopticon run-pack {} --resume {}",
+            manifest.completed_count(),
+            manifest.entries.len(),
+            pack_path,
+            manifest.path().display()
+        );
     }
 
     // Create session name from pack
     let session_name = format!(
         "{}-{}",
-        sanitize_name(&pack.name),
-        chrono::Utc::now().format("%Y-%m-%d_%H%M%S")
+        paths::sanitize_file_name(&pack.name),
+        crate::time_zone::now().format("%Y-%m-%d_%H%M%S")
     );
 
-    // Output results based on format
-    let effective_format = if json_output {
-        OutputFormat::Stdout
-    } else {
-        format
-    };
-
     match effective_format {
         OutputFormat::Files => {
             output_to_files(&all_results, &pack)?;
@@ -113,28 +330,53 @@ pub async fn execute(
         OutputFormat::Stdout => {
             output_to_stdout(&all_results)?;
         }
+        OutputFormat::Ndjson => print_summary(&all_results),
+    }
+    print_hits(&mut hits);
+    if !no_hits.is_empty() {
+        eprintln!("\nNo hits:");
+        for (query, empty, completed) in &no_hits {
+            eprintln!("  - {}: {} of {} workspace(s)", query, empty, completed);
+        }
+    }
+
+    if base_settings.extract_iocs {
+        match crate::ioc::extract_run(&summary, &base_settings.output_folder)? {
+            Some((folder, count)) => eprintln!("{} IOC(s) written to {}", count, folder.display()),
+            None => eprintln!("No IOCs found in the results"),
+        }
+    }
+
+    // A failed notification shouldn't fail a run whose results are already written
+    if let Some(webhook) = WebhookConfig::resolve(&config.webhook, pack.webhook.as_ref()) {
+        if webhook.should_notify(&summary) {
+            match webhook.send(&summary).await {
+                Ok(()) => eprintln!("Notification sent"),
+                Err(e) => eprintln!("Warning: failed to send notification: {}", e),
+            }
+        }
     }
 
     Ok(())
 }
 
-fn load_pack(path_str: &str) -> Result<QueryPack> {
+/// Load a pack, returning it with the path it was loaded from
+fn load_pack(path_str: &str) -> Result<(QueryPack, PathBuf)> {
     let path = Path::new(path_str);
 
     // If absolute path, use directly
     if path.is_absolute() {
-        return QueryPack::load_from_file(path);
+        return Ok((QueryPack::load_from_file(path)?, path.to_path_buf()));
     }
 
     // Try as relative path first
     if path.exists() {
-        return QueryPack::load_from_file(path);
+        return Ok((QueryPack::load_from_file(path)?, path.to_path_buf()));
     }
 
-    // Try in library location
-    let library_path = QueryPack::get_library_path(path_str)?;
-    if library_path.exists() {
-        return QueryPack::load_from_file(&library_path);
+    // Try each pack library in order
+    if let Some(library_path) = QueryPack::find_in_libraries(path_str)? {
+        return Ok((QueryPack::load_from_file(&library_path)?, library_path));
     }
 
     Err(crate::error::KqlPanopticonError::QueryPackNotFound(
@@ -156,7 +398,7 @@ fn select_workspaces(
     if let Some(scope) = pack_scope {
         return match scope {
             WorkspaceScope::All => Ok(all_workspaces.to_vec()),
-            WorkspaceScope::Selected { ids } => Ok(all_workspaces
+            WorkspaceScope::Selected { ids, .. } => Ok(all_workspaces
                 .iter()
                 .filter(|ws| ids.contains(&ws.workspace_id) || ids.contains(&ws.resource_id))
                 .cloned()
@@ -171,7 +413,10 @@ fn select_workspaces(
     Ok(all_workspaces.to_vec())
 }
 
-fn parse_workspace_spec(spec: &str, all_workspaces: &[Workspace]) -> Result<Vec<Workspace>> {
+pub(crate) fn parse_workspace_spec(
+    spec: &str,
+    all_workspaces: &[Workspace],
+) -> Result<Vec<Workspace>> {
     if spec == "all" {
         return Ok(all_workspaces.to_vec());
     }
@@ -205,19 +450,6 @@ fn filter_workspaces_by_pattern(workspaces: &[Workspace], pattern: &str) -> Resu
         .collect())
 }
 
-fn sanitize_name(name: &str) -> String {
-    name.chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == '-' || c == '_' {
-                c
-            } else {
-                '-'
-            }
-        })
-        .collect::<String>()
-        .to_lowercase()
-}
-
 fn output_to_files(results: &[QueryJobResult], _pack: &QueryPack) -> Result<()> {
     // The QueryJobResult already handles file output via its internal logic
     // We just need to report the outcome
@@ -252,6 +484,37 @@ fn output_to_stdout(results: &[QueryJobResult]) -> Result<()> {
     Ok(())
 }
 
+/// Rows returned by a query with a severity
+struct SeverityHit {
+    severity: Severity,
+    query: String,
+    workspace: String,
+    rows: usize,
+}
+
+/// List hits from queries with a severity, highest severity first
+fn print_hits(hits: &mut [SeverityHit]) {
+    if hits.is_empty() {
+        return;
+    }
+    hits.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| b.rows.cmp(&a.rows))
+            .then_with(|| a.query.cmp(&b.query))
+    });
+    eprintln!("\nHits by severity:");
+    for hit in hits.iter() {
+        eprintln!(
+            "  [{}] {} on {}: {} rows",
+            hit.severity.as_str().to_uppercase(),
+            hit.query,
+            hit.workspace,
+            hit.rows
+        );
+    }
+}
+
 fn print_summary(results: &[QueryJobResult]) {
     let total = results.len();
     let success = results.iter().filter(|r| r.result.is_ok()).count();
@@ -262,6 +525,79 @@ fn print_summary(results: &[QueryJobResult]) {
     eprintln!("Succeeded: {}", success);
     eprintln!("Failed: {}", failed);
 
+    let truncated: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.result.as_ref().ok().map(|s| (r, s)))
+        .filter(|(_, success)| success.truncated)
+        .collect();
+    if !truncated.is_empty() {
+        eprintln!("\nTruncated by row limit:");
+        for (result, success) in truncated {
+            eprintln!(
+                "  - {}: stopped at {} rows",
+                result.workspace_name, success.row_count
+            );
+        }
+    }
+
+    let partial: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.result.as_ref().ok().map(|s| (r, s)))
+        .filter(|(_, success)| !success.warnings.is_empty())
+        .collect();
+    if !partial.is_empty() {
+        eprintln!("\nPartial results (service reported errors):");
+        for (result, success) in partial {
+            eprintln!(
+                "  - {}: {}",
+                result.workspace_name,
+                success.warnings.join("; ").replace('\n', " ")
+            );
+        }
+    }
+
+    let estimates: Vec<u64> = results
+        .iter()
+        .filter_map(|r| r.result.as_ref().ok().and_then(|s| s.billed_bytes))
+        .collect();
+    if !estimates.is_empty() {
+        eprintln!(
+            "Est. data scanned: {} ({} execution{})",
+            crate::units::format_bytes(estimates.iter().sum()),
+            estimates.len(),
+            if estimates.len() == 1 { "" } else { "s" }
+        );
+    }
+
+    // Heaviest executions first, to show which hunts need tuning
+    let mut cpu: Vec<(&QueryJobResult, f64)> = results
+        .iter()
+        .filter_map(|r| {
+            let stats = r.result.as_ref().ok()?.statistics.as_ref()?;
+            Some((r, stats.cpu_secs?))
+        })
+        .collect();
+    if !cpu.is_empty() {
+        cpu.sort_by(|a, b| b.1.total_cmp(&a.1));
+        eprintln!(
+            "Query CPU: {:.2}s ({} execution{})",
+            cpu.iter().map(|(_, secs)| secs).sum::<f64>(),
+            cpu.len(),
+            if cpu.len() == 1 { "" } else { "s" }
+        );
+        for (result, secs) in cpu.iter().take(3) {
+            let preview: String = result
+                .query
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .chars()
+                .take(50)
+                .collect();
+            eprintln!("  - {:.2}s  {}: {}", secs, result.workspace_name, preview);
+        }
+    }
+
     if failed > 0 {
         eprintln!("\nFailed executions:");
         for result in results {
@@ -0,0 +1,181 @@
+use crate::cli::args::QueryOutputFormat;
+use crate::{
+    cli::ndjson::NdjsonStdout,
+    client::Client,
+    config::Config,
+    error::{KqlPanopticonError, Result},
+    query_job::{QueryJobBuilder, QuerySettings},
+};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Options for the run-query command
+pub struct RunQueryOptions {
+    pub query: Option<String>,
+    pub query_file: Option<PathBuf>,
+    pub workspaces: String,
+    pub format: QueryOutputFormat,
+    pub max_rows: Option<usize>,
+    pub refresh_workspaces: bool,
+}
+
+pub async fn execute(options: RunQueryOptions) -> Result<()> {
+    let query = read_query(options.query, options.query_file)?;
+    if query.trim().is_empty() {
+        return Err(KqlPanopticonError::InvalidConfiguration(
+            "Query is empty".to_string(),
+        ));
+    }
+
+    // Initialize client
+    let config = Config::resolve();
+    let client = Client::from_config(&config)?;
+
+    eprintln!("Authenticating with Azure...");
+    client.force_validate_auth().await?;
+
+    eprintln!("Loading workspaces...");
+    let cache_ttl = config.workspace_cache_ttl();
+    let all_workspaces = client
+        .list_workspaces_cached(cache_ttl, options.refresh_workspaces)
+        .await?;
+    let workspaces = super::run_pack::parse_workspace_spec(&options.workspaces, &all_workspaces)?;
+    if workspaces.is_empty() {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "No workspaces match '{}'",
+            options.workspaces
+        )));
+    }
+
+    // Results are exported to a scratch folder, then streamed to stdout (NDJSON rows go
+    // straight to stdout as they arrive)
+    let scratch_dir = std::env::temp_dir().join(format!(
+        "kql-panopticon-query-{}-{}",
+        std::process::id(),
+        chrono::Local::now().format("%Y%m%d%H%M%S%3f")
+    ));
+    let mut settings = QuerySettings::with_formats(
+        &scratch_dir,
+        "query",
+        options.format == QueryOutputFormat::Csv,
+        options.format == QueryOutputFormat::Json,
+        true,
+    );
+    settings.max_rows = options.max_rows.or(config.max_rows);
+
+    eprintln!(
+        "Executing query across {} workspace(s)...",
+        workspaces.len()
+    );
+    let multiple = workspaces.len() > 1;
+    let mut builder = QueryJobBuilder::new()
+        .workspaces(workspaces)
+        .queries(vec![query])
+        .settings(settings);
+    if options.format == QueryOutputFormat::Ndjson {
+        builder = builder.row_sink(Arc::new(NdjsonStdout::new(None, multiple)));
+    }
+    let results = builder.execute(&client).await;
+
+    let output = results.and_then(|results| {
+        let mut failed = 0;
+        let mut outputs = Vec::new();
+        for result in &results {
+            match &result.result {
+                Ok(success) => {
+                    for warning in &success.warnings {
+                        eprintln!(
+                            "! {}: partial results: {}",
+                            result.workspace_name,
+                            warning.replace('\n', " ")
+                        );
+                    }
+                    outputs.push((result.workspace_name.as_str(), &success.output_path))
+                }
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("✗ {}: {}", result.workspace_name, e);
+                }
+            }
+        }
+
+        match options.format {
+            QueryOutputFormat::Json => write_json(&outputs)?,
+            QueryOutputFormat::Csv => write_csv(&outputs, multiple)?,
+            QueryOutputFormat::Ndjson => {}
+        }
+
+        eprintln!("✓ {} succeeded, {} failed", outputs.len(), failed);
+        if outputs.is_empty() {
+            return Err(KqlPanopticonError::QueryExecutionFailed(
+                "Query failed on every workspace".to_string(),
+            ));
+        }
+        Ok(())
+    });
+
+    if let Err(e) = std::fs::remove_dir_all(&scratch_dir) {
+        log::debug!("Failed to remove {}: {}", scratch_dir.display(), e);
+    }
+    output
+}
+
+/// Get the query text from the argument, a file, or stdin ('-')
+fn read_query(query: Option<String>, query_file: Option<PathBuf>) -> Result<String> {
+    match (query, query_file) {
+        (Some(query), _) if query == "-" => read_stdin(),
+        (Some(query), _) => Ok(query),
+        (None, Some(path)) if path == Path::new("-") => read_stdin(),
+        (None, Some(path)) => Ok(std::fs::read_to_string(&path).map_err(|e| {
+            KqlPanopticonError::IoError(format!("Failed to read {}: {}", path.display(), e))
+        })?),
+        (None, None) => Err(KqlPanopticonError::InvalidConfiguration(
+            "Provide a query, '-' for stdin, or --query-file".to_string(),
+        )),
+    }
+}
+
+fn read_stdin() -> Result<String> {
+    let mut query = String::new();
+    std::io::stdin().read_to_string(&mut query)?;
+    Ok(query)
+}
+
+/// Print each workspace's JSON result as one array
+fn write_json(outputs: &[(&str, &PathBuf)]) -> Result<()> {
+    let results = outputs
+        .iter()
+        .map(|(_, path)| Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+        .collect::<Result<Vec<serde_json::Value>>>()?;
+    println!("{}", serde_json::to_string_pretty(&results)?);
+    Ok(())
+}
+
+/// Print the CSV results with a single header, prefixing a Workspace column for several workspaces
+fn write_csv(outputs: &[(&str, &PathBuf)], with_workspace: bool) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for (idx, (workspace, path)) in outputs.iter().enumerate() {
+        let content = std::fs::read_to_string(path)?;
+        // Records keep their raw text, so quoted values spanning lines are copied unchanged
+        let records = crate::post_filter::split_csv_records(&content);
+        for (record, (raw, _)) in records.into_iter().enumerate() {
+            // Keep only the first file's header
+            if record == 0 && idx > 0 {
+                continue;
+            }
+            if with_workspace {
+                let prefix = if record == 0 {
+                    "Workspace".to_string()
+                } else {
+                    crate::query_job::format_csv_value(&serde_json::Value::from(*workspace))
+                };
+                writeln!(out, "{},{}", prefix, raw)?;
+            } else {
+                writeln!(out, "{}", raw)?;
+            }
+        }
+    }
+    Ok(())
+}
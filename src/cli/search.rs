@@ -0,0 +1,52 @@
+use crate::error::Result;
+use crate::result_search::{self, SearchTarget};
+use crate::session::Session;
+use std::path::Path;
+
+/// Print the result files containing `pattern`, with match counts per workspace
+pub fn execute(
+    pattern: &str,
+    regex: bool,
+    session: Option<&str>,
+    output_folder: &Path,
+    show_lines: bool,
+) -> Result<()> {
+    let matcher = result_search::matcher(pattern, regex)?;
+    let targets: Vec<SearchTarget> = match session {
+        Some(name) => result_search::session_files(&Session::load(name)?),
+        None => result_search::files_under(output_folder),
+    };
+
+    let found = result_search::search(&targets, &matcher);
+    if found.is_empty() {
+        eprintln!("No matches in {} result file(s)", targets.len());
+        return Ok(());
+    }
+
+    let width = found.iter().map(|m| m.workspace.len()).max().unwrap_or(0);
+    for matches in &found {
+        println!(
+            "{:<width$}  {:>6}  {}",
+            matches.workspace,
+            matches.lines,
+            matches.path.display(),
+            width = width
+        );
+        if show_lines {
+            for (line, text) in &matches.samples {
+                println!("    {}: {}", line, text);
+            }
+        }
+    }
+
+    let workspaces: std::collections::HashSet<&str> =
+        found.iter().map(|m| m.workspace.as_str()).collect();
+    eprintln!(
+        "{} matching line(s) in {} file(s) across {} workspace(s) ({} file(s) searched)",
+        found.iter().map(|m| m.lines).sum::<usize>(),
+        found.len(),
+        workspaces.len(),
+        targets.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::{error::Result, session::Session, session_archive, units::format_bytes};
+use std::path::{Path, PathBuf};
+
+/// Bundle a saved session and its output files into one archive
+pub fn export(session_name: &str, output: Option<PathBuf>) -> Result<()> {
+    eprintln!("Loading session '{}'...", session_name);
+    let session = Session::load(session_name)?;
+    let archive = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", session.name)));
+
+    eprintln!("Writing archive...");
+    let summary = session_archive::export(&session, &archive)?;
+    for path in &summary.missing {
+        eprintln!("  ⚠ Output no longer exists, skipped: {}", path.display());
+    }
+    eprintln!("✓ Exported session '{}'", session.name);
+    eprintln!("  Jobs: {}", session.jobs.len());
+    eprintln!(
+        "  Output files: {} ({})",
+        summary.files,
+        format_bytes(summary.bytes)
+    );
+    eprintln!("  Archive: {}", archive.display());
+    Ok(())
+}
+
+/// Restore a session archive written by `export`
+pub fn import(archive: &Path, output_folder: &Path, name: Option<&str>) -> Result<()> {
+    eprintln!("Importing {}...", archive.display());
+    let summary = session_archive::import(archive, output_folder, name)?;
+    eprintln!("✓ Imported session '{}'", summary.session.name);
+    eprintln!("  Jobs: {}", summary.session.jobs.len());
+    eprintln!(
+        "  Output files: {} (under {})",
+        summary.files,
+        summary.output_folder.display()
+    );
+    Ok(())
+}
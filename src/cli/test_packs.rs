@@ -0,0 +1,277 @@
+use crate::{
+    client::Client,
+    config::Config,
+    error::{KqlPanopticonError, Result},
+    pack_lint,
+    query_pack::QueryPack,
+    workspace::Workspace,
+};
+use std::path::{Path, PathBuf};
+
+/// Options for the test command
+pub struct TestPacksOptions {
+    pub path: Option<PathBuf>,
+    pub online: bool,
+    pub workspace: Option<String>,
+    pub refresh_workspaces: bool,
+}
+
+/// Outcome of one check on one pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Check {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+impl Check {
+    fn label(self) -> &'static str {
+        match self {
+            Check::Pass => "ok",
+            Check::Fail => "FAIL",
+            Check::Skipped => "-",
+        }
+    }
+}
+
+/// Test results for one pack file
+struct PackReport {
+    name: String,
+    queries: usize,
+    parse: Check,
+    validate: Check,
+    lint: Check,
+    params: Check,
+    online: Check,
+    /// Why each failed check failed
+    problems: Vec<String>,
+}
+
+impl PackReport {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            queries: 0,
+            parse: Check::Skipped,
+            validate: Check::Skipped,
+            lint: Check::Skipped,
+            params: Check::Skipped,
+            online: Check::Skipped,
+            problems: Vec::new(),
+        }
+    }
+
+    fn passed(&self) -> bool {
+        ![
+            self.parse,
+            self.validate,
+            self.lint,
+            self.params,
+            self.online,
+        ]
+        .contains(&Check::Fail)
+    }
+}
+
+pub async fn execute(options: TestPacksOptions) -> Result<()> {
+    let roots = match options.path {
+        Some(path) => vec![path],
+        None => QueryPack::libraries()?
+            .into_iter()
+            .map(|library| library.path)
+            .collect(),
+    };
+
+    // Online checks need Azure before anything is tested
+    let target = if options.online {
+        Some(connect(options.workspace, options.refresh_workspaces).await?)
+    } else {
+        None
+    };
+
+    let mut reports = Vec::new();
+    for root in &roots {
+        eprintln!("Checking packs in {}...", root.display());
+        let scan = QueryPack::scan_library(root)?;
+        for path in &scan.packs {
+            reports.push(test_pack(root, path, target.as_ref()).await);
+        }
+        // Files with broken syntax can't be recognized as packs, but are most likely meant to be
+        for (path, reason) in &scan.skipped {
+            if is_unparseable(path) {
+                let mut report = PackReport::new(display_name(root, path));
+                report.parse = Check::Fail;
+                report.problems.push(format!("parse: {}", reason));
+                reports.push(report);
+            }
+        }
+    }
+
+    if reports.is_empty() {
+        eprintln!("No query packs found");
+        return Ok(());
+    }
+    print_table(&reports);
+
+    let failed = reports.iter().filter(|r| !r.passed()).count();
+    if failed > 0 {
+        println!();
+        for report in reports.iter().filter(|r| !r.passed()) {
+            println!("{}:", report.name);
+            for problem in &report.problems {
+                println!("  - {}", problem);
+            }
+        }
+        return Err(KqlPanopticonError::QueryPackValidation(format!(
+            "{} of {} pack(s) failed",
+            failed,
+            reports.len()
+        )));
+    }
+
+    eprintln!("✓ All {} pack(s) passed", reports.len());
+    Ok(())
+}
+
+/// Authenticate and find the single workspace that online checks run against
+async fn connect(spec: Option<String>, refresh_workspaces: bool) -> Result<(Client, Workspace)> {
+    let config = Config::resolve();
+    let spec = spec.or(config.test_workspace.clone()).ok_or_else(|| {
+        KqlPanopticonError::InvalidConfiguration(
+            "--online needs --workspace or `test_workspace` in config.yaml".to_string(),
+        )
+    })?;
+    let client = Client::from_config(&config)?;
+
+    eprintln!("Authenticating with Azure...");
+    client.force_validate_auth().await?;
+
+    eprintln!("Loading workspaces...");
+    let cache_ttl = config.workspace_cache_ttl();
+    let all_workspaces = client
+        .list_workspaces_cached(cache_ttl, refresh_workspaces)
+        .await?;
+    let mut workspaces = super::run_pack::parse_workspace_spec(&spec, &all_workspaces)?;
+    if workspaces.len() != 1 {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "Test workspace '{}' matches {} workspaces (expected exactly one)",
+            spec,
+            workspaces.len()
+        )));
+    }
+    let workspace = workspaces.remove(0);
+    eprintln!("Online checks run against {}", workspace.name);
+    Ok((client, workspace))
+}
+
+/// Run every check on one pack file
+async fn test_pack(root: &Path, path: &Path, target: Option<&(Client, Workspace)>) -> PackReport {
+    let mut report = PackReport::new(display_name(root, path));
+
+    let pack = match QueryPack::load_from_file(path) {
+        Ok(pack) => pack,
+        Err(e) => {
+            report.parse = Check::Fail;
+            report.problems.push(format!("parse: {}", e));
+            return report;
+        }
+    };
+    report.parse = Check::Pass;
+    let queries = pack.get_queries();
+    report.queries = queries.len();
+
+    report.validate = match pack.validate() {
+        Ok(()) => Check::Pass,
+        Err(e) => {
+            report.problems.push(format!("validate: {}", e));
+            Check::Fail
+        }
+    };
+
+    report.lint = Check::Pass;
+    report.params = Check::Pass;
+    for query in &queries {
+        for issue in pack_lint::lint_query(&query.query) {
+            report.lint = Check::Fail;
+            report
+                .problems
+                .push(format!("lint: query '{}': {}", query.name, issue));
+        }
+        for placeholder in pack_lint::unresolved_placeholders(&query.query) {
+            report.params = Check::Fail;
+            report.problems.push(format!(
+                "params: query '{}': unresolved placeholder {}",
+                query.name, placeholder
+            ));
+        }
+    }
+
+    // Only well-formed packs are worth sending to Azure
+    if let Some((client, workspace)) = target {
+        if report.validate == Check::Pass && report.lint == Check::Pass {
+            report.online = Check::Pass;
+            let timespan = pack.timespan();
+            for query in &queries {
+                let probe = format!("{}\n| take 0", query.query.trim_end());
+                if let Err(e) = client
+                    .query_workspace(
+                        &workspace.workspace_id,
+                        &probe,
+                        timespan.as_deref(),
+                        client.query_timeout(),
+                    )
+                    .await
+                {
+                    report.online = Check::Fail;
+                    report
+                        .problems
+                        .push(format!("online: query '{}': {}", query.name, e));
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Check if a skipped file is a YAML/JSON file that doesn't parse at all
+fn is_unparseable(path: &Path) -> bool {
+    std::fs::read_to_string(path)
+        .map(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).is_err())
+        .unwrap_or(false)
+}
+
+/// Pack path relative to its library root
+fn display_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Print the pass/fail table to stdout
+fn print_table(reports: &[PackReport]) {
+    let width = reports
+        .iter()
+        .map(|r| r.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("PACK".len());
+    println!(
+        "{:<width$}  {:>7}  {:<5}  {:<8}  {:<4}  {:<6}  {:<6}  RESULT",
+        "PACK", "QUERIES", "PARSE", "VALIDATE", "LINT", "PARAMS", "ONLINE"
+    );
+    for report in reports {
+        println!(
+            "{:<width$}  {:>7}  {:<5}  {:<8}  {:<4}  {:<6}  {:<6}  {}",
+            report.name,
+            report.queries,
+            report.parse.label(),
+            report.validate.label(),
+            report.lint.label(),
+            report.params.label(),
+            report.online.label(),
+            if report.passed() { "pass" } else { "FAIL" }
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use crate::error::{KqlPanopticonError, Result};
+use crate::tui::model::workspaces::WorkspacesModel;
+use crate::workspace::Workspace;
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        terminal::{disable_raw_mode, enable_raw_mode},
+    },
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Terminal, TerminalOptions, Viewport,
+};
+use std::io::{self, IsTerminal};
+
+/// Maximum height of the inline picker (including borders and the filter line)
+const MAX_PICKER_HEIGHT: u16 = 20;
+
+/// Picker state: selection is tracked in a WorkspacesModel, as in the TUI Workspaces tab
+struct Picker {
+    model: WorkspacesModel,
+    filter: String,
+    /// Indices into model.workspaces matching the filter, best match first
+    visible: Vec<usize>,
+    list_state: ListState,
+}
+
+impl Picker {
+    fn new(workspaces: &[Workspace], preselected: &[Workspace]) -> Self {
+        let mut model = WorkspacesModel::new();
+        model.load_workspaces(workspaces.to_vec());
+        for ws in &mut model.workspaces {
+            ws.selected = preselected
+                .iter()
+                .any(|p| p.workspace_id == ws.workspace.workspace_id);
+        }
+
+        let mut picker = Self {
+            model,
+            filter: String::new(),
+            visible: Vec::new(),
+            list_state: ListState::default(),
+        };
+        picker.apply_filter();
+        picker
+    }
+
+    /// Recompute the visible entries for the current filter
+    fn apply_filter(&mut self) {
+        let mut matches: Vec<(u32, usize)> = self
+            .model
+            .workspaces
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, ws)| {
+                let haystack = format!(
+                    "{} {} {} {}",
+                    ws.workspace.name,
+                    ws.workspace.subscription_name,
+                    ws.workspace.location,
+                    ws.workspace.workspace_id
+                );
+                crate::fuzzy::score(&self.filter, &haystack).map(|score| (score, idx))
+            })
+            .collect();
+        // Stable sort keeps discovery order among equal scores
+        matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+
+        self.visible = matches.into_iter().map(|(_, idx)| idx).collect();
+        self.list_state.select(if self.visible.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    fn previous(&mut self) {
+        if let Some(selected) = self.list_state.selected() {
+            self.list_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    fn next(&mut self) {
+        if let Some(selected) = self.list_state.selected() {
+            if selected + 1 < self.visible.len() {
+                self.list_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    fn toggle_current(&mut self) {
+        if let Some(&idx) = self
+            .list_state
+            .selected()
+            .and_then(|selected| self.visible.get(selected))
+        {
+            self.model.toggle_selection(idx);
+        }
+    }
+
+    /// Select or deselect every entry matching the filter
+    fn set_visible(&mut self, selected: bool) {
+        if self.filter.is_empty() {
+            if selected {
+                self.model.select_all();
+            } else {
+                self.model.select_none();
+            }
+            return;
+        }
+        for &idx in &self.visible {
+            self.model.workspaces[idx].selected = selected;
+        }
+    }
+
+    fn render(&mut self, f: &mut ratatui::Frame) {
+        let [list_area, filter_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(f.area());
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&idx| {
+                let ws = &self.model.workspaces[idx];
+                let checkbox = if ws.selected { "[X]" } else { "[ ]" };
+                let mut spans = vec![
+                    Span::raw(format!("{} {} ", checkbox, ws.workspace.name)),
+                    Span::styled(
+                        format!(
+                            "({}, {})",
+                            ws.workspace.subscription_name, ws.workspace.location
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ];
+                if ws.workspace.delegated {
+                    spans.push(Span::styled(
+                        " [delegated]",
+                        Style::default().fg(Color::Magenta),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Select workspaces ({}/{} selected, {} shown)",
+                        self.model.selected_count(),
+                        self.model.workspaces.len(),
+                        self.visible.len()
+                    ))
+                    .title_bottom(
+                        "↑↓:Navigate Space:Toggle Ctrl+A:All Ctrl+N:None Enter:Run Esc:Cancel",
+                    ),
+            )
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let filter = Paragraph::new(Line::from(vec![
+            Span::styled("Filter: ", Style::default().fg(Color::Yellow)),
+            Span::raw(self.filter.as_str()),
+            Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ]));
+        f.render_widget(filter, filter_area);
+    }
+}
+
+/// Let the user pick workspaces interactively, starting from `preselected`
+/// Type to fuzzy-filter; the picker draws inline on stderr so stdout stays clean for results
+pub fn pick_workspaces(
+    workspaces: &[Workspace],
+    preselected: &[Workspace],
+) -> Result<Vec<Workspace>> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(KqlPanopticonError::InvalidConfiguration(
+            "--interactive requires a terminal".to_string(),
+        ));
+    }
+
+    let height = (workspaces.len() as u16 + 3).clamp(6, MAX_PICKER_HEIGHT);
+    let mut terminal = Terminal::with_options(
+        CrosstermBackend::new(io::stderr()),
+        TerminalOptions {
+            viewport: Viewport::Inline(height),
+        },
+    )?;
+
+    enable_raw_mode()?;
+    let outcome = run_picker(&mut terminal, Picker::new(workspaces, preselected));
+    disable_raw_mode()?;
+    terminal.clear()?;
+
+    outcome?.ok_or_else(|| {
+        KqlPanopticonError::InvalidConfiguration("Workspace selection cancelled".to_string())
+    })
+}
+
+/// Event loop; returns None if the user cancelled
+fn run_picker(
+    terminal: &mut Terminal<CrosstermBackend<io::Stderr>>,
+    mut picker: Picker,
+) -> Result<Option<Vec<Workspace>>> {
+    loop {
+        terminal.draw(|f| picker.render(f))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if ctrl => return Ok(None),
+            KeyCode::Char('a') if ctrl => picker.set_visible(true),
+            KeyCode::Char('n') if ctrl => picker.set_visible(false),
+            KeyCode::Enter => return Ok(Some(picker.model.get_selected_workspaces())),
+            KeyCode::Up => picker.previous(),
+            KeyCode::Down => picker.next(),
+            KeyCode::Char(' ') | KeyCode::Tab => picker.toggle_current(),
+            KeyCode::Backspace => {
+                picker.filter.pop();
+                picker.apply_filter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                picker.filter.push(c);
+                picker.apply_filter();
+            }
+            _ => {}
+        }
+    }
+}
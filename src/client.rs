@@ -1,29 +1,70 @@
+use crate::cloud::CloudEndpoints;
+use crate::config::{Config, NetworkConfig, SubscriptionFilter};
 use crate::error::{KqlPanopticonError, Result};
-use crate::workspace::{Workspace, WorkspaceListResponse};
-use azure_core::auth::TokenCredential;
+use crate::ingestion::IngestionTarget;
+use crate::recording::{RecordMode, RecordedResponse, Recorder};
+use crate::saved_search::{SavedSearch, SavedSearchListResponse};
+use crate::token::{TokenManager, EVENT_HUBS_SCOPE, STORAGE_SCOPE};
+use crate::workspace::{Workspace, WorkspaceCache, WorkspaceListResponse};
 use azure_identity::AzureCliCredential;
 use log::warn;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
-/// Cached token with expiry information
-#[derive(Clone)]
-struct CachedToken {
-    token: String,
-    expires_at: SystemTime,
-}
+/// Blob service REST API version used for uploads
+const BLOB_API_VERSION: &str = "2021-12-02";
+
+/// How long a result file upload may take (the client's query timeout is usually far shorter)
+const BLOB_UPLOAD_TIMEOUT: Duration = Duration::from_secs(600);
 
 /// Azure client for querying Log Analytics workspaces
 #[derive(Clone)]
 pub struct Client {
-    credential: Arc<AzureCliCredential>,
+    tokens: TokenManager,
     http_client: reqwest::Client,
     last_validated: Arc<std::sync::Mutex<Option<SystemTime>>>,
     validation_interval: Duration,
     query_timeout: Duration,
     retry_count: u32,
-    log_analytics_token: Arc<std::sync::Mutex<Option<CachedToken>>>,
+    subscription_filter: SubscriptionFilter,
+    /// Endpoints and token scopes of the configured Azure cloud
+    cloud: CloudEndpoints,
+    /// Records responses to, or replays them from, a fixture directory
+    recorder: Option<Recorder>,
+}
+
+/// An HTTP response read in full, so it can be recorded or come from a fixture
+struct ApiResponse {
+    status: reqwest::StatusCode,
+    /// Retry-After header, in seconds
+    retry_after: Option<u64>,
+    body: String,
+}
+
+impl ApiResponse {
+    fn status(&self) -> reqwest::StatusCode {
+        self.status
+    }
+
+    fn text(self) -> String {
+        self.body
+    }
+
+    fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
+}
+
+impl From<RecordedResponse> for ApiResponse {
+    fn from(recorded: RecordedResponse) -> Self {
+        Self {
+            status: reqwest::StatusCode::from_u16(recorded.status)
+                .unwrap_or(reqwest::StatusCode::INTERNAL_SERVER_ERROR),
+            retry_after: recorded.retry_after,
+            body: recorded.body,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -38,6 +79,136 @@ pub struct QueryResponse {
     pub tables: Vec<Table>,
     #[serde(rename = "nextLink")]
     pub next_link: Option<String>,
+    /// Error returned alongside the tables when the results are partial
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+    /// Query statistics (requested with `Prefer: include-statistics=true`)
+    #[serde(default)]
+    pub statistics: Option<serde_json::Value>,
+    /// Visualization hints (present when requested with `Prefer: include-render=true`)
+    #[serde(default)]
+    pub render: Option<serde_json::Value>,
+}
+
+impl QueryResponse {
+    /// Describe the error reported with a partial result, if any
+    pub fn warning(&self) -> Option<String> {
+        let error = self.error.as_ref()?;
+        Some(match AzureError::deserialize(error) {
+            Ok(error) => error.describe(),
+            Err(_) => error.to_string(),
+        })
+    }
+
+    /// Execution statistics for the query, if the response included them
+    pub fn query_statistics(&self) -> Option<QueryStatistics> {
+        self.statistics
+            .as_ref()
+            .and_then(QueryStatistics::from_value)
+    }
+}
+
+/// Execution statistics for a query (CPU, memory and data scanned)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryStatistics {
+    /// Server-side execution time in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_secs: Option<f64>,
+    /// Total CPU time in seconds, across all nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<f64>,
+    /// Peak memory used on a single node, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+    /// Rows scanned out of the rows in the tables the query reads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows_scanned: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows_total: Option<u64>,
+    /// Data extents (storage shards) scanned out of the extents in those tables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extents_scanned: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extents_total: Option<u64>,
+}
+
+impl QueryStatistics {
+    /// Parse the `statistics` block of a query response
+    fn from_value(value: &serde_json::Value) -> Option<Self> {
+        let query = value.get("query")?;
+        // Field names vary between API versions
+        let find = |pointers: &[&str]| pointers.iter().find_map(|p| query.pointer(p));
+        let count = |pointer: &str| query.pointer(pointer).and_then(|v| v.as_u64());
+
+        Some(Self {
+            execution_secs: query.get("executionTime").and_then(|v| v.as_f64()),
+            cpu_secs: find(&[
+                "/resourceUsage/cpu/total cpu",
+                "/resourceUsage/cpu/totalCpu",
+            ])
+            .and_then(|v| v.as_str())
+            .and_then(parse_timespan),
+            peak_memory_bytes: find(&[
+                "/resourceUsage/memory/peak_per_node",
+                "/resourceUsage/memory/peakPerNode",
+            ])
+            .and_then(|v| v.as_u64()),
+            rows_scanned: count("/inputDatasetStatistics/rows/scanned"),
+            rows_total: count("/inputDatasetStatistics/rows/total"),
+            extents_scanned: count("/inputDatasetStatistics/extents/scanned"),
+            extents_total: count("/inputDatasetStatistics/extents/total"),
+        })
+    }
+}
+
+/// Apply proxy and custom CA settings to an HTTP client
+pub(crate) fn configure_network(
+    mut builder: reqwest::ClientBuilder,
+    network: &NetworkConfig,
+) -> Result<reqwest::ClientBuilder> {
+    if let Some(url) = &network.proxy {
+        let proxy = reqwest::Proxy::all(url).map_err(|e| {
+            KqlPanopticonError::InvalidConfiguration(format!("Invalid proxy URL: {}", e))
+        })?;
+        let no_proxy = network
+            .no_proxy
+            .as_deref()
+            .and_then(reqwest::NoProxy::from_string);
+        builder = builder.proxy(proxy.no_proxy(no_proxy));
+    }
+
+    if let Some(path) = &network.ca_bundle {
+        let path = crate::paths::expand_home(path);
+        let invalid = |reason: String| {
+            KqlPanopticonError::InvalidConfiguration(format!(
+                "CA bundle {}: {}",
+                path.display(),
+                reason
+            ))
+        };
+        let pem = std::fs::read(&path).map_err(|e| invalid(e.to_string()))?;
+        let certificates =
+            reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| invalid(e.to_string()))?;
+        if certificates.is_empty() {
+            return Err(invalid("no PEM certificates found".to_string()));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Parse a .NET timespan (`[d.]hh:mm:ss[.fffffff]`) into seconds
+fn parse_timespan(value: &str) -> Option<f64> {
+    let mut parts = value.splitn(3, ':');
+    let (hours, minutes, seconds) = (parts.next()?, parts.next()?, parts.next()?);
+    let hours = match hours.split_once('.') {
+        Some((days, hours)) => days.parse::<f64>().ok()? * 24.0 + hours.parse::<f64>().ok()?,
+        None => hours.parse::<f64>().ok()?,
+    };
+    Some(hours * 3600.0 + minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()?)
 }
 
 #[derive(Deserialize, Debug)]
@@ -52,11 +223,10 @@ pub struct Table {
 pub struct Column {
     pub name: String,
     #[serde(rename = "type")]
-    #[allow(dead_code)]
     pub column_type: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Subscription {
     #[serde(rename = "subscriptionId")]
     pub subscription_id: String,
@@ -64,8 +234,26 @@ pub struct Subscription {
     pub display_name: String,
     #[allow(dead_code)]
     pub state: String,
+    /// Tenant the subscription is accessed through (the signed-in tenant for delegations)
     #[serde(rename = "tenantId")]
     pub tenant_id: String,
+    /// Tenant that owns the subscription (differs from tenant_id for Lighthouse delegations)
+    #[serde(rename = "homeTenantId", default)]
+    pub home_tenant_id: Option<String>,
+}
+
+impl Subscription {
+    /// Whether access to this subscription comes from an Azure Lighthouse delegation
+    pub fn is_delegated(&self) -> bool {
+        self.home_tenant_id
+            .as_deref()
+            .is_some_and(|home| !home.eq_ignore_ascii_case(&self.tenant_id))
+    }
+
+    /// Tenant that owns the subscription's workspaces
+    pub fn workspace_tenant_id(&self) -> &str {
+        self.home_tenant_id.as_deref().unwrap_or(&self.tenant_id)
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -73,6 +261,31 @@ struct SubscriptionListResponse {
     value: Vec<Subscription>,
 }
 
+/// Resource Graph query listing every subscription visible to the caller,
+/// including subscriptions delegated through Azure Lighthouse
+const DELEGATED_SUBSCRIPTIONS_QUERY: &str = "resourcecontainers \
+| where type =~ 'microsoft.resources/subscriptions' \
+| project subscriptionId, name, tenantId, state = tostring(properties.state)";
+
+#[derive(Deserialize, Debug)]
+struct ResourceGraphResponse {
+    data: Vec<ResourceGraphSubscription>,
+    /// Set when more results are available; sent back in the next request's options
+    #[serde(rename = "$skipToken", default)]
+    skip_token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ResourceGraphSubscription {
+    #[serde(rename = "subscriptionId")]
+    subscription_id: String,
+    name: String,
+    #[serde(rename = "tenantId")]
+    tenant_id: String,
+    #[serde(default)]
+    state: String,
+}
+
 /// Azure API error response structure
 #[derive(Deserialize, Debug)]
 struct AzureErrorResponse {
@@ -90,16 +303,91 @@ struct AzureError {
 
 #[derive(Deserialize, Debug)]
 struct AzureErrorDetail {
-    #[allow(dead_code)]
     code: Option<String>,
+    #[serde(default)]
     message: String,
+    innererror: Option<AzureInnerError>,
 }
 
 #[derive(Deserialize, Debug)]
 struct AzureInnerError {
-    #[allow(dead_code)]
     code: Option<String>,
     message: Option<String>,
+    innererror: Option<Box<AzureInnerError>>,
+}
+
+impl AzureError {
+    /// Format the error with its code, inner error and details
+    fn describe(&self) -> String {
+        let mut message = self.message.clone();
+
+        // Add error code if available
+        if let Some(code) = &self.code {
+            message = format!("{}: {}", code, message);
+        }
+
+        // Add inner error details if available
+        if let Some(inner_msg) = self
+            .innererror
+            .as_ref()
+            .and_then(AzureInnerError::innermost)
+        {
+            message.push_str(&format!("\n  Details: {}", inner_msg));
+        }
+
+        // Add additional error details
+        for detail in &self.details {
+            match detail
+                .innererror
+                .as_ref()
+                .and_then(AzureInnerError::innermost)
+            {
+                Some(inner_msg) => {
+                    message.push_str(&format!("\n  - {} ({})", detail.message, inner_msg))
+                }
+                None => message.push_str(&format!("\n  - {}", detail.message)),
+            }
+        }
+
+        message
+    }
+
+    /// The most specific named error code, looking through wrappers such as `BadArgumentError`
+    /// or `PartialError` into inner errors and details
+    /// Numeric and engine codes (e.g. `SEM0100`) are skipped; the top-level code is the fallback.
+    fn specific_code(&self) -> Option<String> {
+        let mut codes = vec![self.code.as_deref()];
+        codes.extend(self.innererror.iter().flat_map(AzureInnerError::codes));
+        for detail in &self.details {
+            codes.push(detail.code.as_deref());
+            codes.extend(detail.innererror.iter().flat_map(AzureInnerError::codes));
+        }
+        codes
+            .into_iter()
+            .flatten()
+            .rfind(|code| code.ends_with("Error"))
+            .or(self.code.as_deref())
+            .map(str::to_string)
+    }
+}
+
+impl AzureInnerError {
+    /// Codes in a chain of inner errors, outermost first
+    fn codes(&self) -> Vec<Option<&str>> {
+        let mut codes = vec![self.code.as_deref()];
+        if let Some(inner) = &self.innererror {
+            codes.extend(inner.codes());
+        }
+        codes
+    }
+
+    /// The most specific message in a chain of inner errors
+    fn innermost(&self) -> Option<&str> {
+        self.innererror
+            .as_deref()
+            .and_then(AzureInnerError::innermost)
+            .or(self.message.as_deref())
+    }
 }
 
 impl Client {
@@ -112,6 +400,17 @@ impl Client {
         )
     }
 
+    /// Create a client from the timeout, retry and subscription settings in `config`
+    /// Unset values fall back to the same defaults as [`Client::new`].
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::with_config(
+            Duration::from_secs(config.validation_interval_secs.unwrap_or(300)),
+            Duration::from_secs(config.query_timeout_secs.unwrap_or(30)),
+            config.retry_count.unwrap_or(0),
+        )?
+        .with_subscription_filter(config.subscriptions.clone()))
+    }
+
     /// Create a new client with a custom validation interval (deprecated, use with_config)
     #[allow(dead_code)]
     pub fn with_validation_interval(validation_interval: Duration) -> Result<Self> {
@@ -119,28 +418,68 @@ impl Client {
     }
 
     /// Create a new client with full configuration
+    /// Proxy and CA settings come from `network` in config.yaml and the environment, and the
+    /// Azure endpoints from `cloud`.
     pub fn with_config(
         validation_interval: Duration,
         query_timeout: Duration,
         retry_count: u32,
     ) -> Result<Self> {
+        let cloud = Config::resolve().cloud.endpoints()?;
+        if cloud.environment != crate::cloud::CloudEnvironment::Public {
+            log::info!(
+                "Using the {} cloud ({})",
+                cloud.environment.as_str(),
+                cloud.management
+            );
+        }
         let credential = AzureCliCredential::new();
-        let http_client = reqwest::Client::builder()
-            .timeout(query_timeout)
+        let builder = reqwest::Client::builder().timeout(query_timeout);
+        let http_client = configure_network(builder, &NetworkConfig::resolve())?
             .build()
-            .map_err(|e| KqlPanopticonError::HttpRequestFailed(e.to_string()))?;
+            .map_err(KqlPanopticonError::from)?;
 
         Ok(Self {
-            credential: Arc::new(credential),
+            tokens: TokenManager::new(Arc::new(credential)),
             http_client,
             last_validated: Arc::new(std::sync::Mutex::new(None)),
             validation_interval,
             query_timeout,
             retry_count,
-            log_analytics_token: Arc::new(std::sync::Mutex::new(None)),
+            subscription_filter: SubscriptionFilter::default(),
+            cloud,
+            recorder: crate::recording::default_mode().map(Recorder::new),
         })
     }
 
+    /// Record every Azure API response to a fixture directory, or serve them from one
+    /// instead of calling Azure (for deterministic tests and demos)
+    pub fn with_record_mode(mut self, mode: RecordMode) -> Self {
+        self.recorder = Some(Recorder::new(mode));
+        self
+    }
+
+    /// Check if responses are served from fixtures instead of Azure
+    pub fn is_replaying(&self) -> bool {
+        self.recorder.as_ref().is_some_and(Recorder::is_replay)
+    }
+
+    /// Set the subscription filter applied during workspace discovery
+    pub fn with_subscription_filter(mut self, filter: SubscriptionFilter) -> Self {
+        self.subscription_filter = filter;
+        self
+    }
+
+    /// Get the subscription filter applied during workspace discovery
+    pub fn subscription_filter(&self) -> &SubscriptionFilter {
+        &self.subscription_filter
+    }
+
+    /// Get the endpoints of the Azure cloud the client calls
+    pub fn cloud(&self) -> &CloudEndpoints {
+        &self.cloud
+    }
+
     /// Get the configured query timeout
     pub fn query_timeout(&self) -> Duration {
         self.query_timeout
@@ -151,9 +490,18 @@ impl Client {
         self.retry_count
     }
 
+    /// Get when authentication was last successfully validated
+    pub fn last_validated(&self) -> Option<SystemTime> {
+        self.last_validated.lock().ok().and_then(|last| *last)
+    }
+
     /// Validate that the Azure CLI authentication is still valid
     /// This will check if the token can be acquired and if the validation interval has passed
     pub async fn validate_auth(&self) -> Result<()> {
+        if self.is_replaying() {
+            return Ok(());
+        }
+
         // Check if we need to revalidate based on the interval
         let should_validate = {
             let last_validated = self.last_validated.lock().map_err(|e| {
@@ -193,6 +541,10 @@ impl Client {
 
     /// Force validation of authentication regardless of interval
     pub async fn force_validate_auth(&self) -> Result<()> {
+        if self.is_replaying() {
+            return Ok(());
+        }
+
         match self.get_token_for_management().await {
             Ok(_) => {
                 let mut last_validated = self.last_validated.lock().map_err(|e| {
@@ -208,184 +560,266 @@ impl Client {
         }
     }
 
-    /// Get a token for Azure Management API
-    async fn get_token_for_management(&self) -> Result<String> {
-        let token = self
-            .credential
-            .get_token(&["https://management.azure.com/.default"])
-            .await
-            .map_err(|e| {
-                KqlPanopticonError::TokenAcquisitionFailed(format!(
-                    "Failed to get management token: {}",
-                    e
-                ))
-            })?;
-
-        Ok(token.token.secret().to_string())
+    /// Discard cached tokens and validate authentication from scratch
+    /// Clones of this client share the token cache, so running jobs pick up the new tokens.
+    pub async fn reauthenticate(&self) -> Result<()> {
+        self.clear_tokens().await;
+        self.force_validate_auth().await
     }
 
-    /// Get a token for Log Analytics API with caching and expiry tracking
-    async fn get_token_for_log_analytics(&self) -> Result<String> {
-        // Check if we have a cached token that's still valid
-        const TOKEN_REFRESH_BUFFER: Duration = Duration::from_secs(300); // 5 minutes before expiry
-
-        {
-            let cached = self.log_analytics_token.lock().map_err(|e| {
-                KqlPanopticonError::Other(format!("Token cache lock poisoned: {}", e))
-            })?;
-
-            if let Some(cached_token) = cached.as_ref() {
-                // Check if token is still valid (with buffer for refresh)
-                if let Ok(time_until_expiry) =
-                    cached_token.expires_at.duration_since(SystemTime::now())
-                {
-                    if time_until_expiry > TOKEN_REFRESH_BUFFER {
-                        log::debug!(
-                            "Using cached Log Analytics token (expires in {:?})",
-                            time_until_expiry
-                        );
-                        return Ok(cached_token.token.clone());
-                    } else {
-                        log::debug!(
-                            "Cached token expiring soon (in {:?}), refreshing",
-                            time_until_expiry
-                        );
-                    }
-                }
-            }
+    /// Discard cached tokens and mark authentication as unvalidated
+    pub async fn clear_tokens(&self) {
+        self.tokens.clear().await;
+        if let Ok(mut last_validated) = self.last_validated.lock() {
+            *last_validated = None;
         }
+    }
 
-        // No valid cached token, fetch a new one
-        log::debug!("Fetching new Log Analytics token");
-        let token = self
-            .credential
-            .get_token(&["https://api.loganalytics.io/.default"])
-            .await
-            .map_err(|e| {
-                KqlPanopticonError::TokenAcquisitionFailed(format!(
-                    "Failed to get Log Analytics token: {}",
-                    e
-                ))
-            })?;
+    /// Get a token for Azure Management API
+    async fn get_token_for_management(&self) -> Result<String> {
+        self.tokens.get_token(&self.cloud.management_scope()).await
+    }
 
-        let token_string = token.token.secret().to_string();
-        // Convert OffsetDateTime to SystemTime
-        let expires_at =
-            SystemTime::UNIX_EPOCH + Duration::from_secs(token.expires_on.unix_timestamp() as u64);
+    /// Send a request with a bearer token for `scope` and read the response
+    /// On HTTP 401 the cached token is invalidated and the request is retried once with a new one.
+    /// When replaying, the recorded response is returned without a token or a request.
+    async fn send_authorized(
+        &self,
+        scope: &str,
+        request: impl Fn(&str) -> reqwest::RequestBuilder,
+    ) -> Result<ApiResponse> {
+        if let Some(recorder) = self.recorder.as_ref().filter(|r| r.is_replay()) {
+            let request = request("").build()?;
+            let (method, url, body) = request_key(&request);
+            return Ok(recorder.replay(&method, &url, &body)?.into());
+        }
 
-        // Cache the new token
-        {
-            let mut cached = self.log_analytics_token.lock().map_err(|e| {
-                KqlPanopticonError::Other(format!("Token cache lock poisoned: {}", e))
-            })?;
-            *cached = Some(CachedToken {
-                token: token_string.clone(),
-                expires_at,
-            });
+        let send = |token: &str| {
+            let request = request(token).build()?;
+            let key = self.recorder.as_ref().map(|_| request_key(&request));
+            Ok::<_, KqlPanopticonError>((key, self.http_client.execute(request)))
+        };
+        let token = self.tokens.get_token(scope).await?;
+        let (mut key, pending) = send(&token)?;
+        let mut response = pending.await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            warn!("Token rejected (HTTP 401); acquiring a new token and retrying");
+            self.tokens.invalidate(scope).await;
+            let token = self.tokens.get_token(scope).await?;
+            let (retry_key, pending) = send(&token)?;
+            key = retry_key;
+            response = pending.await?;
+        }
 
-            if let Ok(duration) = expires_at.duration_since(SystemTime::now()) {
-                log::debug!("Cached new token (expires in {:?})", duration);
+        let response = ApiResponse {
+            status: response.status(),
+            retry_after: response
+                .headers()
+                .get("Retry-After")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok()),
+            body: response.text().await?,
+        };
+        if let (Some(recorder), Some((method, url, body))) = (&self.recorder, key) {
+            let recorded = RecordedResponse {
+                status: response.status.as_u16(),
+                retry_after: response.retry_after,
+                body: response.body.clone(),
+            };
+            if let Err(e) = recorder.record(&method, &url, &body, &recorded) {
+                warn!("Failed to record response for {} {}: {}", method, url, e);
             }
         }
-
-        Ok(token_string)
+        Ok(response)
     }
 
     /// Parse Azure error response and create a detailed error message
     fn parse_azure_error(status: u16, error_text: &str, context: &str) -> KqlPanopticonError {
         // Try to parse as structured Azure error response
         if let Ok(azure_error) = serde_json::from_str::<AzureErrorResponse>(error_text) {
-            let mut message = azure_error.error.message.clone();
-
-            // Add error code if available
-            if let Some(code) = &azure_error.error.code {
-                message = format!("{}: {}", code, message);
-            }
-
-            // Add inner error details if available
-            if let Some(inner) = &azure_error.error.innererror {
-                if let Some(inner_msg) = &inner.message {
-                    message.push_str(&format!("\n  Details: {}", inner_msg));
-                }
-            }
-
-            // Add additional error details
-            for detail in &azure_error.error.details {
-                message.push_str(&format!("\n  - {}", detail.message));
-            }
-
             KqlPanopticonError::AzureApiError {
                 status,
-                message: format!("{}: {}", context, message),
+                message: format!("{}: {}", context, azure_error.error.describe()),
+                code: azure_error.error.specific_code(),
             }
         } else {
             // Fallback to raw error text if not structured JSON
             KqlPanopticonError::AzureApiError {
                 status,
                 message: format!("{}: {}", context, error_text),
+                code: None,
             }
         }
     }
 
     /// Parse Retry-After header from HTTP response
     /// Returns the number of seconds to wait, defaulting to 60 if header is missing or invalid
-    fn parse_retry_after(response: &reqwest::Response) -> u64 {
-        response
-            .headers()
-            .get("Retry-After")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(60) // Default to 60 seconds if header is missing or invalid
+    fn parse_retry_after(response: &ApiResponse) -> u64 {
+        response.retry_after.unwrap_or(60) // Default to 60 seconds if header is missing or invalid
     }
 
-    /// List all subscriptions the user has access to
+    /// List all subscriptions the user has access to, including Lighthouse delegations
     pub async fn list_subscriptions(&self) -> Result<Vec<Subscription>> {
+        let mut subscriptions = self.list_home_subscriptions().await?;
+
+        // Some credentials only see home-tenant subscriptions through the subscriptions API,
+        // so also ask Resource Graph, which spans Lighthouse delegations
+        match self.list_delegated_subscriptions().await {
+            Ok(delegated) => {
+                for subscription in delegated {
+                    if !subscriptions
+                        .iter()
+                        .any(|s| s.subscription_id == subscription.subscription_id)
+                    {
+                        subscriptions.push(subscription);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to enumerate delegated subscriptions: {}", e),
+        }
+
+        let delegated_count = subscriptions.iter().filter(|s| s.is_delegated()).count();
+        if delegated_count > 0 {
+            log::info!(
+                "Found {} delegated subscription(s) via Azure Lighthouse",
+                delegated_count
+            );
+        }
+
+        if subscriptions.is_empty() {
+            return Err(KqlPanopticonError::NoSubscriptionsFound);
+        }
+
+        Ok(subscriptions)
+    }
+
+    /// List subscriptions returned by the subscriptions API for the signed-in tenant
+    async fn list_home_subscriptions(&self) -> Result<Vec<Subscription>> {
         self.validate_auth().await?;
 
-        let token = self.get_token_for_management().await?;
-        let url = "https://management.azure.com/subscriptions?api-version=2020-01-01";
+        let url = format!(
+            "{}/subscriptions?api-version=2020-01-01",
+            self.cloud.management
+        );
 
         let response = self
-            .http_client
-            .get(url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .send_authorized(&self.cloud.management_scope(), |token| {
+                self.http_client.get(&url).bearer_auth(token)
+            })
             .await?;
 
         if !response.status().is_success() {
             let status = response.status().as_u16();
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = response.text();
             return Err(KqlPanopticonError::AzureApiError {
                 status,
                 message: error_text,
+                code: None,
             });
         }
 
-        let subscription_response: SubscriptionListResponse = response
-            .json()
-            .await
-            .map_err(|e| KqlPanopticonError::ParseFailed(format!("JSON: {}", e)))?;
+        let subscription_response: SubscriptionListResponse = response.json()?;
 
-        if subscription_response.value.is_empty() {
-            return Err(KqlPanopticonError::NoSubscriptionsFound);
+        Ok(subscription_response.value)
+    }
+
+    /// Tenant the Azure CLI is signed in to (recorded like an API response)
+    async fn signed_in_tenant(&self) -> Result<String> {
+        const METHOD: &str = "AZ";
+        const KEY: &str = "account show --query tenantId";
+        if let Some(recorder) = self.recorder.as_ref().filter(|r| r.is_replay()) {
+            return Ok(recorder.replay(METHOD, KEY, &[])?.body);
         }
 
-        Ok(subscription_response.value)
+        let tenant = AzureCliCredential::get_tenant().await.map_err(|e| {
+            KqlPanopticonError::TokenAcquisitionFailed(format!(
+                "Failed to get signed-in tenant: {}",
+                e
+            ))
+        })?;
+        if let Some(recorder) = &self.recorder {
+            let recorded = RecordedResponse {
+                status: 200,
+                retry_after: None,
+                body: tenant.clone(),
+            };
+            if let Err(e) = recorder.record(METHOD, KEY, &[], &recorded) {
+                warn!("Failed to record signed-in tenant: {}", e);
+            }
+        }
+        Ok(tenant)
+    }
+
+    /// List subscriptions visible through Azure Resource Graph (covers Lighthouse delegations)
+    async fn list_delegated_subscriptions(&self) -> Result<Vec<Subscription>> {
+        let signed_in_tenant = self.signed_in_tenant().await?;
+
+        let url = format!(
+            "{}/providers/Microsoft.ResourceGraph/resources?api-version=2021-03-01",
+            self.cloud.management
+        );
+
+        let mut subscriptions = Vec::new();
+        let mut skip_token: Option<String> = None;
+        loop {
+            let mut options = serde_json::json!({ "resultFormat": "objectArray" });
+            if let Some(skip_token) = &skip_token {
+                options["$skipToken"] = serde_json::json!(skip_token);
+            }
+            let body = serde_json::json!({
+                "query": DELEGATED_SUBSCRIPTIONS_QUERY,
+                "options": options,
+            });
+
+            let response = self
+                .send_authorized(&self.cloud.management_scope(), |token| {
+                    self.http_client.post(&url).bearer_auth(token).json(&body)
+                })
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status().as_u16();
+                let error_text = response.text();
+                return Err(Self::parse_azure_error(
+                    status,
+                    &error_text,
+                    "Resource Graph subscription query failed",
+                ));
+            }
+
+            let graph_response: ResourceGraphResponse = response.json()?;
+            subscriptions.extend(graph_response.data.into_iter().map(|sub| Subscription {
+                subscription_id: sub.subscription_id,
+                display_name: sub.name,
+                state: sub.state,
+                tenant_id: signed_in_tenant.clone(),
+                home_tenant_id: Some(sub.tenant_id),
+            }));
+
+            // Resource Graph returns at most 1000 rows per request
+            match graph_response.skip_token {
+                Some(token) if !token.is_empty() => skip_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(subscriptions)
     }
 
     /// Query a single Log Analytics workspace
+    /// The timeout overrides the client default for this request only
     pub async fn query_workspace(
         &self,
         workspace_id: &str,
         query: &str,
         timespan: Option<&str>,
+        timeout: Duration,
     ) -> Result<QueryResponse> {
         self.validate_auth().await?;
 
-        let token = self.get_token_for_log_analytics().await?;
         let url = format!(
-            "https://api.loganalytics.io/v1/workspaces/{}/query",
-            workspace_id
+            "{}/v1/workspaces/{}/query",
+            self.cloud.log_analytics, workspace_id
         );
 
         let body = QueryRequest {
@@ -394,12 +828,15 @@ impl Client {
         };
 
         let response = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+            .send_authorized(&self.cloud.log_analytics_scope(), |token| {
+                self.http_client
+                    .post(&url)
+                    .bearer_auth(token)
+                    .header("Content-Type", "application/json")
+                    .header("Prefer", "include-statistics=true")
+                    .timeout(timeout)
+                    .json(&body)
+            })
             .await?;
 
         if !response.status().is_success() {
@@ -408,7 +845,7 @@ impl Client {
             // Check for rate limiting (429)
             if status == 429 {
                 let retry_after = Self::parse_retry_after(&response);
-                let error_text = response.text().await.unwrap_or_default();
+                let error_text = response.text();
                 warn!(
                     "Rate limited on workspace {}. Retry after {} seconds. Details: {}",
                     workspace_id, retry_after, error_text
@@ -416,7 +853,7 @@ impl Client {
                 return Err(KqlPanopticonError::RateLimitExceeded { retry_after });
             }
 
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = response.text();
             return Err(Self::parse_azure_error(
                 status,
                 &error_text,
@@ -424,25 +861,26 @@ impl Client {
             ));
         }
 
-        let result: QueryResponse = response
-            .json()
-            .await
-            .map_err(|e| KqlPanopticonError::ParseFailed(format!("JSON: {}", e)))?;
+        let result: QueryResponse = response.json()?;
 
         Ok(result)
     }
 
     /// Query the next page using a nextLink URL from a previous QueryResponse
-    pub async fn query_next_page(&self, next_link: &str) -> Result<QueryResponse> {
+    pub async fn query_next_page(
+        &self,
+        next_link: &str,
+        timeout: Duration,
+    ) -> Result<QueryResponse> {
         self.validate_auth().await?;
 
-        let token = self.get_token_for_log_analytics().await?;
-
         let response = self
-            .http_client
-            .get(next_link)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .send_authorized(&self.cloud.log_analytics_scope(), |token| {
+                self.http_client
+                    .get(next_link)
+                    .bearer_auth(token)
+                    .timeout(timeout)
+            })
             .await?;
 
         if !response.status().is_success() {
@@ -451,7 +889,7 @@ impl Client {
             // Check for rate limiting (429)
             if status == 429 {
                 let retry_after = Self::parse_retry_after(&response);
-                let error_text = response.text().await.unwrap_or_default();
+                let error_text = response.text();
                 warn!(
                     "Rate limited during pagination. Retry after {} seconds. Details: {}",
                     retry_after, error_text
@@ -459,7 +897,7 @@ impl Client {
                 return Err(KqlPanopticonError::RateLimitExceeded { retry_after });
             }
 
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = response.text();
             return Err(Self::parse_azure_error(
                 status,
                 &error_text,
@@ -467,36 +905,236 @@ impl Client {
             ));
         }
 
-        let result: QueryResponse = response
-            .json()
-            .await
-            .map_err(|e| KqlPanopticonError::ParseFailed(format!("JSON: {}", e)))?;
+        let result: QueryResponse = response.json()?;
 
         Ok(result)
     }
 
+    /// List workspaces, using the on-disk cache if it is younger than `ttl`
+    /// The cache is only used for the subscription filter, signed-in tenant and cloud it was
+    /// written for. Set `force_refresh` to bypass it and re-enumerate subscriptions.
+    pub async fn list_workspaces_cached(
+        &self,
+        ttl: Duration,
+        force_refresh: bool,
+    ) -> Result<Vec<Workspace>> {
+        let tenant = self.signed_in_tenant().await?;
+        if !force_refresh {
+            if let Some(cache) = WorkspaceCache::load() {
+                if cache.is_fresh(ttl)
+                    && cache.matches(&self.subscription_filter, &tenant, &self.cloud.management)
+                {
+                    log::info!(
+                        "Using {} cached workspaces (fetched at {})",
+                        cache.workspaces.len(),
+                        cache.fetched_at
+                    );
+                    return Ok(cache.workspaces);
+                }
+            }
+        }
+
+        let workspaces = self.list_workspaces().await?;
+
+        let cache = WorkspaceCache::new(
+            workspaces.clone(),
+            self.subscription_filter.clone(),
+            tenant,
+            self.cloud.management.clone(),
+        );
+        if let Err(e) = cache.save() {
+            warn!("Failed to write workspace cache: {}", e);
+        }
+
+        Ok(workspaces)
+    }
+
+    /// Upload a file's contents as a block blob, replacing any blob already at `url`
+    /// Needs a data role on the storage account (e.g. Storage Blob Data Contributor).
+    pub async fn upload_blob(&self, url: &str, content: Vec<u8>, content_type: &str) -> Result<()> {
+        self.validate_auth().await?;
+
+        let response = self
+            .send_authorized(STORAGE_SCOPE, |token| {
+                self.http_client
+                    .put(url)
+                    .bearer_auth(token)
+                    .header("x-ms-version", BLOB_API_VERSION)
+                    .header("x-ms-blob-type", "BlockBlob")
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .body(content.clone())
+                    .timeout(BLOB_UPLOAD_TIMEOUT)
+            })
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            // Storage errors are XML; the code element is the useful part
+            let error_text = response.text();
+            let code = error_text
+                .split("<Code>")
+                .nth(1)
+                .and_then(|rest| rest.split("</Code>").next())
+                .map(str::to_string);
+            return Err(KqlPanopticonError::AzureApiError {
+                status,
+                message: format!(
+                    "Blob upload failed: {}",
+                    code.as_deref().unwrap_or(error_text.trim())
+                ),
+                code,
+            });
+        }
+        Ok(())
+    }
+
+    /// Post a batch of result rows to an Event Hub or a Log Analytics custom table
+    pub async fn ingest_rows(
+        &self,
+        target: &IngestionTarget,
+        rows: &[serde_json::Value],
+    ) -> Result<()> {
+        self.validate_auth().await?;
+
+        let body = target.body(rows)?;
+        let (scope, url, content_type) = match target {
+            IngestionTarget::EventHub {
+                namespace,
+                event_hub,
+            } => {
+                let host = if namespace.contains('.') {
+                    namespace.clone()
+                } else {
+                    format!("{}.{}", namespace, self.cloud.service_bus_suffix)
+                };
+                (
+                    EVENT_HUBS_SCOPE.to_string(),
+                    format!(
+                        "https://{}/{}/messages?api-version=2014-01",
+                        host, event_hub
+                    ),
+                    "application/vnd.microsoft.servicebus.json",
+                )
+            }
+            IngestionTarget::LogAnalytics {
+                endpoint,
+                rule_id,
+                stream,
+            } => (
+                self.cloud.monitor_scope(),
+                format!(
+                    "{}/dataCollectionRules/{}/streams/{}?api-version=2023-01-01",
+                    endpoint.trim_end_matches('/'),
+                    rule_id,
+                    stream
+                ),
+                "application/json",
+            ),
+        };
+
+        let response = self
+            .send_authorized(&scope, |token| {
+                self.http_client
+                    .post(&url)
+                    .bearer_auth(token)
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .body(body.clone())
+            })
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            if status == 429 {
+                return Err(KqlPanopticonError::RateLimitExceeded {
+                    retry_after: Self::parse_retry_after(&response),
+                });
+            }
+            let error_text = response.text();
+            return Err(Self::parse_azure_error(
+                status,
+                &error_text,
+                &format!("Failed to send rows to {}", target.describe()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// List the saved searches and functions stored in a workspace, by category then name
+    pub async fn list_saved_searches(&self, workspace: &Workspace) -> Result<Vec<SavedSearch>> {
+        self.validate_auth().await?;
+
+        let url = format!(
+            "{}{}/savedSearches?api-version=2020-08-01",
+            self.cloud.management, workspace.resource_id
+        );
+        let response = self
+            .send_authorized(&self.cloud.management_scope(), |token| {
+                self.http_client.get(&url).bearer_auth(token)
+            })
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let error_text = response.text();
+            return Err(Self::parse_azure_error(
+                status,
+                &error_text,
+                "Failed to list saved searches",
+            ));
+        }
+
+        let list: SavedSearchListResponse = response.json()?;
+        let mut searches: Vec<SavedSearch> = list.value.into_iter().map(Into::into).collect();
+        searches.sort_by(|a, b| {
+            (a.category.to_lowercase(), a.name.to_lowercase())
+                .cmp(&(b.category.to_lowercase(), b.name.to_lowercase()))
+        });
+        Ok(searches)
+    }
+
     /// List all Log Analytics workspaces across all subscriptions
     /// Returns all workspaces found, with warnings for failed or empty subscriptions
     pub async fn list_workspaces(&self) -> Result<Vec<Workspace>> {
         self.validate_auth().await?;
 
-        // Get all subscriptions
-        let subscriptions = self.list_subscriptions().await?;
-        let token = self.get_token_for_management().await?;
+        // Get all subscriptions, skipping any excluded by the subscription filter
+        let all_subscriptions = self.list_subscriptions().await?;
+        let total_subscriptions = all_subscriptions.len();
+        let subscriptions: Vec<Subscription> = all_subscriptions
+            .into_iter()
+            .filter(|sub| {
+                self.subscription_filter
+                    .allows(&sub.subscription_id, &sub.display_name)
+            })
+            .collect();
+
+        if subscriptions.is_empty() {
+            return Err(KqlPanopticonError::InvalidConfiguration(format!(
+                "Subscription filter excludes all {} subscriptions",
+                total_subscriptions
+            )));
+        }
+
+        if subscriptions.len() < total_subscriptions {
+            log::info!(
+                "Subscription filter: enumerating {} of {} subscriptions",
+                subscriptions.len(),
+                total_subscriptions
+            );
+        }
 
         let mut all_workspaces = Vec::new();
 
         for subscription in subscriptions {
             let url = format!(
-                "https://management.azure.com/subscriptions/{}/providers/Microsoft.OperationalInsights/workspaces?api-version=2021-06-01",
-                subscription.subscription_id
+                "{}/subscriptions/{}/providers/Microsoft.OperationalInsights/workspaces?api-version=2021-06-01",
+                self.cloud.management, subscription.subscription_id
             );
 
             let response = match self
-                .http_client
-                .get(&url)
-                .header("Authorization", format!("Bearer {}", token))
-                .send()
+                .send_authorized(&self.cloud.management_scope(), |token| {
+                    self.http_client.get(&url).bearer_auth(token)
+                })
                 .await
             {
                 Ok(resp) => resp,
@@ -511,7 +1149,7 @@ impl Client {
 
             if !response.status().is_success() {
                 let status = response.status().as_u16();
-                let error_text = response.text().await.unwrap_or_default();
+                let error_text = response.text();
                 warn!(
                     "Warning: Failed to list workspaces in subscription '{}' ({}): HTTP {} - {}",
                     subscription.display_name, subscription.subscription_id, status, error_text
@@ -519,16 +1157,17 @@ impl Client {
                 continue;
             }
 
-            let workspace_response: WorkspaceListResponse = match response.json().await {
-                Ok(resp) => resp,
-                Err(e) => {
-                    warn!(
+            let workspace_response: WorkspaceListResponse =
+                match response.json::<WorkspaceListResponse>() {
+                    Ok(resp) => resp,
+                    Err(e) => {
+                        warn!(
                         "Warning: Failed to parse workspace list for subscription '{}' ({}): {}",
                         subscription.display_name, subscription.subscription_id, e
                     );
-                    continue;
-                }
-            };
+                        continue;
+                    }
+                };
 
             if workspace_response.value.is_empty() {
                 warn!(
@@ -540,13 +1179,7 @@ impl Client {
 
             // Convert workspace resources to Workspace structs
             for workspace_resource in workspace_response.value {
-                let workspace = Workspace::from((
-                    workspace_resource,
-                    subscription.subscription_id.clone(),
-                    subscription.tenant_id.clone(),
-                    subscription.display_name.clone(),
-                ));
-                all_workspaces.push(workspace);
+                all_workspaces.push(Workspace::from((workspace_resource, &subscription)));
             }
         }
 
@@ -559,3 +1192,135 @@ impl Client {
         Ok(all_workspaces)
     }
 }
+
+/// Method, URL and body identifying a request's fixture
+fn request_key(request: &reqwest::Request) -> (String, String, Vec<u8>) {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(<[u8]>::to_vec)
+        .unwrap_or_default();
+    (
+        request.method().to_string(),
+        request.url().to_string(),
+        body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_statistics() {
+        let response: QueryResponse = serde_json::from_value(serde_json::json!({
+            "tables": [],
+            "statistics": {
+                "query": {
+                    "executionTime": 1.25,
+                    "resourceUsage": {
+                        "cpu": { "user": "00:00:01.5", "kernel": "00:00:00", "total cpu": "1.00:00:01.5" },
+                        "memory": { "peak_per_node": 1048576 }
+                    },
+                    "inputDatasetStatistics": {
+                        "extents": { "total": 40, "scanned": 4 },
+                        "rows": { "total": 100000, "scanned": 2500 }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let stats = response.query_statistics().unwrap();
+        assert_eq!(stats.execution_secs, Some(1.25));
+        assert_eq!(stats.cpu_secs, Some(86401.5));
+        assert_eq!(stats.peak_memory_bytes, Some(1048576));
+        assert_eq!(
+            (stats.rows_scanned, stats.rows_total),
+            (Some(2500), Some(100000))
+        );
+        assert_eq!(
+            (stats.extents_scanned, stats.extents_total),
+            (Some(4), Some(40))
+        );
+        assert_eq!(parse_timespan("00:02:03.25"), Some(123.25));
+        assert_eq!(parse_timespan("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_azure_error_code() {
+        let semantic = r#"{"error":{"code":"BadArgumentError","message":"The request had some invalid properties","innererror":{"code":"SemanticError","message":"A semantic error occurred.","innererror":{"code":"SEM0100","message":"'where' operator: Failed to resolve table or column expression named 'SigninLogz'"}}}}"#;
+        let error = Client::parse_azure_error(400, semantic, "Query failed");
+        assert_eq!(error.azure_code(), Some("SemanticError"));
+        assert!(error.is_query_rejected());
+        assert!(error.to_string().contains("named 'SigninLogz'"));
+
+        let partial = r#"{"error":{"code":"PartialError","message":"There were some errors when processing your query.","details":[{"code":"EngineError","message":"Something went wrong processing your query on the server.","innererror":{"code":"-2133196797","message":"Out of memory"}}]}}"#;
+        let error = Client::parse_azure_error(500, partial, "Query failed");
+        assert_eq!(error.azure_code(), Some("EngineError"));
+        assert!(!error.is_query_rejected());
+
+        let error = Client::parse_azure_error(502, "Bad Gateway", "Query failed");
+        assert_eq!(error.azure_code(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delegated_subscriptions_follow_skip_token() {
+        let dir = std::env::temp_dir().join(format!("kql-graph-{}", std::process::id()));
+        let recorder = Recorder::new(RecordMode::Record(dir.clone()));
+        let client = Client::new()
+            .unwrap()
+            .with_record_mode(RecordMode::Replay(dir.clone()));
+        let url = format!(
+            "{}/providers/Microsoft.ResourceGraph/resources?api-version=2021-03-01",
+            client.cloud.management
+        );
+        let respond = |options: serde_json::Value, body: serde_json::Value| {
+            let request = serde_json::json!({
+                "query": DELEGATED_SUBSCRIPTIONS_QUERY,
+                "options": options,
+            });
+            let response = RecordedResponse {
+                status: 200,
+                retry_after: None,
+                body: body.to_string(),
+            };
+            recorder
+                .record(
+                    "POST",
+                    &url,
+                    &serde_json::to_vec(&request).unwrap(),
+                    &response,
+                )
+                .unwrap();
+        };
+        let subscription = |id: &str| serde_json::json!({ "subscriptionId": id, "name": id, "tenantId": "customer", "state": "Enabled" });
+
+        let tenant = RecordedResponse {
+            status: 200,
+            retry_after: None,
+            body: "home".to_string(),
+        };
+        recorder
+            .record("AZ", "account show --query tenantId", &[], &tenant)
+            .unwrap();
+        respond(
+            serde_json::json!({ "resultFormat": "objectArray" }),
+            serde_json::json!({ "data": [subscription("sub-1")], "$skipToken": "page-2" }),
+        );
+        respond(
+            serde_json::json!({ "resultFormat": "objectArray", "$skipToken": "page-2" }),
+            serde_json::json!({ "data": [subscription("sub-2")] }),
+        );
+
+        let subscriptions = client.list_delegated_subscriptions().await.unwrap();
+        let ids: Vec<&str> = subscriptions
+            .iter()
+            .map(|s| s.subscription_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["sub-1", "sub-2"]);
+        assert_eq!(subscriptions[1].home_tenant_id.as_deref(), Some("customer"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
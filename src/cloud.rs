@@ -0,0 +1,222 @@
+use crate::error::{KqlPanopticonError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Azure cloud the APIs are called in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudEnvironment {
+    /// Azure public cloud
+    #[default]
+    Public,
+    /// Azure Government
+    #[serde(alias = "usgovernment")]
+    UsGov,
+    /// Azure China (21Vianet)
+    China,
+    /// Endpoints given in config.yaml (e.g. an air-gapped cloud)
+    Custom,
+}
+
+impl CloudEnvironment {
+    /// Parse a name as written in config.yaml (case-insensitive)
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "public" | "azurecloud" => Some(Self::Public),
+            "usgov" | "usgovernment" | "azureusgovernment" => Some(Self::UsGov),
+            "china" | "azurechinacloud" => Some(Self::China),
+            "custom" => Some(Self::Custom),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::UsGov => "usgov",
+            Self::China => "china",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+/// Cloud selection and endpoint overrides (`cloud` in config.yaml)
+///
+/// Overrides apply on top of the environment's endpoints; `custom` starts from the public
+/// cloud and needs at least `management` and `log_analytics`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudConfig {
+    /// `public` (default), `usgov`, `china` or `custom`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<CloudEnvironment>,
+    /// Azure Resource Manager endpoint, e.g. `https://management.usgovcloudapi.net`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub management: Option<String>,
+    /// Log Analytics query endpoint, e.g. `https://api.loganalytics.us`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_analytics: Option<String>,
+    /// Azure Monitor resource that Logs Ingestion tokens are requested for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+    /// Host suffix of Event Hubs namespaces, e.g. `servicebus.usgovcloudapi.net`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_bus_suffix: Option<String>,
+    /// Storage endpoint suffix of Blob Storage accounts, e.g. `core.usgovcloudapi.net`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_suffix: Option<String>,
+}
+
+impl CloudConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Endpoints of the configured cloud with the overrides applied
+    pub fn endpoints(&self) -> Result<CloudEndpoints> {
+        let environment = self.environment.unwrap_or_default();
+        if environment == CloudEnvironment::Custom
+            && (self.management.is_none() || self.log_analytics.is_none())
+        {
+            return Err(KqlPanopticonError::InvalidConfiguration(
+                "cloud: a custom environment needs `management` and `log_analytics` endpoints"
+                    .to_string(),
+            ));
+        }
+
+        let mut endpoints = CloudEndpoints::for_environment(environment);
+        let url = |value: &String| value.trim().trim_end_matches('/').to_string();
+        if let Some(management) = &self.management {
+            endpoints.management = url(management);
+        }
+        if let Some(log_analytics) = &self.log_analytics {
+            endpoints.log_analytics = url(log_analytics);
+        }
+        if let Some(monitor) = &self.monitor {
+            endpoints.monitor = url(monitor);
+        }
+        if let Some(suffix) = &self.service_bus_suffix {
+            endpoints.service_bus_suffix = suffix.trim().trim_matches('.').to_string();
+        }
+        if let Some(suffix) = &self.storage_suffix {
+            endpoints.storage_suffix = suffix.trim().trim_matches('.').to_string();
+        }
+        Ok(endpoints)
+    }
+}
+
+/// Endpoints and token scopes of one Azure cloud
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudEndpoints {
+    pub environment: CloudEnvironment,
+    /// Azure Resource Manager (subscriptions, workspaces, saved searches)
+    pub management: String,
+    /// Log Analytics query API
+    pub log_analytics: String,
+    /// Azure Monitor Logs Ingestion API resource
+    pub monitor: String,
+    /// Host suffix appended to Event Hubs namespace names
+    pub service_bus_suffix: String,
+    /// Storage endpoint suffix; Blob Storage accounts are `<account>.blob.<suffix>`
+    pub storage_suffix: String,
+}
+
+impl CloudEndpoints {
+    /// Well-known endpoints of `environment` (`custom` starts from the public cloud)
+    pub fn for_environment(environment: CloudEnvironment) -> Self {
+        let (management, log_analytics, monitor, service_bus_suffix, storage_suffix) =
+            match environment {
+                CloudEnvironment::Public | CloudEnvironment::Custom => (
+                    "https://management.azure.com",
+                    "https://api.loganalytics.io",
+                    "https://monitor.azure.com",
+                    "servicebus.windows.net",
+                    "core.windows.net",
+                ),
+                CloudEnvironment::UsGov => (
+                    "https://management.usgovcloudapi.net",
+                    "https://api.loganalytics.us",
+                    "https://monitor.azure.us",
+                    "servicebus.usgovcloudapi.net",
+                    "core.usgovcloudapi.net",
+                ),
+                CloudEnvironment::China => (
+                    "https://management.chinacloudapi.cn",
+                    "https://api.loganalytics.azure.cn",
+                    "https://monitor.azure.cn",
+                    "servicebus.chinacloudapi.cn",
+                    "core.chinacloudapi.cn",
+                ),
+            };
+        Self {
+            environment,
+            management: management.to_string(),
+            log_analytics: log_analytics.to_string(),
+            monitor: monitor.to_string(),
+            service_bus_suffix: service_bus_suffix.to_string(),
+            storage_suffix: storage_suffix.to_string(),
+        }
+    }
+
+    /// Token scope for the Azure Resource Manager API
+    pub fn management_scope(&self) -> String {
+        format!("{}/.default", self.management)
+    }
+
+    /// Token scope for the Log Analytics query API
+    pub fn log_analytics_scope(&self) -> String {
+        format!("{}/.default", self.log_analytics)
+    }
+
+    /// Token scope for the Logs Ingestion API (the double slash is what the API expects)
+    pub fn monitor_scope(&self) -> String {
+        format!("{}//.default", self.monitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloud_endpoints() {
+        let public = CloudConfig::default().endpoints().unwrap();
+        assert_eq!(
+            public.management_scope(),
+            "https://management.azure.com/.default"
+        );
+        assert_eq!(
+            public.monitor_scope(),
+            "https://monitor.azure.com//.default"
+        );
+
+        let gov: CloudConfig = serde_yaml::from_str("environment: usgov").unwrap();
+        let gov = gov.endpoints().unwrap();
+        assert_eq!(
+            gov.log_analytics_scope(),
+            "https://api.loganalytics.us/.default"
+        );
+        assert_eq!(gov.service_bus_suffix, "servicebus.usgovcloudapi.net");
+        assert_eq!(gov.storage_suffix, "core.usgovcloudapi.net");
+
+        let custom = CloudConfig {
+            environment: Some(CloudEnvironment::Custom),
+            management: Some("https://arm.example.local/".to_string()),
+            ..Default::default()
+        };
+        assert!(custom.endpoints().is_err());
+        let custom = CloudConfig {
+            log_analytics: Some("https://la.example.local".to_string()),
+            ..custom
+        };
+        let custom = custom.endpoints().unwrap();
+        assert_eq!(custom.management, "https://arm.example.local");
+        assert_eq!(custom.monitor, "https://monitor.azure.com");
+
+        assert_eq!(
+            CloudEnvironment::parse("AzureUSGovernment"),
+            Some(CloudEnvironment::UsGov)
+        );
+        assert_eq!(CloudEnvironment::parse("mars"), None);
+    }
+}
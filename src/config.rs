@@ -0,0 +1,528 @@
+use crate::blob_storage::BlobStorageConfig;
+use crate::cloud::{CloudConfig, CloudEnvironment};
+use crate::error::Result;
+use crate::webhook::WebhookConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Persistent application configuration (config.yaml in the config directory, see `paths`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Subscriptions to include/exclude during workspace discovery
+    pub subscriptions: SubscriptionFilter,
+    /// Maximum rows written per job when a pack or query doesn't set its own limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<usize>,
+    /// Output folder for results (TUI, and packs without their own `settings`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_folder: Option<PathBuf>,
+    /// Query timeout in seconds when a pack or query doesn't set its own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_timeout_secs: Option<u64>,
+    /// Retries for failed queries when a pack or query doesn't set its own count
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
+    /// Seconds between Azure CLI authentication checks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_interval_secs: Option<u64>,
+    /// Seconds discovered workspaces are reused before re-enumerating (0 disables the cache)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_cache_ttl_secs: Option<u64>,
+    /// Maximum queries running at once (pack runs in the TUI and `run-pack`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    /// Queries running at once against the same workspace in pack runs (default 1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrency_per_workspace: Option<usize>,
+    /// Store sessions, packs, runs and cache here instead of next to this file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    /// Additional pack libraries, merged after the personal library (`<data_dir>/packs`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pack_libraries: Vec<PackLibrary>,
+    /// Git remote the personal pack library syncs with (`packs pull` / `packs push`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack_remote: Option<String>,
+    /// Recently applied result post-filters, most recent first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_filters: Vec<String>,
+    /// Values last entered for query parameters, by parameter name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub query_parameters: BTreeMap<String, String>,
+    /// Limits enforced by `clean` on stored sessions and job output
+    #[serde(skip_serializing_if = "RetentionPolicy::is_empty")]
+    pub retention: RetentionPolicy,
+    /// Proxy and custom CA settings for outgoing HTTPS requests
+    #[serde(skip_serializing_if = "NetworkConfig::is_empty")]
+    pub network: NetworkConfig,
+    /// Azure cloud (public, US Government, China or custom endpoints)
+    #[serde(skip_serializing_if = "CloudConfig::is_empty")]
+    pub cloud: CloudConfig,
+    /// Workspace ID or name that `test --online` runs pack queries against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_workspace: Option<String>,
+    /// Notification POSTed when a pack run or a session's jobs finish
+    #[serde(skip_serializing_if = "WebhookConfig::is_empty")]
+    pub webhook: WebhookConfig,
+    /// Storage account container that finished result files are uploaded to
+    #[serde(skip_serializing_if = "BlobStorageConfig::is_empty")]
+    pub blob_storage: BlobStorageConfig,
+    /// Consecutive failures before a workspace is flagged as failing (0 = never; default 3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_streak: Option<u32>,
+    /// Keep per-workspace failure streaks and latency across sessions (default: off)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persist_workspace_health: Option<bool>,
+    /// Ask for confirmation before a pack run of more jobs (workspaces × queries) than this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm_jobs_threshold: Option<usize>,
+    /// Disable query execution in the TUI, e.g. for demos (default: off)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    /// Bump the pack version and append a changelog entry when saving edits in the TUI (default: off)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack_changelog: Option<bool>,
+    /// Jobs table columns, in order (the default layout if empty)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub jobs_columns: Vec<String>,
+    /// Time zone for output folder names, metadata, the Jobs table and reports:
+    /// `UTC` (default), `local`, or an offset such as `+02:00`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+}
+
+impl Config {
+    /// Get the config file path
+    pub fn path() -> Result<PathBuf> {
+        Ok(crate::paths::config_dir()?.join("config.yaml"))
+    }
+
+    /// Load the config from disk (returns defaults if the file doesn't exist)
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        Ok(serde_yaml::from_str(&content)?)
+    }
+
+    /// Load the config, falling back to defaults (with a warning) if it can't be read
+    pub fn load_or_default() -> Self {
+        Self::load().unwrap_or_else(|e| {
+            log::warn!("Failed to load config, using defaults: {}", e);
+            Self::default()
+        })
+    }
+
+    /// How long discovered workspaces are cached (an hour unless configured)
+    pub fn workspace_cache_ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.workspace_cache_ttl_secs
+                .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_CACHE_TTL_SECS),
+        )
+    }
+
+    /// Load the config with `KQL_PANOPTICON_*` environment overrides applied
+    ///
+    /// Use this for reading settings; `load` keeps environment values out of a config that
+    /// is saved back to disk.
+    pub fn resolve() -> Self {
+        let mut config = Self::load_or_default();
+        config.apply_env(|name| std::env::var(name).ok());
+        config
+    }
+
+    /// Override settings from environment variables (empty or unparseable values are ignored)
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        let number = |name: &str| {
+            let value = var(name)?;
+            match value.trim().parse() {
+                Ok(number) => Some(number),
+                Err(_) => {
+                    log::warn!("Ignoring {}={:?}: not a whole number", name, value);
+                    None
+                }
+            }
+        };
+
+        if let Some(folder) = var(OUTPUT_FOLDER_ENV) {
+            self.output_folder = Some(PathBuf::from(folder));
+        }
+        if let Some(secs) = number(QUERY_TIMEOUT_ENV) {
+            self.query_timeout_secs = Some(secs);
+        }
+        if let Some(count) = number(RETRY_COUNT_ENV) {
+            self.retry_count = Some(count as u32);
+        }
+        if let Some(secs) = number(VALIDATION_INTERVAL_ENV) {
+            self.validation_interval_secs = Some(secs);
+        }
+        if let Some(max) = number(MAX_CONCURRENCY_ENV) {
+            self.max_concurrency = Some(max as usize);
+        }
+        if let Some(max) = number(WORKSPACE_CONCURRENCY_ENV) {
+            self.max_concurrency_per_workspace = Some(max as usize);
+        }
+        if let Some(max) = number(MAX_ROWS_ENV) {
+            self.max_rows = Some(max as usize);
+        }
+        if let Some(workspace) = var(TEST_WORKSPACE_ENV) {
+            self.test_workspace = Some(workspace);
+        }
+        if let Some(url) = var(WEBHOOK_URL_ENV) {
+            self.webhook.url = Some(url);
+        }
+        if let Some(zone) = var(TIME_ZONE_ENV) {
+            self.time_zone = Some(zone);
+        }
+        if let Some(cloud) = var(CLOUD_ENV) {
+            match CloudEnvironment::parse(&cloud) {
+                Some(environment) => self.cloud.environment = Some(environment),
+                None => log::warn!(
+                    "Ignoring {}={:?}: expected public, usgov, china or custom",
+                    CLOUD_ENV,
+                    cloud
+                ),
+            }
+        }
+        self.network.apply_env(var);
+    }
+
+    /// The configured output time zone (UTC if unset or invalid, with a warning)
+    pub fn output_time_zone(&self) -> crate::time_zone::OutputTimeZone {
+        let Some(text) = &self.time_zone else {
+            return Default::default();
+        };
+        crate::time_zone::OutputTimeZone::parse(text).unwrap_or_else(|| {
+            log::warn!(
+                "Ignoring time_zone {:?}: expected UTC, local or an offset like +02:00",
+                text
+            );
+            Default::default()
+        })
+    }
+
+    /// Save the config to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Session and output retention limits (unset limits are not enforced)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Delete sessions last saved more than this many days ago
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_session_age_days: Option<u64>,
+    /// Keep only this many of the most recently saved sessions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_sessions: Option<usize>,
+    /// Delete the oldest run folders once the output folder grows past this many MB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_mb: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// Check if no limits are set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Settings set globally in config.yaml that a pack can override (`webhook`, `blob_storage`)
+///
+/// Each field the pack sets wins and the rest fall back to the global value, so a pack can
+/// change one field, or set `enabled: false` to turn the feature off for its runs.
+pub(crate) trait PackOverride: Clone + Default {
+    /// Fill the fields this doesn't set from `global`
+    fn or(self, global: &Self) -> Self;
+
+    /// The merged `enabled` flag (on unless set to false)
+    fn enabled(&self) -> bool;
+
+    /// Layer a pack's overrides on the global settings
+    /// Returns None if the result is turned off.
+    fn layered(global: &Self, pack: Option<&Self>) -> Option<Self> {
+        let merged = pack.cloned().unwrap_or_default().or(global);
+        merged.enabled().then_some(merged)
+    }
+}
+
+/// Environment variable overriding `output_folder`
+pub const OUTPUT_FOLDER_ENV: &str = "KQL_PANOPTICON_OUTPUT_FOLDER";
+/// Environment variable overriding `query_timeout_secs`
+pub const QUERY_TIMEOUT_ENV: &str = "KQL_PANOPTICON_QUERY_TIMEOUT_SECS";
+/// Environment variable overriding `retry_count`
+pub const RETRY_COUNT_ENV: &str = "KQL_PANOPTICON_RETRY_COUNT";
+/// Environment variable overriding `validation_interval_secs`
+pub const VALIDATION_INTERVAL_ENV: &str = "KQL_PANOPTICON_VALIDATION_INTERVAL_SECS";
+/// Environment variable overriding `max_concurrency`
+pub const MAX_CONCURRENCY_ENV: &str = "KQL_PANOPTICON_MAX_CONCURRENCY";
+/// Environment variable overriding `max_concurrency_per_workspace`
+pub const WORKSPACE_CONCURRENCY_ENV: &str = "KQL_PANOPTICON_MAX_CONCURRENCY_PER_WORKSPACE";
+/// Environment variable overriding `max_rows`
+pub const MAX_ROWS_ENV: &str = "KQL_PANOPTICON_MAX_ROWS";
+/// Environment variable overriding `test_workspace`
+pub const TEST_WORKSPACE_ENV: &str = "KQL_PANOPTICON_TEST_WORKSPACE";
+/// Environment variable overriding `webhook.url`
+pub const WEBHOOK_URL_ENV: &str = "KQL_PANOPTICON_WEBHOOK_URL";
+/// Environment variable overriding `time_zone`
+pub const TIME_ZONE_ENV: &str = "KQL_PANOPTICON_TIME_ZONE";
+/// Environment variable overriding `cloud.environment`
+pub const CLOUD_ENV: &str = "KQL_PANOPTICON_CLOUD";
+/// Environment variable overriding `network.proxy`
+pub const PROXY_ENV: &str = "KQL_PANOPTICON_PROXY";
+/// Environment variable overriding `network.no_proxy`
+pub const NO_PROXY_ENV: &str = "KQL_PANOPTICON_NO_PROXY";
+/// Environment variable overriding `network.ca_bundle`
+pub const CA_BUNDLE_ENV: &str = "KQL_PANOPTICON_CA_BUNDLE";
+
+/// Proxy and TLS settings for outgoing HTTPS requests
+///
+/// Without a `proxy`, the standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` variables apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Proxy URL for all requests, e.g. `http://proxy.corp:8080` (may embed `user:pass@`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Comma-separated hosts, domains or IP ranges that bypass `proxy`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM file of extra root certificates to trust, e.g. a TLS-inspecting proxy's CA
+    /// (`~` is expanded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl NetworkConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Load the network settings from config.yaml with environment overrides applied
+    pub fn resolve() -> Self {
+        let mut network = Config::load_or_default().network;
+        network.apply_env(|name| std::env::var(name).ok());
+        network
+    }
+
+    /// Override settings from environment variables (empty values are ignored)
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) {
+        let var = |name| var(name).filter(|value| !value.trim().is_empty());
+        if let Some(proxy) = var(PROXY_ENV) {
+            self.proxy = Some(proxy);
+        }
+        if let Some(no_proxy) = var(NO_PROXY_ENV) {
+            self.no_proxy = Some(no_proxy);
+        }
+        if let Some(ca_bundle) = var(CA_BUNDLE_ENV) {
+            self.ca_bundle = Some(PathBuf::from(ca_bundle));
+        }
+    }
+}
+
+/// A named directory of query packs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackLibrary {
+    /// Name shown in the Packs tab's Source column
+    pub name: String,
+    /// Library root (`~` is expanded)
+    pub path: PathBuf,
+    /// Refuse to write packs into this library (e.g. a shared network path)
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// Subscription include/exclude filter
+///
+/// Entries match a subscription ID exactly or a display name glob (`*` wildcard),
+/// both case-insensitive. An empty include list includes every subscription.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionFilter {
+    /// Only enumerate subscriptions matching one of these entries
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Never enumerate subscriptions matching one of these entries
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl SubscriptionFilter {
+    /// Check if the filter has no entries
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Check if a subscription passes the filter
+    pub fn allows(&self, subscription_id: &str, display_name: &str) -> bool {
+        let matches = |entry: &String| entry_matches(entry, subscription_id, display_name);
+
+        if !self.include.is_empty() && !self.include.iter().any(matches) {
+            return false;
+        }
+
+        !self.exclude.iter().any(matches)
+    }
+
+    /// Toggle a subscription on or off by ID
+    pub fn toggle(&mut self, subscription_id: &str, display_name: &str) {
+        let same_id = |e: &String| e.eq_ignore_ascii_case(subscription_id);
+
+        if self.allows(subscription_id, display_name) {
+            self.include.retain(|e| !same_id(e));
+            if self.allows(subscription_id, display_name) {
+                self.exclude.push(subscription_id.to_string());
+            }
+        } else {
+            self.exclude.retain(|e| !same_id(e));
+            if !self.allows(subscription_id, display_name) && !self.include.is_empty() {
+                self.include.push(subscription_id.to_string());
+            }
+        }
+    }
+
+    /// Parse the compact text form: comma-separated entries, `!` prefix to exclude
+    /// Example: `prod-*, 00000000-0000-0000-0000-000000000000, !*-sandbox`
+    pub fn parse(text: &str) -> Self {
+        let mut filter = Self::default();
+        for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some(excluded) = entry.strip_prefix('!') {
+                let excluded = excluded.trim();
+                if !excluded.is_empty() {
+                    filter.exclude.push(excluded.to_string());
+                }
+            } else {
+                filter.include.push(entry.to_string());
+            }
+        }
+        filter
+    }
+
+    /// Render the compact text form (inverse of `parse`)
+    pub fn to_text(&self) -> String {
+        self.include
+            .iter()
+            .cloned()
+            .chain(self.exclude.iter().map(|e| format!("!{}", e)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Match a filter entry against a subscription ID or display name glob
+fn entry_matches(entry: &str, subscription_id: &str, display_name: &str) -> bool {
+    if entry.eq_ignore_ascii_case(subscription_id) {
+        return true;
+    }
+
+    let pattern = format!("(?i)^{}$", regex::escape(entry).replace(r"\*", ".*"));
+    regex::Regex::new(&pattern)
+        .map(|re| re.is_match(display_name))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        let filter = SubscriptionFilter::default();
+        assert!(filter.allows("sub-1", "Production"));
+    }
+
+    #[test]
+    fn test_include_and_exclude_patterns() {
+        let filter = SubscriptionFilter::parse("prod-*, sub-42, !prod-sandbox");
+        assert!(filter.allows("sub-1", "Prod-EU"));
+        assert!(filter.allows("SUB-42", "Anything"));
+        assert!(!filter.allows("sub-2", "prod-sandbox"));
+        assert!(!filter.allows("sub-3", "Development"));
+    }
+
+    #[test]
+    fn test_toggle_round_trip() {
+        let mut filter = SubscriptionFilter::default();
+        filter.toggle("sub-1", "Dev");
+        assert!(!filter.allows("sub-1", "Dev"));
+        filter.toggle("sub-1", "Dev");
+        assert!(filter.allows("sub-1", "Dev"));
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let filter = SubscriptionFilter::parse("a, !b");
+        assert_eq!(SubscriptionFilter::parse(&filter.to_text()), filter);
+    }
+
+    #[test]
+    fn test_network_env_overrides() {
+        let mut network: NetworkConfig =
+            serde_yaml::from_str("proxy: http://file-proxy:8080\nno_proxy: localhost\n").unwrap();
+        network.apply_env(|name| match name {
+            PROXY_ENV => Some("http://env-proxy:3128".to_string()),
+            NO_PROXY_ENV => Some(" ".to_string()),
+            CA_BUNDLE_ENV => Some("~/corp-ca.pem".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(network.proxy.as_deref(), Some("http://env-proxy:3128"));
+        assert_eq!(network.no_proxy.as_deref(), Some("localhost"));
+        assert_eq!(network.ca_bundle, Some(PathBuf::from("~/corp-ca.pem")));
+    }
+
+    #[test]
+    fn test_config_env_overrides() {
+        let mut config: Config = serde_yaml::from_str(
+            "max_rows: 100
+query_timeout_secs: 60
+retry_count: 2
+",
+        )
+        .unwrap();
+        config.apply_env(|name| match name {
+            OUTPUT_FOLDER_ENV => Some("/data/output".to_string()),
+            QUERY_TIMEOUT_ENV => Some("120".to_string()),
+            RETRY_COUNT_ENV => Some("lots".to_string()),
+            MAX_CONCURRENCY_ENV => Some("4".to_string()),
+            PROXY_ENV => Some("http://env-proxy:3128".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config.output_folder, Some(PathBuf::from("/data/output")));
+        assert_eq!(config.query_timeout_secs, Some(120));
+        // Unparseable values keep the file's setting
+        assert_eq!(config.retry_count, Some(2));
+        assert_eq!(config.max_concurrency, Some(4));
+        assert_eq!(config.max_rows, Some(100));
+        assert_eq!(
+            config.network.proxy.as_deref(),
+            Some("http://env-proxy:3128")
+        );
+    }
+
+    #[test]
+    fn test_workspace_cache_ttl() {
+        let config = Config::default();
+        assert_eq!(
+            config.workspace_cache_ttl(),
+            std::time::Duration::from_secs(crate::workspace::DEFAULT_WORKSPACE_CACHE_TTL_SECS)
+        );
+        let config: Config = serde_yaml::from_str("workspace_cache_ttl_secs: 0\n").unwrap();
+        assert!(config.workspace_cache_ttl().is_zero());
+    }
+}
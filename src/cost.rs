@@ -0,0 +1,110 @@
+use crate::backend::QueryBackend;
+use crate::error::{KqlPanopticonError, Result};
+use crate::query_params::split_top_level;
+use std::time::Duration;
+
+/// Pipeline operators that only filter rows, so records they see were scanned by the query
+const FILTER_OPERATORS: &[&str] = &["where", "filter", "search"];
+
+/// Build a follow-up query estimating the billed size of the data a query scans
+///
+/// Keeps the query's `let` statements, source and leading row filters, then sums the
+/// `_BilledSize` of the matching records. Returns None if the query has no tabular expression.
+pub fn cost_query(query: &str) -> Option<String> {
+    let mut statements: Vec<&str> = split_top_level(query, ';')
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let expression = statements.pop()?;
+
+    let stages: Vec<&str> = split_top_level(expression, '|')
+        .into_iter()
+        .map(str::trim)
+        .collect();
+    let source = stages.first().filter(|s| !s.is_empty())?;
+
+    let mut kept = vec![*source];
+    kept.extend(stages.iter().skip(1).take_while(|stage| {
+        let operator = stage.split_whitespace().next().unwrap_or_default();
+        FILTER_OPERATORS
+            .iter()
+            .any(|op| op.eq_ignore_ascii_case(operator))
+    }));
+
+    let mut cost_query = String::new();
+    for statement in statements {
+        cost_query.push_str(statement);
+        cost_query.push_str(";\n");
+    }
+    cost_query.push_str(&kept.join("\n| "));
+    cost_query.push_str("\n| summarize BilledBytes = sum(_BilledSize)");
+    Some(cost_query)
+}
+
+/// Estimate the billed bytes scanned by a query on a workspace
+pub async fn estimate_billed_bytes<B: QueryBackend>(
+    client: &B,
+    workspace_id: &str,
+    query: &str,
+    timespan: Option<&str>,
+    timeout: Duration,
+) -> Result<u64> {
+    let cost_query = cost_query(query).ok_or_else(|| {
+        KqlPanopticonError::QueryExecutionFailed(
+            "Query has no tabular expression to estimate".to_string(),
+        )
+    })?;
+
+    let response = client
+        .query_workspace(workspace_id, &cost_query, timespan, timeout)
+        .await?;
+
+    // sum() over no records returns null, which means nothing was scanned
+    let billed_bytes = response
+        .tables
+        .first()
+        .and_then(|table| table.rows.first())
+        .and_then(|row| row.get(0))
+        .and_then(|value| value.as_f64())
+        .unwrap_or(0.0);
+
+    Ok(billed_bytes.max(0.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_query_keeps_source_and_filters() {
+        let query = "SecurityEvent\n| where EventID == 4625\n| where Account has '|'\n| summarize count() by Account";
+        assert_eq!(
+            cost_query(query).unwrap(),
+            "SecurityEvent\n| where EventID == 4625\n| where Account has '|'\n| summarize BilledBytes = sum(_BilledSize)"
+        );
+    }
+
+    #[test]
+    fn test_cost_query_keeps_let_statements() {
+        let query = "let ids = dynamic([4624, 4625]);\nSecurityEvent | where EventID in (ids) | project Account;";
+        assert_eq!(
+            cost_query(query).unwrap(),
+            "let ids = dynamic([4624, 4625]);\nSecurityEvent\n| where EventID in (ids)\n| summarize BilledBytes = sum(_BilledSize)"
+        );
+    }
+
+    #[test]
+    fn test_cost_query_skips_comments() {
+        let query = "SecurityEvent // don't | count\n| where EventID == 4625\n| take 10";
+        assert_eq!(
+            cost_query(query).unwrap(),
+            "SecurityEvent // don't | count\n| where EventID == 4625\n| summarize BilledBytes = sum(_BilledSize)"
+        );
+    }
+
+    #[test]
+    fn test_cost_query_empty() {
+        assert!(cost_query("  \n ").is_none());
+    }
+}
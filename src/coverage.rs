@@ -0,0 +1,208 @@
+use crate::error::Result;
+use crate::query_pack::QueryPack;
+use crate::session::Session;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+/// Navigator colour for techniques the library has queries for
+const COVERED_COLOR: &str = "#ffe766";
+/// Navigator colour for techniques whose queries ran in the session
+const EXERCISED_COLOR: &str = "#8ec843";
+
+/// Library coverage of a single ATT&CK technique
+#[derive(Debug, Clone, Default)]
+pub struct TechniqueCoverage {
+    /// Queries mapped to the technique ("pack / query")
+    pub queries: Vec<String>,
+    /// Whether one of those queries completed in the session being checked
+    pub exercised: bool,
+}
+
+/// ATT&CK technique coverage of the pack library
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Technique ID -> coverage, sorted by ID
+    pub techniques: BTreeMap<String, TechniqueCoverage>,
+    /// Normalized query text -> technique IDs (for matching session jobs)
+    query_techniques: HashMap<String, Vec<String>>,
+}
+
+impl Coverage {
+    /// Collect coverage from every pack in every library
+    /// Packs that fail to load are skipped with a warning.
+    pub fn from_libraries() -> Result<Self> {
+        let mut coverage = Self::default();
+        for library in QueryPack::libraries()? {
+            for path in QueryPack::list_library_packs(&library.path)? {
+                match QueryPack::load_from_file(&path) {
+                    Ok(pack) => coverage.add_pack(&pack),
+                    Err(e) => log::warn!("Skipping pack {}: {}", path.display(), e),
+                }
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Add the technique mappings of a pack's queries
+    pub fn add_pack(&mut self, pack: &QueryPack) {
+        for query in pack.get_queries() {
+            for technique in &query.mitre {
+                self.techniques
+                    .entry(technique.to_uppercase())
+                    .or_default()
+                    .queries
+                    .push(format!("{} / {}", pack.name, query.name));
+            }
+            if !query.mitre.is_empty() {
+                self.query_techniques
+                    .entry(normalize_query(&query.query))
+                    .or_default()
+                    .extend(query.mitre.iter().map(|t| t.to_uppercase()));
+            }
+        }
+    }
+
+    /// Mark techniques whose queries completed in the session
+    /// Jobs are matched to pack queries by query text.
+    pub fn mark_session(&mut self, session: &Session) {
+        let completed = session
+            .jobs
+            .iter()
+            .filter(|job| matches!(job.status.as_str(), "COMPLETED" | "PARTIAL"))
+            .filter_map(|job| job.query.as_deref());
+        self.mark_exercised(completed);
+    }
+
+    /// Mark techniques mapped to any of the given query texts as exercised
+    pub fn mark_exercised<'a>(&mut self, queries: impl IntoIterator<Item = &'a str>) {
+        for query in queries {
+            let Some(techniques) = self.query_techniques.get(&normalize_query(query)) else {
+                continue;
+            };
+            for technique in techniques {
+                if let Some(coverage) = self.techniques.get_mut(technique) {
+                    coverage.exercised = true;
+                }
+            }
+        }
+    }
+
+    /// Number of techniques exercised in the session
+    pub fn exercised_count(&self) -> usize {
+        self.techniques.values().filter(|t| t.exercised).count()
+    }
+
+    /// Build an ATT&CK Navigator layer (score 1 = covered, 2 = exercised)
+    pub fn navigator_layer(&self, name: &str, description: &str) -> serde_json::Value {
+        let techniques: Vec<serde_json::Value> = self
+            .techniques
+            .iter()
+            .map(|(id, coverage)| {
+                let (score, color) = if coverage.exercised {
+                    (2, EXERCISED_COLOR)
+                } else {
+                    (1, COVERED_COLOR)
+                };
+                json!({
+                    "techniqueID": id,
+                    "score": score,
+                    "color": color,
+                    "comment": coverage.queries.join("; "),
+                    "enabled": true,
+                })
+            })
+            .collect();
+
+        json!({
+            "name": name,
+            "description": description,
+            "domain": "enterprise-attack",
+            "versions": { "layer": "4.5", "navigator": "4.9.1" },
+            "techniques": techniques,
+            "gradient": {
+                "colors": [COVERED_COLOR, EXERCISED_COLOR],
+                "minValue": 1,
+                "maxValue": 2,
+            },
+            "legendItems": [
+                { "label": "Covered by pack library", "color": COVERED_COLOR },
+                { "label": "Exercised in session", "color": EXERCISED_COLOR },
+            ],
+        })
+    }
+}
+
+/// Check if a string is an ATT&CK technique ID (T1059 or sub-technique T1021.001)
+pub fn is_technique_id(id: &str) -> bool {
+    let Some(rest) = id.strip_prefix(['T', 't']) else {
+        return false;
+    };
+    let (technique, sub) = match rest.split_once('.') {
+        Some((technique, sub)) => (technique, Some(sub)),
+        None => (rest, None),
+    };
+    let digits = |s: &str, n: usize| s.len() == n && s.chars().all(|c| c.is_ascii_digit());
+    digits(technique, 4) && sub.is_none_or(|sub| digits(sub, 3))
+}
+
+/// Collapse whitespace so formatting differences don't prevent a match
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_technique_id() {
+        assert!(is_technique_id("T1059"));
+        assert!(is_technique_id("T1021.001"));
+        assert!(!is_technique_id("T105"));
+        assert!(!is_technique_id("T1021.1"));
+        assert!(!is_technique_id("TA0001"));
+        assert!(!is_technique_id("1059"));
+    }
+
+    #[test]
+    fn test_coverage_layer() {
+        let pack: QueryPack = serde_yaml::from_str(
+            r#"
+name: "Hunts"
+queries:
+  - name: "Encoded PowerShell"
+    query: "DeviceProcessEvents | where ProcessCommandLine has '-enc'"
+    mitre: ["T1059.001"]
+  - name: "RDP logons"
+    query: "SecurityEvent | where LogonType == 10"
+    mitre: ["T1021.001", "t1078"]
+"#,
+        )
+        .unwrap();
+        let mut coverage = Coverage::default();
+        coverage.add_pack(&pack);
+
+        coverage.mark_exercised(["SecurityEvent\n| where LogonType == 10", "Heartbeat"]);
+
+        assert_eq!(coverage.techniques.len(), 3);
+        assert_eq!(coverage.exercised_count(), 2);
+        assert!(!coverage.techniques["T1059.001"].exercised);
+
+        let layer = coverage.navigator_layer("Coverage", "");
+        let scores: Vec<_> = layer["techniques"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| {
+                (
+                    t["techniqueID"].as_str().unwrap(),
+                    t["score"].as_i64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            scores,
+            vec![("T1021.001", 2), ("T1059.001", 1), ("T1078", 2)]
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Errors returned by kql-panopticon
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 pub enum KqlPanopticonError {
     #[error("Azure authentication failed: {0}")]
     AuthenticationFailed(String),
@@ -9,13 +13,19 @@ pub enum KqlPanopticonError {
     TokenAcquisitionFailed(String),
 
     #[error("HTTP request failed: {0}")]
-    HttpRequestFailed(String),
+    HttpRequestFailed(TransportError),
 
     #[error("Failed to parse response: {0}")]
     ParseFailed(String),
 
+    /// `code` is the most specific Azure error code in the response (e.g. `SemanticError`
+    /// rather than the `BadArgumentError` wrapping it)
     #[error("Azure API error (status {status}): {message}")]
-    AzureApiError { status: u16, message: String },
+    AzureApiError {
+        status: u16,
+        message: String,
+        code: Option<String>,
+    },
 
     #[error("Azure API rate limit exceeded. Retry after {retry_after} seconds")]
     RateLimitExceeded { retry_after: u64 },
@@ -41,16 +51,184 @@ pub enum KqlPanopticonError {
     #[error("Query pack not found: {0}")]
     QueryPackNotFound(String),
 
+    #[error("Git command failed: {0}")]
+    GitCommandFailed(String),
+
     #[error("Home directory not found")]
     HomeDirectoryNotFound,
 
+    #[error("{0} is open in another instance")]
+    FileLocked(String),
+
     #[error("{0}")]
     Other(String),
 }
 
+/// Azure error codes for problems with the query itself, which fail the same way on every attempt
+const QUERY_ERROR_CODES: &[&str] = &["SemanticError", "SyntaxError"];
+
+impl KqlPanopticonError {
+    /// Azure error code behind an API error, if the response had one
+    pub fn azure_code(&self) -> Option<&str> {
+        match self {
+            KqlPanopticonError::AzureApiError { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Check if Azure rejected the query itself, so retrying can't help
+    pub fn is_query_rejected(&self) -> bool {
+        self.azure_code()
+            .is_some_and(|code| QUERY_ERROR_CODES.contains(&code))
+    }
+}
+
+/// Kind of transport-level failure behind an HTTP request error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum TransportErrorKind {
+    /// Host name couldn't be resolved
+    Dns,
+    /// Connection couldn't be established (refused, unreachable)
+    Connect,
+    /// TLS handshake or certificate validation failed
+    Tls,
+    /// Proxy refused or failed to tunnel the request
+    Proxy,
+    /// Connection was reset or closed mid-request
+    Reset,
+    /// Request timed out
+    Timeout,
+    /// Response body couldn't be read
+    Body,
+    /// Response body couldn't be decoded
+    Decode,
+    /// Request couldn't be built or its redirects followed
+    Request,
+    /// Unclassified failure
+    Other,
+}
+
+impl TransportErrorKind {
+    /// Short label for display
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TransportErrorKind::Dns => "DNS",
+            TransportErrorKind::Connect => "Connect",
+            TransportErrorKind::Tls => "TLS",
+            TransportErrorKind::Proxy => "Proxy",
+            TransportErrorKind::Reset => "Reset",
+            TransportErrorKind::Timeout => "Timeout",
+            TransportErrorKind::Body => "Body",
+            TransportErrorKind::Decode => "Decode",
+            TransportErrorKind::Request => "Request",
+            TransportErrorKind::Other => "Other",
+        }
+    }
+
+    /// Check if a failure of this kind may succeed on retry
+    /// TLS, proxy and malformed requests fail the same way until the setup is fixed.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            TransportErrorKind::Dns
+                | TransportErrorKind::Connect
+                | TransportErrorKind::Reset
+                | TransportErrorKind::Timeout
+                | TransportErrorKind::Body
+                | TransportErrorKind::Other
+        )
+    }
+}
+
+/// A transport-level HTTP failure, classified by kind
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportError {
+    pub kind: TransportErrorKind,
+    pub message: String,
+}
+
+impl TransportError {
+    /// Classify a reqwest error by its kind and underlying cause
+    pub fn from_reqwest(err: &reqwest::Error) -> Self {
+        let kind = if err.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if err.is_builder() || err.is_redirect() {
+            TransportErrorKind::Request
+        } else if err.is_decode() {
+            TransportErrorKind::Decode
+        } else if let Some(kind) = classify_cause(err) {
+            kind
+        } else if err.is_connect() {
+            TransportErrorKind::Connect
+        } else if err.is_body() {
+            TransportErrorKind::Body
+        } else {
+            TransportErrorKind::Other
+        };
+
+        Self {
+            kind,
+            message: err.to_string(),
+        }
+    }
+
+    /// Check if the request may succeed on retry
+    pub fn is_retryable(&self) -> bool {
+        self.kind.is_retryable()
+    }
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.kind.as_str(), self.message)
+    }
+}
+
+/// Find the transport failure kind in an error's chain of causes
+/// The HTTP stack doesn't expose DNS, TLS or proxy failures as types, so their messages are matched.
+fn classify_cause(err: &(dyn std::error::Error + 'static)) -> Option<TransportErrorKind> {
+    let mut cause = Some(err);
+    while let Some(err) = cause {
+        let message = err.to_string().to_lowercase();
+        if message.contains("dns error") || message.contains("failed to lookup address") {
+            return Some(TransportErrorKind::Dns);
+        }
+        if message.contains("proxy") || message.contains("tunnel") {
+            return Some(TransportErrorKind::Proxy);
+        }
+        if ["certificate", "ssl", "tls", "handshake"]
+            .iter()
+            .any(|word| message.contains(word))
+        {
+            return Some(TransportErrorKind::Tls);
+        }
+        if message.contains("connection closed") || message.contains("connection reset") {
+            return Some(TransportErrorKind::Reset);
+        }
+
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+            match io.kind() {
+                ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::UnexpectedEof => return Some(TransportErrorKind::Reset),
+                ErrorKind::ConnectionRefused
+                | ErrorKind::NotConnected
+                | ErrorKind::AddrNotAvailable => return Some(TransportErrorKind::Connect),
+                ErrorKind::TimedOut => return Some(TransportErrorKind::Timeout),
+                _ => {}
+            }
+        }
+        cause = err.source();
+    }
+    None
+}
+
 impl From<reqwest::Error> for KqlPanopticonError {
     fn from(err: reqwest::Error) -> Self {
-        KqlPanopticonError::HttpRequestFailed(err.to_string())
+        KqlPanopticonError::HttpRequestFailed(TransportError::from_reqwest(&err))
     }
 }
 
@@ -78,4 +256,53 @@ impl From<serde_yaml::Error> for KqlPanopticonError {
     }
 }
 
+impl From<parquet::errors::ParquetError> for KqlPanopticonError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        KqlPanopticonError::IoError(format!("Parquet: {}", err))
+    }
+}
+
+impl From<rusqlite::Error> for KqlPanopticonError {
+    fn from(err: rusqlite::Error) -> Self {
+        KqlPanopticonError::IoError(format!("SQLite: {}", err))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, KqlPanopticonError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn test_classify_transport_cause() {
+        let cause = |err: Error| classify_cause(&err);
+        assert_eq!(
+            cause(Error::other(
+                "dns error: failed to lookup address information"
+            )),
+            Some(TransportErrorKind::Dns)
+        );
+        assert_eq!(
+            cause(Error::other(
+                "error:0A000086:SSL routines::certificate verify failed"
+            )),
+            Some(TransportErrorKind::Tls)
+        );
+        assert_eq!(
+            cause(Error::other("unsuccessful tunnel")),
+            Some(TransportErrorKind::Proxy)
+        );
+        assert_eq!(
+            cause(Error::from(ErrorKind::ConnectionReset)),
+            Some(TransportErrorKind::Reset)
+        );
+        assert_eq!(cause(Error::other("boom")), None);
+
+        let err = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let transport = TransportError::from_reqwest(&err);
+        assert_eq!(transport.kind, TransportErrorKind::Request);
+        assert!(!transport.is_retryable());
+    }
+}
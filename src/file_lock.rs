@@ -0,0 +1,198 @@
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Who holds a lock, as recorded in the lock file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub pid: u32,
+    pub host: String,
+    pub acquired_at: String,
+}
+
+impl LockInfo {
+    /// Lock info for this process
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            host: host_name(),
+            acquired_at: crate::time_zone::now().to_rfc3339(),
+        }
+    }
+
+    /// Check if the lock was taken by this process
+    fn is_ours(&self) -> bool {
+        self.pid == std::process::id() && self.host == host_name()
+    }
+
+    /// Check if the holding process is known to have exited (only checked on this host)
+    fn is_stale(&self) -> bool {
+        self.host == host_name() && !process_running(self.pid)
+    }
+}
+
+impl std::fmt::Display for LockInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "process {} on {} (since {})",
+            self.pid, self.host, self.acquired_at
+        )
+    }
+}
+
+/// Advisory lock on a file opened for writing (`<file>.lock` next to it)
+///
+/// Other instances check for the lock before writing; it is removed when dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+}
+
+/// Result of trying to lock a file
+#[derive(Debug)]
+pub enum Acquire {
+    Acquired(FileLock),
+    /// Another running instance holds the lock
+    HeldBy(LockInfo),
+}
+
+impl FileLock {
+    /// Path of the lock file guarding `target`
+    pub fn lock_path(target: &Path) -> PathBuf {
+        let mut name = target.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        target.with_file_name(name)
+    }
+
+    /// Lock `target`, unless another running instance already holds it
+    /// Locks left behind by a process that has exited are taken over.
+    pub fn acquire(target: &Path) -> Result<Acquire> {
+        let path = Self::lock_path(target);
+        match Self::create(&path) {
+            Ok(lock) => return Ok(Acquire::Acquired(lock)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+        match Self::held_elsewhere(target) {
+            Some(holder) => Ok(Acquire::HeldBy(holder)),
+            None => Self::take_over(target).map(Acquire::Acquired),
+        }
+    }
+
+    /// The other running instance holding the lock on `target`, if any
+    pub fn held_elsewhere(target: &Path) -> Option<LockInfo> {
+        Self::holder(target).filter(|holder| !holder.is_ours() && !holder.is_stale())
+    }
+
+    /// Check if this lock hasn't been taken over by another instance
+    pub fn is_held(&self) -> bool {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<LockInfo>(&content).ok())
+            .is_some_and(|info| info.is_ours())
+    }
+
+    /// Lock `target` even if another instance holds it (that instance's writes may be lost)
+    pub fn take_over(target: &Path) -> Result<Self> {
+        let path = Self::lock_path(target);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string(&LockInfo::current())?)?;
+        Ok(Self { path })
+    }
+
+    /// Who holds the lock on `target`, if anyone
+    pub fn holder(target: &Path) -> Option<LockInfo> {
+        let content = std::fs::read_to_string(Self::lock_path(target)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Create the lock file, failing if it exists
+    fn create(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        let info = serde_json::to_string(&LockInfo::current()).unwrap_or_default();
+        file.write_all(info.as_bytes())?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Leave the file alone if another instance took the lock over
+        if self.is_held() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// This machine's name, for telling locks held on shared drives apart
+fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Check if a process is running (assumed running where this can't be checked)
+fn process_running(pid: u32) -> bool {
+    let proc = Path::new("/proc");
+    !proc.is_dir() || proc.join(pid.to_string()).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_instance_sees_lock() {
+        let dir = std::env::temp_dir().join(format!("kql-lock-{}", std::process::id()));
+        let target = dir.join("hunt.json");
+
+        let Acquire::Acquired(lock) = FileLock::acquire(&target).unwrap() else {
+            panic!("first lock should be acquired");
+        };
+        assert!(FileLock::lock_path(&target).exists());
+
+        // Another live instance holds the lock
+        let other = LockInfo {
+            pid: 1,
+            host: host_name(),
+            acquired_at: String::new(),
+        };
+        std::fs::write(
+            FileLock::lock_path(&target),
+            serde_json::to_string(&other).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            FileLock::acquire(&target).unwrap(),
+            Acquire::HeldBy(holder) if holder == other
+        ));
+
+        // The taken-over lock stays when the old holder drops it
+        assert!(!lock.is_held());
+        drop(lock);
+        assert_eq!(FileLock::holder(&target), Some(other));
+        let lock = FileLock::take_over(&target).unwrap();
+        assert!(FileLock::holder(&target).unwrap().is_ours());
+        drop(lock);
+        assert!(!FileLock::lock_path(&target).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,57 @@
+/// Score how well `pattern` fuzzy-matches `text` (case-insensitive subsequence match)
+///
+/// Returns None if the pattern's characters don't all appear in order. Higher scores mean
+/// better matches: consecutive characters and matches at word starts score extra.
+/// An empty pattern matches everything with a score of 0.
+pub fn score(pattern: &str, text: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut text_chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut previous_match: Option<usize> = None;
+    let mut previous_char: Option<char> = None;
+
+    for p in pattern.chars().flat_map(char::to_lowercase) {
+        if p.is_whitespace() {
+            continue;
+        }
+        loop {
+            let (idx, c) = text_chars.next()?;
+            let at_word_start = previous_char.is_none_or(|prev| !prev.is_alphanumeric());
+            previous_char = Some(c);
+            if c != p {
+                continue;
+            }
+
+            score += 1;
+            if previous_match.is_some_and(|prev| prev + 1 == idx) {
+                score += 4;
+            }
+            if at_word_start {
+                score += 2;
+            }
+            previous_match = Some(idx);
+            break;
+        }
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_match() {
+        assert!(score("prd", "ws-production").is_some());
+        assert!(score("PROD", "ws-production").is_some());
+        assert!(score("dorp", "ws-production").is_none());
+        assert_eq!(score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_consecutive_and_word_start_rank_higher() {
+        let contiguous = score("prod", "ws-prod-01").unwrap();
+        let scattered = score("prod", "ws-p-r-o-d").unwrap();
+        assert!(contiguous > scattered);
+    }
+}
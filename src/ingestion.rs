@@ -0,0 +1,303 @@
+use crate::backend::QueryBackend;
+use crate::client::Column;
+use crate::error::{KqlPanopticonError, Result};
+use crate::output_sink::{ExportSummary, OutputSink, SinkOutput};
+use crate::query_job::row_object;
+use crate::workspace::Workspace;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// Largest request body sent at once (both services accept 1 MB; this leaves headroom)
+const MAX_BATCH_BYTES: usize = 900_000;
+
+/// Where ingested rows are sent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IngestionTarget {
+    /// An Event Hub; each row becomes one event with the row as its JSON body
+    EventHub {
+        /// Namespace name, or its host (e.g. `hunt.servicebus.windows.net`)
+        namespace: String,
+        event_hub: String,
+    },
+    /// A Log Analytics custom table, through the Logs Ingestion API
+    LogAnalytics {
+        /// Data collection endpoint (or the DCR's logs ingestion endpoint) URL
+        endpoint: String,
+        /// Immutable ID of the data collection rule, e.g. `dcr-0123...`
+        rule_id: String,
+        /// Stream declared in the rule, e.g. `Custom-HuntResults_CL`
+        stream: String,
+    },
+}
+
+/// Ingestion sink settings (`ingestion` in a pack's `settings`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestionConfig {
+    #[serde(flatten)]
+    pub target: IngestionTarget,
+    /// Result column renames, e.g. `Account: UserPrincipalName`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mapping: BTreeMap<String, String>,
+    /// Send only the columns named in `mapping`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mapped_only: bool,
+    /// Column added to every row holding the name of the workspace it came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_column: Option<String>,
+}
+
+impl IngestionTarget {
+    /// Short description for logs and errors
+    pub fn describe(&self) -> String {
+        match self {
+            IngestionTarget::EventHub {
+                namespace,
+                event_hub,
+            } => format!("Event Hub {}/{}", namespace, event_hub),
+            IngestionTarget::LogAnalytics { stream, .. } => {
+                format!("Log Analytics stream {}", stream)
+            }
+        }
+    }
+
+    /// Request body for a batch of rows
+    pub(crate) fn body(&self, rows: &[Value]) -> Result<String> {
+        match self {
+            IngestionTarget::EventHub { .. } => {
+                let events = rows
+                    .iter()
+                    .map(|row| Ok(serde_json::json!({ "Body": serde_json::to_string(row)? })))
+                    .collect::<Result<Vec<Value>>>()?;
+                Ok(serde_json::to_string(&events)?)
+            }
+            IngestionTarget::LogAnalytics { .. } => Ok(serde_json::to_string(rows)?),
+        }
+    }
+}
+
+impl IngestionConfig {
+    /// Apply the column mapping to a row and add the workspace column
+    pub fn map_row(&self, row: Map<String, Value>, workspace: &Workspace) -> Map<String, Value> {
+        let mut mapped: Map<String, Value> = row
+            .into_iter()
+            .filter_map(|(column, value)| match self.mapping.get(&column) {
+                Some(target) => Some((target.clone(), value)),
+                None if self.mapped_only => None,
+                None => Some((column, value)),
+            })
+            .collect();
+        if let Some(column) = &self.workspace_column {
+            mapped.insert(column.clone(), workspace.name.clone().into());
+        }
+        mapped
+    }
+}
+
+/// Split rows into batches whose request bodies stay under `max_bytes`
+/// A row too large on its own still goes in a batch by itself.
+fn batches(rows: Vec<Value>, max_bytes: usize) -> Vec<Vec<Value>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut size = 2;
+    for row in rows {
+        // Event Hub bodies escape each row as a string, so allow for that
+        let row_size = row.to_string().len() * 5 / 4 + 16;
+        if !batch.is_empty() && size + row_size > max_bytes {
+            batches.push(std::mem::take(&mut batch));
+            size = 2;
+        }
+        size += row_size;
+        batch.push(row);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+/// Sends result rows to an Event Hub or Log Analytics table as pages arrive
+///
+/// Rows go to a background task that batches and posts them, so fetching isn't held up.
+/// After a pagination failure it reports how many rows were delivered.
+pub(crate) struct IngestionSink {
+    config: IngestionConfig,
+    workspace: Workspace,
+    columns: Vec<Column>,
+    parse_dynamics: bool,
+    sender: UnboundedSender<Vec<Value>>,
+    task: JoinHandle<Result<usize>>,
+    /// Set by the task when a request fails, so the next page can report why
+    failure: Arc<Mutex<Option<String>>>,
+}
+
+impl IngestionSink {
+    /// Start the sending task (must be called on the async runtime)
+    pub fn start<B: QueryBackend>(
+        client: B,
+        config: IngestionConfig,
+        workspace: Workspace,
+        columns: &[Column],
+        parse_dynamics: bool,
+    ) -> Self {
+        let (sender, mut receiver) = unbounded_channel::<Vec<Value>>();
+        let failure = Arc::new(Mutex::new(None));
+        let target = config.target.clone();
+        let task_failure = failure.clone();
+        let task = tokio::spawn(async move {
+            let mut sent = 0;
+            while let Some(rows) = receiver.recv().await {
+                for batch in batches(rows, MAX_BATCH_BYTES) {
+                    if let Err(e) = client.ingest_rows(&target, &batch).await {
+                        if let Ok(mut failure) = task_failure.lock() {
+                            *failure = Some(e.to_string());
+                        }
+                        return Err(e);
+                    }
+                    sent += batch.len();
+                }
+            }
+            Ok(sent)
+        });
+
+        Self {
+            config,
+            workspace,
+            columns: columns.to_vec(),
+            parse_dynamics,
+            sender,
+            task,
+            failure,
+        }
+    }
+
+    /// Stop accepting rows and wait for everything queued to be sent
+    fn wait(self) -> Result<usize> {
+        let target = self.config.target.describe();
+        drop(self.sender);
+        tokio::runtime::Handle::current()
+            .block_on(self.task)
+            .map_err(|e| KqlPanopticonError::Other(format!("Ingestion task failed: {}", e)))?
+            .map_err(|e| {
+                KqlPanopticonError::Other(format!("Ingestion into {} failed: {}", target, e))
+            })
+    }
+}
+
+impl OutputSink for IngestionSink {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let row = row_object(&self.columns, row, self.parse_dynamics);
+                Value::Object(self.config.map_row(row, &self.workspace))
+            })
+            .collect();
+        if self.sender.send(rows).is_err() {
+            let failure = self
+                .failure
+                .lock()
+                .ok()
+                .and_then(|failure| failure.clone())
+                .unwrap_or_else(|| "sending stopped".to_string());
+            return Err(KqlPanopticonError::Other(format!(
+                "Ingestion into {} failed: {}",
+                self.config.target.describe(),
+                failure
+            )));
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, _summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        let target = self.config.target.describe();
+        let sent = self.wait()?;
+        info!("Sent {} rows to {}", sent, target);
+        Ok(None)
+    }
+
+    fn save_partial(self: Box<Self>, _summary: &ExportSummary) -> Result<String> {
+        let target = self.config.target.describe();
+        match self.wait() {
+            Ok(sent) => Ok(format!("{} rows already sent to {}", sent, target)),
+            Err(e) => {
+                warn!("{}", e);
+                Ok(format!("ingestion into {} also failed", target))
+            }
+        }
+    }
+
+    fn discard(self: Box<Self>) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingestion_config() {
+        let config: IngestionConfig = serde_yaml::from_str(
+            r#"
+type: log_analytics
+endpoint: https://hunt-dce.westeurope-1.ingest.monitor.azure.com
+rule_id: dcr-0123456789abcdef
+stream: Custom-HuntResults_CL
+mapping:
+  Account: UserPrincipalName
+  Count: Attempts
+mapped_only: true
+workspace_column: SourceWorkspace
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.target,
+            IngestionTarget::LogAnalytics { ref stream, .. } if stream == "Custom-HuntResults_CL"
+        ));
+
+        let workspace: Workspace = serde_json::from_value(serde_json::json!({
+            "workspace_id": "ws-eu-id",
+            "resource_id": "/subscriptions/sub/resourceGroups/rg/providers/Microsoft.OperationalInsights/workspaces/ws-eu",
+            "name": "ws-eu",
+            "location": "westeurope",
+            "subscription_id": "sub",
+            "resource_group": "rg",
+            "tenant_id": "tenant",
+            "subscription_name": "Production",
+        }))
+        .unwrap();
+        let row = serde_json::json!({"Account": "alice", "Count": 3, "Extra": "x"});
+        let mapped = config.map_row(row.as_object().unwrap().clone(), &workspace);
+        assert_eq!(
+            Value::Object(mapped),
+            serde_json::json!({"UserPrincipalName": "alice", "Attempts": 3, "SourceWorkspace": "ws-eu"})
+        );
+
+        let event_hub = IngestionTarget::EventHub {
+            namespace: "hunt".to_string(),
+            event_hub: "results".to_string(),
+        };
+        assert_eq!(
+            event_hub.body(&[serde_json::json!({"a": 1})]).unwrap(),
+            r#"[{"Body":"{\"a\":1}"}]"#
+        );
+    }
+
+    #[test]
+    fn test_batches() {
+        let rows: Vec<Value> = (0..10)
+            .map(|i| serde_json::json!({ "n": i, "pad": "x".repeat(100) }))
+            .collect();
+        let split = batches(rows, 400);
+        assert!(split.len() > 1);
+        assert_eq!(split.iter().map(Vec::len).sum::<usize>(), 10);
+        assert!(batches(Vec::new(), 400).is_empty());
+    }
+}
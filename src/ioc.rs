@@ -0,0 +1,330 @@
+use crate::error::{KqlPanopticonError, Result};
+use regex::Regex;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Domain "TLDs" that are really file extensions (`payload.exe`, `results.csv`)
+const FILE_EXTENSIONS: &[&str] = &[
+    "bat", "bin", "cmd", "conf", "cpl", "csv", "dat", "db", "dll", "doc", "docx", "exe", "gz",
+    "hta", "htm", "html", "ini", "jar", "jpg", "js", "json", "jsonl", "lnk", "log", "msi", "pdf",
+    "png", "ps1", "psm1", "py", "rar", "sh", "sys", "tmp", "txt", "vbs", "xls", "xlsx", "xml",
+    "yaml", "yml", "zip",
+];
+
+/// Kind of indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IocKind {
+    Ipv4,
+    Domain,
+    Email,
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl IocKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IocKind::Ipv4 => "ipv4",
+            IocKind::Domain => "domain",
+            IocKind::Email => "email",
+            IocKind::Md5 => "md5",
+            IocKind::Sha1 => "sha1",
+            IocKind::Sha256 => "sha256",
+        }
+    }
+
+    /// STIX 2.1 pattern matching this indicator
+    fn stix_pattern(&self, value: &str) -> String {
+        let value = value.replace('\\', "\\\\").replace('\'', "\\'");
+        match self {
+            IocKind::Ipv4 => format!("[ipv4-addr:value = '{}']", value),
+            IocKind::Domain => format!("[domain-name:value = '{}']", value),
+            IocKind::Email => format!("[email-addr:value = '{}']", value),
+            IocKind::Md5 => format!("[file:hashes.MD5 = '{}']", value),
+            IocKind::Sha1 => format!("[file:hashes.'SHA-1' = '{}']", value),
+            IocKind::Sha256 => format!("[file:hashes.'SHA-256' = '{}']", value),
+        }
+    }
+}
+
+/// Where an indicator was seen
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sightings {
+    /// Result cells containing the indicator
+    pub count: usize,
+    pub workspaces: BTreeSet<String>,
+    pub queries: BTreeSet<String>,
+}
+
+/// Indicators found across a run's results, deduplicated by kind and value
+#[derive(Debug, Clone, Default)]
+pub struct IocSet {
+    pub indicators: BTreeMap<(IocKind, String), Sightings>,
+}
+
+struct Patterns {
+    ipv4: Regex,
+    email: Regex,
+    domain: Regex,
+    hash: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        ipv4: Regex::new(
+            r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b",
+        )
+        .expect("valid IPv4 regex"),
+        email: Regex::new(r"\b[A-Za-z0-9._%+-]+@(?:[A-Za-z0-9-]+\.)+[A-Za-z]{2,24}\b")
+            .expect("valid email regex"),
+        domain: Regex::new(
+            r"\b(?:[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?\.)+[A-Za-z]{2,24}\b",
+        )
+        .expect("valid domain regex"),
+        hash: Regex::new(r"\b(?:[A-Fa-f0-9]{64}|[A-Fa-f0-9]{40}|[A-Fa-f0-9]{32})\b")
+            .expect("valid hash regex"),
+    })
+}
+
+impl IocSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indicators.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indicators.len()
+    }
+
+    /// Record the indicators in one result cell
+    pub fn scan(&mut self, text: &str, workspace: &str, query: &str) {
+        let patterns = patterns();
+        let mut found: BTreeSet<(IocKind, String)> = BTreeSet::new();
+
+        for m in patterns.ipv4.find_iter(text) {
+            found.insert((IocKind::Ipv4, m.as_str().to_string()));
+        }
+        for m in patterns.hash.find_iter(text) {
+            let kind = match m.len() {
+                32 => IocKind::Md5,
+                40 => IocKind::Sha1,
+                _ => IocKind::Sha256,
+            };
+            found.insert((kind, m.as_str().to_lowercase()));
+        }
+        for m in patterns.email.find_iter(text) {
+            found.insert((IocKind::Email, m.as_str().to_lowercase()));
+        }
+        // The domain part of an email address isn't reported on its own
+        let without_emails = patterns.email.replace_all(text, " ");
+        for m in patterns.domain.find_iter(&without_emails) {
+            let domain = m.as_str().to_lowercase();
+            let tld = domain.rsplit('.').next().unwrap_or_default();
+            if !FILE_EXTENSIONS.contains(&tld) {
+                found.insert((IocKind::Domain, domain));
+            }
+        }
+
+        for key in found {
+            let sightings = self.indicators.entry(key).or_default();
+            sightings.count += 1;
+            sightings.workspaces.insert(workspace.to_string());
+            sightings.queries.insert(query.to_string());
+        }
+    }
+
+    /// Record the indicators in every cell of a result file
+    pub fn scan_file(&mut self, path: &Path, workspace: &str, query: &str) -> Result<()> {
+        let (_, rows) =
+            crate::tui::model::chart::read_result_table(path).map_err(KqlPanopticonError::Other)?;
+        for cell in rows.iter().flatten() {
+            self.scan(cell, workspace, query);
+        }
+        Ok(())
+    }
+
+    /// One row per indicator: type, value, count, workspaces and queries (`;`-separated)
+    pub fn to_csv(&self) -> String {
+        let field = |s: String| crate::query_job::format_csv_value(&serde_json::Value::String(s));
+        let mut csv = String::from("type,value,count,workspaces,queries\n");
+        for ((kind, value), sightings) in &self.indicators {
+            let join = |set: &BTreeSet<String>| set.iter().cloned().collect::<Vec<_>>().join(";");
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                kind.as_str(),
+                field(value.clone()),
+                sightings.count,
+                field(join(&sightings.workspaces)),
+                field(join(&sightings.queries)),
+            ));
+        }
+        csv
+    }
+
+    /// A STIX 2.1 bundle with one indicator object per indicator
+    /// Ids are derived from the indicator, so re-running a pack produces the same ids.
+    pub fn to_stix(&self, source: &str, created: &str) -> serde_json::Value {
+        let objects: Vec<serde_json::Value> = self
+            .indicators
+            .iter()
+            .map(|((kind, value), sightings)| {
+                let id = stable_uuid(&format!("{}:{}", kind.as_str(), value));
+                json!({
+                    "type": "indicator",
+                    "spec_version": "2.1",
+                    "id": format!("indicator--{}", id),
+                    "created": created,
+                    "modified": created,
+                    "name": format!("{} {}", kind.as_str(), value),
+                    "pattern": kind.stix_pattern(value),
+                    "pattern_type": "stix",
+                    "valid_from": created,
+                    "labels": [source],
+                    "x_kql_panopticon": {
+                        "count": sightings.count,
+                        "workspaces": sightings.workspaces,
+                        "queries": sightings.queries,
+                    },
+                })
+            })
+            .collect();
+        json!({
+            "type": "bundle",
+            "id": format!("bundle--{}", stable_uuid(&format!("{}:{}", source, created))),
+            "objects": objects,
+        })
+    }
+
+    /// Write `iocs.csv` and `iocs.stix.json` to `<output_folder>/iocs/<source>_<timestamp>/`
+    /// Returns the folder written to.
+    pub fn write(&self, output_folder: &Path, source: &str) -> Result<PathBuf> {
+        let now = chrono::Utc::now();
+        let name: String = source
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let folder = output_folder.join("iocs").join(format!(
+            "{}_{}",
+            name,
+            now.format("%Y-%m-%d_%H-%M-%S")
+        ));
+        std::fs::create_dir_all(&folder)?;
+        std::fs::write(folder.join("iocs.csv"), self.to_csv())?;
+        let stix = self.to_stix(
+            source,
+            &now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        );
+        std::fs::write(
+            folder.join("iocs.stix.json"),
+            serde_json::to_string_pretty(&stix)?,
+        )?;
+        Ok(folder)
+    }
+}
+
+/// Extract the indicators from a finished run's result files and write them out
+/// Returns the folder written to and the number of indicators, or None if there were none.
+pub fn extract_run(
+    summary: &crate::webhook::RunSummary,
+    output_folder: &Path,
+) -> Result<Option<(PathBuf, usize)>> {
+    let mut iocs = IocSet::new();
+    for output in &summary.outputs {
+        if let Err(e) = iocs.scan_file(&output.path, &output.workspace, &output.query) {
+            log::warn!(
+                "Skipping {} for IOC extraction: {}",
+                output.path.display(),
+                e
+            );
+        }
+    }
+    if iocs.is_empty() {
+        return Ok(None);
+    }
+    let folder = iocs.write(output_folder, &summary.source)?;
+    Ok(Some((folder, iocs.len())))
+}
+
+/// Format a hash of `text` as a UUID (version 5 layout, FNV-1a rather than SHA-1)
+fn stable_uuid(text: &str) -> String {
+    let fnv = |seed: u64| {
+        let mut hash = seed;
+        for byte in text.as_bytes() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    };
+    let high = (fnv(0xcbf29ce484222325) & !0xf000) | 0x5000;
+    let low = (fnv(0x84222325cbf29ce4) & !(0xc << 60)) | (0x8 << 60);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_dedups_across_workspaces() {
+        let mut iocs = IocSet::new();
+        iocs.scan(
+            "Logon from 203.0.113.7 by alice@contoso.com via evil-cdn.net",
+            "ws-eu",
+            "Sign-ins",
+        );
+        iocs.scan("203.0.113.7 downloaded payload.exe", "ws-us", "Sign-ins");
+        iocs.scan(
+            "44D88612FEA8A8F36DE82E1278ABB02F 1.2.3",
+            "ws-us",
+            "Process events",
+        );
+
+        let ip = &iocs.indicators[&(IocKind::Ipv4, "203.0.113.7".to_string())];
+        assert_eq!(ip.count, 2);
+        assert_eq!(ip.workspaces.len(), 2);
+        assert!(iocs
+            .indicators
+            .contains_key(&(IocKind::Email, "alice@contoso.com".to_string())));
+        assert!(iocs
+            .indicators
+            .contains_key(&(IocKind::Domain, "evil-cdn.net".to_string())));
+        assert!(iocs
+            .indicators
+            .contains_key(&(IocKind::Md5, "44d88612fea8a8f36de82e1278abb02f".to_string())));
+        // Neither the email's domain nor a file name counts as a domain
+        assert_eq!(iocs.len(), 4);
+
+        let csv = iocs.to_csv();
+        assert!(csv.contains("ipv4,203.0.113.7,2,ws-eu;ws-us,Sign-ins\n"));
+
+        let stix = iocs.to_stix("Sign-ins", "2024-05-01T10:00:00Z");
+        let objects = stix["objects"].as_array().unwrap();
+        assert_eq!(objects.len(), 4);
+        assert!(objects
+            .iter()
+            .any(|o| o["pattern"] == "[ipv4-addr:value = '203.0.113.7']"));
+        // Ids are stable across runs
+        let again = iocs.to_stix("Sign-ins", "2024-06-01T10:00:00Z");
+        assert_eq!(objects[0]["id"], again["objects"][0]["id"]);
+    }
+}
@@ -0,0 +1,568 @@
+use crate::kql_lexer::SegmentKind;
+
+/// Stages longer than this have their `project`/`summarize`/`extend` lists split one item per line
+const MAX_WIDTH: usize = 100;
+
+/// Subqueries up to this long stay on one line
+const MAX_INLINE_SUBQUERY: usize = 60;
+
+/// Indentation of subqueries and continuation lines
+const INDENT: usize = 4;
+
+/// Operators whose comma-separated lists are split and aligned when too long
+const LIST_OPERATORS: &[&str] = &[
+    "distinct",
+    "extend",
+    "project",
+    "project-away",
+    "project-keep",
+    "project-rename",
+    "project-reorder",
+    "summarize",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// String literal, quotes included
+    Str(String),
+    /// `//` comment, up to the end of the line
+    Comment(String),
+    Open(char),
+    Close(char),
+    Pipe,
+    Comma,
+    Semi,
+    /// Whitespace, with the number of line breaks in it
+    Space(usize),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Token(Token),
+    Group {
+        open: char,
+        /// None if the group is never closed
+        close: Option<char>,
+        children: Vec<Node>,
+    },
+}
+
+/// Reformat a query: one pipe stage per line, subqueries indented, long
+/// `project`/`summarize`/`extend` lists split one item per line and aligned
+///
+/// Only whitespace changes: text inside strings and comments is kept as is, and tokens
+/// written without a space between them stay that way.
+pub fn format(query: &str) -> String {
+    let nodes = parse(tokenize(query));
+    let mut writer = Writer::default();
+    write_sequence(&mut writer, trim_spaces(&nodes), 0);
+    writer.finish().join("\n")
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for segment in crate::kql_lexer::segments(query) {
+        let text = &query[segment.start..segment.end];
+        match segment.kind {
+            SegmentKind::Code => tokenize_code(text, &mut tokens),
+            SegmentKind::Str { .. } => tokens.push(Token::Str(text.to_string())),
+            SegmentKind::Comment => tokens.push(Token::Comment(text.trim_end().to_string())),
+        }
+    }
+    tokens
+}
+
+/// Tokenize text with no strings or comments in it
+fn tokenize_code(code: &str, tokens: &mut Vec<Token>) {
+    let chars: Vec<char> = code.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        match c {
+            c if c.is_whitespace() => {
+                while i < chars.len() && chars[i].is_whitespace() {
+                    i += 1;
+                }
+                let newlines = chars[start..i].iter().filter(|&&c| c == '\n').count();
+                tokens.push(Token::Space(newlines));
+                continue;
+            }
+            '(' | '[' | '{' => tokens.push(Token::Open(c)),
+            ')' | ']' | '}' => tokens.push(Token::Close(c)),
+            '|' => tokens.push(Token::Pipe),
+            ',' => tokens.push(Token::Comma),
+            ';' => tokens.push(Token::Semi),
+            _ => {
+                while i < chars.len() && !is_boundary(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+                continue;
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Whether a word ends before `c`
+fn is_boundary(c: char) -> bool {
+    c.is_whitespace() || "()[]{}|,;".contains(c)
+}
+
+/// Nest tokens into bracket groups (unmatched closing brackets are kept as words)
+fn parse(tokens: Vec<Token>) -> Vec<Node> {
+    let mut stack: Vec<(char, Vec<Node>)> = Vec::new();
+    let mut current = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Open(open) => stack.push((open, std::mem::take(&mut current))),
+            Token::Close(close) => match stack.pop() {
+                Some((open, parent)) if closing(open) == close => {
+                    let children = std::mem::replace(&mut current, parent);
+                    current.push(Node::Group {
+                        open,
+                        close: Some(close),
+                        children,
+                    });
+                }
+                unmatched => {
+                    if let Some(frame) = unmatched {
+                        stack.push(frame);
+                    }
+                    current.push(Node::Token(Token::Word(close.to_string())));
+                }
+            },
+            token => current.push(Node::Token(token)),
+        }
+    }
+    // Groups still open at the end of the query
+    while let Some((open, parent)) = stack.pop() {
+        let children = std::mem::replace(&mut current, parent);
+        current.push(Node::Group {
+            open,
+            close: None,
+            children,
+        });
+    }
+    current
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+fn is_space(node: &Node) -> bool {
+    matches!(node, Node::Token(Token::Space(_)))
+}
+
+fn is_token(node: &Node, token: &Token) -> bool {
+    matches!(node, Node::Token(t) if t == token)
+}
+
+fn trim_spaces(nodes: &[Node]) -> &[Node] {
+    let start = nodes
+        .iter()
+        .position(|n| !is_space(n))
+        .unwrap_or(nodes.len());
+    let end = nodes
+        .iter()
+        .rposition(|n| !is_space(n))
+        .map_or(start, |i| i + 1);
+    &nodes[start..end]
+}
+
+/// Line breaks in the whitespace before the first token
+fn leading_newlines(nodes: &[Node]) -> usize {
+    match nodes.first() {
+        Some(Node::Token(Token::Space(newlines))) => *newlines,
+        _ => 0,
+    }
+}
+
+/// Whether a group holds a subquery (pipes or statements at its own level)
+fn has_structure(children: &[Node]) -> bool {
+    children
+        .iter()
+        .any(|n| is_token(n, &Token::Pipe) || is_token(n, &Token::Semi))
+}
+
+fn contains_comment(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Token(Token::Comment(_)) => true,
+        Node::Group { children, .. } => contains_comment(children),
+        _ => false,
+    })
+}
+
+/// Split on a separator token, keeping whether each part ended with it
+fn split<'a>(nodes: &'a [Node], separator: &Token) -> Vec<(&'a [Node], bool)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        if is_token(node, separator) {
+            parts.push((&nodes[start..i], true));
+            start = i + 1;
+        }
+    }
+    parts.push((&nodes[start..], false));
+    parts
+}
+
+/// Output lines under construction
+#[derive(Default)]
+struct Writer {
+    lines: Vec<String>,
+    line: String,
+    /// Whitespace seen since the last token (written as one space before the next)
+    pending_space: bool,
+    /// A comment ended the line; the next token starts a new one at this indent
+    pending_break: Option<usize>,
+}
+
+impl Writer {
+    fn has_content(&self) -> bool {
+        !self.line.trim().is_empty()
+    }
+
+    /// Start a new line at `indent` (an empty current line is reused)
+    fn break_line(&mut self, indent: usize) {
+        if self.has_content() {
+            self.lines
+                .push(std::mem::take(&mut self.line).trim_end().to_string());
+        }
+        self.line = " ".repeat(indent);
+        self.pending_space = false;
+        self.pending_break = None;
+    }
+
+    fn blank_line(&mut self) {
+        if self.has_content() {
+            self.break_line(0);
+        }
+        if self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    /// Write text, after a space if whitespace preceded it
+    fn word(&mut self, text: &str) {
+        if let Some(indent) = self.pending_break {
+            self.break_line(indent);
+        }
+        if self.pending_space && self.has_content() {
+            self.line.push(' ');
+        }
+        self.line.push_str(text);
+        self.pending_space = false;
+    }
+
+    /// Write text directly after the previous token (closing brackets, commas)
+    fn tight(&mut self, text: &str) {
+        self.pending_space = false;
+        self.word(text);
+    }
+
+    fn comment(&mut self, text: &str, own_line: bool, indent: usize, continuation: usize) {
+        if own_line && self.has_content() {
+            self.break_line(indent);
+        }
+        self.pending_break = None;
+        self.word(text);
+        self.pending_break = Some(continuation);
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if self.has_content() {
+            self.lines.push(self.line.trim_end().to_string());
+        }
+        self.lines
+    }
+}
+
+/// Write statements (`;`-separated) made of pipe stages, one stage per line
+fn write_sequence(w: &mut Writer, nodes: &[Node], indent: usize) {
+    let statements = split(nodes, &Token::Semi);
+    for (i, &(statement, terminated)) in statements.iter().enumerate() {
+        if i > 0 {
+            if trim_spaces(statement).is_empty() {
+                continue;
+            }
+            if leading_newlines(statement) >= 2 {
+                w.blank_line();
+            }
+            w.break_line(indent);
+        }
+
+        let mut stages: Vec<Vec<Node>> = split(trim_spaces(statement), &Token::Pipe)
+            .into_iter()
+            .map(|(stage, _)| stage.to_vec())
+            .collect();
+        move_trailing_comments(&mut stages);
+
+        for (k, stage) in stages.iter().enumerate() {
+            // Comments on their own lines before the stage
+            let mut rest = trim_spaces(stage);
+            while let Some(Node::Token(Token::Comment(comment))) = rest.first() {
+                w.break_line(indent);
+                w.word(comment);
+                w.break_line(indent);
+                rest = trim_spaces(&rest[1..]);
+            }
+            if k > 0 {
+                w.break_line(indent);
+                w.word("|");
+                w.pending_space = true;
+            }
+            write_stage(w, rest, indent, k > 0);
+        }
+        if terminated {
+            w.tight(";");
+        }
+    }
+}
+
+/// Move comments written on their own line at the end of a stage to the start of the next
+/// (they describe the next stage, which follows them in the source)
+fn move_trailing_comments(stages: &mut [Vec<Node>]) {
+    for k in 0..stages.len().saturating_sub(1) {
+        let mut moved = Vec::new();
+        loop {
+            let stage = &stages[k];
+            let Some(last) = stage.iter().rposition(|n| !is_space(n)) else {
+                break;
+            };
+            let own_line = last == 0
+                || matches!(stage[last - 1], Node::Token(Token::Space(newlines)) if newlines > 0);
+            if !matches!(stage[last], Node::Token(Token::Comment(_))) || !own_line {
+                break;
+            }
+            let comment = stages[k].remove(last);
+            stages[k].truncate(last);
+            moved.insert(0, comment);
+        }
+        if !moved.is_empty() {
+            moved.push(Node::Token(Token::Space(1)));
+            moved.append(&mut stages[k + 1]);
+            stages[k + 1] = moved;
+        }
+    }
+}
+
+fn write_stage(w: &mut Writer, nodes: &[Node], indent: usize, piped: bool) {
+    if !write_aligned_list(w, nodes, indent, piped) {
+        write_nodes(w, nodes, indent + INDENT);
+    }
+}
+
+fn write_nodes(w: &mut Writer, nodes: &[Node], continuation: usize) {
+    let mut after_newline = false;
+    for node in nodes {
+        match node {
+            Node::Token(Token::Space(newlines)) => {
+                w.pending_space = true;
+                after_newline = *newlines > 0;
+                continue;
+            }
+            Node::Token(Token::Comment(comment)) => {
+                w.comment(comment, after_newline, continuation, continuation);
+            }
+            Node::Token(Token::Comma) => {
+                w.tight(",");
+                w.pending_space = true;
+            }
+            Node::Token(Token::Word(text)) | Node::Token(Token::Str(text)) => w.word(text),
+            Node::Token(Token::Pipe) => w.word("|"),
+            Node::Token(Token::Semi) => w.tight(";"),
+            Node::Token(Token::Open(c)) | Node::Token(Token::Close(c)) => w.word(&c.to_string()),
+            Node::Group {
+                open,
+                close,
+                children,
+            } => write_group(w, *open, *close, children, continuation),
+        }
+        after_newline = false;
+    }
+}
+
+fn write_group(w: &mut Writer, open: char, close: Option<char>, children: &[Node], indent: usize) {
+    let children = trim_spaces(children);
+    w.word(&open.to_string());
+    let inline = (!contains_comment(children)).then(|| inline(children));
+    match inline {
+        Some(text) if !has_structure(children) || text.len() <= MAX_INLINE_SUBQUERY => {
+            if !text.is_empty() {
+                w.tight(&text);
+            }
+        }
+        _ if has_structure(children) => {
+            // Subquery: one stage per line, indented, with the closing bracket on its own line
+            w.break_line(indent);
+            write_sequence(w, children, indent);
+            if close.is_some() {
+                w.break_line(indent);
+            }
+        }
+        _ => {
+            w.pending_space = false;
+            write_nodes(w, children, indent);
+        }
+    }
+    if let Some(close) = close {
+        w.tight(&close.to_string());
+    }
+}
+
+/// Render nodes without comments on a single line (subqueries get ` | ` between stages)
+fn inline(nodes: &[Node]) -> String {
+    let mut w = Writer::default();
+    for (i, &(statement, terminated)) in split(nodes, &Token::Semi).iter().enumerate() {
+        if i > 0 && !trim_spaces(statement).is_empty() {
+            w.pending_space = true;
+        }
+        for (k, (stage, _)) in split(trim_spaces(statement), &Token::Pipe)
+            .into_iter()
+            .enumerate()
+        {
+            if k > 0 {
+                w.pending_space = true;
+                w.word("|");
+                w.pending_space = true;
+            }
+            write_nodes(&mut w, trim_spaces(stage), 0);
+        }
+        if terminated {
+            w.tight(";");
+        }
+    }
+    w.finish().join(" ")
+}
+
+/// Split a long `project`/`summarize`/... list one item per line, aligned after the operator
+/// Returns false (writing nothing) if the stage isn't such a list or fits on one line.
+fn write_aligned_list(w: &mut Writer, nodes: &[Node], indent: usize, piped: bool) -> bool {
+    let Some(Node::Token(Token::Word(operator))) = nodes.first() else {
+        return false;
+    };
+    let operator_lower = operator.to_ascii_lowercase();
+    if !LIST_OPERATORS.contains(&operator_lower.as_str())
+        || contains_comment(nodes)
+        || nodes.iter().any(|node| match node {
+            Node::Group { children, .. } => has_structure(children),
+            _ => false,
+        })
+    {
+        return false;
+    }
+
+    let prefix = if piped { 2 } else { 0 };
+    if indent + prefix + inline(nodes).len() <= MAX_WIDTH {
+        return false;
+    }
+
+    // summarize: aggregations, then the `by` clause on its own line
+    let body = &nodes[1..];
+    let (items, by) = if operator_lower == "summarize" {
+        match body.iter().position(
+            |n| matches!(n, Node::Token(Token::Word(word)) if word.eq_ignore_ascii_case("by")),
+        ) {
+            Some(at) => (&body[..at], Some(&body[at..])),
+            None => (body, None),
+        }
+    } else {
+        (body, None)
+    };
+    let items: Vec<String> = split(items, &Token::Comma)
+        .into_iter()
+        .map(|(item, _)| inline(trim_spaces(item)))
+        .filter(|item| !item.is_empty())
+        .collect();
+    if items.len() < 2 {
+        return false;
+    }
+
+    let align = indent + prefix + operator.len() + 1;
+    w.word(operator);
+    w.pending_space = true;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            w.tight(",");
+            w.break_line(align);
+        }
+        w.word(item);
+    }
+    if let Some(by) = by {
+        w.break_line(align);
+        w.word(&inline(trim_spaces(by)));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_pipes_lists_and_subqueries() {
+        let query = "// Failed sign-ins\nlet threshold = 5;\n\nSigninLogs|where ResultType != 0 and UserPrincipalName has '|' // failures\n// noisy users\n| summarize Failures = count(), Apps = make_set(AppDisplayName), IPs = make_set(IPAddress) by UserPrincipalName, bin(TimeGenerated, 1h) | join kind=inner (AADNonInteractiveUserSignInLogs | where ResultType == 0 | project UserPrincipalName, TimeGenerated, IPAddress, AppDisplayName) on UserPrincipalName | where Failures > threshold";
+        let expected = "\
+// Failed sign-ins
+let threshold = 5;
+
+SigninLogs
+| where ResultType != 0 and UserPrincipalName has '|' // failures
+// noisy users
+| summarize Failures = count(),
+            Apps = make_set(AppDisplayName),
+            IPs = make_set(IPAddress)
+            by UserPrincipalName, bin(TimeGenerated, 1h)
+| join kind=inner (
+    AADNonInteractiveUserSignInLogs
+    | where ResultType == 0
+    | project UserPrincipalName, TimeGenerated, IPAddress, AppDisplayName
+    ) on UserPrincipalName
+| where Failures > threshold";
+        assert_eq!(format(query), expected);
+        // Formatting formatted text changes nothing
+        assert_eq!(format(expected), expected);
+
+        assert_eq!(
+            format("T | extend x = toscalar(U | count) | where a in ('x','y')"),
+            "T\n| extend x = toscalar(U | count)\n| where a in ('x', 'y')"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_strings_and_comments() {
+        let query = "T | where Path startswith @'C:\\temp\\' | where Msg == 'a    b' // keep  this\n| where Q == \"say \\\"hi  there\\\"\" and R == @\"D:\\x\\\" | extend S = ```multi   line``` | project X";
+        let literals: Vec<String> = tokenize(query)
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Str(text) | Token::Comment(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            literals,
+            [
+                "'C:\\temp\\'",
+                "'a    b'",
+                "// keep  this",
+                "\"say \\\"hi  there\\\"\"",
+                "\"D:\\x\\\"",
+                "```multi   line```",
+            ]
+        );
+        let formatted = format(query);
+        for literal in &literals {
+            assert!(formatted.contains(literal.as_str()), "{} lost", literal);
+        }
+        assert!(formatted.contains("@'C:\\temp\\'"));
+        assert_eq!(format(&formatted), formatted);
+    }
+}
@@ -0,0 +1,127 @@
+/// What a span of KQL text is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SegmentKind {
+    /// Anything outside strings and comments
+    Code,
+    /// String literal, quotes included: `'...'`, `"..."`, verbatim `@'...'` or
+    /// multi-line ```` ```...``` ````
+    Str {
+        /// False if the text ends before the closing quote
+        closed: bool,
+    },
+    /// `//` comment, up to (not including) the line break
+    Comment,
+}
+
+/// A span of KQL text, as byte offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Segment {
+    pub kind: SegmentKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Split KQL text into code, string literal and comment spans, in order and covering all
+/// of it
+///
+/// This is the one place that knows KQL's quoting rules; the formatter, the pack linter and
+/// the parameter and cost scanners are built on it.
+pub(crate) fn segments(text: &str) -> Vec<Segment> {
+    let bytes = text.as_bytes();
+    let mut segments = Vec::new();
+    let mut code_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let (kind, end) = match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = text[i..].find('\n').map_or(text.len(), |n| i + n);
+                (SegmentKind::Comment, end)
+            }
+            b'`' if text[i..].starts_with("```") => match text[i + 3..].find("```") {
+                Some(n) => (SegmentKind::Str { closed: true }, i + 3 + n + 3),
+                None => (SegmentKind::Str { closed: false }, text.len()),
+            },
+            quote @ (b'\'' | b'"') => {
+                // Backslashes don't escape in verbatim strings (@'C:\temp\')
+                let verbatim = i > 0 && bytes[i - 1] == b'@';
+                let mut j = i + 1;
+                let mut closed = false;
+                while j < bytes.len() {
+                    if bytes[j] == b'\\' && !verbatim {
+                        j += 2;
+                        continue;
+                    }
+                    j += 1;
+                    if bytes[j - 1] == quote {
+                        closed = true;
+                        break;
+                    }
+                }
+                (SegmentKind::Str { closed }, j.min(bytes.len()))
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if code_start < i {
+            segments.push(Segment {
+                kind: SegmentKind::Code,
+                start: code_start,
+                end: i,
+            });
+        }
+        segments.push(Segment {
+            kind,
+            start: i,
+            end,
+        });
+        i = end;
+        code_start = end;
+    }
+    if code_start < bytes.len() {
+        segments.push(Segment {
+            kind: SegmentKind::Code,
+            start: code_start,
+            end: bytes.len(),
+        });
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(SegmentKind, &str)> {
+        segments(text)
+            .into_iter()
+            .map(|s| (s.kind, &text[s.start..s.end]))
+            .collect()
+    }
+
+    #[test]
+    fn test_segments() {
+        const CLOSED: SegmentKind = SegmentKind::Str { closed: true };
+        assert_eq!(
+            kinds("T | where A == 'it\\'s' // note\n| where P startswith @'C:\\'"),
+            vec![
+                (SegmentKind::Code, "T | where A == "),
+                (CLOSED, "'it\\'s'"),
+                (SegmentKind::Code, " "),
+                (SegmentKind::Comment, "// note"),
+                (SegmentKind::Code, "\n| where P startswith @"),
+                (CLOSED, "'C:\\'"),
+            ]
+        );
+        assert_eq!(
+            kinds("print ```a | b\n// c```, \"x"),
+            vec![
+                (SegmentKind::Code, "print "),
+                (CLOSED, "```a | b\n// c```"),
+                (SegmentKind::Code, ", "),
+                (SegmentKind::Str { closed: false }, "\"x"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Execute KQL queries and query packs across Azure Log Analytics workspaces.
+//!
+//! The `kql-panopticon` binary is a thin front end over this library. The stable API is:
+//!
+//! - [`Client`]: authenticates with the Azure CLI and lists/queries workspaces
+//! - [`QueryJobBuilder`]: runs queries across workspaces, exporting CSV/JSON
+//! - [`QueryPack`]: loads, validates and executes query packs
+//! - [`QueryBackend`]: the Azure calls a run makes, implemented by `Client` and by
+//!   [`backend::FakeBackend`] for tests
+//! - [`Session`]: saved job history, as written by the TUI
+//! - [`KqlPanopticonError`] and [`Result`]
+//!
+//! ```no_run
+//! use kql_panopticon::{Client, QueryPack};
+//!
+//! # async fn run() -> kql_panopticon::Result<()> {
+//! let client = Client::new()?;
+//! let workspaces = client.list_workspaces().await?;
+//!
+//! let pack = QueryPack::load_from_file("packs/failed-auth.yaml".as_ref())?;
+//! pack.validate()?;
+//! for result in pack.execute(&client, &workspaces, None).await? {
+//!     println!("{}: {:?}", result.workspace_name, result.result.map(|s| s.row_count));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The `cli` and `tui` modules, and the `config`, `paths`, `log_buffer`, `recording` and
+//! `time_zone` modules the binary also uses, are hidden from the docs and not covered by
+//! semver guarantees. Everything else is internal.
+
+mod access_check;
+pub mod backend;
+mod blob_storage;
+#[doc(hidden)]
+pub mod cli;
+pub mod client;
+mod cloud;
+#[doc(hidden)]
+pub mod config;
+mod cost;
+mod coverage;
+pub mod error;
+mod file_lock;
+mod fuzzy;
+mod ingestion;
+mod ioc;
+mod kql_format;
+mod kql_lexer;
+#[doc(hidden)]
+pub mod log_buffer;
+mod output_index;
+mod output_sink;
+mod pack_ignore;
+mod pack_lint;
+mod pack_sync;
+mod parquet_export;
+#[doc(hidden)]
+pub mod paths;
+mod post_filter;
+pub mod query_job;
+pub mod query_pack;
+mod query_params;
+#[doc(hidden)]
+pub mod recording;
+mod reexport;
+mod result_search;
+mod retention;
+mod run_manifest;
+mod saved_search;
+mod scheduler;
+pub mod session;
+mod session_archive;
+mod sqlite_export;
+#[doc(hidden)]
+pub mod time_zone;
+mod token;
+#[doc(hidden)]
+pub mod tui;
+mod units;
+mod watch;
+mod webhook;
+pub mod workspace;
+mod workspace_health;
+
+pub use backend::QueryBackend;
+pub use client::Client;
+pub use error::{KqlPanopticonError, Result};
+pub use query_job::{JobSuccess, QueryJobBuilder, QueryJobResult, QuerySettings};
+pub use query_pack::{PackQuery, QueryPack};
+pub use session::Session;
+pub use workspace::Workspace;
@@ -0,0 +1,123 @@
+use log::{Level, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+/// Log records kept in memory for the TUI's Logs tab (older ones are dropped)
+pub const LOG_BUFFER_CAPACITY: usize = 5000;
+
+/// A log record as shown in the Logs tab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// Position in the log since startup, for fetching only new records
+    pub seq: u64,
+    /// Local time, `HH:MM:SS.mmm`
+    pub time: String,
+    pub level: Level,
+    /// Module that logged the record, e.g. `kql_panopticon::client`
+    pub target: String,
+    pub message: String,
+}
+
+struct Ring {
+    lines: VecDeque<LogLine>,
+    next_seq: u64,
+}
+
+fn ring() -> &'static Mutex<Ring> {
+    static RING: OnceLock<Mutex<Ring>> = OnceLock::new();
+    RING.get_or_init(|| {
+        Mutex::new(Ring {
+            lines: VecDeque::new(),
+            next_seq: 1,
+        })
+    })
+}
+
+/// Append a record to the buffer
+pub fn push(level: Level, target: &str, message: String) {
+    let mut ring = ring().lock().unwrap_or_else(|e| e.into_inner());
+    let seq = ring.next_seq;
+    ring.next_seq += 1;
+    if ring.lines.len() == LOG_BUFFER_CAPACITY {
+        ring.lines.pop_front();
+    }
+    ring.lines.push_back(LogLine {
+        seq,
+        time: crate::time_zone::now().format("%H:%M:%S%.3f").to_string(),
+        level,
+        target: target.to_string(),
+        message,
+    });
+}
+
+/// Records logged after `seq` (all buffered records for 0)
+pub fn since(seq: u64) -> Vec<LogLine> {
+    let ring = ring().lock().unwrap_or_else(|e| e.into_inner());
+    ring.lines
+        .iter()
+        .filter(|line| line.seq > seq)
+        .cloned()
+        .collect()
+}
+
+/// Logger that hands records to another logger and keeps a copy in the buffer
+///
+/// Records are buffered only if the inner logger's filter lets them through, so `RUST_LOG`
+/// controls both the log file and the Logs tab.
+pub struct TeeLogger<L: Log> {
+    inner: L,
+}
+
+impl<L: Log> TeeLogger<L> {
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+}
+
+impl<L: Log> Log for TeeLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+            push(record.level(), record.target(), record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_returns_new_records() {
+        let before = since(0).last().map_or(0, |line| line.seq);
+        push(
+            Level::Warn,
+            "kql_panopticon::client",
+            "throttled".to_string(),
+        );
+        push(
+            Level::Info,
+            "kql_panopticon::token",
+            "token refreshed".to_string(),
+        );
+
+        let new = since(before);
+        let records: Vec<_> = new
+            .iter()
+            .map(|line| (line.level, line.message.as_str()))
+            .collect();
+        assert_eq!(
+            records,
+            [(Level::Warn, "throttled"), (Level::Info, "token refreshed")]
+        );
+        assert!(since(new[1].seq).is_empty());
+    }
+}
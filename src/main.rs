@@ -1,56 +1,227 @@
-mod cli;
-mod client;
-mod error;
-mod query_job;
-mod query_pack;
-mod session;
-mod tui;
-mod workspace;
-
 use clap::Parser;
-use cli::args::{Cli, Commands, PackFormat};
-use client::Client;
-use error::Result;
+use kql_panopticon::cli::args::{Cli, Commands, PackFormat, PacksCommand};
+use kql_panopticon::{cli, config, log_buffer, paths, recording, time_zone, tui, Client, Result};
 use std::fs::OpenOptions;
+use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Variables from ./.env fill in anything not already set in the environment
+    let _ = dotenvy::dotenv();
     let cli = Cli::parse();
+    let data_dir = cli.data_dir;
+    if let Some(dir) = cli.record {
+        recording::set_default_mode(recording::RecordMode::Record(dir));
+    } else if let Some(dir) = cli.replay {
+        recording::set_default_mode(recording::RecordMode::Replay(dir));
+    }
 
     match cli.command {
         None | Some(Commands::Tui) => {
             // Launch TUI (existing behavior)
             initialize_logger_to_file();
-            let client = Client::new()?;
-            tui::run_tui(client).await?;
+            initialize_data_dir(data_dir);
+            let client = Client::from_config(&config::Config::resolve())?;
+            tui::run_tui(client, cli.read_only).await?;
         }
         Some(Commands::RunPack {
             pack,
             workspaces,
+            interactive,
             format,
             json,
+            tags,
             validate_only,
+            refresh_workspaces,
+            resume,
+            estimate_cost,
+            csv_metadata,
+            sqlite,
+            keep_partial,
+            sample,
+            skip_empty,
+            extract_iocs,
+            yes,
         }) => {
             initialize_logger_to_stderr();
-            cli::run_pack::execute(pack, workspaces, format, json, validate_only).await?;
+            initialize_data_dir(data_dir);
+            cli::run_pack::execute(
+                pack,
+                cli::run_pack::RunPackOptions {
+                    workspaces_override: workspaces,
+                    interactive,
+                    format,
+                    json_output: json,
+                    tags,
+                    validate_only,
+                    refresh_workspaces,
+                    resume,
+                    estimate_cost,
+                    csv_metadata,
+                    sqlite,
+                    keep_partial,
+                    sample,
+                    skip_empty,
+                    extract_iocs,
+                    yes,
+                },
+            )
+            .await?;
+        }
+        Some(Commands::RunQuery {
+            query,
+            query_file,
+            workspaces,
+            format,
+            max_rows,
+            refresh_workspaces,
+        }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::run_query::execute(cli::run_query::RunQueryOptions {
+                query,
+                query_file,
+                workspaces,
+                format,
+                max_rows,
+                refresh_workspaces,
+            })
+            .await?;
         }
         Some(Commands::ExportPack {
             session,
             output,
             format,
+            only_successful,
+            query_name_pattern,
+            author,
+            description,
+            version,
         }) => {
             initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
             let pack_format = match format {
                 PackFormat::Yaml => cli::export_pack::PackFormat::Yaml,
                 PackFormat::Json => cli::export_pack::PackFormat::Json,
             };
-            cli::export_pack::execute(session, output, pack_format)?;
+            cli::export_pack::execute(
+                session,
+                output,
+                pack_format,
+                cli::export_pack::ExportPackOptions {
+                    only_successful,
+                    query_name_pattern,
+                    author,
+                    description,
+                    version,
+                },
+            )?;
+        }
+        Some(Commands::ExportSession { session, output }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::session_archive::export(&session, output)?;
+        }
+        Some(Commands::ImportSession {
+            archive,
+            output_folder,
+            name,
+        }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::session_archive::import(&archive, &output_folder, name.as_deref())?;
+        }
+        Some(Commands::Coverage { session, output }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::coverage::execute(session, output)?;
+        }
+        Some(Commands::Clean {
+            max_age_days,
+            max_sessions,
+            max_output_mb,
+            output_folder,
+            dry_run,
+        }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            let mut policy = config::Config::load()?.retention;
+            policy.max_session_age_days = max_age_days.or(policy.max_session_age_days);
+            policy.max_sessions = max_sessions.or(policy.max_sessions);
+            policy.max_output_mb = max_output_mb.or(policy.max_output_mb);
+            cli::clean::execute(&policy, &output_folder, dry_run)?;
+        }
+        Some(Commands::Search {
+            pattern,
+            regex,
+            session,
+            output_folder,
+            show_lines,
+        }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::search::execute(
+                &pattern,
+                regex,
+                session.as_deref(),
+                &output_folder,
+                show_lines,
+            )?;
+        }
+        Some(Commands::Test {
+            path,
+            online,
+            workspace,
+            refresh_workspaces,
+        }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::test_packs::execute(cli::test_packs::TestPacksOptions {
+                path,
+                online,
+                workspace,
+                refresh_workspaces,
+            })
+            .await?;
+        }
+        Some(Commands::CheckAccess {
+            refresh_workspaces,
+            json,
+        }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            cli::check_access::execute(cli::check_access::CheckAccessOptions {
+                refresh_workspaces,
+                json,
+            })
+            .await?;
+        }
+        Some(Commands::Packs { command }) => {
+            initialize_logger_to_stderr();
+            initialize_data_dir(data_dir);
+            match command {
+                PacksCommand::Pull => cli::packs::pull().await?,
+                PacksCommand::Push { message } => cli::packs::push(message).await?,
+            }
         }
     }
 
     Ok(())
 }
 
+/// Apply --data-dir, or the config file's `data_dir`, before any storage paths are used
+/// The configured time zone is applied here too, before anything is timestamped.
+fn initialize_data_dir(cli_data_dir: Option<PathBuf>) {
+    if let Some(dir) = cli_data_dir {
+        paths::set_data_dir(dir);
+    }
+    let config = config::Config::resolve();
+    if let Some(dir) = &config.data_dir {
+        paths::set_storage_dir(dir.clone());
+    }
+    time_zone::set(config.output_time_zone());
+}
+
 fn initialize_logger_to_file() {
     let log_file = OpenOptions::new()
         .create(true)
@@ -58,9 +229,14 @@ fn initialize_logger_to_file() {
         .open("kql-panopticon.log")
         .expect("Failed to open log file");
 
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .target(env_logger::Target::Pipe(Box::new(log_file)))
-        .init();
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+            .target(env_logger::Target::Pipe(Box::new(log_file)))
+            .build();
+    // Records also go to the in-memory buffer shown in the Logs tab
+    log::set_max_level(logger.filter());
+    log::set_boxed_logger(Box::new(log_buffer::TeeLogger::new(logger)))
+        .expect("Failed to install logger");
 }
 
 fn initialize_logger_to_stderr() {
@@ -0,0 +1,130 @@
+use crate::error::Result;
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File name of the index, directly under the output folder
+pub const INDEX_FILE: &str = "index.json";
+
+/// Serializes index updates from concurrent jobs in this process
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// The workspace whose results are under an output folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub resource_id: String,
+    pub workspace_id: String,
+    pub name: String,
+    pub subscription_id: String,
+    pub subscription_name: String,
+}
+
+impl From<&Workspace> for IndexEntry {
+    fn from(workspace: &Workspace) -> Self {
+        Self {
+            resource_id: workspace.resource_id.clone(),
+            workspace_id: workspace.workspace_id.clone(),
+            name: workspace.name.clone(),
+            subscription_id: workspace.subscription_id.clone(),
+            subscription_name: workspace.subscription_name.clone(),
+        }
+    }
+}
+
+/// Maps workspace folders under an output folder back to their workspaces
+/// (`<output_folder>/index.json`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutputIndex {
+    /// Workspace folder relative to the output folder (`<subscription>/<workspace>`, with `/`)
+    pub folders: BTreeMap<String, IndexEntry>,
+}
+
+impl OutputIndex {
+    pub fn path(output_folder: &Path) -> PathBuf {
+        output_folder.join(INDEX_FILE)
+    }
+
+    /// Load the index (empty if missing or unreadable)
+    pub fn load(output_folder: &Path) -> Self {
+        std::fs::read_to_string(Self::path(output_folder))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Add the workspace's folder to the index, if it isn't already there
+    /// The index is rewritten through a temporary file, so readers never see half of it.
+    pub fn record(output_folder: &Path, workspace: &Workspace) -> Result<()> {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let key = workspace.output_folder_key();
+        let entry = IndexEntry::from(workspace);
+
+        let mut index = Self::load(output_folder);
+        if index.folders.get(&key) == Some(&entry) {
+            return Ok(());
+        }
+        index.folders.insert(key, entry);
+
+        std::fs::create_dir_all(output_folder)?;
+        let path = Self::path(output_folder);
+        let temp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&temp, serde_json::to_string_pretty(&index)?)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    /// The workspace a result file or run folder under the output folder belongs to
+    pub fn lookup(&self, output_folder: &Path, path: &Path) -> Option<&IndexEntry> {
+        let relative = path.strip_prefix(output_folder).ok()?;
+        let mut components = relative.components();
+        let subscription = components.next()?.as_os_str().to_str()?;
+        let workspace = components.next()?.as_os_str().to_str()?;
+        self.folders.get(&format!("{}/{}", subscription, workspace))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: &str, subscription_id: &str) -> Workspace {
+        Workspace {
+            workspace_id: id.to_string(),
+            resource_id: format!(
+                "/subscriptions/{}/resourceGroups/rg/providers/Microsoft.OperationalInsights/workspaces/Sentinel",
+                subscription_id
+            ),
+            subscription_id: subscription_id.to_string(),
+            subscription_name: "Prod (EU)".to_string(),
+            ..Workspace::fixture("Sentinel")
+        }
+    }
+
+    #[test]
+    fn test_same_names_get_separate_folders() {
+        let dir = std::env::temp_dir().join(format!("kql-output-index-{}", std::process::id()));
+        let eu = workspace("1A2B3C4D-0000-0000-0000-000000000000", "sub-eu");
+        let us = workspace("9f8e7d6c-0000-0000-0000-000000000000", "sub-us");
+
+        assert_eq!(eu.output_folder_key(), "prod__eu_/sentinel_1a2b3c4d");
+        assert_ne!(eu.output_dir(&dir), us.output_dir(&dir));
+
+        OutputIndex::record(&dir, &eu).unwrap();
+        OutputIndex::record(&dir, &us).unwrap();
+        OutputIndex::record(&dir, &eu).unwrap();
+
+        let index = OutputIndex::load(&dir);
+        assert_eq!(index.folders.len(), 2);
+        let file = us
+            .output_dir(&dir)
+            .join("2024-05-01_10-00-00")
+            .join("hunt.csv");
+        let entry = index.lookup(&dir, &file).unwrap();
+        assert_eq!(entry.resource_id, us.resource_id);
+        assert!(index.lookup(&dir, &dir.join("other/sentinel")).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,647 @@
+use crate::client::Column;
+use crate::error::Result;
+use crate::parquet_export::ParquetWriter;
+use crate::query_job::{format_csv_value, row_object, RowSink};
+use crate::workspace::Workspace;
+use log::warn;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The job a sink writes rows for
+#[derive(Debug, Clone)]
+pub(crate) struct SinkJob {
+    pub workspace: Workspace,
+    pub query: String,
+    /// Run timestamp, shared by every job started together
+    pub timestamp: String,
+    pub max_rows: Option<usize>,
+}
+
+/// Counts and flags for the rows a job fetched
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExportSummary {
+    pub row_count: usize,
+    pub page_count: usize,
+    /// Output stopped early (row limit, or a pagination failure with partial results kept)
+    pub truncated: bool,
+    /// Errors the service reported alongside partial results
+    pub warnings: Vec<String>,
+}
+
+/// A file a sink wrote
+#[derive(Debug, Clone)]
+pub(crate) struct SinkOutput {
+    pub path: PathBuf,
+    pub file_size: u64,
+    /// The file also holds other runs (e.g. a SQLite database), so its size only counts when
+    /// it is the job's only output
+    pub shared: bool,
+}
+
+/// Destination for a job's result rows
+///
+/// The job runs the query and follows pagination once, handing every page to each of its
+/// sinks, so a new format or destination only has to implement this.
+pub(crate) trait OutputSink: Send {
+    /// Write one page of rows, already cut to the job's row limit
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()>;
+
+    /// Complete the output once every page is in (the file written, if there is one)
+    /// May block, so jobs call it off the async runtime.
+    fn finish(self: Box<Self>, summary: &ExportSummary) -> Result<Option<SinkOutput>>;
+
+    /// Keep what was written before pagination failed
+    /// Returns where the rows went, e.g. `saved to hunt.partial.csv`. Sinks that stream rows
+    /// elsewhere as pages arrive can't take them back, so they only report what was sent.
+    fn save_partial(self: Box<Self>, summary: &ExportSummary) -> Result<String>;
+
+    /// Remove anything half-written after an error
+    fn discard(self: Box<Self>) {}
+}
+
+impl SinkJob {
+    /// Metadata block describing the output (shared by JSON export and CSV sidecars)
+    fn metadata(&self, summary: &ExportSummary) -> Value {
+        let mut metadata = serde_json::json!({
+            "workspace": self.workspace.name,
+            "workspace_id": self.workspace.workspace_id,
+            "subscription": self.workspace.subscription_name,
+            "tenant_id": self.workspace.tenant_id,
+            "delegated": self.workspace.delegated,
+            "timestamp": self.timestamp,
+            "query": self.query,
+            "row_count": summary.row_count,
+            "page_count": summary.page_count,
+            "truncated": summary.truncated,
+            "max_rows": self.max_rows,
+        });
+        if !summary.warnings.is_empty() {
+            metadata["warnings"] = serde_json::json!(summary.warnings);
+        }
+        metadata
+    }
+}
+
+/// Generate a unique temp file path to avoid collisions during concurrent executions
+fn generate_unique_temp_path(base_path: &Path, extension: &str) -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%3f");
+    let pid = std::process::id();
+
+    // Create unique temp filename: base.tmp.{timestamp}_{pid}.{extension}
+    let mut temp_path = base_path.to_path_buf();
+    let temp_filename = format!(
+        "{}.tmp.{}_{}.{}",
+        base_path.file_stem().unwrap_or_default().to_string_lossy(),
+        timestamp,
+        pid,
+        extension
+    );
+    temp_path.set_file_name(temp_filename);
+    temp_path
+}
+
+/// Describe table columns as `{name, type}` objects
+fn columns_json(columns: &[Column]) -> Vec<Value> {
+    columns
+        .iter()
+        .map(|col| {
+            serde_json::json!({
+                "name": col.name,
+                "type": col.column_type,
+            })
+        })
+        .collect()
+}
+
+/// Flush a buffered temp file and make sure it is on disk
+fn close(file: BufWriter<File>) -> Result<()> {
+    let file = file.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn remove_temp(path: &Path) {
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Read back the rows a JSON sink buffered as NDJSON
+fn read_temp_rows(path: &Path) -> Result<Vec<Value>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Writes rows to a temporary CSV file, moved into place when the job finishes
+pub(crate) struct CsvSink {
+    job: SinkJob,
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: BufWriter<File>,
+    columns: Vec<Column>,
+    /// Write a `<name>.meta.json` sidecar, since CSV has nowhere to carry provenance
+    sidecar: bool,
+}
+
+impl CsvSink {
+    pub fn create(job: SinkJob, path: PathBuf, columns: &[Column], sidecar: bool) -> Result<Self> {
+        let temp_path = generate_unique_temp_path(&path, "csv");
+        let mut file = BufWriter::new(File::create(&temp_path)?);
+        let headers: Vec<&str> = columns.iter().map(|col| col.name.as_str()).collect();
+        writeln!(file, "{}", headers.join(","))?;
+        Ok(Self {
+            job,
+            path,
+            temp_path,
+            file,
+            columns: columns.to_vec(),
+            sidecar,
+        })
+    }
+
+    fn complete(self, summary: &ExportSummary) -> Result<SinkOutput> {
+        close(self.file)?;
+        fs::rename(&self.temp_path, &self.path)?;
+        if self.sidecar {
+            let sidecar = serde_json::json!({
+                "metadata": self.job.metadata(summary),
+                "columns": columns_json(&self.columns),
+            });
+            fs::write(
+                self.path.with_extension("meta.json"),
+                serde_json::to_string_pretty(&sidecar)?,
+            )?;
+        }
+        Ok(SinkOutput {
+            file_size: fs::metadata(&self.path)?.len(),
+            path: self.path,
+            shared: false,
+        })
+    }
+}
+
+impl OutputSink for CsvSink {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        for row in rows {
+            let values: Vec<String> = row.iter().map(format_csv_value).collect();
+            writeln!(self.file, "{}", values.join(","))?;
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        let temp_path = self.temp_path.clone();
+        self.complete(summary).map(Some).inspect_err(|_| {
+            remove_temp(&temp_path);
+        })
+    }
+
+    fn save_partial(self: Box<Self>, summary: &ExportSummary) -> Result<String> {
+        close(self.file)?;
+        let partial_path = self.path.with_extension("partial.csv");
+        fs::rename(&self.temp_path, &partial_path)?;
+        warn!(
+            "Saved partial results ({} rows, {} pages) to: {}",
+            summary.row_count,
+            summary.page_count,
+            partial_path.display()
+        );
+        Ok(format!("saved to {}", partial_path.display()))
+    }
+
+    fn discard(self: Box<Self>) {
+        drop(self.file);
+        remove_temp(&self.temp_path);
+    }
+}
+
+/// Buffers rows as NDJSON in a temporary file, then writes them out with metadata and columns
+pub(crate) struct JsonSink {
+    job: SinkJob,
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: BufWriter<File>,
+    columns: Vec<Column>,
+    parse_dynamics: bool,
+}
+
+impl JsonSink {
+    pub fn create(
+        job: SinkJob,
+        path: PathBuf,
+        columns: &[Column],
+        parse_dynamics: bool,
+    ) -> Result<Self> {
+        let temp_path = generate_unique_temp_path(&path, "json");
+        let file = BufWriter::new(File::create(&temp_path)?);
+        Ok(Self {
+            job,
+            path,
+            temp_path,
+            file,
+            columns: columns.to_vec(),
+            parse_dynamics,
+        })
+    }
+
+    fn complete(self, summary: &ExportSummary) -> Result<SinkOutput> {
+        close(self.file)?;
+        let output = serde_json::json!({
+            "metadata": self.job.metadata(summary),
+            "columns": columns_json(&self.columns),
+            "rows": read_temp_rows(&self.temp_path)?,
+        });
+        fs::write(&self.path, serde_json::to_string_pretty(&output)?)?;
+        fs::remove_file(&self.temp_path)?;
+        Ok(SinkOutput {
+            file_size: fs::metadata(&self.path)?.len(),
+            path: self.path,
+            shared: false,
+        })
+    }
+}
+
+impl OutputSink for JsonSink {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        for row in rows {
+            let row = row_object(&self.columns, row, self.parse_dynamics);
+            serde_json::to_writer(&mut self.file, &row)?;
+            self.file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        let temp_path = self.temp_path.clone();
+        self.complete(summary).map(Some).inspect_err(|_| {
+            remove_temp(&temp_path);
+        })
+    }
+
+    fn save_partial(self: Box<Self>, summary: &ExportSummary) -> Result<String> {
+        close(self.file)?;
+        let workspace = &self.job.workspace;
+        let output = serde_json::json!({
+            "workspace": {
+                "name": workspace.name,
+                "id": workspace.workspace_id,
+                "resource_group": workspace.resource_group,
+                "subscription_id": workspace.subscription_id,
+                "subscription_name": workspace.subscription_name,
+                "tenant_id": workspace.tenant_id,
+                "delegated": workspace.delegated,
+            },
+            "query": self.job.query,
+            "timestamp": self.job.timestamp,
+            "partial": true,
+            "rows_retrieved": summary.row_count,
+            "pages_retrieved": summary.page_count,
+            "note": "This result is incomplete due to pagination failure. Only partial data is available.",
+            "rows": read_temp_rows(&self.temp_path)?,
+        });
+
+        let partial_path = self.path.with_extension("partial.json");
+        fs::write(&partial_path, serde_json::to_string_pretty(&output)?)?;
+        fs::remove_file(&self.temp_path)?;
+        warn!(
+            "Saved partial results ({} rows, {} pages) to: {}",
+            summary.row_count,
+            summary.page_count,
+            partial_path.display()
+        );
+        Ok(format!("saved to {}", partial_path.display()))
+    }
+
+    fn discard(self: Box<Self>) {
+        drop(self.file);
+        remove_temp(&self.temp_path);
+    }
+}
+
+/// Writes rows as NDJSON (one JSON object per line) to a temporary file, moved into place when
+/// the job finishes
+pub(crate) struct NdjsonSink {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: BufWriter<File>,
+    columns: Vec<Column>,
+    parse_dynamics: bool,
+}
+
+impl NdjsonSink {
+    pub fn create(path: PathBuf, columns: &[Column], parse_dynamics: bool) -> Result<Self> {
+        let temp_path = generate_unique_temp_path(&path, "ndjson");
+        let file = BufWriter::new(File::create(&temp_path)?);
+        Ok(Self {
+            path,
+            temp_path,
+            file,
+            columns: columns.to_vec(),
+            parse_dynamics,
+        })
+    }
+}
+
+impl OutputSink for NdjsonSink {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        for row in rows {
+            let row = row_object(&self.columns, row, self.parse_dynamics);
+            serde_json::to_writer(&mut self.file, &row)?;
+            self.file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, _summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        let temp_path = self.temp_path.clone();
+        (|| {
+            close(self.file)?;
+            fs::rename(&self.temp_path, &self.path)?;
+            Ok(Some(SinkOutput {
+                file_size: fs::metadata(&self.path)?.len(),
+                path: self.path,
+                shared: false,
+            }))
+        })()
+        .inspect_err(|_| remove_temp(&temp_path))
+    }
+
+    fn save_partial(self: Box<Self>, summary: &ExportSummary) -> Result<String> {
+        close(self.file)?;
+        let partial_path = self.path.with_extension("partial.ndjson");
+        fs::rename(&self.temp_path, &partial_path)?;
+        warn!(
+            "Saved partial results ({} rows, {} pages) to: {}",
+            summary.row_count,
+            summary.page_count,
+            partial_path.display()
+        );
+        Ok(format!("saved to {}", partial_path.display()))
+    }
+
+    fn discard(self: Box<Self>) {
+        drop(self.file);
+        remove_temp(&self.temp_path);
+    }
+}
+
+/// Writes each page as a Parquet row group in a temporary file, moved into place when the job
+/// finishes
+pub(crate) struct ParquetSink {
+    path: PathBuf,
+    temp_path: PathBuf,
+    writer: ParquetWriter,
+}
+
+impl ParquetSink {
+    pub fn create(path: PathBuf, columns: &[Column]) -> Result<Self> {
+        let temp_path = generate_unique_temp_path(&path, "parquet");
+        let file = BufWriter::new(File::create(&temp_path)?);
+        let writer = ParquetWriter::create(file, columns).inspect_err(|_| {
+            remove_temp(&temp_path);
+        })?;
+        Ok(Self {
+            path,
+            temp_path,
+            writer,
+        })
+    }
+}
+
+impl OutputSink for ParquetSink {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        self.writer.write_rows(rows)
+    }
+
+    fn finish(self: Box<Self>, _summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        let temp_path = self.temp_path.clone();
+        (|| {
+            close(self.writer.finish()?)?;
+            fs::rename(&self.temp_path, &self.path)?;
+            Ok(Some(SinkOutput {
+                file_size: fs::metadata(&self.path)?.len(),
+                path: self.path,
+                shared: false,
+            }))
+        })()
+        .inspect_err(|_| remove_temp(&temp_path))
+    }
+
+    fn save_partial(self: Box<Self>, summary: &ExportSummary) -> Result<String> {
+        close(self.writer.finish()?)?;
+        let partial_path = self.path.with_extension("partial.parquet");
+        fs::rename(&self.temp_path, &partial_path)?;
+        warn!(
+            "Saved partial results ({} rows, {} pages) to: {}",
+            summary.row_count,
+            summary.page_count,
+            partial_path.display()
+        );
+        Ok(format!("saved to {}", partial_path.display()))
+    }
+
+    fn discard(self: Box<Self>) {
+        drop(self.writer);
+        remove_temp(&self.temp_path);
+    }
+}
+
+/// Collects rows and appends them to a SQLite table in one transaction when the job finishes
+/// Nothing is written if pagination fails, so a run never leaves partial rows behind.
+pub(crate) struct SqliteSink {
+    job: SinkJob,
+    db_path: PathBuf,
+    table: String,
+    columns: Vec<Column>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl SqliteSink {
+    pub fn new(job: SinkJob, db_path: PathBuf, table: String, columns: &[Column]) -> Self {
+        Self {
+            job,
+            db_path,
+            table,
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+}
+
+impl OutputSink for SqliteSink {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        self.rows.extend(rows.iter().map(|row| row.to_vec()));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, _summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        crate::sqlite_export::append_rows(
+            &self.db_path,
+            &self.table,
+            &self.job.workspace.name,
+            &self.job.timestamp,
+            &self.columns,
+            &self.rows,
+        )?;
+        Ok(Some(SinkOutput {
+            file_size: fs::metadata(&self.db_path)?.len(),
+            path: self.db_path,
+            shared: true,
+        }))
+    }
+
+    fn save_partial(self: Box<Self>, _summary: &ExportSummary) -> Result<String> {
+        Ok("nothing written to SQLite".to_string())
+    }
+}
+
+/// Hands each page to a caller's [`RowSink`] (e.g. NDJSON on stdout) as it arrives
+pub(crate) struct RowSinkOutput {
+    sink: Arc<dyn RowSink>,
+    workspace: Workspace,
+    columns: Vec<Column>,
+    parse_dynamics: bool,
+}
+
+impl RowSinkOutput {
+    pub fn new(
+        sink: Arc<dyn RowSink>,
+        workspace: Workspace,
+        columns: &[Column],
+        parse_dynamics: bool,
+    ) -> Self {
+        Self {
+            sink,
+            workspace,
+            columns: columns.to_vec(),
+            parse_dynamics,
+        }
+    }
+}
+
+impl OutputSink for RowSinkOutput {
+    fn write_page(&mut self, rows: &[&[Value]]) -> Result<()> {
+        let rows = rows
+            .iter()
+            .map(|row| row_object(&self.columns, row, self.parse_dynamics))
+            .collect();
+        self.sink.write_page(&self.workspace, rows)
+    }
+
+    fn finish(self: Box<Self>, _summary: &ExportSummary) -> Result<Option<SinkOutput>> {
+        Ok(None)
+    }
+
+    fn save_partial(self: Box<Self>, _summary: &ExportSummary) -> Result<String> {
+        Ok("already streamed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    fn job() -> SinkJob {
+        SinkJob {
+            workspace: Workspace::fixture("ws-eu"),
+            query: "SigninLogs".to_string(),
+            timestamp: "2024-01-01_00-00-00".to_string(),
+            max_rows: None,
+        }
+    }
+
+    #[test]
+    fn test_file_sinks() {
+        let dir = std::env::temp_dir().join(format!("kql-sink-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let columns = vec![
+            Column {
+                name: "Account".to_string(),
+                column_type: "string".to_string(),
+            },
+            Column {
+                name: "Details".to_string(),
+                column_type: "dynamic".to_string(),
+            },
+        ];
+        let page = [
+            vec![Value::from("alice"), Value::from("{\"ip\":\"10.0.0.1\"}")],
+            vec![Value::from("bob, jr"), Value::Null],
+        ];
+        let rows: Vec<&[Value]> = page.iter().map(Vec::as_slice).collect();
+        let summary = ExportSummary {
+            row_count: 2,
+            page_count: 1,
+            ..Default::default()
+        };
+
+        let mut sinks: Vec<Box<dyn OutputSink>> = vec![
+            Box::new(CsvSink::create(job(), dir.join("hunt.csv"), &columns, true).unwrap()),
+            Box::new(JsonSink::create(job(), dir.join("hunt.json"), &columns, true).unwrap()),
+            Box::new(NdjsonSink::create(dir.join("hunt.ndjson"), &columns, true).unwrap()),
+            Box::new(ParquetSink::create(dir.join("hunt.parquet"), &columns).unwrap()),
+        ];
+        for sink in sinks.iter_mut() {
+            sink.write_page(&rows).unwrap();
+        }
+        for sink in sinks {
+            assert!(sink.finish(&summary).unwrap().is_some());
+        }
+
+        assert_eq!(
+            fs::read_to_string(dir.join("hunt.csv")).unwrap(),
+            "Account,Details\nalice,\"{\"\"ip\"\":\"\"10.0.0.1\"\"}\"\n\"bob, jr\",\n"
+        );
+        let sidecar: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("hunt.meta.json")).unwrap()).unwrap();
+        assert_eq!(sidecar["metadata"]["row_count"], 2);
+        let json: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("hunt.json")).unwrap()).unwrap();
+        assert_eq!(json["rows"][0]["Details"]["ip"], "10.0.0.1");
+        assert_eq!(json["columns"][1]["type"], "dynamic");
+        assert_eq!(
+            fs::read_to_string(dir.join("hunt.ndjson")).unwrap(),
+            "{\"Account\":\"alice\",\"Details\":{\"ip\":\"10.0.0.1\"}}\n{\"Account\":\"bob, jr\",\"Details\":null}\n"
+        );
+
+        let reader =
+            SerializedFileReader::new(File::open(dir.join("hunt.parquet")).unwrap()).unwrap();
+        let accounts: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_string(0).unwrap().clone())
+            .collect();
+        assert_eq!(accounts, vec!["alice", "bob, jr"]);
+
+        // A failed run keeps its rows under a .partial name and leaves no temp files
+        let mut sink =
+            Box::new(CsvSink::create(job(), dir.join("late.csv"), &columns, false).unwrap());
+        sink.write_page(&rows).unwrap();
+        assert!(sink
+            .save_partial(&summary)
+            .unwrap()
+            .contains("late.partial.csv"));
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains(".tmp.")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
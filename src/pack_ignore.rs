@@ -0,0 +1,116 @@
+use regex::Regex;
+use std::path::Path;
+
+/// Name of the ignore file read from the root of each pack library
+pub const IGNORE_FILE: &str = ".kqlignore";
+
+/// Patterns from a library's `.kqlignore` (gitignore-style, without negation)
+///
+/// - `#` starts a comment; blank lines are skipped
+/// - `*` and `?` match within one path component, `**` matches across components
+/// - A pattern ending in `/` only matches directories (and everything under them)
+/// - A pattern containing `/` is anchored to the library root; otherwise it matches
+///   the file or directory name at any depth
+#[derive(Debug, Default)]
+pub struct PackIgnore {
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug)]
+struct Pattern {
+    regex: Regex,
+    dir_only: bool,
+}
+
+impl PackIgnore {
+    /// Load the ignore file from a library root (empty if there isn't one)
+    pub fn load(library_root: &Path) -> Self {
+        match std::fs::read_to_string(library_root.join(IGNORE_FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse ignore file contents
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let dir_only = line.ends_with('/');
+                let line = line.trim_end_matches('/');
+                let anchored = line.contains('/');
+                let body = glob_to_regex(line.trim_start_matches('/'));
+                let pattern = if anchored {
+                    format!("^{}$", body)
+                } else {
+                    format!("(^|/){}$", body)
+                };
+                match Regex::new(&pattern) {
+                    Ok(regex) => Some(Pattern { regex, dir_only }),
+                    Err(e) => {
+                        log::warn!("Ignoring invalid {} pattern '{}': {}", IGNORE_FILE, line, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Check if a path relative to the library root is ignored
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        self.patterns
+            .iter()
+            .any(|p| (is_dir || !p.dir_only) && p.regex.is_match(&path))
+    }
+}
+
+/// Translate a glob into a regex body
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches zero directories
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_patterns() {
+        let ignore = PackIgnore::parse(
+            "# CI and tooling\nci/\n*.schema.json\n/drafts/**/*.yaml\nrenovate.json\n",
+        );
+
+        assert!(ignore.is_ignored(Path::new("ci"), true));
+        assert!(ignore.is_ignored(Path::new("team/ci"), true));
+        assert!(!ignore.is_ignored(Path::new("ci"), false));
+        assert!(ignore.is_ignored(Path::new("pack.schema.json"), false));
+        assert!(ignore.is_ignored(Path::new("schemas/pack.schema.json"), false));
+        assert!(ignore.is_ignored(Path::new("drafts/a.yaml"), false));
+        assert!(ignore.is_ignored(Path::new("drafts/x/y/a.yaml"), false));
+        assert!(!ignore.is_ignored(Path::new("team/drafts/a.yaml"), false));
+        assert!(ignore.is_ignored(Path::new("renovate.json"), false));
+        assert!(!ignore.is_ignored(Path::new("hunts/failed-logins.yaml"), false));
+    }
+}
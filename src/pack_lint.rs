@@ -0,0 +1,276 @@
+use crate::kql_lexer::SegmentKind;
+use regex::Regex;
+
+/// Tabular operators that may follow a `|`
+const TABULAR_OPERATORS: &[&str] = &[
+    "as",
+    "consume",
+    "count",
+    "distinct",
+    "evaluate",
+    "extend",
+    "facet",
+    "filter",
+    "find",
+    "fork",
+    "getschema",
+    "graph-mark-components",
+    "graph-match",
+    "graph-merge",
+    "graph-shortest-paths",
+    "graph-to-table",
+    "invoke",
+    "join",
+    "limit",
+    "lookup",
+    "make-graph",
+    "make-series",
+    "mv-apply",
+    "mv-expand",
+    "mvexpand",
+    "order",
+    "parse",
+    "parse-kv",
+    "parse-where",
+    "partition",
+    "print",
+    "project",
+    "project-away",
+    "project-keep",
+    "project-rename",
+    "project-reorder",
+    "range",
+    "reduce",
+    "render",
+    "sample",
+    "sample-distinct",
+    "scan",
+    "search",
+    "serialize",
+    "sort",
+    "summarize",
+    "take",
+    "top",
+    "top-hitters",
+    "top-nested",
+    "union",
+    "where",
+];
+
+/// A problem found in query text, with the 1-based line it was found on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Offline checks on KQL query text that catch mistakes before a query reaches Azure
+///
+/// This is not a KQL parser: it checks bracket and string balance, pipe placement and the
+/// operator after each pipe, ignoring string literals and `//` comments.
+pub fn lint_query(query: &str) -> Vec<String> {
+    diagnostics(query).into_iter().map(|d| d.message).collect()
+}
+
+/// Run the `lint_query` checks, keeping the line each problem was found on
+pub fn diagnostics(query: &str) -> Vec<Diagnostic> {
+    let issue = |line: usize, message: String| Diagnostic { line, message };
+    if query.trim().is_empty() {
+        return vec![issue(1, "query is empty".to_string())];
+    }
+
+    let mut issues = Vec::new();
+    let code = match strip_strings_and_comments(query) {
+        Ok(code) => code,
+        Err(line) => {
+            return vec![issue(
+                line,
+                format!("unterminated string literal at line {}", line),
+            )];
+        }
+    };
+
+    // Brackets must close in order
+    let mut open: Vec<(char, usize)> = Vec::new();
+    for &(c, line) in &code {
+        match c {
+            '(' | '[' | '{' => open.push((c, line)),
+            ')' | ']' | '}' => {
+                let expected = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                match open.pop() {
+                    Some((opener, _)) if opener == expected => {}
+                    Some((opener, opened)) => {
+                        issues.push(issue(
+                            line,
+                            format!(
+                                "'{}' at line {} closes '{}' opened at line {}",
+                                c, line, opener, opened
+                            ),
+                        ));
+                        break;
+                    }
+                    None => {
+                        issues.push(issue(line, format!("unmatched '{}' at line {}", c, line)));
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if issues.is_empty() {
+        if let Some(&(opener, line)) = open.first() {
+            issues.push(issue(
+                line,
+                format!("'{}' opened at line {} is never closed", opener, line),
+            ));
+        }
+    }
+
+    // Pipes need an operator on both sides
+    let significant: Vec<&(char, usize)> =
+        code.iter().filter(|(c, _)| !c.is_whitespace()).collect();
+    if let Some(&&(_, line)) = significant.first().filter(|(c, _)| *c == '|') {
+        issues.push(issue(
+            line,
+            format!("query starts with '|' at line {}", line),
+        ));
+    }
+    if let Some(&&(_, line)) = significant.last().filter(|(c, _)| *c == '|') {
+        issues.push(issue(line, format!("query ends with '|' at line {}", line)));
+    }
+    for pair in significant.windows(2) {
+        if pair[0].0 == '|' && pair[1].0 == '|' {
+            issues.push(issue(
+                pair[1].1,
+                format!("empty pipe stage at line {}", pair[1].1),
+            ));
+        }
+    }
+
+    // Each pipe stage starts with a tabular operator (a leading pipe is reported above)
+    let first_pipe = code.iter().position(|(c, _)| !c.is_whitespace());
+    for (idx, &(c, _)) in code.iter().enumerate() {
+        if c != '|' || Some(idx) == first_pipe {
+            continue;
+        }
+        let mut rest = code[idx + 1..]
+            .iter()
+            .skip_while(|(c, _)| c.is_whitespace());
+        let Some(&(_, line)) = rest.clone().next() else {
+            continue;
+        };
+        let word: String = rest
+            .by_ref()
+            .map(|(c, _)| *c)
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        if !word.is_empty() && !TABULAR_OPERATORS.contains(&word.to_ascii_lowercase().as_str()) {
+            issues.push(issue(
+                line,
+                format!("unknown operator '{}' after '|' at line {}", word, line),
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Find template placeholders (`{{name}}` or `${name}`) left in query text
+///
+/// Only the query editor prompts for parameter values; pack runs would send a placeholder to
+/// Azure verbatim.
+pub fn unresolved_placeholders(query: &str) -> Vec<String> {
+    let Ok(placeholder) = Regex::new(r"\{\{\s*[\w.-]+\s*\}\}|\$\{[\w.-]+\}") else {
+        return Vec::new();
+    };
+    let mut found: Vec<String> = placeholder
+        .find_iter(query)
+        .map(|m| m.as_str().to_string())
+        .collect();
+    found.dedup();
+    found
+}
+
+/// Characters outside string literals and comments, with their 1-based line numbers
+/// Returns the line of an unterminated string literal as the error.
+fn strip_strings_and_comments(query: &str) -> Result<Vec<(char, usize)>, usize> {
+    let mut code = Vec::new();
+    let mut line = 1;
+
+    for segment in crate::kql_lexer::segments(query) {
+        let text = &query[segment.start..segment.end];
+        match segment.kind {
+            SegmentKind::Code => {
+                for c in text.chars() {
+                    code.push((c, line));
+                    if c == '\n' {
+                        line += 1;
+                    }
+                }
+                continue;
+            }
+            SegmentKind::Str { closed: false } => return Err(line),
+            // Strings stand in for an operand
+            SegmentKind::Str { closed: true } => code.push(('"', line)),
+            // Comments end before the newline, which is kept with the code for line counting
+            SegmentKind::Comment => {}
+        }
+        line += text.matches('\n').count();
+    }
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_query() {
+        assert!(lint_query("SecurityEvent\n| where EventID == 4625\n| take 10").is_empty());
+        // Brackets and pipes inside strings and comments don't count
+        assert!(lint_query("T | where Msg has \"(|\" // unclosed ( here\n| take 1").is_empty());
+        assert!(lint_query(r#"T | where Path startswith @"C:\" | take 1"#).is_empty());
+        assert!(lint_query("T | where Msg has ```(\n| x```\n| take 1").is_empty());
+
+        assert_eq!(lint_query("  "), vec!["query is empty"]);
+        assert_eq!(
+            lint_query("T\n| where (A == 1"),
+            vec!["'(' opened at line 2 is never closed"]
+        );
+        assert_eq!(
+            lint_query("T | where A in (1, 2]"),
+            vec!["']' at line 1 closes '(' opened at line 1"]
+        );
+        assert_eq!(
+            lint_query("T\n| where A == \"x"),
+            vec!["unterminated string literal at line 2"]
+        );
+        assert_eq!(
+            lint_query("T\n| wehre A == 1\n| project-away B"),
+            vec!["unknown operator 'wehre' after '|' at line 2"]
+        );
+        assert_eq!(
+            lint_query("| T\n|\n| take 1\n|"),
+            vec![
+                "query starts with '|' at line 1",
+                "query ends with '|' at line 4",
+                "empty pipe stage at line 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unresolved_placeholders() {
+        assert_eq!(
+            unresolved_placeholders("T | where Account == '{{ user }}' and Host == \"${host}\""),
+            vec!["{{ user }}", "${host}"]
+        );
+        assert!(unresolved_placeholders("T | extend d = dynamic({\"a\": 1})").is_empty());
+    }
+}
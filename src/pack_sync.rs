@@ -0,0 +1,285 @@
+use crate::error::{KqlPanopticonError, Result};
+use std::fmt;
+use std::path::Path;
+use tokio::process::Command;
+
+/// How a pack file changed in a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+/// A pack file changed by a pull or included in a push
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackChange {
+    pub kind: ChangeKind,
+    /// Path relative to the library root (the new path for renames)
+    pub path: String,
+}
+
+impl fmt::Display for PackChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.kind {
+            ChangeKind::Added => "A",
+            ChangeKind::Modified => "M",
+            ChangeKind::Deleted => "D",
+            ChangeKind::Renamed => "R",
+        };
+        write!(f, "{} {}", marker, self.path)
+    }
+}
+
+/// Pull pack updates from the library's git remote
+///
+/// An empty or missing library is cloned from `remote`. Pulls are fast-forward only, so local
+/// commits that diverge from the remote are reported as an error instead of merged.
+pub async fn pull(library: &Path, remote: Option<&str>) -> Result<Vec<PackChange>> {
+    if !library.join(".git").exists() {
+        let changes = clone(library, remote).await?;
+        exclude_lock_files(library)?;
+        return Ok(changes);
+    }
+    exclude_lock_files(library)?;
+
+    if let Some(remote) = remote {
+        ensure_origin(library, remote).await?;
+    }
+
+    let before = head(library).await;
+    if has_upstream(library).await {
+        git(library, &["pull", "--ff-only"]).await?;
+    } else {
+        git(library, &["pull", "--ff-only", "origin", "HEAD"]).await?;
+    }
+
+    match (before, head(library).await) {
+        (Some(before), Some(after)) if before == after => Ok(Vec::new()),
+        (Some(before), Some(after)) => {
+            let diff = git(
+                library,
+                &["diff", "--name-status", "-M", &before, &after, "--"],
+            )
+            .await?;
+            Ok(parse_name_status(&diff))
+        }
+        _ => list_all(library).await,
+    }
+}
+
+/// Commit all local pack changes and push them to the library's git remote
+/// Returns the changes included in the new commit (empty if there was nothing to commit).
+pub async fn push(library: &Path, remote: Option<&str>, message: &str) -> Result<Vec<PackChange>> {
+    if !library.join(".git").exists() {
+        return Err(KqlPanopticonError::GitCommandFailed(format!(
+            "Pack library {} is not a git repository (run `packs pull` with pack_remote configured first)",
+            library.display()
+        )));
+    }
+
+    if let Some(remote) = remote {
+        ensure_origin(library, remote).await?;
+    }
+
+    exclude_lock_files(library)?;
+    git(library, &["add", "-A"]).await?;
+    let staged = git(library, &["diff", "--cached", "--name-status", "-M"]).await?;
+    let changes = parse_name_status(&staged);
+    if !staged.trim().is_empty() {
+        git(library, &["commit", "-m", message]).await?;
+    }
+
+    if has_upstream(library).await {
+        git(library, &["push"]).await?;
+    } else {
+        git(library, &["push", "-u", "origin", "HEAD"]).await?;
+    }
+
+    Ok(changes)
+}
+
+/// Clone the remote into an empty (or missing) library directory
+async fn clone(library: &Path, remote: Option<&str>) -> Result<Vec<PackChange>> {
+    let remote = remote.ok_or_else(|| {
+        KqlPanopticonError::InvalidConfiguration(
+            "Pack library is not a git repository and no pack_remote is configured".to_string(),
+        )
+    })?;
+
+    let is_empty = match std::fs::read_dir(library) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => true,
+    };
+    if !is_empty {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "Pack library {} already contains files; move them aside or `git init` it before syncing",
+            library.display()
+        )));
+    }
+
+    std::fs::create_dir_all(library)?;
+    git(library, &["clone", remote, "."]).await?;
+    list_all(library).await
+}
+
+/// Keep the advisory locks of open packs (`<pack>.yaml.lock`) out of git
+///
+/// The patterns go in the clone's own `.git/info/exclude`, so the library's `.gitignore`
+/// is left alone and other `*.lock` files are still committed.
+fn exclude_lock_files(library: &Path) -> Result<()> {
+    const PATTERNS: [&str; 3] = ["*.yaml.lock", "*.yml.lock", "*.json.lock"];
+
+    let info = library.join(".git").join("info");
+    let exclude = info.join("exclude");
+    let mut content = std::fs::read_to_string(&exclude).unwrap_or_default();
+    let missing: Vec<&str> = PATTERNS
+        .into_iter()
+        .filter(|pattern| !content.lines().any(|line| line.trim() == *pattern))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("# Locks held by kql-panopticon while a pack is open\n");
+    for pattern in missing {
+        content.push_str(pattern);
+        content.push('\n');
+    }
+    std::fs::create_dir_all(&info)?;
+    std::fs::write(&exclude, content)?;
+    Ok(())
+}
+
+/// Point `origin` at the configured remote, adding it if missing
+async fn ensure_origin(library: &Path, remote: &str) -> Result<()> {
+    match git(library, &["remote", "get-url", "origin"]).await {
+        Ok(url) if url.trim() == remote => Ok(()),
+        Ok(_) => git(library, &["remote", "set-url", "origin", remote])
+            .await
+            .map(|_| ()),
+        Err(_) => git(library, &["remote", "add", "origin", remote])
+            .await
+            .map(|_| ()),
+    }
+}
+
+/// Get the current commit, or None for a repository without commits
+async fn head(library: &Path) -> Option<String> {
+    git(library, &["rev-parse", "HEAD"])
+        .await
+        .ok()
+        .map(|sha| sha.trim().to_string())
+}
+
+/// Check if the current branch tracks a remote branch
+async fn has_upstream(library: &Path) -> bool {
+    git(library, &["rev-parse", "--abbrev-ref", "@{upstream}"])
+        .await
+        .is_ok()
+}
+
+/// List every tracked pack as added (after a clone or first pull)
+async fn list_all(library: &Path) -> Result<Vec<PackChange>> {
+    let files = git(library, &["ls-files"]).await?;
+    Ok(files
+        .lines()
+        .filter(|path| is_pack_file(path))
+        .map(|path| PackChange {
+            kind: ChangeKind::Added,
+            path: path.to_string(),
+        })
+        .collect())
+}
+
+/// Run git in the library directory and return its stdout
+async fn git(library: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(library)
+        .output()
+        .await
+        .map_err(|e| KqlPanopticonError::GitCommandFailed(format!("Failed to run git: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KqlPanopticonError::GitCommandFailed(format!(
+            "git {}: {}",
+            args.first().unwrap_or(&""),
+            stderr.trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `git diff --name-status` output, keeping only pack files
+fn parse_name_status(output: &str) -> Vec<PackChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (status, paths) = line.split_once('\t')?;
+            // Renames and copies list the old path first, then the new one
+            let path = paths.rsplit('\t').next()?;
+            let kind = match status.chars().next()? {
+                'A' | 'C' => ChangeKind::Added,
+                'D' => ChangeKind::Deleted,
+                'R' => ChangeKind::Renamed,
+                _ => ChangeKind::Modified,
+            };
+            is_pack_file(path).then(|| PackChange {
+                kind,
+                path: path.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Check if a path is a query pack or a .kql file referenced by one
+fn is_pack_file(path: &str) -> bool {
+    matches!(
+        Path::new(path).extension().and_then(|ext| ext.to_str()),
+        Some("yaml" | "yml" | "json" | "kql")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclude_lock_files() {
+        let dir = std::env::temp_dir().join(format!("kql-pack-exclude-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(".git/info")).unwrap();
+        std::fs::write(dir.join(".git/info/exclude"), "*.swp").unwrap();
+
+        exclude_lock_files(&dir).unwrap();
+        exclude_lock_files(&dir).unwrap();
+        let exclude = std::fs::read_to_string(dir.join(".git/info/exclude")).unwrap();
+        assert!(exclude.starts_with("*.swp\n"));
+        assert_eq!(exclude.matches("*.yaml.lock").count(), 1);
+        assert!(!exclude.lines().any(|line| line == "*.lock"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_name_status() {
+        let output = "M\tsecurity/failed-auth.yaml\nA\tREADME.md\nR087\told.yaml\tnew/renamed.yml\nD\tgone.json\n";
+        assert_eq!(
+            parse_name_status(output)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "M security/failed-auth.yaml",
+                "R new/renamed.yml",
+                "D gone.json"
+            ]
+        );
+    }
+}
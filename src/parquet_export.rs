@@ -0,0 +1,118 @@
+use crate::client::Column;
+use crate::error::Result;
+use parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::Arc;
+
+/// Writes result rows to a Parquet file, one row group per page
+///
+/// `int`/`long`, `real` and `bool` columns keep their type; everything else (including
+/// `datetime` and `dynamic`) is stored as a UTF-8 string, as it appears in the CSV export.
+pub(crate) struct ParquetWriter {
+    writer: SerializedFileWriter<BufWriter<File>>,
+}
+
+impl ParquetWriter {
+    pub fn create(file: BufWriter<File>, columns: &[Column]) -> Result<Self> {
+        let fields = columns
+            .iter()
+            .map(|column| {
+                let (physical, logical) = parquet_type(&column.column_type);
+                Type::primitive_type_builder(&column.name, physical)
+                    .with_repetition(Repetition::OPTIONAL)
+                    .with_logical_type(logical)
+                    .build()
+                    .map(Arc::new)
+            })
+            .collect::<parquet::errors::Result<Vec<_>>>()?;
+        let schema = Type::group_type_builder("schema")
+            .with_fields(fields)
+            .build()?;
+        let properties = WriterProperties::builder().build();
+        let writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))?;
+        Ok(Self { writer })
+    }
+
+    /// Write a page of rows as one row group
+    pub fn write_rows(&mut self, rows: &[&[Value]]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut row_group = self.writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column()? {
+            let values = rows
+                .iter()
+                .map(|row| row.get(index).unwrap_or(&Value::Null));
+            match column.untyped() {
+                ColumnWriter::Int64ColumnWriter(writer) => {
+                    let (present, definition) = levels(values, Value::as_i64);
+                    writer.write_batch(&present, Some(&definition), None)?;
+                }
+                ColumnWriter::DoubleColumnWriter(writer) => {
+                    let (present, definition) = levels(values, Value::as_f64);
+                    writer.write_batch(&present, Some(&definition), None)?;
+                }
+                ColumnWriter::BoolColumnWriter(writer) => {
+                    let (present, definition) = levels(values, Value::as_bool);
+                    writer.write_batch(&present, Some(&definition), None)?;
+                }
+                ColumnWriter::ByteArrayColumnWriter(writer) => {
+                    let (present, definition) = levels(values, |value| match value {
+                        Value::Null => None,
+                        Value::String(text) => Some(ByteArray::from(text.as_str())),
+                        other => Some(ByteArray::from(other.to_string().into_bytes())),
+                    });
+                    writer.write_batch(&present, Some(&definition), None)?;
+                }
+                _ => unreachable!("schema only has INT64, DOUBLE, BOOLEAN and BYTE_ARRAY columns"),
+            }
+            column.close()?;
+            index += 1;
+        }
+        row_group.close()?;
+        Ok(())
+    }
+
+    /// Write the footer and hand back the file
+    pub fn finish(self) -> Result<BufWriter<File>> {
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+/// Parquet type for a Log Analytics column type
+fn parquet_type(column_type: &str) -> (PhysicalType, Option<LogicalType>) {
+    match column_type {
+        "int" | "long" => (PhysicalType::INT64, None),
+        "real" | "double" => (PhysicalType::DOUBLE, None),
+        "bool" | "boolean" => (PhysicalType::BOOLEAN, None),
+        _ => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+    }
+}
+
+/// A column's non-null values and its definition levels (1 for a value, 0 for null)
+/// Values that don't convert to the column's type are written as null.
+fn levels<'a, T>(
+    values: impl Iterator<Item = &'a Value>,
+    convert: impl Fn(&Value) -> Option<T>,
+) -> (Vec<T>, Vec<i16>) {
+    let mut present = Vec::new();
+    let mut definition = Vec::new();
+    for value in values {
+        match convert(value) {
+            Some(value) => {
+                present.push(value);
+                definition.push(1);
+            }
+            None => definition.push(0),
+        }
+    }
+    (present, definition)
+}
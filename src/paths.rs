@@ -0,0 +1,75 @@
+use crate::error::{KqlPanopticonError, Result};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directory holding config.yaml (set by --data-dir)
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Directory holding sessions, packs, runs and cache (set by --data-dir or config `data_dir`)
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` for both the config file and stored data (the --data-dir flag)
+/// Must be called before anything reads a path; later calls are ignored.
+pub fn set_data_dir(dir: PathBuf) {
+    let dir = expand_home(&dir);
+    let _ = CONFIG_DIR.set(dir.clone());
+    let _ = DATA_DIR.set(dir);
+}
+
+/// Relocate stored data (from the config file's `data_dir`)
+/// Ignored if --data-dir was given, since the flag takes precedence.
+pub fn set_storage_dir(dir: PathBuf) {
+    let _ = DATA_DIR.set(expand_home(&dir));
+}
+
+/// Get the default root directory (~/.kql-panopticon)
+fn default_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or(KqlPanopticonError::HomeDirectoryNotFound)?;
+    Ok(home.join(".kql-panopticon"))
+}
+
+/// Get the directory holding config.yaml
+pub fn config_dir() -> Result<PathBuf> {
+    match CONFIG_DIR.get() {
+        Some(dir) => Ok(dir.clone()),
+        None => default_dir(),
+    }
+}
+
+/// Get the root directory for sessions, packs, runs and cache
+pub fn data_dir() -> Result<PathBuf> {
+    match DATA_DIR.get() {
+        Some(dir) => Ok(dir.clone()),
+        None => config_dir(),
+    }
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Check that a path is relative and has no `..` components, so joining it onto a folder
+/// stays inside that folder
+pub fn is_plain(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Turn a pack, query or workspace name into a lowercase file name stem
+/// Anything but letters, digits, `-` and `_` becomes `-`, and leading or trailing dashes are
+/// dropped.
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_lowercase()
+}
@@ -0,0 +1,434 @@
+use crate::error::{KqlPanopticonError, Result};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// Most recent filter expressions kept in config.yaml
+pub const MAX_SAVED_FILTERS: usize = 20;
+
+/// Comparison operator in a filter condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Contains,
+    NotContains,
+    StartsWith,
+}
+
+/// A single `Column op value` comparison
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    column: String,
+    op: Op,
+    value: String,
+}
+
+/// A local filter over a job's result rows
+///
+/// Conditions are `Column op value` with `==`, `!=`, `<`, `<=`, `>`, `>=`, `contains`,
+/// `!contains` or `startswith`, joined by `and` / `or` (`and` binds tighter). Values can be
+/// quoted with `'` or `"`. `contains` and `startswith` ignore case; comparisons are numeric
+/// when both sides are numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostFilter {
+    /// Alternatives (or), each a list of conditions that must all hold (and)
+    groups: Vec<Vec<Condition>>,
+}
+
+/// Outcome of filtering a result file
+#[derive(Debug, Clone)]
+pub struct FilterOutput {
+    pub path: PathBuf,
+    pub kept: usize,
+    pub total: usize,
+}
+
+impl PostFilter {
+    /// Parse a filter expression, e.g. `Account != 'SYSTEM' and EventID == 4625`
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut groups = vec![Vec::new()];
+        let mut rest = tokens.as_slice();
+
+        loop {
+            let [column, op, value, tail @ ..] = rest else {
+                return Err(invalid(expression, "expected `Column op value`"));
+            };
+            let op = parse_op(op)
+                .ok_or_else(|| invalid(expression, &format!("unknown operator '{}'", op.text)))?;
+            groups.last_mut().unwrap().push(Condition {
+                column: column.text.clone(),
+                op,
+                value: value.text.clone(),
+            });
+
+            match tail {
+                [] => break,
+                [joiner, tail @ ..] if !joiner.quoted => {
+                    match joiner.text.to_ascii_lowercase().as_str() {
+                        "and" => {}
+                        "or" => groups.push(Vec::new()),
+                        other => {
+                            return Err(invalid(
+                                expression,
+                                &format!("expected and/or, got '{}'", other),
+                            ))
+                        }
+                    }
+                    rest = tail;
+                }
+                [joiner, ..] => {
+                    return Err(invalid(
+                        expression,
+                        &format!("expected and/or, got '{}'", joiner.text),
+                    ))
+                }
+            }
+        }
+
+        Ok(Self { groups })
+    }
+
+    /// Column names referenced by the filter
+    fn columns(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().flatten().map(|c| c.column.as_str())
+    }
+
+    /// Check a row, looking up column values with `value_of`
+    fn matches<'a>(&self, value_of: impl Fn(&str) -> Option<&'a str>) -> bool {
+        self.groups.iter().any(|group| {
+            group
+                .iter()
+                .all(|condition| condition.matches(value_of(&condition.column).unwrap_or("")))
+        })
+    }
+
+    /// Filter a CSV or JSON result file into `<stem>.filtered.<ext>` beside it
+    pub fn apply_to_file(&self, path: &Path, expression: &str) -> Result<FilterOutput> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let output_path = path.with_extension(format!("filtered.{}", extension));
+        let content = std::fs::read_to_string(path)?;
+
+        let (filtered, kept, total) = match extension {
+            "csv" => self.filter_csv(&content)?,
+            "json" => self.filter_json(&content, expression)?,
+            _ => {
+                return Err(KqlPanopticonError::InvalidConfiguration(format!(
+                    "Cannot filter {}: only CSV and JSON results are supported",
+                    path.display()
+                )))
+            }
+        };
+
+        std::fs::write(&output_path, filtered)?;
+        Ok(FilterOutput {
+            path: output_path,
+            kept,
+            total,
+        })
+    }
+
+    /// Filter CSV records, keeping the header and the raw text of matching records
+    fn filter_csv(&self, content: &str) -> Result<(String, usize, usize)> {
+        let mut records = split_csv_records(content).into_iter();
+        let Some((header_raw, header)) = records.next() else {
+            return Ok((String::new(), 0, 0));
+        };
+        self.check_columns(&header)?;
+
+        let mut output = format!("{}\n", header_raw);
+        let (mut kept, mut total) = (0, 0);
+        for (raw, fields) in records {
+            total += 1;
+            let matched = self.matches(|column| {
+                header
+                    .iter()
+                    .position(|h| h == column)
+                    .and_then(|idx| fields.get(idx))
+                    .map(String::as_str)
+            });
+            if matched {
+                kept += 1;
+                output.push_str(raw);
+                output.push('\n');
+            }
+        }
+        Ok((output, kept, total))
+    }
+
+    /// Filter the `rows` of a JSON result, recording the filter in its metadata
+    fn filter_json(&self, content: &str, expression: &str) -> Result<(String, usize, usize)> {
+        let mut result: serde_json::Value = serde_json::from_str(content)?;
+        let columns: Vec<String> = result["columns"]
+            .as_array()
+            .map(|columns| {
+                columns
+                    .iter()
+                    .filter_map(|c| c["name"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.check_columns(&columns)?;
+
+        let rows = result["rows"].as_array().cloned().unwrap_or_default();
+        let total = rows.len();
+        let filtered: Vec<serde_json::Value> = rows
+            .into_iter()
+            .filter(|row| {
+                let values: Vec<(String, String)> = self
+                    .columns()
+                    .map(|column| (column.to_string(), json_text(&row[column])))
+                    .collect();
+                self.matches(|column| {
+                    values
+                        .iter()
+                        .find(|(name, _)| name == column)
+                        .map(|(_, value)| value.as_str())
+                })
+            })
+            .collect();
+        let kept = filtered.len();
+
+        result["rows"] = serde_json::Value::Array(filtered);
+        if let Some(metadata) = result["metadata"].as_object_mut() {
+            metadata.insert("row_count".to_string(), kept.into());
+            metadata.insert("post_filter".to_string(), expression.into());
+        }
+        Ok((serde_json::to_string_pretty(&result)?, kept, total))
+    }
+
+    /// Fail early if the filter names a column the result doesn't have
+    fn check_columns(&self, columns: &[String]) -> Result<()> {
+        match self
+            .columns()
+            .find(|c| !columns.iter().any(|name| name == c))
+        {
+            Some(missing) => Err(KqlPanopticonError::InvalidConfiguration(format!(
+                "Unknown column '{}' (available: {})",
+                missing,
+                columns.join(", ")
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Condition {
+    fn matches(&self, actual: &str) -> bool {
+        let ordering = || match (actual.parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(actual.cmp(self.value.as_str())),
+        };
+        let lower = || actual.to_lowercase();
+
+        match self.op {
+            Op::Eq => ordering() == Some(Ordering::Equal),
+            Op::NotEq => ordering() != Some(Ordering::Equal),
+            Op::Lt => ordering() == Some(Ordering::Less),
+            Op::LtEq => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+            Op::Gt => ordering() == Some(Ordering::Greater),
+            Op::GtEq => matches!(ordering(), Some(Ordering::Greater | Ordering::Equal)),
+            Op::Contains => lower().contains(&self.value.to_lowercase()),
+            Op::NotContains => !lower().contains(&self.value.to_lowercase()),
+            Op::StartsWith => lower().starts_with(&self.value.to_lowercase()),
+        }
+    }
+}
+
+/// Remember a filter expression in config.yaml (most recent first)
+pub fn save_filter(expression: &str) -> Result<()> {
+    let mut config = crate::config::Config::load()?;
+    config.post_filters.retain(|f| f != expression);
+    config.post_filters.insert(0, expression.to_string());
+    config.post_filters.truncate(MAX_SAVED_FILTERS);
+    config.save()
+}
+
+/// A token of a filter expression
+#[derive(Debug)]
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+/// Split an expression into words, quoted values and operators
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some(ch) if ch == c => break,
+                    Some(ch) => text.push(ch),
+                    None => return Err(invalid(expression, "unterminated quote")),
+                }
+            }
+            tokens.push(Token { text, quoted: true });
+        } else if "=!<>".contains(c) {
+            let mut text = String::new();
+            while let Some(&ch) = chars.peek() {
+                if !"=!<>".contains(ch) {
+                    break;
+                }
+                text.push(ch);
+                chars.next();
+            }
+            // `!contains` is written as one word
+            if text == "!" {
+                while let Some(&ch) = chars.peek() {
+                    if !ch.is_alphanumeric() {
+                        break;
+                    }
+                    text.push(ch);
+                    chars.next();
+                }
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+            });
+        } else {
+            let mut text = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() || "=!<>'\"".contains(ch) {
+                    break;
+                }
+                text.push(ch);
+                chars.next();
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_op(token: &Token) -> Option<Op> {
+    if token.quoted {
+        return None;
+    }
+    Some(match token.text.to_ascii_lowercase().as_str() {
+        "==" | "=" => Op::Eq,
+        "!=" | "<>" => Op::NotEq,
+        "<" => Op::Lt,
+        "<=" => Op::LtEq,
+        ">" => Op::Gt,
+        ">=" => Op::GtEq,
+        "contains" | "has" => Op::Contains,
+        "!contains" | "!has" => Op::NotContains,
+        "startswith" => Op::StartsWith,
+        _ => return None,
+    })
+}
+
+fn invalid(expression: &str, reason: &str) -> KqlPanopticonError {
+    KqlPanopticonError::InvalidConfiguration(format!("Invalid filter '{}': {}", expression, reason))
+}
+
+/// Text of a JSON value as it would appear in CSV output
+fn json_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Split CSV content into records, returning each record's raw text and unquoted fields
+///
+/// Quoted fields may contain commas, doubled quotes and line breaks. This is the one CSV
+/// reader for output files; the raw text lets callers copy records through unchanged.
+pub(crate) fn split_csv_records(content: &str) -> Vec<(&str, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    let mut chars = content.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek().is_some_and(|(_, next)| *next == '"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                let raw = content[start..idx].trim_end_matches('\r');
+                records.push((raw, std::mem::take(&mut fields)));
+                start = idx + 1;
+            }
+            '\r' if !in_quotes => {}
+            _ => field.push(c),
+        }
+    }
+    if start < content.len() {
+        fields.push(field);
+        records.push((content[start..].trim_end_matches('\r'), fields));
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_match() {
+        let filter =
+            PostFilter::parse("Account != 'SYSTEM' and Count >= 10 or Computer contains dc")
+                .unwrap();
+        let row = |account: &'static str, count: &'static str, computer: &'static str| {
+            move |column: &str| match column {
+                "Account" => Some(account),
+                "Count" => Some(count),
+                "Computer" => Some(computer),
+                _ => None,
+            }
+        };
+
+        assert!(filter.matches(row("alice", "12", "ws01")));
+        assert!(!filter.matches(row("SYSTEM", "12", "ws01")));
+        assert!(!filter.matches(row("alice", "9", "ws01")));
+        assert!(filter.matches(row("SYSTEM", "1", "DC01")));
+
+        assert!(PostFilter::parse("Account").is_err());
+        assert!(PostFilter::parse("Account ~ x").is_err());
+        assert!(PostFilter::parse("Account == 'x").is_err());
+        assert!(PostFilter::parse("A == 1 B == 2").is_err());
+        assert!(PostFilter::parse("Cmd !contains \"-enc\"").is_ok());
+    }
+
+    #[test]
+    fn test_filter_csv() {
+        let content = "Account,Note\nSYSTEM,plain\nalice,\"multi\nline, \"\"quoted\"\"\"\nbob,x\n";
+        let filter = PostFilter::parse("Account != SYSTEM").unwrap();
+        let (output, kept, total) = filter.filter_csv(content).unwrap();
+
+        assert_eq!((kept, total), (2, 3));
+        assert_eq!(
+            output,
+            "Account,Note\nalice,\"multi\nline, \"\"quoted\"\"\"\nbob,x\n"
+        );
+        assert!(PostFilter::parse("Missing == 1")
+            .unwrap()
+            .filter_csv(content)
+            .is_err());
+    }
+}
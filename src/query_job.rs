@@ -1,30 +1,21 @@
-use crate::client::{Client, QueryResponse, Table};
+use crate::backend::QueryBackend;
+use crate::blob_storage::{BlobDestination, BlobStorageConfig, BlobUpload};
+use crate::client::{Column, QueryResponse, QueryStatistics};
+use crate::cloud::CloudEndpoints;
 use crate::error::{KqlPanopticonError, Result};
+use crate::ingestion::{IngestionConfig, IngestionSink};
+use crate::output_index::OutputIndex;
+use crate::output_sink::{
+    CsvSink, ExportSummary, JsonSink, NdjsonSink, OutputSink, ParquetSink, RowSinkOutput, SinkJob,
+    SinkOutput, SqliteSink,
+};
 use crate::workspace::Workspace;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset};
 use log::{debug, info, warn};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
-
-/// Generate a unique temp file path to avoid collisions during concurrent executions
-fn generate_unique_temp_path(base_path: &Path, extension: &str) -> PathBuf {
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%3f");
-    let pid = std::process::id();
-
-    // Create unique temp filename: base.tmp.{timestamp}_{pid}.{extension}
-    let mut temp_path = base_path.to_path_buf();
-    let temp_filename = format!(
-        "{}.tmp.{}_{}.{}",
-        base_path.file_stem().unwrap_or_default().to_string_lossy(),
-        timestamp,
-        pid,
-        extension
-    );
-    temp_path.set_file_name(temp_filename);
-    temp_path
-}
 
 /// Settings for query execution
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -42,8 +33,73 @@ pub struct QuerySettings {
     /// Export results as JSON files
     pub export_json: bool,
 
+    /// Export results as NDJSON files (one JSON object per row)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub export_ndjson: bool,
+
+    /// Export results as Parquet files
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub export_parquet: bool,
+
     /// Parse nested dynamic fields into JSON objects (only affects JSON export)
     pub parse_dynamics: bool,
+
+    /// Query timeout override in seconds (uses the client default if omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Retry count override (uses the client default if omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
+
+    /// Maximum rows to write; pagination stops once reached (unlimited if omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<usize>,
+
+    /// Smoke-test a pack cheaply: append `| take N` to every query and keep at most N rows
+    /// per workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rows: Option<usize>,
+
+    /// Time range sent with the query as an ISO 8601 duration, e.g. `P7D`
+    /// (only the query's own time filters apply if omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timespan: Option<String>,
+
+    /// Run a follow-up query estimating the billed size of the data scanned
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub estimate_cost: bool,
+
+    /// Write a `<job_name>.meta.json` sidecar with query metadata next to each CSV
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub csv_metadata: bool,
+
+    /// SQLite database to append results to, one table per job name
+    /// Relative paths are resolved against `output_folder`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sqlite_path: Option<PathBuf>,
+
+    /// Keep the rows already fetched when pagination fails or times out, instead of failing
+    /// the job; the output is marked truncated and the failure recorded as a warning
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub keep_partial_results: bool,
+
+    /// Write the indicators (IPs, domains, hashes, emails) found in the run's results to
+    /// IOC files once the run finishes
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub extract_iocs: bool,
+
+    /// Don't write output files for a job that returns no rows
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub skip_empty_output: bool,
+
+    /// Blob Storage upload overrides (merged over `blob_storage` in config.yaml)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_storage: Option<BlobStorageConfig>,
+
+    /// Also send the rows to an Event Hub or a Log Analytics custom table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingestion: Option<IngestionConfig>,
 }
 
 impl Default for QuerySettings {
@@ -53,7 +109,22 @@ impl Default for QuerySettings {
             job_name: "query".to_string(),
             export_csv: true,
             export_json: false,
+            export_ndjson: false,
+            export_parquet: false,
             parse_dynamics: true,
+            timeout_secs: None,
+            retry_count: None,
+            max_rows: None,
+            sample_rows: None,
+            timespan: None,
+            estimate_cost: false,
+            csv_metadata: false,
+            sqlite_path: None,
+            keep_partial_results: false,
+            extract_iocs: false,
+            skip_empty_output: false,
+            blob_storage: None,
+            ingestion: None,
         }
     }
 }
@@ -66,7 +137,22 @@ impl QuerySettings {
             job_name: job_name.into(),
             export_csv: true,
             export_json: false,
+            export_ndjson: false,
+            export_parquet: false,
             parse_dynamics: true,
+            timeout_secs: None,
+            retry_count: None,
+            max_rows: None,
+            sample_rows: None,
+            timespan: None,
+            estimate_cost: false,
+            csv_metadata: false,
+            sqlite_path: None,
+            keep_partial_results: false,
+            extract_iocs: false,
+            skip_empty_output: false,
+            blob_storage: None,
+            ingestion: None,
         }
     }
 
@@ -82,9 +168,93 @@ impl QuerySettings {
             job_name: job_name.into(),
             export_csv,
             export_json,
+            export_ndjson: false,
+            export_parquet: false,
             parse_dynamics,
+            timeout_secs: None,
+            retry_count: None,
+            max_rows: None,
+            sample_rows: None,
+            timespan: None,
+            estimate_cost: false,
+            csv_metadata: false,
+            sqlite_path: None,
+            keep_partial_results: false,
+            extract_iocs: false,
+            skip_empty_output: false,
+            blob_storage: None,
+            ingestion: None,
         }
     }
+
+    /// Resolve the effective query timeout, falling back to the client default
+    pub fn effective_timeout<B: QueryBackend>(&self, client: &B) -> Duration {
+        self.timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| client.query_timeout())
+    }
+
+    /// Check if any output file format (CSV, JSON, NDJSON or Parquet) is enabled
+    pub fn exports_files(&self) -> bool {
+        self.export_csv || self.export_json || self.export_ndjson || self.export_parquet
+    }
+
+    /// Rows to keep per workspace: the smaller of `max_rows` and `sample_rows`
+    pub fn row_limit(&self) -> Option<usize> {
+        match (self.max_rows, self.sample_rows) {
+            (Some(max), Some(sample)) => Some(max.min(sample)),
+            (max, sample) => max.or(sample),
+        }
+    }
+
+    /// Resolve the SQLite database path, if SQLite output is enabled
+    pub fn sqlite_db_path(&self) -> Option<PathBuf> {
+        self.sqlite_path
+            .as_ref()
+            .map(|path| self.output_folder.join(crate::paths::expand_home(path)))
+    }
+
+    /// Where results go, for previews: file formats, SQLite, Blob Storage and ingestion
+    pub fn output_summary(&self) -> Vec<String> {
+        let mut outputs = Vec::new();
+        if self.export_csv {
+            outputs.push("CSV".to_string());
+        }
+        if self.export_json {
+            outputs.push("JSON".to_string());
+        }
+        if self.export_ndjson {
+            outputs.push("NDJSON".to_string());
+        }
+        if self.export_parquet {
+            outputs.push("Parquet".to_string());
+        }
+        if let Some(path) = self.sqlite_db_path() {
+            outputs.push(format!("SQLite ({})", path.display()));
+        }
+        let config = crate::config::Config::resolve();
+        let cloud = config
+            .cloud
+            .endpoints()
+            .unwrap_or_else(|_| CloudEndpoints::for_environment(Default::default()));
+        let blob =
+            BlobStorageConfig::resolve(&config.blob_storage, self.blob_storage.as_ref(), &cloud);
+        if let Some(blob) = blob {
+            outputs.push(format!(
+                "Blob Storage ({}/{})",
+                blob.account, blob.container
+            ));
+        }
+        if let Some(ingestion) = &self.ingestion {
+            outputs.push(ingestion.target.describe());
+        }
+        outputs
+    }
+
+    /// Resolve the effective retry count, falling back to the client default
+    pub fn effective_retry_count<B: QueryBackend>(&self, client: &B) -> u32 {
+        self.retry_count.unwrap_or_else(|| client.retry_count())
+    }
 }
 
 /// Result of a single query job execution
@@ -105,8 +275,8 @@ pub struct QueryJobResult {
     /// Time taken to execute
     pub elapsed: Duration,
 
-    /// Timestamp when the job completed
-    pub timestamp: DateTime<Local>,
+    /// Timestamp when the job completed (in the configured time zone)
+    pub timestamp: DateTime<FixedOffset>,
 }
 
 /// Success information for a completed job
@@ -116,14 +286,44 @@ pub struct JobSuccess {
     pub row_count: usize,
 
     /// Number of pages fetched (for paginated queries)
-    #[allow(dead_code)]
     pub page_count: usize,
 
-    /// Output file path
+    /// Output file path (empty when the rows only went to a row sink)
     pub output_path: PathBuf,
 
     /// File size in bytes
     pub file_size: u64,
+
+    /// Whether output stopped early because the max_rows limit was reached
+    #[serde(default)]
+    pub truncated: bool,
+
+    /// Estimated billed bytes scanned (when cost estimation is enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billed_bytes: Option<u64>,
+
+    /// Errors the service reported alongside the results (the results are partial)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+
+    /// Execution statistics (CPU, memory, data scanned) reported by the service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<QueryStatistics>,
+
+    /// Result files uploaded to Blob Storage, with any upload failures
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uploads: Vec<BlobUpload>,
+}
+
+/// Receives result rows as each page arrives, e.g. to stream them to stdout
+pub trait RowSink: Send + Sync {
+    /// Handle one page of rows from a workspace, as objects keyed by column name
+    /// An error fails the job (e.g. stdout was closed).
+    fn write_page(
+        &self,
+        workspace: &Workspace,
+        rows: Vec<serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<()>;
 }
 
 /// Individual query job
@@ -132,360 +332,146 @@ struct QueryJob {
     query: String,
     settings: QuerySettings,
     timestamp: String,
+    row_sink: Option<Arc<dyn RowSink>>,
+    /// Where finished result files are uploaded, if anywhere
+    blob: Option<BlobDestination>,
 }
 
-/// Helper for streaming CSV writes to a temporary file
-struct StreamingCsvWriter {
-    temp_path: PathBuf,
-    file: tokio::fs::File,
-    row_count: usize,
-    page_count: usize,
-    buffer: Vec<String>,
-    buffer_size: usize,
-}
-
-impl StreamingCsvWriter {
-    /// Create a new streaming CSV writer
-    async fn new(temp_path: PathBuf, buffer_size: usize) -> Result<Self> {
-        let file = tokio::fs::File::create(&temp_path).await?;
-        Ok(Self {
-            temp_path,
-            file,
-            row_count: 0,
-            page_count: 0,
-            buffer: Vec::with_capacity(buffer_size),
-            buffer_size,
-        })
-    }
-
-    /// Write CSV header
-    async fn write_header(&mut self, table: &Table) -> Result<()> {
-        let headers: Vec<String> = table.columns.iter().map(|col| col.name.clone()).collect();
-        let header_line = format!("{}\n", headers.join(","));
-        self.file.write_all(header_line.as_bytes()).await?;
-        Ok(())
-    }
-
-    /// Add rows from a page to the buffer
-    fn add_page(&mut self, table: &Table, format_fn: &impl Fn(&serde_json::Value) -> String) {
-        self.page_count += 1;
-        for row in &table.rows {
-            if let Some(row_array) = row.as_array() {
-                let row_strings: Vec<String> = row_array.iter().map(format_fn).collect();
-                self.buffer.push(format!("{}\n", row_strings.join(",")));
-                self.row_count += 1;
-            }
-        }
-    }
-
-    /// Flush buffer to disk if it exceeds buffer_size
-    async fn flush_if_needed(&mut self) -> Result<()> {
-        if self.buffer.len() >= self.buffer_size {
-            self.flush().await?;
+/// Keep the warning from a partial response, once per distinct message
+fn record_warning(warnings: &mut Vec<String>, response: &QueryResponse) {
+    if let Some(warning) = response.warning() {
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
         }
-        Ok(())
-    }
-
-    /// Flush buffer to disk
-    async fn flush(&mut self) -> Result<()> {
-        if !self.buffer.is_empty() {
-            let content = self.buffer.join("");
-            self.file.write_all(content.as_bytes()).await?;
-            self.buffer.clear();
-        }
-        Ok(())
-    }
-
-    /// Finalize the file and move to final location
-    async fn finalize(mut self, final_path: &Path) -> Result<usize> {
-        // Flush any remaining buffered data
-        self.flush().await?;
-
-        // Ensure all data is written to disk
-        self.file.sync_all().await?;
-
-        // Close the file
-        drop(self.file);
-
-        // Move temp file to final location
-        tokio::fs::rename(&self.temp_path, final_path).await?;
-
-        Ok(self.row_count)
-    }
-
-    /// Clean up temp file on error
-    async fn cleanup(self) -> Result<()> {
-        drop(self.file);
-        if self.temp_path.exists() {
-            tokio::fs::remove_file(&self.temp_path).await?;
-        }
-        Ok(())
-    }
-
-    /// Save partial results when pagination fails
-    async fn save_partial(mut self, output_path: &Path) -> Result<(usize, PathBuf)> {
-        // Flush any remaining buffered data
-        self.flush().await?;
-
-        // Ensure all data is written to disk
-        self.file.sync_all().await?;
-
-        // Close the file
-        drop(self.file);
-
-        // Create partial result filename
-        let partial_path = output_path.with_extension("partial.csv");
-
-        // Move temp file to partial location
-        tokio::fs::rename(&self.temp_path, &partial_path).await?;
-
-        warn!(
-            "Saved partial results ({} rows, {} pages) to: {}",
-            self.row_count,
-            self.page_count,
-            partial_path.display()
-        );
-
-        Ok((self.row_count, partial_path))
     }
 }
 
-/// Helper for streaming JSON writes to a temporary file
-struct StreamingJsonWriter {
-    temp_path: PathBuf,
-    file: tokio::fs::File,
-    row_count: usize,
-    page_count: usize,
-    buffer: Vec<serde_json::Value>,
-    buffer_size: usize,
-    table_columns: Option<Vec<crate::client::Column>>,
+/// Turn a result row into an object keyed by column name
+pub(crate) fn row_object(
+    columns: &[crate::client::Column],
+    row: &[serde_json::Value],
     parse_dynamics: bool,
-}
-
-impl StreamingJsonWriter {
-    /// Create a new streaming JSON writer
-    async fn new(temp_path: PathBuf, buffer_size: usize, parse_dynamics: bool) -> Result<Self> {
-        let file = tokio::fs::File::create(&temp_path).await?;
-        Ok(Self {
-            temp_path,
-            file,
-            row_count: 0,
-            page_count: 0,
-            buffer: Vec::with_capacity(buffer_size),
-            buffer_size,
-            table_columns: None,
-            parse_dynamics,
+) -> serde_json::Map<String, serde_json::Value> {
+    columns
+        .iter()
+        .zip(row)
+        .map(|(column, value)| {
+            let value = if parse_dynamics && column.column_type == "dynamic" {
+                parse_dynamic_value(value)
+            } else {
+                value.clone()
+            };
+            (column.name.clone(), value)
         })
-    }
-
-    /// Set table columns (must be called before adding pages)
-    fn set_columns(&mut self, columns: Vec<crate::client::Column>) {
-        self.table_columns = Some(columns);
-    }
-
-    /// Add rows from a page to the buffer
-    fn add_page(&mut self, table: &Table) -> Result<()> {
-        if self.table_columns.is_none() {
-            return Err(KqlPanopticonError::InvalidConfiguration(
-                "Table columns not set before adding page".to_string(),
-            ));
-        }
+        .collect()
+}
 
-        self.page_count += 1;
-        let columns = self.table_columns.as_ref().unwrap();
-
-        for row in &table.rows {
-            if let Some(row_array) = row.as_array() {
-                let mut row_object = serde_json::Map::new();
-                for (idx, value) in row_array.iter().enumerate() {
-                    if let Some(column) = columns.get(idx) {
-                        let processed_value =
-                            if self.parse_dynamics && column.column_type == "dynamic" {
-                                Self::parse_dynamic_value(value)
-                            } else {
-                                value.clone()
-                            };
-                        row_object.insert(column.name.clone(), processed_value);
-                    }
-                }
-                self.buffer.push(serde_json::Value::Object(row_object));
-                self.row_count += 1;
+/// Recursively parse dynamic values that might be JSON strings
+pub(crate) fn parse_dynamic_value(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(s) {
+                parse_dynamic_value(&parsed)
+            } else {
+                value.clone()
             }
         }
-
-        Ok(())
-    }
-
-    /// Flush buffer to disk if it exceeds buffer_size
-    async fn flush_if_needed(&mut self) -> Result<()> {
-        if self.buffer.len() >= self.buffer_size {
-            self.flush().await?;
+        serde_json::Value::Array(arr) => {
+            let processed: Vec<_> = arr.iter().map(parse_dynamic_value).collect();
+            serde_json::Value::Array(processed)
         }
-        Ok(())
-    }
-
-    /// Flush buffer to disk (as newline-delimited JSON)
-    async fn flush(&mut self) -> Result<()> {
-        if !self.buffer.is_empty() {
-            for value in &self.buffer {
-                let line = serde_json::to_string(value)?;
-                self.file.write_all(line.as_bytes()).await?;
-                self.file.write_all(b"\n").await?;
+        serde_json::Value::Object(obj) => {
+            let mut processed = serde_json::Map::new();
+            for (k, v) in obj {
+                processed.insert(k.clone(), parse_dynamic_value(v));
             }
-            self.buffer.clear();
+            serde_json::Value::Object(processed)
         }
-        Ok(())
-    }
-
-    /// Finalize the file and move to final location with metadata
-    async fn finalize(
-        mut self,
-        final_path: &Path,
-        workspace: &Workspace,
-        timestamp: &str,
-        query: &str,
-    ) -> Result<usize> {
-        // Flush any remaining buffered data
-        self.flush().await?;
-
-        // Close the temp file
-        drop(self.file);
-
-        // Read all rows from temp file
-        let temp_content = tokio::fs::read_to_string(&self.temp_path).await?;
-        let rows: Vec<serde_json::Value> = temp_content
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| serde_json::from_str(line).unwrap_or(serde_json::Value::Null))
-            .collect();
-
-        // Build final JSON with metadata
-        let columns = self.table_columns.as_ref().ok_or_else(|| {
-            KqlPanopticonError::InvalidConfiguration("Table columns not set".to_string())
-        })?;
-
-        let output = serde_json::json!({
-            "metadata": {
-                "workspace": workspace.name,
-                "workspace_id": workspace.workspace_id,
-                "subscription": workspace.subscription_name,
-                "timestamp": timestamp,
-                "query": query,
-                "row_count": self.row_count,
-                "page_count": self.page_count,
-            },
-            "columns": columns.iter().map(|col| {
-                serde_json::json!({
-                    "name": col.name,
-                    "type": col.column_type,
-                })
-            }).collect::<Vec<_>>(),
-            "rows": rows,
-        });
-
-        // Write final JSON to destination
-        let json_content = serde_json::to_string_pretty(&output)?;
-        tokio::fs::write(final_path, json_content).await?;
-
-        // Clean up temp file
-        tokio::fs::remove_file(&self.temp_path).await?;
-
-        Ok(self.row_count)
+        _ => value.clone(),
     }
+}
 
-    /// Clean up temp file on error
-    async fn cleanup(self) -> Result<()> {
-        drop(self.file);
-        if self.temp_path.exists() {
-            tokio::fs::remove_file(&self.temp_path).await?;
+/// Format a JSON value for CSV output
+pub(crate) fn format_csv_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => {
+            // Escape quotes and wrap in quotes if needed
+            if s.contains(',') || s.contains('"') || s.contains('\n') {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.clone()
+            }
+        }
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            // Serialize complex types as JSON strings
+            let json_str = value.to_string();
+            format!("\"{}\"", json_str.replace('"', "\"\""))
         }
-        Ok(())
     }
+}
 
-    /// Save partial results when pagination fails
-    async fn save_partial(
-        mut self,
-        output_path: &Path,
-        workspace: &Workspace,
-        timestamp: &str,
-        query: &str,
-    ) -> Result<(usize, PathBuf)> {
-        // Flush any remaining buffered data
-        self.flush().await?;
-
-        // Close the temp file
-        drop(self.file);
-
-        // Read all rows from temp file
-        let temp_content = tokio::fs::read_to_string(&self.temp_path).await?;
-        let rows: Vec<serde_json::Value> = temp_content
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| serde_json::from_str(line).unwrap_or(serde_json::Value::Null))
+/// Write a page to every sink off the async runtime, handing the sinks back
+/// On failure every sink is discarded.
+async fn write_page(
+    mut sinks: Vec<Box<dyn OutputSink>>,
+    rows: Vec<serde_json::Value>,
+) -> Result<Vec<Box<dyn OutputSink>>> {
+    tokio::task::spawn_blocking(move || {
+        let rows: Vec<&[serde_json::Value]> = rows
+            .iter()
+            .filter_map(|row| row.as_array().map(Vec::as_slice))
             .collect();
-
-        // Build partial JSON output with metadata indicating incompleteness
-        let output = serde_json::json!({
-            "workspace": {
-                "name": workspace.name,
-                "id": workspace.workspace_id,
-                "resource_group": workspace.resource_group,
-                "subscription_id": workspace.subscription_id,
-                "subscription_name": workspace.subscription_name,
-            },
-            "query": query,
-            "timestamp": timestamp,
-            "partial": true,
-            "rows_retrieved": self.row_count,
-            "pages_retrieved": self.page_count,
-            "note": "This result is incomplete due to pagination failure. Only partial data is available.",
-            "rows": rows,
-        });
-
-        // Create partial result filename
-        let partial_path = output_path.with_extension("partial.json");
-
-        // Write partial JSON to destination
-        let json_content = serde_json::to_string_pretty(&output)?;
-        tokio::fs::write(&partial_path, json_content).await?;
-
-        // Clean up temp file
-        tokio::fs::remove_file(&self.temp_path).await?;
-
-        warn!(
-            "Saved partial results ({} rows, {} pages) to: {}",
-            self.row_count,
-            self.page_count,
-            partial_path.display()
-        );
-
-        Ok((self.row_count, partial_path))
-    }
-
-    /// Recursively parse dynamic values that might be JSON strings
-    fn parse_dynamic_value(value: &serde_json::Value) -> serde_json::Value {
-        match value {
-            serde_json::Value::String(s) => {
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(s) {
-                    Self::parse_dynamic_value(&parsed)
-                } else {
-                    value.clone()
-                }
-            }
-            serde_json::Value::Array(arr) => {
-                let processed: Vec<_> = arr.iter().map(Self::parse_dynamic_value).collect();
-                serde_json::Value::Array(processed)
+        for sink in sinks.iter_mut() {
+            if let Err(e) = sink.write_page(&rows) {
+                sinks.into_iter().for_each(|sink| sink.discard());
+                return Err(e);
             }
-            serde_json::Value::Object(obj) => {
-                let mut processed = serde_json::Map::new();
-                for (k, v) in obj {
-                    processed.insert(k.clone(), Self::parse_dynamic_value(v));
+        }
+        Ok(sinks)
+    })
+    .await
+    .map_err(|e| KqlPanopticonError::Other(format!("Output task failed: {}", e)))?
+}
+
+/// Finish every sink off the async runtime, returning the files written
+async fn finish(
+    sinks: Vec<Box<dyn OutputSink>>,
+    summary: &ExportSummary,
+) -> Result<Vec<SinkOutput>> {
+    let summary = summary.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut outputs = Vec::new();
+        let mut sinks = sinks.into_iter();
+        while let Some(sink) = sinks.next() {
+            match sink.finish(&summary) {
+                Ok(output) => outputs.extend(output),
+                Err(e) => {
+                    sinks.for_each(|sink| sink.discard());
+                    return Err(e);
                 }
-                serde_json::Value::Object(processed)
             }
-            _ => value.clone(),
         }
-    }
+        Ok(outputs)
+    })
+    .await
+    .map_err(|e| KqlPanopticonError::Other(format!("Output task failed: {}", e)))?
+}
+
+/// Keep what each sink has after pagination failed, describing where the rows went
+async fn save_partial(sinks: Vec<Box<dyn OutputSink>>, summary: &ExportSummary) -> Result<String> {
+    let summary = summary.clone();
+    tokio::task::spawn_blocking(move || {
+        let notes: Vec<String> = sinks
+            .into_iter()
+            .map(|sink| sink.save_partial(&summary))
+            .collect::<Result<_>>()?;
+        Ok(notes.join("; "))
+    })
+    .await
+    .map_err(|e| KqlPanopticonError::Other(format!("Output task failed: {}", e)))?
 }
 
 /// Builder for creating and executing query jobs
@@ -493,6 +479,7 @@ pub struct QueryJobBuilder {
     workspaces: Vec<Workspace>,
     queries: Vec<String>,
     settings: Option<QuerySettings>,
+    row_sink: Option<Arc<dyn RowSink>>,
 }
 
 impl QueryJobBuilder {
@@ -502,6 +489,7 @@ impl QueryJobBuilder {
             workspaces: Vec::new(),
             queries: Vec::new(),
             settings: None,
+            row_sink: None,
         }
     }
 
@@ -523,14 +511,22 @@ impl QueryJobBuilder {
         self
     }
 
+    /// Also send each page of rows to `sink` as it arrives
+    /// With CSV and JSON export off, nothing is written to the output folder.
+    pub fn row_sink(mut self, sink: Arc<dyn RowSink>) -> Self {
+        self.row_sink = Some(sink);
+        self
+    }
+
     /// Generate timestamp string in format: YYYY-MM-DD_HH-MM-SS
     fn generate_timestamp() -> String {
-        let now: DateTime<Local> = Local::now();
-        now.format("%Y-%m-%d_%H-%M-%S").to_string()
+        crate::time_zone::now()
+            .format("%Y-%m-%d_%H-%M-%S")
+            .to_string()
     }
 
     /// Execute all query jobs
-    pub async fn execute(self, client: &Client) -> Result<Vec<QueryJobResult>> {
+    pub async fn execute<B: QueryBackend>(self, client: &B) -> Result<Vec<QueryJobResult>> {
         let settings = self.settings.ok_or_else(|| {
             KqlPanopticonError::InvalidConfiguration("QuerySettings not provided".to_string())
         })?;
@@ -548,6 +544,12 @@ impl QueryJobBuilder {
         }
 
         let timestamp = Self::generate_timestamp();
+        let config = crate::config::Config::resolve();
+        let blob = BlobStorageConfig::resolve(
+            &config.blob_storage,
+            settings.blob_storage.as_ref(),
+            &config.cloud.endpoints()?,
+        );
 
         // Create all jobs (cartesian product of workspaces � queries)
         let mut jobs = Vec::new();
@@ -558,6 +560,8 @@ impl QueryJobBuilder {
                     query: query.clone(),
                     settings: settings.clone(),
                     timestamp: timestamp.clone(),
+                    row_sink: self.row_sink.clone(),
+                    blob: blob.clone(),
                 });
             }
         }
@@ -595,7 +599,7 @@ impl Default for QueryJobBuilder {
 
 impl QueryJob {
     /// Execute this query job
-    async fn execute(self, client: &Client) -> QueryJobResult {
+    async fn execute<B: QueryBackend>(self, client: &B) -> QueryJobResult {
         let start = Instant::now();
 
         debug!(
@@ -631,261 +635,360 @@ impl QueryJob {
             query: self.query.clone(),
             result,
             elapsed,
-            timestamp: Local::now(),
+            timestamp: crate::time_zone::now(),
         }
     }
 
-    /// Execute query and save to configured formats (CSV and/or JSON) with pagination support
-    async fn execute_and_save(&self, client: &Client) -> Result<JobSuccess> {
-        // Build output directory: output_folder/subscription_name/workspace_name/timestamp/
-        let normalized_subscription = Workspace::normalize_name(&self.workspace.subscription_name);
-        let normalized_workspace = Workspace::normalize_name(&self.workspace.name);
-
-        let output_dir = self
-            .settings
-            .output_folder
-            .join(normalized_subscription)
-            .join(normalized_workspace)
-            .join(&self.timestamp);
-
-        // Create directory structure
-        fs::create_dir_all(&output_dir).await?;
-
-        let mut row_count = 0;
-        let mut page_count = 0;
-        let mut total_file_size = 0u64;
-        let mut primary_output_path = None;
-
-        // Export as CSV if enabled
-        if self.settings.export_csv {
-            let csv_path = output_dir.join(format!("{}.csv", self.settings.job_name));
-            let (rows, pages) = self.write_csv_streaming(client, &csv_path).await?;
-            row_count = rows;
-            page_count = pages;
-            let metadata = fs::metadata(&csv_path).await?;
-            total_file_size += metadata.len();
-            if primary_output_path.is_none() {
-                primary_output_path = Some(csv_path);
-            }
-        }
-
-        // Export as JSON if enabled
-        if self.settings.export_json {
-            let json_path = output_dir.join(format!("{}.json", self.settings.job_name));
-            let (rows, pages) = self.write_json_streaming(client, &json_path).await?;
-            row_count = rows;
-            page_count = pages;
-            let metadata = fs::metadata(&json_path).await?;
-            total_file_size += metadata.len();
-            if primary_output_path.is_none() {
-                primary_output_path = Some(json_path);
-            }
+    /// Execute query and write the rows to every configured output, following pagination
+    async fn execute_and_save<B: QueryBackend>(&self, client: &B) -> Result<JobSuccess> {
+        if !self.settings.exports_files()
+            && self.settings.sqlite_db_path().is_none()
+            && self.settings.ingestion.is_none()
+            && self.row_sink.is_none()
+        {
+            return Err(KqlPanopticonError::InvalidConfiguration(
+                "No export format enabled (CSV, JSON, NDJSON, Parquet or SQLite required)"
+                    .to_string(),
+            ));
         }
 
-        let output_path = primary_output_path.ok_or_else(|| {
-            KqlPanopticonError::InvalidConfiguration(
-                "No export format enabled (CSV or JSON required)".to_string(),
-            )
-        })?;
-
-        Ok(JobSuccess {
-            row_count,
-            page_count,
-            output_path,
-            file_size: total_file_size,
-        })
-    }
-
-    /// Write query response to CSV file with streaming and pagination
-    async fn write_csv_streaming(
-        &self,
-        client: &Client,
-        output_path: &Path,
-    ) -> Result<(usize, usize)> {
-        // Create unique temp file path to avoid collisions during concurrent executions
-        let temp_path = generate_unique_temp_path(output_path, "csv");
-
-        // Buffer 100 pages before flushing to disk (adjustable)
-        const PAGE_BUFFER_SIZE: usize = 100;
-
-        let mut writer = StreamingCsvWriter::new(temp_path.clone(), PAGE_BUFFER_SIZE).await?;
-
-        // Execute first query with retry logic
-        let timeout = client.query_timeout();
-        let retry_count = client.retry_count();
+        let timeout = self.settings.effective_timeout(client);
+        let retry_count = self.settings.effective_retry_count(client);
         let mut response = self
             .execute_with_retry(client, timeout, retry_count)
             .await?;
+        let statistics = response.query_statistics();
 
         if response.tables.is_empty() {
-            writer.cleanup().await?;
             return Err(KqlPanopticonError::QueryExecutionFailed(
                 "Query returned no tables".to_string(),
             ));
         }
 
-        // Write header from first table
-        let table = &response.tables[0];
-        writer.write_header(table).await?;
+        let columns = response.tables[0].columns.clone();
+        let mut sinks = self.open_sinks(client, &columns).await?;
+        let limit = self.settings.row_limit().unwrap_or(usize::MAX);
+        let mut summary = ExportSummary::default();
+
+        loop {
+            record_warning(&mut summary.warnings, &response);
+            if let Some(table) = response.tables.first_mut() {
+                summary.page_count += 1;
+                let mut rows = std::mem::take(&mut table.rows);
+                rows.retain(serde_json::Value::is_array);
+                let remaining = limit - summary.row_count;
+                summary.truncated |= rows.len() > remaining;
+                rows.truncate(remaining);
+                summary.row_count += rows.len();
+                sinks = write_page(sinks, rows).await?;
+            }
 
-        // Process first page
-        writer.add_page(table, &|value| self.format_csv_value(value));
-        writer.flush_if_needed().await?;
+            let Some(ref next_link) = response.next_link else {
+                break;
+            };
+            // Stop paginating once the row limit is reached
+            if summary.row_count >= limit {
+                summary.truncated = true;
+                break;
+            }
 
-        // Follow pagination links
-        while let Some(ref next_link) = response.next_link {
-            debug!("Fetching next page: {} rows so far", writer.row_count);
+            debug!("Fetching next page: {} rows so far", summary.row_count);
 
-            let page_future = client.query_next_page(next_link);
             response =
-                match tokio::time::timeout(timeout, page_future).await {
+                match tokio::time::timeout(timeout, client.query_next_page(next_link, timeout))
+                    .await
+                {
                     Ok(Ok(page)) => page,
+                    Ok(Err(e)) if self.settings.keep_partial_results => {
+                        summary.truncated = true;
+                        summary.warnings.push(self.partial_warning(
+                            &format!("failed: {}", e),
+                            summary.page_count,
+                            summary.row_count,
+                        ));
+                        break;
+                    }
+                    Err(_) if self.settings.keep_partial_results => {
+                        summary.truncated = true;
+                        summary.warnings.push(self.partial_warning(
+                            &format!("timed out after {} seconds", timeout.as_secs()),
+                            summary.page_count,
+                            summary.row_count,
+                        ));
+                        break;
+                    }
                     Ok(Err(e)) => {
-                        // Pagination failed, save partial results
-                        let (rows, partial_path) = writer.save_partial(output_path).await?;
+                        let saved = save_partial(sinks, &summary).await?;
                         return Err(KqlPanopticonError::QueryExecutionFailed(format!(
-                            "Pagination failed after {} rows (saved to {}): {}",
-                            rows,
-                            partial_path.display(),
-                            e
+                            "Pagination failed after {} rows ({}): {}",
+                            summary.row_count, saved, e
                         )));
                     }
                     Err(_) => {
-                        // Timeout, save partial results
-                        let (rows, partial_path) = writer.save_partial(output_path).await?;
+                        let saved = save_partial(sinks, &summary).await?;
                         return Err(KqlPanopticonError::QueryExecutionFailed(format!(
-                        "Pagination timed out after {} seconds, {} rows retrieved (saved to {})",
-                        timeout.as_secs(), rows, partial_path.display()
-                    )));
+                            "Pagination timed out after {} seconds, {} rows retrieved ({})",
+                            timeout.as_secs(),
+                            summary.row_count,
+                            saved
+                        )));
                     }
                 };
-
-            if !response.tables.is_empty() {
-                let table = &response.tables[0];
-                writer.add_page(table, &|value| self.format_csv_value(value));
-                writer.flush_if_needed().await?;
-            }
         }
 
-        // Finalize: flush remaining buffer and move to final location
-        let row_count = writer.row_count;
-        let page_count = writer.page_count;
-
-        match writer.finalize(output_path).await {
-            Ok(_) => Ok((row_count, page_count)),
-            Err(e) => {
-                // Try to cleanup temp file on finalization error
-                let _ = tokio::fs::remove_file(&temp_path).await;
-                Err(e)
-            }
+        if summary.truncated && summary.row_count >= limit {
+            warn!(
+                "Row limit reached on workspace '{}': output truncated at {} rows",
+                self.workspace.name, summary.row_count
+            );
         }
-    }
-
-    /// Write query response to JSON file with streaming and pagination
-    async fn write_json_streaming(
-        &self,
-        client: &Client,
-        output_path: &Path,
-    ) -> Result<(usize, usize)> {
-        // Create unique temp file path to avoid collisions during concurrent executions
-        let temp_path = generate_unique_temp_path(output_path, "json");
-
-        // Buffer 100 pages before flushing to disk (adjustable)
-        const PAGE_BUFFER_SIZE: usize = 100;
-
-        let mut writer = StreamingJsonWriter::new(
-            temp_path.clone(),
-            PAGE_BUFFER_SIZE,
-            self.settings.parse_dynamics,
-        )
-        .await?;
-
-        // Execute first query with retry logic
-        let timeout = client.query_timeout();
-        let retry_count = client.retry_count();
-        let mut response = self
-            .execute_with_retry(client, timeout, retry_count)
-            .await?;
-
-        if response.tables.is_empty() {
-            writer.cleanup().await?;
-            return Err(KqlPanopticonError::QueryExecutionFailed(
-                "Query returned no tables".to_string(),
-            ));
+        if !summary.warnings.is_empty() {
+            warn!(
+                "Partial results on workspace '{}': {}",
+                self.workspace.name,
+                summary.warnings.join("; ")
+            );
         }
 
-        // Set columns from first table
-        let table = &response.tables[0];
-        writer.set_columns(table.columns.clone());
-
-        // Process first page
-        writer.add_page(table)?;
-        writer.flush_if_needed().await?;
+        // Nothing to keep for a job without rows when empty output is skipped
+        let outputs = if summary.row_count == 0 && self.settings.skip_empty_output {
+            sinks.into_iter().for_each(|sink| sink.discard());
+            Vec::new()
+        } else {
+            finish(sinks, &summary).await?
+        };
+        let uploads = match &self.blob {
+            Some(destination) => self.upload_outputs(client, destination, &outputs).await,
+            None => Vec::new(),
+        };
+        // The first file written is the job's output; a row sink alone leaves the path empty
+        let output_path = outputs
+            .first()
+            .map(|output| output.path.clone())
+            .unwrap_or_default();
+        let file_size = outputs
+            .iter()
+            .enumerate()
+            .filter(|(i, output)| *i == 0 || !output.shared)
+            .map(|(_, output)| output.file_size)
+            .sum();
+
+        // An estimate that fails shouldn't fail the job
+        let billed_bytes = if self.settings.estimate_cost {
+            match crate::cost::estimate_billed_bytes(
+                client,
+                &self.workspace.workspace_id,
+                &self.query,
+                self.settings.timespan.as_deref(),
+                timeout,
+            )
+            .await
+            {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    warn!(
+                        "Cost estimate failed on workspace '{}': {}",
+                        self.workspace.name, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
-        // Follow pagination links
-        while let Some(ref next_link) = response.next_link {
-            debug!("Fetching next page: {} rows so far", writer.row_count);
+        Ok(JobSuccess {
+            row_count: summary.row_count,
+            page_count: summary.page_count,
+            output_path,
+            file_size,
+            truncated: summary.truncated,
+            billed_bytes,
+            warnings: summary.warnings,
+            statistics,
+            uploads,
+        })
+    }
 
-            let page_future = client.query_next_page(next_link);
-            response = match tokio::time::timeout(timeout, page_future).await {
-                Ok(Ok(page)) => page,
-                Ok(Err(e)) => {
-                    // Pagination failed, save partial results
-                    let (rows, partial_path) = writer
-                        .save_partial(output_path, &self.workspace, &self.timestamp, &self.query)
-                        .await?;
-                    return Err(KqlPanopticonError::QueryExecutionFailed(format!(
-                        "Pagination failed after {} rows (saved to {}): {}",
-                        rows,
-                        partial_path.display(),
-                        e
-                    )));
+    /// Upload the job's result files, keeping the layout under the output folder
+    /// A failed upload is recorded but doesn't fail the job; the local file is still there.
+    async fn upload_outputs<B: QueryBackend>(
+        &self,
+        client: &B,
+        destination: &BlobDestination,
+        outputs: &[SinkOutput],
+    ) -> Vec<BlobUpload> {
+        let mut uploads = Vec::new();
+        // Shared files (the SQLite database) hold other runs and are never uploaded
+        for output in outputs.iter().filter(|output| !output.shared) {
+            let relative = output
+                .path
+                .strip_prefix(&self.settings.output_folder)
+                .unwrap_or_else(|_| {
+                    output
+                        .path
+                        .file_name()
+                        .map(Path::new)
+                        .unwrap_or(&output.path)
+                });
+            let (url, result) = match destination.blob_url(relative) {
+                Ok(url) => {
+                    let result = match fs::read(&output.path).await {
+                        Ok(content) => {
+                            let content_type = crate::blob_storage::content_type(&output.path);
+                            client.upload_blob(&url, content, content_type).await
+                        }
+                        Err(e) => Err(e.into()),
+                    };
+                    (url, result)
                 }
-                Err(_) => {
-                    // Timeout, save partial results
-                    let (rows, partial_path) = writer
-                        .save_partial(output_path, &self.workspace, &self.timestamp, &self.query)
-                        .await?;
-                    return Err(KqlPanopticonError::QueryExecutionFailed(format!(
-                        "Pagination timed out after {} seconds, {} rows retrieved (saved to {})",
-                        timeout.as_secs(),
-                        rows,
-                        partial_path.display()
-                    )));
+                Err(e) => (String::new(), Err(e)),
+            };
+            let error = match result {
+                Ok(()) => {
+                    info!("Uploaded {} to {}", output.path.display(), url);
+                    None
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to upload {} to blob storage: {}",
+                        output.path.display(),
+                        e
+                    );
+                    Some(e.to_string())
                 }
             };
+            uploads.push(BlobUpload {
+                file: output.path.clone(),
+                url,
+                error,
+            });
+        }
+        uploads
+    }
 
-            if !response.tables.is_empty() {
-                let table = &response.tables[0];
-                writer.add_page(table)?;
-                writer.flush_if_needed().await?;
+    /// Open a sink for each configured output, in order of preference for the job's output path
+    async fn open_sinks<B: QueryBackend>(
+        &self,
+        client: &B,
+        columns: &[Column],
+    ) -> Result<Vec<Box<dyn OutputSink>>> {
+        // Build output directory: output_folder/subscription_name/workspace_name_id/timestamp/
+        let output_dir = self
+            .workspace
+            .output_dir(&self.settings.output_folder)
+            .join(&self.timestamp);
+
+        // Create directory structure (SQLite, ingestion and row sink output don't need it)
+        if self.settings.exports_files() {
+            fs::create_dir_all(&output_dir).await?;
+            if let Err(e) = OutputIndex::record(&self.settings.output_folder, &self.workspace) {
+                warn!("Failed to update the output index: {}", e);
             }
         }
 
-        // Finalize: flush remaining buffer, wrap with metadata, and move to final location
-        let row_count = writer.row_count;
-        let page_count = writer.page_count;
-
-        match writer
-            .finalize(output_path, &self.workspace, &self.timestamp, &self.query)
-            .await
-        {
-            Ok(_) => Ok((row_count, page_count)),
-            Err(e) => {
-                // Try to cleanup temp file on finalization error
-                let _ = tokio::fs::remove_file(&temp_path).await;
-                Err(e)
+        let job = SinkJob {
+            workspace: self.workspace.clone(),
+            query: self.query.clone(),
+            timestamp: self.timestamp.clone(),
+            max_rows: self.settings.row_limit(),
+        };
+        let file_path =
+            |extension: &str| output_dir.join(format!("{}.{}", self.settings.job_name, extension));
+
+        let mut sinks: Vec<Box<dyn OutputSink>> = Vec::new();
+        let opened = (|| -> Result<()> {
+            if self.settings.export_csv {
+                sinks.push(Box::new(CsvSink::create(
+                    job.clone(),
+                    file_path("csv"),
+                    columns,
+                    self.settings.csv_metadata,
+                )?));
+            }
+            if self.settings.export_json {
+                sinks.push(Box::new(JsonSink::create(
+                    job.clone(),
+                    file_path("json"),
+                    columns,
+                    self.settings.parse_dynamics,
+                )?));
             }
+            if self.settings.export_ndjson {
+                sinks.push(Box::new(NdjsonSink::create(
+                    file_path("ndjson"),
+                    columns,
+                    self.settings.parse_dynamics,
+                )?));
+            }
+            if self.settings.export_parquet {
+                sinks.push(Box::new(ParquetSink::create(
+                    file_path("parquet"),
+                    columns,
+                )?));
+            }
+            Ok(())
+        })();
+        if let Err(e) = opened {
+            sinks.into_iter().for_each(|sink| sink.discard());
+            return Err(e);
+        }
+
+        if let Some(db_path) = self.settings.sqlite_db_path() {
+            sinks.push(Box::new(SqliteSink::new(
+                job.clone(),
+                db_path,
+                self.settings.job_name.clone(),
+                columns,
+            )));
+        }
+        if let Some(ingestion) = &self.settings.ingestion {
+            sinks.push(Box::new(IngestionSink::start(
+                client.clone(),
+                ingestion.clone(),
+                self.workspace.clone(),
+                columns,
+                self.settings.parse_dynamics,
+            )));
         }
+        if let Some(sink) = &self.row_sink {
+            sinks.push(Box::new(RowSinkOutput::new(
+                sink.clone(),
+                self.workspace.clone(),
+                columns,
+                self.settings.parse_dynamics,
+            )));
+        }
+        Ok(sinks)
+    }
+
+    /// Describe a pagination failure whose partial results are being kept
+    fn partial_warning(&self, failure: &str, pages: usize, rows: usize) -> String {
+        warn!(
+            "Pagination {} on workspace '{}'; keeping {} rows from {} pages",
+            failure, self.workspace.name, rows, pages
+        );
+        format!(
+            "Pagination {} after {} pages ({} rows); partial results kept",
+            failure, pages, rows
+        )
     }
 
     /// Execute query with retry logic and timeout
-    async fn execute_with_retry(
+    async fn execute_with_retry<B: QueryBackend>(
         &self,
-        client: &Client,
+        client: &B,
         timeout: Duration,
         retry_count: u32,
     ) -> Result<QueryResponse> {
+        let query = match self.settings.sample_rows {
+            Some(rows) => format!(
+                "{}\n| take {}",
+                self.query.trim_end().trim_end_matches(';'),
+                rows
+            ),
+            None => self.query.clone(),
+        };
         let mut last_error = None;
         let max_attempts = retry_count + 1; // retry_count of 0 means 1 attempt total
 
@@ -918,10 +1021,32 @@ impl QueryJob {
                 tokio::time::sleep(backoff).await;
             }
 
-            let query_future =
-                client.query_workspace(&self.workspace.workspace_id, &self.query, None);
+            let query_future = client.query_workspace(
+                &self.workspace.workspace_id,
+                &query,
+                self.settings.timespan.as_deref(),
+                timeout,
+            );
             match tokio::time::timeout(timeout, query_future).await {
                 Ok(Ok(response)) => return Ok(response),
+                // TLS, proxy and malformed requests fail the same way on every attempt
+                Ok(Err(KqlPanopticonError::HttpRequestFailed(transport)))
+                    if !transport.is_retryable() =>
+                {
+                    warn!(
+                        "Not retrying query on workspace '{}': {}",
+                        self.workspace.name, transport
+                    );
+                    return Err(KqlPanopticonError::HttpRequestFailed(transport));
+                }
+                // Semantic and syntax errors need the query fixed first
+                Ok(Err(e)) if e.is_query_rejected() => {
+                    warn!(
+                        "Not retrying query on workspace '{}': {}",
+                        self.workspace.name, e
+                    );
+                    return Err(e);
+                }
                 Ok(Err(e)) => {
                     last_error = Some(e);
                 }
@@ -943,26 +1068,88 @@ impl QueryJob {
             ))
         }))
     }
+}
 
-    /// Format a JSON value for CSV output
-    fn format_csv_value(&self, value: &serde_json::Value) -> String {
-        match value {
-            serde_json::Value::Null => String::new(),
-            serde_json::Value::Bool(b) => b.to_string(),
-            serde_json::Value::Number(n) => n.to_string(),
-            serde_json::Value::String(s) => {
-                // Escape quotes and wrap in quotes if needed
-                if s.contains(',') || s.contains('"') || s.contains('\n') {
-                    format!("\"{}\"", s.replace('"', "\"\""))
-                } else {
-                    s.clone()
-                }
-            }
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                // Serialize complex types as JSON strings
-                let json_str = value.to_string();
-                format!("\"{}\"", json_str.replace('"', "\"\""))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{FakeBackend, FakeCall};
+
+    #[tokio::test]
+    async fn test_sample_rows_appends_take() {
+        let output = std::env::temp_dir().join(format!("kql-sample-{}", std::process::id()));
+        let fake = FakeBackend::new();
+        // The fake ignores `take`, so the row limit still has to apply client-side
+        fake.respond(
+            "SigninLogs",
+            FakeBackend::table(
+                &["User"],
+                vec![vec!["a".into()], vec!["b".into()], vec!["c".into()]],
+            ),
+        );
+        let workspace = Workspace::fixture("ws");
+        let mut settings = QuerySettings::new(&output, "sample");
+        settings.max_rows = Some(100);
+        settings.sample_rows = Some(2);
+        assert_eq!(settings.row_limit(), Some(2));
+
+        let results = QueryJobBuilder::new()
+            .workspaces(vec![workspace])
+            .queries(vec!["SigninLogs | where ResultType != 0;\n".to_string()])
+            .settings(settings)
+            .execute(&fake)
+            .await
+            .unwrap();
+        let success = results[0].result.as_ref().unwrap();
+        assert_eq!(success.row_count, 2);
+        assert_eq!(
+            fake.calls()[0],
+            FakeCall::Query {
+                workspace_id: "ws-id".to_string(),
+                query: "SigninLogs | where ResultType != 0\n| take 2".to_string(),
             }
-        }
+        );
+
+        let _ = std::fs::remove_dir_all(&output);
+    }
+
+    #[tokio::test]
+    async fn test_skip_empty_output() {
+        let output = std::env::temp_dir().join(format!("kql-skip-empty-{}", std::process::id()));
+        let fake = FakeBackend::new();
+        fake.respond("SigninLogs", FakeBackend::table(&["User"], vec![]));
+        fake.respond(
+            "AuditLogs",
+            FakeBackend::table(&["User"], vec![vec!["a".into()]]),
+        );
+        let workspace = Workspace::fixture("ws");
+        let mut settings = QuerySettings::new(&output, "hunt");
+        settings.export_json = true;
+        settings.skip_empty_output = true;
+
+        let run = |query: &str| {
+            QueryJobBuilder::new()
+                .workspaces(vec![workspace.clone()])
+                .queries(vec![query.to_string()])
+                .settings(settings.clone())
+                .execute(&fake)
+        };
+        let empty = run("SigninLogs").await.unwrap();
+        let success = empty[0].result.as_ref().unwrap();
+        assert_eq!(success.row_count, 0);
+        assert!(success.output_path.as_os_str().is_empty());
+        let files = walkdir::WalkDir::new(&output)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.file_type().is_file() && entry.file_name() != crate::output_index::INDEX_FILE
+            })
+            .count();
+        assert_eq!(files, 0);
+
+        let hits = run("AuditLogs").await.unwrap();
+        assert!(hits[0].result.as_ref().unwrap().output_path.exists());
+
+        let _ = std::fs::remove_dir_all(&output);
     }
 }
@@ -1,11 +1,35 @@
+use crate::backend::QueryBackend;
+use crate::config::{Config, PackLibrary};
 use crate::error::Result;
-use crate::query_job::QuerySettings;
+use crate::paths;
+use crate::query_job::{QueryJobBuilder, QueryJobResult, QuerySettings};
+use crate::webhook::WebhookConfig;
+use crate::workspace::Workspace;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+/// Name of the personal pack library (`<data_dir>/packs`)
+pub const PERSONAL_LIBRARY: &str = "local";
+
+/// Value of the optional `kind` marker that identifies a file as a query pack
+pub const PACK_KIND: &str = "querypack";
+
+/// Files found while scanning a pack library
+#[derive(Debug, Default)]
+pub struct LibraryScan {
+    /// Files that look like query packs
+    pub packs: Vec<PathBuf>,
+    /// YAML/JSON files that aren't query packs, with the reason each was skipped
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
 /// A query pack containing one or more KQL queries
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryPack {
+    /// Optional marker identifying the file as a query pack (`querypack`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
     /// Pack name
     pub name: String,
 
@@ -21,6 +45,18 @@ pub struct QueryPack {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
 
+    /// Tags applying to every query in the pack (e.g. MITRE tactics)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Lookback window applied as the query timespan, e.g. `7d`, `24h` or `P7D`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_timespan: Option<String>,
+
+    /// How often the pack is meant to be run, e.g. `1h` or `daily` (informational only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recommended_frequency: Option<String>,
+
     /// Single query (for simple packs)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query: Option<String>,
@@ -36,6 +72,33 @@ pub struct QueryPack {
     /// Workspace scope (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workspaces: Option<WorkspaceScope>,
+
+    /// Run notification overrides for this pack (merged over `webhook` in config.yaml)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+
+    /// Ask for explicit confirmation before running (`--yes` in the CLI)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub requires_confirmation: bool,
+
+    /// Changes saved from the TUI, oldest first (recorded when `pack_changelog` is enabled)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changelog: Vec<ChangelogEntry>,
+}
+
+/// A recorded change to a pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    /// When the change was saved (RFC 3339)
+    pub timestamp: String,
+    /// Pack version after the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Name of the query that changed
+    pub query: String,
+    /// Who saved the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
 }
 
 /// A single query within a pack
@@ -46,7 +109,80 @@ pub struct PackQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
+    /// Query text (filled from `query_file` at load time when that is set)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub query: String,
+
+    /// Path to a .kql file holding the query, relative to the pack file (instead of inline `query`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_file: Option<PathBuf>,
+
+    /// Tags for this query, in addition to the pack's tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// MITRE ATT&CK technique IDs the query detects (e.g. T1059, T1021.001)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mitre: Vec<String>,
+
+    /// How urgent a hit from this query is; non-empty results are colored and sorted by it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+
+    /// Query timeout override in seconds (takes precedence over pack settings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Retry count override (takes precedence over pack settings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
+
+    /// Row limit override (takes precedence over pack settings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<usize>,
+}
+
+/// Severity of a pack query's hits, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// Per-workspace execution overrides (takes precedence over query and pack settings)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceOverride {
+    /// Workspace ID or full resource ID
+    pub id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
+}
+
+impl WorkspaceOverride {
+    /// Check if this override applies to the given workspace
+    pub fn matches(&self, workspace: &Workspace) -> bool {
+        self.id == workspace.workspace_id || self.id == workspace.resource_id
+    }
 }
 
 /// Workspace selection scope
@@ -57,39 +193,202 @@ pub enum WorkspaceScope {
     All,
 
     /// Execute on specific workspace IDs
-    Selected { ids: Vec<String> },
+    Selected {
+        ids: Vec<String>,
+        /// Optional per-workspace timeout/retry overrides
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        overrides: Vec<WorkspaceOverride>,
+    },
 
     /// Execute on workspaces matching pattern
     Pattern { pattern: String },
 }
 
 impl QueryPack {
+    /// Build a pack from a list of queries
+    /// Uses the simple single-query format when there is only one query
+    pub fn from_queries(
+        name: String,
+        description: Option<String>,
+        mut queries: Vec<PackQuery>,
+        settings: Option<QuerySettings>,
+    ) -> Self {
+        let (query, queries) = if queries.len() == 1 {
+            (Some(queries.remove(0).query), None)
+        } else {
+            (None, Some(queries))
+        };
+
+        QueryPack {
+            kind: Some(PACK_KIND.to_string()),
+            name,
+            description,
+            author: Some("kql-panopticon".to_string()),
+            version: Some("1.0".to_string()),
+            tags: Vec::new(),
+            default_timespan: None,
+            recommended_frequency: None,
+            query,
+            queries,
+            settings,
+            workspaces: None, // Don't include workspace scope
+            webhook: None,
+            requires_confirmation: false,
+            changelog: Vec::new(),
+        }
+    }
+
     /// Load a query pack from a file
     pub fn load_from_file(path: &Path) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
 
         // Try YAML first, fall back to JSON
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            Ok(serde_json::from_str(&content)?)
+        let mut pack: QueryPack = if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            serde_json::from_str(&content)?
         } else {
             // Default to YAML for .yaml, .yml, or no extension
-            Ok(serde_yaml::from_str(&content)?)
-        }
+            serde_yaml::from_str(&content)?
+        };
+
+        pack.read_query_files(path.parent().unwrap_or(Path::new("")))?;
+        Ok(pack)
     }
 
     /// Save a query pack to a file
-    #[allow(dead_code)]
+    /// Queries loaded from a `query_file` are written back to that file instead of inline.
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        let pack = self.write_query_files(path.parent().unwrap_or(Path::new("")))?;
+
         let content = if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            serde_json::to_string_pretty(self)?
+            serde_json::to_string_pretty(&pack)?
         } else {
-            serde_yaml::to_string(self)?
+            serde_yaml::to_string(&pack)?
         };
 
         std::fs::write(path, content)?;
         Ok(())
     }
 
+    /// Fill in the text of queries that reference a `query_file`, relative to `pack_dir`
+    fn read_query_files(&mut self, pack_dir: &Path) -> Result<()> {
+        for query in self.queries.iter_mut().flatten() {
+            let Some(query_file) = &query.query_file else {
+                continue;
+            };
+            if !query.query.is_empty() {
+                return Err(crate::error::KqlPanopticonError::QueryPackValidation(
+                    format!(
+                        "Query '{}' cannot have both 'query' and 'query_file' fields",
+                        query.name
+                    ),
+                ));
+            }
+
+            let file_path = query_file_path(pack_dir, &query.name, query_file)?;
+            query.query = std::fs::read_to_string(&file_path).map_err(|e| {
+                crate::error::KqlPanopticonError::QueryPackValidation(format!(
+                    "Failed to read query file {} for '{}': {}",
+                    file_path.display(),
+                    query.name,
+                    e
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Write queries that reference a `query_file` back to their files, relative to `pack_dir`
+    /// Returns a copy of the pack with those queries' inline text removed.
+    fn write_query_files(&self, pack_dir: &Path) -> Result<QueryPack> {
+        let mut pack = self.clone();
+        for query in pack.queries.iter_mut().flatten() {
+            let Some(query_file) = &query.query_file else {
+                continue;
+            };
+
+            let file_path = query_file_path(pack_dir, &query.name, query_file)?;
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&file_path, std::mem::take(&mut query.query))?;
+        }
+        Ok(pack)
+    }
+
+    /// Hash a pack file's contents, identifying the pack if the file is renamed or moved
+    pub fn content_hash(path: &Path) -> Option<u64> {
+        use std::hash::{Hash, Hasher};
+
+        let content = std::fs::read(path).ok()?;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        content.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// Replace the text of the query at `index` (handles both single and multiple query formats)
+    /// Returns false if there is no query at that index.
+    pub fn set_query_text(&mut self, index: usize, text: String) -> bool {
+        if let Some(queries) = &mut self.queries {
+            match queries.get_mut(index) {
+                Some(query) => {
+                    query.query = text;
+                    true
+                }
+                None => false,
+            }
+        } else if self.query.is_some() && index == 0 {
+            self.query = Some(text);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Why running `queries` queries across `workspaces` workspaces needs explicit confirmation
+    /// None if the pack doesn't require it and the job count is within `threshold` (if set).
+    pub fn confirmation_reason(
+        &self,
+        queries: usize,
+        workspaces: usize,
+        threshold: Option<usize>,
+    ) -> Option<String> {
+        let jobs = queries * workspaces;
+        if self.requires_confirmation {
+            Some(format!("Pack '{}' requires confirmation", self.name))
+        } else {
+            threshold
+                .filter(|&threshold| jobs > threshold)
+                .map(|threshold| format!("{} jobs exceeds the threshold of {}", jobs, threshold))
+        }
+    }
+
+    /// Increment the last number in the version (`1.0` -> `1.1`, `2` -> `3`)
+    /// A pack without a version starts at `1.0`; versions not ending in a number are left as is.
+    pub fn bump_version(&mut self) {
+        let Some(version) = &self.version else {
+            self.version = Some("1.0".to_string());
+            return;
+        };
+        let (prefix, last) = match version.rsplit_once('.') {
+            Some((prefix, last)) => (format!("{}.", prefix), last),
+            None => (String::new(), version.as_str()),
+        };
+        if let Ok(number) = last.parse::<u64>() {
+            self.version = Some(format!("{}{}", prefix, number + 1));
+        }
+    }
+
+    /// Bump the version and append a changelog entry for a change to `query_name`
+    pub fn record_change(&mut self, query_name: &str, user: Option<String>) {
+        self.bump_version();
+        self.changelog.push(ChangelogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            version: self.version.clone(),
+            query: query_name.to_string(),
+            user,
+        });
+    }
+
     /// Get all queries from the pack (handles both single and multiple query formats)
     pub fn get_queries(&self) -> Vec<PackQuery> {
         if let Some(queries) = &self.queries {
@@ -99,14 +398,149 @@ impl QueryPack {
                 name: self.name.clone(),
                 description: self.description.clone(),
                 query: query.clone(),
+                query_file: None,
+                tags: Vec::new(),
+                mitre: Vec::new(),
+                severity: None,
+                timeout_secs: None,
+                retry_count: None,
+                max_rows: None,
             }]
         } else {
             vec![]
         }
     }
 
+    /// Get every tag used by the pack or its queries (sorted, without duplicates)
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .tags
+            .iter()
+            .chain(self.queries.iter().flatten().flat_map(|q| &q.tags))
+            .cloned()
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Check if the pack or any of its queries carries one of `tags` (case-insensitive)
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        self.all_tags().iter().any(|tag| contains_tag(tags, tag))
+    }
+
+    /// Get the queries tagged with any of `tags`, directly or through the pack's tags
+    /// Returns all queries when `tags` is empty.
+    pub fn queries_with_tags(&self, tags: &[String]) -> Vec<PackQuery> {
+        if tags.is_empty() || self.tags.iter().any(|tag| contains_tag(tags, tag)) {
+            return self.get_queries();
+        }
+        self.get_queries()
+            .into_iter()
+            .filter(|q| q.tags.iter().any(|tag| contains_tag(tags, tag)))
+            .collect()
+    }
+
+    /// Get the per-workspace overrides declared in the workspace scope (if any)
+    pub fn workspace_overrides(&self) -> &[WorkspaceOverride] {
+        match &self.workspaces {
+            Some(WorkspaceScope::Selected { overrides, .. }) => overrides,
+            _ => &[],
+        }
+    }
+
+    /// Get the pack's default timespan as an ISO 8601 duration (None if unset or invalid)
+    pub fn timespan(&self) -> Option<String> {
+        self.default_timespan.as_deref().and_then(iso_duration)
+    }
+
+    /// Resolve execution settings for a query on a specific workspace
+    /// Precedence: workspace override > query override > base settings
+    /// The pack's default timespan applies unless the base settings set one.
+    pub fn resolve_settings(
+        &self,
+        base: &QuerySettings,
+        query: &PackQuery,
+        workspace: &Workspace,
+    ) -> QuerySettings {
+        let mut settings = base.clone();
+
+        if settings.timespan.is_none() {
+            settings.timespan = self.timespan();
+        }
+
+        if query.timeout_secs.is_some() {
+            settings.timeout_secs = query.timeout_secs;
+        }
+        if query.retry_count.is_some() {
+            settings.retry_count = query.retry_count;
+        }
+        if query.max_rows.is_some() {
+            settings.max_rows = query.max_rows;
+        }
+
+        if let Some(ws_override) = self
+            .workspace_overrides()
+            .iter()
+            .find(|o| o.matches(workspace))
+        {
+            if ws_override.timeout_secs.is_some() {
+                settings.timeout_secs = ws_override.timeout_secs;
+            }
+            if ws_override.retry_count.is_some() {
+                settings.retry_count = ws_override.retry_count;
+            }
+        }
+
+        settings
+    }
+
+    /// Execute every query in the pack on each workspace, concurrently per query
+    /// Settings come from `base` (or the pack's own settings) with query and workspace overrides
+    /// applied; each query's output is named after the query.
+    pub async fn execute<B: QueryBackend>(
+        &self,
+        client: &B,
+        workspaces: &[Workspace],
+        base: Option<&QuerySettings>,
+    ) -> Result<Vec<QueryJobResult>> {
+        let base = base
+            .cloned()
+            .or_else(|| self.settings.clone())
+            .unwrap_or_default();
+
+        let mut results = Vec::new();
+        for query in self.get_queries() {
+            let mut settings = base.clone();
+            settings.job_name = job_name(&query.name);
+
+            let executions = workspaces.iter().map(|workspace| {
+                QueryJobBuilder::new()
+                    .workspaces(vec![workspace.clone()])
+                    .queries(vec![query.query.clone()])
+                    .settings(self.resolve_settings(&settings, &query, workspace))
+                    .execute(client)
+            });
+            for batch in futures::future::join_all(executions).await {
+                results.extend(batch?);
+            }
+        }
+        Ok(results)
+    }
+
     /// Validate the query pack
     pub fn validate(&self) -> Result<()> {
+        if let Some(timespan) = &self.default_timespan {
+            if iso_duration(timespan).is_none() {
+                return Err(crate::error::KqlPanopticonError::QueryPackValidation(
+                    format!(
+                        "Invalid default_timespan '{}' (expected e.g. 30m, 24h, 7d or P7D)",
+                        timespan
+                    ),
+                ));
+            }
+        }
+
         // Must have either query or queries
         if self.query.is_none() && self.queries.is_none() {
             return Err(crate::error::KqlPanopticonError::QueryPackValidation(
@@ -128,46 +562,204 @@ impl QueryPack {
                     "Query pack 'queries' array cannot be empty".into(),
                 ));
             }
+
+            // Run manifests and output files are keyed by query name
+            let mut names = std::collections::HashSet::new();
+            if let Some(query) = queries.iter().find(|q| !names.insert(q.name.as_str())) {
+                return Err(crate::error::KqlPanopticonError::QueryPackValidation(
+                    format!("Query name '{}' is used more than once", query.name),
+                ));
+            }
+
+            // Technique mappings must be ATT&CK IDs
+            for query in queries {
+                if let Some(id) = query
+                    .mitre
+                    .iter()
+                    .find(|id| !crate::coverage::is_technique_id(id))
+                {
+                    return Err(crate::error::KqlPanopticonError::QueryPackValidation(format!(
+                        "Query '{}' has invalid MITRE technique ID '{}' (expected e.g. T1059 or T1021.001)",
+                        query.name, id
+                    )));
+                }
+            }
+
+            // Each query needs inline text or a query file
+            if let Some(query) = queries
+                .iter()
+                .find(|q| q.query.is_empty() && q.query_file.is_none())
+            {
+                return Err(crate::error::KqlPanopticonError::QueryPackValidation(
+                    format!(
+                        "Query '{}' must contain either 'query' or 'query_file' field",
+                        query.name
+                    ),
+                ));
+            }
         }
 
         Ok(())
     }
 
-    /// Get the pack's file path in the standard library location
+    /// Get the pack's file path in the personal library (where new packs are written)
     pub fn get_library_path(relative_path: &str) -> Result<PathBuf> {
-        let home =
-            dirs::home_dir().ok_or(crate::error::KqlPanopticonError::HomeDirectoryNotFound)?;
+        Ok(paths::data_dir()?.join("packs").join(relative_path))
+    }
 
-        Ok(home.join(".kql-panopticon/packs").join(relative_path))
+    /// Get all pack libraries: the personal library first, then those from the config
+    pub fn libraries() -> Result<Vec<PackLibrary>> {
+        let mut libraries = vec![PackLibrary {
+            name: PERSONAL_LIBRARY.to_string(),
+            path: Self::get_library_path("")?,
+            read_only: false,
+        }];
+        libraries.extend(
+            Config::load_or_default()
+                .pack_libraries
+                .into_iter()
+                .map(|library| PackLibrary {
+                    path: paths::expand_home(&library.path),
+                    ..library
+                }),
+        );
+        Ok(libraries)
     }
 
-    /// List all query packs in the library
-    pub fn list_library_packs() -> Result<Vec<PathBuf>> {
-        let packs_dir = Self::get_library_path("")?;
+    /// Find a pack by path relative to a library root, searching libraries in order
+    pub fn find_in_libraries(relative_path: &str) -> Result<Option<PathBuf>> {
+        Ok(Self::libraries()?
+            .into_iter()
+            .map(|library| library.path.join(relative_path))
+            .find(|path| path.is_file()))
+    }
+
+    /// List all query packs in a library directory
+    pub fn list_library_packs(library_root: &Path) -> Result<Vec<PathBuf>> {
+        Ok(Self::scan_library(library_root)?.packs)
+    }
 
-        if !packs_dir.exists() {
-            std::fs::create_dir_all(&packs_dir)?;
-            return Ok(vec![]);
+    /// Find the query packs in a library directory, reporting files that aren't packs
+    ///
+    /// Hidden directories (e.g. `.git`, `.github`) and paths matched by the library's
+    /// `.kqlignore` are skipped without being reported.
+    pub fn scan_library(library_root: &Path) -> Result<LibraryScan> {
+        let mut scan = LibraryScan::default();
+        if !library_root.exists() {
+            return Ok(scan);
         }
 
-        let mut packs = Vec::new();
+        let ignore = crate::pack_ignore::PackIgnore::load(library_root);
+        let is_skipped = |entry: &walkdir::DirEntry| {
+            let relative = entry
+                .path()
+                .strip_prefix(library_root)
+                .unwrap_or(entry.path());
+            let hidden = entry.depth() > 0
+                && entry.file_type().is_dir()
+                && entry.file_name().to_string_lossy().starts_with('.');
+            hidden || ignore.is_ignored(relative, entry.file_type().is_dir())
+        };
 
         // Recursively find all .yaml, .yml, .json files
-        for entry in walkdir::WalkDir::new(&packs_dir)
+        for entry in walkdir::WalkDir::new(library_root)
             .into_iter()
+            .filter_entry(|e| !is_skipped(e))
             .filter_map(|e| e.ok())
         {
             if entry.file_type().is_file() {
                 if let Some(ext) = entry.path().extension().and_then(|s| s.to_str()) {
                     if ext == "yaml" || ext == "yml" || ext == "json" {
-                        packs.push(entry.path().to_path_buf());
+                        match Self::check_pack_marker(entry.path()) {
+                            Ok(()) => scan.packs.push(entry.path().to_path_buf()),
+                            Err(reason) => scan.skipped.push((entry.into_path(), reason)),
+                        }
                     }
                 }
             }
         }
 
-        Ok(packs)
+        Ok(scan)
+    }
+
+    /// Check that a file looks like a query pack without fully loading it
+    /// Returns the reason it isn't one otherwise.
+    fn check_pack_marker(path: &Path) -> std::result::Result<(), String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        // YAML is a superset of JSON, so this reads both
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(&content).map_err(|e| format!("not valid YAML/JSON: {}", e))?;
+        let Some(mapping) = value.as_mapping() else {
+            return Err("not a mapping".to_string());
+        };
+
+        if let Some(kind) = mapping.get("kind") {
+            return match kind.as_str() {
+                Some(kind) if kind.eq_ignore_ascii_case(PACK_KIND) => Ok(()),
+                _ => Err(format!("kind is not '{}'", PACK_KIND)),
+            };
+        }
+        if mapping.contains_key("query") || mapping.contains_key("queries") {
+            Ok(())
+        } else {
+            Err("no 'query' or 'queries' field".to_string())
+        }
+    }
+}
+
+/// Check if `tags` contains `tag`, ignoring case
+fn contains_tag(tags: &[String], tag: &str) -> bool {
+    tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+/// Turn a query name into a file-safe job name
+fn job_name(query_name: &str) -> String {
+    query_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Convert a timespan (`30m`, `24h`, `7d` or an ISO 8601 duration) to an ISO 8601 duration
+pub fn iso_duration(value: &str) -> Option<String> {
+    let value = value.trim();
+    let iso = regex::Regex::new(r"^P(\d+[YMWD])*(T(\d+(\.\d+)?[HMS])+)?$").ok()?;
+    if value.len() > 1 && iso.is_match(value) {
+        return Some(value.to_string());
+    }
+
+    let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit())?);
+    let number: u64 = number.parse().ok()?;
+    match unit.trim() {
+        "d" => Some(format!("P{}D", number)),
+        "h" => Some(format!("PT{}H", number)),
+        "m" => Some(format!("PT{}M", number)),
+        "s" => Some(format!("PT{}S", number)),
+        _ => None,
+    }
+}
+
+/// Path of a query's `query_file` under `pack_dir`
+/// Absolute paths and `..` are rejected so a shared pack can't read or overwrite files
+/// outside its own folder.
+fn query_file_path(pack_dir: &Path, query_name: &str, query_file: &Path) -> Result<PathBuf> {
+    if !paths::is_plain(query_file) {
+        return Err(crate::error::KqlPanopticonError::QueryPackValidation(
+            format!(
+                "Query file {} for '{}' must be a relative path inside the pack's folder",
+                query_file.display(),
+                query_name
+            ),
+        ));
     }
+    Ok(pack_dir.join(query_file))
 }
 
 #[cfg(test)]
@@ -205,17 +797,39 @@ workspaces:
         pack.validate().unwrap();
     }
 
+    #[test]
+    fn test_validate_duplicate_query_names() {
+        let yaml = r#"
+name: "Security Hunt"
+queries:
+  - name: "Sign-ins"
+    query: "SigninLogs | limit 5"
+  - name: "Sign-ins"
+    query: "AADNonInteractiveUserSignInLogs | limit 5"
+"#;
+        let pack: QueryPack = serde_yaml::from_str(yaml).unwrap();
+        let err = pack.validate().unwrap_err().to_string();
+        assert!(err.contains("'Sign-ins' is used more than once"), "{}", err);
+    }
+
     #[test]
     fn test_validate_empty_pack() {
         let pack = QueryPack {
+            kind: None,
             name: "Test".into(),
             description: None,
             author: None,
             version: None,
+            tags: Vec::new(),
+            default_timespan: None,
+            recommended_frequency: None,
             query: None,
             queries: None,
             settings: None,
             workspaces: None,
+            webhook: None,
+            requires_confirmation: false,
+            changelog: Vec::new(),
         };
         assert!(pack.validate().is_err());
     }
@@ -223,19 +837,279 @@ workspaces:
     #[test]
     fn test_validate_both_query_and_queries() {
         let pack = QueryPack {
+            kind: None,
             name: "Test".into(),
             description: None,
             author: None,
             version: None,
+            tags: Vec::new(),
+            default_timespan: None,
+            recommended_frequency: None,
             query: Some("SecurityEvent".into()),
             queries: Some(vec![PackQuery {
                 name: "Q1".into(),
                 description: None,
                 query: "SigninLogs".into(),
+                query_file: None,
+                tags: Vec::new(),
+                mitre: Vec::new(),
+                severity: None,
+                timeout_secs: None,
+                retry_count: None,
+                max_rows: None,
             }]),
             settings: None,
             workspaces: None,
+            webhook: None,
+            requires_confirmation: false,
+            changelog: Vec::new(),
         };
         assert!(pack.validate().is_err());
     }
+
+    #[test]
+    fn test_resolve_settings_precedence() {
+        let yaml = r#"
+name: "Overrides"
+default_timespan: 7d
+queries:
+  - name: "Slow aggregation"
+    query: "SecurityEvent | summarize count() by Computer"
+    timeout_secs: 600
+    retry_count: 1
+  - name: "Fast lookup"
+    query: "SigninLogs | limit 5"
+settings:
+  timeout_secs: 60
+workspaces:
+  scope: selected
+  ids: ["ws-1", "ws-2"]
+  overrides:
+    - id: "ws-2"
+      timeout_secs: 900
+"#;
+        let pack: QueryPack = serde_yaml::from_str(yaml).unwrap();
+        let base = pack.settings.clone().unwrap();
+        let queries = pack.get_queries();
+
+        let workspace = |id: &str| Workspace {
+            workspace_id: id.into(),
+            ..Workspace::fixture(id)
+        };
+
+        let slow_ws1 = pack.resolve_settings(&base, &queries[0], &workspace("ws-1"));
+        assert_eq!(slow_ws1.timeout_secs, Some(600));
+        assert_eq!(slow_ws1.retry_count, Some(1));
+
+        let slow_ws2 = pack.resolve_settings(&base, &queries[0], &workspace("ws-2"));
+        assert_eq!(slow_ws2.timeout_secs, Some(900));
+        assert_eq!(slow_ws2.retry_count, Some(1));
+
+        let fast_ws1 = pack.resolve_settings(&base, &queries[1], &workspace("ws-1"));
+        assert_eq!(fast_ws1.timeout_secs, Some(60));
+        assert_eq!(fast_ws1.retry_count, None);
+        assert_eq!(fast_ws1.timespan.as_deref(), Some("P7D"));
+
+        // An explicit timespan in the base settings wins over the pack default
+        let explicit = QuerySettings {
+            timespan: Some("PT1H".into()),
+            ..base.clone()
+        };
+        let fast_explicit = pack.resolve_settings(&explicit, &queries[1], &workspace("ws-1"));
+        assert_eq!(fast_explicit.timespan.as_deref(), Some("PT1H"));
+    }
+
+    #[test]
+    fn test_iso_duration() {
+        assert_eq!(iso_duration("7d").as_deref(), Some("P7D"));
+        assert_eq!(iso_duration("24h").as_deref(), Some("PT24H"));
+        assert_eq!(iso_duration("30m").as_deref(), Some("PT30M"));
+        assert_eq!(iso_duration("P1DT12H").as_deref(), Some("P1DT12H"));
+        assert_eq!(iso_duration("P"), None);
+        assert_eq!(iso_duration("7 weeks"), None);
+        assert_eq!(iso_duration("d"), None);
+    }
+
+    #[test]
+    fn test_set_query_text() {
+        let mut single: QueryPack =
+            serde_yaml::from_str("name: Single\nquery: T | take 1\n").unwrap();
+        assert!(single.set_query_text(0, "T | take 5".into()));
+        assert!(!single.set_query_text(1, "T".into()));
+        assert_eq!(single.query.as_deref(), Some("T | take 5"));
+
+        let mut multi: QueryPack = serde_yaml::from_str(
+            "name: Multi\nqueries:\n  - name: a\n    query: A\n  - name: b\n    query: B\n",
+        )
+        .unwrap();
+        assert!(multi.set_query_text(1, "B | count".into()));
+        assert_eq!(multi.get_queries()[1].query, "B | count");
+    }
+
+    #[test]
+    fn test_query_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("kql-pack-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("queries")).unwrap();
+        std::fs::write(dir.join("queries/logons.kql"), "SecurityEvent\n| take 10\n").unwrap();
+        let pack_path = dir.join("pack.yaml");
+        std::fs::write(
+            &pack_path,
+            "name: Refs\nqueries:\n  - name: Logons\n    query_file: queries/logons.kql\n  - name: Inline\n    query: T\n",
+        )
+        .unwrap();
+
+        let mut pack = QueryPack::load_from_file(&pack_path).unwrap();
+        pack.validate().unwrap();
+        assert_eq!(pack.get_queries()[0].query, "SecurityEvent\n| take 10\n");
+
+        assert!(pack.set_query_text(0, "SecurityEvent | take 5".into()));
+        pack.save_to_file(&pack_path).unwrap();
+
+        let saved = std::fs::read_to_string(&pack_path).unwrap();
+        assert!(saved.contains("query_file: queries/logons.kql"));
+        assert!(!saved.contains("SecurityEvent"));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("queries/logons.kql")).unwrap(),
+            "SecurityEvent | take 5"
+        );
+
+        for escape in ["../outside.kql", "/etc/passwd"] {
+            std::fs::write(
+                &pack_path,
+                format!(
+                    "name: Refs\nqueries:\n  - name: Escape\n    query_file: {}\n",
+                    escape
+                ),
+            )
+            .unwrap();
+            assert!(QueryPack::load_from_file(&pack_path).is_err());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_queries_with_tags() {
+        let yaml = r#"
+name: "Tagged"
+tags: ["windows"]
+queries:
+  - name: "Run keys"
+    query: "DeviceRegistryEvents"
+    tags: ["persistence"]
+  - name: "Remote logons"
+    query: "SecurityEvent"
+    tags: ["lateral-movement", "credential-access"]
+  - name: "Untagged"
+    query: "Heartbeat"
+"#;
+        let pack: QueryPack = serde_yaml::from_str(yaml).unwrap();
+        let names = |tags: &[&str]| {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            pack.queries_with_tags(&tags)
+                .into_iter()
+                .map(|q| q.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&[]).len(), 3);
+        assert_eq!(names(&["Persistence"]), vec!["Run keys"]);
+        assert_eq!(
+            names(&["persistence", "lateral-movement"]),
+            vec!["Run keys", "Remote logons"]
+        );
+        assert_eq!(names(&["windows"]).len(), 3);
+        assert!(names(&["exfiltration"]).is_empty());
+        assert!(pack.has_any_tag(&["CREDENTIAL-ACCESS".to_string()]));
+        assert_eq!(pack.all_tags().len(), 4);
+    }
+
+    #[test]
+    fn test_scan_library_skips_non_packs() {
+        let dir = std::env::temp_dir().join(format!("kql-scan-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for sub in [".github/workflows", "ci", "hunts"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let write = |path: &str, content: &str| std::fs::write(dir.join(path), content).unwrap();
+        write("hunts/logons.yaml", "name: Logons\nquery: SigninLogs\n");
+        write(
+            "hunts/marked.yml",
+            "kind: QueryPack\nname: Marked\nqueries: []\n",
+        );
+        write(
+            "hunts/schema.json",
+            r#"{"$schema": "http://json-schema.org/draft-07/schema"}"#,
+        );
+        write("hunts/other.yaml", "kind: pipeline\nquery: not a pack\n");
+        write(".github/workflows/ci.yml", "on: push\n");
+        write("ci/config.yaml", "stages: []\n");
+        write("renovate.json", "{}");
+        write(".kqlignore", "ci/\nrenovate.json\n");
+
+        let scan = QueryPack::scan_library(&dir).unwrap();
+        let names = |paths: Vec<&PathBuf>| {
+            let mut names: Vec<String> = paths
+                .into_iter()
+                .map(|p| {
+                    p.strip_prefix(&dir)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(scan.packs.iter().collect()),
+            vec!["hunts/logons.yaml", "hunts/marked.yml"]
+        );
+        assert_eq!(
+            names(scan.skipped.iter().map(|(p, _)| p).collect()),
+            vec!["hunts/other.yaml", "hunts/schema.json"]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_record_change_bumps_version() {
+        let mut pack: QueryPack =
+            serde_yaml::from_str("name: Test\nversion: '1.9'\nquery: T").unwrap();
+        pack.record_change("Test", Some("alice".to_string()));
+        pack.record_change("Test", None);
+        assert_eq!(pack.version.as_deref(), Some("1.11"));
+        assert_eq!(pack.changelog.len(), 2);
+        assert_eq!(pack.changelog[0].version.as_deref(), Some("1.10"));
+        assert_eq!(pack.changelog[0].user.as_deref(), Some("alice"));
+
+        let yaml = serde_yaml::to_string(&pack).unwrap();
+        let reloaded: QueryPack = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(reloaded.changelog, pack.changelog);
+
+        for (before, after) in [
+            (None, Some("1.0")),
+            (Some("3"), Some("4")),
+            (Some("beta"), Some("beta")),
+        ] {
+            pack.version = before.map(str::to_string);
+            pack.bump_version();
+            assert_eq!(pack.version.as_deref(), after);
+        }
+    }
+
+    #[test]
+    fn test_confirmation_reason() {
+        let mut pack: QueryPack = serde_yaml::from_str("name: Test\nquery: T").unwrap();
+        assert_eq!(pack.confirmation_reason(2, 5, None), None);
+        assert_eq!(pack.confirmation_reason(2, 5, Some(10)), None);
+        assert_eq!(
+            pack.confirmation_reason(3, 5, Some(10)).as_deref(),
+            Some("15 jobs exceeds the threshold of 10")
+        );
+
+        pack = serde_yaml::from_str("name: Test\nquery: T\nrequires_confirmation: true").unwrap();
+        assert!(pack.confirmation_reason(1, 1, None).is_some());
+    }
 }
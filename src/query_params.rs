@@ -0,0 +1,316 @@
+use crate::error::Result;
+use crate::kql_lexer::SegmentKind;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// A value a query asks for before it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParameter {
+    pub name: String,
+    /// Declared type (None for `{{placeholder}}` text substitutions)
+    pub kind: Option<String>,
+    /// Default from the declaration, as written (used when no value is entered)
+    pub default: Option<String>,
+}
+
+/// A `declare query_parameters(...)` statement found in a query
+struct Declaration {
+    /// Byte range of the statement, including its trailing `;`
+    start: usize,
+    end: usize,
+    parameters: Vec<QueryParameter>,
+}
+
+fn placeholder_regex() -> Regex {
+    Regex::new(r"\{\{\s*([\w.-]+)\s*\}\}").expect("valid placeholder regex")
+}
+
+/// Find the parameters a query declares or uses as `{{placeholders}}`, declared ones first
+pub fn find_parameters(query: &str) -> Vec<QueryParameter> {
+    let mut parameters: Vec<QueryParameter> = Vec::new();
+    if let Some(declaration) = find_declaration(query) {
+        parameters.extend(declaration.parameters);
+    }
+    for capture in placeholder_regex().captures_iter(query) {
+        let name = &capture[1];
+        if !parameters.iter().any(|p| p.name == name) {
+            parameters.push(QueryParameter {
+                name: name.to_string(),
+                kind: None,
+                default: None,
+            });
+        }
+    }
+    parameters
+}
+
+/// Replace the parameter declaration with `let` statements and fill in the placeholders
+///
+/// Declared parameters without a value fall back to their default; the error names the
+/// first parameter that has neither.
+pub fn substitute(
+    query: &str,
+    values: &BTreeMap<String, String>,
+) -> std::result::Result<String, String> {
+    let value_of = |parameter: &QueryParameter| -> std::result::Result<String, String> {
+        match values.get(&parameter.name).map(|v| v.trim()) {
+            Some(value) if !value.is_empty() => Ok(literal(parameter.kind.as_deref(), value)),
+            _ => parameter
+                .default
+                .clone()
+                .ok_or_else(|| format!("No value for parameter '{}'", parameter.name)),
+        }
+    };
+
+    let mut text = query.to_string();
+    if let Some(declaration) = find_declaration(query) {
+        let mut lets = Vec::new();
+        for parameter in &declaration.parameters {
+            lets.push(format!(
+                "let {} = {};",
+                parameter.name,
+                value_of(parameter)?
+            ));
+        }
+        text.replace_range(declaration.start..declaration.end, &lets.join("\n"));
+    }
+
+    let mut missing = None;
+    let text = placeholder_regex().replace_all(&text, |capture: &regex::Captures| {
+        match values.get(&capture[1]).map(|v| v.trim()) {
+            Some(value) if !value.is_empty() => value.to_string(),
+            _ => {
+                missing.get_or_insert_with(|| capture[1].to_string());
+                capture[0].to_string()
+            }
+        }
+    });
+    match missing {
+        Some(name) => Err(format!("No value for parameter '{}'", name)),
+        None => Ok(text.into_owned()),
+    }
+}
+
+/// Remember entered values in config.yaml so the prompt starts from them next time
+/// Blank values are not saved, so a parameter's default keeps applying.
+pub fn save_values(values: &BTreeMap<String, String>) -> Result<()> {
+    let mut config = crate::config::Config::load()?;
+    for (name, value) in values {
+        if !value.trim().is_empty() {
+            config
+                .query_parameters
+                .insert(name.clone(), value.trim().to_string());
+        }
+    }
+    config.save()
+}
+
+/// Turn an entered value into a KQL literal of the declared type
+/// Values that already look like literals or expressions (quoted strings, `ago(7d)`) are kept.
+fn literal(kind: Option<&str>, value: &str) -> String {
+    let kind = kind.unwrap_or("").to_ascii_lowercase();
+    match kind.as_str() {
+        "string" => {
+            let quoted = value.len() >= 2
+                && ((value.starts_with('\'') && value.ends_with('\''))
+                    || (value.starts_with('"') && value.ends_with('"')));
+            if quoted {
+                value.to_string()
+            } else {
+                format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+            }
+        }
+        "datetime" | "date" | "timespan" | "time" | "guid" | "dynamic" if !value.contains('(') => {
+            format!("{}({})", kind, value)
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Locate and parse the query's `declare query_parameters(...)` statement
+fn find_declaration(query: &str) -> Option<Declaration> {
+    let declare = Regex::new(r"declare\s+query_parameters\s*\(").ok()?;
+    let code = code_chars(query);
+    let is_code = |offset: usize| code.binary_search_by_key(&offset, |&(i, _, _)| i).is_ok();
+    let found = declare.find_iter(query).find(|m| is_code(m.start()))?;
+
+    // The closing parenthesis is the first one back at the declaration's depth
+    let body_start = found.end();
+    let opening = code.iter().find(|&&(i, _, _)| i == body_start - 1)?.2;
+    let body_end = code
+        .iter()
+        .find(|&&(i, c, depth)| i >= body_start && c == ')' && depth == opening)?
+        .0;
+
+    let rest = &query[body_end + 1..];
+    let trimmed = rest.trim_start();
+    let end = if trimmed.starts_with(';') {
+        body_end + 1 + (rest.len() - trimmed.len()) + 1
+    } else {
+        body_end + 1
+    };
+
+    let parameters = split_top_level(&query[body_start..body_end], ',')
+        .into_iter()
+        .map(without_comments)
+        .filter_map(|declaration| {
+            let (name, rest) = declaration.trim().split_once(':')?;
+            let (kind, default) = match rest.split_once('=') {
+                Some((kind, default)) => (kind, Some(default.trim().to_string())),
+                None => (rest, None),
+            };
+            Some(QueryParameter {
+                name: name.trim().to_string(),
+                kind: Some(kind.trim().to_string()),
+                default,
+            })
+        })
+        .collect();
+
+    Some(Declaration {
+        start: found.start(),
+        end,
+        parameters,
+    })
+}
+
+/// Characters of KQL text outside string literals (including ```` ``` ```` multi-line ones)
+/// and `//` comments, as
+/// `(byte offset, char, bracket depth)`; a closing bracket has the depth of its opening one
+pub(crate) fn code_chars(text: &str) -> Vec<(usize, char, usize)> {
+    scan(text, false)
+}
+
+/// KQL text with its `//` comments removed
+fn without_comments(text: &str) -> String {
+    scan(text, true).into_iter().map(|(_, c, _)| c).collect()
+}
+
+fn scan(text: &str, keep_strings: bool) -> Vec<(usize, char, usize)> {
+    let mut code = Vec::new();
+    let mut depth = 0usize;
+    for segment in crate::kql_lexer::segments(text) {
+        let chars = text[segment.start..segment.end]
+            .char_indices()
+            .map(|(i, c)| (segment.start + i, c));
+        match segment.kind {
+            SegmentKind::Code => {
+                for (idx, c) in chars {
+                    match c {
+                        '(' | '[' | '{' => {
+                            code.push((idx, c, depth));
+                            depth += 1;
+                        }
+                        ')' | ']' | '}' => {
+                            depth = depth.saturating_sub(1);
+                            code.push((idx, c, depth));
+                        }
+                        _ => code.push((idx, c, depth)),
+                    }
+                }
+            }
+            SegmentKind::Str { .. } if keep_strings => {
+                code.extend(chars.map(|(idx, c)| (idx, c, depth)));
+            }
+            SegmentKind::Str { .. } | SegmentKind::Comment => {}
+        }
+    }
+    code
+}
+
+/// Split KQL text on a delimiter, ignoring delimiters inside brackets, string literals and
+/// `//` comments
+pub(crate) fn split_top_level(text: &str, delimiter: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (idx, c, depth) in code_chars(text) {
+        if c == delimiter && depth == 0 {
+            parts.push(&text[start..idx]);
+            start = idx + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_substitute() {
+        let query = "declare query_parameters(user:string, since:datetime = ago(7d), \
+                     hosts:dynamic = dynamic(['a', 'b']));\n\
+                     SigninLogs\n\
+                     | where TimeGenerated > since and UserPrincipalName == user\n\
+                     | where ResultType == {{ result }}";
+        let parameters = find_parameters(query);
+        let names: Vec<&str> = parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["user", "since", "hosts", "result"]);
+        assert_eq!(
+            parameters[2].default.as_deref(),
+            Some("dynamic(['a', 'b'])")
+        );
+        assert_eq!(parameters[3].kind, None);
+
+        let mut values = BTreeMap::new();
+        values.insert("user".to_string(), "o'brien@contoso.com".to_string());
+        assert_eq!(
+            substitute(query, &values),
+            Err("No value for parameter 'result'".to_string())
+        );
+
+        values.insert("result".to_string(), "50126".to_string());
+        values.insert("since".to_string(), "2024-01-01".to_string());
+        assert_eq!(
+            substitute(query, &values).unwrap(),
+            "let user = 'o\\'brien@contoso.com';\n\
+             let since = datetime(2024-01-01);\n\
+             let hosts = dynamic(['a', 'b']);\n\
+             SigninLogs\n\
+             | where TimeGenerated > since and UserPrincipalName == user\n\
+             | where ResultType == 50126"
+        );
+
+        assert!(find_parameters("SigninLogs | take 10").is_empty());
+    }
+
+    #[test]
+    fn test_comments_and_strings_are_skipped() {
+        let query = "// don't declare query_parameters(old:string) here\n\
+                     let note = 'declare query_parameters(x:int)';\n\
+                     declare query_parameters(user:string = 'a,b', // the user's UPN\n\
+                     since:datetime);\n\
+                     SigninLogs";
+        let parameters = find_parameters(query);
+        let names: Vec<&str> = parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["user", "since"]);
+        assert_eq!(parameters[0].default.as_deref(), Some("'a,b'"));
+
+        assert_eq!(
+            split_top_level("A | where B == '|' // it's | not a pipe\n| take 1", '|'),
+            vec!["A ", " where B == '|' // it's | not a pipe\n", " take 1"]
+        );
+    }
+
+    #[test]
+    fn test_multi_line_strings_are_skipped() {
+        let query = "let pattern = ```\n\
+                     declare query_parameters(old:string);\n\
+                     a | b // c\n\
+                     ```;\n\
+                     declare query_parameters(user:string);\n\
+                     T | where Msg matches regex pattern";
+        let names: Vec<String> = find_parameters(query).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["user"]);
+
+        // Watch mode puts its filter before the first pipe found here
+        let text = "print m = ```a | b\n// c```\n| take 1";
+        let pipes: Vec<usize> = code_chars(text)
+            .into_iter()
+            .filter(|&(_, c, _)| c == '|')
+            .map(|(idx, _, _)| idx)
+            .collect();
+        assert_eq!(pipes, vec![text.rfind('|').unwrap()]);
+    }
+}
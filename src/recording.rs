@@ -0,0 +1,197 @@
+use crate::error::{KqlPanopticonError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Mode used by clients created without an explicit one (set by --record / --replay)
+static DEFAULT_MODE: OnceLock<RecordMode> = OnceLock::new();
+
+/// Whether a client saves Azure API responses to a fixture directory or serves them from one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordMode {
+    /// Send requests to Azure as usual and save every response under the directory
+    Record(PathBuf),
+    /// Serve responses saved by `Record` from the directory; nothing is sent to Azure and no
+    /// credentials are needed
+    Replay(PathBuf),
+}
+
+impl RecordMode {
+    fn dir(&self) -> &Path {
+        match self {
+            RecordMode::Record(dir) | RecordMode::Replay(dir) => dir,
+        }
+    }
+}
+
+/// Use `mode` for every client created afterwards (the --record and --replay flags)
+/// Later calls are ignored.
+pub fn set_default_mode(mode: RecordMode) {
+    let _ = DEFAULT_MODE.set(mode);
+}
+
+/// Mode set by [`set_default_mode`], if any
+pub fn default_mode() -> Option<RecordMode> {
+    DEFAULT_MODE.get().cloned()
+}
+
+/// A response as stored in a fixture
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RecordedResponse {
+    pub status: u16,
+    /// Retry-After header, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    pub body: String,
+}
+
+/// Responses recorded for one request (one fixture file)
+/// Repeated identical requests (e.g. watch polls) append responses, replayed in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    request_body: String,
+    responses: Vec<RecordedResponse>,
+}
+
+/// Saves responses to, or serves them from, a fixture directory
+///
+/// Each distinct request (method, URL and body) gets one JSON file named after a hash of it.
+/// Tokens and other headers are never written.
+#[derive(Debug, Clone)]
+pub(crate) struct Recorder {
+    mode: RecordMode,
+    /// Responses served so far per fixture, so repeated requests step through them; also
+    /// serializes fixture writes
+    served: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Recorder {
+    pub fn new(mode: RecordMode) -> Self {
+        Self {
+            mode,
+            served: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, RecordMode::Replay(_))
+    }
+
+    /// Save a response received for a request (record mode only)
+    pub fn record(
+        &self,
+        method: &str,
+        url: &str,
+        body: &[u8],
+        response: &RecordedResponse,
+    ) -> Result<()> {
+        if self.is_replay() {
+            return Ok(());
+        }
+        let _guard = self.served.lock().map_err(lock_error)?;
+        let path = self.path(method, url, body);
+        let mut fixture = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(_) => Fixture {
+                method: method.to_string(),
+                url: url.to_string(),
+                request_body: String::from_utf8_lossy(body).into_owned(),
+                responses: Vec::new(),
+            },
+        };
+        fixture.responses.push(response.clone());
+        std::fs::create_dir_all(self.mode.dir())?;
+        std::fs::write(&path, serde_json::to_string_pretty(&fixture)?)?;
+        Ok(())
+    }
+
+    /// Serve the next recorded response for a request (the last one repeats once exhausted)
+    pub fn replay(&self, method: &str, url: &str, body: &[u8]) -> Result<RecordedResponse> {
+        let path = self.path(method, url, body);
+        let fixture: Fixture = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(_) => {
+                return Err(KqlPanopticonError::Other(format!(
+                    "No recorded response for {} {} in {}",
+                    method,
+                    url,
+                    self.mode.dir().display()
+                )))
+            }
+        };
+        let mut served = self.served.lock().map_err(lock_error)?;
+        let count = served
+            .entry(path.to_string_lossy().into_owned())
+            .or_insert(0);
+        let response = fixture
+            .responses
+            .get(*count)
+            .or(fixture.responses.last())
+            .cloned()
+            .ok_or_else(|| {
+                KqlPanopticonError::Other(format!("Fixture {} is empty", path.display()))
+            })?;
+        *count += 1;
+        Ok(response)
+    }
+
+    fn path(&self, method: &str, url: &str, body: &[u8]) -> PathBuf {
+        self.mode
+            .dir()
+            .join(format!("{:016x}.json", fixture_hash(method, url, body)))
+    }
+}
+
+fn lock_error<T>(e: std::sync::PoisonError<T>) -> KqlPanopticonError {
+    KqlPanopticonError::Other(format!("Fixture lock poisoned: {}", e))
+}
+
+/// FNV-1a hash of a request (stable across builds, unlike `DefaultHasher`)
+fn fixture_hash(method: &str, url: &str, body: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in [method.as_bytes(), b"\n", url.as_bytes(), b"\n", body] {
+        for byte in part {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_then_replay() {
+        let dir = std::env::temp_dir().join(format!("kql-fixtures-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let url = "https://api.loganalytics.io/v1/workspaces/ws/query";
+        let response = |body: &str| RecordedResponse {
+            status: 200,
+            retry_after: None,
+            body: body.to_string(),
+        };
+
+        let recorder = Recorder::new(RecordMode::Record(dir.clone()));
+        recorder
+            .record("POST", url, b"{\"query\":\"T\"}", &response("first"))
+            .unwrap();
+        recorder
+            .record("POST", url, b"{\"query\":\"T\"}", &response("second"))
+            .unwrap();
+
+        let replayer = Recorder::new(RecordMode::Replay(dir.clone()));
+        let body = b"{\"query\":\"T\"}";
+        assert_eq!(replayer.replay("POST", url, body).unwrap().body, "first");
+        assert_eq!(replayer.replay("POST", url, body).unwrap().body, "second");
+        assert_eq!(replayer.replay("POST", url, body).unwrap().body, "second");
+        assert!(replayer.replay("POST", url, b"{\"query\":\"U\"}").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
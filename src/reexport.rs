@@ -0,0 +1,313 @@
+use crate::error::{KqlPanopticonError, Result};
+use crate::query_job::format_csv_value;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Format a job's result file can be converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultFormat {
+    Csv,
+    /// Rows with `metadata` and `columns`, as written by jobs
+    Json,
+    /// One row object per line
+    Ndjson,
+}
+
+/// Outcome of converting a result file
+#[derive(Debug, Clone)]
+pub struct ReexportOutput {
+    pub path: PathBuf,
+    pub rows: usize,
+}
+
+/// Rows read from a result file, with whatever metadata came with them
+struct ResultTable {
+    columns: Vec<String>,
+    /// Column types (`string` where the source doesn't say)
+    types: Vec<String>,
+    rows: Vec<Map<String, Value>>,
+    metadata: Option<Value>,
+}
+
+impl ResultFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ResultFormat::Csv => "csv",
+            ResultFormat::Json => "json",
+            ResultFormat::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResultFormat::Csv => "CSV",
+            ResultFormat::Json => "JSON",
+            ResultFormat::Ndjson => "NDJSON",
+        }
+    }
+
+    /// Format of a result file, by extension
+    pub fn of_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(ResultFormat::Csv),
+            "json" => Some(ResultFormat::Json),
+            "ndjson" => Some(ResultFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a job's CSV or JSON result file to another format, beside the original
+///
+/// CSV values are typed using the column types in the `<name>.meta.json` sidecar when one
+/// exists; otherwise they stay strings. Existing files are never overwritten.
+pub fn reexport(path: &Path, format: ResultFormat) -> Result<ReexportOutput> {
+    let source = ResultFormat::of_path(path);
+    if source == Some(format) {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "{} is already {}",
+            path.display(),
+            format.label()
+        )));
+    }
+    let output_path = path.with_extension(format.extension());
+    if output_path.exists() {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "{} already exists",
+            output_path.display()
+        )));
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let mut table = match source {
+        Some(ResultFormat::Csv) => read_csv(&content, read_sidecar(path)),
+        Some(ResultFormat::Json) => read_json(&content)?,
+        _ => {
+            return Err(KqlPanopticonError::InvalidConfiguration(format!(
+                "Cannot re-export {}: only CSV and JSON results are supported",
+                path.display()
+            )))
+        }
+    };
+
+    let source_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let metadata = table
+        .metadata
+        .get_or_insert_with(|| Value::Object(Map::new()));
+    if let Some(metadata) = metadata.as_object_mut() {
+        metadata.insert("converted_from".to_string(), source_name.into());
+    }
+
+    let output = match format {
+        ResultFormat::Csv => {
+            // CSV can't carry provenance, so it goes in a sidecar, as for job output
+            let sidecar_path = output_path.with_extension("meta.json");
+            if !sidecar_path.exists() {
+                let sidecar = serde_json::json!({
+                    "metadata": table.metadata,
+                    "columns": table.columns_json(),
+                });
+                std::fs::write(&sidecar_path, serde_json::to_string_pretty(&sidecar)?)?;
+            }
+            table.to_csv()
+        }
+        ResultFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "metadata": table.metadata,
+            "columns": table.columns_json(),
+            "rows": table.rows,
+        }))?,
+        ResultFormat::Ndjson => {
+            let mut output = String::new();
+            for row in &table.rows {
+                output.push_str(&serde_json::to_string(row)?);
+                output.push('\n');
+            }
+            output
+        }
+    };
+
+    std::fs::write(&output_path, output)?;
+    Ok(ReexportOutput {
+        path: output_path,
+        rows: table.rows.len(),
+    })
+}
+
+impl ResultTable {
+    fn columns_json(&self) -> Vec<Value> {
+        self.columns
+            .iter()
+            .zip(&self.types)
+            .map(|(name, column_type)| serde_json::json!({ "name": name, "type": column_type }))
+            .collect()
+    }
+
+    fn to_csv(&self) -> String {
+        let line = |values: Vec<String>| values.join(",") + "\n";
+        let mut output = line(
+            self.columns
+                .iter()
+                .map(|name| format_csv_value(&Value::String(name.clone())))
+                .collect(),
+        );
+        for row in &self.rows {
+            output.push_str(&line(
+                self.columns
+                    .iter()
+                    .map(|name| format_csv_value(row.get(name).unwrap_or(&Value::Null)))
+                    .collect(),
+            ));
+        }
+        output
+    }
+}
+
+/// Read the `<name>.meta.json` sidecar written next to a CSV result, if there is one
+fn read_sidecar(path: &Path) -> Option<Value> {
+    let content = std::fs::read_to_string(path.with_extension("meta.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn read_csv(content: &str, sidecar: Option<Value>) -> ResultTable {
+    let mut records = crate::post_filter::split_csv_records(content).into_iter();
+    let columns = records.next().map(|(_, header)| header).unwrap_or_default();
+
+    let sidecar_types: Vec<(String, String)> = sidecar
+        .as_ref()
+        .and_then(|sidecar| sidecar["columns"].as_array())
+        .map(|columns| {
+            columns
+                .iter()
+                .filter_map(|c| Some((c["name"].as_str()?.into(), c["type"].as_str()?.into())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let types: Vec<String> = columns
+        .iter()
+        .map(|name| {
+            sidecar_types
+                .iter()
+                .find(|(column, _)| column == name)
+                .map(|(_, column_type)| column_type.clone())
+                .unwrap_or_else(|| "string".to_string())
+        })
+        .collect();
+
+    let rows = records
+        .filter(|(raw, _)| !raw.is_empty())
+        .map(|(_, fields)| {
+            columns
+                .iter()
+                .zip(&types)
+                .zip(fields)
+                .map(|((name, column_type), field)| (name.clone(), typed_value(field, column_type)))
+                .collect()
+        })
+        .collect();
+
+    ResultTable {
+        columns,
+        types,
+        rows,
+        metadata: sidecar.map(|sidecar| sidecar["metadata"].clone()),
+    }
+}
+
+fn read_json(content: &str) -> Result<ResultTable> {
+    let mut result: Value = serde_json::from_str(content)?;
+    let (columns, types) = result["columns"]
+        .as_array()
+        .map(|columns| {
+            columns
+                .iter()
+                .filter_map(|c| {
+                    let name = c["name"].as_str()?.to_string();
+                    let column_type = c["type"].as_str().unwrap_or("string").to_string();
+                    Some((name, column_type))
+                })
+                .unzip()
+        })
+        .unwrap_or_default();
+    let rows = result["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| row.as_object().cloned())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(ResultTable {
+        columns,
+        types,
+        rows,
+        metadata: Some(result["metadata"].take()).filter(|m| !m.is_null()),
+    })
+}
+
+/// Turn a CSV field back into a JSON value of its column's Kusto type
+fn typed_value(field: String, column_type: &str) -> Value {
+    if field.is_empty() && column_type != "string" {
+        return Value::Null;
+    }
+    let parsed = match column_type {
+        "int" | "long" | "real" | "bool" | "dynamic" => serde_json::from_str(&field).ok(),
+        _ => None,
+    };
+    parsed.unwrap_or(Value::String(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reexport_csv() {
+        let dir = std::env::temp_dir().join(format!("kql-reexport-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let csv = dir.join("hunt.csv");
+        std::fs::write(
+            &csv,
+            "Account,Count,Details\nalice,3,\"{\"\"ip\"\":\"\"10.0.0.1\"\"}\"\nbob,,x\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("hunt.meta.json"),
+            r#"{"metadata":{"workspace":"ws-eu"},"columns":[{"name":"Account","type":"string"},{"name":"Count","type":"long"},{"name":"Details","type":"dynamic"}]}"#,
+        )
+        .unwrap();
+
+        let output = reexport(&csv, ResultFormat::Ndjson).unwrap();
+        assert_eq!(output.rows, 2);
+        let ndjson = std::fs::read_to_string(&output.path).unwrap();
+        assert_eq!(
+            ndjson,
+            "{\"Account\":\"alice\",\"Count\":3,\"Details\":{\"ip\":\"10.0.0.1\"}}\n{\"Account\":\"bob\",\"Count\":null,\"Details\":\"x\"}\n"
+        );
+
+        let output = reexport(&csv, ResultFormat::Json).unwrap();
+        let json: Value =
+            serde_json::from_str(&std::fs::read_to_string(&output.path).unwrap()).unwrap();
+        assert_eq!(json["metadata"]["workspace"], "ws-eu");
+        assert_eq!(json["metadata"]["converted_from"], "hunt.csv");
+        assert_eq!(json["columns"][1]["type"], "long");
+        assert_eq!(json["rows"][0]["Count"], 3);
+
+        // Existing files and same-format conversions are refused
+        assert!(reexport(&csv, ResultFormat::Json).is_err());
+        assert!(reexport(&csv, ResultFormat::Csv).is_err());
+
+        // Back to CSV from JSON reproduces the original rows
+        std::fs::remove_file(&csv).unwrap();
+        let output = reexport(&dir.join("hunt.json"), ResultFormat::Csv).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&output.path).unwrap(),
+            "Account,Count,Details\nalice,3,\"{\"\"ip\"\":\"\"10.0.0.1\"\"}\"\nbob,,x\n"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
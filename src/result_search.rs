@@ -0,0 +1,189 @@
+use crate::error::{KqlPanopticonError, Result};
+use crate::output_index::OutputIndex;
+use crate::session::Session;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Matching lines kept per file for previews
+const SAMPLE_LINES: usize = 5;
+
+/// Characters of a matching line kept for previews
+const SAMPLE_WIDTH: usize = 200;
+
+/// Extensions of the result files written by jobs
+const RESULT_EXTENSIONS: &[&str] = &["csv", "json", "jsonl"];
+
+/// A result file to search and the workspace whose results it holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchTarget {
+    pub path: PathBuf,
+    pub workspace: String,
+}
+
+/// A result file with lines matching a search
+#[derive(Debug, Clone)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub workspace: String,
+    /// Number of matching lines
+    pub lines: usize,
+    /// The first matching lines, as (1-based line number, text)
+    pub samples: Vec<(usize, String)>,
+}
+
+/// Build a case-insensitive matcher for `pattern`, taken literally unless `regex` is set
+pub fn matcher(pattern: &str, regex: bool) -> Result<Regex> {
+    if pattern.is_empty() {
+        return Err(KqlPanopticonError::InvalidConfiguration(
+            "Search pattern is empty".to_string(),
+        ));
+    }
+    let pattern = if regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+    Regex::new(&format!("(?i){}", pattern))
+        .map_err(|e| KqlPanopticonError::InvalidConfiguration(format!("Invalid regex: {}", e)))
+}
+
+/// Result files under an output folder
+/// The workspace is looked up in the output index, or else taken from the
+/// `<subscription>/<workspace>/<run>/` folder layout.
+pub fn files_under(output_folder: &Path) -> Vec<SearchTarget> {
+    let index = OutputIndex::load(output_folder);
+    let mut targets: Vec<SearchTarget> = walkdir::WalkDir::new(output_folder)
+        .min_depth(2)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_result_file(entry.path()))
+        .map(|entry| {
+            let workspace = match index.lookup(output_folder, entry.path()) {
+                Some(indexed) => indexed.name.clone(),
+                None => entry
+                    .path()
+                    .parent()
+                    .and_then(Path::parent)
+                    .and_then(Path::file_name)
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            };
+            SearchTarget {
+                path: entry.into_path(),
+                workspace,
+            }
+        })
+        .collect();
+    targets.sort_by(|a, b| a.path.cmp(&b.path));
+    targets
+}
+
+/// Result files written by a saved session's jobs
+pub fn session_files(session: &Session) -> Vec<SearchTarget> {
+    dedup(
+        session
+            .jobs
+            .iter()
+            .filter_map(|job| {
+                Some(SearchTarget {
+                    path: job.output_path.clone()?,
+                    workspace: job.workspace_name.clone(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Drop repeated paths, keeping the first
+pub fn dedup(targets: Vec<SearchTarget>) -> Vec<SearchTarget> {
+    let mut seen = std::collections::HashSet::new();
+    targets
+        .into_iter()
+        .filter(|target| seen.insert(target.path.clone()))
+        .collect()
+}
+
+fn is_result_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RESULT_EXTENSIONS.contains(&ext))
+}
+
+/// Search files line by line, returning those with matches in the order given
+/// Files that can't be read (e.g. deleted since the run) are skipped.
+pub fn search(targets: &[SearchTarget], matcher: &Regex) -> Vec<FileMatches> {
+    targets
+        .iter()
+        .filter_map(|target| {
+            let content = match std::fs::read_to_string(&target.path) {
+                Ok(content) => content,
+                Err(e) => {
+                    log::warn!("Skipping {}: {}", target.path.display(), e);
+                    return None;
+                }
+            };
+            let mut matches = FileMatches {
+                path: target.path.clone(),
+                workspace: target.workspace.clone(),
+                lines: 0,
+                samples: Vec::new(),
+            };
+            for (idx, line) in content.lines().enumerate() {
+                if matcher.is_match(line) {
+                    matches.lines += 1;
+                    if matches.samples.len() < SAMPLE_LINES {
+                        let text = line.trim().chars().take(SAMPLE_WIDTH).collect();
+                        matches.samples.push((idx + 1, text));
+                    }
+                }
+            }
+            (matches.lines > 0).then_some(matches)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_output_folder() {
+        let dir = std::env::temp_dir().join(format!("kql-result-search-{}", std::process::id()));
+        let write = |relative: &str, content: &str| {
+            let path = dir.join(relative);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "prod/ws-eu/2024-05-01_10-00-00/signins.csv",
+            "IPAddress,User\n10.0.0.1,alice\n10x0x0x1,bob\n10.0.0.1,carol\n",
+        );
+        write(
+            "prod/ws-us/2024-05-01_10-00-00/signins.json",
+            "{\"rows\": [[\"10.2.0.1\", \"dave\"]]}\n",
+        );
+        write(
+            "prod/ws-us/2024-05-01_10-00-00/signins.meta.txt",
+            "10.0.0.1",
+        );
+
+        let targets = files_under(&dir);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].workspace, "ws-eu");
+
+        // Literal by default: the dots don't match any character
+        let found = search(&targets, &matcher("10.0.0.1", false).unwrap());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].lines, 2);
+        assert_eq!(found[0].samples[0], (2, "10.0.0.1,alice".to_string()));
+
+        let found = search(&targets, &matcher(r"10\.\d\.0\.1\b", true).unwrap());
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].workspace, "ws-us");
+
+        assert_eq!(search(&targets, &matcher("DAVE", false).unwrap()).len(), 1);
+        assert!(matcher("(", true).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::config::RetentionPolicy;
+use crate::error::Result;
+use crate::session::Session;
+use chrono::{DateTime, Local, NaiveDateTime};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Format of the per-run folder names under `output/<subscription>/<workspace>/`
+const RUN_DIR_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// Disk usage of a saved session and the run folders its jobs wrote to
+#[derive(Debug, Clone)]
+pub struct SessionUsage {
+    pub name: String,
+    /// When the session was last saved (None if the timestamp can't be parsed)
+    pub last_saved: Option<DateTime<Local>>,
+    /// Size of the session file
+    pub session_bytes: u64,
+    /// Run folders holding the session's job output
+    pub run_dirs: Vec<PathBuf>,
+    /// Combined size of `run_dirs`
+    pub output_bytes: u64,
+}
+
+impl SessionUsage {
+    /// Measure a saved session
+    pub fn of(session: &Session) -> Result<Self> {
+        let path = Session::file_path(&session.name)?;
+        let session_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+        let mut run_dirs: Vec<PathBuf> = session
+            .jobs
+            .iter()
+            .filter_map(|job| job.output_path.as_deref()?.parent())
+            .filter(|dir| is_run_dir(dir))
+            .map(Path::to_path_buf)
+            .collect();
+        run_dirs.sort();
+        run_dirs.dedup();
+        let output_bytes = run_dirs.iter().map(|dir| dir_size(dir)).sum();
+
+        Ok(Self {
+            name: session.name.clone(),
+            last_saved: DateTime::parse_from_rfc3339(&session.last_saved)
+                .ok()
+                .map(|dt| dt.with_timezone(&Local)),
+            session_bytes,
+            run_dirs,
+            output_bytes,
+        })
+    }
+
+    /// Session file plus output size
+    pub fn total_bytes(&self) -> u64 {
+        self.session_bytes + self.output_bytes
+    }
+}
+
+/// Measure every saved session, most recently saved first
+pub fn all_sessions() -> Result<Vec<SessionUsage>> {
+    let mut usage = Vec::new();
+    for name in Session::list_all()? {
+        match Session::load(&name) {
+            Ok(session) => usage.push(SessionUsage::of(&session)?),
+            Err(e) => log::warn!("Skipping unreadable session '{}': {}", name, e),
+        }
+    }
+    usage.sort_by_key(|u| std::cmp::Reverse(u.last_saved));
+    Ok(usage)
+}
+
+/// Sessions and run folders selected for deletion
+#[derive(Debug, Clone, Default)]
+pub struct CleanPlan {
+    pub sessions: Vec<String>,
+    pub run_dirs: Vec<PathBuf>,
+    /// Disk space freed by applying the plan
+    pub bytes: u64,
+}
+
+impl CleanPlan {
+    /// Delete the given sessions along with output no remaining session refers to
+    pub fn for_sessions(names: &[String], all: &[SessionUsage]) -> Self {
+        let mut plan = Self::default();
+        let kept_dirs: HashSet<&PathBuf> = all
+            .iter()
+            .filter(|usage| !names.contains(&usage.name))
+            .flat_map(|usage| &usage.run_dirs)
+            .collect();
+
+        for usage in all.iter().filter(|usage| names.contains(&usage.name)) {
+            plan.sessions.push(usage.name.clone());
+            plan.bytes += usage.session_bytes;
+            for dir in &usage.run_dirs {
+                if !kept_dirs.contains(dir) && !plan.run_dirs.contains(dir) {
+                    plan.bytes += dir_size(dir);
+                    plan.run_dirs.push(dir.clone());
+                }
+            }
+        }
+        plan
+    }
+
+    /// Delete the given session files only, keeping all output
+    pub fn sessions_only(names: &[String], all: &[SessionUsage]) -> Self {
+        let mut plan = Self::default();
+        for usage in all.iter().filter(|usage| names.contains(&usage.name)) {
+            plan.sessions.push(usage.name.clone());
+            plan.bytes += usage.session_bytes;
+        }
+        plan
+    }
+
+    /// Select what `policy` says to delete from the saved sessions and `output_folder`
+    pub fn for_policy(
+        policy: &RetentionPolicy,
+        all: &[SessionUsage],
+        output_folder: &Path,
+        now: DateTime<Local>,
+    ) -> Self {
+        // `all` is newest first, so anything past the count limit is the oldest
+        let expired: Vec<String> = all
+            .iter()
+            .enumerate()
+            .filter(|(idx, usage)| {
+                let over_count = policy.max_sessions.is_some_and(|max| *idx >= max);
+                let too_old = match (policy.max_session_age_days, usage.last_saved) {
+                    (Some(days), Some(saved)) => (now - saved).num_days() >= days as i64,
+                    _ => false,
+                };
+                over_count || too_old
+            })
+            .map(|(_, usage)| usage.name.clone())
+            .collect();
+        let mut plan = Self::for_sessions(&expired, all);
+
+        if let Some(max_mb) = policy.max_output_mb {
+            let limit = max_mb * 1024 * 1024;
+            let mut remaining: Vec<(PathBuf, u64)> = run_dirs(output_folder)
+                .into_iter()
+                .filter(|dir| !plan.run_dirs.contains(dir))
+                .map(|dir| {
+                    let size = dir_size(&dir);
+                    (dir, size)
+                })
+                .collect();
+            // Oldest runs first (folder names are timestamps)
+            remaining.sort_by(|(a, _), (b, _)| a.file_name().cmp(&b.file_name()));
+
+            let mut total: u64 = remaining.iter().map(|(_, size)| size).sum();
+            for (dir, size) in remaining {
+                if total <= limit {
+                    break;
+                }
+                total -= size;
+                plan.bytes += size;
+                plan.run_dirs.push(dir);
+            }
+        }
+
+        plan
+    }
+
+    /// Check if there is nothing to delete
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty() && self.run_dirs.is_empty()
+    }
+
+    /// Delete the planned sessions and run folders
+    pub fn apply(&self) -> Result<()> {
+        for name in &self.sessions {
+            Session::delete(name)?;
+        }
+        for dir in &self.run_dirs {
+            if dir.exists() {
+                std::fs::remove_dir_all(dir)?;
+            }
+            // Drop workspace and subscription folders left empty
+            for parent in dir.ancestors().skip(1).take(2) {
+                if std::fs::remove_dir(parent).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check if a folder is a per-run output folder (named by its run timestamp)
+pub(crate) fn is_run_dir(dir: &Path) -> bool {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| NaiveDateTime::parse_from_str(name, RUN_DIR_FORMAT).is_ok())
+}
+
+/// Find the run folders under an output folder (`<subscription>/<workspace>/<timestamp>`)
+fn run_dirs(output_folder: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(output_folder)
+        .min_depth(3)
+        .max_depth(3)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir() && is_run_dir(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Total size of the files under a folder
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn usage(name: &str, days_old: i64, run_dirs: &[&str], now: DateTime<Local>) -> SessionUsage {
+        SessionUsage {
+            name: name.to_string(),
+            last_saved: Some(now - Duration::days(days_old)),
+            session_bytes: 10,
+            run_dirs: run_dirs.iter().map(PathBuf::from).collect(),
+            output_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_policy_selects_old_and_excess_sessions() {
+        let now = Local::now();
+        let all = vec![
+            usage("today", 0, &["out/a/b/2024-01-03_00-00-00"], now),
+            usage("last-week", 7, &["out/a/b/2024-01-02_00-00-00"], now),
+            usage("last-month", 30, &["out/a/b/2024-01-02_00-00-00"], now),
+            usage("last-year", 365, &["out/a/b/2024-01-01_00-00-00"], now),
+        ];
+
+        let by_age = RetentionPolicy {
+            max_session_age_days: Some(14),
+            ..Default::default()
+        };
+        let plan = CleanPlan::for_policy(&by_age, &all, Path::new("missing"), now);
+        assert_eq!(plan.sessions, vec!["last-month", "last-year"]);
+        // Output shared with a kept session stays
+        assert_eq!(
+            plan.run_dirs,
+            vec![PathBuf::from("out/a/b/2024-01-01_00-00-00")]
+        );
+
+        let by_count = RetentionPolicy {
+            max_sessions: Some(1),
+            ..Default::default()
+        };
+        let plan = CleanPlan::for_policy(&by_count, &all, Path::new("missing"), now);
+        assert_eq!(plan.sessions, vec!["last-week", "last-month", "last-year"]);
+        assert_eq!(plan.run_dirs.len(), 2);
+    }
+
+    #[test]
+    fn test_sessions_only_keeps_output() {
+        let now = Local::now();
+        let all = vec![
+            usage("a", 0, &["out/a/b/2024-01-01_00-00-00"], now),
+            usage("b", 0, &["out/a/b/2024-01-02_00-00-00"], now),
+        ];
+        let plan = CleanPlan::sessions_only(&["a".to_string(), "b".to_string()], &all);
+        assert_eq!(plan.sessions, vec!["a", "b"]);
+        assert!(plan.run_dirs.is_empty());
+        assert_eq!(plan.bytes, 20);
+    }
+
+    #[test]
+    fn test_is_run_dir() {
+        assert!(is_run_dir(Path::new("output/sub/ws/2025-11-08_18-46-20")));
+        assert!(!is_run_dir(Path::new("output")));
+        assert!(!is_run_dir(Path::new("output/sub/ws/notes")));
+    }
+}
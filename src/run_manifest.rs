@@ -0,0 +1,177 @@
+use crate::error::Result;
+use crate::paths;
+use crate::query_job::QueryJobResult;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Completion state of a query/workspace pair in a pack run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestStatus {
+    Pending,
+    Completed,
+    Failed,
+}
+
+/// One query/workspace pair of a pack run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub query_name: String,
+    pub workspace_id: String,
+    pub workspace_name: String,
+    pub status: ManifestStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+}
+
+/// Tracks which query/workspace pairs of a pack run have completed, so an interrupted
+/// run can be resumed. Stored in <data_dir>/runs/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub pack_name: String,
+    /// Absolute path of the pack file (used to find resumable runs)
+    pub pack_path: PathBuf,
+    pub started_at: String,
+    pub updated_at: String,
+    pub entries: Vec<ManifestEntry>,
+    /// Where this manifest is stored (not serialized)
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl RunManifest {
+    /// Create a manifest for a new run; the file is written on the first save
+    pub fn new(pack_name: &str, pack_path: &Path) -> Result<Self> {
+        let now = Utc::now();
+        let file_name = format!(
+            "{}-{}.json",
+            paths::sanitize_file_name(pack_name),
+            now.format("%Y-%m-%d_%H%M%S")
+        );
+
+        Ok(Self {
+            pack_name: pack_name.to_string(),
+            pack_path: absolute(pack_path),
+            started_at: now.to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+            entries: Vec::new(),
+            path: get_runs_dir()?.join(file_name),
+        })
+    }
+
+    /// Load a manifest from a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let mut manifest: RunManifest = serde_json::from_str(&json)?;
+        manifest.path = path.to_path_buf();
+        Ok(manifest)
+    }
+
+    /// Write the manifest to disk
+    pub fn save(&mut self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        self.updated_at = Utc::now().to_rfc3339();
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Path of the manifest file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Find the most recent incomplete run of a pack, if any
+    pub fn find_incomplete(pack_path: &Path) -> Option<Self> {
+        let pack_path = absolute(pack_path);
+        let runs_dir = get_runs_dir().ok()?;
+
+        fs::read_dir(runs_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| Self::load(&entry.path()).ok())
+            .filter(|m| m.pack_path == pack_path && !m.is_complete())
+            .max_by(|a, b| a.started_at.cmp(&b.started_at))
+    }
+
+    /// Add a pair as pending unless it is already tracked
+    pub fn ensure_entry(&mut self, query_name: &str, workspace_id: &str, workspace_name: &str) {
+        if self.entry(query_name, workspace_id).is_none() {
+            self.entries.push(ManifestEntry {
+                query_name: query_name.to_string(),
+                workspace_id: workspace_id.to_string(),
+                workspace_name: workspace_name.to_string(),
+                status: ManifestStatus::Pending,
+                output_path: None,
+                error: None,
+                finished_at: None,
+            });
+        }
+    }
+
+    fn entry(&self, query_name: &str, workspace_id: &str) -> Option<&ManifestEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.query_name == query_name && e.workspace_id == workspace_id)
+    }
+
+    /// Whether a pair has already completed successfully (and can be skipped on resume)
+    pub fn is_completed(&self, query_name: &str, workspace_id: &str) -> bool {
+        self.entry(query_name, workspace_id)
+            .is_some_and(|e| e.status == ManifestStatus::Completed)
+    }
+
+    /// Record the outcome of a pair
+    pub fn record(&mut self, query_name: &str, result: &QueryJobResult) {
+        self.ensure_entry(query_name, &result.workspace_id, &result.workspace_name);
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|e| e.query_name == query_name && e.workspace_id == result.workspace_id)
+        {
+            match &result.result {
+                Ok(success) => {
+                    entry.status = ManifestStatus::Completed;
+                    entry.output_path = Some(success.output_path.clone());
+                    entry.error = None;
+                }
+                Err(e) => {
+                    entry.status = ManifestStatus::Failed;
+                    entry.error = Some(e.to_string());
+                }
+            }
+            entry.finished_at = Some(Utc::now().to_rfc3339());
+        }
+    }
+
+    /// Number of pairs that completed successfully
+    pub fn completed_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.status == ManifestStatus::Completed)
+            .count()
+    }
+
+    /// Whether every tracked pair completed successfully
+    pub fn is_complete(&self) -> bool {
+        self.completed_count() == self.entries.len()
+    }
+}
+
+/// Get the run manifests directory path (<data_dir>/runs)
+pub fn get_runs_dir() -> Result<PathBuf> {
+    Ok(paths::data_dir()?.join("runs"))
+}
+
+/// Resolve a path to an absolute one so TUI and CLI runs of the same pack match
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
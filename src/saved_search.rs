@@ -0,0 +1,176 @@
+use crate::query_pack::{PackQuery, QueryPack};
+use serde::Deserialize;
+
+/// A saved search or function stored in a Log Analytics workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearch {
+    /// Display name
+    pub name: String,
+    pub category: String,
+    pub query: String,
+    /// Alias the search is callable by, when it is saved as a function
+    pub function_alias: Option<String>,
+    /// Function parameter list, e.g. `start:datetime, user:string`
+    pub function_parameters: Option<String>,
+}
+
+/// Response from the savedSearches API
+#[derive(Debug, Deserialize)]
+pub(crate) struct SavedSearchListResponse {
+    pub value: Vec<SavedSearchResource>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SavedSearchResource {
+    pub properties: SavedSearchProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SavedSearchProperties {
+    #[serde(default)]
+    pub category: String,
+    pub display_name: String,
+    pub query: String,
+    #[serde(default)]
+    pub function_alias: Option<String>,
+    #[serde(default)]
+    pub function_parameters: Option<String>,
+}
+
+impl From<SavedSearchResource> for SavedSearch {
+    fn from(resource: SavedSearchResource) -> Self {
+        let properties = resource.properties;
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        SavedSearch {
+            name: properties.display_name,
+            category: properties.category,
+            query: properties.query,
+            function_alias: non_empty(properties.function_alias),
+            function_parameters: non_empty(properties.function_parameters),
+        }
+    }
+}
+
+impl SavedSearch {
+    /// Check if the query needs arguments, so it can't run on its own
+    pub fn has_parameters(&self) -> bool {
+        self.function_parameters.is_some()
+    }
+
+    /// How the function is called, e.g. `SignIns(start:datetime)` (None for plain searches)
+    pub fn signature(&self) -> Option<String> {
+        let alias = self.function_alias.as_ref()?;
+        Some(format!(
+            "{}({})",
+            alias,
+            self.function_parameters.as_deref().unwrap_or("")
+        ))
+    }
+
+    /// Pack tags for the search (its category)
+    fn tags(&self) -> Vec<String> {
+        let category = self.category.trim();
+        if category.is_empty() {
+            Vec::new()
+        } else {
+            vec![category.to_string()]
+        }
+    }
+}
+
+/// Build a query pack from a workspace's saved searches
+/// Categories become tags; functions note their signature in the description, since
+/// functions with parameters need them supplied (e.g. with `let`) before they can run.
+pub fn to_query_pack(workspace_name: &str, searches: &[SavedSearch]) -> QueryPack {
+    let mut queries: Vec<PackQuery> = Vec::new();
+    for search in searches {
+        // Pack query names should be unique; saved search display names needn't be
+        let mut name = search.name.clone();
+        let mut n = 2;
+        while queries.iter().any(|q| q.name == name) {
+            name = format!("{} ({})", search.name, n);
+            n += 1;
+        }
+
+        queries.push(PackQuery {
+            name,
+            description: search
+                .signature()
+                .map(|signature| format!("Workspace function {}", signature)),
+            query: search.query.clone(),
+            query_file: None,
+            tags: search.tags(),
+            mitre: Vec::new(),
+            severity: None,
+            timeout_secs: None,
+            retry_count: None,
+            max_rows: None,
+        });
+    }
+
+    let mut pack = QueryPack::from_queries(
+        format!("{} saved searches", workspace_name),
+        Some(format!(
+            "Saved searches and functions from workspace {}",
+            workspace_name
+        )),
+        queries,
+        None,
+    );
+    // A single search is written in the simple form, which has no place for its tags
+    if let (Some(search), None) = (searches.first(), &pack.queries) {
+        pack.tags = search.tags();
+    }
+    pack
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_searches_to_pack() {
+        let response: SavedSearchListResponse = serde_json::from_value(serde_json::json!({
+            "value": [
+                {
+                    "id": "/subscriptions/x/savedSearches/a",
+                    "properties": {
+                        "category": "Hunting",
+                        "displayName": "Rare processes",
+                        "query": "SecurityEvent | summarize count() by Process",
+                        "functionAlias": ""
+                    }
+                },
+                {
+                    "id": "/subscriptions/x/savedSearches/b",
+                    "properties": {
+                        "category": "Parsers",
+                        "displayName": "Rare processes",
+                        "query": "SigninLogs | where TimeGenerated > start",
+                        "functionAlias": "SignIns",
+                        "functionParameters": "start:datetime"
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+        let searches: Vec<SavedSearch> = response.value.into_iter().map(Into::into).collect();
+        assert_eq!(searches[0].function_alias, None);
+        assert_eq!(
+            searches[1].signature().as_deref(),
+            Some("SignIns(start:datetime)")
+        );
+        assert!(searches[1].has_parameters());
+
+        let pack = to_query_pack("ws-eu", &searches);
+        pack.validate().unwrap();
+        let queries = pack.queries.unwrap();
+        assert_eq!(queries[0].tags, vec!["Hunting"]);
+        assert_eq!(queries[1].name, "Rare processes (2)");
+        assert_eq!(
+            queries[1].description.as_deref(),
+            Some("Workspace function SignIns(start:datetime)")
+        );
+    }
+}
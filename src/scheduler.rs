@@ -0,0 +1,316 @@
+use crate::query_pack::Severity;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Order in which queued jobs get a free slot (higher first, then first queued)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum JobPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    /// Bumped by hand to jump the queue
+    Urgent,
+}
+
+impl JobPriority {
+    const ALL: [JobPriority; 4] = [
+        JobPriority::Low,
+        JobPriority::Normal,
+        JobPriority::High,
+        JobPriority::Urgent,
+    ];
+
+    /// Priority of a pack query's jobs, from its severity
+    /// Urgent is left for jobs bumped by hand.
+    pub fn from_severity(severity: Option<Severity>) -> Self {
+        match severity {
+            Some(Severity::Info | Severity::Low) => JobPriority::Low,
+            None | Some(Severity::Medium) => JobPriority::Normal,
+            Some(Severity::High | Severity::Critical) => JobPriority::High,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobPriority::Low => "low",
+            JobPriority::Normal => "normal",
+            JobPriority::High => "high",
+            JobPriority::Urgent => "urgent",
+        }
+    }
+
+    /// One step up (Urgent stays Urgent)
+    pub fn raised(self) -> Self {
+        Self::ALL[(self as usize + 1).min(Self::ALL.len() - 1)]
+    }
+
+    /// One step down (Low stays Low)
+    pub fn lowered(self) -> Self {
+        Self::ALL[(self as usize).saturating_sub(1)]
+    }
+}
+
+/// A job's priority, shared with the scheduler so it can change while the job waits
+/// Clones share the same value.
+#[derive(Debug, Clone, Default)]
+pub struct SharedPriority(Arc<AtomicU8>);
+
+impl SharedPriority {
+    pub fn new(priority: JobPriority) -> Self {
+        Self(Arc::new(AtomicU8::new(priority as u8)))
+    }
+
+    pub fn get(&self) -> JobPriority {
+        JobPriority::ALL[self.0.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set(&self, priority: JobPriority) {
+        self.0.store(priority as u8, Ordering::Relaxed);
+    }
+}
+
+/// Limits how many queries run at once, overall and against any one workspace
+///
+/// Azure throttles per workspace, so a pack's queries against the same workspace queue for
+/// that workspace's slots while queries for other workspaces use the free global slots.
+/// Waiting queries get slots by priority rather than in arrival order.
+/// Clones share the same limits.
+#[derive(Debug, Clone)]
+pub struct QueryScheduler {
+    global: Arc<PriorityGate>,
+    per_workspace: usize,
+    workspaces: Arc<Mutex<HashMap<String, Arc<PriorityGate>>>>,
+}
+
+/// Slots held by a running query, released on drop
+#[derive(Debug)]
+pub struct SchedulerPermit {
+    _workspace: GatePermit,
+    _global: GatePermit,
+}
+
+impl QueryScheduler {
+    /// Allow `max_concurrency` queries at once, at most `per_workspace` on the same workspace
+    pub fn new(max_concurrency: usize, per_workspace: usize) -> Self {
+        Self {
+            global: PriorityGate::new(max_concurrency.max(1)),
+            per_workspace: per_workspace.max(1),
+            workspaces: Arc::default(),
+        }
+    }
+
+    /// Most queries a run of `queries` x `workspaces` jobs can have in flight at once
+    pub fn estimated_concurrency(
+        max_concurrency: usize,
+        per_workspace: usize,
+        workspaces: usize,
+        queries: usize,
+    ) -> usize {
+        (workspaces * queries.min(per_workspace.max(1))).min(max_concurrency.max(1))
+    }
+
+    /// Wait for a slot on `workspace_id` and a global slot
+    /// The workspace slot is taken first, so queries waiting on a busy workspace don't hold
+    /// global slots other workspaces could use.
+    pub async fn acquire(&self, workspace_id: &str, priority: &SharedPriority) -> SchedulerPermit {
+        let workspace = self.workspace_gate(workspace_id).acquire(priority).await;
+        let global = self.global.acquire(priority).await;
+        SchedulerPermit {
+            _workspace: workspace,
+            _global: global,
+        }
+    }
+
+    fn workspace_gate(&self, workspace_id: &str) -> Arc<PriorityGate> {
+        let mut workspaces = self.workspaces.lock().unwrap_or_else(|e| e.into_inner());
+        workspaces
+            .entry(workspace_id.to_string())
+            .or_insert_with(|| PriorityGate::new(self.per_workspace))
+            .clone()
+    }
+}
+
+/// Counting semaphore that hands freed slots to the highest-priority waiter
+#[derive(Debug)]
+struct PriorityGate {
+    state: Mutex<GateState>,
+}
+
+#[derive(Debug)]
+struct GateState {
+    available: usize,
+    waiting: Vec<Waiter>,
+    next_seq: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority: SharedPriority,
+    /// Queue position, so equal priorities keep arrival order
+    seq: u64,
+    tx: oneshot::Sender<GatePermit>,
+}
+
+/// One slot of a gate, handed back when dropped
+#[derive(Debug)]
+struct GatePermit {
+    /// None once the slot has been handed back
+    gate: Option<Arc<PriorityGate>>,
+}
+
+impl PriorityGate {
+    fn new(slots: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(GateState {
+                available: slots,
+                waiting: Vec::new(),
+                next_seq: 0,
+            }),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, GateState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn acquire(self: &Arc<Self>, priority: &SharedPriority) -> GatePermit {
+        let rx = {
+            let mut state = self.lock();
+            if state.available > 0 {
+                state.available -= 1;
+                return GatePermit {
+                    gate: Some(self.clone()),
+                };
+            }
+            let (tx, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority: priority.clone(),
+                seq,
+                tx,
+            });
+            rx
+        };
+        // The sender is only dropped after sending, and the gate outlives its waiters
+        rx.await.expect("scheduler gates never drop waiters")
+    }
+
+    /// Hand a freed slot to the highest-priority waiter still waiting
+    fn release(self: &Arc<Self>) {
+        let mut state = self.lock();
+        while let Some(index) = state
+            .waiting
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, waiter)| (waiter.priority.get(), std::cmp::Reverse(waiter.seq)))
+            .map(|(index, _)| index)
+        {
+            let waiter = state.waiting.swap_remove(index);
+            let permit = GatePermit {
+                gate: Some(self.clone()),
+            };
+            match waiter.tx.send(permit) {
+                Ok(()) => return,
+                // The waiting task was dropped: defuse the permit (dropping it would re-lock)
+                Err(mut permit) => {
+                    permit.gate = None;
+                }
+            }
+        }
+        state.available += 1;
+    }
+}
+
+impl Drop for GatePermit {
+    fn drop(&mut self) {
+        if let Some(gate) = self.gate.take() {
+            gate.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_limits_per_workspace_and_overall() {
+        let scheduler = QueryScheduler::new(3, 1);
+        let running: Arc<Mutex<HashMap<&str, usize>>> = Arc::default();
+        let peak_total = Arc::new(AtomicUsize::new(0));
+        let peak_workspace = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = Vec::new();
+        for workspace in ["ws-a", "ws-a", "ws-a", "ws-b", "ws-b", "ws-c", "ws-d"] {
+            let scheduler = scheduler.clone();
+            let running = running.clone();
+            let peak_total = peak_total.clone();
+            let peak_workspace = peak_workspace.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = scheduler
+                    .acquire(workspace, &SharedPriority::default())
+                    .await;
+                {
+                    let mut running = running.lock().unwrap();
+                    *running.entry(workspace).or_default() += 1;
+                    peak_total.fetch_max(running.values().sum(), Ordering::SeqCst);
+                    peak_workspace.fetch_max(running[workspace], Ordering::SeqCst);
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                *running.lock().unwrap().get_mut(workspace).unwrap() -= 1;
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(peak_workspace.load(Ordering::SeqCst), 1);
+        assert_eq!(peak_total.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_waiting_jobs_run_by_priority() {
+        let scheduler = QueryScheduler::new(1, 1);
+        let held = scheduler.acquire("ws", &SharedPriority::default()).await;
+
+        let order: Arc<Mutex<Vec<&str>>> = Arc::default();
+        let mut priorities = Vec::new();
+        let mut tasks = Vec::new();
+        for (name, priority) in [
+            ("low", JobPriority::Low),
+            ("normal", JobPriority::Normal),
+            ("bumped", JobPriority::Low),
+            ("high", JobPriority::High),
+        ] {
+            let priority = SharedPriority::new(priority);
+            priorities.push(priority.clone());
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire("ws", &priority).await;
+                order.lock().unwrap().push(name);
+            }));
+            // Queue in a known order
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // Bump a job that is already waiting
+        priorities[2].set(JobPriority::Low.raised().raised().raised());
+        assert_eq!(priorities[2].get(), JobPriority::Urgent);
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["bumped", "high", "normal", "low"]
+        );
+    }
+}
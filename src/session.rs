@@ -11,7 +11,8 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 /// Session file format version
-const SESSION_VERSION: u32 = 1;
+/// v2 added workspace selection and job triage; v1 files are migrated on load.
+const SESSION_VERSION: u32 = 2;
 
 /// A saved session containing jobs and settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +28,55 @@ pub struct Session {
     /// Query pack that created this session (if any)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_from_pack: Option<String>,
+    /// Free-form tags for finding the session later
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// What the session is about, e.g. the investigation it belongs to
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Resource IDs of the workspaces selected at time of save (None in older sessions,
+    /// which leave the selection alone)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_workspaces: Option<Vec<String>>,
     /// Settings at time of save
     pub settings: SerializableSettings,
     /// Jobs at time of save
     pub jobs: Vec<SerializableJob>,
 }
 
+/// Bring a session file's JSON up to the current format
+/// Files without a version predate versioning and are read as v1.
+fn migrate(
+    name: &str,
+    mut value: serde_json::Value,
+) -> Result<serde_json::Value, KqlPanopticonError> {
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(1);
+    if version > SESSION_VERSION as u64 {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "Session '{}' uses format v{}, but this version of kql-panopticon only reads up to v{}; upgrade to open it",
+            name, version, SESSION_VERSION
+        )));
+    }
+
+    let Some(session) = value.as_object_mut() else {
+        return Ok(value);
+    };
+    if version < 2 {
+        // v1 had no workspace selection (null leaves the current one alone) and no triage
+        session
+            .entry("selected_workspaces")
+            .or_insert(serde_json::Value::Null);
+        if let Some(jobs) = session.get_mut("jobs").and_then(|j| j.as_array_mut()) {
+            for job in jobs.iter_mut().filter_map(|j| j.as_object_mut()) {
+                job.entry("triage").or_insert(serde_json::Value::Null);
+                job.entry("note").or_insert("".into());
+            }
+        }
+    }
+    session.insert("version".to_string(), SESSION_VERSION.into());
+    Ok(value)
+}
+
 /// Serializable settings (subset of SettingsModel)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerializableSettings {
@@ -43,6 +87,28 @@ pub struct SerializableSettings {
     pub export_csv: bool,
     pub export_json: bool,
     pub parse_dynamics: bool,
+    #[serde(default = "default_watch_interval_secs")]
+    pub watch_interval_secs: u64,
+    #[serde(default)]
+    pub estimate_cost: bool,
+    #[serde(default)]
+    pub csv_metadata: bool,
+    #[serde(default)]
+    pub sqlite_path: String,
+    #[serde(default)]
+    pub duplicate_jobs: crate::tui::model::settings::DuplicateJobs,
+    #[serde(default)]
+    pub keep_partial_results: bool,
+    #[serde(default)]
+    pub skip_failing_workspaces: bool,
+    #[serde(default)]
+    pub extract_iocs: bool,
+    #[serde(default)]
+    pub skip_empty_output: bool,
+}
+
+fn default_watch_interval_secs() -> u64 {
+    crate::tui::model::settings::DEFAULT_WATCH_INTERVAL_SECS
 }
 
 impl From<&SettingsModel> for SerializableSettings {
@@ -55,6 +121,45 @@ impl From<&SettingsModel> for SerializableSettings {
             export_csv: model.export_csv,
             export_json: model.export_json,
             parse_dynamics: model.parse_dynamics,
+            watch_interval_secs: model.watch_interval_secs,
+            estimate_cost: model.estimate_cost,
+            csv_metadata: model.csv_metadata,
+            sqlite_path: model.sqlite_path.clone(),
+            duplicate_jobs: model.duplicate_jobs,
+            keep_partial_results: model.keep_partial_results,
+            skip_failing_workspaces: model.skip_failing_workspaces,
+            extract_iocs: model.extract_iocs,
+            skip_empty_output: model.skip_empty_output,
+        }
+    }
+}
+
+/// Which of a session's jobs are exported as a query pack (all of them by default)
+#[derive(Debug, Clone, Default)]
+pub struct PackExportFilter {
+    /// Skip jobs that didn't complete successfully
+    pub only_successful: bool,
+    /// Only export jobs whose query name matches (jobs without a name are skipped)
+    pub query_name: Option<regex::Regex>,
+}
+
+impl PackExportFilter {
+    /// Check if every job is exported
+    pub fn is_empty(&self) -> bool {
+        !self.only_successful && self.query_name.is_none()
+    }
+
+    /// Check if `job` passes the filter
+    fn matches(&self, job: &SerializableJob) -> bool {
+        if self.only_successful && (job.status != "Completed" || job.error_message.is_some()) {
+            return false;
+        }
+        match &self.query_name {
+            Some(pattern) => job
+                .query_name
+                .as_deref()
+                .is_some_and(|name| pattern.is_match(name)),
+            None => true,
         }
     }
 }
@@ -73,6 +178,26 @@ pub struct SerializableJob {
     pub error_details: Option<crate::tui::model::jobs::JobError>, // Structured error (v2+)
     #[serde(default)]
     pub timestamp: Option<String>, // ISO 8601 / RFC3339 format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billed_bytes: Option<u64>, // Estimated billed bytes scanned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triage: Option<crate::tui::model::jobs::TriageStatus>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<PathBuf>, // Primary output file of a completed job
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>, // Errors reported alongside partial results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<crate::client::QueryStatistics>, // Query execution statistics
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uploads: Vec<crate::blob_storage::BlobUpload>, // Blob Storage uploads of the output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_name: Option<String>, // Entered with an ad-hoc run or taken from the pack
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<crate::query_pack::Severity>, // From the pack query
 }
 
 impl From<&JobState> for SerializableJob {
@@ -99,6 +224,17 @@ impl From<&JobState> for SerializableJob {
         // Extract timestamp from result if available
         let timestamp = job.result.as_ref().map(|r| r.timestamp.to_rfc3339());
 
+        let success = job.result.as_ref().and_then(|r| r.result.as_ref().ok());
+        let billed_bytes = success.and_then(|success| success.billed_bytes);
+        let output_path = success.map(|success| success.output_path.clone());
+        let warnings = success
+            .map(|success| success.warnings.clone())
+            .unwrap_or_default();
+        let statistics = success.and_then(|success| success.statistics.clone());
+        let uploads = success
+            .map(|success| success.uploads.clone())
+            .unwrap_or_default();
+
         Self {
             status: job.status.as_str().to_string(),
             workspace_name: job.workspace_name.clone(),
@@ -110,6 +246,16 @@ impl From<&JobState> for SerializableJob {
             error_message,
             error_details,
             timestamp,
+            billed_bytes,
+            triage: job.triage,
+            note: job.note.clone(),
+            output_path,
+            warnings,
+            statistics,
+            uploads,
+            query_name: job.query_name.clone(),
+            description: job.description.clone(),
+            severity: job.severity,
         }
     }
 }
@@ -128,7 +274,7 @@ impl Session {
         jobs: &[JobState],
         created_from_pack: Option<String>,
     ) -> Self {
-        let now = chrono::Local::now().to_rfc3339();
+        let now = crate::time_zone::now().to_rfc3339();
 
         Self {
             version: SESSION_VERSION,
@@ -136,6 +282,9 @@ impl Session {
             created_at: now.clone(),
             last_saved: now,
             created_from_pack,
+            tags: Vec::new(),
+            description: String::new(),
+            selected_workspaces: None,
             settings: SerializableSettings::from(settings),
             jobs: jobs.iter().map(SerializableJob::from).collect(),
         }
@@ -143,7 +292,25 @@ impl Session {
 
     /// Update the last_saved timestamp
     pub fn touch(&mut self) {
-        self.last_saved = chrono::Local::now().to_rfc3339();
+        self.last_saved = crate::time_zone::now().to_rfc3339();
+    }
+
+    /// Check that `name` can name a session file: one plain path component, so it stays in
+    /// the sessions directory
+    pub fn check_name(name: &str) -> Result<(), KqlPanopticonError> {
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(KqlPanopticonError::Other(format!(
+                "Invalid session name '{}': it cannot be empty, '.' or '..', or contain path separators",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Path of the session file for `name`
+    pub fn file_path(name: &str) -> Result<PathBuf, KqlPanopticonError> {
+        Self::check_name(name)?;
+        Ok(get_sessions_dir()?.join(format!("{}.json", name)))
     }
 
     /// Save session to file
@@ -151,7 +318,7 @@ impl Session {
         let sessions_dir = get_sessions_dir()?;
         fs::create_dir_all(&sessions_dir)?;
 
-        let file_path = sessions_dir.join(format!("{}.json", self.name));
+        let file_path = Self::file_path(&self.name)?;
         let json = serde_json::to_string_pretty(self)?;
         fs::write(&file_path, json)?;
 
@@ -160,46 +327,109 @@ impl Session {
 
     /// Load session from file
     pub fn load(name: &str) -> Result<Self, KqlPanopticonError> {
-        let sessions_dir = get_sessions_dir()?;
-        let file_path = sessions_dir.join(format!("{}.json", name));
-
-        let json = fs::read_to_string(&file_path)?;
-        let session: Session = serde_json::from_str(&json)?;
+        let json = fs::read_to_string(Self::file_path(name)?)?;
+        Self::from_json(name, &json)
+    }
 
-        Ok(session)
+    /// Parse a session file, migrating older formats to the current one
+    pub fn from_json(name: &str, json: &str) -> Result<Self, KqlPanopticonError> {
+        let invalid = |e: serde_json::Error| {
+            KqlPanopticonError::InvalidConfiguration(format!(
+                "Session '{}' is not a valid session file: {}",
+                name, e
+            ))
+        };
+        let value: serde_json::Value = serde_json::from_str(json).map_err(invalid)?;
+        let value = migrate(name, value)?;
+        serde_json::from_value(value).map_err(invalid)
     }
 
     /// Delete session file
     pub fn delete(name: &str) -> Result<(), KqlPanopticonError> {
-        let sessions_dir = get_sessions_dir()?;
-        let file_path = sessions_dir.join(format!("{}.json", name));
-        fs::remove_file(&file_path)?;
+        fs::remove_file(Self::file_path(name)?)?;
+        Ok(())
+    }
+
+    /// Rename a saved session, updating the name stored inside the file
+    pub fn rename(old_name: &str, new_name: &str) -> Result<(), KqlPanopticonError> {
+        if Self::file_path(new_name)?.exists() {
+            return Err(KqlPanopticonError::Other(format!(
+                "Session '{}' already exists",
+                new_name
+            )));
+        }
+
+        let mut session = Self::load(old_name)?;
+        session.name = new_name.to_string();
+        session.save()?;
+        Self::delete(old_name)
+    }
+
+    /// Set the tags and description of a saved session (the save time is left alone)
+    pub fn set_details(
+        name: &str,
+        tags: Vec<String>,
+        description: String,
+    ) -> Result<(), KqlPanopticonError> {
+        let mut session = Self::load(name)?;
+        session.tags = tags;
+        session.description = description;
+        session.save()?;
         Ok(())
     }
 
     /// Convert session to a reusable query pack
     pub fn to_query_pack(&self) -> Result<QueryPack, KqlPanopticonError> {
+        self.to_query_pack_filtered(&PackExportFilter::default())
+    }
+
+    /// Convert the session's jobs passing `filter` to a reusable query pack
+    pub fn to_query_pack_filtered(
+        &self,
+        filter: &PackExportFilter,
+    ) -> Result<QueryPack, KqlPanopticonError> {
         // Deduplicate queries - use HashMap to track unique queries
         let mut unique_queries: HashMap<String, PackQuery> = HashMap::new();
 
         for (idx, job) in self.jobs.iter().enumerate() {
+            if !filter.matches(job) {
+                continue;
+            }
             if let Some(query) = &job.query {
                 // Use query text as key for deduplication
                 if !unique_queries.contains_key(query) {
-                    let query_name = if self.jobs.len() == 1 {
+                    let mut query_name = if let Some(name) = &job.query_name {
+                        name.clone()
+                    } else if self.jobs.len() == 1 {
                         // Single query: use session name
                         self.name.clone()
                     } else {
                         // Multiple queries: generate names
                         format!("Query {}", idx + 1)
                     };
+                    // Named runs can share a name; keep pack query names unique
+                    let base_name = query_name.clone();
+                    let mut suffix = 2;
+                    while unique_queries.values().any(|q| q.name == query_name) {
+                        query_name = format!("{} ({})", base_name, suffix);
+                        suffix += 1;
+                    }
 
                     unique_queries.insert(
                         query.clone(),
                         PackQuery {
                             name: query_name,
-                            description: Some(format!("From workspace: {}", job.workspace_name)),
+                            description: Some(job.description.clone().unwrap_or_else(|| {
+                                format!("From workspace: {}", job.workspace_name)
+                            })),
                             query: query.clone(),
+                            query_file: None,
+                            tags: Vec::new(),
+                            mitre: Vec::new(),
+                            severity: job.severity,
+                            timeout_secs: None,
+                            retry_count: None,
+                            max_rows: None,
                         },
                     );
                 }
@@ -208,7 +438,12 @@ impl Session {
 
         if unique_queries.is_empty() {
             return Err(KqlPanopticonError::QueryPackValidation(
-                "Session contains no queries to export".into(),
+                if filter.is_empty() {
+                    "Session contains no queries to export"
+                } else {
+                    "No jobs in the session match the export filters"
+                }
+                .into(),
             ));
         }
 
@@ -232,37 +467,33 @@ impl Session {
             job_name: "exported-query".to_string(),
             export_csv: self.settings.export_csv,
             export_json: self.settings.export_json,
+            export_ndjson: false,
+            export_parquet: false,
             parse_dynamics: self.settings.parse_dynamics,
+            timeout_secs: None,
+            retry_count: None,
+            max_rows: None,
+            sample_rows: None,
+            timespan: None,
+            estimate_cost: self.settings.estimate_cost,
+            csv_metadata: self.settings.csv_metadata,
+            sqlite_path: (!self.settings.sqlite_path.is_empty())
+                .then(|| PathBuf::from(&self.settings.sqlite_path)),
+            keep_partial_results: self.settings.keep_partial_results,
+            extract_iocs: self.settings.extract_iocs,
+            skip_empty_output: self.settings.skip_empty_output,
+            blob_storage: None,
+            ingestion: None,
         };
 
         // Build query pack
         let queries: Vec<PackQuery> = unique_queries.into_values().collect();
-
-        let pack = if queries.len() == 1 {
-            // Single query: use simple format
-            QueryPack {
-                name: pack_name,
-                description: Some(format!("Exported from session: {}", self.name)),
-                author: Some("kql-panopticon".to_string()),
-                version: Some("1.0".to_string()),
-                query: Some(queries[0].query.clone()),
-                queries: None,
-                settings: Some(settings),
-                workspaces: None, // Don't include workspace scope
-            }
-        } else {
-            // Multiple queries: use multi-query format
-            QueryPack {
-                name: pack_name,
-                description: Some(format!("Exported from session: {}", self.name)),
-                author: Some("kql-panopticon".to_string()),
-                version: Some("1.0".to_string()),
-                query: None,
-                queries: Some(queries),
-                settings: Some(settings),
-                workspaces: None,
-            }
-        };
+        let pack = QueryPack::from_queries(
+            pack_name,
+            Some(format!("Exported from session: {}", self.name)),
+            queries,
+            Some(settings),
+        );
 
         Ok(pack)
     }
@@ -303,6 +534,15 @@ impl Session {
         model.export_csv = self.settings.export_csv;
         model.export_json = self.settings.export_json;
         model.parse_dynamics = self.settings.parse_dynamics;
+        model.watch_interval_secs = self.settings.watch_interval_secs;
+        model.estimate_cost = self.settings.estimate_cost;
+        model.csv_metadata = self.settings.csv_metadata;
+        model.sqlite_path = self.settings.sqlite_path.clone();
+        model.duplicate_jobs = self.settings.duplicate_jobs;
+        model.keep_partial_results = self.settings.keep_partial_results;
+        model.skip_failing_workspaces = self.settings.skip_failing_workspaces;
+        model.extract_iocs = self.settings.extract_iocs;
+        model.skip_empty_output = self.settings.skip_empty_output;
     }
 
     /// Convert this session's jobs to JobState vector
@@ -314,6 +554,7 @@ impl Session {
                     "QUEUED" => JobStatus::Queued,
                     "RUNNING" => JobStatus::Running,
                     "COMPLETED" => JobStatus::Completed,
+                    "PARTIAL" => JobStatus::CompletedWithWarnings,
                     "FAILED" => JobStatus::Failed,
                     _ => JobStatus::Queued,
                 };
@@ -337,8 +578,8 @@ impl Session {
                     .timestamp
                     .as_ref()
                     .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
-                    .map(|dt| dt.with_timezone(&chrono::Local))
-                    .unwrap_or_else(chrono::Local::now);
+                    .map(|dt| crate::time_zone::convert(&dt))
+                    .unwrap_or_else(crate::time_zone::now);
 
                 // Reconstruct result and error info
                 let (result, error) = if let Some(err) = &job.error_message {
@@ -371,7 +612,10 @@ impl Session {
                         }),
                         Some(job_error),
                     )
-                } else if status == JobStatus::Completed {
+                } else if matches!(
+                    status,
+                    JobStatus::Completed | JobStatus::CompletedWithWarnings
+                ) {
                     // Completed jobs - create success result placeholder
                     (
                         Some(QueryJobResult {
@@ -385,8 +629,13 @@ impl Session {
                             result: Ok(crate::query_job::JobSuccess {
                                 row_count: 0,  // We don't save row count, but it's not critical
                                 page_count: 1, // Default to 1 page
-                                output_path: PathBuf::from(""),
+                                output_path: job.output_path.clone().unwrap_or_default(),
                                 file_size: 0,
+                                truncated: false,
+                                billed_bytes: job.billed_bytes,
+                                warnings: job.warnings.clone(),
+                                statistics: job.statistics.clone(),
+                                uploads: job.uploads.clone(),
                             }),
                             elapsed: duration.unwrap_or_default(),
                             timestamp,
@@ -410,17 +659,157 @@ impl Session {
                     result,
                     error,
                     retry_context,
+                    watch: None,
+                    triage: job.triage,
+                    note: job.note.clone(),
+                    query_name: job.query_name.clone(),
+                    description: job.description.clone(),
+                    severity: job.severity,
+                    priority: crate::scheduler::SharedPriority::new(
+                        crate::scheduler::JobPriority::from_severity(job.severity),
+                    ),
                 }
             })
             .collect()
     }
 }
 
-/// Get the sessions directory path (~/.kql-panopticon/sessions)
+/// Get the sessions directory path (<data_dir>/sessions)
 pub fn get_sessions_dir() -> Result<PathBuf, KqlPanopticonError> {
-    let home = dirs::home_dir().ok_or_else(|| {
-        KqlPanopticonError::InvalidConfiguration("Could not find home directory".to_string())
-    })?;
+    Ok(crate::paths::data_dir()?.join("sessions"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_versions() {
+        let v1 = r#"{
+            "version": 1,
+            "name": "old",
+            "created_at": "2024-01-01T00:00:00+00:00",
+            "last_saved": "2024-01-01T00:00:00+00:00",
+            "settings": {
+                "output_folder": "./output",
+                "query_timeout_secs": 30,
+                "retry_count": 0,
+                "validation_interval_secs": 300,
+                "export_csv": true,
+                "export_json": false,
+                "parse_dynamics": true
+            },
+            "jobs": [{
+                "status": "Completed",
+                "workspace_name": "ws-eu",
+                "query_preview": "SigninLogs",
+                "duration_millis": 1200,
+                "workspace": null,
+                "query": "SigninLogs",
+                "settings": null,
+                "error_message": null,
+                "error_details": null
+            }]
+        }"#;
+        let session = Session::from_json("old", v1).unwrap();
+        assert_eq!(session.version, SESSION_VERSION);
+        assert_eq!(session.selected_workspaces, None);
+        assert_eq!(session.jobs[0].triage, None);
+
+        let future = v1.replace("\"version\": 1", "\"version\": 99");
+        let err = Session::from_json("old", &future).unwrap_err().to_string();
+        assert!(err.contains("format v99"), "{}", err);
+
+        let err = Session::from_json("old", "{\"version\": 2}").unwrap_err();
+        assert!(err.to_string().contains("not a valid session file"));
+    }
 
-    Ok(home.join(".kql-panopticon").join("sessions"))
+    #[test]
+    fn test_query_pack_uses_job_names() {
+        let job = |query: &str, name: &str, description: &str| {
+            format!(
+                r#"{{
+                    "status": "Completed",
+                    "workspace_name": "ws-eu",
+                    "query_preview": "{query}",
+                    "duration_millis": 1200,
+                    "workspace": null,
+                    "query": "{query}",
+                    "settings": null,
+                    "error_message": null,
+                    "error_details": null,
+                    "query_name": "{name}",
+                    "description": "{description}"
+                }}"#
+            )
+        };
+        let json = format!(
+            r#"{{
+                "version": 1,
+                "name": "hunt",
+                "created_at": "2024-01-01T00:00:00+00:00",
+                "last_saved": "2024-01-01T00:00:00+00:00",
+                "settings": {{
+                    "output_folder": "./output",
+                    "query_timeout_secs": 30,
+                    "retry_count": 0,
+                    "validation_interval_secs": 300,
+                    "export_csv": true,
+                    "export_json": false,
+                    "parse_dynamics": true
+                }},
+                "jobs": [{}, {}]
+            }}"#,
+            job("SigninLogs", "Failed logins", "Sign-in failures by user"),
+            job(
+                "AADNonInteractiveUserSignInLogs",
+                "Failed logins",
+                "Non-interactive"
+            ),
+        );
+        let session = Session::from_json("hunt", &json).unwrap();
+        let pack = session.to_query_pack().unwrap();
+        let mut queries: Vec<(String, Option<String>)> = pack
+            .get_queries()
+            .into_iter()
+            .map(|q| (q.name, q.description))
+            .collect();
+        queries.sort();
+        assert_eq!(
+            queries,
+            vec![
+                (
+                    "Failed logins".to_string(),
+                    Some("Sign-in failures by user".to_string())
+                ),
+                (
+                    "Failed logins (2)".to_string(),
+                    Some("Non-interactive".to_string())
+                ),
+            ]
+        );
+
+        // Filters pick which jobs are exported
+        let json = json.replacen(r#""status": "Completed""#, r#""status": "Failed""#, 1);
+        let session = Session::from_json("hunt", &json).unwrap();
+        let filter = PackExportFilter {
+            only_successful: true,
+            ..Default::default()
+        };
+        let pack = session.to_query_pack_filtered(&filter).unwrap();
+        let queries = pack.get_queries();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].query, "AADNonInteractiveUserSignInLogs");
+
+        let filter = PackExportFilter {
+            query_name: Some(crate::result_search::matcher("^triage", true).unwrap()),
+            ..Default::default()
+        };
+        let err = session.to_query_pack_filtered(&filter).unwrap_err();
+        assert!(
+            err.to_string().contains("match the export filters"),
+            "{}",
+            err
+        );
+    }
 }
@@ -0,0 +1,352 @@
+use crate::error::{KqlPanopticonError, Result};
+use crate::paths::is_plain;
+use crate::session::Session;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the session file inside an archive (always the first entry)
+const SESSION_ENTRY: &str = "session.json";
+/// Folder inside an archive holding the jobs' output files
+const OUTPUT_ENTRY: &str = "output";
+
+/// What `export` wrote
+#[derive(Debug, Clone)]
+pub struct ExportSummary {
+    pub files: usize,
+    pub bytes: u64,
+    /// Output files referenced by jobs that no longer exist
+    pub missing: Vec<PathBuf>,
+}
+
+/// What `import` restored
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    /// The imported session, with output paths rewritten
+    pub session: Session,
+    pub files: usize,
+    /// Where the output files were extracted
+    pub output_folder: PathBuf,
+}
+
+/// Write a session and the output files its jobs reference into a `.tar.gz` archive
+///
+/// Each job's run folder (`<subscription>/<workspace>/<timestamp>`) is included whole, so
+/// JSON copies, metadata sidecars and IOC files travel with the CSV.
+pub fn export(session: &Session, archive: &Path) -> Result<ExportSummary> {
+    let output_folder = PathBuf::from(&session.settings.output_folder);
+
+    // Folder in the archive for each folder holding output, keyed by its path on disk
+    let mut folders: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut used = HashSet::new();
+    let mut files: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut missing = Vec::new();
+    let mut archived = session.clone();
+    for job in &mut archived.jobs {
+        let Some(path) = job.output_path.clone() else {
+            continue;
+        };
+        let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+            continue;
+        };
+        if !path.is_file() {
+            missing.push(path);
+            continue;
+        }
+        let folder = match folders.get(dir) {
+            Some(folder) => folder.clone(),
+            None => {
+                let folder = unique_folder(archive_folder(dir, &output_folder), &mut used);
+                folders.insert(dir.to_path_buf(), folder.clone());
+                if crate::retention::is_run_dir(dir) {
+                    for entry in walkdir::WalkDir::new(dir).into_iter().flatten() {
+                        if entry.file_type().is_file() {
+                            let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+                            files.push((entry.path().to_path_buf(), folder.join(relative)));
+                        }
+                    }
+                }
+                folder
+            }
+        };
+        let entry = folder.join(file_name);
+        if !files.iter().any(|(_, name)| *name == entry) {
+            files.push((path.clone(), entry.clone()));
+        }
+        job.output_path = Some(entry);
+    }
+
+    let encoder = GzEncoder::new(File::create(archive)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    let json = serde_json::to_vec_pretty(&archived)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, SESSION_ENTRY, json.as_slice())?;
+
+    let mut bytes = 0;
+    for (path, name) in &files {
+        builder.append_path_with_name(path, Path::new(OUTPUT_ENTRY).join(name))?;
+        bytes += std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    }
+    builder.into_inner()?.finish()?;
+
+    Ok(ExportSummary {
+        files: files.len(),
+        bytes,
+        missing,
+    })
+}
+
+/// Restore a session archive: extract its output files under `output_folder` and save the
+/// session (as `name` if given) with its jobs pointing at the extracted files
+///
+/// Fails before extracting anything if the name isn't a plain file name or a session with
+/// that name already exists, and stops at the first output file that already exists.
+pub fn import(archive: &Path, output_folder: &Path, name: Option<&str>) -> Result<ImportSummary> {
+    let summary = unpack(archive, output_folder, name, |name| {
+        Ok(Session::file_path(name)?.exists())
+    })?;
+    summary.session.save()?;
+    Ok(summary)
+}
+
+/// Extract an archive and rewrite its session without saving it
+fn unpack(
+    archive: &Path,
+    output_folder: &Path,
+    name: Option<&str>,
+    session_exists: impl Fn(&str) -> Result<bool>,
+) -> Result<ImportSummary> {
+    let not_an_archive = |reason: &str| {
+        KqlPanopticonError::InvalidConfiguration(format!(
+            "{} is not a session archive: {}",
+            archive.display(),
+            reason
+        ))
+    };
+
+    let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut entries = tar.entries()?;
+    let mut first = entries
+        .next()
+        .ok_or_else(|| not_an_archive("it is empty"))??;
+    if first.path()?.as_ref() != Path::new(SESSION_ENTRY) {
+        return Err(not_an_archive("it doesn't start with session.json"));
+    }
+    let mut json = String::new();
+    first.read_to_string(&mut json)?;
+    let mut session: Session = serde_json::from_str(&json)
+        .map_err(|e| not_an_archive(&format!("invalid session.json: {}", e)))?;
+    if let Some(name) = name {
+        session.name = name.to_string();
+    }
+    // The name comes from the archive (or --name) and becomes a file name
+    Session::check_name(&session.name)?;
+    if session_exists(&session.name)? {
+        return Err(KqlPanopticonError::InvalidConfiguration(format!(
+            "Session '{}' already exists; import it under another name with --name",
+            session.name
+        )));
+    }
+
+    let mut extracted = HashSet::new();
+    for entry in entries {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let Some(relative) = output_relative(&path) else {
+            log::warn!("Skipping unexpected archive entry {}", path.display());
+            continue;
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let target = output_folder.join(&relative);
+        if target.symlink_metadata().is_ok() {
+            return Err(KqlPanopticonError::InvalidConfiguration(format!(
+                "{} already exists; import into another output folder",
+                target.display()
+            )));
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&target)?;
+        extracted.insert(relative);
+    }
+
+    // Paths of files that weren't in the archive are left as they were
+    for job in &mut session.jobs {
+        if let Some(path) = &job.output_path {
+            if extracted.contains(path) {
+                job.output_path = Some(output_folder.join(path));
+            }
+        }
+    }
+    session.settings.output_folder = output_folder.to_string_lossy().into_owned();
+    session.touch();
+
+    Ok(ImportSummary {
+        session,
+        files: extracted.len(),
+        output_folder: output_folder.to_path_buf(),
+    })
+}
+
+/// Folder in the archive for a folder of output: its path under the output folder, or its
+/// last three components (`<subscription>/<workspace>/<timestamp>`) when it lies elsewhere
+fn archive_folder(dir: &Path, output_folder: &Path) -> PathBuf {
+    if let Ok(relative) = dir.strip_prefix(output_folder) {
+        if is_plain(relative) && relative.components().next().is_some() {
+            return relative.to_path_buf();
+        }
+    }
+    let names: Vec<_> = dir
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    names[names.len().saturating_sub(3)..].iter().collect()
+}
+
+/// `folder`, or `folder-2`, `folder-3`... if another folder on disk already took it
+fn unique_folder(folder: PathBuf, used: &mut HashSet<PathBuf>) -> PathBuf {
+    let mut candidate = folder.clone();
+    let mut n = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = PathBuf::from(format!("{}-{}", folder.display(), n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Path of an `output/...` entry relative to the output folder (None for anything else,
+/// including entries that would escape it)
+fn output_relative(path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(OUTPUT_ENTRY).ok()?;
+    (is_plain(relative) && relative.components().next().is_some()).then(|| relative.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SerializableJob;
+
+    fn job(output_path: &Path) -> SerializableJob {
+        serde_json::from_value(serde_json::json!({
+            "status": "Completed",
+            "workspace_name": "ws-eu",
+            "query_preview": "SigninLogs",
+            "duration_millis": 10,
+            "workspace": null,
+            "query": "SigninLogs",
+            "settings": null,
+            "error_message": null,
+            "error_details": null,
+            "output_path": output_path,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_export_and_import_rewrite_output_paths() {
+        let dir = std::env::temp_dir().join(format!("kql-archive-{}", std::process::id()));
+        let run = dir.join("output/sub/ws-eu/2024-05-01_10-00-00");
+        std::fs::create_dir_all(&run).unwrap();
+        std::fs::write(run.join("signins.csv"), "User\nalice\n").unwrap();
+        std::fs::write(run.join("signins.meta.json"), "{}").unwrap();
+
+        let mut session: Session = serde_json::from_value(serde_json::json!({
+            "version": 2,
+            "name": "handoff",
+            "created_at": "2024-05-01T10:00:00+00:00",
+            "last_saved": "2024-05-01T10:00:00+00:00",
+            "settings": {
+                "output_folder": dir.join("output"),
+                "query_timeout_secs": 30,
+                "retry_count": 0,
+                "validation_interval_secs": 300,
+                "export_csv": true,
+                "export_json": false,
+                "parse_dynamics": true
+            },
+            "jobs": []
+        }))
+        .unwrap();
+        session.jobs.push(job(&run.join("signins.csv")));
+        session.jobs.push(job(&dir.join("gone/missing.csv")));
+
+        let archive = dir.join("handoff.tar.gz");
+        let exported = export(&session, &archive).unwrap();
+        assert_eq!(exported.files, 2);
+        assert_eq!(exported.missing, vec![dir.join("gone/missing.csv")]);
+
+        let target = dir.join("imported");
+        let taken = unpack(&archive, &target, None, |name| Ok(name == "handoff"));
+        assert!(taken.is_err());
+        assert!(!target.exists());
+        let imported = unpack(&archive, &target, Some("handoff-2"), |_| Ok(false)).unwrap();
+
+        assert_eq!(imported.session.name, "handoff-2");
+        assert_eq!(imported.files, 2);
+        let csv = target.join("sub/ws-eu/2024-05-01_10-00-00/signins.csv");
+        assert_eq!(imported.session.jobs[0].output_path.as_ref(), Some(&csv));
+        assert_eq!(std::fs::read_to_string(&csv).unwrap(), "User\nalice\n");
+        assert!(csv.with_file_name("signins.meta.json").exists());
+        assert_eq!(
+            imported.session.jobs[1].output_path,
+            Some(dir.join("gone/missing.csv"))
+        );
+        assert_eq!(output_relative(Path::new("output/../../etc/passwd")), None);
+
+        // Importing again into the same folder would overwrite the extracted files
+        let again = unpack(&archive, &target, Some("handoff-3"), |_| Ok(false));
+        assert!(again.unwrap_err().to_string().contains("already exists"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_rejects_path_names() {
+        let dir = std::env::temp_dir().join(format!("kql-archive-name-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut session: Session = serde_json::from_value(serde_json::json!({
+            "version": 2,
+            "name": "../../.config/evil",
+            "created_at": "2024-05-01T10:00:00+00:00",
+            "last_saved": "2024-05-01T10:00:00+00:00",
+            "settings": {
+                "output_folder": dir.join("output"),
+                "query_timeout_secs": 30,
+                "retry_count": 0,
+                "validation_interval_secs": 300,
+                "export_csv": true,
+                "export_json": false,
+                "parse_dynamics": true
+            },
+            "jobs": []
+        }))
+        .unwrap();
+        let archive = dir.join("evil.tar.gz");
+        export(&session, &archive).unwrap();
+
+        let target = dir.join("imported");
+        assert!(unpack(&archive, &target, None, |_| Ok(false)).is_err());
+        assert!(unpack(&archive, &target, Some("a/b"), |_| Ok(false)).is_err());
+        assert!(unpack(&archive, &target, Some(".."), |_| Ok(false)).is_err());
+        assert!(unpack(&archive, &target, Some("evil"), |_| Ok(false)).is_ok());
+
+        session.name = "..\\evil".to_string();
+        assert!(Session::file_path(&session.name).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+use crate::client::Column;
+use crate::error::Result;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, TransactionBehavior};
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a job waits for another job's write to the same database to finish
+const BUSY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Append result rows to `table` in the database at `db_path`
+///
+/// The table is created on first use with `_workspace` and `_run_timestamp` columns ahead of
+/// the result columns; columns that appear in later runs are added to the existing table.
+pub(crate) fn append_rows(
+    db_path: &Path,
+    table: &str,
+    workspace: &str,
+    run_timestamp: &str,
+    columns: &[Column],
+    rows: &[Vec<serde_json::Value>],
+) -> Result<()> {
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut conn = Connection::open(db_path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+    let column_defs: Vec<String> = columns
+        .iter()
+        .map(|c| format!("{} {}", quote(&c.name), sql_type(&c.column_type)))
+        .collect();
+    let mut create = format!(
+        "CREATE TABLE IF NOT EXISTS {} (\"_workspace\" TEXT, \"_run_timestamp\" TEXT",
+        quote(table)
+    );
+    for def in &column_defs {
+        create.push_str(", ");
+        create.push_str(def);
+    }
+    create.push(')');
+    tx.execute(&create, [])?;
+
+    // Queries evolve between runs; add any columns the table doesn't have yet
+    let existing: Vec<String> = tx
+        .prepare(&format!("PRAGMA table_info({})", quote(table)))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (column, def) in columns.iter().zip(&column_defs) {
+        if !existing.iter().any(|name| name == &column.name) {
+            tx.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {}", quote(table), def),
+                [],
+            )?;
+        }
+    }
+
+    let names: Vec<String> = ["_workspace", "_run_timestamp"]
+        .into_iter()
+        .chain(columns.iter().map(|c| c.name.as_str()))
+        .map(quote)
+        .collect();
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote(table),
+        names.join(", "),
+        vec!["?"; names.len()].join(", ")
+    );
+    {
+        let mut statement = tx.prepare(&insert)?;
+        for row in rows {
+            let values = [
+                Value::Text(workspace.to_string()),
+                Value::Text(run_timestamp.to_string()),
+            ]
+            .into_iter()
+            .chain((0..columns.len()).map(|idx| sql_value(row.get(idx))));
+            statement.execute(params_from_iter(values))?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Quote an identifier (table or column name)
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// SQLite column affinity for a Log Analytics column type
+fn sql_type(column_type: &str) -> &'static str {
+    match column_type {
+        "bool" | "boolean" | "int" | "long" => "INTEGER",
+        "real" | "double" | "decimal" => "REAL",
+        _ => "TEXT",
+    }
+}
+
+/// Convert a result cell to a SQLite value (dynamic values are stored as JSON text)
+fn sql_value(value: Option<&serde_json::Value>) -> Value {
+    match value {
+        None | Some(serde_json::Value::Null) => Value::Null,
+        Some(serde_json::Value::Bool(b)) => Value::Integer(i64::from(*b)),
+        Some(serde_json::Value::Number(n)) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => n.as_f64().map_or(Value::Null, Value::Real),
+        },
+        Some(serde_json::Value::String(s)) => Value::Text(s.clone()),
+        Some(other) => Value::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(name: &str, column_type: &str) -> Column {
+        Column {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_append_rows_across_runs() {
+        let dir = std::env::temp_dir().join(format!("kql-sqlite-test-{}", std::process::id()));
+        let db = dir.join("hunts.db");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let columns = vec![column("Account", "string"), column("Count", "long")];
+        let rows = vec![
+            vec![json!("alice"), json!(3)],
+            vec![json!("bob"), json!(null)],
+        ];
+        append_rows(
+            &db,
+            "logons",
+            "ws-1",
+            "2024-01-01_00-00-00",
+            &columns,
+            &rows,
+        )
+        .unwrap();
+
+        // A later run with an extra column appends to the same table
+        let columns = vec![
+            column("Account", "string"),
+            column("Count", "long"),
+            column("Details", "dynamic"),
+        ];
+        let rows = vec![vec![json!("carol"), json!(1), json!({"ip": "10.0.0.1"})]];
+        append_rows(
+            &db,
+            "logons",
+            "ws-2",
+            "2024-01-02_00-00-00",
+            &columns,
+            &rows,
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db).unwrap();
+        let total: i64 = conn
+            .query_row("SELECT SUM(\"Count\") FROM logons", [], |r| r.get(0))
+            .unwrap();
+        let details: String = conn
+            .query_row(
+                "SELECT Details FROM logons WHERE _workspace = 'ws-2'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM logons", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!((count, total), (3, 4));
+        assert_eq!(details, r#"{"ip":"10.0.0.1"}"#);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
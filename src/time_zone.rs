@@ -0,0 +1,127 @@
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Time zone timestamps are written in (set from config `time_zone`)
+static OUTPUT_TIME_ZONE: OnceLock<OutputTimeZone> = OnceLock::new();
+
+/// Time zone for output folder names, metadata, the Jobs table and reports
+///
+/// Defaults to UTC so analysts in different regions see the same times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputTimeZone {
+    #[default]
+    Utc,
+    /// The machine's local time zone
+    Local,
+    /// A fixed offset from UTC, e.g. `+05:30`
+    Offset(FixedOffset),
+}
+
+impl OutputTimeZone {
+    /// Parse `UTC`, `local`, or an offset such as `+02:00`, `-0800` or `UTC+5:30`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text.to_ascii_lowercase().as_str() {
+            "utc" | "z" | "gmt" => return Some(Self::Utc),
+            "local" => return Some(Self::Local),
+            _ => {}
+        }
+
+        let offset = text
+            .strip_prefix("UTC")
+            .or_else(|| text.strip_prefix("utc"))
+            .or_else(|| text.strip_prefix("GMT"))
+            .unwrap_or(text);
+        let (sign, rest) = match offset.chars().next()? {
+            '+' => (1, &offset[1..]),
+            '-' => (-1, &offset[1..]),
+            _ => return None,
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((hours, minutes)) => (hours, minutes),
+            None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+            None => (rest, "0"),
+        };
+        let hours: i32 = hours.parse().ok()?;
+        let minutes: i32 = minutes.parse().ok()?;
+        if hours > 14 || minutes >= 60 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(Self::Offset)
+    }
+
+    /// `time` in this time zone
+    pub fn convert<Tz: TimeZone>(self, time: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Utc => time.with_timezone(&Utc).fixed_offset(),
+            Self::Local => time.with_timezone(&Local).fixed_offset(),
+            Self::Offset(offset) => time.with_timezone(&offset),
+        }
+    }
+}
+
+impl fmt::Display for OutputTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => write!(f, "UTC"),
+            Self::Local => write!(f, "local"),
+            Self::Offset(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// Use `zone` for every timestamp written from now on
+/// Must be called before anything is timestamped; later calls are ignored.
+pub fn set(zone: OutputTimeZone) {
+    let _ = OUTPUT_TIME_ZONE.set(zone);
+}
+
+/// The configured time zone (UTC unless set)
+pub fn get() -> OutputTimeZone {
+    OUTPUT_TIME_ZONE.get().copied().unwrap_or_default()
+}
+
+/// The current time in the configured time zone
+pub fn now() -> DateTime<FixedOffset> {
+    get().convert(&Utc::now())
+}
+
+/// `time` in the configured time zone
+pub fn convert<Tz: TimeZone>(time: &DateTime<Tz>) -> DateTime<FixedOffset> {
+    get().convert(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_zones() {
+        assert_eq!(OutputTimeZone::parse("utc"), Some(OutputTimeZone::Utc));
+        assert_eq!(OutputTimeZone::parse("Local"), Some(OutputTimeZone::Local));
+        for (text, seconds) in [
+            ("+05:30", 5 * 3600 + 30 * 60),
+            ("-0800", -8 * 3600),
+            ("UTC+2", 2 * 3600),
+        ] {
+            assert_eq!(
+                OutputTimeZone::parse(text),
+                Some(OutputTimeZone::Offset(
+                    FixedOffset::east_opt(seconds).unwrap()
+                )),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(OutputTimeZone::parse("Europe/London"), None);
+        assert_eq!(OutputTimeZone::parse("+25:00"), None);
+
+        let zone = OutputTimeZone::parse("+01:00").unwrap();
+        let noon = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            zone.convert(&noon).to_rfc3339(),
+            "2024-03-01T13:00:00+01:00"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use crate::error::{KqlPanopticonError, Result};
+use azure_core::auth::TokenCredential;
+use azure_identity::AzureCliCredential;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime};
+
+// Scopes for the management, Log Analytics and Monitor APIs depend on the cloud (see `cloud`)
+
+/// Scope for Azure Blob Storage (result uploads; the same in every cloud)
+pub const STORAGE_SCOPE: &str = "https://storage.azure.com/.default";
+/// Scope for sending events to Event Hubs (the same in every cloud)
+pub const EVENT_HUBS_SCOPE: &str = "https://eventhubs.azure.net/.default";
+
+/// Cached tokens closer than this to expiry are re-acquired on use
+const TOKEN_REFRESH_BUFFER: Duration = Duration::from_secs(300);
+/// The background task renews tokens closer than this to expiry, ahead of the on-use buffer
+const PROACTIVE_REFRESH_WINDOW: Duration = Duration::from_secs(600);
+/// How often the background task checks cached tokens
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Cached token with expiry information
+#[derive(Clone)]
+struct CachedToken {
+    token: String,
+    expires_at: SystemTime,
+}
+
+impl CachedToken {
+    /// Time left before the token expires (zero if already expired)
+    fn time_until_expiry(&self) -> Duration {
+        self.expires_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+}
+
+type TokenCache = Mutex<HashMap<String, CachedToken>>;
+
+/// Acquires and caches Azure CLI tokens per scope
+/// Tokens are renewed in the background before they expire, and can be invalidated when an
+/// API rejects them (HTTP 401), so long pack runs keep working across token lifetimes.
+#[derive(Clone)]
+pub struct TokenManager {
+    credential: Arc<AzureCliCredential>,
+    cache: Arc<TokenCache>,
+}
+
+impl TokenManager {
+    /// Create a token manager, starting background renewal if a Tokio runtime is running
+    /// The background task stops once every clone of the manager has been dropped.
+    pub fn new(credential: Arc<AzureCliCredential>) -> Self {
+        let manager = Self {
+            credential,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(refresh_loop(
+                manager.credential.clone(),
+                Arc::downgrade(&manager.cache),
+            ));
+        }
+
+        manager
+    }
+
+    /// Get a token for `scope`, using the cached token unless it is close to expiry
+    pub async fn get_token(&self, scope: &str) -> Result<String> {
+        if let Some(cached) = lock(&self.cache)?.get(scope) {
+            let time_until_expiry = cached.time_until_expiry();
+            if time_until_expiry > TOKEN_REFRESH_BUFFER {
+                log::debug!(
+                    "Using cached token for {} (expires in {:?})",
+                    scope,
+                    time_until_expiry
+                );
+                return Ok(cached.token.clone());
+            }
+            log::debug!(
+                "Cached token for {} expiring soon (in {:?}), refreshing",
+                scope,
+                time_until_expiry
+            );
+        }
+
+        fetch_token(&self.credential, &self.cache, scope).await
+    }
+
+    /// Drop the cached token for `scope` (e.g. after a 401) so the next call acquires a new one
+    pub async fn invalidate(&self, scope: &str) {
+        log::info!("Invalidating token for {}", scope);
+        if let Ok(mut cache) = lock(&self.cache) {
+            cache.remove(scope);
+        }
+        // The credential keeps its own cache, which would hand back the rejected token
+        if let Err(e) = self.credential.clear_cache().await {
+            log::warn!("Failed to clear credential token cache: {}", e);
+        }
+    }
+
+    /// Drop every cached token (e.g. after `az login`) so the next calls acquire new ones
+    pub async fn clear(&self) {
+        log::info!("Clearing all cached tokens");
+        if let Ok(mut cache) = lock(&self.cache) {
+            cache.clear();
+        }
+        if let Err(e) = self.credential.clear_cache().await {
+            log::warn!("Failed to clear credential token cache: {}", e);
+        }
+    }
+}
+
+/// Acquire a new token for `scope` and cache it
+async fn fetch_token(
+    credential: &AzureCliCredential,
+    cache: &TokenCache,
+    scope: &str,
+) -> Result<String> {
+    log::debug!("Fetching new token for {}", scope);
+    let token = credential.get_token(&[scope]).await.map_err(|e| {
+        KqlPanopticonError::TokenAcquisitionFailed(format!(
+            "Failed to get token for {}: {}",
+            scope, e
+        ))
+    })?;
+
+    let cached = CachedToken {
+        token: token.token.secret().to_string(),
+        // Convert OffsetDateTime to SystemTime
+        expires_at: SystemTime::UNIX_EPOCH
+            + Duration::from_secs(token.expires_on.unix_timestamp().max(0) as u64),
+    };
+    log::debug!(
+        "Cached new token for {} (expires in {:?})",
+        scope,
+        cached.time_until_expiry()
+    );
+
+    let token_string = cached.token.clone();
+    lock(cache)?.insert(scope.to_string(), cached);
+    Ok(token_string)
+}
+
+/// Periodically renew cached tokens that are approaching expiry
+async fn refresh_loop(credential: Arc<AzureCliCredential>, cache: Weak<TokenCache>) {
+    loop {
+        tokio::time::sleep(REFRESH_CHECK_INTERVAL).await;
+
+        let Some(cache) = cache.upgrade() else {
+            return;
+        };
+        let expiring: Vec<String> = match lock(&cache) {
+            Ok(tokens) => tokens
+                .iter()
+                .filter(|(_, token)| token.time_until_expiry() < PROACTIVE_REFRESH_WINDOW)
+                .map(|(scope, _)| scope.clone())
+                .collect(),
+            Err(_) => return,
+        };
+
+        for scope in expiring {
+            // Skip the credential's cache, which may still hold the expiring token
+            if let Err(e) = credential.clear_cache().await {
+                log::warn!("Failed to clear credential token cache: {}", e);
+            }
+            match fetch_token(&credential, &cache, &scope).await {
+                Ok(_) => log::info!("Renewed token for {} before expiry", scope),
+                Err(e) => log::warn!("Background token renewal failed: {}", e),
+            }
+        }
+    }
+}
+
+fn lock(cache: &TokenCache) -> Result<std::sync::MutexGuard<'_, HashMap<String, CachedToken>>> {
+    cache
+        .lock()
+        .map_err(|e| KqlPanopticonError::Other(format!("Token cache lock poisoned: {}", e)))
+}
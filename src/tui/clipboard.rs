@@ -0,0 +1,43 @@
+/// System clipboard access
+/// The backend is created lazily and kept alive for the whole session, since on X11/Wayland
+/// copied content is only served while the owning handle exists
+pub struct SystemClipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl SystemClipboard {
+    /// Create a clipboard handle (the backend connects on first use)
+    pub fn new() -> Self {
+        Self { inner: None }
+    }
+
+    /// Get the backend, connecting if needed
+    fn backend(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        let clipboard = match self.inner.take() {
+            Some(clipboard) => clipboard,
+            None => arboard::Clipboard::new()
+                .map_err(|e| format!("System clipboard unavailable: {}", e))?,
+        };
+        Ok(self.inner.insert(clipboard))
+    }
+
+    /// Copy text to the system clipboard
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        self.backend()?
+            .set_text(text.to_string())
+            .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+    }
+
+    /// Read text from the system clipboard
+    pub fn paste(&mut self) -> Result<String, String> {
+        self.backend()?
+            .get_text()
+            .map_err(|e| format!("Failed to read clipboard: {}", e))
+    }
+}
+
+impl Default for SystemClipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
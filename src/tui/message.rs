@@ -1,3 +1,7 @@
+use crate::client::Subscription;
+use crate::saved_search::SavedSearch;
+use crate::tui::model::query::LineOperator;
+use crate::tui::model::ListJump;
 use crate::workspace::Workspace;
 
 /// All possible messages that can update the application state
@@ -30,16 +34,50 @@ pub enum Message {
     WorkspacesPrevious,
     /// Navigate workspace list down
     WorkspacesNext,
+    /// Jump through the workspace list by a page or to either end
+    WorkspacesJump(ListJump),
     /// Toggle selection of current workspace
     WorkspacesToggle,
     /// Select all workspaces
     WorkspacesSelectAll,
     /// Deselect all workspaces
     WorkspacesSelectNone,
+    /// Cycle the workspace table's sort order
+    WorkspacesCycleSort,
+    /// Run the editor query against only the highlighted workspace
+    WorkspacesQuickExec,
     /// Refresh workspaces from Azure
     WorkspacesRefresh,
     /// Workspaces loaded successfully
     WorkspacesLoaded(Vec<Workspace>),
+    /// Background workspace refresh failed
+    WorkspacesRefreshFailed(String),
+    /// Stop the running workspace refresh, keeping the current list
+    WorkspacesCancelRefresh,
+    /// Open the subscription filter picker (fetches subscriptions from Azure)
+    SubscriptionsOpen,
+    /// Subscriptions loaded for the picker
+    SubscriptionsLoaded(Vec<Subscription>),
+    /// Open the saved search browser for the highlighted workspace (fetched async)
+    SavedSearchesOpen,
+    /// Saved searches fetched for a workspace
+    SavedSearchesLoaded(Workspace, Vec<SavedSearch>),
+    /// Move up in the saved search browser
+    SavedSearchesPrevious,
+    /// Move down in the saved search browser
+    SavedSearchesNext,
+    /// Load the selected saved search into a new editor buffer
+    SavedSearchesLoadQuery,
+    /// Export all of the workspace's saved searches as a query pack
+    SavedSearchesExport,
+    /// Navigate subscription picker up
+    SubscriptionsPrevious,
+    /// Navigate subscription picker down
+    SubscriptionsNext,
+    /// Toggle the selected subscription in the picker
+    SubscriptionsToggle,
+    /// Apply the picker's filter, save config and refresh workspaces
+    SubscriptionsApply,
 
     // === Query ===
     /// Enter insert mode (vim-style)
@@ -48,6 +86,8 @@ pub enum Message {
     QueryExitInsertMode,
     /// Enter visual mode (vim-style)
     QueryEnterVisualMode,
+    /// Enter line-wise visual mode (vim 'V')
+    QueryEnterVisualLineMode,
     /// Exit visual mode (vim-style)
     QueryExitVisualMode,
     /// Copy selected text (yank in vim)
@@ -64,10 +104,18 @@ pub enum Message {
     QueryOpenAbove,
     /// Delete character under cursor (vim 'x')
     QueryDeleteChar,
-    /// Delete current line (vim 'dd' or Ctrl+D)
+    /// Delete from the cursor to the start of the line (Ctrl+D)
     QueryDeleteLine,
+    /// Add a digit to the repeat count for the next editor command (vim '3j')
+    QueryCount(usize),
+    /// Line-wise operator key; applied to whole lines when repeated (vim 'dd', 'yy')
+    QueryLineOperator(LineOperator),
     /// Move cursor (vim hjkl or arrow keys)
     QueryMoveCursor(ratatui::crossterm::event::KeyCode),
+    /// Fold or unfold the //#region section at the cursor (like vim 'za')
+    QueryToggleFold,
+    /// Show or hide the latest run's result below the editor
+    QueryToggleSplit,
     /// Move to top of file (vim 'gg')
     QueryMoveTop,
     /// Move to bottom of file (vim 'G')
@@ -80,14 +128,36 @@ pub enum Message {
     QueryInput(ratatui::crossterm::event::KeyEvent),
     /// Clear query text
     QueryClear,
+    /// Reformat the query (one pipe stage per line, aligned lists)
+    QueryFormat,
+    /// Paste the system clipboard at the cursor
+    QueryPasteClipboard,
+    /// Insert pasted text at the cursor (bracketed paste)
+    QueryPasteText(String),
     /// Start job name input for query execution
     QueryStartExecution,
+    /// Start job name input for watch (live tail) execution
+    QueryStartWatch,
     /// Job name input character
     JobNameInputChar(char),
     /// Job name input backspace
     JobNameInputBackspace,
+    /// Move the job name popup focus between the name and the format toggles
+    JobNameInputCycleFocus(bool),
+    /// Toggle the focused export format in the job name popup
+    JobNameInputToggleFormat,
     /// Execute query with job name
     ExecuteQuery(String),
+    /// Start watching query with job name (re-runs on an interval)
+    ExecuteWatch(String),
+    /// Query parameter popup input character
+    QueryParamInputChar(char),
+    /// Query parameter popup backspace
+    QueryParamInputBackspace,
+    /// Move the query parameter popup focus to the next or previous parameter
+    QueryParamCycleFocus(bool),
+    /// Substitute the entered parameter values and continue the pending run
+    QueryParamsSubmit,
     /// Open load query panel
     QueryOpenLoadPanel,
     /// Navigate jobs in load panel
@@ -104,24 +174,121 @@ pub enum Message {
     QueryNextPackQuery,
     /// Navigate to previous query in pack ([ key)
     QueryPrevPackQuery,
+    /// Open a new empty query buffer (n key)
+    QueryNewBuffer,
+    /// Switch to the next query buffer (} key)
+    QueryNextBuffer,
+    /// Switch to the previous query buffer ({ key)
+    QueryPrevBuffer,
+    /// Close the active query buffer (X key)
+    QueryCloseBuffer,
 
     // === Jobs ===
     /// Navigate jobs list up
     JobsPrevious,
     /// Navigate jobs list down
     JobsNext,
+    /// Jump through the jobs list by a page or to either end
+    JobsJump(ListJump),
     /// View details of selected job
     JobsViewDetails,
     /// Clear completed and failed jobs
     JobsClearCompleted,
     /// Retry selected job
     JobsRetry,
+    /// Stop the selected watch job
+    JobsStopWatch,
+    /// Chart the results of the job at the given index
+    JobsShowChart(usize),
+    /// Switch between bar chart and sparkline
+    JobsChartToggleStyle,
+    /// Inspect the result rows of the job at the given index
+    JobsInspectRows(usize),
+    /// Inspect the rows of the result shown in the Query tab's split view
+    QueryInspectResult,
+    /// Move the row inspector's highlighted line
+    RowInspectorMove(isize),
+    /// Expand or collapse the highlighted JSON object or array
+    RowInspectorToggle,
+    /// Expand (true) or collapse (false) the highlighted JSON object or array
+    RowInspectorExpand(bool),
+    /// Show the next (1) or previous (-1) row in the row inspector
+    RowInspectorStepRow(isize),
+    /// Copy the output path (or error message, for failed jobs) of the job at the given index
+    JobsCopyDetails(usize),
+    /// Open the output file of the job at the given index with the default application
+    JobsOpenOutput(usize),
+    /// Show the output file of the job at the given index in the file manager
+    JobsRevealOutput(usize),
+    /// Toggle mark on selected job (for bulk actions)
+    JobsToggleMark,
+    /// Start pack name input for exporting marked jobs
+    JobsStartExportMarked,
+    /// Pack name input character
+    PackNameInputChar(char),
+    /// Pack name input backspace
+    PackNameInputBackspace,
+    /// Export marked jobs' queries as a new query pack with the given name
+    JobsExportMarked(String),
+    /// Cycle the selected job's triage verdict
+    JobsCycleTriage,
+    /// Cycle the jobs table triage filter
+    JobsCycleTriageFilter,
+    /// Switch the jobs table between newest-first and severity-first order
+    JobsToggleSeveritySort,
+    /// Move the selected queued job up (true) or down (false) the scheduling order
+    JobsChangePriority(bool),
+    /// Open the jobs table column picker
+    JobsColumnsOpen,
+    /// Move the column picker highlight by an offset
+    JobsColumnsNavigate(i32),
+    /// Show or hide the highlighted column
+    JobsColumnsToggle,
+    /// Move the highlighted column earlier (negative) or later in the table
+    JobsColumnsMove(i32),
+    /// Apply the picked columns and save them to config.yaml
+    JobsColumnsApply,
+    /// Open the search across the jobs' output files
+    JobsStartResultSearch,
+    ResultSearchInputChar(char),
+    ResultSearchInputBackspace,
+    /// Switch between literal text and regex
+    ResultSearchToggleRegex,
+    ResultSearchNavigate(i32),
+    /// Search, or once results are shown, open the job that wrote the selected file
+    ResultSearchSubmit,
+    /// Start note input for the selected job
+    JobsStartNote,
+    /// Note input character
+    NoteInputChar(char),
+    /// Note input backspace
+    NoteInputBackspace,
+    /// Set the selected job's note
+    JobsSetNote(String),
+    /// Start post-filter input for the job at the given index
+    JobsStartPostFilter(usize),
+    /// Post-filter input character
+    PostFilterInputChar(char),
+    /// Post-filter input backspace
+    PostFilterInputBackspace,
+    /// Recall the previous (older) saved post-filter
+    PostFilterHistoryOlder,
+    /// Recall the next (newer) saved post-filter
+    PostFilterHistoryNewer,
+    /// Filter the job's result file locally, writing a derivative file
+    JobsApplyPostFilter(usize, String),
+    /// Ask which format to re-export the job's results to
+    JobsStartReexport(usize),
+    /// Convert the job's result file to another format, without re-running the query
+    JobsReexport(usize, crate::reexport::ResultFormat),
 
     // === Sessions ===
     /// Navigate sessions list up
     SessionsPrevious,
     /// Navigate sessions list down
     SessionsNext,
+    /// Jump through the sessions list by a page or to either end
+    SessionsJump(ListJump),
     /// Refresh sessions list from disk
     SessionsRefresh,
     /// Start new session name input
@@ -134,35 +301,172 @@ pub enum Message {
     SessionsSave(Option<String>),
     /// Load selected session
     SessionsLoad,
-    /// Delete selected session
+    /// Delete selected session (asks first when sessions are marked)
     SessionsDelete,
-    /// Export selected session as query pack
+    /// Toggle mark on selected session (for bulk delete/export)
+    SessionsToggleMark,
+    /// Mark all saved sessions
+    SessionsMarkAll,
+    /// Clear all session marks
+    SessionsUnmarkAll,
+    /// Ask to delete the marked sessions (or the selected one) along with their output
+    SessionsStartDeleteMarked,
+    /// Delete the sessions and output awaiting confirmation
+    SessionsDeleteMarked,
+    /// Export the marked sessions (or the selected one) as query packs
     SessionExportAsPack,
+    /// Start rename input for the selected session
+    SessionsStartRename,
+    /// Session rename input character
+    SessionRenameInputChar(char),
+    /// Session rename input backspace
+    SessionRenameInputBackspace,
+    /// Rename the selected session (file and stored name)
+    SessionsRename(String),
+    /// Start editing the selected session's tags and description
+    SessionsStartEditDetails,
+    /// Session details input character (into the focused field)
+    SessionDetailsInputChar(char),
+    /// Session details input backspace (in the focused field)
+    SessionDetailsInputBackspace,
+    /// Switch between the tags and description fields
+    SessionDetailsToggleField,
+    /// Save the edited tags and description
+    SessionsSaveDetails,
+    /// Start search input for the session list
+    SessionsStartSearch,
+    /// Session search input character
+    SessionSearchInputChar(char),
+    /// Session search input backspace
+    SessionSearchInputBackspace,
+    /// Filter the session list by name, tags, pack origin and description (empty clears)
+    SessionsSetSearch(String),
+
+    // === Fuzzy finder ===
+    /// Open the finder over packs, pack queries and sessions
+    FinderOpen,
+    /// Finder input character
+    FinderInputChar(char),
+    /// Finder input backspace
+    FinderInputBackspace,
+    /// Move the finder highlight (+1 down, -1 up)
+    FinderNavigate(i32),
+    /// Go to the highlighted result (true also loads it: a pack or query into the editor,
+    /// a session as the current session)
+    FinderSelect(bool),
 
     // === Query Packs ===
     /// Navigate packs list up
     PacksPrevious,
     /// Navigate packs list down
     PacksNext,
+    /// Jump through the packs list by a page or to either end
+    PacksJump(ListJump),
+    /// Highlight the next query in the selected pack's details
+    PacksNextQuery,
+    /// Highlight the previous query (or go back to the whole pack)
+    PacksPreviousQuery,
+    /// Stop highlighting a query so the whole pack is loaded and run
+    PacksClearQuery,
     /// Refresh packs list from disk
     PacksRefresh,
     /// Load selected pack details
     #[allow(dead_code)]
     PacksLoadDetails,
-    /// Load first query from selected pack into query editor
+    /// Load the highlighted (or first) query from selected pack into query editor
     PacksLoadQuery,
-    /// Execute selected pack on selected workspaces
+    /// Execute selected pack (or just its highlighted query) on selected workspaces
     PacksExecute,
+    /// Run selected pack, optionally resuming the incomplete previous run
+    /// Runs needing confirmation open a prompt first unless `confirmed` is set.
+    PacksRun {
+        resume: bool,
+        confirmed: bool,
+    },
     /// Save current query changes back to the loaded pack
     PacksSave,
+    /// Start path input for saving the loaded pack to a new file
+    PacksStartSaveAs,
+    /// Pack save-as input character
+    PackSaveAsInputChar(char),
+    /// Pack save-as input backspace
+    PackSaveAsInputBackspace,
+    /// Save the loaded pack (with current query changes) to a path in the personal library
+    PacksSaveAs(String),
+    /// Start tag filter input for the pack list
+    PacksStartTagFilter,
+    /// Tag filter input character
+    PackTagInputChar(char),
+    /// Tag filter input backspace
+    PackTagInputBackspace,
+    /// Filter the pack list by comma-separated tags (empty clears the filter)
+    PacksSetTagFilter(String),
+    /// Open the search across pack names, descriptions and KQL
+    PacksStartSearch,
+    /// Pack search input character
+    PackSearchInputChar(char),
+    /// Pack search input backspace
+    PackSearchInputBackspace,
+    /// Switch the pack search between literal text and regex
+    PackSearchToggleRegex,
+    PackSearchNavigate(i32),
+    /// Select the pack and query of the highlighted match
+    PackSearchOpen,
+    /// Start rename input for the selected pack
+    PacksStartRename,
+    /// Pack rename input character
+    PackRenameInputChar(char),
+    /// Pack rename input backspace
+    PackRenameInputBackspace,
+    /// Rename the selected pack (file and `name` field)
+    PacksRename(String),
+    /// Pull pack updates from the library's git remote
+    PacksPull,
+    /// Git pull finished with these changed packs
+    PacksPulled(Vec<crate::pack_sync::PackChange>),
+
+    // === Logs ===
+    /// Scroll the log up one record
+    LogsPrevious,
+    /// Scroll the log down one record
+    LogsNext,
+    /// Page or jump through the log (jumping to the end resumes following)
+    LogsJump(ListJump),
+    /// Show one level fewer (all → debug → info → warn → error → all)
+    LogsCycleLevel,
+    /// Toggle keeping the newest record in view
+    LogsToggleFollow,
+    /// Start search input for the log
+    LogsStartSearch,
+    /// Log search input character
+    LogSearchInputChar(char),
+    /// Log search input backspace
+    LogSearchInputBackspace,
+    /// Show only records containing this text (empty clears the search)
+    LogsSetSearch(String),
 
     // === Popups ===
-    /// Show an error popup (red)
+    /// Show an error popup (red) and record it in notification history
     ShowError(String),
-    /// Show a success popup (green)
+    /// Show a success toast (green) and record it in notification history
     ShowSuccess(String),
+    /// Show an informational toast (or popup, for multi-line reports) and record it in
+    /// notification history
+    ShowInfo(String),
+    /// Open a session or pack another instance has locked, taking the lock over or read-only
+    ResolveLockConflict {
+        take_over: bool,
+    },
     /// Close the current popup
     ClosePopup,
+    /// Open the notification history popup
+    NotificationsOpen,
+    /// Navigate up in notification history
+    NotificationsPrevious,
+    /// Navigate down in notification history
+    NotificationsNext,
+    /// Clear notification history
+    NotificationsClear,
 
     // === System ===
     /// No operation (used for events that don't produce messages)
@@ -173,6 +477,52 @@ pub enum Message {
     AuthFailed(String),
     /// Initialization completed successfully
     InitCompleted,
+    /// Clear cached tokens and re-run authentication (leaves offline mode)
+    ReAuthenticate,
+    /// Re-authentication succeeded
+    ReAuthCompleted,
+}
+
+impl Message {
+    /// Whether the message runs queries (refused in read-only mode)
+    pub fn executes_queries(&self) -> bool {
+        matches!(
+            self,
+            Message::WorkspacesQuickExec
+                | Message::QueryStartExecution
+                | Message::QueryStartWatch
+                | Message::ExecuteQuery(_)
+                | Message::ExecuteWatch(_)
+                | Message::QueryParamsSubmit
+                | Message::JobsRetry
+                | Message::PacksExecute
+                | Message::PacksRun { .. }
+        )
+    }
+
+    /// Whether the message is part of an editor command still being typed (a count, or the
+    /// first `d` of `dd`), so the pending count must be kept for the next message
+    pub fn continues_editor_command(&self) -> bool {
+        matches!(self, Message::QueryCount(_) | Message::QueryLineOperator(_))
+    }
+
+    /// Check if handling this message needs Azure (refused while offline)
+    pub fn requires_connection(&self) -> bool {
+        matches!(
+            self,
+            Message::WorkspacesRefresh
+                | Message::WorkspacesQuickExec
+                | Message::SubscriptionsOpen
+                | Message::SavedSearchesOpen
+                | Message::QueryStartExecution
+                | Message::QueryStartWatch
+                | Message::ExecuteQuery(_)
+                | Message::ExecuteWatch(_)
+                | Message::JobsRetry
+                | Message::PacksExecute
+                | Message::PacksRun { .. }
+        )
+    }
 }
 
 /// Application tabs
@@ -184,6 +534,8 @@ pub enum Tab {
     Settings,
     Jobs,
     Sessions,
+    Dashboard,
+    Logs,
 }
 
 impl Tab {
@@ -194,18 +546,36 @@ impl Tab {
             Tab::Workspaces => Tab::Settings,
             Tab::Settings => Tab::Jobs,
             Tab::Jobs => Tab::Sessions,
-            Tab::Sessions => Tab::Query,
+            Tab::Sessions => Tab::Dashboard,
+            Tab::Dashboard => Tab::Logs,
+            Tab::Logs => Tab::Query,
         }
     }
 
     pub fn previous(self) -> Self {
         match self {
-            Tab::Query => Tab::Sessions,
+            Tab::Query => Tab::Logs,
             Tab::Packs => Tab::Query,
             Tab::Workspaces => Tab::Packs,
             Tab::Settings => Tab::Workspaces,
             Tab::Jobs => Tab::Settings,
             Tab::Sessions => Tab::Jobs,
+            Tab::Dashboard => Tab::Sessions,
+            Tab::Logs => Tab::Dashboard,
+        }
+    }
+
+    /// Compact title for narrow terminals
+    pub fn short_str(self) -> &'static str {
+        match self {
+            Tab::Query => "1:Qry",
+            Tab::Packs => "2:Pck",
+            Tab::Workspaces => "3:Wsp",
+            Tab::Settings => "4:Set",
+            Tab::Jobs => "5:Job",
+            Tab::Sessions => "6:Ses",
+            Tab::Dashboard => "7:Dsh",
+            Tab::Logs => "8:Log",
         }
     }
 
@@ -217,6 +587,8 @@ impl Tab {
             Tab::Settings => "Settings (4)",
             Tab::Jobs => "Jobs (5)",
             Tab::Sessions => "Sessions (6)",
+            Tab::Dashboard => "Dashboard (7)",
+            Tab::Logs => "Logs (8)",
         }
     }
 }
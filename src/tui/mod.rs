@@ -1,14 +1,24 @@
+pub mod clipboard;
 pub mod message;
 pub mod model;
+pub mod opener;
 pub mod update;
 pub mod view;
 
+use crate::backend::QueryBackend;
 use crate::client::Client;
 use crate::error::Result;
+use crate::workspace::Workspace;
 use message::{Message, Tab};
-use model::{query::EditorMode, Model};
+use model::{
+    query::{EditorDraft, EditorMode, LineOperator},
+    ListJump, Model,
+};
 use ratatui::crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -17,52 +27,72 @@ use std::io;
 use std::time::Duration;
 
 /// Main TUI entry point
-pub async fn run_tui(client: Client) -> Result<()> {
+pub async fn run_tui(client: Client, read_only: bool) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Check minimum terminal size
-    let size = terminal.size()?;
-    if size.width < 80 || size.height < 24 {
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
-        return Err(crate::error::KqlPanopticonError::Other(format!(
-            "Terminal too small. Minimum size: 80x24, current: {}x{}",
-            size.width, size.height
-        )));
-    }
-
     // Create model
     let mut model = Model::new(client.clone());
+    model.read_only |= read_only;
+    if let Some(draft) = EditorDraft::load() {
+        model.query.restore_draft(draft);
+    }
 
     // Create a channel for initialization messages
     let (init_tx, mut init_rx) = tokio::sync::mpsc::unbounded_channel::<message::Message>();
 
+    // Load sessions and query packs from disk (available even if authentication fails)
+    let _ = init_tx.send(message::Message::SessionsRefresh);
+    let _ = init_tx.send(message::Message::PacksRefresh);
+
     // Start async initialization in background
-    let init_client = client;
-    let tx = init_tx.clone();
-    tokio::spawn(async move {
-        // Load sessions from disk (no async needed)
-        let _ = tx.send(message::Message::SessionsRefresh);
+    let cache_ttl = Duration::from_secs(model.settings.workspace_cache_ttl_secs);
+    spawn_init(client, cache_ttl, init_tx.clone());
+
+    // Run the application loop with init channel
+    let result = run_app(&mut terminal, &mut model, &init_tx, &mut init_rx).await;
 
-        // Load query packs from disk (no async needed)
-        let _ = tx.send(message::Message::PacksRefresh);
+    // Keep the editor's buffers for the next launch, however the app exited
+    if let Err(e) = model.query.to_draft().save() {
+        log::warn!("Failed to save the editor draft: {}", e);
+    }
 
-        // Authenticate and load workspaces
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+/// Authenticate and load workspaces in the background, reporting through `tx`
+fn spawn_init(
+    client: Client,
+    cache_ttl: Duration,
+    tx: tokio::sync::mpsc::UnboundedSender<Message>,
+) {
+    tokio::spawn(async move {
+        let init_client = client;
         match init_client.force_validate_auth().await {
             Ok(_) => {
                 let _ = tx.send(message::Message::AuthCompleted);
 
-                // Now load workspaces
-                match init_client.list_workspaces().await {
+                // Now load workspaces (from cache if still fresh)
+                match init_client.list_workspaces_cached(cache_ttl, false).await {
                     Ok(workspaces) => {
                         let _ = tx.send(message::Message::WorkspacesLoaded(workspaces));
                         let _ = tx.send(message::Message::InitCompleted);
@@ -81,28 +111,77 @@ pub async fn run_tui(client: Client) -> Result<()> {
             }
         }
     });
+}
 
-    // Run the application loop with init channel
-    let result = run_app(&mut terminal, &mut model, &mut init_rx).await;
+/// Clear cached tokens and re-validate authentication, keeping the loaded workspaces
+fn spawn_reauth(client: Client, tx: tokio::sync::mpsc::UnboundedSender<Message>) {
+    tokio::spawn(async move {
+        match client.reauthenticate().await {
+            Ok(_) => {
+                let _ = tx.send(message::Message::ReAuthCompleted);
+            }
+            Err(e) => {
+                let _ = tx.send(message::Message::AuthFailed(e.to_string()));
+            }
+        }
+    });
+}
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+/// Fetch workspaces from Azure in the background, bypassing the cache
+fn spawn_workspace_refresh(
+    client: Client,
+    cache_ttl: Duration,
+    tx: tokio::sync::mpsc::UnboundedSender<Message>,
+) -> tokio::task::AbortHandle {
+    tokio::spawn(async move {
+        let message = match client.list_workspaces_cached(cache_ttl, true).await {
+            Ok(workspaces) => Message::WorkspacesLoaded(workspaces),
+            Err(e) => Message::WorkspacesRefreshFailed(e.to_string()),
+        };
+        let _ = tx.send(message);
+    })
+    .abort_handle()
+}
 
-    result
+/// List subscriptions in the background for the subscription picker
+fn spawn_subscriptions_load(client: Client, tx: tokio::sync::mpsc::UnboundedSender<Message>) {
+    tokio::spawn(async move {
+        let message = match client.list_subscriptions().await {
+            Ok(subscriptions) => Message::SubscriptionsLoaded(subscriptions),
+            Err(e) => Message::ShowError(format!("Failed to list subscriptions: {}", e)),
+        };
+        let _ = tx.send(message);
+    });
+}
+
+/// List a workspace's saved searches and functions in the background
+fn spawn_saved_searches_load(
+    client: Client,
+    workspace: Workspace,
+    tx: tokio::sync::mpsc::UnboundedSender<Message>,
+) {
+    tokio::spawn(async move {
+        let message = match client.list_saved_searches(&workspace).await {
+            Ok(searches) => Message::SavedSearchesLoaded(workspace, searches),
+            Err(e) => Message::ShowError(format!(
+                "Failed to list saved searches for {}: {}",
+                workspace.name, e
+            )),
+        };
+        let _ = tx.send(message);
+    });
 }
 
 /// Main application loop
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     model: &mut Model,
+    init_tx: &tokio::sync::mpsc::UnboundedSender<Message>,
     init_rx: &mut tokio::sync::mpsc::UnboundedReceiver<Message>,
 ) -> Result<()> {
+    // Running workspace refresh, aborted when cancelled
+    let mut refresh_task: Option<tokio::task::AbortHandle> = None;
+
     loop {
         // Process any pending job updates
         model.process_job_updates();
@@ -129,90 +208,202 @@ async fn run_app(
         }
 
         // Increment spinner frame for loading animation
-        if model.init_state == model::InitState::Initializing {
+        if model.init_state == model::InitState::Initializing || model.workspaces.refreshing {
             model.spinner_frame = model.spinner_frame.wrapping_add(1);
         }
 
+        // Tail the application's own log records for the Logs tab
+        model.logs.sync();
+
         terminal.draw(|f| view::ui(f, model))?;
 
         // Handle events with timeout (50ms for smooth spinner animation)
         if event::poll(Duration::from_millis(50))? {
-            match event::read()? {
-                Event::Key(key) => {
-                    let message = handle_key_event(key.code, key.modifiers, model);
-
-                    // Process the message and any subsequent messages
-                    let mut messages_to_process = vec![message];
-                    while let Some(msg) = messages_to_process.pop() {
-                        // Check for quit
-                        if matches!(msg, Message::Quit) {
-                            return Ok(());
-                        }
+            let message = match event::read()? {
+                Event::Key(key) => Some(handle_key_event(key.code, key.modifiers, model)),
+                // Bracketed paste delivers the whole pasted text at once
+                Event::Paste(text) => handle_paste_event(text, model),
+                // Resize: view::ui re-checks the size each frame and may switch layouts
+                // (side by side <-> stacked), so repaint from scratch instead of diffing
+                Event::Resize(_, _) => {
+                    terminal.clear()?;
+                    None
+                }
+                // Other events (mouse, etc.) are ignored
+                _ => None,
+            };
 
-                        // Handle workspace refresh (async operation)
-                        if matches!(msg, Message::WorkspacesRefresh) {
-                            match model.client.list_workspaces().await {
-                                Ok(workspaces) => {
-                                    messages_to_process.push(Message::WorkspacesLoaded(workspaces));
-                                }
-                                Err(e) => {
-                                    messages_to_process.push(Message::ShowError(format!(
-                                        "Failed to refresh workspaces: {}",
-                                        e
-                                    )));
-                                }
-                            }
+            if let Some(message) = message {
+                // Process the message and any subsequent messages
+                let mut messages_to_process = vec![message];
+                while let Some(msg) = messages_to_process.pop() {
+                    // Check for quit
+                    if matches!(msg, Message::Quit) {
+                        return Ok(());
+                    }
+
+                    // Offline mode only browses local state
+                    if model.init_state == model::InitState::Offline && msg.requires_connection() {
+                        messages_to_process.push(Message::ShowError(
+                            "Offline: authentication failed. Press F5 to re-authenticate."
+                                .to_string(),
+                        ));
+                        continue;
+                    }
+
+                    // Re-authenticate in the background (e.g. after `az login`)
+                    if matches!(msg, Message::ReAuthenticate) {
+                        if matches!(model.popup, Some(model::Popup::ConfirmReauthenticate)) {
+                            model.popup = None;
+                        }
+                        if model.init_state == model::InitState::Initializing {
                             continue;
                         }
+                        model.init_state = model::InitState::Initializing;
+                        if model.workspaces.workspaces.is_empty() {
+                            // Workspaces never loaded (offline since startup): run full init
+                            let client = model.client.clone();
+                            let cache_ttl =
+                                Duration::from_secs(model.settings.workspace_cache_ttl_secs);
+                            let tx = init_tx.clone();
+                            tokio::spawn(async move {
+                                client.clear_tokens().await;
+                                spawn_init(client, cache_ttl, tx);
+                            });
+                        } else {
+                            spawn_reauth(model.client.clone(), init_tx.clone());
+                        }
+                        continue;
+                    }
 
-                        // Handle sessions refresh (load from disk)
-                        if matches!(msg, Message::SessionsRefresh) {
-                            match crate::session::Session::list_all() {
-                                Ok(sessions) => {
-                                    model.sessions.refresh_from_disk(sessions);
-                                }
-                                Err(e) => {
-                                    messages_to_process.push(Message::ShowError(format!(
-                                        "Failed to refresh sessions: {}",
-                                        e
-                                    )));
-                                }
-                            }
+                    // Refresh workspaces in the background (bypasses the cache)
+                    if matches!(msg, Message::WorkspacesRefresh) {
+                        // A newer refresh replaces one still running
+                        if let Some(task) = refresh_task.take() {
+                            task.abort();
+                        }
+                        let cache_ttl =
+                            Duration::from_secs(model.settings.workspace_cache_ttl_secs);
+                        refresh_task = Some(spawn_workspace_refresh(
+                            model.client.clone(),
+                            cache_ttl,
+                            init_tx.clone(),
+                        ));
+                    }
+                    if matches!(msg, Message::WorkspacesCancelRefresh) {
+                        if let Some(task) = refresh_task.take() {
+                            task.abort();
+                        }
+                    }
+
+                    // Open the subscription picker once subscriptions are listed in the background
+                    if matches!(msg, Message::SubscriptionsOpen) {
+                        spawn_subscriptions_load(model.client.clone(), init_tx.clone());
+                        continue;
+                    }
+
+                    // Open the saved search browser once searches are listed in the background
+                    if matches!(msg, Message::SavedSearchesOpen) {
+                        let Some(workspace) = model.workspaces.get_highlighted_workspace().cloned()
+                        else {
+                            messages_to_process
+                                .push(Message::ShowError("No workspace highlighted".to_string()));
                             continue;
+                        };
+                        spawn_saved_searches_load(model.client.clone(), workspace, init_tx.clone());
+                        continue;
+                    }
+
+                    // Handle pack library git pull (async operation)
+                    if matches!(msg, Message::PacksPull) {
+                        let remote = crate::config::Config::load_or_default().pack_remote;
+                        let result = match crate::query_pack::QueryPack::get_library_path("") {
+                            Ok(library) => {
+                                crate::pack_sync::pull(&library, remote.as_deref()).await
+                            }
+                            Err(e) => Err(e),
+                        };
+                        match result {
+                            Ok(changes) => {
+                                messages_to_process.push(Message::PacksPulled(changes));
+                            }
+                            Err(e) => {
+                                messages_to_process.push(Message::ShowError(format!(
+                                    "Failed to pull packs: {}",
+                                    e
+                                )));
+                            }
                         }
+                        continue;
+                    }
 
-                        // Update model and collect new messages
-                        let new_messages = update::update(model, msg);
-                        messages_to_process.extend(new_messages);
+                    // Handle sessions refresh (load from disk)
+                    if matches!(msg, Message::SessionsRefresh) {
+                        match crate::session::Session::list_all() {
+                            Ok(sessions) => {
+                                model.sessions.refresh_from_disk(sessions);
+                            }
+                            Err(e) => {
+                                messages_to_process.push(Message::ShowError(format!(
+                                    "Failed to refresh sessions: {}",
+                                    e
+                                )));
+                            }
+                        }
+                        continue;
                     }
+
+                    // Update model and collect new messages
+                    let new_messages = update::update(model, msg);
+                    messages_to_process.extend(new_messages);
                 }
-                Event::Resize(_width, _height) => {
-                    // Terminal was resized, force a redraw on next iteration
-                    // The terminal.draw() call will automatically adapt to new size
-                }
-                _ => {
-                    // Ignore other events (mouse, etc.)
+
+                // Each Normal mode edit, or visit to Insert mode, is one step of the draft's
+                // undo history
+                if model.query.mode == EditorMode::Normal {
+                    model.query.record_undo_snapshot();
                 }
             }
         }
     }
 }
 
+/// Convert a bracketed paste into a message
+/// Pastes only go to the query editor; elsewhere they are ignored rather than replayed as keys
+fn handle_paste_event(text: String, model: &Model) -> Option<Message> {
+    if model.popup.is_none() && model.current_tab == Tab::Query && model.query.load_panel.is_none()
+    {
+        Some(Message::QueryPasteText(text))
+    } else {
+        None
+    }
+}
+
 /// Convert key events into messages
-fn handle_key_event(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Message {
+fn handle_key_event<B: QueryBackend>(
+    key: KeyCode,
+    modifiers: KeyModifiers,
+    model: &Model<B>,
+) -> Message {
     // Handle popup interactions first
     if let Some(popup) = &model.popup {
         return handle_popup_key(key, popup, model);
     }
 
     // Check if we're in query edit mode (blocks most global keys)
-    let in_query_edit_mode = model.current_tab == Tab::Query
-        && (model.query.mode == EditorMode::Insert || model.query.mode == EditorMode::Visual);
+    let in_query_edit_mode =
+        model.current_tab == Tab::Query && model.query.mode != EditorMode::Normal;
+    // Digits are repeat counts in the editor's Normal mode, not tab shortcuts
+    let in_query_normal_mode = model.current_tab == Tab::Query
+        && model.query.mode == EditorMode::Normal
+        && model.query.load_panel.is_none();
 
     // Handle global keys (only work outside query edit mode)
     if !in_query_edit_mode {
         match key {
             KeyCode::Char('q') => return Message::Quit,
+            KeyCode::Char('m') => return Message::NotificationsOpen,
+            KeyCode::F(5) => return Message::ReAuthenticate,
             KeyCode::Char('r') => {
                 if model.current_tab == Tab::Workspaces {
                     return Message::WorkspacesRefresh;
@@ -220,12 +411,15 @@ fn handle_key_event(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
                     return Message::SessionsRefresh;
                 }
             }
+            KeyCode::Char('1'..='8') if in_query_normal_mode => {}
             KeyCode::Char('1') => return Message::SwitchTab(Tab::Query),
             KeyCode::Char('2') => return Message::SwitchTab(Tab::Packs),
             KeyCode::Char('3') => return Message::SwitchTab(Tab::Workspaces),
             KeyCode::Char('4') => return Message::SwitchTab(Tab::Settings),
             KeyCode::Char('5') => return Message::SwitchTab(Tab::Jobs),
             KeyCode::Char('6') => return Message::SwitchTab(Tab::Sessions),
+            KeyCode::Char('7') => return Message::SwitchTab(Tab::Dashboard),
+            KeyCode::Char('8') => return Message::SwitchTab(Tab::Logs),
             _ => {}
         }
     }
@@ -240,6 +434,11 @@ fn handle_key_event(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
         return Message::SwitchTab(model.current_tab.previous());
     }
 
+    // Ctrl+P opens the fuzzy finder from any tab
+    if modifiers.contains(KeyModifiers::CONTROL) && key == KeyCode::Char('p') {
+        return Message::FinderOpen;
+    }
+
     // Ctrl+J for query execution (works in any mode)
     if modifiers.contains(KeyModifiers::CONTROL)
         && key == KeyCode::Char('j')
@@ -248,21 +447,41 @@ fn handle_key_event(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
         return Message::QueryStartExecution;
     }
 
+    // Ctrl+W starts watch mode for the current query
+    if modifiers.contains(KeyModifiers::CONTROL)
+        && key == KeyCode::Char('w')
+        && model.current_tab == Tab::Query
+    {
+        return Message::QueryStartWatch;
+    }
+
     // Handle tab-specific keys
     match model.current_tab {
         Tab::Settings => handle_settings_key(key),
-        Tab::Workspaces => handle_workspaces_key(key),
+        Tab::Workspaces => handle_workspaces_key(key, model),
         Tab::Query => handle_query_key(key, modifiers, model),
         Tab::Jobs => handle_jobs_key(key),
         Tab::Sessions => handle_sessions_key(key, modifiers),
         Tab::Packs => handle_packs_key(key),
+        Tab::Dashboard => Message::NoOp,
+        Tab::Logs => handle_logs_key(key),
     }
 }
 
 /// Handle key events when a popup is open
-fn handle_popup_key(key: KeyCode, popup: &model::Popup, model: &Model) -> Message {
+fn handle_popup_key<B: QueryBackend>(
+    key: KeyCode,
+    popup: &model::Popup,
+    model: &Model<B>,
+) -> Message {
     match popup {
-        model::Popup::Error(_) | model::Popup::Success(_) => {
+        model::Popup::LockConflict => match key {
+            KeyCode::Char('r') => Message::ResolveLockConflict { take_over: false },
+            KeyCode::Char('t') => Message::ResolveLockConflict { take_over: true },
+            KeyCode::Esc => Message::ClosePopup,
+            _ => Message::NoOp,
+        },
+        model::Popup::Error(_) | model::Popup::Info(_) => {
             if matches!(key, KeyCode::Esc | KeyCode::Enter) {
                 Message::ClosePopup
             } else {
@@ -281,11 +500,21 @@ fn handle_popup_key(key: KeyCode, popup: &model::Popup, model: &Model) -> Messag
             KeyCode::Enter => {
                 if let Some(ref job_name) = model.query.job_name_input {
                     if !job_name.trim().is_empty() {
+                        if model.query.watch_requested {
+                            return Message::ExecuteWatch(job_name.clone());
+                        }
                         return Message::ExecuteQuery(job_name.clone());
                     }
                 }
                 Message::ClosePopup
             }
+            KeyCode::Tab | KeyCode::Down => Message::JobNameInputCycleFocus(true),
+            KeyCode::BackTab | KeyCode::Up => Message::JobNameInputCycleFocus(false),
+            // With a format toggle focused, Space toggles it and typing is ignored
+            KeyCode::Char(' ') if model.query.format_focus().is_some() => {
+                Message::JobNameInputToggleFormat
+            }
+            _ if model.query.format_focus().is_some() => Message::NoOp,
             KeyCode::Backspace => Message::JobNameInputBackspace,
             KeyCode::Char(c) => Message::JobNameInputChar(c),
             _ => Message::NoOp,
@@ -304,18 +533,283 @@ fn handle_popup_key(key: KeyCode, popup: &model::Popup, model: &Model) -> Messag
             KeyCode::Char(c) => Message::SessionNameInputChar(c),
             _ => Message::NoOp,
         },
+        model::Popup::Notifications => match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('m') => Message::ClosePopup,
+            KeyCode::Up => Message::NotificationsPrevious,
+            KeyCode::Down => Message::NotificationsNext,
+            KeyCode::Char('c') => Message::NotificationsClear,
+            _ => Message::NoOp,
+        },
+        model::Popup::ConfirmSessionsDelete => match key {
+            KeyCode::Char('y') | KeyCode::Enter => Message::SessionsDeleteMarked,
+            KeyCode::Esc | KeyCode::Char('n') => Message::ClosePopup,
+            _ => Message::NoOp,
+        },
+        model::Popup::ConfirmReauthenticate => match key {
+            KeyCode::Char('y') | KeyCode::Enter => Message::ReAuthenticate,
+            KeyCode::Esc | KeyCode::Char('n') => Message::ClosePopup,
+            _ => Message::NoOp,
+        },
+        model::Popup::ResumePackRun => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Char('y') | KeyCode::Enter => Message::PacksRun {
+                resume: true,
+                confirmed: false,
+            },
+            KeyCode::Char('n') => Message::PacksRun {
+                resume: false,
+                confirmed: false,
+            },
+            _ => Message::NoOp,
+        },
+        model::Popup::ConfirmPackRun => match key {
+            KeyCode::Enter
+                if model
+                    .packs
+                    .pending_confirmation
+                    .as_ref()
+                    .is_some_and(|confirmation| confirmation.reason.is_some()) =>
+            {
+                Message::NoOp
+            }
+            KeyCode::Char('y') | KeyCode::Enter => Message::PacksRun {
+                resume: model
+                    .packs
+                    .pending_confirmation
+                    .as_ref()
+                    .is_some_and(|confirmation| confirmation.resume),
+                confirmed: true,
+            },
+            KeyCode::Esc | KeyCode::Char('n') => Message::ClosePopup,
+            _ => Message::NoOp,
+        },
+        model::Popup::JobChart => match key {
+            KeyCode::Esc | KeyCode::Enter => Message::ClosePopup,
+            KeyCode::Char('t') => Message::JobsChartToggleStyle,
+            _ => Message::NoOp,
+        },
+        model::Popup::RowInspector => match key {
+            KeyCode::Esc | KeyCode::Char('q') => Message::ClosePopup,
+            KeyCode::Up | KeyCode::Char('k') => Message::RowInspectorMove(-1),
+            KeyCode::Down | KeyCode::Char('j') => Message::RowInspectorMove(1),
+            KeyCode::PageUp => Message::RowInspectorMove(-(model::PAGE_ROWS as isize)),
+            KeyCode::PageDown => Message::RowInspectorMove(model::PAGE_ROWS as isize),
+            KeyCode::Enter | KeyCode::Char(' ') => Message::RowInspectorToggle,
+            KeyCode::Right | KeyCode::Char('l') => Message::RowInspectorExpand(true),
+            KeyCode::Left | KeyCode::Char('h') => Message::RowInspectorExpand(false),
+            KeyCode::Char('n') => Message::RowInspectorStepRow(1),
+            KeyCode::Char('p') => Message::RowInspectorStepRow(-1),
+            _ => Message::NoOp,
+        },
+        model::Popup::PackNameInput => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => {
+                if let Some(ref name) = model.jobs.pack_name_input {
+                    if !name.trim().is_empty() {
+                        return Message::JobsExportMarked(name.clone());
+                    }
+                }
+                Message::ClosePopup
+            }
+            KeyCode::Backspace => Message::PackNameInputBackspace,
+            KeyCode::Char(c) => Message::PackNameInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::JobNoteInput => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.jobs.note_input {
+                Some(ref note) => Message::JobsSetNote(note.clone()),
+                None => Message::ClosePopup,
+            },
+            KeyCode::Backspace => Message::NoteInputBackspace,
+            KeyCode::Char(c) => Message::NoteInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::ReexportFormat(job_idx) => {
+            use crate::reexport::ResultFormat;
+            match key {
+                KeyCode::Esc => Message::ClosePopup,
+                KeyCode::Char('c') => Message::JobsReexport(*job_idx, ResultFormat::Csv),
+                KeyCode::Char('j') => Message::JobsReexport(*job_idx, ResultFormat::Json),
+                KeyCode::Char('n') => Message::JobsReexport(*job_idx, ResultFormat::Ndjson),
+                _ => Message::NoOp,
+            }
+        }
+        model::Popup::PostFilterInput => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.jobs.post_filter {
+                Some(ref input) if !input.text.trim().is_empty() => {
+                    Message::JobsApplyPostFilter(input.job_idx, input.text.clone())
+                }
+                _ => Message::ClosePopup,
+            },
+            KeyCode::Up => Message::PostFilterHistoryOlder,
+            KeyCode::Down => Message::PostFilterHistoryNewer,
+            KeyCode::Backspace => Message::PostFilterInputBackspace,
+            KeyCode::Char(c) => Message::PostFilterInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::PackTagFilter => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.packs.tag_input {
+                Some(ref tags) => Message::PacksSetTagFilter(tags.clone()),
+                None => Message::ClosePopup,
+            },
+            KeyCode::Backspace => Message::PackTagInputBackspace,
+            KeyCode::Char(c) => Message::PackTagInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::PackSaveAs => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => {
+                if let Some(ref path) = model.packs.save_as_input {
+                    if !path.trim().is_empty() {
+                        return Message::PacksSaveAs(path.clone());
+                    }
+                }
+                Message::ClosePopup
+            }
+            KeyCode::Backspace => Message::PackSaveAsInputBackspace,
+            KeyCode::Char(c) => Message::PackSaveAsInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::SessionRename => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.sessions.rename_input {
+                Some(ref name) if !name.trim().is_empty() => Message::SessionsRename(name.clone()),
+                _ => Message::ClosePopup,
+            },
+            KeyCode::Backspace => Message::SessionRenameInputBackspace,
+            KeyCode::Char(c) => Message::SessionRenameInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::PackSearch => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::PackSearchOpen,
+            KeyCode::Tab => Message::PackSearchToggleRegex,
+            KeyCode::Down => Message::PackSearchNavigate(1),
+            KeyCode::Up => Message::PackSearchNavigate(-1),
+            KeyCode::PageDown => Message::PackSearchNavigate(model::PAGE_ROWS as i32),
+            KeyCode::PageUp => Message::PackSearchNavigate(-(model::PAGE_ROWS as i32)),
+            KeyCode::Backspace => Message::PackSearchInputBackspace,
+            KeyCode::Char(c) => Message::PackSearchInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::ResultSearch => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::ResultSearchSubmit,
+            KeyCode::Tab => Message::ResultSearchToggleRegex,
+            KeyCode::Down => Message::ResultSearchNavigate(1),
+            KeyCode::Up => Message::ResultSearchNavigate(-1),
+            KeyCode::Backspace => Message::ResultSearchInputBackspace,
+            KeyCode::Char(c) => Message::ResultSearchInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::Finder => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::FinderSelect(false),
+            KeyCode::Tab => Message::FinderSelect(true),
+            KeyCode::Down => Message::FinderNavigate(1),
+            KeyCode::Up => Message::FinderNavigate(-1),
+            KeyCode::Backspace => Message::FinderInputBackspace,
+            KeyCode::Char(c) => Message::FinderInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::QueryParameters => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::QueryParamsSubmit,
+            KeyCode::Tab | KeyCode::Down => Message::QueryParamCycleFocus(true),
+            KeyCode::BackTab | KeyCode::Up => Message::QueryParamCycleFocus(false),
+            KeyCode::Backspace => Message::QueryParamInputBackspace,
+            KeyCode::Char(c) => Message::QueryParamInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::SessionDetails => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::SessionsSaveDetails,
+            KeyCode::Tab | KeyCode::BackTab => Message::SessionDetailsToggleField,
+            KeyCode::Backspace => Message::SessionDetailsInputBackspace,
+            KeyCode::Char(c) => Message::SessionDetailsInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::SessionSearch => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.sessions.search_input {
+                Some(ref search) => Message::SessionsSetSearch(search.clone()),
+                None => Message::ClosePopup,
+            },
+            KeyCode::Backspace => Message::SessionSearchInputBackspace,
+            KeyCode::Char(c) => Message::SessionSearchInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::LogSearch => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.logs.search_input {
+                Some(ref search) => Message::LogsSetSearch(search.clone()),
+                None => Message::ClosePopup,
+            },
+            KeyCode::Backspace => Message::LogSearchInputBackspace,
+            KeyCode::Char(c) => Message::LogSearchInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::PackRename => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => match model.packs.rename_input {
+                Some(ref name) if !name.trim().is_empty() => Message::PacksRename(name.clone()),
+                _ => Message::ClosePopup,
+            },
+            KeyCode::Backspace => Message::PackRenameInputBackspace,
+            KeyCode::Char(c) => Message::PackRenameInputChar(c),
+            _ => Message::NoOp,
+        },
+        model::Popup::SavedSearches => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::SavedSearchesLoadQuery,
+            KeyCode::Up => Message::SavedSearchesPrevious,
+            KeyCode::Down => Message::SavedSearchesNext,
+            KeyCode::Char('x') => Message::SavedSearchesExport,
+            _ => Message::NoOp,
+        },
+        model::Popup::JobColumns => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::JobsColumnsApply,
+            KeyCode::Up | KeyCode::Char('k') => Message::JobsColumnsNavigate(-1),
+            KeyCode::Down | KeyCode::Char('j') => Message::JobsColumnsNavigate(1),
+            KeyCode::Char('K') => Message::JobsColumnsMove(-1),
+            KeyCode::Char('J') => Message::JobsColumnsMove(1),
+            KeyCode::Char(' ') => Message::JobsColumnsToggle,
+            _ => Message::NoOp,
+        },
+        model::Popup::SubscriptionFilter => match key {
+            KeyCode::Esc => Message::ClosePopup,
+            KeyCode::Enter => Message::SubscriptionsApply,
+            KeyCode::Up => Message::SubscriptionsPrevious,
+            KeyCode::Down => Message::SubscriptionsNext,
+            KeyCode::Char(' ') => Message::SubscriptionsToggle,
+            _ => Message::NoOp,
+        },
         model::Popup::JobDetails(job_idx) => {
             match key {
                 KeyCode::Esc | KeyCode::Enter => Message::ClosePopup,
+                KeyCode::Char('g') => Message::JobsShowChart(*job_idx),
+                KeyCode::Char('i') => Message::JobsInspectRows(*job_idx),
+                KeyCode::Char('y') => Message::JobsCopyDetails(*job_idx),
+                KeyCode::Char('o') => Message::JobsOpenOutput(*job_idx),
+                KeyCode::Char('f') => Message::JobsRevealOutput(*job_idx),
+                KeyCode::Char('w') => Message::JobsStartPostFilter(*job_idx),
+                KeyCode::Char('x') => Message::JobsStartReexport(*job_idx),
                 KeyCode::Char('r') => {
                     // Validate that the job can and should be retried
                     if let Some(job) = model.jobs.jobs.get(*job_idx) {
                         use crate::tui::model::jobs::JobStatus;
 
                         // Check basic retry eligibility
-                        let can_retry =
-                            matches!(job.status, JobStatus::Failed | JobStatus::Completed)
-                                && job.retry_context.is_some();
+                        let can_retry = matches!(
+                            job.status,
+                            JobStatus::Failed
+                                | JobStatus::Completed
+                                | JobStatus::CompletedWithWarnings
+                        ) && job.retry_context.is_some();
 
                         if !can_retry {
                             return Message::ShowError(
@@ -352,24 +846,38 @@ fn handle_settings_key(key: KeyCode) -> Message {
         KeyCode::Up => Message::SettingsPrevious,
         KeyCode::Down => Message::SettingsNext,
         KeyCode::Enter | KeyCode::Char(' ') => Message::SettingsStartEdit,
+        KeyCode::Char('a') => Message::ReAuthenticate,
         _ => Message::NoOp,
     }
 }
 
 /// Handle key events for the Workspaces tab
-fn handle_workspaces_key(key: KeyCode) -> Message {
+fn handle_workspaces_key<B: QueryBackend>(key: KeyCode, model: &Model<B>) -> Message {
     match key {
+        KeyCode::Esc if model.workspaces.refreshing => Message::WorkspacesCancelRefresh,
         KeyCode::Up => Message::WorkspacesPrevious,
         KeyCode::Down => Message::WorkspacesNext,
+        KeyCode::PageUp => Message::WorkspacesJump(ListJump::PageUp),
+        KeyCode::PageDown => Message::WorkspacesJump(ListJump::PageDown),
+        KeyCode::Home | KeyCode::Char('g') => Message::WorkspacesJump(ListJump::First),
+        KeyCode::End | KeyCode::Char('G') => Message::WorkspacesJump(ListJump::Last),
         KeyCode::Char(' ') => Message::WorkspacesToggle,
         KeyCode::Char('a') => Message::WorkspacesSelectAll,
         KeyCode::Char('n') => Message::WorkspacesSelectNone,
+        KeyCode::Char('s') => Message::SubscriptionsOpen,
+        KeyCode::Char('e') => Message::WorkspacesQuickExec,
+        KeyCode::Char('o') => Message::WorkspacesCycleSort,
+        KeyCode::Char('f') => Message::SavedSearchesOpen,
         _ => Message::NoOp,
     }
 }
 
 /// Handle key events for the Query tab
-fn handle_query_key(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Message {
+fn handle_query_key<B: QueryBackend>(
+    key: KeyCode,
+    modifiers: KeyModifiers,
+    model: &Model<B>,
+) -> Message {
     // If load panel is open, handle panel-specific keys
     if model.query.load_panel.is_some() {
         match key {
@@ -389,6 +897,7 @@ fn handle_query_key(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
             match key {
                 KeyCode::Char('i') => Message::QueryEnterInsertMode,
                 KeyCode::Char('v') => Message::QueryEnterVisualMode, // Enter visual mode
+                KeyCode::Char('V') => Message::QueryEnterVisualLineMode, // Line-wise visual mode
                 KeyCode::Char('a') => Message::QueryAppend,          // Insert after cursor
                 KeyCode::Char('A') => Message::QueryAppendEnd,       // Insert at end of line
                 KeyCode::Char('o') => Message::QueryOpenBelow,       // Open new line below
@@ -403,10 +912,20 @@ fn handle_query_key(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
                 KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
                     Message::QueryRedo
                 }
+                KeyCode::Char('d') => Message::QueryLineOperator(LineOperator::Delete), // dd
+                KeyCode::Char('y') => Message::QueryLineOperator(LineOperator::Yank),   // yy
+                KeyCode::Char(digit @ '1'..='9') => Message::QueryCount(count_digit(digit)),
+                KeyCode::Char('0') if model.query.has_pending_count() => Message::QueryCount(0),
                 KeyCode::Char('c') => Message::QueryClear, // Clear all text
+                KeyCode::Char('=') => Message::QueryFormat, // Reformat the query
+                KeyCode::Char('p') => Message::QueryPasteClipboard, // Paste from system clipboard
                 KeyCode::Char('l') => Message::QueryOpenLoadPanel, // Load query from job
                 KeyCode::Char('[') => Message::QueryPrevPackQuery, // Previous query in pack
                 KeyCode::Char(']') => Message::QueryNextPackQuery, // Next query in pack
+                KeyCode::Char('n') => Message::QueryNewBuffer, // Open a new buffer
+                KeyCode::Char('}') => Message::QueryNextBuffer, // Next buffer
+                KeyCode::Char('{') => Message::QueryPrevBuffer, // Previous buffer
+                KeyCode::Char('X') => Message::QueryCloseBuffer, // Close buffer
                 // Navigation in normal mode
                 KeyCode::Char('h') | KeyCode::Left => Message::QueryMoveCursor(KeyCode::Left),
                 KeyCode::Char('j') | KeyCode::Down => Message::QueryMoveCursor(KeyCode::Down),
@@ -416,6 +935,9 @@ fn handle_query_key(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
                 KeyCode::Char('$') => Message::QueryMoveCursor(KeyCode::End),
                 KeyCode::Char('g') => Message::QueryMoveTop,
                 KeyCode::Char('G') => Message::QueryMoveBottom,
+                KeyCode::Char('z') => Message::QueryToggleFold, // Fold/unfold //#region
+                KeyCode::Char('s') => Message::QueryToggleSplit, // Show/hide last result pane
+                KeyCode::Char('R') => Message::QueryInspectResult, // Inspect last result's rows
                 _ => Message::NoOp,
             }
         }
@@ -423,13 +945,21 @@ fn handle_query_key(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
             // Insert mode - pass most keys to tui-textarea
             match key {
                 KeyCode::Esc => Message::QueryExitInsertMode,
+                KeyCode::Char('v') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    Message::QueryPasteClipboard
+                }
                 _ => Message::QueryInput(ratatui::crossterm::event::KeyEvent::new(key, modifiers)),
             }
         }
-        EditorMode::Visual => {
-            // Visual mode - text selection
+        EditorMode::Visual | EditorMode::VisualLine => {
+            // Visual mode - text selection (whole lines in line-wise mode)
             match key {
                 KeyCode::Esc => Message::QueryExitVisualMode,
+                KeyCode::Char('V') if model.query.mode == EditorMode::Visual => {
+                    Message::QueryEnterVisualLineMode
+                }
+                KeyCode::Char(digit @ '1'..='9') => Message::QueryCount(count_digit(digit)),
+                KeyCode::Char('0') if model.query.has_pending_count() => Message::QueryCount(0),
                 KeyCode::Char('y') => Message::QueryYank, // Copy selected text
                 KeyCode::Char('d') | KeyCode::Char('x') => Message::QueryDeleteSelection, // Delete selection
                 // Navigation extends selection
@@ -447,14 +977,34 @@ fn handle_query_key(key: KeyCode, modifiers: KeyModifiers, model: &Model) -> Mes
     }
 }
 
+/// Value of a count digit key
+fn count_digit(digit: char) -> usize {
+    digit.to_digit(10).unwrap_or(0) as usize
+}
+
 /// Handle key events for the Jobs tab
 fn handle_jobs_key(key: KeyCode) -> Message {
     match key {
         KeyCode::Up => Message::JobsPrevious,
         KeyCode::Down => Message::JobsNext,
+        KeyCode::PageUp => Message::JobsJump(ListJump::PageUp),
+        KeyCode::PageDown => Message::JobsJump(ListJump::PageDown),
+        KeyCode::Home | KeyCode::Char('g') => Message::JobsJump(ListJump::First),
+        KeyCode::End | KeyCode::Char('G') => Message::JobsJump(ListJump::Last),
         KeyCode::Enter => Message::JobsViewDetails,
         KeyCode::Char('c') => Message::JobsClearCompleted,
         KeyCode::Char('r') => Message::JobsRetry,
+        KeyCode::Char('s') => Message::JobsStopWatch,
+        KeyCode::Char(' ') => Message::JobsToggleMark,
+        KeyCode::Char('p') => Message::JobsStartExportMarked,
+        KeyCode::Char('t') => Message::JobsCycleTriage,
+        KeyCode::Char('n') => Message::JobsStartNote,
+        KeyCode::Char('f') => Message::JobsCycleTriageFilter,
+        KeyCode::Char('o') => Message::JobsToggleSeveritySort,
+        KeyCode::Char('+') => Message::JobsChangePriority(true),
+        KeyCode::Char('-') => Message::JobsChangePriority(false),
+        KeyCode::Char('v') => Message::JobsColumnsOpen,
+        KeyCode::Char('/') => Message::JobsStartResultSearch,
         _ => Message::NoOp,
     }
 }
@@ -464,6 +1014,10 @@ fn handle_sessions_key(key: KeyCode, modifiers: KeyModifiers) -> Message {
     match key {
         KeyCode::Up => Message::SessionsPrevious,
         KeyCode::Down => Message::SessionsNext,
+        KeyCode::PageUp => Message::SessionsJump(ListJump::PageUp),
+        KeyCode::PageDown => Message::SessionsJump(ListJump::PageDown),
+        KeyCode::Home | KeyCode::Char('g') => Message::SessionsJump(ListJump::First),
+        KeyCode::End | KeyCode::Char('G') => Message::SessionsJump(ListJump::Last),
         KeyCode::Char('n') => Message::SessionsStartNew,
         KeyCode::Char('s') => {
             // 's' = save current session
@@ -476,7 +1030,30 @@ fn handle_sessions_key(key: KeyCode, modifiers: KeyModifiers) -> Message {
         }
         KeyCode::Char('l') => Message::SessionsLoad,
         KeyCode::Char('d') => Message::SessionsDelete,
+        KeyCode::Char('D') => Message::SessionsStartDeleteMarked,
+        KeyCode::Char(' ') => Message::SessionsToggleMark,
+        KeyCode::Char('a') => Message::SessionsMarkAll,
+        KeyCode::Char('A') => Message::SessionsUnmarkAll,
         KeyCode::Char('p') => Message::SessionExportAsPack,
+        KeyCode::Char('R') => Message::SessionsStartRename,
+        KeyCode::Char('e') => Message::SessionsStartEditDetails,
+        KeyCode::Char('/') => Message::SessionsStartSearch,
+        _ => Message::NoOp,
+    }
+}
+
+/// Handle key events for the Logs tab
+fn handle_logs_key(key: KeyCode) -> Message {
+    match key {
+        KeyCode::Up => Message::LogsPrevious,
+        KeyCode::Down => Message::LogsNext,
+        KeyCode::PageUp => Message::LogsJump(ListJump::PageUp),
+        KeyCode::PageDown => Message::LogsJump(ListJump::PageDown),
+        KeyCode::Home | KeyCode::Char('g') => Message::LogsJump(ListJump::First),
+        KeyCode::End | KeyCode::Char('G') => Message::LogsJump(ListJump::Last),
+        KeyCode::Char('f') => Message::LogsToggleFollow,
+        KeyCode::Char('l') => Message::LogsCycleLevel,
+        KeyCode::Char('/') => Message::LogsStartSearch,
         _ => Message::NoOp,
     }
 }
@@ -486,10 +1063,47 @@ fn handle_packs_key(key: KeyCode) -> Message {
     match key {
         KeyCode::Up => Message::PacksPrevious,
         KeyCode::Down => Message::PacksNext,
+        KeyCode::Right => Message::PacksNextQuery,
+        KeyCode::Left => Message::PacksPreviousQuery,
+        KeyCode::Esc => Message::PacksClearQuery,
+        KeyCode::PageUp => Message::PacksJump(ListJump::PageUp),
+        KeyCode::PageDown => Message::PacksJump(ListJump::PageDown),
+        KeyCode::Home | KeyCode::Char('g') => Message::PacksJump(ListJump::First),
+        KeyCode::End | KeyCode::Char('G') => Message::PacksJump(ListJump::Last),
         KeyCode::Char('r') => Message::PacksRefresh,
         KeyCode::Enter => Message::PacksLoadQuery,
         KeyCode::Char('e') => Message::PacksExecute,
         KeyCode::Char('s') => Message::PacksSave,
+        KeyCode::Char('S') => Message::PacksStartSaveAs,
+        KeyCode::Char('p') => Message::PacksPull,
+        KeyCode::Char('t') => Message::PacksStartTagFilter,
+        KeyCode::Char('R') => Message::PacksStartRename,
+        KeyCode::Char('/') => Message::PacksStartSearch,
         _ => Message::NoOp,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FakeBackend;
+
+    #[test]
+    fn test_digits_are_counts_in_query_normal_mode() {
+        let mut model = Model::new(FakeBackend::new());
+        model.current_tab = Tab::Query;
+        model.query.mode = EditorMode::Normal;
+        for digit in '1'..='8' {
+            assert!(matches!(
+                handle_key_event(KeyCode::Char(digit), KeyModifiers::NONE, &model),
+                Message::QueryCount(_)
+            ));
+        }
+
+        model.current_tab = Tab::Jobs;
+        assert!(matches!(
+            handle_key_event(KeyCode::Char('8'), KeyModifiers::NONE, &model),
+            Message::SwitchTab(Tab::Logs)
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::units::format_bytes;
+use chrono::DateTime;
+use std::path::Path;
+
+/// Maximum number of points kept for charting (newest are kept)
+const MAX_CHART_POINTS: usize = 1000;
+
+/// Largest output file read for charting (a chartable result is a few columns of summarized rows)
+const MAX_CHART_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Chart rendering style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartStyle {
+    Bar,
+    Sparkline,
+}
+
+impl ChartStyle {
+    pub fn toggle(self) -> Self {
+        match self {
+            ChartStyle::Bar => ChartStyle::Sparkline,
+            ChartStyle::Sparkline => ChartStyle::Bar,
+        }
+    }
+}
+
+/// A numeric column plotted against the label column
+#[derive(Debug, Clone)]
+pub struct ChartSeries {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+impl ChartSeries {
+    pub fn min(&self) -> f64 {
+        self.values.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    pub fn total(&self) -> f64 {
+        self.values.iter().sum()
+    }
+}
+
+/// Chartable data extracted from a job's output file
+/// Supports results with 2-3 columns: one label (time or text) column plus numeric columns,
+/// e.g. `summarize count() by bin(TimeGenerated, 1h)`
+#[derive(Debug, Clone)]
+pub struct ChartData {
+    /// Name of the label (x-axis) column
+    pub label_column: String,
+    /// Label for each point
+    pub labels: Vec<String>,
+    /// One or two numeric series
+    pub series: Vec<ChartSeries>,
+    /// Whether the label column holds timestamps (points are sorted by time)
+    pub is_time_series: bool,
+}
+
+/// Chart popup state for the Jobs tab
+#[derive(Debug, Clone)]
+pub struct JobChart {
+    pub title: String,
+    pub data: ChartData,
+    pub style: ChartStyle,
+}
+
+impl ChartData {
+    /// Load chart data from a job output file (.csv, .json or .jsonl)
+    pub fn load(path: &Path) -> Result<Self, String> {
+        Self::load_within(path, MAX_CHART_FILE_BYTES)
+    }
+
+    /// Load chart data, refusing files larger than `max_bytes` before reading them
+    fn load_within(path: &Path, max_bytes: u64) -> Result<Self, String> {
+        let size = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .len();
+        if size > max_bytes {
+            return Err(format!(
+                "Result file is {}; charts read at most {}. Summarize the query into fewer rows",
+                format_bytes(size),
+                format_bytes(max_bytes)
+            ));
+        }
+        let (columns, rows) = read_result_table(path)?;
+        Self::from_table(columns, rows)
+    }
+
+    /// Build chart data from a table of string cells
+    pub fn from_table(columns: Vec<String>, rows: Vec<Vec<String>>) -> Result<Self, String> {
+        if !(2..=3).contains(&columns.len()) {
+            return Err(format!(
+                "Charts need 2 or 3 columns (label + numeric values), result has {}",
+                columns.len()
+            ));
+        }
+        if rows.is_empty() {
+            return Err("Result has no rows to chart".to_string());
+        }
+
+        let column_values = |idx: usize| rows.iter().map(move |row| row[idx].as_str());
+        let is_numeric = |idx: usize| column_values(idx).all(|v| v.parse::<f64>().is_ok());
+        let is_time =
+            |idx: usize| column_values(idx).all(|v| DateTime::parse_from_rfc3339(v).is_ok());
+
+        // Prefer a time column for labels, then a non-numeric column, then the first column
+        let label_idx = (0..columns.len())
+            .find(|&i| is_time(i))
+            .or_else(|| (0..columns.len()).find(|&i| !is_numeric(i)))
+            .unwrap_or(0);
+        let is_time_series = is_time(label_idx);
+
+        let series_indices: Vec<usize> = (0..columns.len()).filter(|&i| i != label_idx).collect();
+        if let Some(&idx) = series_indices.iter().find(|&&i| !is_numeric(i)) {
+            return Err(format!(
+                "Column '{}' is not numeric; charts need one label column and numeric values",
+                columns[idx]
+            ));
+        }
+
+        let mut rows = rows;
+        if is_time_series {
+            rows.sort_by_key(|row| DateTime::parse_from_rfc3339(&row[label_idx]).ok());
+        }
+        if rows.len() > MAX_CHART_POINTS {
+            rows.drain(..rows.len() - MAX_CHART_POINTS);
+        }
+
+        let labels = rows
+            .iter()
+            .map(|row| {
+                let label = &row[label_idx];
+                match DateTime::parse_from_rfc3339(label) {
+                    Ok(ts) if is_time_series => ts.format("%m-%d %H:%M").to_string(),
+                    _ => label.clone(),
+                }
+            })
+            .collect();
+
+        let series = series_indices
+            .iter()
+            .map(|&idx| ChartSeries {
+                name: columns[idx].clone(),
+                values: rows
+                    .iter()
+                    .map(|row| row[idx].parse::<f64>().unwrap_or(0.0))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            label_column: columns[label_idx].clone(),
+            labels,
+            series,
+            is_time_series,
+        })
+    }
+}
+
+/// Parse CSV written by the exporter (quoted fields, doubled quotes)
+/// Read a job output file (.csv, .json or .jsonl) as column names and rows of string cells
+pub fn read_result_table(path: &Path) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => Ok(parse_csv(&content)),
+        Some("json") => parse_json(&content),
+        Some("jsonl") => parse_jsonl(&content),
+        _ => Err(format!("Unsupported output file: {}", path.display())),
+    }
+}
+
+fn parse_csv(content: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut records = crate::post_filter::split_csv_records(content)
+        .into_iter()
+        .map(|(_, fields)| fields);
+    let columns = records.next().unwrap_or_default();
+    let rows = records.filter(|r| r.len() == columns.len()).collect();
+    (columns, rows)
+}
+
+/// Convert a JSON cell to the string form used for charting
+fn json_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Parse the exporter's JSON format ({ metadata, columns, rows })
+fn parse_json(content: &str) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid JSON output: {}", e))?;
+
+    let columns: Vec<String> = value["columns"]
+        .as_array()
+        .ok_or("JSON output has no columns")?
+        .iter()
+        .filter_map(|c| c["name"].as_str().map(str::to_string))
+        .collect();
+
+    let rows = value["rows"]
+        .as_array()
+        .ok_or("JSON output has no rows")?
+        .iter()
+        .map(|row| columns.iter().map(|c| json_cell(&row[c])).collect())
+        .collect();
+
+    Ok((columns, rows))
+}
+
+/// Parse newline-delimited JSON rows (watch mode output)
+fn parse_jsonl(content: &str) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let objects: Vec<serde_json::Map<String, serde_json::Value>> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| format!("Invalid JSON line: {}", e)))
+        .collect::<Result<_, _>>()?;
+
+    let columns: Vec<String> = objects
+        .first()
+        .map(|o| o.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let rows = objects
+        .iter()
+        .map(|o| {
+            columns
+                .iter()
+                .map(|c| o.get(c).map(json_cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    Ok((columns, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_refuses_large_files() {
+        let path = std::env::temp_dir().join(format!("kql-chart-{}.csv", std::process::id()));
+        std::fs::write(&path, "Computer,count_\nhost1,5\nhost2,3\n").unwrap();
+
+        let err = ChartData::load_within(&path, 16).unwrap_err();
+        assert!(err.contains("charts read at most 16 B"), "{}", err);
+        assert_eq!(
+            ChartData::load(&path).unwrap().labels,
+            vec!["host1", "host2"]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_time_series_from_csv() {
+        let csv = "TimeGenerated,count_\n\
+                   2024-01-01T02:00:00Z,5\n\
+                   2024-01-01T00:00:00Z,3\n\
+                   2024-01-01T01:00:00Z,\"4\"\n";
+        let (columns, rows) = parse_csv(csv);
+        let data = ChartData::from_table(columns, rows).unwrap();
+
+        assert!(data.is_time_series);
+        assert_eq!(data.label_column, "TimeGenerated");
+        assert_eq!(data.series.len(), 1);
+        assert_eq!(data.series[0].values, vec![3.0, 4.0, 5.0]);
+        assert_eq!(data.labels[0], "01-01 00:00");
+    }
+
+    #[test]
+    fn test_rejects_unchartable_results() {
+        let columns = vec!["Computer".to_string(), "Account".to_string()];
+        let rows = vec![vec!["host1".to_string(), "alice".to_string()]];
+        assert!(ChartData::from_table(columns, rows).is_err());
+
+        let columns = vec!["a".to_string()];
+        let rows = vec![vec!["1".to_string()]];
+        assert!(ChartData::from_table(columns, rows).is_err());
+    }
+
+    #[test]
+    fn test_csv_quoted_fields() {
+        let (columns, rows) = parse_csv("a,b\r\n\"x, y\",\"say \"\"hi\"\"\"\r\n");
+        assert_eq!(columns, vec!["a", "b"]);
+        assert_eq!(rows, vec![vec!["x, y", "say \"hi\""]]);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::query_pack::Severity;
+use crate::tui::model::jobs::{JobState, JobStatus};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Maximum number of entries shown in each dashboard list
+const MAX_LIST_ENTRIES: usize = 5;
+
+/// Aggregated statistics across all jobs in the current session
+#[derive(Debug, Clone, Default)]
+pub struct DashboardStats {
+    /// Total number of jobs
+    pub total: usize,
+    /// Jobs waiting to run
+    pub queued: usize,
+    /// Jobs currently running
+    pub running: usize,
+    /// Jobs that completed successfully
+    pub completed: usize,
+    /// Jobs that failed
+    pub failed: usize,
+    /// Rows returned per query (job name), highest severity then most rows first
+    pub rows_per_query: Vec<QueryRows>,
+    /// Completed jobs that returned no rows
+    pub no_hits: usize,
+    /// Slowest workspaces by max job duration, sorted descending
+    pub slowest_workspaces: Vec<(String, Duration)>,
+    /// Most recent failures as (workspace, short error description)
+    pub recent_failures: Vec<(String, String)>,
+    /// Total size of output files written (bytes)
+    pub output_bytes: u64,
+    /// Estimated billed bytes scanned, summed over jobs with a cost estimate
+    pub scanned_bytes: u64,
+    /// Number of jobs with a cost estimate
+    pub estimated_jobs: usize,
+    /// Query CPU time in seconds, summed over jobs with statistics
+    pub cpu_secs: f64,
+    /// Number of jobs with CPU statistics
+    pub cpu_jobs: usize,
+    /// Durations of finished jobs in milliseconds (chronological, for sparklines)
+    pub duration_history: Vec<u64>,
+}
+
+/// Rows returned by one query across the session's jobs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryRows {
+    pub name: String,
+    pub severity: Option<Severity>,
+    pub rows: usize,
+    /// Completed jobs running the query
+    pub jobs: usize,
+    /// Completed jobs running the query that returned no rows
+    pub no_hits: usize,
+}
+
+impl QueryRows {
+    /// The query's severity, if it had hits
+    pub fn hit_severity(&self) -> Option<Severity> {
+        self.severity.filter(|_| self.rows > 0)
+    }
+}
+
+impl DashboardStats {
+    /// Compute dashboard statistics from the current job list
+    pub fn from_jobs(jobs: &[JobState]) -> Self {
+        let mut stats = Self {
+            total: jobs.len(),
+            ..Default::default()
+        };
+
+        let mut rows_per_query: HashMap<String, QueryRows> = HashMap::new();
+        let mut slowest: HashMap<String, Duration> = HashMap::new();
+        let mut failures = Vec::new();
+        let mut finished = Vec::new();
+
+        for job in jobs {
+            match job.status {
+                JobStatus::Queued => stats.queued += 1,
+                JobStatus::Running => stats.running += 1,
+                JobStatus::Completed | JobStatus::CompletedWithWarnings => stats.completed += 1,
+                JobStatus::Failed => stats.failed += 1,
+            }
+
+            if let Some(duration) = job.duration {
+                let entry = slowest
+                    .entry(job.workspace_name.clone())
+                    .or_insert(Duration::ZERO);
+                *entry = (*entry).max(duration);
+            }
+
+            let Some(result) = &job.result else {
+                continue;
+            };
+
+            finished.push((result.timestamp, result.elapsed));
+
+            match &result.result {
+                Ok(success) => {
+                    let query_name = job
+                        .retry_context
+                        .as_ref()
+                        .map(|ctx| ctx.settings.job_name.clone())
+                        .unwrap_or_else(|| job.query_preview.chars().take(30).collect());
+                    let entry =
+                        rows_per_query
+                            .entry(query_name.clone())
+                            .or_insert_with(|| QueryRows {
+                                name: query_name,
+                                severity: None,
+                                rows: 0,
+                                jobs: 0,
+                                no_hits: 0,
+                            });
+                    entry.severity = entry.severity.max(job.severity);
+                    entry.rows += success.row_count;
+                    entry.jobs += 1;
+                    if success.row_count == 0 {
+                        entry.no_hits += 1;
+                        stats.no_hits += 1;
+                    }
+                    stats.output_bytes += success.file_size;
+                    if let Some(billed_bytes) = success.billed_bytes {
+                        stats.scanned_bytes += billed_bytes;
+                        stats.estimated_jobs += 1;
+                    }
+                    if let Some(cpu_secs) = success.statistics.as_ref().and_then(|s| s.cpu_secs) {
+                        stats.cpu_secs += cpu_secs;
+                        stats.cpu_jobs += 1;
+                    }
+                }
+                Err(e) => {
+                    let description = job
+                        .error
+                        .as_ref()
+                        .map(|err| err.short_description())
+                        .unwrap_or_else(|| e.to_string());
+                    failures.push((result.timestamp, job.workspace_name.clone(), description));
+                }
+            }
+        }
+
+        stats.rows_per_query = rows_per_query.into_values().collect();
+        // Queries with hits sort by severity; those without hits go last whatever their severity
+        stats.rows_per_query.sort_by(|a, b| {
+            b.hit_severity()
+                .cmp(&a.hit_severity())
+                .then_with(|| b.rows.cmp(&a.rows))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        stats.rows_per_query.truncate(MAX_LIST_ENTRIES);
+
+        stats.slowest_workspaces = slowest.into_iter().collect();
+        stats
+            .slowest_workspaces
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats.slowest_workspaces.truncate(MAX_LIST_ENTRIES);
+
+        // Newest failures first
+        failures.sort_by_key(|f| std::cmp::Reverse(f.0));
+        stats.recent_failures = failures
+            .into_iter()
+            .take(MAX_LIST_ENTRIES)
+            .map(|(_, workspace, description)| (workspace, description))
+            .collect();
+
+        // Oldest first so the sparkline reads left-to-right in time
+        finished.sort_by_key(|f| f.0);
+        stats.duration_history = finished
+            .into_iter()
+            .map(|(_, elapsed)| elapsed.as_millis() as u64)
+            .collect();
+
+        stats
+    }
+
+    /// Number of jobs that have finished (completed or failed)
+    pub fn finished(&self) -> usize {
+        self.completed + self.failed
+    }
+
+    /// Fraction of jobs finished, between 0.0 and 1.0
+    pub fn progress_ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.finished() as f64 / self.total as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KqlPanopticonError;
+    use crate::query_job::{JobSuccess, QueryJobResult};
+    use crate::tui::model::jobs::JobsModel;
+
+    /// Add a job for `query` in `workspace` and finish it with `rows` (or a failure if None)
+    fn finish(
+        model: &mut JobsModel,
+        workspace: &str,
+        query: &str,
+        rows: Option<usize>,
+        elapsed_ms: u64,
+    ) -> u64 {
+        model.add_job(workspace.to_string(), query.to_string());
+        let job_id = model.jobs.last().unwrap().job_id;
+        let result = match rows {
+            Some(rows) => Ok(JobSuccess {
+                row_count: rows,
+                page_count: 1,
+                output_path: Default::default(),
+                file_size: 100,
+                truncated: false,
+                billed_bytes: None,
+                warnings: Vec::new(),
+                statistics: None,
+                uploads: Vec::new(),
+            }),
+            None => Err(KqlPanopticonError::Other("boom".to_string())),
+        };
+        model.complete_job(
+            job_id,
+            QueryJobResult {
+                workspace_id: format!("{}-id", workspace),
+                workspace_name: workspace.to_string(),
+                query: query.to_string(),
+                result,
+                elapsed: Duration::from_millis(elapsed_ms),
+                timestamp: crate::time_zone::now()
+                    + chrono::Duration::seconds(model.jobs.len() as i64),
+            },
+        );
+        job_id
+    }
+
+    #[test]
+    fn test_from_jobs() {
+        let mut model = JobsModel::new();
+        let high = finish(&mut model, "ws-a", "SigninLogs", Some(3), 200);
+        model.set_severity(high, Some(Severity::High));
+        finish(&mut model, "ws-b", "SigninLogs", Some(0), 100);
+        finish(&mut model, "ws-a", "AuditLogs", Some(5), 50);
+        finish(&mut model, "ws-c", "AuditLogs", None, 300);
+        model.add_job("ws-d".to_string(), "AuditLogs".to_string());
+        model.add_job("ws-e".to_string(), "AuditLogs".to_string());
+        let running = model.jobs.last().unwrap().job_id;
+        model.start_job(running);
+
+        let stats = DashboardStats::from_jobs(&model.jobs);
+        assert_eq!(stats.total, 6);
+        assert_eq!(stats.queued, 1);
+        assert_eq!(stats.running, 1);
+        assert_eq!(stats.completed, 3);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.finished(), 4);
+        assert_eq!(stats.no_hits, 1);
+        assert_eq!(stats.output_bytes, 300);
+
+        // The query with a severity ranks above the one with more rows
+        assert_eq!(
+            stats.rows_per_query,
+            vec![
+                QueryRows {
+                    name: "SigninLogs".to_string(),
+                    severity: Some(Severity::High),
+                    rows: 3,
+                    jobs: 2,
+                    no_hits: 1,
+                },
+                QueryRows {
+                    name: "AuditLogs".to_string(),
+                    severity: None,
+                    rows: 5,
+                    jobs: 1,
+                    no_hits: 0,
+                },
+            ]
+        );
+
+        let slowest: Vec<_> = stats
+            .slowest_workspaces
+            .iter()
+            .map(|(workspace, duration)| (workspace.as_str(), duration.as_millis()))
+            .collect();
+        assert_eq!(slowest, [("ws-c", 300), ("ws-a", 200), ("ws-b", 100)]);
+        assert_eq!(stats.recent_failures.len(), 1);
+        assert_eq!(stats.recent_failures[0].0, "ws-c");
+        assert_eq!(stats.duration_history, [200, 100, 50, 300]);
+    }
+}
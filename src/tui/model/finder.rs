@@ -0,0 +1,172 @@
+use crate::tui::model::packs::PacksModel;
+use crate::tui::model::session::SessionModel;
+
+/// Most matches listed at once
+const MAX_RESULTS: usize = 50;
+
+/// What a finder result points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinderTarget {
+    /// Index into `PacksModel::packs`
+    Pack(usize),
+    /// Pack index and the query's index within the pack
+    PackQuery(usize, usize),
+    /// Index into `SessionModel::sessions`
+    Session(usize),
+}
+
+impl FinderTarget {
+    /// Short kind label shown before each result
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FinderTarget::Pack(_) => "pack",
+            FinderTarget::PackQuery(..) => "query",
+            FinderTarget::Session(_) => "session",
+        }
+    }
+}
+
+/// A searchable pack, pack query or session
+#[derive(Debug, Clone)]
+pub struct FinderItem {
+    pub target: FinderTarget,
+    /// Text matched against and shown (a query is shown with its pack's name)
+    pub label: String,
+}
+
+/// Fuzzy finder overlay state (while the finder popup is open)
+#[derive(Debug, Clone)]
+pub struct FinderModel {
+    pub input: String,
+    items: Vec<FinderItem>,
+    /// Indices into `items` matching the input, best first
+    pub matches: Vec<usize>,
+    /// Index into `matches` of the highlighted result
+    pub selected: usize,
+}
+
+impl FinderModel {
+    /// Index the packs (which must already be loaded) and sessions
+    pub fn new(packs: &PacksModel, sessions: &SessionModel) -> Self {
+        let mut items = Vec::new();
+        for (pack_idx, entry) in packs.packs.iter().enumerate() {
+            let pack_name = entry.get_display_name();
+            items.push(FinderItem {
+                target: FinderTarget::Pack(pack_idx),
+                label: pack_name.clone(),
+            });
+            if let Some(pack) = &entry.pack {
+                for (query_idx, query) in pack.get_queries().iter().enumerate() {
+                    items.push(FinderItem {
+                        target: FinderTarget::PackQuery(pack_idx, query_idx),
+                        label: format!("{} › {}", pack_name, query.name),
+                    });
+                }
+            }
+        }
+        for (session_idx, session) in sessions.sessions.iter().enumerate() {
+            items.push(FinderItem {
+                target: FinderTarget::Session(session_idx),
+                label: session.name.clone(),
+            });
+        }
+
+        let mut finder = Self {
+            input: String::new(),
+            items,
+            matches: Vec::new(),
+            selected: 0,
+        };
+        finder.filter();
+        finder
+    }
+
+    /// Re-rank the items against the input, highlighting the best match
+    pub fn filter(&mut self) {
+        let mut scored: Vec<(u32, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, item)| {
+                crate::fuzzy::score(&self.input, &item.label).map(|score| (score, idx))
+            })
+            .collect();
+        // Best score first; ties keep list order
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, idx)| idx)
+            .collect();
+        self.selected = 0;
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.input.push(c);
+        self.filter();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.input.pop();
+        self.filter();
+    }
+
+    /// Move the highlight by `delta`, wrapping around
+    pub fn navigate(&mut self, delta: i32) {
+        let len = self.matches.len() as i32;
+        if len > 0 {
+            self.selected = (self.selected as i32 + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Matching items, best first
+    pub fn results(&self) -> impl Iterator<Item = &FinderItem> {
+        self.matches.iter().map(|&idx| &self.items[idx])
+    }
+
+    /// Target of the highlighted result
+    pub fn selected_target(&self) -> Option<FinderTarget> {
+        self.matches
+            .get(self.selected)
+            .map(|&idx| self.items[idx].target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::model::session::{SessionEntry, SessionState};
+
+    #[test]
+    fn test_finder_ranks_and_navigates() {
+        let mut sessions = SessionModel::new();
+        for name in ["incident-4711", "weekly-hunt", "phishing-triage"] {
+            sessions.sessions.push(SessionEntry {
+                name: name.to_string(),
+                state: SessionState::Loadable,
+                last_saved: None,
+                created_from_pack: None,
+                disk_usage: None,
+                tags: Vec::new(),
+                description: String::new(),
+            });
+        }
+        let mut finder = FinderModel::new(&PacksModel::new(), &sessions);
+        assert_eq!(finder.matches.len(), 3);
+
+        for c in "hunt".chars() {
+            finder.push_char(c);
+        }
+        assert_eq!(finder.selected_target(), Some(FinderTarget::Session(1)));
+
+        finder.pop_char();
+        finder.pop_char();
+        finder.pop_char();
+        finder.pop_char();
+        finder.navigate(-1);
+        assert_eq!(finder.selected_target(), Some(FinderTarget::Session(2)));
+
+        finder.push_char('z');
+        assert!(finder.selected_target().is_none());
+    }
+}
@@ -1,7 +1,15 @@
-use crate::query_job::{QueryJobResult, QuerySettings};
+use crate::query_job::{JobSuccess, QueryJobResult, QuerySettings};
+use crate::query_pack::Severity;
+use crate::result_search::{FileMatches, SearchTarget};
+use crate::scheduler::{JobPriority, SharedPriority};
+use crate::tui::model::ListJump;
+use crate::watch::WatchTick;
 use crate::workspace::Workspace;
 use ratatui::widgets::TableState;
-use std::time::Duration;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 
 /// Context needed to retry a job
 #[derive(Debug, Clone)]
@@ -11,6 +19,39 @@ pub struct RetryContext {
     pub settings: QuerySettings,
 }
 
+impl RetryContext {
+    /// Key shared by jobs running the same query on the same workspace with the same settings
+    /// The job name is left out, since it only names the output files.
+    pub fn dedup_key(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut settings = self.settings.clone();
+        settings.job_name.clear();
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.query.trim().hash(&mut hasher);
+        self.workspace.workspace_id.hash(&mut hasher);
+        serde_json::to_string(&settings)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// State of a watch (live tail) job
+#[derive(Debug, Clone)]
+pub struct WatchState {
+    /// Time between polls
+    pub interval: Duration,
+    /// Number of polls completed
+    pub runs: u32,
+    /// Rows appended by the most recent poll
+    pub last_new_rows: usize,
+    /// Rows appended since the watch started
+    pub total_rows: usize,
+    /// Signals the background task to stop
+    pub stop: Arc<Notify>,
+}
+
 /// Structured job error information for better user feedback
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum JobError {
@@ -30,9 +71,22 @@ pub enum JobError {
     Network {
         message: String,
         status_code: Option<u16>,
+        /// Transport failure kind (None for sessions saved before it was recorded)
+        #[serde(default)]
+        kind: Option<crate::error::TransportErrorKind>,
     },
+    /// Query references a table the workspace doesn't have
+    TableNotFound { table: String, message: String },
+    /// Query is valid syntax but Azure couldn't resolve it (unknown column or function, type mismatch)
+    SemanticError { message: String },
     /// Azure API error
-    AzureApi { status: u16, message: String },
+    AzureApi {
+        status: u16,
+        message: String,
+        /// Azure error code (None for sessions saved before it was recorded)
+        #[serde(default)]
+        code: Option<String>,
+    },
     /// General error
     Other { message: String },
 }
@@ -46,16 +100,19 @@ impl JobError {
             }
             JobError::Authentication { .. } => "Auth Failed".to_string(),
             JobError::QuerySyntax { .. } => "Query Error".to_string(),
-            JobError::Network { status_code, .. } => {
-                if let Some(code) = status_code {
-                    format!("Network Error ({})", code)
-                } else {
-                    "Network Error".to_string()
-                }
-            }
-            JobError::AzureApi { status, .. } => {
-                format!("Azure API Error ({})", status)
-            }
+            JobError::TableNotFound { table, .. } => format!("No Table ({})", table),
+            JobError::SemanticError { .. } => "Semantic Error".to_string(),
+            JobError::Network {
+                status_code, kind, ..
+            } => match (status_code, kind) {
+                (Some(code), _) => format!("Network Error ({})", code),
+                (None, Some(kind)) => format!("Network Error ({})", kind.as_str()),
+                (None, None) => "Network Error".to_string(),
+            },
+            JobError::AzureApi { status, code, .. } => match code {
+                Some(code) => format!("Azure API Error ({} {})", status, code),
+                None => format!("Azure API Error ({})", status),
+            },
             JobError::Other { .. } => "Failed".to_string(),
         }
     }
@@ -82,17 +139,25 @@ impl JobError {
                     format!("Query syntax error: {}", message)
                 }
             }
+            JobError::TableNotFound { table, message } => {
+                format!(
+                    "Table '{}' doesn't exist in this workspace (or isn't ingesting data yet)\n\n{}",
+                    table, message
+                )
+            }
+            JobError::SemanticError { message } => format!("Semantic error: {}", message),
             JobError::Network {
                 message,
                 status_code,
+                kind,
+            } => match (status_code, kind) {
+                (Some(code), _) => format!("Network error (HTTP {}): {}", code, message),
+                (None, Some(kind)) => format!("Network error ({}): {}", kind.as_str(), message),
+                (None, None) => format!("Network error: {}", message),
+            },
+            JobError::AzureApi {
+                status, message, ..
             } => {
-                if let Some(code) = status_code {
-                    format!("Network error (HTTP {}): {}", code, message)
-                } else {
-                    format!("Network error: {}", message)
-                }
-            }
-            JobError::AzureApi { status, message } => {
                 format!("Azure API error (status {}): {}", status, message)
             }
             JobError::Other { message } => message.clone(),
@@ -105,7 +170,8 @@ impl JobError {
         match self {
             // Transient errors - may recover on retry
             JobError::Timeout { .. } => true, // Network/server may recover
-            JobError::Network { .. } => true, // Connectivity issues are transient
+            // Connectivity issues are transient; TLS/proxy/request failures need fixing first
+            JobError::Network { kind, .. } => kind.is_none_or(|kind| kind.is_retryable()),
             JobError::Authentication { .. } => true, // Token may refresh
             JobError::AzureApi { status, .. } => {
                 // Retry 5xx server errors, not 4xx client errors
@@ -113,7 +179,9 @@ impl JobError {
             }
             // Permanent errors - won't fix themselves
             JobError::QuerySyntax { .. } => false, // Query must be fixed first
-            JobError::Other { .. } => false,       // Unknown error - don't retry
+            JobError::TableNotFound { .. } => false,
+            JobError::SemanticError { .. } => false,
+            JobError::Other { .. } => false, // Unknown error - don't retry
         }
     }
 }
@@ -130,6 +198,41 @@ pub struct JobState {
     pub result: Option<QueryJobResult>,
     pub error: Option<JobError>,
     pub retry_context: Option<RetryContext>,
+    /// Set for watch jobs that re-run on an interval
+    pub watch: Option<WatchState>,
+    /// Analyst triage verdict (persisted in sessions)
+    pub triage: Option<TriageStatus>,
+    /// Free-form analyst note (persisted in sessions)
+    pub note: String,
+    /// Name of the query: the name entered for an ad-hoc run, or the pack query's name
+    pub query_name: Option<String>,
+    /// What the query looks for, entered with an ad-hoc run or taken from the pack
+    pub description: Option<String>,
+    /// Severity of the pack query the job ran
+    pub severity: Option<Severity>,
+    /// Scheduling priority while queued (shared with the job's task)
+    pub priority: SharedPriority,
+}
+
+impl JobState {
+    /// Query name, falling back to the job name used for the output files
+    pub fn display_name(&self) -> Option<&str> {
+        self.query_name.as_deref().or_else(|| {
+            self.retry_context
+                .as_ref()
+                .map(|context| context.settings.job_name.as_str())
+        })
+    }
+
+    /// Severity of the job's results, if it returned rows
+    pub fn hit_severity(&self) -> Option<Severity> {
+        let rows = self
+            .result
+            .as_ref()
+            .and_then(|r| r.result.as_ref().ok())
+            .map_or(0, |success| success.row_count);
+        self.severity.filter(|_| rows > 0)
+    }
 }
 
 /// Job status
@@ -138,6 +241,8 @@ pub enum JobStatus {
     Queued,
     Running,
     Completed,
+    /// Completed, but the service reported errors alongside partial results
+    CompletedWithWarnings,
     Failed,
 }
 
@@ -147,6 +252,7 @@ impl JobStatus {
             JobStatus::Queued => "QUEUED",
             JobStatus::Running => "RUNNING",
             JobStatus::Completed => "COMPLETED",
+            JobStatus::CompletedWithWarnings => "PARTIAL",
             JobStatus::Failed => "FAILED",
         }
     }
@@ -157,11 +263,294 @@ impl JobStatus {
             JobStatus::Queued => Color::Yellow,
             JobStatus::Running => Color::Cyan,
             JobStatus::Completed => Color::Green,
+            JobStatus::CompletedWithWarnings => Color::LightYellow,
             JobStatus::Failed => Color::Red,
         }
     }
 }
 
+/// Analyst triage verdict for a job's results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TriageStatus {
+    Reviewed,
+    Interesting,
+    FalsePositive,
+}
+
+impl TriageStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriageStatus::Reviewed => "Reviewed",
+            TriageStatus::Interesting => "Interesting",
+            TriageStatus::FalsePositive => "False Positive",
+        }
+    }
+
+    pub fn color(&self) -> ratatui::style::Color {
+        use ratatui::style::Color;
+        match self {
+            TriageStatus::Reviewed => Color::Blue,
+            TriageStatus::Interesting => Color::Magenta,
+            TriageStatus::FalsePositive => Color::DarkGray,
+        }
+    }
+
+    /// Next verdict in the cycle: none -> reviewed -> interesting -> false positive -> none
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(TriageStatus::Reviewed),
+            Some(TriageStatus::Reviewed) => Some(TriageStatus::Interesting),
+            Some(TriageStatus::Interesting) => Some(TriageStatus::FalsePositive),
+            Some(TriageStatus::FalsePositive) => None,
+        }
+    }
+}
+
+/// Which jobs the Jobs table shows, by triage verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TriageFilter {
+    #[default]
+    All,
+    Untriaged,
+    Only(TriageStatus),
+}
+
+impl TriageFilter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriageFilter::All => "All",
+            TriageFilter::Untriaged => "Untriaged",
+            TriageFilter::Only(status) => status.as_str(),
+        }
+    }
+
+    /// Next filter in the cycle
+    pub fn next(&self) -> Self {
+        match self {
+            TriageFilter::All => TriageFilter::Untriaged,
+            TriageFilter::Untriaged => TriageFilter::Only(TriageStatus::Reviewed),
+            TriageFilter::Only(status) => match TriageStatus::cycle(Some(*status)) {
+                Some(next) => TriageFilter::Only(next),
+                None => TriageFilter::All,
+            },
+        }
+    }
+
+    /// Check whether a job passes this filter
+    pub fn matches(&self, job: &JobState) -> bool {
+        match self {
+            TriageFilter::All => true,
+            TriageFilter::Untriaged => job.triage.is_none(),
+            TriageFilter::Only(status) => job.triage == Some(*status),
+        }
+    }
+}
+
+/// Post-filter expression input for a completed job's result file
+#[derive(Debug, Clone)]
+pub struct PostFilterInput {
+    /// Index of the job whose output is filtered
+    pub job_idx: usize,
+    /// Expression being edited
+    pub text: String,
+    /// Recently applied expressions (from config), most recent first
+    pub saved: Vec<String>,
+    /// Position in `saved` when recalling with Up/Down
+    history_pos: Option<usize>,
+}
+
+impl PostFilterInput {
+    pub fn new(job_idx: usize, saved: Vec<String>) -> Self {
+        Self {
+            job_idx,
+            text: String::new(),
+            saved,
+            history_pos: None,
+        }
+    }
+
+    /// Recall the next older saved expression
+    pub fn recall_older(&mut self) {
+        let pos = self.history_pos.map_or(0, |pos| pos + 1);
+        if let Some(expression) = self.saved.get(pos) {
+            self.text = expression.clone();
+            self.history_pos = Some(pos);
+        }
+    }
+
+    /// Recall the next newer saved expression (clearing the input past the newest)
+    pub fn recall_newer(&mut self) {
+        match self.history_pos {
+            Some(0) => {
+                self.text.clear();
+                self.history_pos = None;
+            }
+            Some(pos) => {
+                self.text = self.saved[pos - 1].clone();
+                self.history_pos = Some(pos - 1);
+            }
+            None => {}
+        }
+    }
+}
+
+/// A column the jobs table can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobColumn {
+    Status,
+    Triage,
+    Workspace,
+    /// Preview of the query text
+    Query,
+    /// Job name (the pack query's name for pack runs)
+    QueryName,
+    /// Severity of the pack query
+    Severity,
+    /// Scheduling priority
+    Priority,
+    Rows,
+    Duration,
+    OutputSize,
+    Timestamp,
+}
+
+impl JobColumn {
+    pub const ALL: [JobColumn; 11] = [
+        JobColumn::Status,
+        JobColumn::Triage,
+        JobColumn::Workspace,
+        JobColumn::Query,
+        JobColumn::QueryName,
+        JobColumn::Severity,
+        JobColumn::Priority,
+        JobColumn::Rows,
+        JobColumn::Duration,
+        JobColumn::OutputSize,
+        JobColumn::Timestamp,
+    ];
+
+    /// Columns shown when config.yaml doesn't set `jobs_columns`
+    pub const DEFAULT: [JobColumn; 6] = [
+        JobColumn::Status,
+        JobColumn::Triage,
+        JobColumn::Workspace,
+        JobColumn::Query,
+        JobColumn::Duration,
+        JobColumn::Timestamp,
+    ];
+
+    /// Name used in config.yaml
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobColumn::Status => "status",
+            JobColumn::Triage => "triage",
+            JobColumn::Workspace => "workspace",
+            JobColumn::Query => "query",
+            JobColumn::QueryName => "query_name",
+            JobColumn::Severity => "severity",
+            JobColumn::Priority => "priority",
+            JobColumn::Rows => "rows",
+            JobColumn::Duration => "duration",
+            JobColumn::OutputSize => "output_size",
+            JobColumn::Timestamp => "timestamp",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|column| column.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Table header
+    pub fn title(&self) -> &'static str {
+        match self {
+            JobColumn::Status => "Status",
+            JobColumn::Triage => "Triage",
+            JobColumn::Workspace => "Workspace",
+            JobColumn::Query => "Query",
+            JobColumn::QueryName => "Name",
+            JobColumn::Severity => "Severity",
+            JobColumn::Priority => "Priority",
+            JobColumn::Rows => "Rows",
+            JobColumn::Duration => "Duration",
+            JobColumn::OutputSize => "Size",
+            JobColumn::Timestamp => "Timestamp",
+        }
+    }
+
+    /// Columns named in config.yaml, in order (unknown names are ignored)
+    /// Falls back to the default layout if none are valid.
+    pub fn from_config(names: &[String]) -> Vec<JobColumn> {
+        let mut columns: Vec<JobColumn> = Vec::new();
+        for column in names.iter().filter_map(|name| JobColumn::parse(name)) {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        if columns.is_empty() {
+            JobColumn::DEFAULT.to_vec()
+        } else {
+            columns
+        }
+    }
+}
+
+/// Column picker state (while the jobs column popup is open)
+/// Lists every column, shown ones first in table order.
+#[derive(Debug, Clone)]
+pub struct JobColumnPicker {
+    pub columns: Vec<(JobColumn, bool)>,
+    pub selected: usize,
+}
+
+impl JobColumnPicker {
+    pub fn new(shown: &[JobColumn]) -> Self {
+        let mut columns: Vec<(JobColumn, bool)> =
+            shown.iter().map(|&column| (column, true)).collect();
+        columns.extend(
+            JobColumn::ALL
+                .into_iter()
+                .filter(|column| !shown.contains(column))
+                .map(|column| (column, false)),
+        );
+        Self {
+            columns,
+            selected: 0,
+        }
+    }
+
+    /// Move the highlight by `delta`, staying in bounds
+    pub fn navigate(&mut self, delta: i32) {
+        let max = self.columns.len().saturating_sub(1) as i32;
+        self.selected = (self.selected as i32 + delta).clamp(0, max) as usize;
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some((_, shown)) = self.columns.get_mut(self.selected) {
+            *shown = !*shown;
+        }
+    }
+
+    /// Move the highlighted column up (negative) or down the order, keeping it highlighted
+    pub fn move_selected(&mut self, delta: i32) {
+        let target = self.selected as i32 + delta;
+        if target >= 0 && (target as usize) < self.columns.len() {
+            self.columns.swap(self.selected, target as usize);
+            self.selected = target as usize;
+        }
+    }
+
+    /// Shown columns, in order
+    pub fn shown(&self) -> Vec<JobColumn> {
+        self.columns
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|(column, _)| *column)
+            .collect()
+    }
+}
+
 /// Jobs tab state
 #[derive(Debug, Clone)]
 pub struct JobsModel {
@@ -171,6 +560,105 @@ pub struct JobsModel {
     pub table_state: TableState,
     /// Counter for generating unique job IDs
     next_job_id: u64,
+    /// IDs of jobs marked for bulk actions (stable across sorting)
+    pub marked: HashSet<u64>,
+    /// Pack name input buffer (when exporting marked jobs)
+    pub pack_name_input: Option<String>,
+    /// Chart of the selected job's results (when the chart popup is open)
+    pub chart: Option<super::chart::JobChart>,
+    /// Result rows of a job (when the row inspector popup is open)
+    pub row_inspector: Option<super::row_inspector::RowInspector>,
+    /// Triage verdict filter for the jobs table
+    pub triage_filter: TriageFilter,
+    /// Triage note input buffer (when editing the selected job's note)
+    pub note_input: Option<String>,
+    /// Post-filter input (when the post-filter popup is open)
+    pub post_filter: Option<PostFilterInput>,
+    /// Jobs queued since the job list was last idle (for progress and ETA)
+    batch: Option<JobBatch>,
+    /// Columns shown in the jobs table, in order
+    pub columns: Vec<JobColumn>,
+    /// Column picker (when the column popup is open)
+    pub column_picker: Option<JobColumnPicker>,
+    /// Search across the jobs' output files (when the search popup is open)
+    pub result_search: Option<ResultSearch>,
+    /// List jobs with the highest-severity hits first instead of newest first
+    pub sort_by_severity: bool,
+}
+
+/// Search across the output files of the jobs in the list
+#[derive(Debug, Clone, Default)]
+pub struct ResultSearch {
+    pub input: String,
+    /// Treat the input as a regex instead of literal text
+    pub regex: bool,
+    /// Files with matches (None until the input is searched; editing clears it)
+    pub results: Option<Vec<FileMatches>>,
+    pub selected: usize,
+    /// Invalid pattern message
+    pub error: Option<String>,
+}
+
+impl ResultSearch {
+    /// Forget the results after the input or mode changed
+    pub fn reset(&mut self) {
+        self.results = None;
+        self.selected = 0;
+        self.error = None;
+    }
+
+    pub fn selected_match(&self) -> Option<&FileMatches> {
+        self.results.as_ref()?.get(self.selected)
+    }
+
+    pub fn navigate(&mut self, delta: i32) {
+        let count = self.results.as_ref().map_or(0, Vec::len);
+        if count > 0 {
+            self.selected =
+                (self.selected as i64 + delta as i64).clamp(0, count as i64 - 1) as usize;
+        }
+    }
+}
+
+/// Jobs started together, e.g. a pack run, tracked until all of them finish
+#[derive(Debug, Clone)]
+struct JobBatch {
+    started: Instant,
+    job_ids: HashSet<u64>,
+}
+
+/// Completion of the current batch of jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    /// Estimated time until the remaining jobs finish (None until a job has finished)
+    pub remaining: Option<Duration>,
+}
+
+impl BatchProgress {
+    /// Estimate progress from the rate jobs have finished at so far
+    ///
+    /// Jobs run concurrently, so the observed completion rate already reflects both job
+    /// durations and parallelism.
+    pub fn estimate(completed: usize, total: usize, elapsed: Duration) -> Self {
+        let remaining =
+            (completed > 0).then(|| elapsed.mul_f64((total - completed) as f64 / completed as f64));
+        Self {
+            completed,
+            total,
+            remaining,
+        }
+    }
+
+    /// Fraction of jobs finished (0.0 to 1.0)
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.completed as f64 / self.total as f64
+        }
+    }
 }
 
 impl JobsModel {
@@ -180,6 +668,18 @@ impl JobsModel {
             jobs: Vec::new(),
             table_state: TableState::default(),
             next_job_id: 1, // Start from 1 (0 reserved for invalid/unset)
+            marked: HashSet::new(),
+            pack_name_input: None,
+            chart: None,
+            row_inspector: None,
+            triage_filter: TriageFilter::default(),
+            note_input: None,
+            post_filter: None,
+            batch: None,
+            columns: JobColumn::DEFAULT.to_vec(),
+            column_picker: None,
+            result_search: None,
+            sort_by_severity: false,
         }
     }
 
@@ -195,6 +695,19 @@ impl JobsModel {
         &mut self.next_job_id
     }
 
+    /// Check if a queued, running or completed job already matches `context`
+    /// Failed jobs don't count, so a failed query can be re-run.
+    pub fn has_duplicate(&self, context: &RetryContext) -> bool {
+        let key = context.dedup_key();
+        self.jobs.iter().any(|job| {
+            job.status != JobStatus::Failed
+                && job
+                    .retry_context
+                    .as_ref()
+                    .is_some_and(|ctx| ctx.dedup_key() == key)
+        })
+    }
+
     /// Add a new job in queued state (deprecated - use add_job_with_context)
     #[allow(dead_code)]
     pub fn add_job(&mut self, workspace_name: String, query_preview: String) {
@@ -209,6 +722,13 @@ impl JobsModel {
             result: None,
             error: None,
             retry_context: None,
+            watch: None,
+            triage: None,
+            note: String::new(),
+            query_name: None,
+            description: None,
+            severity: None,
+            priority: SharedPriority::default(),
         });
 
         // Set initial selection to first job if this is the first one
@@ -226,6 +746,17 @@ impl JobsModel {
     ) -> u64 {
         let job_id = self.next_id();
 
+        // Jobs queued while others are pending join their batch; otherwise a new one starts
+        if !self.has_pending_jobs() || self.batch.is_none() {
+            self.batch = Some(JobBatch {
+                started: Instant::now(),
+                job_ids: HashSet::new(),
+            });
+        }
+        if let Some(batch) = &mut self.batch {
+            batch.job_ids.insert(job_id);
+        }
+
         self.jobs.push(JobState {
             job_id,
             status: JobStatus::Queued,
@@ -235,6 +766,13 @@ impl JobsModel {
             result: None,
             error: None,
             retry_context: Some(retry_context),
+            watch: None,
+            triage: None,
+            note: String::new(),
+            query_name: None,
+            description: None,
+            severity: None,
+            priority: SharedPriority::default(),
         });
 
         // Set initial selection to first job if this is the first one
@@ -245,6 +783,170 @@ impl JobsModel {
         job_id // Return the job ID for tracking
     }
 
+    /// Attach a query name and description to a job
+    pub fn set_query_info(&mut self, job_id: u64, name: String, description: Option<String>) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.job_id == job_id) {
+            job.query_name = Some(name);
+            job.description = description;
+        }
+    }
+
+    /// Attach the pack query's severity to a job
+    pub fn set_severity(&mut self, job_id: u64, severity: Option<Severity>) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.job_id == job_id) {
+            job.severity = severity;
+        }
+    }
+
+    /// Set the order in which a queued job gets a free slot
+    pub fn set_priority(&mut self, job_id: u64, priority: JobPriority) {
+        if let Some(job) = self.jobs.iter().find(|j| j.job_id == job_id) {
+            job.priority.set(priority);
+        }
+    }
+
+    /// Priority handle the job's task waits with
+    pub fn priority_handle(&self, job_id: u64) -> SharedPriority {
+        self.jobs
+            .iter()
+            .find(|j| j.job_id == job_id)
+            .map(|job| job.priority.clone())
+            .unwrap_or_default()
+    }
+
+    /// Raise or lower the selected job's priority (queued jobs only)
+    /// Returns the new priority, or None if the selected job isn't queued.
+    pub fn change_selected_priority(&mut self, raise: bool) -> Option<JobPriority> {
+        let job = self
+            .get_selected_job()
+            .filter(|job| job.status == JobStatus::Queued)?;
+        let current = job.priority.get();
+        let priority = if raise {
+            current.raised()
+        } else {
+            current.lowered()
+        };
+        job.priority.set(priority);
+        Some(priority)
+    }
+
+    /// Mark a queued job as running once it gets a scheduler slot
+    pub fn start_job(&mut self, job_id: u64) {
+        if let Some(job) = self
+            .jobs
+            .iter_mut()
+            .find(|j| j.job_id == job_id && j.status == JobStatus::Queued)
+        {
+            job.status = JobStatus::Running;
+        }
+    }
+
+    /// Add a watch job that stays running until stopped
+    /// Returns the job ID and the handle used to stop the background task
+    pub fn add_watch_job(
+        &mut self,
+        workspace_name: String,
+        query_preview: String,
+        retry_context: RetryContext,
+        interval: Duration,
+    ) -> (u64, Arc<Notify>) {
+        let job_id = self.add_job_with_context(workspace_name, query_preview, retry_context);
+        let stop = Arc::new(Notify::new());
+        // Watches never finish, so they aren't part of a batch's progress
+        if let Some(batch) = &mut self.batch {
+            batch.job_ids.remove(&job_id);
+        }
+
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.job_id == job_id) {
+            job.status = JobStatus::Running;
+            job.watch = Some(WatchState {
+                interval,
+                runs: 0,
+                last_new_rows: 0,
+                total_rows: 0,
+                stop: stop.clone(),
+            });
+        }
+
+        (job_id, stop)
+    }
+
+    /// Record the outcome of a watch poll
+    pub fn apply_watch_tick(&mut self, job_id: u64, tick: WatchTick) {
+        let Some(job) = self.jobs.iter_mut().find(|j| j.job_id == job_id) else {
+            log::error!("Watch update for non-existent job with ID {}", job_id);
+            return;
+        };
+        let Some(watch) = job.watch.as_mut() else {
+            return;
+        };
+
+        watch.runs = tick.runs;
+        watch.last_new_rows = tick.new_rows;
+        watch.total_rows = tick.total_rows;
+        job.duration = Some(tick.elapsed);
+
+        let (workspace_id, query) = job
+            .retry_context
+            .as_ref()
+            .map(|ctx| (ctx.workspace.workspace_id.clone(), ctx.query.clone()))
+            .unwrap_or_default();
+
+        // Keep the last error visible but leave the watch running
+        let result = match tick.error {
+            Some(err) => {
+                job.error = Some(Self::categorize_error(
+                    &err,
+                    &job.workspace_name,
+                    tick.elapsed,
+                ));
+                Err(err)
+            }
+            None => {
+                job.error = None;
+                Ok(JobSuccess {
+                    row_count: tick.total_rows,
+                    page_count: tick.runs as usize,
+                    output_path: tick.output_path,
+                    file_size: tick.file_size,
+                    truncated: false,
+                    billed_bytes: None,
+                    warnings: Vec::new(),
+                    statistics: None,
+                    uploads: Vec::new(),
+                })
+            }
+        };
+
+        job.result = Some(QueryJobResult {
+            workspace_id,
+            workspace_name: job.workspace_name.clone(),
+            query,
+            result,
+            elapsed: tick.elapsed,
+            timestamp: tick.timestamp,
+        });
+    }
+
+    /// Stop the selected job if it is an active watch
+    /// Returns the workspace name of the stopped watch
+    pub fn stop_selected_watch(&mut self) -> Option<String> {
+        let idx = self.table_state.selected()?;
+        let job = self.jobs.get_mut(idx)?;
+        if job.status != JobStatus::Running {
+            return None;
+        }
+        let watch = job.watch.as_ref()?;
+
+        watch.stop.notify_one();
+        job.status = if job.error.is_some() {
+            JobStatus::Failed
+        } else {
+            JobStatus::Completed
+        };
+        Some(job.workspace_name.clone())
+    }
+
     /// Update a job's status to completed
     /// Finds the job by ID (stable across sorting) instead of index
     pub fn complete_job(&mut self, job_id: u64, result: QueryJobResult) {
@@ -253,16 +955,23 @@ impl JobsModel {
             job.duration = Some(result.elapsed);
 
             // Extract error information if the job failed
-            if let Err(ref err) = result.result {
-                job.status = JobStatus::Failed;
-                job.error = Some(Self::categorize_error(
-                    err,
-                    &result.workspace_name,
-                    result.elapsed,
-                ));
-            } else {
-                job.status = JobStatus::Completed;
-                job.error = None;
+            match &result.result {
+                Err(err) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(Self::categorize_error(
+                        err,
+                        &result.workspace_name,
+                        result.elapsed,
+                    ));
+                }
+                Ok(success) => {
+                    job.status = if success.warnings.is_empty() {
+                        JobStatus::Completed
+                    } else {
+                        JobStatus::CompletedWithWarnings
+                    };
+                    job.error = None;
+                }
             }
 
             job.result = Some(result);
@@ -271,6 +980,44 @@ impl JobsModel {
         }
     }
 
+    /// Check if any one-off job is still queued or running (watch jobs don't count)
+    pub fn has_pending_jobs(&self) -> bool {
+        self.jobs.iter().any(|job| {
+            job.watch.is_none() && matches!(job.status, JobStatus::Queued | JobStatus::Running)
+        })
+    }
+
+    /// Progress of the jobs started since the job list was last idle
+    /// Returns None once they have all finished, or for a single job.
+    pub fn batch_progress(&self) -> Option<BatchProgress> {
+        let batch = self.batch.as_ref()?;
+        let total = batch.job_ids.len();
+        // Jobs removed from the list (cleared, or replaced by a loaded session) count as done
+        let pending = self
+            .jobs
+            .iter()
+            .filter(|job| {
+                batch.job_ids.contains(&job.job_id)
+                    && matches!(job.status, JobStatus::Queued | JobStatus::Running)
+            })
+            .count();
+        if pending == 0 || total < 2 {
+            return None;
+        }
+        Some(BatchProgress::estimate(
+            total - pending,
+            total,
+            batch.started.elapsed(),
+        ))
+    }
+
+    /// Check if a job failed because authentication was rejected
+    pub fn is_auth_failure(&self, job_id: u64) -> bool {
+        self.jobs.iter().any(|job| {
+            job.job_id == job_id && matches!(job.error, Some(JobError::Authentication { .. }))
+        })
+    }
+
     /// Categorize a KqlPanopticonError into a JobError for better display
     pub fn categorize_error(
         error: &crate::error::KqlPanopticonError,
@@ -299,7 +1046,23 @@ impl JobsModel {
             | KqlPanopticonError::TokenAcquisitionFailed(msg) => JobError::Authentication {
                 message: msg.clone(),
             },
-            KqlPanopticonError::AzureApiError { status, message } => {
+            KqlPanopticonError::AzureApiError {
+                status,
+                message,
+                code,
+            } => {
+                if code.as_deref() == Some("SemanticError") {
+                    return match unresolved_table(message) {
+                        Some(table) => JobError::TableNotFound {
+                            table,
+                            message: message.clone(),
+                        },
+                        None => JobError::SemanticError {
+                            message: message.clone(),
+                        },
+                    };
+                }
+
                 // Check for specific status codes
                 match *status {
                     401 | 403 => JobError::Authentication {
@@ -316,12 +1079,14 @@ impl JobsModel {
                     _ => JobError::AzureApi {
                         status: *status,
                         message: message.clone(),
+                        code: code.clone(),
                     },
                 }
             }
-            KqlPanopticonError::HttpRequestFailed(msg) => JobError::Network {
-                message: msg.clone(),
+            KqlPanopticonError::HttpRequestFailed(transport) => JobError::Network {
+                message: transport.message.clone(),
                 status_code: None,
+                kind: Some(transport.kind),
             },
             _ => JobError::Other {
                 message: error.to_string(),
@@ -333,22 +1098,162 @@ impl JobsModel {
     pub fn clear_completed(&mut self) {
         self.jobs
             .retain(|job| job.status == JobStatus::Queued || job.status == JobStatus::Running);
-        // If jobs remain after clearing, select the first one
-        if !self.jobs.is_empty() {
-            self.table_state.select(Some(0));
-        } else {
-            self.table_state.select(None);
+        // Drop marks for jobs that no longer exist
+        let remaining: HashSet<u64> = self.jobs.iter().map(|j| j.job_id).collect();
+        self.marked.retain(|id| remaining.contains(id));
+        // If jobs remain after clearing, select the first visible one
+        self.table_state
+            .select(self.visible_indices().first().copied());
+    }
+
+    /// Indices (into `jobs`) of the jobs shown under the current triage filter
+    pub fn visible_indices(&self) -> Vec<usize> {
+        self.jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| self.triage_filter.matches(job))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Move the selection to the previous visible job
+    pub fn select_previous(&mut self) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        if let Some(idx) = self
+            .visible_indices()
+            .into_iter()
+            .rev()
+            .find(|&i| i < selected)
+        {
+            self.table_state.select(Some(idx));
+        }
+    }
+
+    /// Jump through the visible jobs by a page or to either end
+    pub fn select_jump(&mut self, jump: ListJump) {
+        if let Some(idx) = jump.target(&self.visible_indices(), self.table_state.selected()) {
+            self.table_state.select(Some(idx));
+        }
+    }
+
+    /// Move the selection to the next visible job
+    pub fn select_next(&mut self) {
+        let visible = self.visible_indices();
+        let next = match self.table_state.selected() {
+            Some(selected) => visible.into_iter().find(|&i| i > selected),
+            None => visible.first().copied(),
+        };
+        if let Some(idx) = next {
+            self.table_state.select(Some(idx));
         }
     }
 
+    /// Switch to the next triage filter, keeping the selection on a visible job
+    pub fn cycle_triage_filter(&mut self) {
+        self.triage_filter = self.triage_filter.next();
+        self.keep_selection_visible();
+    }
+
+    /// If the selected job is hidden by the filter, select the next visible job (or the last)
+    pub fn keep_selection_visible(&mut self) {
+        let visible = self.visible_indices();
+        let selected = self.table_state.selected();
+        if !selected.is_some_and(|i| visible.contains(&i)) {
+            let idx = selected
+                .and_then(|s| visible.iter().copied().find(|&i| i > s))
+                .or_else(|| visible.last().copied());
+            self.table_state.select(idx);
+        }
+    }
+
+    /// Advance the selected job's triage verdict
+    /// Returns false if no job is selected
+    pub fn cycle_selected_triage(&mut self) -> bool {
+        let Some(job) = self
+            .table_state
+            .selected()
+            .and_then(|idx| self.jobs.get_mut(idx))
+        else {
+            return false;
+        };
+        job.triage = TriageStatus::cycle(job.triage);
+        true
+    }
+
+    /// Toggle the mark on the currently selected job
+    pub fn toggle_mark_selected(&mut self) {
+        if let Some(job_id) = self.get_selected_job().map(|j| j.job_id) {
+            if !self.marked.remove(&job_id) {
+                self.marked.insert(job_id);
+            }
+        }
+    }
+
+    /// Check if a job is marked
+    pub fn is_marked(&self, job_id: u64) -> bool {
+        self.marked.contains(&job_id)
+    }
+
+    /// Get marked jobs in display order
+    pub fn marked_jobs(&self) -> Vec<&JobState> {
+        self.jobs
+            .iter()
+            .filter(|j| self.marked.contains(&j.job_id))
+            .collect()
+    }
+
     /// Get the currently selected job
+    /// Output files of the completed jobs, for searching
+    pub fn output_files(&self) -> Vec<SearchTarget> {
+        crate::result_search::dedup(
+            self.jobs
+                .iter()
+                .filter_map(|job| {
+                    let success = job.result.as_ref()?.result.as_ref().ok()?;
+                    Some(SearchTarget {
+                        path: success.output_path.clone(),
+                        workspace: job.workspace_name.clone(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Index of the job that wrote `path`
+    pub fn job_with_output(&self, path: &std::path::Path) -> Option<usize> {
+        self.jobs.iter().position(|job| {
+            job.result
+                .as_ref()
+                .and_then(|r| r.result.as_ref().ok())
+                .is_some_and(|success| success.output_path == path)
+        })
+    }
+
     pub fn get_selected_job(&self) -> Option<&JobState> {
         self.table_state.selected().and_then(|i| self.jobs.get(i))
     }
 
-    /// Sort jobs by timestamp (newest first)
-    pub fn sort_by_timestamp(&mut self) {
+    /// Switch between newest-first and severity-first order, keeping the selected job
+    pub fn toggle_severity_sort(&mut self) {
+        let selected = self.get_selected_job().map(|job| job.job_id);
+        self.sort_by_severity = !self.sort_by_severity;
+        self.sort_jobs();
+        if let Some(job_id) = selected {
+            let idx = self.jobs.iter().position(|job| job.job_id == job_id);
+            self.table_state.select(idx);
+        }
+    }
+
+    /// Sort jobs newest first, or by the severity of their hits then newest first
+    pub fn sort_jobs(&mut self) {
+        let by_severity = self.sort_by_severity;
         self.jobs.sort_by(|a, b| {
+            if by_severity {
+                let order = b.hit_severity().cmp(&a.hit_severity());
+                if order.is_ne() {
+                    return order;
+                }
+            }
             let timestamp_a = a.result.as_ref().map(|r| r.timestamp);
             let timestamp_b = b.result.as_ref().map(|r| r.timestamp);
 
@@ -368,3 +1273,144 @@ impl Default for JobsModel {
         Self::new()
     }
 }
+
+/// Table name in a semantic error about an unknown table
+/// Azure reports these as "Failed to resolve table (or column) expression named 'X'"; unknown
+/// columns in a scalar context are "scalar expression" errors and don't match.
+fn unresolved_table(message: &str) -> Option<String> {
+    let rest = &message[message.find("Failed to resolve table")?..];
+    let name = rest[rest.find("named '")? + "named '".len()..]
+        .split('\'')
+        .next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_sort() {
+        let mut model = JobsModel::new();
+        // (severity, rows): oldest first
+        let jobs = [
+            (Some(Severity::Critical), 0),
+            (Some(Severity::Low), 3),
+            (None, 10),
+            (Some(Severity::High), 1),
+        ];
+        for (i, (severity, rows)) in jobs.into_iter().enumerate() {
+            model.add_job(format!("ws-{}", i), "SigninLogs".to_string());
+            let job_id = model.jobs[i].job_id;
+            model.set_severity(job_id, severity);
+            model.complete_job(
+                job_id,
+                QueryJobResult {
+                    workspace_id: format!("ws-{}", i),
+                    workspace_name: format!("ws-{}", i),
+                    query: "SigninLogs".to_string(),
+                    result: Ok(JobSuccess {
+                        row_count: rows,
+                        page_count: 1,
+                        output_path: Default::default(),
+                        file_size: 0,
+                        truncated: false,
+                        billed_bytes: None,
+                        warnings: Vec::new(),
+                        statistics: None,
+                        uploads: Vec::new(),
+                    }),
+                    elapsed: Duration::from_millis(10),
+                    timestamp: crate::time_zone::now() + chrono::Duration::seconds(i as i64),
+                },
+            );
+        }
+        let order = |model: &JobsModel| -> Vec<String> {
+            model
+                .jobs
+                .iter()
+                .map(|job| job.workspace_name.clone())
+                .collect()
+        };
+
+        model.sort_jobs();
+        assert_eq!(order(&model), ["ws-3", "ws-2", "ws-1", "ws-0"]);
+
+        // A critical query without hits ranks with the jobs without a severity
+        model.table_state.select(Some(2));
+        model.toggle_severity_sort();
+        assert_eq!(order(&model), ["ws-3", "ws-1", "ws-2", "ws-0"]);
+        assert_eq!(model.get_selected_job().unwrap().workspace_name, "ws-1");
+        assert_eq!(model.jobs[3].hit_severity(), None);
+    }
+
+    #[test]
+    fn test_batch_progress_estimate() {
+        let progress = BatchProgress::estimate(0, 600, Duration::from_secs(30));
+        assert_eq!(progress.remaining, None);
+
+        // 150 of 600 done in 4 minutes leaves 450 at the same rate: 12 minutes
+        let progress = BatchProgress::estimate(150, 600, Duration::from_secs(240));
+        assert_eq!(progress.remaining, Some(Duration::from_secs(720)));
+        assert_eq!(progress.ratio(), 0.25);
+    }
+
+    #[test]
+    fn test_categorize_semantic_errors() {
+        use crate::error::KqlPanopticonError;
+        let categorize = |status: u16, message: &str, code: &str| {
+            JobsModel::categorize_error(
+                &KqlPanopticonError::AzureApiError {
+                    status,
+                    message: message.to_string(),
+                    code: Some(code.to_string()),
+                },
+                "ws-eu",
+                Duration::from_secs(1),
+            )
+        };
+
+        let error = categorize(
+            400,
+            "Query failed: BadArgumentError: A semantic error occurred.\n  Details: 'where' operator: Failed to resolve table or column expression named 'SigninLogz'",
+            "SemanticError",
+        );
+        assert!(matches!(&error, JobError::TableNotFound { table, .. } if table == "SigninLogz"));
+        assert!(!error.is_retryable());
+
+        let error = categorize(
+            400,
+            "Failed to resolve scalar expression named 'Acount'",
+            "SemanticError",
+        );
+        assert!(matches!(error, JobError::SemanticError { .. }));
+
+        let error = categorize(404, "Workspace not found", "PathNotFoundError");
+        assert_eq!(
+            error.short_description(),
+            "Azure API Error (404 PathNotFoundError)"
+        );
+    }
+
+    #[test]
+    fn test_job_columns_from_config_and_picker() {
+        let names = ["rows", "Workspace", "bogus", "rows"].map(String::from);
+        assert_eq!(
+            JobColumn::from_config(&names),
+            vec![JobColumn::Rows, JobColumn::Workspace]
+        );
+        assert_eq!(JobColumn::from_config(&[]), JobColumn::DEFAULT.to_vec());
+
+        let mut picker = JobColumnPicker::new(&[JobColumn::Rows, JobColumn::Workspace]);
+        assert_eq!(picker.columns.len(), JobColumn::ALL.len());
+        picker.move_selected(1);
+        assert_eq!(picker.shown(), vec![JobColumn::Workspace, JobColumn::Rows]);
+        picker.navigate(1);
+        assert_eq!(picker.selected, 2);
+        picker.toggle_selected();
+        assert_eq!(
+            picker.shown(),
+            vec![JobColumn::Workspace, JobColumn::Rows, JobColumn::Status]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::log_buffer::{LogLine, LOG_BUFFER_CAPACITY};
+use crate::tui::model::ListJump;
+use log::Level;
+use std::collections::VecDeque;
+
+/// Logs tab state: the application's own log records, tailed from the log buffer
+#[derive(Debug, Clone)]
+pub struct LogsModel {
+    lines: VecDeque<LogLine>,
+    /// Sequence number of the newest record pulled from the buffer
+    last_seq: u64,
+    /// Least severe level shown
+    pub min_level: Level,
+    /// Case-insensitive text records must contain (empty shows all)
+    pub search: String,
+    /// Search input buffer (while the search popup is open)
+    pub search_input: Option<String>,
+    /// Highlighted record, as an index into `visible()` (ignored while following)
+    pub selected: usize,
+    /// Keep the newest record in view as records arrive
+    pub follow: bool,
+}
+
+impl LogsModel {
+    pub fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            last_seq: 0,
+            min_level: Level::Trace,
+            search: String::new(),
+            search_input: None,
+            selected: 0,
+            follow: true,
+        }
+    }
+
+    /// Pull records logged since the last call
+    pub fn sync(&mut self) {
+        self.append(crate::log_buffer::since(self.last_seq));
+    }
+
+    fn append(&mut self, new: Vec<LogLine>) {
+        let Some(last) = new.last() else {
+            return;
+        };
+        self.last_seq = last.seq;
+        self.lines.extend(new);
+        let excess = self.lines.len().saturating_sub(LOG_BUFFER_CAPACITY);
+        if excess > 0 {
+            self.lines.drain(..excess);
+            self.selected = self.selected.saturating_sub(excess);
+        }
+    }
+
+    /// Number of records held (before filtering)
+    pub fn total(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Records at `min_level` or more severe that contain the search text, oldest first
+    pub fn visible(&self) -> Vec<&LogLine> {
+        let search = self.search.to_lowercase();
+        self.lines
+            .iter()
+            .filter(|line| line.level <= self.min_level)
+            .filter(|line| {
+                search.is_empty()
+                    || line.message.to_lowercase().contains(&search)
+                    || line.target.to_lowercase().contains(&search)
+            })
+            .collect()
+    }
+
+    /// Index into `visible()` of the highlighted record
+    pub fn selected_index(&self, visible: usize) -> Option<usize> {
+        let last = visible.checked_sub(1)?;
+        Some(if self.follow {
+            last
+        } else {
+            self.selected.min(last)
+        })
+    }
+
+    /// Show one level fewer (all → debug → info → warn → error → all)
+    pub fn cycle_level(&mut self) {
+        self.min_level = match self.min_level {
+            Level::Trace => Level::Debug,
+            Level::Debug => Level::Info,
+            Level::Info => Level::Warn,
+            Level::Warn => Level::Error,
+            Level::Error => Level::Trace,
+        };
+        self.follow = true;
+    }
+
+    /// Label for the level filter
+    pub fn level_label(&self) -> &'static str {
+        match self.min_level {
+            Level::Trace => "all",
+            Level::Debug => "debug+",
+            Level::Info => "info+",
+            Level::Warn => "warn+",
+            Level::Error => "error",
+        }
+    }
+
+    pub fn set_search(&mut self, search: String) {
+        self.search = search.trim().to_string();
+        self.follow = true;
+    }
+
+    /// Move the highlight by `delta` records; moving stops following
+    pub fn scroll(&mut self, delta: isize) {
+        let count = self.visible().len();
+        let Some(current) = self.selected_index(count) else {
+            return;
+        };
+        self.selected = current.saturating_add_signed(delta).min(count - 1);
+        self.follow = false;
+    }
+
+    /// Jump through the records; jumping to the last one resumes following
+    pub fn jump(&mut self, jump: ListJump) {
+        let count = self.visible().len();
+        let rows: Vec<usize> = (0..count).collect();
+        if let Some(row) = jump.target(&rows, self.selected_index(count)) {
+            self.selected = row;
+        }
+        self.follow = jump == ListJump::Last;
+    }
+
+    pub fn toggle_follow(&mut self) {
+        if self.follow {
+            self.selected = self.visible().len().saturating_sub(1);
+        }
+        self.follow = !self.follow;
+    }
+}
+
+impl Default for LogsModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(seq: u64, level: Level, message: &str) -> LogLine {
+        LogLine {
+            seq,
+            time: "12:00:00.000".to_string(),
+            level,
+            target: "kql_panopticon::client".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_level_and_search_filters() {
+        let mut logs = LogsModel::new();
+        logs.append(vec![
+            line(1, Level::Debug, "GET workspaces"),
+            line(2, Level::Warn, "Throttled (429), retrying in 5s"),
+            line(3, Level::Error, "Token refresh failed"),
+            line(4, Level::Info, "Query completed"),
+        ]);
+        assert_eq!(logs.visible().len(), 4);
+        assert_eq!(logs.selected_index(4), Some(3));
+
+        logs.cycle_level();
+        logs.cycle_level();
+        logs.cycle_level();
+        assert_eq!(logs.level_label(), "warn+");
+        let messages: Vec<_> = logs.visible().iter().map(|l| l.seq).collect();
+        assert_eq!(messages, vec![2, 3]);
+
+        logs.set_search("THROTTLED".to_string());
+        assert_eq!(logs.visible().len(), 1);
+
+        // Scrolling stops following; new records don't move the highlight
+        logs.set_search(String::new());
+        logs.scroll(-1);
+        assert!(!logs.follow);
+        logs.append(vec![line(5, Level::Error, "Request timed out")]);
+        assert_eq!(logs.selected_index(logs.visible().len()), Some(0));
+        logs.jump(ListJump::Last);
+        assert!(logs.follow);
+        assert_eq!(logs.selected_index(logs.visible().len()), Some(2));
+    }
+}
@@ -1,14 +1,27 @@
+pub mod chart;
+pub mod dashboard;
+pub mod finder;
 pub mod jobs;
+pub mod logs;
+pub mod notifications;
 pub mod packs;
 pub mod query;
+pub mod row_inspector;
 pub mod session;
 pub mod settings;
 pub mod workspaces;
 
+use crate::backend::QueryBackend;
 use crate::client::Client;
 use crate::query_job::QueryJobResult;
+use crate::scheduler::QueryScheduler;
+use crate::tui::clipboard::SystemClipboard;
 use crate::tui::message::Tab;
-use jobs::JobsModel;
+use crate::webhook::{RunSummary, WebhookConfig};
+use finder::FinderModel;
+use jobs::{JobColumn, JobsModel};
+use logs::LogsModel;
+use notifications::NotificationsModel;
 use packs::PacksModel;
 use query::QueryModel;
 use session::SessionModel;
@@ -17,7 +30,10 @@ use tokio::sync::mpsc;
 use workspaces::WorkspacesModel;
 
 /// Main application model (state)
-pub struct Model {
+/// Generic over the backend so update flows can run against a [`FakeBackend`] in tests.
+///
+/// [`FakeBackend`]: crate::backend::FakeBackend
+pub struct Model<B: QueryBackend = Client> {
     /// Current active tab
     pub current_tab: Tab,
     /// Settings state
@@ -32,10 +48,18 @@ pub struct Model {
     pub sessions: SessionModel,
     /// Query packs state
     pub packs: PacksModel,
+    /// Logs tab state
+    pub logs: LogsModel,
     /// Azure client
-    pub client: Client,
+    pub client: B,
     /// Current popup message (if any)
     pub popup: Option<Popup>,
+    /// Notification history and toast state
+    pub notifications: NotificationsModel,
+    /// System clipboard (connected on first use)
+    pub clipboard: SystemClipboard,
+    /// Limits queries running at once across pack runs, ad-hoc runs and retries
+    pub scheduler: QueryScheduler,
     /// Channel for receiving job updates from background tasks
     pub job_update_rx: mpsc::UnboundedReceiver<JobUpdateMessage>,
     /// Channel for sending job updates from background tasks
@@ -44,6 +68,16 @@ pub struct Model {
     pub init_state: InitState,
     /// Spinner animation frame counter
     pub spinner_frame: usize,
+    /// Whether the re-authentication prompt was shown since the last successful auth
+    pub auth_prompted: bool,
+    /// Outcome of the jobs outside a pack run finished since the job list was last idle
+    pub run_summary: Option<RunSummary>,
+    /// Fuzzy finder over packs, pack queries and sessions (while the finder popup is open)
+    pub finder: Option<FinderModel>,
+    /// Query execution is disabled (`--read-only` or `read_only` in config)
+    pub read_only: bool,
+    /// File locked by another instance (while the lock conflict popup is open)
+    pub lock_conflict: Option<LockConflict>,
 }
 
 /// Popup types
@@ -51,22 +85,130 @@ pub struct Model {
 pub enum Popup {
     /// Error message (red)
     Error(String),
-    /// Success message (green)
-    Success(String),
+    /// Multi-line informational report (cyan), too long for a toast
+    Info(String),
+    /// A session or pack is locked by another instance (state in `Model::lock_conflict`)
+    LockConflict,
     /// Settings edit popup
     SettingsEdit,
     /// Job name input popup
     JobNameInput,
+    /// Values for the query's parameters, asked for before it runs (state lives in QueryModel)
+    QueryParameters,
     /// Job details popup with job index
     JobDetails(usize),
     /// Session name input popup (for save as / new session)
     SessionNameInput,
+    /// Subscription filter picker (state lives in WorkspacesModel)
+    SubscriptionFilter,
+    /// Pack name input popup (for exporting marked jobs)
+    PackNameInput,
+    /// Tag filter input popup for the pack list (state lives in PacksModel)
+    PackTagFilter,
+    /// Search across pack contents (state lives in PacksModel)
+    PackSearch,
+    /// Triage note input popup for the selected job (state lives in JobsModel)
+    JobNoteInput,
+    /// Post-filter expression input for a job's results (state lives in JobsModel)
+    PostFilterInput,
+    /// Confirm deleting marked sessions and their output (state lives in SessionModel)
+    ConfirmSessionsDelete,
+    /// Scrollable notification history
+    Notifications,
+    /// Chart of a job's results (state lives in JobsModel)
+    JobChart,
+    /// Result rows one at a time, with dynamic columns as JSON trees (state lives in JobsModel)
+    RowInspector,
+    /// Prompt to resume an incomplete pack run (state lives in PacksModel)
+    ResumePackRun,
+    /// Confirmation of a high-impact pack run (state lives in PacksModel)
+    ConfirmPackRun,
+    /// Path input for saving the loaded pack to a new file (state lives in PacksModel)
+    PackSaveAs,
+    /// New name input for the selected session (state lives in SessionModel)
+    SessionRename,
+    /// Tags and description input for a session (state lives in SessionModel)
+    SessionDetails,
+    /// Search input for the session list (state lives in SessionModel)
+    SessionSearch,
+    /// Search input for the Logs tab (state lives in LogsModel)
+    LogSearch,
+    /// New name input for the selected pack (state lives in PacksModel)
+    PackRename,
+    /// Prompt to re-authenticate after a job failed with an authentication error
+    ConfirmReauthenticate,
+    /// Format choice for re-exporting a job's results, with the job index
+    ReexportFormat(usize),
+    /// Saved search browser for a workspace (state lives in WorkspacesModel)
+    SavedSearches,
+    /// Fuzzy finder over packs, pack queries and sessions (state lives in Model)
+    Finder,
+    /// Jobs table column picker (state lives in JobsModel)
+    JobColumns,
+    /// Search across job output files (state lives in JobsModel)
+    ResultSearch,
 }
 
 /// Message for job status updates from background tasks
 #[derive(Debug, Clone)]
 pub enum JobUpdateMessage {
-    Completed(u64, QueryJobResult), // Job ID (not index!) completed with result
+    Started(u64),                            // Job ID got a scheduler slot and is running
+    Completed(u64, QueryJobResult),          // Job ID (not index!) completed with result
+    WatchTick(u64, crate::watch::WatchTick), // Watch job polled (job keeps running)
+}
+
+/// Rows moved by PageUp/PageDown in the list tabs
+pub const PAGE_ROWS: usize = 20;
+
+/// A jump through a list, beyond moving one row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListJump {
+    PageUp,
+    PageDown,
+    First,
+    Last,
+}
+
+impl ListJump {
+    /// Row to select among `visible` (row indices in display order), given the selected row
+    /// Pages stop at the ends of the list rather than wrapping.
+    pub fn target(self, visible: &[usize], selected: Option<usize>) -> Option<usize> {
+        let last = visible.len().checked_sub(1)?;
+        let position = selected.and_then(|s| visible.iter().position(|&i| i == s));
+        let position = match self {
+            ListJump::First => 0,
+            ListJump::Last => last,
+            ListJump::PageUp => position.map_or(0, |p| p.saturating_sub(PAGE_ROWS)),
+            ListJump::PageDown => position.map_or(0, |p| (p + PAGE_ROWS).min(last)),
+        };
+        Some(visible[position])
+    }
+}
+
+/// A file locked by another instance, waiting for the user to open it read-only or take it over
+#[derive(Debug, Clone)]
+pub struct LockConflict {
+    pub file: LockedFile,
+    pub holder: crate::file_lock::LockInfo,
+}
+
+/// What was being opened when a lock conflict was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockedFile {
+    /// A saved session, by name
+    Session(String),
+    /// The selected pack, loading this query into the editor
+    Pack { query_index: usize },
+}
+
+impl LockedFile {
+    /// Description for the lock conflict prompt
+    pub fn describe(&self) -> String {
+        match self {
+            LockedFile::Session(name) => format!("Session '{}'", name),
+            LockedFile::Pack { .. } => "This pack".to_string(),
+        }
+    }
 }
 
 /// Initialization state of the application
@@ -76,29 +218,61 @@ pub enum InitState {
     Initializing,
     /// Fully initialized and ready
     Ready,
-    /// Initialization failed
-    Failed,
+    /// Authentication failed - sessions, packs and saved results stay browsable,
+    /// but anything that needs Azure is disabled until re-authenticated
+    Offline,
 }
 
-impl Model {
+impl<B: QueryBackend> Model<B> {
     /// Create a new Model
-    pub fn new(client: Client) -> Self {
+    pub fn new(client: B) -> Self {
         let (job_update_tx, job_update_rx) = mpsc::unbounded_channel();
 
+        let config = crate::config::Config::resolve();
+        let mut settings = SettingsModel::new();
+        settings.subscription_filter = client.subscription_filter().clone();
+        settings.apply_config(&config);
+
+        let scheduler = QueryScheduler::new(
+            settings.max_concurrency,
+            settings.max_concurrency_per_workspace,
+        );
+
+        let mut jobs = JobsModel::new();
+        jobs.columns = JobColumn::from_config(&config.jobs_columns);
+
+        let mut packs = PacksModel::new();
+        packs.changelog = config.pack_changelog.unwrap_or(false);
+        packs.confirm_threshold = config.confirm_jobs_threshold;
+
+        let mut workspaces = WorkspacesModel::new();
+        if config.persist_workspace_health.unwrap_or(false) {
+            workspaces.load_health();
+        }
+
         Self {
             current_tab: Tab::Query,
-            settings: SettingsModel::new(),
-            workspaces: WorkspacesModel::new(),
+            settings,
+            workspaces,
             query: QueryModel::new(),
-            jobs: JobsModel::new(),
+            jobs,
             sessions: SessionModel::new(),
-            packs: PacksModel::new(),
+            packs,
+            logs: LogsModel::new(),
             client,
             popup: None,
+            notifications: NotificationsModel::new(),
+            clipboard: SystemClipboard::new(),
+            scheduler,
             job_update_rx,
             job_update_tx,
             init_state: InitState::Initializing,
             spinner_frame: 0,
+            auth_prompted: false,
+            run_summary: None,
+            finder: None,
+            read_only: config.read_only.unwrap_or(false),
+            lock_conflict: None,
         }
     }
 
@@ -106,29 +280,188 @@ impl Model {
     pub fn rebuild_client(&mut self) -> Result<(), crate::error::KqlPanopticonError> {
         use std::time::Duration;
 
-        self.client = Client::with_config(
+        self.client = self.client.reconfigure(
             Duration::from_secs(self.settings.validation_interval_secs),
             Duration::from_secs(self.settings.query_timeout_secs),
             self.settings.retry_count,
+            self.settings.subscription_filter.clone(),
         )?;
 
         Ok(())
     }
 
+    /// Persist config-backed settings to config.yaml
+    pub fn save_config(&self) -> Result<(), crate::error::KqlPanopticonError> {
+        let mut config = crate::config::Config::load_or_default();
+        config.subscriptions = self.settings.subscription_filter.clone();
+        if self.settings.workspace_cache_ttl_secs != config.workspace_cache_ttl().as_secs() {
+            config.workspace_cache_ttl_secs = Some(self.settings.workspace_cache_ttl_secs);
+        }
+        // Keep a row limit that came from the environment out of the file unless it was edited
+        if self.settings.max_rows_limit() != crate::config::Config::resolve().max_rows {
+            config.max_rows = self.settings.max_rows_limit();
+        }
+        config.save()
+    }
+
     /// Process pending job updates from the channel
     pub fn process_job_updates(&mut self) {
         let mut should_sort = false;
         while let Ok(message) = self.job_update_rx.try_recv() {
             match message {
+                JobUpdateMessage::Started(job_id) => {
+                    self.jobs.start_job(job_id);
+                }
                 JobUpdateMessage::Completed(job_idx, result) => {
+                    if !self.packs.record_job_result(job_idx, &result) {
+                        self.record_run_result(job_idx, &result);
+                    }
+                    self.workspaces.health.record(&result);
                     self.jobs.complete_job(job_idx, result);
                     should_sort = true;
+                    if self.jobs.is_auth_failure(job_idx) {
+                        self.prompt_reauthenticate();
+                    }
+                }
+                JobUpdateMessage::WatchTick(job_id, tick) => {
+                    self.jobs.apply_watch_tick(job_id, tick);
                 }
             }
         }
         // Sort jobs after all updates are processed
         if should_sort {
-            self.jobs.sort_by_timestamp();
+            self.jobs.sort_jobs();
+            self.workspaces.save_health();
+            self.refresh_result_preview();
         }
+
+        for run in self.packs.take_finished_runs() {
+            if let Some(folder) = &run.ioc_folder {
+                self.write_run_iocs(&run.summary, folder);
+            }
+            notify_run(run.summary, run.webhook);
+        }
+        if !self.jobs.has_pending_jobs() {
+            if let Some(summary) = self.run_summary.take() {
+                if self.settings.extract_iocs {
+                    let folder = std::path::PathBuf::from(&self.settings.output_folder);
+                    self.write_run_iocs(&summary, &folder);
+                }
+                notify_run(summary, None);
+            }
+        }
+    }
+
+    /// Write the indicators found in a finished run's results, reporting where they went
+    fn write_run_iocs(&mut self, summary: &RunSummary, output_folder: &std::path::Path) {
+        use notifications::NotificationLevel;
+        match crate::ioc::extract_run(summary, output_folder) {
+            Ok(Some((folder, count))) => self.notifications.push_toast(
+                NotificationLevel::Info,
+                format!(
+                    "{}: {} IOC(s) written to {}",
+                    summary.source,
+                    count,
+                    folder.display()
+                ),
+            ),
+            Ok(None) => {}
+            Err(e) => self.notifications.push_toast(
+                NotificationLevel::Error,
+                format!("{}: failed to write IOCs: {}", summary.source, e),
+            ),
+        }
+    }
+
+    /// Show the most recently finished job of the editor's latest run in the split view
+    pub fn refresh_result_preview(&mut self) {
+        if !self.query.split_view {
+            return;
+        }
+        let latest = self
+            .jobs
+            .jobs
+            .iter()
+            .filter(|job| self.query.run_job_ids.contains(&job.job_id))
+            .filter_map(|job| Some((job, job.result.as_ref()?.timestamp)))
+            .max_by_key(|(_, finished)| *finished)
+            .map(|(job, _)| job);
+        if let Some(job) = latest {
+            if self
+                .query
+                .result_preview
+                .as_ref()
+                .is_none_or(|preview| preview.job_id != job.job_id)
+            {
+                self.query.result_preview = Some(query::ResultPreview::of_job(job));
+            }
+        }
+    }
+
+    /// Add a finished one-off job to the summary sent once the job list is idle
+    fn record_run_result(&mut self, job_id: u64, result: &QueryJobResult) {
+        let Some(job) = self.jobs.jobs.iter().find(|job| job.job_id == job_id) else {
+            return;
+        };
+        if job.watch.is_some() {
+            return;
+        }
+        let query_name = job
+            .retry_context
+            .as_ref()
+            .map(|context| context.settings.job_name.clone())
+            .unwrap_or_else(|| "query".to_string());
+        let source = self
+            .sessions
+            .current_session_name
+            .clone()
+            .unwrap_or_else(|| "Unsaved session".to_string());
+        self.run_summary
+            .get_or_insert_with(|| RunSummary::new(&source))
+            .add(&query_name, result);
+    }
+
+    /// Offer to re-authenticate (once until auth succeeds again, never over another popup)
+    fn prompt_reauthenticate(&mut self) {
+        if self.auth_prompted || self.popup.is_some() || self.init_state != InitState::Ready {
+            return;
+        }
+        self.auth_prompted = true;
+        self.popup = Some(Popup::ConfirmReauthenticate);
+    }
+}
+
+/// Send a finished run's summary to the configured webhook in the background
+fn notify_run(summary: RunSummary, pack_webhook: Option<WebhookConfig>) {
+    let config = crate::config::Config::resolve();
+    let Some(webhook) = WebhookConfig::resolve(&config.webhook, pack_webhook.as_ref()) else {
+        return;
+    };
+    if !webhook.should_notify(&summary) {
+        return;
+    }
+    tokio::spawn(async move {
+        if let Err(e) = webhook.send(&summary).await {
+            log::warn!("Failed to send run notification: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_jump_stops_at_ends() {
+        // Filtered lists skip hidden rows
+        let visible: Vec<usize> = (0..50).map(|i| i * 2).collect();
+        assert_eq!(ListJump::PageDown.target(&visible, Some(0)), Some(40));
+        assert_eq!(ListJump::PageDown.target(&visible, Some(90)), Some(98));
+        assert_eq!(ListJump::PageUp.target(&visible, Some(10)), Some(0));
+        assert_eq!(ListJump::Last.target(&visible, None), Some(98));
+        assert_eq!(ListJump::First.target(&visible, Some(60)), Some(0));
+        // A hidden selection pages from the top
+        assert_eq!(ListJump::PageDown.target(&visible, Some(3)), Some(0));
+        assert_eq!(ListJump::First.target(&[], Some(0)), None);
     }
 }
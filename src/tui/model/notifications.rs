@@ -0,0 +1,137 @@
+use chrono::{DateTime, FixedOffset};
+use ratatui::widgets::ListState;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Maximum number of notifications kept in history
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Notification severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationLevel {
+    Info,
+    Success,
+    Error,
+}
+
+impl NotificationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationLevel::Info => "INFO",
+            NotificationLevel::Success => "OK",
+            NotificationLevel::Error => "ERROR",
+        }
+    }
+
+    pub fn color(&self) -> ratatui::style::Color {
+        use ratatui::style::Color;
+        match self {
+            NotificationLevel::Info => Color::Cyan,
+            NotificationLevel::Success => Color::Green,
+            NotificationLevel::Error => Color::Red,
+        }
+    }
+}
+
+/// A single recorded notification
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: NotificationLevel,
+    pub message: String,
+    pub timestamp: DateTime<FixedOffset>,
+}
+
+/// Notification history and toast state
+#[derive(Debug, Clone)]
+pub struct NotificationsModel {
+    /// Recorded notifications (oldest first)
+    pub entries: VecDeque<Notification>,
+    /// List state for the history popup
+    pub list_state: ListState,
+    /// When the current toast should disappear
+    toast_until: Option<Instant>,
+}
+
+impl NotificationsModel {
+    /// Create an empty NotificationsModel
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            list_state: ListState::default(),
+            toast_until: None,
+        }
+    }
+
+    /// Record a notification, dropping the oldest once history is full
+    pub fn push(&mut self, level: NotificationLevel, message: String) {
+        if self.entries.len() == MAX_NOTIFICATIONS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Notification {
+            level,
+            message,
+            timestamp: crate::time_zone::now(),
+        });
+    }
+
+    /// Record a notification and show it as a toast
+    pub fn push_toast(&mut self, level: NotificationLevel, message: String) {
+        self.push(level, message);
+        self.toast_until = Some(Instant::now() + TOAST_DURATION);
+    }
+
+    /// The notification to show as a toast, if one is still active
+    pub fn active_toast(&self) -> Option<&Notification> {
+        match self.toast_until {
+            Some(until) if Instant::now() < until => self.entries.back(),
+            _ => None,
+        }
+    }
+
+    /// Prepare the history popup (newest entry selected)
+    pub fn open(&mut self) {
+        self.toast_until = None;
+        self.list_state.select(if self.entries.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    /// Entries in display order (newest first)
+    pub fn newest_first(&self) -> impl Iterator<Item = &Notification> {
+        self.entries.iter().rev()
+    }
+
+    /// Move selection up
+    pub fn previous(&mut self) {
+        if let Some(selected) = self.list_state.selected() {
+            self.list_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    /// Move selection down
+    pub fn next(&mut self) {
+        if let Some(selected) = self.list_state.selected() {
+            if selected + 1 < self.entries.len() {
+                self.list_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    /// Clear all notifications
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.list_state.select(None);
+        self.toast_until = None;
+    }
+}
+
+impl Default for NotificationsModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,10 @@
-use crate::query_pack::QueryPack;
+use crate::query_job::QueryJobResult;
+use crate::query_pack::{QueryPack, PERSONAL_LIBRARY};
+use crate::run_manifest::RunManifest;
+use crate::tui::model::ListJump;
+use crate::webhook::{RunSummary, WebhookConfig};
 use ratatui::widgets::TableState;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Query Packs tab state
@@ -13,6 +18,136 @@ pub struct PacksModel {
     pub loading: bool,
     /// Error message if pack loading failed
     pub error: Option<String>,
+    /// Library files that were skipped because they aren't query packs
+    pub skipped: Vec<SkippedFile>,
+    /// Incomplete previous run offered for resume (while the resume prompt is open)
+    pub resume_candidate: Option<RunManifest>,
+    /// Pack runs in progress, each with the jobs it's still waiting for
+    pub active_runs: Vec<ActivePackRun>,
+    /// Path input for saving the loaded pack to a new file (while the save-as popup is open)
+    pub save_as_input: Option<String>,
+    /// New name for the selected pack (while the rename popup is open)
+    pub rename_input: Option<String>,
+    /// Tags the pack list is filtered by (empty shows every pack)
+    pub tag_filter: Vec<String>,
+    /// Tag filter input buffer (while the tag filter popup is open)
+    pub tag_input: Option<String>,
+    /// Bump the version and add a changelog entry when saving edits (`pack_changelog` in config)
+    pub changelog: bool,
+    /// Job count above which a run needs confirmation (`confirm_jobs_threshold` in config)
+    pub confirm_threshold: Option<usize>,
+    /// Run waiting for confirmation (while the confirmation popup is open)
+    pub pending_confirmation: Option<RunConfirmation>,
+    /// Search across every pack's contents (while the search popup is open)
+    pub search: Option<PackSearch>,
+    /// Query highlighted in the details panel, as (pack index, query index)
+    query_cursor: Option<(usize, usize)>,
+}
+
+/// Search across the names, descriptions and KQL of the packs in the libraries
+#[derive(Debug, Clone, Default)]
+pub struct PackSearch {
+    pub input: String,
+    /// Treat the input as a regex instead of literal text
+    pub regex: bool,
+    /// Matches of the current input, in library order
+    pub results: Vec<PackMatch>,
+    pub selected: usize,
+    /// Invalid pattern message
+    pub error: Option<String>,
+}
+
+impl PackSearch {
+    pub fn selected_match(&self) -> Option<&PackMatch> {
+        self.results.get(self.selected)
+    }
+
+    pub fn navigate(&mut self, delta: i32) {
+        if !self.results.is_empty() {
+            self.selected = (self.selected as i64 + delta as i64)
+                .clamp(0, self.results.len() as i64 - 1) as usize;
+        }
+    }
+}
+
+/// Part of a pack matching a pack search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackMatch {
+    /// Index into `PacksModel::packs`
+    pub pack: usize,
+    /// Index of the query the match is in (None for the pack's own name or description)
+    pub query: Option<usize>,
+    pub field: MatchField,
+    /// The matching line
+    pub text: String,
+}
+
+/// Where in a pack a search matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchField {
+    PackName,
+    PackDescription,
+    QueryName,
+    QueryDescription,
+    /// KQL text, at this 1-based line
+    Kql(usize),
+}
+
+impl MatchField {
+    pub fn label(&self) -> String {
+        match self {
+            Self::PackName => "pack".to_string(),
+            Self::PackDescription => "pack desc".to_string(),
+            Self::QueryName => "query".to_string(),
+            Self::QueryDescription => "query desc".to_string(),
+            Self::Kql(line) => format!("kql:{}", line),
+        }
+    }
+}
+
+/// A pack run whose progress is being recorded in a manifest
+#[derive(Debug, Clone)]
+pub struct ActivePackRun {
+    pub manifest: RunManifest,
+    /// Job ID -> pack query name
+    pub jobs: HashMap<u64, String>,
+    /// Outcome of the jobs finished so far (sent as a notification when the run completes)
+    pub summary: RunSummary,
+    /// The pack's notification overrides
+    pub webhook: Option<WebhookConfig>,
+    /// Folder the run's IOC files are written to, when extraction is enabled
+    pub ioc_folder: Option<std::path::PathBuf>,
+}
+
+/// Preview of a pack run, shown until it's confirmed or cancelled
+#[derive(Debug, Clone)]
+pub struct RunConfirmation {
+    pub pack_name: String,
+    /// Why the run is high-impact and needs an explicit `y`, if it is
+    pub reason: Option<String>,
+    /// Names of the queries that would run
+    pub queries: Vec<String>,
+    /// Names of the workspaces the pack would run against
+    pub workspaces: Vec<String>,
+    pub subscriptions: usize,
+    /// Lookback window applied to each query, if the pack sets one
+    pub timespan: Option<String>,
+    /// Most jobs expected to run at once under the concurrency limits
+    pub concurrency: usize,
+    pub output_folder: PathBuf,
+    /// Where results go (file formats, SQLite, uploads, ingestion)
+    pub outputs: Vec<String>,
+    /// Resume the incomplete previous run once confirmed
+    pub resume: bool,
+}
+
+/// A library file skipped while scanning for packs
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SkippedFile {
+    /// Library name and path relative to the library root
+    pub path: String,
+    /// Why the file isn't treated as a pack
+    pub reason: String,
 }
 
 /// A query pack entry in the browser
@@ -22,8 +157,12 @@ pub struct PackEntry {
     pub path: PathBuf,
     /// Loaded pack (lazy-loaded when selected)
     pub pack: Option<QueryPack>,
-    /// Relative path from packs directory (for display)
+    /// Relative path from the library root (for display)
     pub relative_path: String,
+    /// Name of the library the pack was found in
+    pub source: String,
+    /// Whether the library refuses writes
+    pub read_only: bool,
     /// Load error if pack failed to parse
     pub load_error: Option<String>,
 }
@@ -36,6 +175,18 @@ impl PacksModel {
             table_state: TableState::default(),
             loading: false,
             error: None,
+            skipped: Vec::new(),
+            resume_candidate: None,
+            active_runs: Vec::new(),
+            save_as_input: None,
+            rename_input: None,
+            tag_filter: Vec::new(),
+            tag_input: None,
+            changelog: false,
+            confirm_threshold: None,
+            pending_confirmation: None,
+            search: None,
+            query_cursor: None,
         }
     }
 
@@ -45,12 +196,16 @@ impl PacksModel {
         self.error = None;
 
         match self.load_packs_from_library() {
-            Ok(packs) => {
+            Ok((packs, skipped)) => {
                 self.packs = packs;
-                // Set initial selection if we have packs
-                if !self.packs.is_empty() && self.table_state.selected().is_none() {
-                    self.table_state.select(Some(0));
+                self.query_cursor = None;
+                self.skipped = skipped;
+                // Filtering needs every pack's tags
+                if !self.tag_filter.is_empty() {
+                    self.load_all_packs();
                 }
+                // Set initial selection if we have packs
+                self.keep_selection_visible();
             }
             Err(e) => {
                 self.error = Some(format!("Failed to load packs: {}", e));
@@ -60,33 +215,65 @@ impl PacksModel {
         self.loading = false;
     }
 
-    /// Load all packs from the library directory
-    fn load_packs_from_library(&self) -> crate::error::Result<Vec<PackEntry>> {
-        let pack_paths = QueryPack::list_library_packs()?;
-        let library_root = QueryPack::get_library_path("")?;
-
+    /// Load all packs from every library, along with the files skipped as non-packs
+    fn load_packs_from_library(&self) -> crate::error::Result<(Vec<PackEntry>, Vec<SkippedFile>)> {
         let mut entries = Vec::new();
+        let mut skipped = Vec::new();
 
-        for path in pack_paths {
-            // Compute relative path for display
-            let relative_path = path
-                .strip_prefix(&library_root)
-                .unwrap_or(&path)
-                .to_string_lossy()
-                .to_string();
+        for library in QueryPack::libraries()? {
+            // Create the personal library so users have somewhere to drop packs
+            if library.name == PERSONAL_LIBRARY {
+                std::fs::create_dir_all(&library.path)?;
+            }
+
+            let scan = match QueryPack::scan_library(&library.path) {
+                Ok(scan) => scan,
+                Err(e) => {
+                    log::warn!("Failed to list pack library '{}': {}", library.name, e);
+                    continue;
+                }
+            };
 
-            entries.push(PackEntry {
-                path: path.clone(),
-                pack: None, // Lazy load when needed
-                relative_path,
-                load_error: None,
-            });
+            for (path, reason) in scan.skipped {
+                let relative_path = path.strip_prefix(&library.path).unwrap_or(&path);
+                log::debug!("Skipping non-pack file {}: {}", path.display(), reason);
+                skipped.push(SkippedFile {
+                    path: format!("{}/{}", library.name, relative_path.display()),
+                    reason,
+                });
+            }
+
+            for path in scan.packs {
+                // Compute relative path for display
+                let relative_path = path
+                    .strip_prefix(&library.path)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+
+                entries.push(PackEntry {
+                    path: path.clone(),
+                    pack: None, // Lazy load when needed
+                    relative_path,
+                    source: library.name.clone(),
+                    read_only: library.read_only,
+                    load_error: None,
+                });
+            }
         }
 
-        // Sort by relative path
+        // Sort by relative path (stable, so libraries keep their order for equal paths)
         entries.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        skipped.sort();
 
-        Ok(entries)
+        Ok((entries, skipped))
+    }
+
+    /// Find the pack whose file contents match `hash` (e.g. after it was renamed or moved)
+    pub fn find_by_content_hash(&self, hash: u64) -> Option<&PackEntry> {
+        self.packs
+            .iter()
+            .find(|entry| QueryPack::content_hash(&entry.path) == Some(hash))
     }
 
     /// Get the currently selected pack entry
@@ -119,42 +306,214 @@ impl PacksModel {
         Ok(())
     }
 
+    /// Load every pack that hasn't been loaded yet (parse errors are kept on the entry)
+    pub fn load_all_packs(&mut self) {
+        for entry in &mut self.packs {
+            if entry.pack.is_none() && entry.load_error.is_none() {
+                match QueryPack::load_from_file(&entry.path) {
+                    Ok(pack) => entry.pack = Some(pack),
+                    Err(e) => entry.load_error = Some(format!("Parse error: {}", e)),
+                }
+            }
+        }
+    }
+
+    /// Filter the pack list to packs tagged with any of `tags` (empty clears the filter)
+    pub fn set_tag_filter(&mut self, tags: Vec<String>) {
+        self.tag_filter = tags;
+        if !self.tag_filter.is_empty() {
+            self.load_all_packs();
+        }
+        self.keep_selection_visible();
+    }
+
+    /// Select the first visible pack if nothing visible is selected
+    fn keep_selection_visible(&mut self) {
+        let visible = self.visible_indices();
+        if !self
+            .table_state
+            .selected()
+            .is_some_and(|i| visible.contains(&i))
+        {
+            self.table_state.select(visible.first().copied());
+        }
+    }
+
+    /// Indices (into `packs`) of the packs shown under the current tag filter
+    pub fn visible_indices(&self) -> Vec<usize> {
+        self.packs
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                self.tag_filter.is_empty()
+                    || entry
+                        .pack
+                        .as_ref()
+                        .is_some_and(|pack| pack.has_any_tag(&self.tag_filter))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
     /// Navigate to the previous pack in the list
     pub fn previous(&mut self) {
-        if self.packs.is_empty() {
+        let visible = self.visible_indices();
+        if visible.is_empty() {
             return;
         }
 
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.packs.len() - 1
-                } else {
-                    i - 1
-                }
-            }
+        let i = match self
+            .table_state
+            .selected()
+            .and_then(|selected| visible.iter().position(|&i| i == selected))
+        {
+            Some(0) => visible.len() - 1,
+            Some(pos) => pos - 1,
             None => 0,
         };
-        self.table_state.select(Some(i));
+        self.table_state.select(Some(visible[i]));
+    }
+
+    /// Jump through the visible packs by a page or to either end
+    pub fn jump(&mut self, jump: ListJump) {
+        if let Some(idx) = jump.target(&self.visible_indices(), self.table_state.selected()) {
+            self.table_state.select(Some(idx));
+        }
     }
 
     /// Navigate to the next pack in the list
     pub fn next(&mut self) {
-        if self.packs.is_empty() {
+        let visible = self.visible_indices();
+        if visible.is_empty() {
+            return;
+        }
+
+        let i = match self
+            .table_state
+            .selected()
+            .and_then(|selected| visible.iter().position(|&i| i == selected))
+        {
+            Some(pos) if pos + 1 < visible.len() => pos + 1,
+            _ => 0,
+        };
+        self.table_state.select(Some(visible[i]));
+    }
+
+    /// Record a finished job in the manifest of the run it belongs to (if any)
+    /// Returns whether it did; the run is kept until `take_finished_runs` collects it.
+    pub fn record_job_result(&mut self, job_id: u64, result: &QueryJobResult) -> bool {
+        let Some((run, query_name)) = self
+            .active_runs
+            .iter_mut()
+            .find_map(|run| run.jobs.remove(&job_id).map(|name| (run, name)))
+        else {
+            return false;
+        };
+
+        run.manifest.record(&query_name, result);
+        run.summary.add(&query_name, result);
+        if let Err(e) = run.manifest.save() {
+            log::warn!("Failed to save run manifest: {}", e);
+        }
+        true
+    }
+
+    /// Take the runs whose jobs have all finished
+    pub fn take_finished_runs(&mut self) -> Vec<ActivePackRun> {
+        let (finished, running) = std::mem::take(&mut self.active_runs)
+            .into_iter()
+            .partition(|run| run.jobs.is_empty());
+        self.active_runs = running;
+        finished
+    }
+
+    /// Index of the query highlighted in the selected pack (None means the whole pack)
+    pub fn selected_query(&self) -> Option<usize> {
+        match (self.query_cursor, self.table_state.selected()) {
+            (Some((pack, query)), Some(selected)) if pack == selected => Some(query),
+            _ => None,
+        }
+    }
+
+    /// Highlight the next query of the selected pack (the first, if none is highlighted)
+    pub fn next_query(&mut self) {
+        let Some(selected) = self.table_state.selected() else {
+            return;
+        };
+        let Some(count) = self
+            .packs
+            .get(selected)
+            .and_then(PackEntry::get_query_count)
+        else {
+            return;
+        };
+        if count == 0 {
             return;
         }
+        let query = self
+            .selected_query()
+            .map_or(0, |query| (query + 1).min(count - 1));
+        self.query_cursor = Some((selected, query));
+    }
+
+    /// Highlight the previous query, going back to the whole pack from the first one
+    pub fn previous_query(&mut self) {
+        self.query_cursor = match (self.selected_query(), self.table_state.selected()) {
+            (Some(query), Some(selected)) if query > 0 => Some((selected, query - 1)),
+            _ => None,
+        };
+    }
 
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i >= self.packs.len() - 1 {
-                    0
-                } else {
-                    i + 1
+    /// Stop highlighting a query, so the whole pack is acted on again
+    pub fn clear_query_selection(&mut self) {
+        self.query_cursor = None;
+    }
+
+    /// Find `matcher` in every loaded pack's name, description, query names and descriptions,
+    /// and KQL lines
+    pub fn search_contents(&self, matcher: &regex::Regex) -> Vec<PackMatch> {
+        let mut results = Vec::new();
+        for (pack_idx, entry) in self.packs.iter().enumerate() {
+            let Some(pack) = &entry.pack else {
+                continue;
+            };
+            let mut found = |query, field, text: &str| {
+                results.push(PackMatch {
+                    pack: pack_idx,
+                    query,
+                    field,
+                    text: text.trim().to_string(),
+                })
+            };
+            for (_, line) in matching_lines(matcher, Some(&pack.name)) {
+                found(None, MatchField::PackName, line);
+            }
+            for (_, line) in matching_lines(matcher, pack.description.as_ref()) {
+                found(None, MatchField::PackDescription, line);
+            }
+            for (query_idx, query) in pack.get_queries().iter().enumerate() {
+                let query_idx = Some(query_idx);
+                for (_, line) in matching_lines(matcher, Some(&query.name)) {
+                    found(query_idx, MatchField::QueryName, line);
+                }
+                for (_, line) in matching_lines(matcher, query.description.as_ref()) {
+                    found(query_idx, MatchField::QueryDescription, line);
+                }
+                for (line_idx, line) in matching_lines(matcher, Some(&query.query)) {
+                    found(query_idx, MatchField::Kql(line_idx + 1), line);
                 }
             }
-            None => 0,
-        };
-        self.table_state.select(Some(i));
+        }
+        results
+    }
+
+    /// Select a pack, and one of its queries if given, showing it if the tag filter hides it
+    pub fn select_pack_query(&mut self, pack: usize, query: Option<usize>) {
+        if !self.visible_indices().contains(&pack) {
+            self.tag_filter.clear();
+        }
+        self.table_state.select(Some(pack));
+        self.query_cursor = query.map(|query| (pack, query));
     }
 
     /// Get pack count
@@ -163,6 +522,16 @@ impl PacksModel {
     }
 }
 
+/// Lines of `text` matching `matcher`, with their 0-based index
+fn matching_lines<'a>(
+    matcher: &'a regex::Regex,
+    text: Option<&'a String>,
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    text.into_iter()
+        .flat_map(|text| text.lines().enumerate())
+        .filter(|(_, line)| matcher.is_match(line))
+}
+
 impl Default for PacksModel {
     fn default() -> Self {
         Self::new()
@@ -195,3 +564,84 @@ impl PackEntry {
         self.pack.as_ref().map(|p| p.get_queries().len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_job::JobSuccess;
+
+    fn run(dir: &std::path::Path, pack_name: &str, jobs: &[(u64, &str)]) -> ActivePackRun {
+        let path = dir.join(format!("{}.json", pack_name));
+        std::fs::write(
+            &path,
+            serde_json::json!({
+                "pack_name": pack_name,
+                "pack_path": dir.join(format!("{}.yaml", pack_name)),
+                "started_at": "2024-05-01T10:00:00+00:00",
+                "updated_at": "2024-05-01T10:00:00+00:00",
+                "entries": []
+            })
+            .to_string(),
+        )
+        .unwrap();
+        ActivePackRun {
+            summary: RunSummary::new(pack_name),
+            manifest: RunManifest::load(&path).unwrap(),
+            jobs: jobs
+                .iter()
+                .map(|(id, query)| (*id, query.to_string()))
+                .collect(),
+            webhook: None,
+            ioc_folder: None,
+        }
+    }
+
+    fn result() -> QueryJobResult {
+        QueryJobResult {
+            workspace_id: "ws-eu-id".to_string(),
+            workspace_name: "ws-eu".to_string(),
+            query: "SigninLogs".to_string(),
+            result: Ok(JobSuccess {
+                row_count: 1,
+                page_count: 1,
+                output_path: PathBuf::new(),
+                file_size: 0,
+                truncated: false,
+                billed_bytes: None,
+                warnings: Vec::new(),
+                statistics: None,
+                uploads: Vec::new(),
+            }),
+            elapsed: std::time::Duration::from_millis(10),
+            timestamp: crate::time_zone::now(),
+        }
+    }
+
+    #[test]
+    fn test_overlapping_runs_are_recorded_separately() {
+        let dir = std::env::temp_dir().join(format!("kql-pack-runs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut packs = PacksModel::new();
+        packs
+            .active_runs
+            .push(run(&dir, "triage", &[(1, "Signins"), (2, "Audit")]));
+        packs.active_runs.push(run(&dir, "lateral", &[(3, "SMB")]));
+
+        // The second run finishes first; the first keeps waiting for its jobs
+        assert!(packs.record_job_result(3, &result()));
+        assert!(packs.record_job_result(1, &result()));
+        assert!(!packs.record_job_result(4, &result()));
+        let finished = packs.take_finished_runs();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].manifest.pack_name, "lateral");
+        assert!(finished[0].manifest.is_complete());
+
+        assert!(packs.record_job_result(2, &result()));
+        let finished = packs.take_finished_runs();
+        assert_eq!(finished[0].manifest.pack_name, "triage");
+        assert_eq!(finished[0].manifest.completed_count(), 2);
+        assert!(packs.active_runs.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
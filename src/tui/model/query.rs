@@ -1,12 +1,31 @@
-use crate::query_pack::PackQuery;
-use tui_textarea::TextArea;
+use crate::file_lock::FileLock;
+use crate::query_pack::{PackQuery, QueryPack};
+use crate::query_params::QueryParameter;
+use crate::tui::model::jobs::JobState;
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tui_textarea::{CursorMove, TextArea};
 
 /// Query editor mode (Vim-style)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EditorMode {
-    Normal, // Normal mode - navigation and commands
-    Insert, // Insert mode - text editing
-    Visual, // Visual mode - text selection
+    Normal,     // Normal mode - navigation and commands
+    Insert,     // Insert mode - text editing
+    Visual,     // Visual mode - text selection
+    VisualLine, // Line-wise visual mode - whole-line selection
+}
+
+/// Largest repeat count accepted before a command (keeps `99999999j` from spinning)
+const MAX_COUNT: usize = 9999;
+
+/// A line-wise operator typed in Normal mode, applied when its key is repeated (`dd`, `yy`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineOperator {
+    Delete,
+    Yank,
 }
 
 /// Pack context - tracks which query pack is currently loaded in the editor
@@ -14,12 +33,20 @@ pub enum EditorMode {
 pub struct PackContext {
     /// Display name of the pack
     pub pack_name: String,
-    /// Relative path from packs directory (for matching with PacksModel)
-    pub pack_path: String,
+    /// Full path to the pack file (for matching with PacksModel)
+    pub pack_path: PathBuf,
     /// All queries in this pack
     pub queries: Vec<PackQuery>,
     /// Index of currently displayed query
     pub current_index: usize,
+    /// In-memory copy of the pack as last loaded or saved (written out by save-as)
+    pub pack: QueryPack,
+    /// Hash of the pack file's contents when loaded or last saved (finds it if moved)
+    pub content_hash: Option<u64>,
+    /// Lock on the pack file, so other instances don't overwrite it
+    pub lock: Option<Arc<FileLock>>,
+    /// Opened read-only because another instance holds the pack's lock
+    pub read_only: bool,
 }
 
 impl PackContext {
@@ -105,43 +132,405 @@ pub struct LoadPanelState {
     pub sorted_indices: Vec<usize>,
 }
 
+/// A `//#region` ... `//#endregion` section of the editor text (0-based rows, inclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoldRegion {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl FoldRegion {
+    /// Check if a row is hidden when this region is folded (the start row stays visible)
+    pub fn hides(&self, row: usize) -> bool {
+        row > self.start && row <= self.end
+    }
+}
+
+/// Kind of region marker comment on a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionMarker {
+    Start,
+    End,
+}
+
+/// Check if a line is a `//#region` or `//#endregion` comment (`// #region` also works)
+pub fn region_marker(line: &str) -> Option<RegionMarker> {
+    let comment = line.trim_start().strip_prefix("//")?.trim_start();
+    if comment.starts_with("#endregion") {
+        Some(RegionMarker::End)
+    } else if comment.starts_with("#region") {
+        Some(RegionMarker::Start)
+    } else {
+        None
+    }
+}
+
+/// Find the foldable sections of the text (nested sections are supported; a region without
+/// a matching `//#endregion` can't be folded)
+pub fn fold_regions(lines: &[String]) -> Vec<FoldRegion> {
+    let mut open = Vec::new();
+    let mut regions = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        match region_marker(line) {
+            Some(RegionMarker::Start) => open.push(row),
+            Some(RegionMarker::End) => {
+                if let Some(start) = open.pop() {
+                    regions.push(FoldRegion { start, end: row });
+                }
+            }
+            None => {}
+        }
+    }
+    regions.sort_by_key(|region| region.start);
+    regions
+}
+
+/// A query buffer that isn't currently shown in the editor
+pub struct QueryBuffer {
+    pub textarea: TextArea<'static>,
+    pub pack_context: Option<PackContext>,
+    pub folded: HashSet<String>,
+    pub undo_snapshots: Vec<String>,
+}
+
+impl Default for QueryBuffer {
+    fn default() -> Self {
+        Self {
+            textarea: new_textarea(Vec::new()),
+            pack_context: None,
+            folded: HashSet::new(),
+            undo_snapshots: Vec::new(),
+        }
+    }
+}
+
+/// Earlier states of the active buffer kept for the draft's undo history
+const MAX_DRAFT_UNDO: usize = 25;
+
+/// Editor buffers saved on exit and restored on the next launch (`<data_dir>/editor_draft.json`)
+/// Kept apart from sessions, so a draft survives quitting without saving anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditorDraft {
+    /// Text of each buffer, in tab order
+    pub buffers: Vec<String>,
+    pub active_buffer: usize,
+    /// Cursor (row, column) in the active buffer
+    pub cursor: (usize, usize),
+    /// Earlier states of the active buffer, oldest first, replayed into its undo history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub undo: Vec<String>,
+}
+
+impl EditorDraft {
+    pub fn path() -> crate::error::Result<PathBuf> {
+        Ok(crate::paths::data_dir()?.join("editor_draft.json"))
+    }
+
+    /// Load the saved draft (None if missing or unreadable)
+    pub fn load() -> Option<Self> {
+        let json = std::fs::read_to_string(Self::path().ok()?).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self) -> crate::error::Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Export formats for one run, chosen in the job name popup (starts from the Settings values)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunFormats {
+    pub export_csv: bool,
+    pub export_json: bool,
+    pub parse_dynamics: bool,
+}
+
+impl RunFormats {
+    /// Labels of the toggles in the job name popup, in focus order
+    pub const OPTIONS: [&'static str; 3] = ["CSV", "JSON", "Parse dynamics"];
+
+    /// Value of a toggle by its index in `OPTIONS`
+    pub fn get(&self, option: usize) -> bool {
+        match option {
+            0 => self.export_csv,
+            1 => self.export_json,
+            _ => self.parse_dynamics,
+        }
+    }
+
+    pub fn toggle(&mut self, option: usize) {
+        match option {
+            0 => self.export_csv = !self.export_csv,
+            1 => self.export_json = !self.export_json,
+            _ => self.parse_dynamics = !self.parse_dynamics,
+        }
+    }
+}
+
+/// Values being entered for a query's parameters before it runs
+#[derive(Debug, Clone)]
+pub struct ParameterInput {
+    /// Job name the run continues with once the values are entered
+    pub job_name: String,
+    /// Whether the run is a watch
+    pub watch: bool,
+    pub parameters: Vec<QueryParameter>,
+    /// Entered values, in the same order as `parameters`
+    pub values: Vec<String>,
+    /// Index of the parameter being typed into
+    pub focus: usize,
+    /// Why the last submit was refused
+    pub error: Option<String>,
+}
+
+impl ParameterInput {
+    /// Start with the values last entered for parameters of the same name
+    pub fn new(
+        job_name: String,
+        watch: bool,
+        parameters: Vec<QueryParameter>,
+        remembered: &BTreeMap<String, String>,
+    ) -> Self {
+        let values = parameters
+            .iter()
+            .map(|p| remembered.get(&p.name).cloned().unwrap_or_default())
+            .collect();
+        Self {
+            job_name,
+            watch,
+            parameters,
+            values,
+            focus: 0,
+            error: None,
+        }
+    }
+
+    /// The value being typed into
+    pub fn focused_mut(&mut self) -> Option<&mut String> {
+        self.values.get_mut(self.focus)
+    }
+
+    /// Move the focus forward or back, wrapping around
+    pub fn cycle_focus(&mut self, forward: bool) {
+        let fields = self.parameters.len().max(1);
+        self.focus = if forward {
+            (self.focus + 1) % fields
+        } else {
+            (self.focus + fields - 1) % fields
+        };
+    }
+
+    /// Entered values by parameter name
+    pub fn values_by_name(&self) -> BTreeMap<String, String> {
+        self.parameters
+            .iter()
+            .zip(&self.values)
+            .map(|(p, v)| (p.name.clone(), v.clone()))
+            .collect()
+    }
+}
+
+/// Rows kept for the split view's result preview
+pub const MAX_PREVIEW_ROWS: usize = 200;
+
+/// A finished job's result, as shown in the split view
+#[derive(Debug, Clone)]
+pub struct ResultPreview {
+    pub job_id: u64,
+    pub workspace_name: String,
+    pub outcome: PreviewOutcome,
+}
+
+#[derive(Debug, Clone)]
+pub enum PreviewOutcome {
+    /// The first `MAX_PREVIEW_ROWS` rows of the output file
+    Rows {
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+        total_rows: usize,
+    },
+    /// The job failed, or its output can't be previewed
+    Error(String),
+}
+
+impl ResultPreview {
+    /// Preview a finished job (reads its output file)
+    pub fn of_job(job: &JobState) -> Self {
+        let outcome = match (&job.error, job.result.as_ref().map(|r| &r.result)) {
+            (Some(error), _) => PreviewOutcome::Error(error.detailed_description()),
+            (None, Some(Ok(success))) if success.output_path.as_os_str().is_empty() => {
+                PreviewOutcome::Error(
+                    "Nothing to preview: the job had no CSV or JSON output".to_string(),
+                )
+            }
+            (None, Some(Ok(success))) => {
+                match crate::tui::model::chart::read_result_table(&success.output_path) {
+                    Ok((columns, mut rows)) => {
+                        let total_rows = rows.len();
+                        rows.truncate(MAX_PREVIEW_ROWS);
+                        PreviewOutcome::Rows {
+                            columns,
+                            rows,
+                            total_rows,
+                        }
+                    }
+                    Err(e) => PreviewOutcome::Error(e),
+                }
+            }
+            (None, Some(Err(e))) => PreviewOutcome::Error(e.to_string()),
+            (None, None) => PreviewOutcome::Error("The job has not finished".to_string()),
+        };
+        Self {
+            job_id: job.job_id,
+            workspace_name: job.workspace_name.clone(),
+            outcome,
+        }
+    }
+}
+
 /// Query tab state
 pub struct QueryModel {
-    /// Text area widget with full editor capabilities
+    /// Text area widget with full editor capabilities (the active buffer)
     pub textarea: TextArea<'static>,
     /// Editor mode (Normal or Insert)
     pub mode: EditorMode,
     /// Job name input buffer (when executing)
     pub job_name_input: Option<String>,
+    /// Export formats for the pending run
+    pub run_formats: RunFormats,
+    /// Optional description for the pending run (stored on its jobs and in sessions)
+    pub description_input: String,
+    /// Focused field in the job name popup: 0 = name, 1 = description, otherwise a
+    /// `RunFormats` toggle (see `format_focus`)
+    pub job_name_focus: usize,
+    /// Whether the pending job name input starts a watch instead of a one-off run
+    pub watch_requested: bool,
+    /// Single workspace the pending run targets instead of the selection
+    pub target_workspace: Option<Workspace>,
+    /// Parameter values being entered for the pending run
+    pub parameter_input: Option<ParameterInput>,
+    /// Query text with parameter values substituted, used by the pending run instead of the
+    /// editor text
+    pub resolved_query: Option<String>,
     /// Load panel state (None = closed, Some = open)
     pub load_panel: Option<LoadPanelState>,
     /// Pack context (if query was loaded from a pack)
     pub pack_context: Option<PackContext>,
+    /// Show the latest run's result in a pane below the editor
+    pub split_view: bool,
+    /// Jobs started by the latest run from the editor (followed by the split view)
+    pub run_job_ids: Vec<u64>,
+    /// Result shown in the split view
+    pub result_preview: Option<ResultPreview>,
+    /// Repeat count typed before a Normal or Visual mode command
+    pending_count: Option<usize>,
+    /// Line-wise operator waiting for its key to be repeated
+    pending_operator: Option<LineOperator>,
+    /// Row where line-wise visual mode started
+    visual_anchor: usize,
+    /// States of the active buffer after each Normal mode edit or Insert mode visit, oldest
+    /// first, ending with the current text (saved as the draft's undo history)
+    undo_snapshots: Vec<String>,
+    /// Folded regions of the active buffer, by their `//#region` line (survives edits that
+    /// move the region)
+    folded: HashSet<String>,
+    /// All buffers in tab order; the active buffer's slot is a placeholder while its
+    /// state lives in `textarea` and `pack_context`
+    buffers: Vec<QueryBuffer>,
+    /// Index of the active buffer
+    active_buffer: usize,
 }
 
 impl QueryModel {
     /// Create a new QueryModel
     pub fn new() -> Self {
-        let mut textarea = TextArea::default();
-        textarea.set_cursor_line_style(ratatui::style::Style::default());
-        textarea.set_line_number_style(
-            ratatui::style::Style::default().fg(ratatui::style::Color::DarkGray),
-        );
-
         Self {
-            textarea,
+            textarea: new_textarea(Vec::new()),
             mode: EditorMode::Normal,
             job_name_input: None,
+            run_formats: RunFormats {
+                export_csv: true,
+                export_json: false,
+                parse_dynamics: true,
+            },
+            description_input: String::new(),
+            job_name_focus: 0,
+            watch_requested: false,
+            target_workspace: None,
+            parameter_input: None,
+            resolved_query: None,
             load_panel: None,
             pack_context: None,
+            split_view: false,
+            run_job_ids: Vec::new(),
+            result_preview: None,
+            pending_count: None,
+            pending_operator: None,
+            visual_anchor: 0,
+            undo_snapshots: vec![String::new()],
+            folded: HashSet::new(),
+            buffers: vec![QueryBuffer::default()],
+            active_buffer: 0,
+        }
+    }
+
+    /// Open the job name input, with the export formats preset for this run
+    pub fn start_job_name_input(&mut self, formats: RunFormats) {
+        self.job_name_input = Some(String::new());
+        self.description_input.clear();
+        self.run_formats = formats;
+        self.job_name_focus = 0;
+    }
+
+    /// Index of the focused `RunFormats` toggle, if a toggle has focus
+    pub fn format_focus(&self) -> Option<usize> {
+        self.job_name_focus.checked_sub(2)
+    }
+
+    /// The text field focused in the job name popup
+    pub fn focused_job_field_mut(&mut self) -> Option<&mut String> {
+        match self.job_name_focus {
+            0 => self.job_name_input.as_mut(),
+            1 => Some(&mut self.description_input),
+            _ => None,
         }
     }
 
+    /// Description entered for the pending run, if any
+    pub fn run_description(&self) -> Option<String> {
+        let description = self.description_input.trim();
+        (!description.is_empty()).then(|| description.to_string())
+    }
+
+    /// Move the job name popup focus forward or back, wrapping around
+    pub fn cycle_job_name_focus(&mut self, forward: bool) {
+        let fields = RunFormats::OPTIONS.len() + 2;
+        self.job_name_focus = if forward {
+            (self.job_name_focus + 1) % fields
+        } else {
+            (self.job_name_focus + fields - 1) % fields
+        };
+    }
+
     /// Get the query text as a single string
     pub fn get_text(&self) -> String {
         self.textarea.lines().join("\n")
     }
 
+    /// Lint the editor text (nothing is reported for an empty editor)
+    pub fn diagnostics(&self) -> Vec<crate::pack_lint::Diagnostic> {
+        let text = self.get_text();
+        if text.trim().is_empty() {
+            return Vec::new();
+        }
+        crate::pack_lint::diagnostics(&text)
+    }
+
     /// Get a preview of the query (first N chars)
     pub fn get_preview(&self, max_len: usize) -> String {
         self.get_text().chars().take(max_len).collect()
@@ -149,26 +538,419 @@ impl QueryModel {
 
     /// Clear the query text
     pub fn clear(&mut self) {
-        self.textarea = TextArea::default();
-        self.textarea
-            .set_cursor_line_style(ratatui::style::Style::default());
-        self.textarea.set_line_number_style(
-            ratatui::style::Style::default().fg(ratatui::style::Color::DarkGray),
-        );
+        self.textarea = new_textarea(Vec::new());
+        self.folded.clear();
+        self.reset_undo_snapshots();
     }
 
     /// Set query text from string
     pub fn set_text(&mut self, text: String) {
-        let lines: Vec<String> = text.lines().map(|s| s.to_string()).collect();
-        self.textarea = TextArea::from(lines);
+        self.textarea = new_textarea(text.lines().map(|s| s.to_string()).collect());
+        self.folded.clear();
+        self.reset_undo_snapshots();
+    }
+
+    /// Start the draft's undo history over from the current text (the textarea's own history
+    /// starts over too when its text is replaced)
+    fn reset_undo_snapshots(&mut self) {
+        self.undo_snapshots = vec![self.get_text()];
+    }
+
+    /// Record the active buffer's text as a step of the draft's undo history
+    /// Text matching an earlier step means the edits since were undone, so they're dropped.
+    pub fn record_undo_snapshot(&mut self) {
+        let text = self.get_text();
+        if self.undo_snapshots.last() == Some(&text) {
+            return;
+        }
+        match self.undo_snapshots.iter().rposition(|s| *s == text) {
+            Some(pos) => self.undo_snapshots.truncate(pos + 1),
+            None => self.undo_snapshots.push(text),
+        }
+        let excess = self.undo_snapshots.len().saturating_sub(MAX_DRAFT_UNDO + 1);
+        self.undo_snapshots.drain(..excess);
+    }
+
+    /// Every buffer's text and the active buffer's undo steps, to save on exit
+    pub fn to_draft(&self) -> EditorDraft {
+        let text = self.get_text();
+        let mut undo = self.undo_snapshots.clone();
+        if undo.last() == Some(&text) {
+            undo.pop();
+        }
+        let buffers = (0..self.buffers.len())
+            .map(|idx| {
+                if idx == self.active_buffer {
+                    text.clone()
+                } else {
+                    self.buffers[idx].textarea.lines().join("\n")
+                }
+            })
+            .collect();
+        EditorDraft {
+            buffers,
+            active_buffer: self.active_buffer,
+            cursor: self.textarea.cursor(),
+            undo,
+        }
+    }
+
+    /// Replace the buffers with a saved draft, replaying its undo steps so `Ctrl+u` walks back
+    /// through them
+    pub fn restore_draft(&mut self, draft: EditorDraft) {
+        if draft.buffers.is_empty() {
+            return;
+        }
+        let active = draft.active_buffer.min(draft.buffers.len() - 1);
+        let text_lines = |text: &str| text.lines().map(|s| s.to_string()).collect();
+        self.buffers = draft
+            .buffers
+            .iter()
+            .map(|text| QueryBuffer {
+                textarea: new_textarea(text_lines(text)),
+                ..QueryBuffer::default()
+            })
+            .collect();
+        self.active_buffer = active;
+        self.pack_context = None;
+        self.folded.clear();
+
+        let mut steps = draft
+            .undo
+            .iter()
+            .chain(std::iter::once(&draft.buffers[active]));
+        let first = steps.next().cloned().unwrap_or_default();
+        self.textarea = new_textarea(text_lines(&first));
+        let mut previous = first;
+        for step in steps {
+            replace_changed_text(&mut self.textarea, &previous, step);
+            previous = step.clone();
+        }
+        self.buffers[active] = QueryBuffer::default();
+        self.undo_snapshots = draft.undo;
+        self.undo_snapshots.push(previous);
+
+        let (row, col) = draft.cursor;
+        self.textarea
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+    }
+
+    /// Reformat the query with the KQL formatter, keeping undo history
+    /// The cursor stays on its row (clamped to the new text). Returns false if nothing changed.
+    pub fn format_text(&mut self) -> bool {
+        let text = self.get_text();
+        let formatted = crate::kql_format::format(&text);
+        if formatted == text {
+            return false;
+        }
+        let (row, _) = self.textarea.cursor();
+        self.textarea.select_all();
+        self.textarea.insert_str(&formatted);
+        let last_row = formatted.lines().count().saturating_sub(1);
+        self.textarea
+            .move_cursor(tui_textarea::CursorMove::Jump(row.min(last_row) as u16, 0));
+        true
+    }
+
+    /// Folded regions, outermost first
+    fn closed_regions(&self) -> Vec<FoldRegion> {
+        let lines = self.textarea.lines();
+        fold_regions(lines)
+            .into_iter()
+            .filter(|region| self.folded.contains(lines[region.start].trim()))
+            .collect()
+    }
+
+    /// Folded regions to render collapsed
+    /// A region the cursor is inside (e.g. after inserting a line below its start) stays open.
+    pub fn visible_folds(&self) -> Vec<FoldRegion> {
+        let (row, _) = self.textarea.cursor();
+        self.closed_regions()
+            .into_iter()
+            .filter(|region| !region.hides(row))
+            .collect()
+    }
+
+    /// Fold or unfold the innermost region containing the cursor
+    /// Returns false if the cursor isn't in a region.
+    pub fn toggle_fold(&mut self) -> bool {
+        let (row, _) = self.textarea.cursor();
+        let lines = self.textarea.lines();
+        let Some(region) = fold_regions(lines)
+            .into_iter()
+            .filter(|region| region.start <= row && row <= region.end)
+            .max_by_key(|region| region.start)
+        else {
+            return false;
+        };
+
+        let key = lines[region.start].trim().to_string();
+        if !self.folded.remove(&key) {
+            self.folded.insert(key);
+            // Keep the cursor on the summary line rather than inside the hidden rows
+            self.textarea
+                .move_cursor(CursorMove::Jump(region.start as u16, 0));
+        }
+        true
+    }
+
+    /// Move the cursor past folded rows it landed on after moving up or down a line
+    pub fn skip_folds(&mut self, down: bool) {
+        let (row, col) = self.textarea.cursor();
+        let last_row = self.textarea.lines().len().saturating_sub(1);
+        let Some(region) = self
+            .closed_regions()
+            .into_iter()
+            .find(|region| region.hides(row))
+        else {
+            return;
+        };
+        let target = if down && region.end < last_row {
+            region.end + 1
+        } else {
+            region.start
+        };
+        self.textarea
+            .move_cursor(CursorMove::Jump(target as u16, col as u16));
+    }
+
+    /// Add a digit to the repeat count for the next command
+    pub fn push_count_digit(&mut self, digit: usize) {
+        let count = self.pending_count.unwrap_or(0) * 10 + digit;
+        self.pending_count = Some(count.min(MAX_COUNT));
+    }
+
+    /// Whether a repeat count is being typed (so `0` extends it rather than moving the cursor)
+    pub fn has_pending_count(&self) -> bool {
+        self.pending_count.is_some()
+    }
+
+    /// Take the repeat count typed for this command, dropping any half-typed operator
+    pub fn take_pending(&mut self) -> Option<usize> {
+        self.pending_operator = None;
+        self.pending_count.take()
+    }
+
+    /// Handle `d` or `y` in Normal mode
+    /// The first press waits for the repeat; the second applies the operator to the count's
+    /// worth of lines from the cursor. Returns the rows affected (first, last) once applied.
+    pub fn line_operator(&mut self, operator: LineOperator) -> Option<(usize, usize)> {
+        if self.pending_operator != Some(operator) {
+            self.pending_operator = Some(operator);
+            return None;
+        }
+        let count = self.take_pending().unwrap_or(1);
+        let (row, _) = self.textarea.cursor();
+        let last_row = self.textarea.lines().len().saturating_sub(1);
+        Some((row, (row + count - 1).min(last_row)))
+    }
+
+    /// Enter line-wise visual mode on the cursor's line (or the lines of the current selection)
+    pub fn start_line_selection(&mut self) {
+        let cursor = self.textarea.cursor();
+        self.visual_anchor = match self.textarea.selection_range() {
+            Some((start, end)) if start == cursor => end.0,
+            Some((start, _)) => start.0,
+            None => cursor.0,
+        };
+        self.textarea.cancel_selection();
+        self.mode = EditorMode::VisualLine;
+    }
+
+    /// Rows covered by line-wise visual mode (first, last)
+    pub fn line_selection(&self) -> Option<(usize, usize)> {
+        if self.mode != EditorMode::VisualLine {
+            return None;
+        }
+        let row = self.textarea.cursor().0;
+        Some((row.min(self.visual_anchor), row.max(self.visual_anchor)))
+    }
+
+    /// Copy whole lines into the yank buffer, ending in a newline so pasting them is line-wise
+    /// Returns the text copied.
+    pub fn yank_lines(&mut self, first: usize, last: usize) -> String {
+        let mut text = self.textarea.lines()[first..=last].join("\n");
+        text.push('\n');
+        self.textarea.set_yank_text(text.clone());
+        text
+    }
+
+    /// Delete whole lines (keeping undo history) and yank them
+    /// The cursor moves to the start of the line that took their place. Returns the text deleted.
+    pub fn delete_lines(&mut self, first: usize, last: usize) -> String {
+        let text = self.yank_lines(first, last);
+        let line_end = |textarea: &TextArea, row: usize| textarea.lines()[row].chars().count();
+        let last_row = self.textarea.lines().len() - 1;
+
+        self.textarea.cancel_selection();
+        if last < last_row {
+            // Up to the start of the next line
+            self.textarea.move_cursor(CursorMove::Jump(first as u16, 0));
+            self.textarea.start_selection();
+            self.textarea
+                .move_cursor(CursorMove::Jump((last + 1) as u16, 0));
+        } else if first > 0 {
+            // The last lines: take the newline before them instead
+            let end = line_end(&self.textarea, first - 1);
+            self.textarea
+                .move_cursor(CursorMove::Jump((first - 1) as u16, end as u16));
+            self.textarea.start_selection();
+            let end = line_end(&self.textarea, last);
+            self.textarea
+                .move_cursor(CursorMove::Jump(last as u16, end as u16));
+        } else {
+            self.textarea.move_cursor(CursorMove::Jump(0, 0));
+            self.textarea.start_selection();
+            let end = line_end(&self.textarea, last);
+            self.textarea
+                .move_cursor(CursorMove::Jump(last as u16, end as u16));
+        }
+        self.textarea.cut();
+        self.textarea.set_yank_text(text.clone());
+
+        let row = first.min(self.textarea.lines().len() - 1);
+        self.textarea.move_cursor(CursorMove::Jump(row as u16, 0));
+        self.mode = EditorMode::Normal;
+        text
+    }
+
+    /// Paste whole lines below the cursor's line, leaving the cursor on the first of them
+    pub fn paste_lines_below(&mut self, text: &str) {
+        let lines = text.strip_suffix('\n').unwrap_or(text);
+        let row = self.textarea.cursor().0;
+        self.textarea.move_cursor(CursorMove::End);
+        self.textarea.insert_newline();
+        self.textarea.insert_str(lines);
         self.textarea
-            .set_cursor_line_style(ratatui::style::Style::default());
-        self.textarea.set_line_number_style(
-            ratatui::style::Style::default().fg(ratatui::style::Color::DarkGray),
+            .move_cursor(CursorMove::Jump((row + 1) as u16, 0));
+    }
+
+    /// Number of open buffers
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Index of the active buffer
+    pub fn active_buffer(&self) -> usize {
+        self.active_buffer
+    }
+
+    /// Open an empty buffer after the active one and switch to it
+    pub fn new_buffer(&mut self) {
+        self.buffers
+            .insert(self.active_buffer + 1, QueryBuffer::default());
+        self.switch_buffer(self.active_buffer + 1);
+    }
+
+    /// Switch to the next buffer (wraps around)
+    pub fn next_buffer(&mut self) {
+        self.switch_buffer((self.active_buffer + 1) % self.buffers.len());
+    }
+
+    /// Switch to the previous buffer (wraps around)
+    pub fn prev_buffer(&mut self) {
+        let count = self.buffers.len();
+        self.switch_buffer((self.active_buffer + count - 1) % count);
+    }
+
+    /// Close the active buffer, or clear it if it's the only one
+    pub fn close_buffer(&mut self) {
+        if self.buffers.len() == 1 {
+            self.clear();
+            self.pack_context = None;
+            return;
+        }
+        self.buffers.remove(self.active_buffer);
+        let next = self.active_buffer.min(self.buffers.len() - 1);
+        self.load_buffer(next);
+    }
+
+    /// Point every buffer loaded from a renamed pack at its new file and name
+    pub fn rename_pack(&mut self, old_path: &Path, new_path: &Path, name: &str) {
+        let contexts = self.pack_context.iter_mut().chain(
+            self.buffers
+                .iter_mut()
+                .filter_map(|b| b.pack_context.as_mut()),
         );
+        for ctx in contexts.filter(|ctx| ctx.pack_path == old_path) {
+            ctx.pack_name = name.to_string();
+            ctx.pack_path = new_path.to_path_buf();
+            ctx.pack.name = name.to_string();
+            ctx.queries = ctx.pack.get_queries();
+            ctx.content_hash = QueryPack::content_hash(new_path);
+        }
+    }
+
+    /// Park the active buffer and load another
+    fn switch_buffer(&mut self, idx: usize) {
+        if idx == self.active_buffer || idx >= self.buffers.len() {
+            return;
+        }
+        self.buffers[self.active_buffer] = QueryBuffer {
+            textarea: std::mem::replace(&mut self.textarea, new_textarea(Vec::new())),
+            pack_context: self.pack_context.take(),
+            folded: std::mem::take(&mut self.folded),
+            undo_snapshots: std::mem::take(&mut self.undo_snapshots),
+        };
+        self.load_buffer(idx);
+    }
+
+    /// Move a parked buffer into the editor
+    fn load_buffer(&mut self, idx: usize) {
+        let buffer = std::mem::take(&mut self.buffers[idx]);
+        self.textarea = buffer.textarea;
+        self.pack_context = buffer.pack_context;
+        self.folded = buffer.folded;
+        self.active_buffer = idx;
+        self.load_panel = None;
+        self.undo_snapshots = buffer.undo_snapshots;
+        if self.undo_snapshots.is_empty() {
+            self.reset_undo_snapshots();
+        }
     }
 }
 
+/// Edit the textarea from `old` to `new` by replacing only the span that differs, so the
+/// change is a single insertion or deletion in the undo history where possible
+fn replace_changed_text(textarea: &mut TextArea<'static>, old: &str, new: &str) {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // (row, column) of a character offset into `old`
+    let position = |offset: usize| {
+        let before = &old[..offset];
+        let row = before.iter().filter(|&&c| c == '\n').count();
+        let col = before.iter().rev().take_while(|&&c| c != '\n').count();
+        CursorMove::Jump(row as u16, col as u16)
+    };
+
+    textarea.cancel_selection();
+    textarea.move_cursor(position(prefix));
+    if prefix < old.len() - suffix {
+        textarea.start_selection();
+        textarea.move_cursor(position(old.len() - suffix));
+    }
+    let inserted: String = new[prefix..new.len() - suffix].iter().collect();
+    textarea.insert_str(inserted);
+}
+
+/// Create an editor textarea with the Query tab's styling
+fn new_textarea(lines: Vec<String>) -> TextArea<'static> {
+    let mut textarea = TextArea::from(lines);
+    textarea.set_cursor_line_style(ratatui::style::Style::default());
+    textarea.set_line_number_style(
+        ratatui::style::Style::default().fg(ratatui::style::Color::DarkGray),
+    );
+    textarea
+}
+
 impl Default for QueryModel {
     fn default() -> Self {
         Self::new()
@@ -188,7 +970,7 @@ impl LoadPanelState {
                     JobStatus::Running => 0,
                     JobStatus::Queued => 1,
                     JobStatus::Failed => 2,
-                    JobStatus::Completed => 3,
+                    JobStatus::Completed | JobStatus::CompletedWithWarnings => 3,
                 });
             }
             LoadPanelSort::Alphabetical => {
@@ -206,3 +988,149 @@ impl LoadPanelState {
         indices
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_text_keeps_undo_history() {
+        let mut model = QueryModel::default();
+        model.set_text("T | where x > 1 | take 10".to_string());
+        assert!(model.format_text());
+        assert_eq!(model.get_text(), "T\n| where x > 1\n| take 10");
+        assert_eq!(model.textarea.cursor(), (0, 0));
+        assert!(!model.format_text());
+
+        while model.textarea.undo() {}
+        assert_eq!(model.get_text(), "T | where x > 1 | take 10");
+    }
+
+    #[test]
+    fn test_line_operators() {
+        let mut model = QueryModel::default();
+        model.set_text("a\nb\nc\nd".to_string());
+        model.textarea.move_cursor(CursorMove::Jump(1, 0));
+
+        // `2dd`: the first `d` waits, the second deletes two lines
+        model.push_count_digit(2);
+        assert_eq!(model.line_operator(LineOperator::Delete), None);
+        let (first, last) = model.line_operator(LineOperator::Delete).unwrap();
+        assert_eq!(model.delete_lines(first, last), "b\nc\n");
+        assert_eq!(model.get_text(), "a\nd");
+        assert_eq!(model.textarea.cursor(), (1, 0));
+
+        // `yy` then `p` duplicates the line below
+        model.line_operator(LineOperator::Yank);
+        let (first, last) = model.line_operator(LineOperator::Yank).unwrap();
+        let yanked = model.yank_lines(first, last);
+        model.paste_lines_below(&yanked);
+        assert_eq!(model.get_text(), "a\nd\nd");
+
+        // `V` on the last lines, extended upwards, deletes back to the previous line's end
+        model.textarea.move_cursor(CursorMove::Jump(2, 0));
+        model.start_line_selection();
+        model.textarea.move_cursor(CursorMove::Up);
+        assert_eq!(model.line_selection(), Some((1, 2)));
+        model.delete_lines(1, 2);
+        assert_eq!(model.get_text(), "a");
+        assert_eq!(model.mode, EditorMode::Normal);
+
+        model.textarea.undo();
+        assert_eq!(model.get_text(), "a\nd\nd");
+
+        // A different operator key restarts the wait; taking the count drops it
+        model.push_count_digit(3);
+        model.line_operator(LineOperator::Delete);
+        assert_eq!(model.line_operator(LineOperator::Yank), None);
+        assert_eq!(model.take_pending(), Some(3));
+        assert_eq!(model.line_operator(LineOperator::Yank), None);
+    }
+
+    #[test]
+    fn test_draft_restores_buffers_and_undo() {
+        let mut model = QueryModel::default();
+        model.set_text("SigninLogs".to_string());
+        model.textarea.move_cursor(CursorMove::End);
+        model.textarea.insert_str("\n| take 10");
+        model.record_undo_snapshot();
+        model.textarea.undo();
+        model.record_undo_snapshot();
+        model.textarea.redo();
+        model.record_undo_snapshot();
+        model.textarea.move_cursor(CursorMove::Jump(0, 0));
+        model.textarea.insert_str("// draft\n");
+        model.new_buffer();
+        model.set_text("AuditLogs".to_string());
+        model.prev_buffer();
+
+        let draft = model.to_draft();
+        assert_eq!(
+            draft.buffers,
+            ["// draft\nSigninLogs\n| take 10", "AuditLogs"]
+        );
+        assert_eq!(draft.undo, ["SigninLogs", "SigninLogs\n| take 10"]);
+        let json = serde_json::to_string(&draft).unwrap();
+
+        let mut restored = QueryModel::default();
+        restored.restore_draft(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.get_text(), "// draft\nSigninLogs\n| take 10");
+        assert_eq!(restored.textarea.cursor(), draft.cursor);
+        assert_eq!(restored.buffer_count(), 2);
+
+        // Each saved step is one undo
+        assert!(restored.textarea.undo());
+        assert_eq!(restored.get_text(), "SigninLogs\n| take 10");
+        assert!(restored.textarea.undo());
+        assert_eq!(restored.get_text(), "SigninLogs");
+        assert!(!restored.textarea.undo());
+
+        restored.next_buffer();
+        assert_eq!(restored.get_text(), "AuditLogs");
+    }
+
+    #[test]
+    fn test_fold_regions() {
+        let lines: Vec<String> = [
+            "// #region Logons",
+            "SecurityEvent",
+            "//#region Filters",
+            "| where EventID == 4624",
+            "//#endregion",
+            "//#endregion",
+            "//#region unterminated",
+            "| take 10",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            fold_regions(&lines),
+            vec![
+                FoldRegion { start: 0, end: 5 },
+                FoldRegion { start: 2, end: 4 },
+            ]
+        );
+        assert_eq!(region_marker("  // #region x"), Some(RegionMarker::Start));
+        assert_eq!(region_marker("// region"), None);
+
+        let mut model = QueryModel::default();
+        model.set_text(lines.join("\n"));
+        model.textarea.move_cursor(CursorMove::Jump(3, 0));
+        assert!(model.toggle_fold());
+        assert_eq!(model.textarea.cursor(), (2, 0));
+        assert_eq!(model.visible_folds(), vec![FoldRegion { start: 2, end: 4 }]);
+
+        // Moving down from the folded line skips its body
+        model.textarea.move_cursor(CursorMove::Down);
+        model.skip_folds(true);
+        assert_eq!(model.textarea.cursor().0, 5);
+        model.textarea.move_cursor(CursorMove::Up);
+        model.skip_folds(false);
+        assert_eq!(model.textarea.cursor().0, 2);
+
+        model.textarea.move_cursor(CursorMove::Jump(7, 0));
+        assert!(!model.toggle_fold());
+    }
+}
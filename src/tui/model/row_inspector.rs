@@ -0,0 +1,255 @@
+use super::query::{PreviewOutcome, ResultPreview};
+use crate::query_job::parse_dynamic_value;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// One result row at a time, with `dynamic` cells shown as collapsible JSON trees
+#[derive(Debug, Clone)]
+pub struct RowInspector {
+    pub title: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// Total rows in the output (only the previewed rows can be inspected)
+    pub total_rows: usize,
+    /// Index of the row shown
+    pub row: usize,
+    /// Index into `lines()` of the highlighted line
+    pub cursor: usize,
+    /// Paths of expanded objects and arrays
+    expanded: HashSet<String>,
+}
+
+/// A line of the tree: a column, or a field or item of a dynamic value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeLine {
+    pub depth: usize,
+    /// Column name, object key, or `[index]`
+    pub key: String,
+    /// Scalar value, or a summary such as `{3 keys}` for objects and arrays
+    pub value: String,
+    /// Object or array that can be expanded
+    pub expandable: bool,
+    pub expanded: bool,
+    path: String,
+}
+
+impl RowInspector {
+    /// Inspect the rows of a result preview (None if it has no rows)
+    pub fn new(title: String, preview: &ResultPreview) -> Option<Self> {
+        let PreviewOutcome::Rows {
+            columns,
+            rows,
+            total_rows,
+        } = &preview.outcome
+        else {
+            return None;
+        };
+        if rows.is_empty() {
+            return None;
+        }
+        let mut inspector = Self {
+            title,
+            columns: columns.clone(),
+            rows: rows.clone(),
+            total_rows: *total_rows,
+            row: 0,
+            cursor: 0,
+            expanded: HashSet::new(),
+        };
+        inspector.reset_expansion();
+        Some(inspector)
+    }
+
+    /// Number of rows that can be inspected
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Lines of the current row's tree, as currently expanded
+    pub fn lines(&self) -> Vec<TreeLine> {
+        let mut lines = Vec::new();
+        let Some(row) = self.rows.get(self.row) else {
+            return lines;
+        };
+        for (column, cell) in self.columns.iter().zip(row) {
+            match dynamic_value(cell) {
+                Some(value) => self.push_value(&mut lines, 0, column.clone(), column, &value),
+                None => lines.push(TreeLine {
+                    depth: 0,
+                    key: column.clone(),
+                    value: cell.clone(),
+                    expandable: false,
+                    expanded: false,
+                    path: column.clone(),
+                }),
+            }
+        }
+        lines
+    }
+
+    fn push_value(
+        &self,
+        lines: &mut Vec<TreeLine>,
+        depth: usize,
+        key: String,
+        path: &str,
+        value: &Value,
+    ) {
+        let children: Vec<(String, &Value)> = match value {
+            Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (format!("[{}]", i), v))
+                .collect(),
+            scalar => {
+                lines.push(TreeLine {
+                    depth,
+                    key,
+                    value: scalar.to_string(),
+                    expandable: false,
+                    expanded: false,
+                    path: path.to_string(),
+                });
+                return;
+            }
+        };
+
+        let expanded = self.expanded.contains(path);
+        let summary = match value {
+            Value::Object(_) => format!("{{{} keys}}", children.len()),
+            _ => format!("[{} items]", children.len()),
+        };
+        lines.push(TreeLine {
+            depth,
+            key,
+            value: summary,
+            expandable: !children.is_empty(),
+            expanded,
+            path: path.to_string(),
+        });
+        if expanded {
+            for (child_key, child) in children {
+                let child_path = format!("{}/{}", path, child_key);
+                self.push_value(lines, depth + 1, child_key, &child_path, child);
+            }
+        }
+    }
+
+    /// Move the highlighted line
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.lines().len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Expand or collapse the highlighted object or array
+    pub fn toggle(&mut self) {
+        self.set_expanded(None);
+    }
+
+    /// Expand (true) or collapse (false) the highlighted object or array
+    /// Collapsing a field moves the cursor to its parent, like a file tree.
+    pub fn expand(&mut self, expand: bool) {
+        self.set_expanded(Some(expand));
+    }
+
+    fn set_expanded(&mut self, expand: Option<bool>) {
+        let lines = self.lines();
+        let Some(line) = lines.get(self.cursor) else {
+            return;
+        };
+        let expand = expand.unwrap_or(!line.expanded);
+        if line.expandable && line.expanded != expand {
+            if expand {
+                self.expanded.insert(line.path.clone());
+            } else {
+                self.expanded.remove(&line.path);
+            }
+        } else if !expand && line.depth > 0 {
+            // Jump to the enclosing object or array
+            if let Some(parent) = lines[..self.cursor]
+                .iter()
+                .rposition(|l| l.depth < line.depth)
+            {
+                self.cursor = parent;
+            }
+        }
+    }
+
+    /// Show the next (1) or previous (-1) row
+    pub fn step_row(&mut self, delta: isize) {
+        let last = self.rows.len().saturating_sub(1);
+        let row = self.row.saturating_add_signed(delta).min(last);
+        if row != self.row {
+            self.row = row;
+            self.cursor = 0;
+            self.reset_expansion();
+        }
+    }
+
+    /// Expand the top level of each dynamic column of the current row
+    fn reset_expansion(&mut self) {
+        self.expanded.clear();
+        if let Some(row) = self.rows.get(self.row) {
+            for (column, cell) in self.columns.iter().zip(row) {
+                if dynamic_value(cell).is_some_and(|v| v.is_object() || v.is_array()) {
+                    self.expanded.insert(column.clone());
+                }
+            }
+        }
+    }
+}
+
+/// A cell's JSON value if it holds a dynamic object or array (possibly as escaped JSON text)
+fn dynamic_value(cell: &str) -> Option<Value> {
+    let trimmed = cell.trim_start();
+    if !(trimmed.starts_with('{') || trimmed.starts_with('[') || trimmed.starts_with('"')) {
+        return None;
+    }
+    let value = parse_dynamic_value(&Value::String(cell.to_string()));
+    (value.is_object() || value.is_array()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dynamic_cells_become_trees() {
+        let preview = ResultPreview {
+            job_id: 1,
+            workspace_name: "prod".to_string(),
+            outcome: PreviewOutcome::Rows {
+                columns: vec!["Account".to_string(), "EventData".to_string()],
+                rows: vec![vec![
+                    "alice".to_string(),
+                    r#"{"Data":"{\"Port\":445,\"Hosts\":[\"a\",\"b\"]}","Level":4}"#.to_string(),
+                ]],
+                total_rows: 1,
+            },
+        };
+        let mut inspector = RowInspector::new("prod".to_string(), &preview).unwrap();
+
+        let keys = |inspector: &RowInspector| -> Vec<String> {
+            inspector.lines().into_iter().map(|l| l.key).collect()
+        };
+        // Dynamic columns start expanded one level; nested JSON strings are parsed
+        assert_eq!(keys(&inspector), ["Account", "EventData", "Data", "Level"]);
+        assert_eq!(inspector.lines()[2].value, "{2 keys}");
+
+        inspector.move_cursor(2);
+        inspector.toggle();
+        assert_eq!(
+            keys(&inspector),
+            ["Account", "EventData", "Data", "Hosts", "Port", "Level"]
+        );
+        assert_eq!(inspector.lines()[4].value, "445");
+
+        // Collapsing a leaf jumps to its parent
+        inspector.move_cursor(2);
+        inspector.expand(false);
+        assert_eq!(inspector.cursor, 2);
+        inspector.expand(false);
+        assert_eq!(keys(&inspector), ["Account", "EventData", "Data", "Level"]);
+    }
+}
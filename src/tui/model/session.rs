@@ -1,4 +1,9 @@
+use crate::file_lock::FileLock;
+use crate::retention::CleanPlan;
+use crate::tui::model::ListJump;
 use ratatui::{style::Color, widgets::TableState};
+use std::collections::HashSet;
+use std::sync::Arc;
 
 /// Session state in the UI
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -48,6 +53,65 @@ pub struct SessionEntry {
     pub state: SessionState,
     pub last_saved: Option<String>, // Timestamp or "Never" for unsaved
     pub created_from_pack: Option<String>, // Pack origin if any
+    pub disk_usage: Option<u64>,    // Session file plus job output, in bytes
+    pub tags: Vec<String>,
+    pub description: String,
+}
+
+impl SessionEntry {
+    /// Check if every search term appears (ignoring case) in the name, tags, pack origin or
+    /// description
+    pub fn matches(&self, search: &str) -> bool {
+        let haystack = format!(
+            "{}\n{}\n{}\n{}",
+            self.name,
+            self.tags.join("\n"),
+            self.created_from_pack.as_deref().unwrap_or(""),
+            self.description
+        )
+        .to_lowercase();
+        search
+            .split_whitespace()
+            .all(|term| haystack.contains(&term.to_lowercase()))
+    }
+}
+
+/// Tags and description being edited for a session (while the details popup is open)
+#[derive(Debug, Clone)]
+pub struct SessionDetailsInput {
+    /// Session being edited
+    pub session: String,
+    /// Comma-separated tags
+    pub tags: String,
+    pub description: String,
+    /// Typing goes to the description rather than the tags
+    pub editing_description: bool,
+}
+
+impl SessionDetailsInput {
+    /// The field being typed into
+    pub fn focused_mut(&mut self) -> &mut String {
+        if self.editing_description {
+            &mut self.description
+        } else {
+            &mut self.tags
+        }
+    }
+}
+
+/// Split comma-separated tag input, dropping empty and repeated tags
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+    {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
 }
 
 /// Sessions tab state
@@ -63,8 +127,28 @@ pub struct SessionModel {
     pub has_unsaved_changes: bool,
     /// Input buffer for new session name
     pub name_input: Option<String>,
+    /// New name for the selected session (while the rename popup is open)
+    pub rename_input: Option<String>,
     /// Query pack that created the current session (if any)
     pub current_pack_origin: Option<String>,
+    /// Tags of the current session (kept when it is saved)
+    pub current_tags: Vec<String>,
+    /// Description of the current session (kept when it is saved)
+    pub current_description: String,
+    /// Tags and description being edited (while the details popup is open)
+    pub details_input: Option<SessionDetailsInput>,
+    /// Terms the session list is filtered by (empty shows every session)
+    pub search: String,
+    /// Search input buffer (while the search popup is open)
+    pub search_input: Option<String>,
+    /// Names of sessions marked for bulk delete and export
+    pub marked: HashSet<String>,
+    /// Deletion awaiting confirmation (when the confirm popup is open)
+    pub pending_delete: Option<CleanPlan>,
+    /// Lock on the current session's file, so other instances don't overwrite it
+    pub current_lock: Option<Arc<FileLock>>,
+    /// The current session was opened read-only because another instance holds its lock
+    pub read_only: bool,
 }
 
 impl SessionModel {
@@ -76,7 +160,17 @@ impl SessionModel {
             current_session_name: None,
             has_unsaved_changes: false,
             name_input: None,
+            rename_input: None,
             current_pack_origin: None,
+            current_tags: Vec::new(),
+            current_description: String::new(),
+            details_input: None,
+            search: String::new(),
+            search_input: None,
+            marked: HashSet::new(),
+            pending_delete: None,
+            current_lock: None,
+            read_only: false,
         }
     }
 
@@ -92,8 +186,35 @@ impl SessionModel {
         self.refresh_session_states();
     }
 
-    /// Set the current session name
+    /// Set the lock held on the current session (`read_only` when another instance holds it)
+    pub fn set_lock(&mut self, lock: Option<FileLock>, read_only: bool) {
+        self.current_lock = lock.map(Arc::new);
+        self.read_only = read_only;
+    }
+
+    /// Why the current session can't be saved in place, if it can't
+    pub fn save_blocker(&self) -> Option<String> {
+        let name = self.current_session_name.as_ref()?;
+        if self.read_only {
+            return Some(format!(
+                "Session '{}' is open read-only (another instance holds it). Save it under a new name instead",
+                name
+            ));
+        }
+        match &self.current_lock {
+            Some(lock) if !lock.is_held() => Some(format!(
+                "Session '{}' was taken over by another instance. Save it under a new name to keep these changes",
+                name
+            )),
+            _ => None,
+        }
+    }
+
+    /// Set the current session name (switching sessions releases the old one's lock)
     pub fn set_current_session(&mut self, name: Option<String>) {
+        if name != self.current_session_name {
+            self.set_lock(None, false);
+        }
         self.current_session_name = name;
         self.has_unsaved_changes = false;
         self.refresh_session_states();
@@ -104,6 +225,25 @@ impl SessionModel {
         self.current_pack_origin = pack_path;
     }
 
+    /// Set the tags and description of the current session
+    pub fn set_current_details(&mut self, tags: Vec<String>, description: String) {
+        self.current_tags = tags;
+        self.current_description = description;
+    }
+
+    /// Follow a session rename (current session, marks and list entry keep their state)
+    pub fn apply_rename(&mut self, old_name: &str, new_name: &str) {
+        if self.is_current(old_name) {
+            self.current_session_name = Some(new_name.to_string());
+        }
+        if self.marked.remove(old_name) {
+            self.marked.insert(new_name.to_string());
+        }
+        for entry in self.sessions.iter_mut().filter(|s| s.name == old_name) {
+            entry.name = new_name.to_string();
+        }
+    }
+
     /// Check if a session name is the current session
     fn is_current(&self, name: &str) -> bool {
         self.current_session_name.as_deref() == Some(name)
@@ -142,6 +282,9 @@ impl SessionModel {
                     state: SessionState::CurrentNeverSaved,
                     last_saved: None,
                     created_from_pack: self.current_pack_origin.clone(),
+                    disk_usage: None,
+                    tags: self.current_tags.clone(),
+                    description: self.current_description.clone(),
                 });
             }
         }
@@ -155,15 +298,28 @@ impl SessionModel {
             let session = crate::session::Session::load(&name).ok();
             let last_saved = session.as_ref().map(|s| s.last_saved.clone());
             let created_from_pack = session.as_ref().and_then(|s| s.created_from_pack.clone());
+            let disk_usage = session
+                .as_ref()
+                .and_then(|s| crate::retention::SessionUsage::of(s).ok())
+                .map(|usage| usage.total_bytes());
+
+            let (tags, description) = session.map(|s| (s.tags, s.description)).unwrap_or_default();
 
             self.sessions.push(SessionEntry {
                 name,
                 state,
                 last_saved,
                 created_from_pack,
+                disk_usage,
+                tags,
+                description,
             });
         }
 
+        // Forget marks on sessions that no longer exist
+        let names: HashSet<&String> = self.sessions.iter().map(|s| &s.name).collect();
+        self.marked.retain(|name| names.contains(name));
+
         // Sort: current session first, then alphabetically
         self.sessions.sort_by(|a, b| {
             match (&a.state, &b.state) {
@@ -186,14 +342,76 @@ impl SessionModel {
         });
 
         // Restore selection if possible
-        if let Some(name) = selected_name {
-            if let Some(idx) = self.sessions.iter().position(|s| s.name == name) {
-                self.table_state.select(Some(idx));
-            } else if !self.sessions.is_empty() {
-                self.table_state.select(Some(0));
+        self.table_state.select(
+            selected_name.and_then(|name| self.sessions.iter().position(|s| s.name == name)),
+        );
+        self.keep_selection_visible();
+    }
+
+    /// Filter the session list by search terms (empty clears the search)
+    pub fn set_search(&mut self, search: String) {
+        self.search = search.trim().to_string();
+        self.keep_selection_visible();
+    }
+
+    /// Indices (into `sessions`) of the sessions shown under the current search
+    pub fn visible_indices(&self) -> Vec<usize> {
+        self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, session)| session.matches(&self.search))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Select the first visible session if nothing visible is selected
+    fn keep_selection_visible(&mut self) {
+        let visible = self.visible_indices();
+        if !self
+            .table_state
+            .selected()
+            .is_some_and(|i| visible.contains(&i))
+        {
+            self.table_state.select(visible.first().copied());
+        }
+    }
+
+    /// Navigate to the previous visible session
+    pub fn previous(&mut self) {
+        let visible = self.visible_indices();
+        if let Some(pos) = self
+            .table_state
+            .selected()
+            .and_then(|selected| visible.iter().position(|&i| i == selected))
+        {
+            if pos > 0 {
+                self.table_state.select(Some(visible[pos - 1]));
+            }
+        } else {
+            self.table_state.select(visible.first().copied());
+        }
+    }
+
+    /// Jump through the visible sessions by a page or to either end
+    pub fn jump(&mut self, jump: ListJump) {
+        if let Some(idx) = jump.target(&self.visible_indices(), self.table_state.selected()) {
+            self.table_state.select(Some(idx));
+        }
+    }
+
+    /// Navigate to the next visible session
+    pub fn next(&mut self) {
+        let visible = self.visible_indices();
+        if let Some(pos) = self
+            .table_state
+            .selected()
+            .and_then(|selected| visible.iter().position(|&i| i == selected))
+        {
+            if pos + 1 < visible.len() {
+                self.table_state.select(Some(visible[pos + 1]));
             }
-        } else if !self.sessions.is_empty() {
-            self.table_state.select(Some(0));
+        } else {
+            self.table_state.select(visible.first().copied());
         }
     }
 
@@ -236,6 +454,47 @@ impl SessionModel {
         });
     }
 
+    /// Toggle the bulk delete mark on the selected session
+    pub fn toggle_mark_selected(&mut self) {
+        if let Some(name) = self.get_selected_session().map(|s| s.name.clone()) {
+            if !self.marked.remove(&name) {
+                self.marked.insert(name);
+            }
+        }
+    }
+
+    /// Mark every saved session shown under the current search
+    pub fn mark_all(&mut self) {
+        self.marked = self
+            .sessions
+            .iter()
+            .filter(|session| session.last_saved.is_some() && session.matches(&self.search))
+            .map(|session| session.name.clone())
+            .collect();
+    }
+
+    /// Names of saved sessions a bulk action applies to (the marked ones, or else the selected one)
+    pub fn bulk_targets(&self) -> Vec<String> {
+        self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(idx, session)| {
+                if self.marked.is_empty() {
+                    self.table_state.selected() == Some(*idx)
+                } else {
+                    self.marked.contains(&session.name)
+                }
+            })
+            .filter(|(_, session)| session.last_saved.is_some())
+            .map(|(_, session)| session.name.clone())
+            .collect()
+    }
+
+    /// Combined disk usage of all listed sessions
+    pub fn total_disk_usage(&self) -> u64 {
+        self.sessions.iter().filter_map(|s| s.disk_usage).sum()
+    }
+
     /// Get the currently selected session
     pub fn get_selected_session(&self) -> Option<&SessionEntry> {
         self.table_state
@@ -257,3 +516,54 @@ impl Default for SessionModel {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, tags: &[&str], pack: Option<&str>) -> SessionEntry {
+        SessionEntry {
+            name: name.to_string(),
+            state: SessionState::Loadable,
+            last_saved: Some("2024-05-01T10:00:00+00:00".to_string()),
+            created_from_pack: pack.map(str::to_string),
+            disk_usage: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_session_search() {
+        assert_eq!(
+            parse_tags(" IR-2024-17, phishing,,Phishing "),
+            vec!["IR-2024-17", "phishing"]
+        );
+
+        let mut model = SessionModel::new();
+        model.sessions = vec![
+            entry("session_20240501", &["IR-2024-17", "phishing"], None),
+            entry("session_20240502", &[], Some("security/failed-auth.yaml")),
+            entry("daily", &["phishing"], Some("hunts/daily.yaml")),
+        ];
+        model.table_state.select(Some(1));
+
+        model.set_search("PHISHING".to_string());
+        assert_eq!(model.visible_indices(), vec![0, 2]);
+        // The hidden selection moves to the first match
+        assert_eq!(model.table_state.selected(), Some(0));
+        model.next();
+        assert_eq!(model.table_state.selected(), Some(2));
+        model.next();
+        assert_eq!(model.table_state.selected(), Some(2));
+
+        // Every term must match somewhere
+        model.set_search("phishing hunts".to_string());
+        assert_eq!(model.visible_indices(), vec![2]);
+        model.set_search("failed-auth".to_string());
+        assert_eq!(model.visible_indices(), vec![1]);
+
+        model.set_search(String::new());
+        assert_eq!(model.visible_indices().len(), 3);
+    }
+}
@@ -1,5 +1,47 @@
+use crate::config::SubscriptionFilter;
+use crate::tui::model::query::RunFormats;
 use ratatui::widgets::ListState;
 
+/// Default seconds between watch mode polls
+pub const DEFAULT_WATCH_INTERVAL_SECS: u64 = 60;
+
+/// Default limit on queries running at once during TUI pack runs
+pub const DEFAULT_MAX_CONCURRENCY: usize = 15;
+/// Default for queries running at once against the same workspace
+pub const DEFAULT_WORKSPACE_CONCURRENCY: usize = 1;
+
+/// What to do when a new job repeats one already in the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateJobs {
+    /// Run duplicates without comment
+    Allow,
+    /// Run duplicates, but say how many there were
+    #[default]
+    Warn,
+    /// Don't create duplicate jobs
+    Skip,
+}
+
+impl DuplicateJobs {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateJobs::Allow => "allow",
+            DuplicateJobs::Warn => "warn",
+            DuplicateJobs::Skip => "skip",
+        }
+    }
+
+    /// Next policy in the cycle: allow -> warn -> skip -> allow
+    pub fn next(self) -> Self {
+        match self {
+            DuplicateJobs::Allow => DuplicateJobs::Warn,
+            DuplicateJobs::Warn => DuplicateJobs::Skip,
+            DuplicateJobs::Skip => DuplicateJobs::Allow,
+        }
+    }
+}
+
 /// Settings state
 #[derive(Debug, Clone)]
 pub struct SettingsModel {
@@ -17,7 +59,37 @@ pub struct SettingsModel {
     pub export_json: bool,
     /// Parse nested dynamic fields into JSON objects (only for JSON export)
     pub parse_dynamics: bool,
-    /// Currently selected setting index (0-6)
+    /// Workspace discovery cache TTL in seconds (0 disables the cache)
+    pub workspace_cache_ttl_secs: u64,
+    /// Subscriptions to include/exclude during workspace discovery (persisted in config)
+    pub subscription_filter: SubscriptionFilter,
+    /// Seconds between polls in watch (live tail) mode
+    pub watch_interval_secs: u64,
+    /// Maximum rows written per job, 0 = unlimited (persisted in config)
+    pub max_rows: usize,
+    /// Run a follow-up query per job estimating the billed data scanned
+    pub estimate_cost: bool,
+    /// Write a metadata sidecar (.meta.json) next to each CSV export
+    pub csv_metadata: bool,
+    /// SQLite database results are appended to (empty = disabled)
+    pub sqlite_path: String,
+    /// Handling of jobs that repeat a query, workspace and settings already in the session
+    pub duplicate_jobs: DuplicateJobs,
+    /// Keep fetched rows when pagination fails instead of failing the job
+    pub keep_partial_results: bool,
+    /// Leave workspaces on a failure streak out of query, watch and pack runs
+    pub skip_failing_workspaces: bool,
+    /// Write the indicators found in a finished run's results to IOC files
+    pub extract_iocs: bool,
+    /// Don't write output files for jobs that return no rows
+    pub skip_empty_output: bool,
+    /// Consecutive failures before a workspace counts as failing (from config; not editable here)
+    pub failure_streak: u32,
+    /// Maximum queries running at once during pack runs (from config; not editable here)
+    pub max_concurrency: usize,
+    /// Maximum queries running at once against one workspace (from config; not editable here)
+    pub max_concurrency_per_workspace: usize,
+    /// Currently selected setting index (0-18)
     pub selected_index: usize,
     /// List state for scrolling
     pub list_state: ListState,
@@ -39,12 +111,63 @@ impl SettingsModel {
             export_csv: true,     // CSV enabled by default
             export_json: false,   // JSON disabled by default
             parse_dynamics: true, // Parse dynamics enabled by default
+            workspace_cache_ttl_secs: crate::workspace::DEFAULT_WORKSPACE_CACHE_TTL_SECS,
+            subscription_filter: SubscriptionFilter::default(),
+            watch_interval_secs: DEFAULT_WATCH_INTERVAL_SECS,
+            max_rows: 0,
+            estimate_cost: false,
+            csv_metadata: false,
+            sqlite_path: String::new(),
+            duplicate_jobs: DuplicateJobs::default(),
+            keep_partial_results: false,
+            skip_failing_workspaces: false,
+            extract_iocs: false,
+            skip_empty_output: false,
+            failure_streak: crate::workspace_health::DEFAULT_FAILURE_STREAK,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            max_concurrency_per_workspace: DEFAULT_WORKSPACE_CONCURRENCY,
             selected_index: 0,
             list_state,
             editing: None,
         }
     }
 
+    /// Export formats a new run starts with
+    pub fn run_formats(&self) -> RunFormats {
+        RunFormats {
+            export_csv: self.export_csv,
+            export_json: self.export_json,
+            parse_dynamics: self.parse_dynamics,
+        }
+    }
+
+    /// Start from the config file and environment instead of the built-in defaults
+    pub fn apply_config(&mut self, config: &crate::config::Config) {
+        if let Some(folder) = &config.output_folder {
+            self.output_folder = folder.display().to_string();
+        }
+        if let Some(secs) = config.query_timeout_secs {
+            self.query_timeout_secs = secs;
+        }
+        if let Some(count) = config.retry_count {
+            self.retry_count = count;
+        }
+        if let Some(secs) = config.validation_interval_secs {
+            self.validation_interval_secs = secs;
+        }
+        if let Some(max) = config.max_concurrency {
+            self.max_concurrency = max.max(1);
+        }
+        if let Some(max) = config.max_concurrency_per_workspace {
+            self.max_concurrency_per_workspace = max.max(1);
+        }
+        self.workspace_cache_ttl_secs = config.workspace_cache_ttl().as_secs();
+        if let Some(streak) = config.failure_streak {
+            self.failure_streak = streak;
+        }
+        self.max_rows = config.max_rows.unwrap_or(0);
+    }
+
     /// Get the currently selected setting's value as a string
     pub fn get_selected_value(&self) -> String {
         match self.selected_index {
@@ -70,13 +193,55 @@ impl SettingsModel {
                 "disabled"
             }
             .to_string(),
+            7 => self.workspace_cache_ttl_secs.to_string(),
+            8 => self.subscription_filter.to_text(),
+            9 => self.watch_interval_secs.to_string(),
+            10 => self.max_rows.to_string(),
+            11 => if self.estimate_cost {
+                "enabled"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            12 => if self.csv_metadata {
+                "enabled"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            13 => self.sqlite_path.clone(),
+            14 => self.duplicate_jobs.as_str().to_string(),
+            15 => if self.keep_partial_results {
+                "enabled"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            16 => if self.skip_failing_workspaces {
+                "enabled"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            17 => if self.extract_iocs {
+                "enabled"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            18 => if self.skip_empty_output {
+                "enabled"
+            } else {
+                "disabled"
+            }
+            .to_string(),
             _ => String::new(),
         }
     }
 
     /// Check if the selected setting is a toggle (boolean)
     pub fn is_selected_toggle(&self) -> bool {
-        matches!(self.selected_index, 4..=6)
+        matches!(self.selected_index, 4..=6 | 11 | 12 | 14..=18)
     }
 
     /// Get the currently selected setting's name
@@ -89,10 +254,62 @@ impl SettingsModel {
             4 => "Export CSV",
             5 => "Export JSON",
             6 => "Parse Dynamics (JSON only)",
+            7 => "Workspace Cache TTL (secs, 0 = off)",
+            8 => "Subscription Filter (comma-separated, !pattern to exclude)",
+            9 => "Watch Interval (secs)",
+            10 => "Max Rows per Job (0 = unlimited)",
+            11 => "Estimate Query Cost",
+            12 => "CSV Metadata Sidecar",
+            13 => "SQLite Database (empty = off, relative to output folder)",
+            14 => "Duplicate Jobs (allow / warn / skip)",
+            15 => "Keep Partial Results on Pagination Failure",
+            16 => "Skip Failing Workspaces",
+            17 => "Extract IOCs from Run Results",
+            18 => "Skip Output Files for Zero-Row Jobs",
             _ => "Unknown Setting",
         }
     }
 
+    /// Number of settings shown in the list
+    pub const COUNT: usize = 19;
+
+    /// Index of the workspace cache TTL setting (persisted to config on save)
+    pub const WORKSPACE_CACHE_TTL_INDEX: usize = 7;
+
+    /// Index of the subscription filter setting (persisted to config on save)
+    pub const SUBSCRIPTION_FILTER_INDEX: usize = 8;
+
+    /// Index of the row limit setting (persisted to config on save)
+    pub const MAX_ROWS_INDEX: usize = 10;
+
+    /// Index of the SQLite database setting
+    pub const SQLITE_PATH_INDEX: usize = 13;
+
+    /// Settings that an empty value clears (all subscriptions, unlimited rows, no database)
+    pub const CLEARABLE_INDICES: [usize; 3] = [
+        Self::SUBSCRIPTION_FILTER_INDEX,
+        Self::MAX_ROWS_INDEX,
+        Self::SQLITE_PATH_INDEX,
+    ];
+
+    /// Settings stored in the global config rather than the session
+    pub const CONFIG_INDICES: [usize; 3] = [
+        Self::WORKSPACE_CACHE_TTL_INDEX,
+        Self::SUBSCRIPTION_FILTER_INDEX,
+        Self::MAX_ROWS_INDEX,
+    ];
+
+    /// Row limit to apply to new jobs (None = unlimited)
+    pub fn max_rows_limit(&self) -> Option<usize> {
+        (self.max_rows > 0).then_some(self.max_rows)
+    }
+
+    /// SQLite database to append new jobs' results to (None = disabled)
+    pub fn sqlite_path_setting(&self) -> Option<std::path::PathBuf> {
+        let path = self.sqlite_path.trim();
+        (!path.is_empty()).then(|| path.into())
+    }
+
     /// Get all settings as display strings
     pub fn get_all_settings(&self) -> Vec<String> {
         vec![
@@ -115,6 +332,69 @@ impl SettingsModel {
                 "Parse Dynamics (JSON only): {}",
                 if self.parse_dynamics { "[X]" } else { "[ ]" }
             ),
+            format!(
+                "Workspace Cache TTL (secs, 0 = off): {}",
+                self.workspace_cache_ttl_secs
+            ),
+            format!(
+                "Subscription Filter: {}",
+                if self.subscription_filter.is_empty() {
+                    "(all subscriptions)".to_string()
+                } else {
+                    self.subscription_filter.to_text()
+                }
+            ),
+            format!("Watch Interval (secs): {}", self.watch_interval_secs),
+            format!(
+                "Max Rows per Job: {}",
+                if self.max_rows == 0 {
+                    "unlimited".to_string()
+                } else {
+                    self.max_rows.to_string()
+                }
+            ),
+            format!(
+                "Estimate Query Cost: {}",
+                if self.estimate_cost { "[X]" } else { "[ ]" }
+            ),
+            format!(
+                "CSV Metadata Sidecar: {}",
+                if self.csv_metadata { "[X]" } else { "[ ]" }
+            ),
+            format!(
+                "SQLite Database: {}",
+                if self.sqlite_path.is_empty() {
+                    "(off)"
+                } else {
+                    &self.sqlite_path
+                }
+            ),
+            format!("Duplicate Jobs: {}", self.duplicate_jobs.as_str()),
+            format!(
+                "Keep Partial Results: {}",
+                if self.keep_partial_results {
+                    "[X]"
+                } else {
+                    "[ ]"
+                }
+            ),
+            format!(
+                "Skip Failing Workspaces ({}+ failures in a row): {}",
+                self.failure_streak,
+                if self.skip_failing_workspaces {
+                    "[X]"
+                } else {
+                    "[ ]"
+                }
+            ),
+            format!(
+                "Extract IOCs: {}",
+                if self.extract_iocs { "[X]" } else { "[ ]" }
+            ),
+            format!(
+                "Skip Zero-Row Output: {}",
+                if self.skip_empty_output { "[X]" } else { "[ ]" }
+            ),
         ]
     }
 
@@ -124,6 +404,13 @@ impl SettingsModel {
             4 => self.export_csv = !self.export_csv,
             5 => self.export_json = !self.export_json,
             6 => self.parse_dynamics = !self.parse_dynamics,
+            11 => self.estimate_cost = !self.estimate_cost,
+            12 => self.csv_metadata = !self.csv_metadata,
+            14 => self.duplicate_jobs = self.duplicate_jobs.next(),
+            15 => self.keep_partial_results = !self.keep_partial_results,
+            16 => self.skip_failing_workspaces = !self.skip_failing_workspaces,
+            17 => self.extract_iocs = !self.extract_iocs,
+            18 => self.skip_empty_output = !self.skip_empty_output,
             _ => {}
         }
     }
@@ -157,10 +444,44 @@ impl SettingsModel {
                 }
                 Err(_) => Err("Invalid number format".to_string()),
             },
-            4..=6 => {
+            4..=6 | 11 | 12 | 14..=18 => {
                 // Toggle settings - should use toggle_selected() instead
                 Err("Use Space to toggle this setting".to_string())
             }
+            7 => match value.parse::<u64>() {
+                Ok(val) => {
+                    self.workspace_cache_ttl_secs = val;
+                    Ok(())
+                }
+                Err(_) => Err("Invalid number format".to_string()),
+            },
+            8 => {
+                self.subscription_filter = SubscriptionFilter::parse(&value);
+                Ok(())
+            }
+            9 => match value.parse::<u64>() {
+                Ok(0) => Err("Watch interval must be at least 1 second".to_string()),
+                Ok(val) => {
+                    self.watch_interval_secs = val;
+                    Ok(())
+                }
+                Err(_) => Err("Invalid number format".to_string()),
+            },
+            10 if value.trim().is_empty() => {
+                self.max_rows = 0;
+                Ok(())
+            }
+            10 => match value.parse::<usize>() {
+                Ok(val) => {
+                    self.max_rows = val;
+                    Ok(())
+                }
+                Err(_) => Err("Invalid number format".to_string()),
+            },
+            13 => {
+                self.sqlite_path = value.trim().to_string();
+                Ok(())
+            }
             _ => Err("Invalid setting index".to_string()),
         }
     }
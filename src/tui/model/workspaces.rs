@@ -1,5 +1,10 @@
+use crate::client::Subscription;
+use crate::config::SubscriptionFilter;
+use crate::saved_search::SavedSearch;
+use crate::tui::model::ListJump;
 use crate::workspace::Workspace;
-use ratatui::widgets::TableState;
+use crate::workspace_health::WorkspaceHealth;
+use ratatui::widgets::{ListState, TableState};
 
 /// Workspace with selection state
 #[derive(Debug, Clone)]
@@ -13,8 +18,159 @@ pub struct WorkspaceState {
 pub struct WorkspacesModel {
     /// List of workspaces with selection state
     pub workspaces: Vec<WorkspaceState>,
-    /// Table state for scrolling
+    /// Table state for scrolling (the selection is an index into `workspaces`)
     pub table_state: TableState,
+    /// Subscription filter picker (None = closed)
+    pub subscription_picker: Option<SubscriptionPicker>,
+    /// Saved search browser for one workspace (None = closed)
+    pub saved_searches: Option<SavedSearchBrowser>,
+    /// Current sort order of the table
+    pub sort: WorkspaceSort,
+    /// Indices into `workspaces` in display order (rebuilt on load and when the sort changes)
+    order: Vec<usize>,
+    /// Failure streaks and latency of the workspaces queried so far
+    pub health: WorkspaceHealth,
+    /// Write `health` to disk after jobs finish (`persist_workspace_health` in config)
+    persist_health: bool,
+    /// Selection from a session loaded before the workspace list, applied once it loads
+    pub pending_selection: Option<Vec<String>>,
+    /// A refresh from Azure is running in the background
+    pub refreshing: bool,
+}
+
+/// Sort order of the Workspaces table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkspaceSort {
+    /// Order returned by workspace discovery
+    #[default]
+    Discovery,
+    Name,
+    /// Subscription name, then workspace name
+    Subscription,
+    /// Selected workspaces first, each group by name
+    SelectedFirst,
+}
+
+impl WorkspaceSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkspaceSort::Discovery => "Discovery",
+            WorkspaceSort::Name => "Name",
+            WorkspaceSort::Subscription => "Subscription",
+            WorkspaceSort::SelectedFirst => "Selected first",
+        }
+    }
+
+    /// Next sort order in the cycle
+    pub fn next(&self) -> Self {
+        match self {
+            WorkspaceSort::Discovery => WorkspaceSort::Name,
+            WorkspaceSort::Name => WorkspaceSort::Subscription,
+            WorkspaceSort::Subscription => WorkspaceSort::SelectedFirst,
+            WorkspaceSort::SelectedFirst => WorkspaceSort::Discovery,
+        }
+    }
+}
+
+/// State for toggling subscriptions in/out of workspace discovery
+#[derive(Debug, Clone)]
+pub struct SubscriptionPicker {
+    /// All subscriptions visible to the current credentials
+    pub subscriptions: Vec<Subscription>,
+    /// Working copy of the filter (applied on confirm)
+    pub filter: SubscriptionFilter,
+    /// List state for scrolling
+    pub list_state: ListState,
+}
+
+impl SubscriptionPicker {
+    /// Create a picker for the given subscriptions and current filter
+    pub fn new(mut subscriptions: Vec<Subscription>, filter: SubscriptionFilter) -> Self {
+        subscriptions.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+        let mut list_state = ListState::default();
+        if !subscriptions.is_empty() {
+            list_state.select(Some(0));
+        }
+        Self {
+            subscriptions,
+            filter,
+            list_state,
+        }
+    }
+
+    /// Move selection up
+    pub fn previous(&mut self) {
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(Some(selected.saturating_sub(1)));
+    }
+
+    /// Move selection down
+    pub fn next(&mut self) {
+        let selected = self.list_state.selected().unwrap_or(0);
+        let max = self.subscriptions.len().saturating_sub(1);
+        self.list_state.select(Some((selected + 1).min(max)));
+    }
+
+    /// Toggle the selected subscription
+    pub fn toggle_selected(&mut self) {
+        if let Some(sub) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.subscriptions.get(i))
+        {
+            self.filter.toggle(&sub.subscription_id, &sub.display_name);
+        }
+    }
+
+    /// Count of subscriptions currently allowed by the working filter
+    pub fn enabled_count(&self) -> usize {
+        self.subscriptions
+            .iter()
+            .filter(|s| self.filter.allows(&s.subscription_id, &s.display_name))
+            .count()
+    }
+}
+
+/// Saved searches and functions of a workspace, for loading into the editor or exporting
+#[derive(Debug, Clone)]
+pub struct SavedSearchBrowser {
+    pub workspace: Workspace,
+    pub searches: Vec<SavedSearch>,
+    /// List state for scrolling
+    pub list_state: ListState,
+}
+
+impl SavedSearchBrowser {
+    pub fn new(workspace: Workspace, searches: Vec<SavedSearch>) -> Self {
+        let mut list_state = ListState::default();
+        if !searches.is_empty() {
+            list_state.select(Some(0));
+        }
+        Self {
+            workspace,
+            searches,
+            list_state,
+        }
+    }
+
+    /// Move selection up
+    pub fn previous(&mut self) {
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(Some(selected.saturating_sub(1)));
+    }
+
+    /// Move selection down
+    pub fn next(&mut self) {
+        let selected = self.list_state.selected().unwrap_or(0);
+        let max = self.searches.len().saturating_sub(1);
+        self.list_state.select(Some((selected + 1).min(max)));
+    }
+
+    pub fn selected(&self) -> Option<&SavedSearch> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.searches.get(i))
+    }
 }
 
 impl WorkspacesModel {
@@ -23,6 +179,14 @@ impl WorkspacesModel {
         Self {
             workspaces: Vec::new(),
             table_state: TableState::default(),
+            subscription_picker: None,
+            saved_searches: None,
+            sort: WorkspaceSort::default(),
+            order: Vec::new(),
+            health: WorkspaceHealth::default(),
+            persist_health: false,
+            pending_selection: None,
+            refreshing: false,
         }
     }
 
@@ -35,13 +199,79 @@ impl WorkspacesModel {
                 selected: true, // Default all selected
             })
             .collect();
+        self.apply_sort();
 
-        // Set initial selection to first workspace if any exist
-        if !self.workspaces.is_empty() {
-            self.table_state.select(Some(0));
+        // Set initial selection to the first row if any exist
+        self.table_state.select(self.order.first().copied());
+    }
+
+    /// Indices into `workspaces` in display order
+    pub fn display_order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Switch to the next sort order, keeping the cursor on the same workspace
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        self.apply_sort();
+    }
+
+    /// Rebuild the display order for the current sort
+    ///
+    /// Toggling selection doesn't re-sort, so rows don't jump away from the cursor under
+    /// `SelectedFirst`; cycling the sort again picks up the new selection.
+    fn apply_sort(&mut self) {
+        let workspaces = &self.workspaces;
+        let name = |i: &usize| workspaces[*i].workspace.name.to_lowercase();
+        let mut order: Vec<usize> = (0..workspaces.len()).collect();
+        match self.sort {
+            WorkspaceSort::Discovery => {}
+            WorkspaceSort::Name => order.sort_by_key(name),
+            WorkspaceSort::Subscription => order.sort_by_key(|i| {
+                (
+                    workspaces[*i].workspace.subscription_name.to_lowercase(),
+                    name(i),
+                )
+            }),
+            WorkspaceSort::SelectedFirst => {
+                order.sort_by_key(|i| (!workspaces[*i].selected, name(i)))
+            }
+        }
+        self.order = order;
+    }
+
+    /// Move the cursor to the previous row
+    pub fn select_previous(&mut self) {
+        let position = self.cursor_position().unwrap_or(0);
+        if position > 0 {
+            self.table_state.select(Some(self.order[position - 1]));
+        }
+    }
+
+    /// Jump the cursor by a page or to either end
+    pub fn select_jump(&mut self, jump: ListJump) {
+        if let Some(idx) = jump.target(&self.order, self.table_state.selected()) {
+            self.table_state.select(Some(idx));
+        }
+    }
+
+    /// Move the cursor to the next row
+    pub fn select_next(&mut self) {
+        let next = match self.cursor_position() {
+            Some(position) => self.order.get(position + 1),
+            None => self.order.first(),
+        };
+        if let Some(&idx) = next {
+            self.table_state.select(Some(idx));
         }
     }
 
+    /// Row position of the cursor in display order
+    pub fn cursor_position(&self) -> Option<usize> {
+        let selected = self.table_state.selected()?;
+        self.order.iter().position(|&i| i == selected)
+    }
+
     /// Get selected workspaces
     pub fn get_selected_workspaces(&self) -> Vec<Workspace> {
         self.workspaces
@@ -51,6 +281,83 @@ impl WorkspacesModel {
             .collect()
     }
 
+    /// Resource IDs of the selected workspaces
+    pub fn selected_resource_ids(&self) -> Vec<String> {
+        self.workspaces
+            .iter()
+            .filter(|ws| ws.selected)
+            .map(|ws| ws.workspace.resource_id.clone())
+            .collect()
+    }
+
+    /// Select exactly the workspaces with these resource IDs
+    /// Returns the IDs that match no listed workspace. Before the workspace list has loaded,
+    /// the selection is kept in `pending_selection` instead.
+    pub fn restore_selection(&mut self, resource_ids: Vec<String>) -> Vec<String> {
+        if self.workspaces.is_empty() {
+            self.pending_selection = Some(resource_ids);
+            return Vec::new();
+        }
+
+        // Resource IDs are case-insensitive
+        for ws in &mut self.workspaces {
+            ws.selected = resource_ids
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(&ws.workspace.resource_id));
+        }
+        resource_ids
+            .into_iter()
+            .filter(|id| {
+                !self
+                    .workspaces
+                    .iter()
+                    .any(|ws| ws.workspace.resource_id.eq_ignore_ascii_case(id))
+            })
+            .collect()
+    }
+
+    /// Selected workspaces to fan a run out to, and the names of those left out
+    /// With `skip_failing`, workspaces on a failure streak of at least `streak` are left out.
+    pub fn workspaces_for_run(
+        &self,
+        skip_failing: bool,
+        streak: u32,
+    ) -> (Vec<Workspace>, Vec<String>) {
+        let (failing, healthy): (Vec<_>, Vec<_>) = self
+            .get_selected_workspaces()
+            .into_iter()
+            .partition(|workspace| {
+                skip_failing && self.health.is_failing(&workspace.workspace_id, streak)
+            });
+        (healthy, failing.into_iter().map(|w| w.name).collect())
+    }
+
+    /// Start from the statistics persisted by earlier sessions, and keep them up to date
+    pub fn load_health(&mut self) {
+        self.persist_health = true;
+        match WorkspaceHealth::load() {
+            Ok(health) => self.health = health,
+            Err(e) => log::warn!("Failed to load workspace health: {}", e),
+        }
+    }
+
+    /// Write the statistics to disk if they are persisted
+    pub fn save_health(&self) {
+        if self.persist_health {
+            if let Err(e) = self.health.save() {
+                log::warn!("Failed to save workspace health: {}", e);
+            }
+        }
+    }
+
+    /// Get the workspace under the cursor
+    pub fn get_highlighted_workspace(&self) -> Option<&Workspace> {
+        self.table_state
+            .selected()
+            .and_then(|idx| self.workspaces.get(idx))
+            .map(|ws| &ws.workspace)
+    }
+
     /// Toggle selection for a workspace at index
     pub fn toggle_selection(&mut self, index: usize) {
         if let Some(ws) = self.workspaces.get_mut(index) {
@@ -83,3 +390,33 @@ impl Default for WorkspacesModel {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_selection() {
+        let mut model = WorkspacesModel::new();
+        let saved = vec![
+            Workspace::fixture("ws-eu").resource_id.to_uppercase(),
+            Workspace::fixture("ws-gone").resource_id,
+        ];
+
+        // Before the workspace list loads, the selection waits
+        assert!(model.restore_selection(saved.clone()).is_empty());
+        assert_eq!(model.pending_selection.as_ref(), Some(&saved));
+
+        model.load_workspaces(vec![
+            Workspace::fixture("ws-eu"),
+            Workspace::fixture("ws-us"),
+        ]);
+        let pending = model.pending_selection.take().unwrap();
+        let missing = model.restore_selection(pending);
+        assert_eq!(missing, vec![Workspace::fixture("ws-gone").resource_id]);
+        assert_eq!(
+            model.selected_resource_ids(),
+            vec![Workspace::fixture("ws-eu").resource_id]
+        );
+    }
+}
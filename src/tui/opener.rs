@@ -0,0 +1,65 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Open a file with the platform's default application
+pub fn open_path(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(path);
+        command
+    } else if cfg!(target_os = "windows") {
+        // `start` is a cmd builtin; the empty string is the window title
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(path);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(path);
+        command
+    };
+
+    spawn_detached(&mut command)
+}
+
+/// Show a file in the platform's file manager (selecting it where supported)
+pub fn reveal_path(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        command
+    } else {
+        // xdg-open has no "select" mode, so open the containing folder
+        let folder = path.parent().unwrap_or(path);
+        let mut command = Command::new("xdg-open");
+        command.arg(folder);
+        command
+    };
+
+    spawn_detached(&mut command)
+}
+
+/// Launch a helper without letting it write over the TUI
+/// The child is reaped on a background thread so it doesn't linger as a zombie
+fn spawn_detached(command: &mut Command) -> Result<(), String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to launch {:?}: {}", command.get_program(), e))?;
+
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
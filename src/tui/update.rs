@@ -1,23 +1,456 @@
+use crate::backend::QueryBackend;
+use crate::error::KqlPanopticonError;
+use crate::file_lock::{Acquire, FileLock};
+use crate::paths;
 use crate::query_job::{QueryJobBuilder, QueryJobResult, QuerySettings};
+use crate::scheduler::JobPriority;
 use crate::tui::message::{Message, Tab};
-use crate::tui::model::{query::EditorMode, Model, Popup};
-use log::error;
+use crate::tui::model::{
+    finder::FinderModel,
+    finder::FinderTarget,
+    jobs::{JobColumnPicker, ResultSearch, TriageFilter},
+    notifications::NotificationLevel,
+    packs::{PackSearch, RunConfirmation},
+    query::{EditorMode, LineOperator},
+    session::{parse_tags, SessionDetailsInput},
+    settings::{DuplicateJobs, SettingsModel},
+    LockConflict, LockedFile, Model, Popup,
+};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Semaphore;
-
-/// Sanitize a string to be safe for use as a filename
-fn sanitize_filename(name: &str) -> String {
-    name.chars()
-        .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
-            c if c.is_whitespace() => '-',
-            c if c.is_alphanumeric() || c == '-' || c == '_' => c,
-            _ => '-',
+
+/// Pulled pack changes listed in a toast; longer lists open an info popup
+const PULL_TOAST_CHANGES: usize = 3;
+
+/// Notice listing the selected workspaces a run left out for failing repeatedly
+fn skipped_failing_notice(skipped: &[String]) -> Option<Message> {
+    (!skipped.is_empty()).then(|| {
+        Message::ShowInfo(format!(
+            "Skipped {} failing workspace(s): {}",
+            skipped.len(),
+            skipped.join(", ")
+        ))
+    })
+}
+
+/// Notice listing the workspaces a loaded session selected that no longer exist
+fn missing_workspaces_notice(missing: Vec<String>) -> Option<Message> {
+    if missing.is_empty() {
+        return None;
+    }
+    // Resource IDs end in the workspace name
+    let names: Vec<&str> = missing
+        .iter()
+        .map(|id| id.rsplit('/').next().unwrap_or(id))
+        .collect();
+    Some(Message::ShowInfo(format!(
+        "{} workspace(s) selected in this session no longer exist or aren't visible: {}",
+        missing.len(),
+        names.join(", ")
+    )))
+}
+
+/// Open the parameter popup if the editor query has parameters that haven't been filled in
+/// for this run yet
+fn prompt_for_parameters<B: QueryBackend>(
+    model: &mut Model<B>,
+    job_name: &str,
+    watch: bool,
+) -> Option<Vec<Message>> {
+    if model.query.resolved_query.is_some() {
+        return None;
+    }
+    let parameters = crate::query_params::find_parameters(&model.query.get_text());
+    if parameters.is_empty() {
+        return None;
+    }
+    let remembered = crate::config::Config::load_or_default().query_parameters;
+    model.query.parameter_input = Some(crate::tui::model::query::ParameterInput::new(
+        job_name.to_string(),
+        watch,
+        parameters,
+        &remembered,
+    ));
+    model.query.job_name_input = None;
+    model.popup = Some(Popup::QueryParameters);
+    Some(vec![])
+}
+
+/// Load a query of the selected pack into the editor, with the pack as context
+fn load_pack_query<B: QueryBackend>(model: &mut Model<B>, query_index: usize) -> Vec<Message> {
+    // First ensure the pack is loaded
+    if let Err(e) = model.packs.load_selected_pack() {
+        return vec![Message::ShowError(format!("Failed to load pack: {}", e))];
+    }
+    let Some(entry) = model.packs.get_selected_entry() else {
+        return vec![Message::ShowError("No pack selected".to_string())];
+    };
+
+    // Lock the pack for editing; switching queries within the open pack keeps its lock
+    let open = model
+        .query
+        .pack_context
+        .as_ref()
+        .filter(|ctx| ctx.pack_path == entry.path)
+        .map(|ctx| (ctx.lock.clone(), ctx.read_only));
+    let (lock, read_only) = match open {
+        Some(state) => state,
+        // Packs in read-only libraries can't be saved anyway
+        None if entry.read_only => (None, false),
+        None => match FileLock::acquire(&entry.path) {
+            Ok(Acquire::Acquired(lock)) => (Some(Arc::new(lock)), false),
+            Ok(Acquire::HeldBy(holder)) => {
+                model.lock_conflict = Some(LockConflict {
+                    file: LockedFile::Pack { query_index },
+                    holder,
+                });
+                model.popup = Some(Popup::LockConflict);
+                return vec![];
+            }
+            Err(e) => {
+                log::warn!("Failed to lock {}: {}", entry.path.display(), e);
+                (None, false)
+            }
+        },
+    };
+    open_pack_query(model, query_index, lock, read_only)
+}
+
+/// Load a query from the selected pack into the editor, holding `lock` on the pack
+fn open_pack_query<B: QueryBackend>(
+    model: &mut Model<B>,
+    query_index: usize,
+    lock: Option<Arc<FileLock>>,
+    read_only: bool,
+) -> Vec<Message> {
+    // Now get the loaded pack and extract query
+    if let Some(entry) = model.packs.get_selected_entry() {
+        if let Some(pack) = &entry.pack {
+            let queries = pack.get_queries();
+            if let Some(query) = queries.get(query_index) {
+                // Set the query text in the editor
+                model.query.textarea.select_all();
+                let len = model.query.textarea.yank_text().len();
+                model.query.textarea.delete_str(len);
+                model.query.textarea.insert_str(&query.query);
+
+                // Set pack context for navigation
+                model.query.pack_context = Some(crate::tui::model::query::PackContext {
+                    pack_name: pack.name.clone(),
+                    pack_path: entry.path.clone(),
+                    queries: queries.clone(),
+                    current_index: query_index,
+                    pack: pack.clone(),
+                    content_hash: crate::query_pack::QueryPack::content_hash(&entry.path),
+                    lock,
+                    read_only,
+                });
+
+                // Switch to Query tab
+                vec![Message::SwitchTab(Tab::Query)]
+            } else {
+                vec![Message::ShowError("Pack contains no queries".to_string())]
+            }
+        } else {
+            vec![Message::ShowError(
+                "Failed to load pack details".to_string(),
+            )]
+        }
+    } else {
+        vec![Message::ShowError("No pack selected".to_string())]
+    }
+}
+
+/// Load a saved session, holding `lock` on its file (`read_only` if another instance holds it)
+fn open_session<B: QueryBackend>(
+    model: &mut Model<B>,
+    session_name: String,
+    lock: Option<FileLock>,
+    read_only: bool,
+) -> Vec<Message> {
+    // Load session from disk
+    match crate::session::Session::load(&session_name) {
+        Ok(session) => {
+            // Apply settings
+            session.apply_to_settings(&mut model.settings);
+
+            // Rebuild client with loaded settings
+            if let Err(e) = model.rebuild_client() {
+                return vec![Message::ShowError(format!(
+                    "Failed to update client settings: {}",
+                    e
+                ))];
+            }
+
+            // Load jobs - pass mutable reference to next_id generator
+            model.jobs.jobs = session.to_job_states(model.jobs.next_job_id_mut());
+            // Sort jobs by timestamp (newest first)
+            model.jobs.sort_jobs();
+            // If jobs were loaded, select the first one
+            if !model.jobs.jobs.is_empty() {
+                model.jobs.table_state.select(Some(0));
+            } else {
+                model.jobs.table_state.select(None);
+            }
+
+            // Load pack origin (if any)
+            model
+                .sessions
+                .set_pack_origin(session.created_from_pack.clone());
+            model
+                .sessions
+                .set_current_details(session.tags.clone(), session.description.clone());
+
+            // Restore the workspace selection (sessions saved before it was recorded
+            // leave it alone)
+            let missing = match session.selected_workspaces {
+                Some(ids) => model.workspaces.restore_selection(ids),
+                None => Vec::new(),
+            };
+
+            // Set as current session
+            model.sessions.set_current_session(Some(session_name));
+            model.sessions.set_lock(lock, read_only);
+            std::iter::once(Message::SessionsRefresh)
+                .chain(missing_workspaces_notice(missing))
+                .collect()
+        }
+        Err(e) => vec![Message::ShowError(format!("Failed to load session: {}", e))],
+    }
+}
+
+/// Error for changing a session another instance has open, if one does
+fn locked_session_error(name: &str) -> Option<Message> {
+    let path = crate::session::Session::file_path(name).ok()?;
+    let holder = FileLock::held_elsewhere(&path)?;
+    Some(Message::ShowError(format!(
+        "Session '{}' is open in another instance ({})",
+        name, holder
+    )))
+}
+
+/// Error for a run with no workspaces left to query
+fn no_workspaces_error(skipped: &[String]) -> Message {
+    if skipped.is_empty() {
+        Message::ShowError("No workspaces selected".to_string())
+    } else {
+        Message::ShowError(format!(
+            "Every selected workspace is failing ({}). Turn off Skip Failing Workspaces in Settings to run anyway",
+            skipped.join(", ")
+        ))
+    }
+}
+
+/// Write the pack loaded in the query editor to `path` and point the pack context at it
+fn save_loaded_pack(
+    model: &mut Model<impl QueryBackend>,
+    pack: crate::query_pack::QueryPack,
+    path: std::path::PathBuf,
+) -> crate::error::Result<()> {
+    // Only write packs this instance holds the lock on
+    let display = path.display().to_string();
+    let current = model
+        .query
+        .pack_context
+        .as_ref()
+        .filter(|ctx| ctx.pack_path == path);
+    let new_lock = match current {
+        Some(ctx) if ctx.read_only => {
+            return Err(KqlPanopticonError::FileLocked(format!(
+                "{} (opened read-only; save a copy with S)",
+                display
+            )))
+        }
+        Some(ctx) if ctx.lock.as_ref().is_some_and(|lock| !lock.is_held()) => {
+            return Err(KqlPanopticonError::FileLocked(format!(
+                "{} (taken over; save a copy with S)",
+                display
+            )))
+        }
+        Some(_) => None,
+        // Saving somewhere new (save-as, or the file moved): lock the new file first
+        None => match FileLock::acquire(&path)? {
+            Acquire::Acquired(lock) => Some(Arc::new(lock)),
+            Acquire::HeldBy(_) => return Err(KqlPanopticonError::FileLocked(display)),
+        },
+    };
+
+    pack.save_to_file(&path)?;
+
+    // Keep the Packs list in sync with what's on disk
+    if let Some(entry) = model
+        .packs
+        .packs
+        .iter_mut()
+        .find(|entry| entry.path == path)
+    {
+        entry.pack = Some(pack.clone());
+    }
+
+    if let Some(ctx) = &mut model.query.pack_context {
+        if new_lock.is_some() {
+            ctx.lock = new_lock;
+            ctx.read_only = false;
+        }
+        ctx.queries = pack.get_queries();
+        ctx.content_hash = crate::query_pack::QueryPack::content_hash(&path);
+        ctx.pack_path = path;
+        ctx.pack = pack;
+    }
+
+    Ok(())
+}
+
+/// Mirror yanked lines to the system clipboard
+fn copy_lines<B: QueryBackend>(model: &mut Model<B>, text: &str, lines: usize) -> Vec<Message> {
+    match model.clipboard.copy(text) {
+        Ok(()) => vec![Message::ShowInfo(format!(
+            "Copied {} line(s) to clipboard",
+            lines
+        ))],
+        Err(e) => vec![Message::ShowError(e)],
+    }
+}
+
+/// Login name of the user saving a pack, for its changelog
+fn current_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+/// Export a saved session as a query pack in the library
+/// Returns the path of the written pack.
+fn export_session_as_pack(session_name: &str) -> Result<std::path::PathBuf, String> {
+    let session = crate::session::Session::load(session_name)
+        .map_err(|e| format!("Failed to load session: {}", e))?;
+    let pack = session
+        .to_query_pack()
+        .map_err(|e| format!("Failed to convert to pack: {}", e))?;
+
+    // Generate output filename (remove timestamp suffix if present)
+    let pack_name = session_name
+        .rsplit_once('_')
+        .and_then(|(prefix, suffix)| {
+            if suffix.chars().all(|c| c.is_ascii_digit()) && suffix.len() >= 6 {
+                Some(prefix)
+            } else {
+                None
+            }
         })
-        .collect::<String>()
-        .trim_matches('-')
-        .to_lowercase()
+        .unwrap_or(session_name);
+
+    let output_path =
+        crate::query_pack::QueryPack::get_library_path(&format!("{}.yaml", pack_name))
+            .map_err(|e| format!("Failed to get output path: {}", e))?;
+
+    // Ensure parent directory exists
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    pack.save_to_file(&output_path)
+        .map_err(|e| format!("Failed to save pack: {}", e))?;
+    Ok(output_path)
+}
+
+/// Run a job in the background once the shared scheduler gives it a slot
+/// Each job runs on its own to keep its own settings (QueryJobBuilder applies a single
+/// settings to all jobs, losing per-query output names).
+fn spawn_scheduled_job<B: QueryBackend>(
+    model: &Model<B>,
+    job_id: u64,
+    retry_ctx: crate::tui::model::jobs::RetryContext,
+) {
+    let client = model.client.clone();
+    let tx = model.job_update_tx.clone();
+    let scheduler = model.scheduler.clone();
+    let priority = model.jobs.priority_handle(job_id);
+
+    log::debug!("Spawning task for job ID {}", job_id);
+
+    tokio::spawn(async move {
+        // Wait for a free slot on the job's workspace and overall, by priority
+        let _permit = scheduler
+            .acquire(&retry_ctx.workspace.workspace_id, &priority)
+            .await;
+        log::debug!("Job {} acquired scheduler permit, executing", job_id);
+        let _ = tx.send(crate::tui::model::JobUpdateMessage::Started(job_id));
+
+        // Clone retry_ctx for error cases (will be moved into builder)
+        let retry_ctx_for_errors = retry_ctx.clone();
+
+        let results = QueryJobBuilder::new()
+            .workspaces(vec![retry_ctx.workspace])
+            .queries(vec![retry_ctx.query])
+            .settings(retry_ctx.settings)
+            .execute(&client)
+            .await;
+
+        // Send completion message in ALL cases (success or failure)
+        match results {
+            Ok(mut results) if !results.is_empty() => {
+                let result = results.remove(0);
+                log::debug!(
+                    "Job {} completed successfully, sending completion message",
+                    job_id
+                );
+                let _ = tx.send(crate::tui::model::JobUpdateMessage::Completed(
+                    job_id, result,
+                ));
+            }
+            Ok(_) => {
+                // Empty results - shouldn't happen but handle it
+                log::error!(
+                    "Job {} produced no results (empty vec), sending failed message",
+                    job_id
+                );
+                // Create a failed result to update the UI
+                let failed_result = create_failed_result(
+                    retry_ctx_for_errors,
+                    "Query execution returned no results".to_string(),
+                );
+                let _ = tx.send(crate::tui::model::JobUpdateMessage::Completed(
+                    job_id,
+                    failed_result,
+                ));
+            }
+            Err(e) => {
+                // Execution error - create failed result
+                log::error!("Job {} failed: {}, sending failed message", job_id, e);
+                let failed_result = create_failed_result(retry_ctx_for_errors, e.to_string());
+                let _ = tx.send(crate::tui::model::JobUpdateMessage::Completed(
+                    job_id,
+                    failed_result,
+                ));
+            }
+        }
+        // Permit is automatically released when _permit is dropped
+    });
+}
+
+/// Open the row inspector on a result preview's rows
+fn open_row_inspector<B: QueryBackend>(
+    model: &mut Model<B>,
+    title: String,
+    preview: &crate::tui::model::query::ResultPreview,
+) -> Vec<Message> {
+    use crate::tui::model::query::PreviewOutcome;
+    use crate::tui::model::row_inspector::RowInspector;
+
+    if let PreviewOutcome::Error(e) = &preview.outcome {
+        return vec![Message::ShowError(format!("Cannot inspect rows: {}", e))];
+    }
+    match RowInspector::new(title, preview) {
+        Some(inspector) => {
+            model.jobs.row_inspector = Some(inspector);
+            model.popup = Some(Popup::RowInspector);
+            vec![]
+        }
+        None => vec![Message::ShowInfo("The result has no rows".to_string())],
+    }
 }
 
 /// Create a failed QueryJobResult for when execution fails
@@ -31,13 +464,26 @@ fn create_failed_result(
         query: retry_ctx.query,
         result: Err(crate::error::KqlPanopticonError::Other(error_msg)),
         elapsed: Duration::from_secs(0),
-        timestamp: chrono::Local::now(),
+        timestamp: crate::time_zone::now(),
     }
 }
 
 /// Update the model based on a message
 /// Returns a list of additional messages to process
-pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
+pub fn update<B: QueryBackend>(model: &mut Model<B>, message: Message) -> Vec<Message> {
+    if model.read_only && message.executes_queries() {
+        return vec![Message::ShowError(
+            "Read-only mode: query execution is disabled".to_string(),
+        )];
+    }
+
+    // A count or `d`/`y` applies only to the editor command typed right after it
+    let count = if message.continues_editor_command() {
+        None
+    } else {
+        model.query.take_pending()
+    };
+
     match message {
         // === Navigation ===
         Message::SwitchTab(tab) => {
@@ -71,7 +517,7 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
         }
 
         Message::SettingsNext => {
-            if model.settings.selected_index < 6 {
+            if model.settings.selected_index < SettingsModel::COUNT - 1 {
                 model.settings.selected_index += 1;
                 model
                     .settings
@@ -110,7 +556,9 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
         Message::SettingsSave => {
             if let Some(value) = model.settings.editing.take() {
-                if !value.trim().is_empty() {
+                if !value.trim().is_empty()
+                    || SettingsModel::CLEARABLE_INDICES.contains(&model.settings.selected_index)
+                {
                     match model.settings.save_edit(value) {
                         Ok(()) => {
                             model.popup = None;
@@ -121,6 +569,18 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                                     e
                                 ))];
                             }
+                            // Cache TTL, subscription filter and row limit are global config, not session state
+                            if SettingsModel::CONFIG_INDICES
+                                .contains(&model.settings.selected_index)
+                            {
+                                if let Err(e) = model.save_config() {
+                                    return vec![Message::ShowError(format!(
+                                        "Failed to save config: {}",
+                                        e
+                                    ))];
+                                }
+                                return vec![];
+                            }
                             // Mark session as dirty when settings change
                             model.sessions.mark_dirty();
                             vec![]
@@ -147,19 +607,22 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
         // === Workspaces ===
         Message::WorkspacesPrevious => {
-            let selected = model.workspaces.table_state.selected().unwrap_or(0);
-            if selected > 0 {
-                model.workspaces.table_state.select(Some(selected - 1));
-            }
+            model.workspaces.select_previous();
             vec![]
         }
 
         Message::WorkspacesNext => {
-            let selected = model.workspaces.table_state.selected().unwrap_or(0);
-            let max = model.workspaces.workspaces.len().saturating_sub(1);
-            if selected < max {
-                model.workspaces.table_state.select(Some(selected + 1));
-            }
+            model.workspaces.select_next();
+            vec![]
+        }
+
+        Message::WorkspacesJump(jump) => {
+            model.workspaces.select_jump(jump);
+            vec![]
+        }
+
+        Message::WorkspacesCycleSort => {
+            model.workspaces.cycle_sort();
             vec![]
         }
 
@@ -180,17 +643,233 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             vec![]
         }
 
+        Message::WorkspacesQuickExec => {
+            let Some(workspace) = model.workspaces.get_highlighted_workspace().cloned() else {
+                return vec![Message::ShowError("No workspace highlighted".to_string())];
+            };
+            if model.query.get_text().trim().is_empty() {
+                return vec![Message::ShowError("Query is empty".to_string())];
+            }
+            let formats = model.settings.run_formats();
+            model.query.start_job_name_input(formats);
+            model.query.watch_requested = false;
+            model.query.target_workspace = Some(workspace);
+            model.popup = Some(Popup::JobNameInput);
+            vec![]
+        }
+
         Message::WorkspacesRefresh => {
-            // This will be handled asyncronously in the main loop
-            // The main loop will detect this message and trigger an async operation
+            // The main loop spawns the fetch, which reports back with WorkspacesLoaded
+            model.workspaces.refreshing = true;
             vec![]
         }
 
+        Message::WorkspacesRefreshFailed(error) => {
+            // A cancelled refresh has nothing left to report
+            if !std::mem::take(&mut model.workspaces.refreshing) {
+                return vec![];
+            }
+            vec![Message::ShowError(format!(
+                "Failed to refresh workspaces: {}",
+                error
+            ))]
+        }
+
+        Message::WorkspacesCancelRefresh => {
+            // The main loop aborts the task
+            if !std::mem::take(&mut model.workspaces.refreshing) {
+                return vec![];
+            }
+            vec![Message::ShowInfo("Workspace refresh cancelled".to_string())]
+        }
+
         Message::WorkspacesLoaded(workspaces) => {
+            model.workspaces.refreshing = false;
             model.workspaces.load_workspaces(workspaces);
+            // A session loaded before the workspaces brings its selection with it
+            match model.workspaces.pending_selection.take() {
+                Some(ids) => missing_workspaces_notice(model.workspaces.restore_selection(ids))
+                    .into_iter()
+                    .collect(),
+                None => vec![],
+            }
+        }
+
+        Message::SubscriptionsOpen => {
+            // Subscriptions are listed in the background by the main loop
+            vec![]
+        }
+
+        Message::SubscriptionsLoaded(subscriptions) => {
+            model.workspaces.subscription_picker =
+                Some(crate::tui::model::workspaces::SubscriptionPicker::new(
+                    subscriptions,
+                    model.settings.subscription_filter.clone(),
+                ));
+            model.popup = Some(Popup::SubscriptionFilter);
+            vec![]
+        }
+
+        Message::SavedSearchesOpen => {
+            // Saved searches are listed in the background by the main loop
+            vec![]
+        }
+
+        Message::SavedSearchesLoaded(workspace, searches) => {
+            if searches.is_empty() {
+                return vec![Message::ShowInfo(format!(
+                    "No saved searches or functions in {}",
+                    workspace.name
+                ))];
+            }
+            model.workspaces.saved_searches = Some(
+                crate::tui::model::workspaces::SavedSearchBrowser::new(workspace, searches),
+            );
+            model.popup = Some(Popup::SavedSearches);
+            vec![]
+        }
+
+        Message::SavedSearchesPrevious => {
+            if let Some(browser) = &mut model.workspaces.saved_searches {
+                browser.previous();
+            }
             vec![]
         }
 
+        Message::SavedSearchesNext => {
+            if let Some(browser) = &mut model.workspaces.saved_searches {
+                browser.next();
+            }
+            vec![]
+        }
+
+        Message::SavedSearchesLoadQuery => {
+            let Some(search) = model
+                .workspaces
+                .saved_searches
+                .as_ref()
+                .and_then(|browser| browser.selected())
+                .cloned()
+            else {
+                return vec![];
+            };
+            model.workspaces.saved_searches = None;
+            model.popup = None;
+
+            // Keep whatever is in the editor; the search gets a buffer of its own
+            if !model.query.get_text().trim().is_empty() {
+                model.query.new_buffer();
+            }
+            model.query.set_text(search.query.clone());
+            model.query.pack_context = None;
+
+            let mut messages = vec![Message::SwitchTab(Tab::Query)];
+            if let Some(signature) = search.signature().filter(|_| search.has_parameters()) {
+                messages.push(Message::ShowInfo(format!(
+                    "Loaded function {}: define its parameters (e.g. with let) before running it",
+                    signature
+                )));
+            }
+            messages
+        }
+
+        Message::SavedSearchesExport => {
+            let Some(browser) = model.workspaces.saved_searches.take() else {
+                return vec![];
+            };
+            model.popup = None;
+
+            let output_path = match crate::query_pack::QueryPack::get_library_path(&format!(
+                "saved-searches/{}.yaml",
+                paths::sanitize_file_name(&browser.workspace.name)
+            )) {
+                Ok(p) => p,
+                Err(e) => {
+                    return vec![Message::ShowError(format!(
+                        "Failed to get output path: {}",
+                        e
+                    ))]
+                }
+            };
+
+            if output_path.exists() {
+                return vec![Message::ShowError(format!(
+                    "Pack already exists: {}",
+                    output_path.display()
+                ))];
+            }
+
+            if let Some(parent) = output_path.parent() {
+                if let Err(e) = std::fs::create_dir_all(parent) {
+                    return vec![Message::ShowError(format!(
+                        "Failed to create directory: {}",
+                        e
+                    ))];
+                }
+            }
+
+            let pack =
+                crate::saved_search::to_query_pack(&browser.workspace.name, &browser.searches);
+            match pack.save_to_file(&output_path) {
+                Ok(()) => vec![
+                    Message::PacksRefresh,
+                    Message::ShowSuccess(format!(
+                        "Exported {} saved search(es) to {}",
+                        browser.searches.len(),
+                        output_path.display()
+                    )),
+                ],
+                Err(e) => vec![Message::ShowError(format!("Failed to save pack: {}", e))],
+            }
+        }
+
+        Message::SubscriptionsPrevious => {
+            if let Some(picker) = &mut model.workspaces.subscription_picker {
+                picker.previous();
+            }
+            vec![]
+        }
+
+        Message::SubscriptionsNext => {
+            if let Some(picker) = &mut model.workspaces.subscription_picker {
+                picker.next();
+            }
+            vec![]
+        }
+
+        Message::SubscriptionsToggle => {
+            if let Some(picker) = &mut model.workspaces.subscription_picker {
+                picker.toggle_selected();
+            }
+            vec![]
+        }
+
+        Message::SubscriptionsApply => {
+            let Some(picker) = model.workspaces.subscription_picker.take() else {
+                return vec![];
+            };
+            model.popup = None;
+
+            if picker.enabled_count() == 0 {
+                return vec![Message::ShowError(
+                    "Subscription filter must leave at least one subscription enabled".to_string(),
+                )];
+            }
+
+            model.settings.subscription_filter = picker.filter;
+            if let Err(e) = model.rebuild_client() {
+                return vec![Message::ShowError(format!(
+                    "Failed to update client settings: {}",
+                    e
+                ))];
+            }
+            if let Err(e) = model.save_config() {
+                return vec![Message::ShowError(format!("Failed to save config: {}", e))];
+            }
+
+            vec![Message::WorkspacesRefresh]
+        }
+
         // === Query ===
         Message::QueryEnterInsertMode => {
             model.query.mode = EditorMode::Insert;
@@ -208,6 +887,11 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             vec![]
         }
 
+        Message::QueryEnterVisualLineMode => {
+            model.query.start_line_selection();
+            vec![]
+        }
+
         Message::QueryExitVisualMode => {
             model.query.textarea.cancel_selection();
             model.query.mode = EditorMode::Normal;
@@ -215,18 +899,60 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
         }
 
         Message::QueryYank => {
+            if let Some((first, last)) = model.query.line_selection() {
+                model.query.mode = EditorMode::Normal;
+                let yanked = model.query.yank_lines(first, last);
+                return copy_lines(model, &yanked, last - first + 1);
+            }
             model.query.textarea.copy();
             model.query.textarea.cancel_selection();
             model.query.mode = EditorMode::Normal;
-            vec![]
+
+            // Mirror the yank to the system clipboard
+            let yanked = model.query.textarea.yank_text();
+            if yanked.is_empty() {
+                return vec![];
+            }
+            match model.clipboard.copy(&yanked) {
+                Ok(()) => vec![Message::ShowInfo(format!(
+                    "Copied {} characters to clipboard",
+                    yanked.chars().count()
+                ))],
+                Err(e) => vec![Message::ShowError(e)],
+            }
         }
 
         Message::QueryDeleteSelection => {
+            if let Some((first, last)) = model.query.line_selection() {
+                model.query.delete_lines(first, last);
+                return vec![];
+            }
             model.query.textarea.delete_char(); // Deletes selection if active
             model.query.mode = EditorMode::Normal;
             vec![]
         }
 
+        Message::QueryCount(digit) => {
+            model.query.push_count_digit(digit);
+            vec![]
+        }
+
+        Message::QueryLineOperator(operator) => {
+            let Some((first, last)) = model.query.line_operator(operator) else {
+                return vec![];
+            };
+            match operator {
+                LineOperator::Delete => {
+                    model.query.delete_lines(first, last);
+                    vec![]
+                }
+                LineOperator::Yank => {
+                    let yanked = model.query.yank_lines(first, last);
+                    copy_lines(model, &yanked, last - first + 1)
+                }
+            }
+        }
+
         Message::QueryInput(key_event) => {
             model.query.textarea.input(key_event);
             vec![]
@@ -244,7 +970,39 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                 KeyCode::End => CursorMove::End,
                 _ => return vec![],
             };
-            model.query.textarea.move_cursor(cursor_move);
+            for _ in 0..count.unwrap_or(1) {
+                model.query.textarea.move_cursor(cursor_move);
+                match direction {
+                    KeyCode::Up => model.query.skip_folds(false),
+                    KeyCode::Down => model.query.skip_folds(true),
+                    _ => {}
+                }
+            }
+            vec![]
+        }
+
+        Message::QueryToggleFold => {
+            if model.query.toggle_fold() {
+                vec![]
+            } else {
+                vec![Message::ShowInfo(
+                    "No //#region section at the cursor".to_string(),
+                )]
+            }
+        }
+
+        Message::QueryInspectResult => {
+            let Some(preview) = &model.query.result_preview else {
+                return vec![Message::ShowError(
+                    "No result to inspect: run the query with Ctrl+J first".to_string(),
+                )];
+            };
+            open_row_inspector(model, preview.workspace_name.clone(), &preview.clone())
+        }
+
+        Message::QueryToggleSplit => {
+            model.query.split_view = !model.query.split_view;
+            model.refresh_result_preview();
             vec![]
         }
 
@@ -291,7 +1049,9 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
         }
 
         Message::QueryDeleteChar => {
-            model.query.textarea.delete_char();
+            for _ in 0..count.unwrap_or(1) {
+                model.query.textarea.delete_char();
+            }
             vec![]
         }
 
@@ -319,10 +1079,12 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
         }
 
         Message::QueryMoveBottom => {
-            model
-                .query
-                .textarea
-                .move_cursor(tui_textarea::CursorMove::Bottom);
+            // With a count, `G` goes to that line (1-based)
+            let cursor_move = match count {
+                Some(line) => tui_textarea::CursorMove::Jump(line.saturating_sub(1) as u16, 0),
+                None => tui_textarea::CursorMove::Bottom,
+            };
+            model.query.textarea.move_cursor(cursor_move);
             vec![]
         }
 
@@ -331,52 +1093,165 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             vec![]
         }
 
+        Message::QueryFormat => {
+            model.query.format_text();
+            vec![]
+        }
+
+        Message::QueryPasteClipboard => match model.clipboard.paste() {
+            Ok(text) => vec![Message::QueryPasteText(text)],
+            Err(e) => vec![Message::ShowError(e)],
+        },
+
+        Message::QueryPasteText(text) => {
+            // Normalise line endings so Windows/browser clipboards don't leave stray \r
+            let text = text.replace("\r\n", "\n").replace('\r', "\n");
+            // Whole lines (as yanked by `yy`) go below the cursor's line in Normal mode
+            if model.query.mode == EditorMode::Normal && text.ends_with('\n') {
+                model.query.paste_lines_below(&text);
+            } else if !text.is_empty() {
+                model.query.textarea.insert_str(text);
+            }
+            vec![]
+        }
+
         Message::QueryStartExecution => {
-            model.query.job_name_input = Some(String::new());
+            let formats = model.settings.run_formats();
+            model.query.start_job_name_input(formats);
+            model.query.watch_requested = false;
+            model.popup = Some(Popup::JobNameInput);
+            vec![]
+        }
+
+        Message::QueryStartWatch => {
+            let formats = model.settings.run_formats();
+            model.query.start_job_name_input(formats);
+            model.query.watch_requested = true;
             model.popup = Some(Popup::JobNameInput);
             vec![]
         }
 
         Message::JobNameInputChar(c) => {
-            if let Some(ref mut input) = model.query.job_name_input {
+            if let Some(input) = model.query.focused_job_field_mut() {
                 input.push(c);
             }
             vec![]
         }
 
         Message::JobNameInputBackspace => {
-            if let Some(ref mut input) = model.query.job_name_input {
+            if let Some(input) = model.query.focused_job_field_mut() {
                 input.pop();
             }
             vec![]
         }
 
+        Message::JobNameInputCycleFocus(forward) => {
+            model.query.cycle_job_name_focus(forward);
+            vec![]
+        }
+
+        Message::JobNameInputToggleFormat => {
+            if let Some(option) = model.query.format_focus() {
+                model.query.run_formats.toggle(option);
+            }
+            vec![]
+        }
+
         Message::ExecuteQuery(job_name) => {
-            let selected_workspaces = model.workspaces.get_selected_workspaces();
+            if let Some(prompt) = prompt_for_parameters(model, &job_name, false) {
+                return prompt;
+            }
+
+            // Running on one chosen workspace never skips it
+            let (selected_workspaces, skipped) = match model.query.target_workspace.take() {
+                Some(workspace) => (vec![workspace], Vec::new()),
+                None => model.workspaces.workspaces_for_run(
+                    model.settings.skip_failing_workspaces,
+                    model.settings.failure_streak,
+                ),
+            };
 
             if selected_workspaces.is_empty() {
                 model.query.job_name_input = None;
                 model.popup = None;
-                return vec![Message::ShowError("No workspaces selected".to_string())];
+                return vec![no_workspaces_error(&skipped)];
             }
 
-            let query_text = model.query.get_text();
+            let query_text = model
+                .query
+                .resolved_query
+                .take()
+                .unwrap_or_else(|| model.query.get_text());
             if query_text.trim().is_empty() {
                 model.query.job_name_input = None;
                 model.popup = None;
                 return vec![Message::ShowError("Query is empty".to_string())];
             }
 
-            let settings = QuerySettings::with_formats(
+            let formats = model.query.run_formats;
+            let mut settings = QuerySettings::with_formats(
                 &model.settings.output_folder,
                 &job_name,
-                model.settings.export_csv,
-                model.settings.export_json,
-                model.settings.parse_dynamics,
+                formats.export_csv,
+                formats.export_json,
+                formats.parse_dynamics,
             );
+            settings.max_rows = model.settings.max_rows_limit();
+            settings.estimate_cost = model.settings.estimate_cost;
+            settings.csv_metadata = model.settings.csv_metadata;
+            settings.sqlite_path = model.settings.sqlite_path_setting();
+            settings.keep_partial_results = model.settings.keep_partial_results;
+            settings.skip_empty_output = model.settings.skip_empty_output;
+            // Queries loaded from a pack keep the pack's lookback window
+            settings.timespan = model
+                .query
+                .pack_context
+                .as_ref()
+                .and_then(|ctx| ctx.pack.timespan());
+
+            // Check for jobs already in the session (e.g. an accidental second Ctrl+J)
+            let (duplicates, fresh): (Vec<_>, Vec<_>) =
+                selected_workspaces.into_iter().partition(|workspace| {
+                    model
+                        .jobs
+                        .has_duplicate(&crate::tui::model::jobs::RetryContext {
+                            workspace: workspace.clone(),
+                            query: query_text.clone(),
+                            settings: settings.clone(),
+                        })
+                });
+            let mut notices: Vec<Message> = skipped_failing_notice(&skipped).into_iter().collect();
+            let selected_workspaces = match model.settings.duplicate_jobs {
+                _ if duplicates.is_empty() => fresh,
+                DuplicateJobs::Skip => {
+                    notices.push(Message::ShowInfo(format!(
+                        "Skipped {} duplicate job(s) already in this session",
+                        duplicates.len()
+                    )));
+                    fresh
+                }
+                policy => {
+                    if policy == DuplicateJobs::Warn {
+                        notices.push(Message::ShowInfo(format!(
+                            "{} job(s) repeat a query already run on the same workspace",
+                            duplicates.len()
+                        )));
+                    }
+                    duplicates.into_iter().chain(fresh).collect()
+                }
+            };
+
+            // Clear popup and input
+            model.query.job_name_input = None;
+            model.popup = None;
+
+            if selected_workspaces.is_empty() {
+                return notices;
+            }
 
             // Create job entries with retry context and capture their IDs
-            let mut job_ids = Vec::new();
+            let description = model.query.run_description();
+            let mut jobs = Vec::new();
             for workspace in &selected_workspaces {
                 // Use 200 chars for preview to show more KQL query context
                 let preview = model.query.get_preview(200);
@@ -385,16 +1260,21 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                     query: query_text.clone(),
                     settings: settings.clone(),
                 };
-                let job_id =
-                    model
-                        .jobs
-                        .add_job_with_context(workspace.name.clone(), preview, retry_context);
-                job_ids.push(job_id);
+                let job_id = model.jobs.add_job_with_context(
+                    workspace.name.clone(),
+                    preview,
+                    retry_context.clone(),
+                );
+                model
+                    .jobs
+                    .set_query_info(job_id, job_name.clone(), description.clone());
+                // An analyst is waiting on ad-hoc queries, so they go ahead of queued pack jobs
+                model.jobs.set_priority(job_id, JobPriority::High);
+                jobs.push((job_id, retry_context));
             }
-
-            // Clear popup and input
-            model.query.job_name_input = None;
-            model.popup = None;
+            // The split view follows this run
+            model.query.run_job_ids = jobs.iter().map(|(job_id, _)| *job_id).collect();
+            model.query.result_preview = None;
 
             // Clear pack origin since this is a manual query
             model.sessions.set_pack_origin(None);
@@ -403,40 +1283,149 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             model.sessions.mark_dirty();
 
             // Execute in background
-            let client = model.client.clone();
-            let query = query_text;
-            let workspaces = selected_workspaces;
-            let job_settings = settings;
-            let update_tx = model.job_update_tx.clone();
-
-            tokio::spawn(async move {
-                let results = QueryJobBuilder::new()
-                    .workspaces(workspaces)
-                    .queries(vec![query])
-                    .settings(job_settings)
-                    .execute(&client)
-                    .await;
-
-                // Send results through the channel using job IDs (not indices!)
-                match results {
-                    Ok(results) => {
-                        for (idx, result) in results.into_iter().enumerate() {
-                            if let Some(&job_id) = job_ids.get(idx) {
-                                let _ = update_tx.send(
-                                    crate::tui::model::JobUpdateMessage::Completed(job_id, result),
-                                );
-                            }
-                        }
-                    }
+            for (job_id, retry_context) in jobs {
+                spawn_scheduled_job(model, job_id, retry_context);
+            }
+
+            notices
+        }
+
+        Message::ExecuteWatch(job_name) => {
+            if let Some(prompt) = prompt_for_parameters(model, &job_name, true) {
+                return prompt;
+            }
+
+            model.query.job_name_input = None;
+            model.query.watch_requested = false;
+            model.popup = None;
+
+            let (selected_workspaces, skipped) = model.workspaces.workspaces_for_run(
+                model.settings.skip_failing_workspaces,
+                model.settings.failure_streak,
+            );
+            if selected_workspaces.is_empty() {
+                return vec![no_workspaces_error(&skipped)];
+            }
+
+            let query_text = model
+                .query
+                .resolved_query
+                .take()
+                .unwrap_or_else(|| model.query.get_text());
+            if query_text.trim().is_empty() {
+                return vec![Message::ShowError("Query is empty".to_string())];
+            }
+
+            let formats = model.query.run_formats;
+            let settings = QuerySettings::with_formats(
+                &model.settings.output_folder,
+                &job_name,
+                formats.export_csv,
+                formats.export_json,
+                formats.parse_dynamics,
+            );
+            let interval = Duration::from_secs(model.settings.watch_interval_secs);
+
+            let description = model.query.run_description();
+            for workspace in selected_workspaces {
+                let watch_job = match crate::watch::WatchJob::new(
+                    workspace.clone(),
+                    query_text.clone(),
+                    settings.clone(),
+                    interval,
+                ) {
+                    Ok(job) => job,
                     Err(e) => {
-                        error!("Query execution error: {}", e);
+                        return vec![Message::ShowError(format!("Failed to start watch: {}", e))]
                     }
+                };
+
+                let preview = model.query.get_preview(200);
+                let retry_context = crate::tui::model::jobs::RetryContext {
+                    workspace: workspace.clone(),
+                    query: query_text.clone(),
+                    settings: settings.clone(),
+                };
+                let (job_id, stop) = model.jobs.add_watch_job(
+                    workspace.name.clone(),
+                    preview,
+                    retry_context,
+                    interval,
+                );
+                model
+                    .jobs
+                    .set_query_info(job_id, job_name.clone(), description.clone());
+
+                let client = model.client.clone();
+                let update_tx = model.job_update_tx.clone();
+                tokio::spawn(watch_job.run(client, stop, move |tick| {
+                    let _ = update_tx
+                        .send(crate::tui::model::JobUpdateMessage::WatchTick(job_id, tick));
+                }));
+            }
+
+            model.sessions.set_pack_origin(None);
+            model.sessions.mark_dirty();
+
+            std::iter::once(Message::SwitchTab(Tab::Jobs))
+                .chain(skipped_failing_notice(&skipped))
+                .collect()
+        }
+
+        Message::QueryParamInputChar(c) => {
+            if let Some(input) = model.query.parameter_input.as_mut() {
+                if let Some(value) = input.focused_mut() {
+                    value.push(c);
                 }
-            });
+                input.error = None;
+            }
+            vec![]
+        }
+
+        Message::QueryParamInputBackspace => {
+            if let Some(input) = model.query.parameter_input.as_mut() {
+                if let Some(value) = input.focused_mut() {
+                    value.pop();
+                }
+                input.error = None;
+            }
+            vec![]
+        }
 
+        Message::QueryParamCycleFocus(forward) => {
+            if let Some(input) = model.query.parameter_input.as_mut() {
+                input.cycle_focus(forward);
+            }
             vec![]
         }
 
+        Message::QueryParamsSubmit => {
+            let text = model.query.get_text();
+            let Some(input) = model.query.parameter_input.as_mut() else {
+                return vec![];
+            };
+            let values = input.values_by_name();
+            let query = match crate::query_params::substitute(&text, &values) {
+                Ok(query) => query,
+                Err(e) => {
+                    input.error = Some(e);
+                    return vec![];
+                }
+            };
+            if let Err(e) = crate::query_params::save_values(&values) {
+                log::warn!("Failed to remember query parameter values: {}", e);
+            }
+
+            let input = model.query.parameter_input.take().expect("checked above");
+            model.query.resolved_query = Some(query);
+            model.popup = None;
+            if input.watch {
+                vec![Message::ExecuteWatch(input.job_name)]
+            } else {
+                vec![Message::ExecuteQuery(input.job_name)]
+            }
+        }
+
         Message::QueryOpenLoadPanel => {
             // Open load panel if we have jobs
             if model.jobs.jobs.is_empty() {
@@ -598,21 +1587,39 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             vec![]
         }
 
+        Message::QueryNewBuffer => {
+            model.query.new_buffer();
+            vec![]
+        }
+
+        Message::QueryNextBuffer => {
+            model.query.next_buffer();
+            vec![]
+        }
+
+        Message::QueryPrevBuffer => {
+            model.query.prev_buffer();
+            vec![]
+        }
+
+        Message::QueryCloseBuffer => {
+            model.query.close_buffer();
+            vec![]
+        }
+
         // === Jobs ===
         Message::JobsPrevious => {
-            let selected = model.jobs.table_state.selected().unwrap_or(0);
-            if selected > 0 {
-                model.jobs.table_state.select(Some(selected - 1));
-            }
+            model.jobs.select_previous();
             vec![]
         }
 
         Message::JobsNext => {
-            let selected = model.jobs.table_state.selected().unwrap_or(0);
-            let max = model.jobs.jobs.len().saturating_sub(1);
-            if selected < max {
-                model.jobs.table_state.select(Some(selected + 1));
-            }
+            model.jobs.select_next();
+            vec![]
+        }
+
+        Message::JobsJump(jump) => {
+            model.jobs.select_jump(jump);
             vec![]
         }
 
@@ -648,7 +1655,10 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
             // Only retry failed or completed jobs
             use crate::tui::model::jobs::JobStatus;
-            if !matches!(job.status, JobStatus::Failed | JobStatus::Completed) {
+            if !matches!(
+                job.status,
+                JobStatus::Failed | JobStatus::Completed | JobStatus::CompletedWithWarnings
+            ) {
                 return vec![Message::ShowError(
                     "Can only retry failed or completed jobs".to_string(),
                 )];
@@ -660,6 +1670,11 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                     "Job cannot be retried (missing context)".to_string(),
                 )];
             };
+            let query_info = job
+                .query_name
+                .clone()
+                .map(|name| (name, job.description.clone()));
+            let severity = job.severity;
 
             // Create new job entry with retry context and capture its ID
             let preview = retry_ctx.query.chars().take(200).collect(); // Use 200 chars like elsewhere
@@ -668,6 +1683,13 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                 preview,
                 retry_ctx.clone(),
             );
+            if let Some((name, description)) = query_info {
+                model.jobs.set_query_info(new_job_id, name, description);
+            }
+            model.jobs.set_severity(new_job_id, severity);
+            model
+                .jobs
+                .set_priority(new_job_id, JobPriority::from_severity(severity));
 
             // Auto-select the new job for visibility (it's at the end of the list)
             let new_job_idx = model.jobs.jobs.len() - 1;
@@ -677,34 +1699,7 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             model.sessions.mark_dirty();
 
             // Execute in background (same pattern as QueryExecute)
-            let client = model.client.clone();
-            let workspace = retry_ctx.workspace.clone();
-            let query = retry_ctx.query.clone();
-            let settings = retry_ctx.settings.clone();
-            let update_tx = model.job_update_tx.clone();
-
-            tokio::spawn(async move {
-                let results = QueryJobBuilder::new()
-                    .workspaces(vec![workspace])
-                    .queries(vec![query])
-                    .settings(settings)
-                    .execute(&client)
-                    .await;
-
-                match results {
-                    Ok(mut results) if !results.is_empty() => {
-                        let result = results.remove(0);
-                        let _ = update_tx.send(crate::tui::model::JobUpdateMessage::Completed(
-                            new_job_id, // Use job ID, not index!
-                            result,
-                        ));
-                    }
-                    Err(e) => {
-                        error!("Retry execution error: {}", e);
-                    }
-                    _ => {}
-                }
-            });
+            spawn_scheduled_job(model, new_job_id, retry_ctx);
 
             // Close popup, switch to Jobs tab to show progress
             vec![Message::ClosePopup, Message::SwitchTab(Tab::Jobs)]
@@ -712,19 +1707,17 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
         // === Sessions ===
         Message::SessionsPrevious => {
-            let selected = model.sessions.table_state.selected().unwrap_or(0);
-            if selected > 0 {
-                model.sessions.table_state.select(Some(selected - 1));
-            }
+            model.sessions.previous();
             vec![]
         }
 
         Message::SessionsNext => {
-            let selected = model.sessions.table_state.selected().unwrap_or(0);
-            let max = model.sessions.sessions.len().saturating_sub(1);
-            if selected < max {
-                model.sessions.table_state.select(Some(selected + 1));
-            }
+            model.sessions.next();
+            vec![]
+        }
+
+        Message::SessionsJump(jump) => {
+            model.sessions.jump(jump);
             vec![]
         }
 
@@ -776,6 +1769,32 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                 return vec![];
             };
 
+            // Only write sessions this instance holds the lock on
+            let new_lock = if Some(&session_name) == model.sessions.current_session_name.as_ref() {
+                if let Some(reason) = model.sessions.save_blocker() {
+                    model.popup = None;
+                    return vec![Message::ShowError(reason)];
+                }
+                None
+            } else {
+                match crate::session::Session::file_path(&session_name)
+                    .and_then(|path| FileLock::acquire(&path))
+                {
+                    Ok(Acquire::Acquired(lock)) => Some(lock),
+                    Ok(Acquire::HeldBy(holder)) => {
+                        model.popup = None;
+                        return vec![Message::ShowError(format!(
+                            "Session '{}' is open in another instance ({})",
+                            session_name, holder
+                        ))];
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to lock session '{}': {}", session_name, e);
+                        None
+                    }
+                }
+            };
+
             // CRITICAL: Drain all pending job updates before saving session
             // This ensures we capture the latest state of all jobs, including
             // completion messages that may have arrived while the UI was busy
@@ -808,11 +1827,21 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             if Some(&session_name) == model.sessions.current_session_name.as_ref() {
                 session.touch();
             }
+            session.tags = model.sessions.current_tags.clone();
+            session.description = model.sessions.current_description.clone();
+            session.selected_workspaces = Some(match &model.workspaces.pending_selection {
+                // The workspace list hasn't loaded, so keep the selection the session had
+                Some(ids) => ids.clone(),
+                None => model.workspaces.selected_resource_ids(),
+            });
 
             // Save to disk
             match session.save() {
                 Ok(_) => {
                     model.sessions.set_current_session(Some(session_name));
+                    if new_lock.is_some() {
+                        model.sessions.set_lock(new_lock, false);
+                    }
                     model.sessions.mark_saved();
                     model.popup = None;
                     vec![Message::SessionsRefresh]
@@ -836,41 +1865,75 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
             let session_name = selected_session.name.clone();
 
-            // Load session from disk
-            match crate::session::Session::load(&session_name) {
-                Ok(session) => {
-                    // Apply settings
-                    session.apply_to_settings(&mut model.settings);
-
-                    // Rebuild client with loaded settings
-                    if let Err(e) = model.rebuild_client() {
-                        return vec![Message::ShowError(format!(
-                            "Failed to update client settings: {}",
-                            e
-                        ))];
-                    }
+            // Lock the session so another instance can't overwrite it
+            let lock = match crate::session::Session::file_path(&session_name)
+                .and_then(|path| FileLock::acquire(&path))
+            {
+                Ok(Acquire::Acquired(lock)) => Some(lock),
+                Ok(Acquire::HeldBy(holder)) => {
+                    model.lock_conflict = Some(LockConflict {
+                        file: LockedFile::Session(session_name),
+                        holder,
+                    });
+                    model.popup = Some(Popup::LockConflict);
+                    return vec![];
+                }
+                Err(e) => {
+                    log::warn!("Failed to lock session '{}': {}", session_name, e);
+                    None
+                }
+            };
+            open_session(model, session_name, lock, false)
+        }
 
-                    // Load jobs - pass mutable reference to next_id generator
-                    model.jobs.jobs = session.to_job_states(model.jobs.next_job_id_mut());
-                    // Sort jobs by timestamp (newest first)
-                    model.jobs.sort_by_timestamp();
-                    // If jobs were loaded, select the first one
-                    if !model.jobs.jobs.is_empty() {
-                        model.jobs.table_state.select(Some(0));
-                    } else {
-                        model.jobs.table_state.select(None);
+        Message::ResolveLockConflict { take_over } => {
+            model.popup = None;
+            let Some(conflict) = model.lock_conflict.take() else {
+                return vec![];
+            };
+            let path = match &conflict.file {
+                LockedFile::Session(name) => crate::session::Session::file_path(name),
+                LockedFile::Pack { .. } => model
+                    .packs
+                    .get_selected_entry()
+                    .map(|entry| entry.path.clone())
+                    .ok_or_else(|| KqlPanopticonError::Other("No pack selected".to_string())),
+            };
+            let lock = match path {
+                Ok(path) if take_over => match FileLock::take_over(&path) {
+                    Ok(lock) => Some(lock),
+                    Err(e) => {
+                        return vec![Message::ShowError(format!("Failed to take over: {}", e))]
                     }
+                },
+                Ok(_) => None,
+                Err(e) => return vec![Message::ShowError(e.to_string())],
+            };
+            match conflict.file {
+                LockedFile::Session(name) => open_session(model, name, lock, !take_over),
+                LockedFile::Pack { query_index } => {
+                    open_pack_query(model, query_index, lock.map(Arc::new), !take_over)
+                }
+            }
+        }
 
-                    // Load pack origin (if any)
-                    model
-                        .sessions
-                        .set_pack_origin(session.created_from_pack.clone());
-
-                    // Set as current session
-                    model.sessions.set_current_session(Some(session_name));
-                    vec![Message::SessionsRefresh]
+        Message::SessionsDelete if !model.sessions.marked.is_empty() => {
+            // Bulk delete asks first; output folders are kept (D deletes them too)
+            let names = model.sessions.bulk_targets();
+            if names.is_empty() {
+                return vec![Message::ShowError("No saved session marked".to_string())];
+            }
+            match crate::retention::all_sessions() {
+                Ok(all) => {
+                    let plan = crate::retention::CleanPlan::sessions_only(&names, &all);
+                    model.sessions.pending_delete = Some(plan);
+                    model.popup = Some(Popup::ConfirmSessionsDelete);
+                    vec![]
                 }
-                Err(e) => vec![Message::ShowError(format!("Failed to load session: {}", e))],
+                Err(e) => vec![Message::ShowError(format!(
+                    "Failed to measure sessions: {}",
+                    e
+                ))],
             }
         }
 
@@ -880,6 +1943,9 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             };
 
             let session_name = selected_session.name.clone();
+            if let Some(message) = locked_session_error(&session_name) {
+                return vec![message];
+            }
 
             // Don't delete current session if it's unsaved
             if Some(&session_name) == model.sessions.current_session_name.as_ref() {
@@ -899,180 +1965,1449 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             }
         }
 
-        Message::SessionExportAsPack => {
-            let Some(selected_session) = model.sessions.get_selected_session() else {
-                return vec![Message::ShowError("No session selected".to_string())];
-            };
+        Message::SessionsToggleMark => {
+            model.sessions.toggle_mark_selected();
+            vec![Message::SessionsNext]
+        }
 
-            let session_name = selected_session.name.clone();
+        Message::SessionsMarkAll => {
+            model.sessions.mark_all();
+            vec![]
+        }
 
-            // Load session from disk
-            let session = match crate::session::Session::load(&session_name) {
-                Ok(s) => s,
-                Err(e) => {
-                    return vec![Message::ShowError(format!("Failed to load session: {}", e))]
-                }
-            };
+        Message::SessionsUnmarkAll => {
+            model.sessions.marked.clear();
+            vec![]
+        }
 
-            // Convert to query pack
-            let pack = match session.to_query_pack() {
-                Ok(p) => p,
-                Err(e) => {
-                    return vec![Message::ShowError(format!(
-                        "Failed to convert to pack: {}",
-                        e
-                    ))]
+        Message::SessionsStartDeleteMarked => {
+            let names = model.sessions.bulk_targets();
+            if names.is_empty() {
+                return vec![Message::ShowError("No saved session selected".to_string())];
+            }
+            match crate::retention::all_sessions() {
+                Ok(all) => {
+                    let plan = crate::retention::CleanPlan::for_sessions(&names, &all);
+                    model.sessions.pending_delete = Some(plan);
+                    model.popup = Some(Popup::ConfirmSessionsDelete);
+                    vec![]
                 }
-            };
-
-            // Generate output filename (remove timestamp suffix if present)
-            let pack_name = session_name
-                .rsplit_once('_')
-                .and_then(|(prefix, suffix)| {
-                    if suffix.chars().all(|c| c.is_ascii_digit()) && suffix.len() >= 6 {
-                        Some(prefix)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or(&session_name);
+                Err(e) => vec![Message::ShowError(format!(
+                    "Failed to measure sessions: {}",
+                    e
+                ))],
+            }
+        }
 
-            let output_path = match crate::query_pack::QueryPack::get_library_path(&format!(
-                "{}.yaml",
-                pack_name
-            )) {
-                Ok(p) => p,
-                Err(e) => {
-                    return vec![Message::ShowError(format!(
-                        "Failed to get output path: {}",
-                        e
-                    ))]
-                }
+        Message::SessionsDeleteMarked => {
+            model.popup = None;
+            let Some(plan) = model.sessions.pending_delete.take() else {
+                return vec![];
             };
 
-            // Ensure parent directory exists
-            if let Some(parent) = output_path.parent() {
-                if let Err(e) = std::fs::create_dir_all(parent) {
-                    return vec![Message::ShowError(format!(
-                        "Failed to create directory: {}",
-                        e
-                    ))];
-                }
+            let current = model.sessions.current_session_name.clone();
+            if current.is_some_and(|name| plan.sessions.contains(&name)) {
+                model.sessions.set_current_session(None);
+                model.jobs.jobs.clear();
+                model.jobs.table_state.select(None);
             }
-
-            // Save pack
-            match pack.save_to_file(&output_path) {
-                Ok(()) => {
-                    // Refresh packs list to show the new pack
-                    // Note: Success is indicated by the pack appearing in the Packs tab
-                    vec![Message::PacksRefresh]
-                }
-                Err(e) => vec![Message::ShowError(format!("Failed to save pack: {}", e))],
+            model.sessions.marked.clear();
+
+            match plan.apply() {
+                Ok(()) => vec![
+                    Message::ShowSuccess(format!(
+                        "Deleted {} session(s), freed {}",
+                        plan.sessions.len(),
+                        crate::units::format_bytes(plan.bytes)
+                    )),
+                    Message::SessionsRefresh,
+                ],
+                Err(e) => vec![
+                    Message::ShowError(format!("Failed to delete sessions: {}", e)),
+                    Message::SessionsRefresh,
+                ],
             }
         }
 
-        // === Query Packs ===
-        Message::PacksPrevious => {
-            model.packs.previous();
+        Message::JobsStopWatch => match model.jobs.stop_selected_watch() {
+            Some(workspace) => vec![Message::ShowSuccess(format!(
+                "Stopped watching {}",
+                workspace
+            ))],
+            None => vec![Message::ShowError(
+                "Selected job is not an active watch".to_string(),
+            )],
+        },
+
+        Message::JobsInspectRows(job_idx) => {
+            let Some(job) = model.jobs.jobs.get(job_idx) else {
+                return vec![Message::ShowError("Job not found".to_string())];
+            };
+            let title = match job.display_name() {
+                Some(name) => format!("{} - {}", job.workspace_name, name),
+                None => job.workspace_name.clone(),
+            };
+            let preview = crate::tui::model::query::ResultPreview::of_job(job);
+            open_row_inspector(model, title, &preview)
+        }
+
+        Message::RowInspectorMove(delta) => {
+            if let Some(inspector) = &mut model.jobs.row_inspector {
+                inspector.move_cursor(delta);
+            }
             vec![]
         }
 
-        Message::PacksNext => {
-            model.packs.next();
+        Message::RowInspectorToggle => {
+            if let Some(inspector) = &mut model.jobs.row_inspector {
+                inspector.toggle();
+            }
             vec![]
         }
 
-        Message::PacksRefresh => {
-            model.packs.refresh();
+        Message::RowInspectorExpand(expand) => {
+            if let Some(inspector) = &mut model.jobs.row_inspector {
+                inspector.expand(expand);
+            }
             vec![]
         }
 
-        Message::PacksLoadDetails => {
-            // Lazy load the selected pack
-            if let Err(e) = model.packs.load_selected_pack() {
-                vec![Message::ShowError(format!("Failed to load pack: {}", e))]
-            } else {
-                vec![]
+        Message::RowInspectorStepRow(delta) => {
+            if let Some(inspector) = &mut model.jobs.row_inspector {
+                inspector.step_row(delta);
             }
+            vec![]
         }
 
-        Message::PacksLoadQuery => {
-            // First ensure the pack is loaded
+        Message::JobsShowChart(job_idx) => {
+            use crate::tui::model::chart::{ChartData, ChartStyle, JobChart};
+
+            let Some(job) = model.jobs.jobs.get(job_idx) else {
+                return vec![Message::ShowError("Job not found".to_string())];
+            };
+            let Some(Ok(success)) = job.result.as_ref().map(|r| r.result.as_ref()) else {
+                return vec![Message::ShowError(
+                    "Only completed jobs can be charted".to_string(),
+                )];
+            };
+
+            match ChartData::load(&success.output_path) {
+                Ok(data) => {
+                    model.jobs.chart = Some(JobChart {
+                        title: format!(
+                            "{} - {}",
+                            job.workspace_name,
+                            job.retry_context
+                                .as_ref()
+                                .map(|ctx| ctx.settings.job_name.as_str())
+                                .unwrap_or("results")
+                        ),
+                        data,
+                        style: ChartStyle::Bar,
+                    });
+                    model.popup = Some(Popup::JobChart);
+                    vec![]
+                }
+                Err(e) => vec![Message::ShowError(format!("Cannot chart results: {}", e))],
+            }
+        }
+
+        Message::JobsCopyDetails(job_idx) => {
+            let Some(job) = model.jobs.jobs.get(job_idx) else {
+                return vec![Message::ShowError("Job not found".to_string())];
+            };
+
+            let (label, text) = match (&job.error, job.result.as_ref().map(|r| &r.result)) {
+                (Some(error), _) => ("error message", error.detailed_description()),
+                (None, Some(Ok(success))) => {
+                    ("output path", success.output_path.display().to_string())
+                }
+                _ => {
+                    return vec![Message::ShowError(
+                        "Job has no output path or error to copy yet".to_string(),
+                    )]
+                }
+            };
+
+            match model.clipboard.copy(&text) {
+                Ok(()) => vec![Message::ShowSuccess(format!(
+                    "Copied {} to clipboard",
+                    label
+                ))],
+                Err(e) => vec![Message::ShowError(e)],
+            }
+        }
+
+        Message::JobsOpenOutput(job_idx) | Message::JobsRevealOutput(job_idx) => {
+            let reveal = matches!(message, Message::JobsRevealOutput(_));
+            let output_path = model
+                .jobs
+                .jobs
+                .get(job_idx)
+                .and_then(|job| job.result.as_ref())
+                .and_then(|r| r.result.as_ref().ok())
+                .map(|success| success.output_path.clone());
+
+            let Some(path) = output_path else {
+                return vec![Message::ShowError("Job has no output file yet".to_string())];
+            };
+
+            let result = if reveal {
+                crate::tui::opener::reveal_path(&path)
+            } else {
+                crate::tui::opener::open_path(&path)
+            };
+            match result {
+                Ok(()) => vec![Message::ShowInfo(format!(
+                    "{} {}",
+                    if reveal { "Revealed" } else { "Opened" },
+                    path.display()
+                ))],
+                Err(e) => vec![Message::ShowError(e)],
+            }
+        }
+
+        Message::JobsChartToggleStyle => {
+            if let Some(chart) = &mut model.jobs.chart {
+                chart.style = chart.style.toggle();
+            }
+            vec![]
+        }
+
+        Message::JobsToggleMark => {
+            model.jobs.toggle_mark_selected();
+            // Advance so consecutive jobs can be marked quickly
+            vec![Message::JobsNext]
+        }
+
+        Message::JobsStartExportMarked => {
+            if model.jobs.marked.is_empty() {
+                return vec![Message::ShowError(
+                    "No jobs marked. Press Space to mark jobs first.".to_string(),
+                )];
+            }
+            model.jobs.pack_name_input = Some(String::new());
+            model.popup = Some(Popup::PackNameInput);
+            vec![]
+        }
+
+        Message::PackNameInputChar(c) => {
+            if let Some(ref mut input) = model.jobs.pack_name_input {
+                input.push(c);
+            }
+            vec![]
+        }
+
+        Message::PackNameInputBackspace => {
+            if let Some(ref mut input) = model.jobs.pack_name_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::JobsCycleTriage => {
+            if model.jobs.cycle_selected_triage() {
+                // The verdict may hide the job under the current filter
+                model.jobs.keep_selection_visible();
+            }
+            vec![]
+        }
+
+        Message::JobsCycleTriageFilter => {
+            model.jobs.cycle_triage_filter();
+            vec![]
+        }
+
+        Message::JobsToggleSeveritySort => {
+            model.jobs.toggle_severity_sort();
+            vec![]
+        }
+
+        Message::JobsChangePriority(raise) => match model.jobs.change_selected_priority(raise) {
+            Some(priority) => vec![Message::ShowSuccess(format!(
+                "Job priority set to {}",
+                priority.as_str()
+            ))],
+            None => vec![Message::ShowError(
+                "Only queued jobs can be reprioritized".to_string(),
+            )],
+        },
+
+        Message::JobsColumnsOpen => {
+            model.jobs.column_picker = Some(JobColumnPicker::new(&model.jobs.columns));
+            model.popup = Some(Popup::JobColumns);
+            vec![]
+        }
+
+        Message::JobsColumnsNavigate(delta) => {
+            if let Some(picker) = &mut model.jobs.column_picker {
+                picker.navigate(delta);
+            }
+            vec![]
+        }
+
+        Message::JobsColumnsToggle => {
+            if let Some(picker) = &mut model.jobs.column_picker {
+                picker.toggle_selected();
+            }
+            vec![]
+        }
+
+        Message::JobsColumnsMove(delta) => {
+            if let Some(picker) = &mut model.jobs.column_picker {
+                picker.move_selected(delta);
+            }
+            vec![]
+        }
+
+        Message::JobsColumnsApply => {
+            let Some(picker) = model.jobs.column_picker.take() else {
+                return vec![];
+            };
+            model.popup = None;
+
+            let columns = picker.shown();
+            if columns.is_empty() {
+                return vec![Message::ShowError(
+                    "The jobs table needs at least one column".to_string(),
+                )];
+            }
+            model.jobs.columns = columns;
+
+            let saved = crate::config::Config::load().and_then(|mut config| {
+                config.jobs_columns = model
+                    .jobs
+                    .columns
+                    .iter()
+                    .map(|column| column.as_str().to_string())
+                    .collect();
+                config.save()
+            });
+            match saved {
+                Ok(()) => vec![],
+                Err(e) => vec![Message::ShowError(format!("Failed to save config: {}", e))],
+            }
+        }
+
+        Message::JobsStartResultSearch => {
+            model.jobs.result_search = Some(ResultSearch::default());
+            model.popup = Some(Popup::ResultSearch);
+            vec![]
+        }
+
+        Message::ResultSearchInputChar(c) => {
+            if let Some(search) = &mut model.jobs.result_search {
+                search.input.push(c);
+                search.reset();
+            }
+            vec![]
+        }
+
+        Message::ResultSearchInputBackspace => {
+            if let Some(search) = &mut model.jobs.result_search {
+                search.input.pop();
+                search.reset();
+            }
+            vec![]
+        }
+
+        Message::ResultSearchToggleRegex => {
+            if let Some(search) = &mut model.jobs.result_search {
+                search.regex = !search.regex;
+                search.reset();
+            }
+            vec![]
+        }
+
+        Message::ResultSearchNavigate(delta) => {
+            if let Some(search) = &mut model.jobs.result_search {
+                search.navigate(delta);
+            }
+            vec![]
+        }
+
+        Message::ResultSearchSubmit => {
+            let targets = model.jobs.output_files();
+            let Some(search) = &mut model.jobs.result_search else {
+                return vec![];
+            };
+
+            if search.results.is_none() {
+                match crate::result_search::matcher(&search.input, search.regex) {
+                    Ok(matcher) => {
+                        search.results = Some(crate::result_search::search(&targets, &matcher));
+                    }
+                    Err(e) => search.error = Some(e.to_string()),
+                }
+                return vec![];
+            }
+
+            // Jump to the job that wrote the selected file
+            let Some(path) = search.selected_match().map(|found| found.path.clone()) else {
+                return vec![];
+            };
+            let Some(job_idx) = model.jobs.job_with_output(&path) else {
+                return vec![Message::ShowError("Job no longer in the list".to_string())];
+            };
+            model.jobs.result_search = None;
+            if !model.jobs.visible_indices().contains(&job_idx) {
+                model.jobs.triage_filter = TriageFilter::All;
+            }
+            model.jobs.table_state.select(Some(job_idx));
+            model.current_tab = Tab::Jobs;
+            model.popup = Some(Popup::JobDetails(job_idx));
+            vec![]
+        }
+
+        Message::JobsStartNote => {
+            let Some(job) = model.jobs.get_selected_job() else {
+                return vec![];
+            };
+            model.jobs.note_input = Some(job.note.clone());
+            model.popup = Some(Popup::JobNoteInput);
+            vec![]
+        }
+
+        Message::NoteInputChar(c) => {
+            if let Some(ref mut input) = model.jobs.note_input {
+                input.push(c);
+            }
+            vec![]
+        }
+
+        Message::NoteInputBackspace => {
+            if let Some(ref mut input) = model.jobs.note_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::JobsSetNote(note) => {
+            model.popup = None;
+            model.jobs.note_input = None;
+            if let Some(idx) = model.jobs.table_state.selected() {
+                if let Some(job) = model.jobs.jobs.get_mut(idx) {
+                    job.note = note.trim().to_string();
+                }
+            }
+            vec![]
+        }
+
+        Message::JobsStartPostFilter(job_idx) => {
+            let has_output = model
+                .jobs
+                .jobs
+                .get(job_idx)
+                .and_then(|job| job.result.as_ref())
+                .is_some_and(|r| r.result.is_ok());
+            if !has_output {
+                return vec![Message::ShowError(
+                    "Only completed jobs can be filtered".to_string(),
+                )];
+            }
+            let saved = crate::config::Config::load_or_default().post_filters;
+            model.jobs.post_filter = Some(crate::tui::model::jobs::PostFilterInput::new(
+                job_idx, saved,
+            ));
+            model.popup = Some(Popup::PostFilterInput);
+            vec![]
+        }
+
+        Message::PostFilterInputChar(c) => {
+            if let Some(ref mut input) = model.jobs.post_filter {
+                input.text.push(c);
+            }
+            vec![]
+        }
+
+        Message::PostFilterInputBackspace => {
+            if let Some(ref mut input) = model.jobs.post_filter {
+                input.text.pop();
+            }
+            vec![]
+        }
+
+        Message::PostFilterHistoryOlder => {
+            if let Some(ref mut input) = model.jobs.post_filter {
+                input.recall_older();
+            }
+            vec![]
+        }
+
+        Message::PostFilterHistoryNewer => {
+            if let Some(ref mut input) = model.jobs.post_filter {
+                input.recall_newer();
+            }
+            vec![]
+        }
+
+        Message::JobsApplyPostFilter(job_idx, expression) => {
+            use crate::post_filter::PostFilter;
+
+            let expression = expression.trim().to_string();
+            let filter = match PostFilter::parse(&expression) {
+                Ok(filter) => filter,
+                Err(e) => return vec![Message::ShowError(e.to_string())],
+            };
+            let output_path = model
+                .jobs
+                .jobs
+                .get(job_idx)
+                .and_then(|job| job.result.as_ref())
+                .and_then(|r| r.result.as_ref().ok())
+                .map(|success| success.output_path.clone());
+            let Some(path) = output_path else {
+                return vec![Message::ShowError("Job has no output file yet".to_string())];
+            };
+
+            match filter.apply_to_file(&path, &expression) {
+                Ok(output) => {
+                    model.popup = None;
+                    model.jobs.post_filter = None;
+                    if let Err(e) = crate::post_filter::save_filter(&expression) {
+                        log::warn!("Failed to save post-filter: {}", e);
+                    }
+                    vec![Message::ShowInfo(format!(
+                        "Kept {} of {} rows: {}",
+                        output.kept,
+                        output.total,
+                        output.path.display()
+                    ))]
+                }
+                Err(e) => vec![Message::ShowError(format!("Post-filter failed: {}", e))],
+            }
+        }
+
+        Message::JobsStartReexport(job_idx) => {
+            let has_output = model
+                .jobs
+                .jobs
+                .get(job_idx)
+                .and_then(|job| job.result.as_ref())
+                .is_some_and(|r| r.result.is_ok());
+            if !has_output {
+                return vec![Message::ShowError(
+                    "Only completed jobs can be re-exported".to_string(),
+                )];
+            }
+            model.popup = Some(Popup::ReexportFormat(job_idx));
+            vec![]
+        }
+
+        Message::JobsReexport(job_idx, format) => {
+            let output_path = model
+                .jobs
+                .jobs
+                .get(job_idx)
+                .and_then(|job| job.result.as_ref())
+                .and_then(|r| r.result.as_ref().ok())
+                .map(|success| success.output_path.clone());
+            let Some(path) = output_path else {
+                return vec![Message::ShowError("Job has no output file yet".to_string())];
+            };
+
+            match crate::reexport::reexport(&path, format) {
+                Ok(output) => {
+                    model.popup = Some(Popup::JobDetails(job_idx));
+                    vec![Message::ShowInfo(format!(
+                        "Re-exported {} rows as {}: {}",
+                        output.rows,
+                        format.label(),
+                        output.path.display()
+                    ))]
+                }
+                Err(e) => vec![Message::ShowError(format!("Re-export failed: {}", e))],
+            }
+        }
+
+        Message::JobsExportMarked(pack_name) => {
+            model.popup = None;
+            model.jobs.pack_name_input = None;
+
+            let pack_name = pack_name.trim().to_string();
+            let file_stem = paths::sanitize_file_name(&pack_name);
+            if file_stem.is_empty() {
+                return vec![Message::ShowError("Pack name cannot be empty".to_string())];
+            }
+
+            // Collect unique queries from marked jobs, in display order
+            let mut queries: Vec<crate::query_pack::PackQuery> = Vec::new();
+            let mut settings: Option<QuerySettings> = None;
+            for job in model.jobs.marked_jobs() {
+                let Some(ctx) = &job.retry_context else {
+                    continue;
+                };
+                if queries.iter().any(|q| q.query == ctx.query) {
+                    continue;
+                }
+
+                // Keep query names unique within the pack
+                let base_name = job
+                    .display_name()
+                    .unwrap_or(&ctx.settings.job_name)
+                    .to_string();
+                let mut query_name = base_name.clone();
+                let mut suffix = 2;
+                while queries.iter().any(|q| q.name == query_name) {
+                    query_name = format!("{} ({})", base_name, suffix);
+                    suffix += 1;
+                }
+
+                queries.push(crate::query_pack::PackQuery {
+                    name: query_name,
+                    description: Some(
+                        job.description
+                            .clone()
+                            .unwrap_or_else(|| format!("From workspace: {}", job.workspace_name)),
+                    ),
+                    query: ctx.query.clone(),
+                    query_file: None,
+                    tags: Vec::new(),
+                    mitre: Vec::new(),
+                    severity: job.severity,
+                    timeout_secs: None,
+                    retry_count: None,
+                    max_rows: None,
+                });
+
+                if settings.is_none() {
+                    settings = Some(QuerySettings {
+                        job_name: "exported-query".to_string(),
+                        timeout_secs: None,
+                        retry_count: None,
+                        ..ctx.settings.clone()
+                    });
+                }
+            }
+
+            if queries.is_empty() {
+                return vec![Message::ShowError(
+                    "Marked jobs contain no queries to export".to_string(),
+                )];
+            }
+
+            let output_path = match crate::query_pack::QueryPack::get_library_path(&format!(
+                "{}.yaml",
+                file_stem
+            )) {
+                Ok(p) => p,
+                Err(e) => {
+                    return vec![Message::ShowError(format!(
+                        "Failed to get output path: {}",
+                        e
+                    ))]
+                }
+            };
+
+            if output_path.exists() {
+                return vec![Message::ShowError(format!(
+                    "Pack already exists: {}",
+                    output_path.display()
+                ))];
+            }
+
+            if let Some(parent) = output_path.parent() {
+                if let Err(e) = std::fs::create_dir_all(parent) {
+                    return vec![Message::ShowError(format!(
+                        "Failed to create directory: {}",
+                        e
+                    ))];
+                }
+            }
+
+            let query_count = queries.len();
+            let pack = crate::query_pack::QueryPack::from_queries(
+                pack_name,
+                Some("Exported from selected jobs".to_string()),
+                queries,
+                settings,
+            );
+
+            match pack.save_to_file(&output_path) {
+                Ok(()) => {
+                    model.jobs.marked.clear();
+                    vec![
+                        Message::PacksRefresh,
+                        Message::ShowSuccess(format!(
+                            "Exported {} quer{} to {}",
+                            query_count,
+                            if query_count == 1 { "y" } else { "ies" },
+                            output_path.display()
+                        )),
+                    ]
+                }
+                Err(e) => vec![Message::ShowError(format!("Failed to save pack: {}", e))],
+            }
+        }
+
+        Message::SessionsStartRename => {
+            let Some(selected_session) = model.sessions.get_selected_session() else {
+                return vec![Message::ShowError("No session selected".to_string())];
+            };
+            model.sessions.rename_input = Some(selected_session.name.clone());
+            model.popup = Some(Popup::SessionRename);
+            vec![]
+        }
+
+        Message::SessionRenameInputChar(c) => {
+            if let Some(ref mut input) = model.sessions.rename_input {
+                input.push(c);
+            }
+            vec![]
+        }
+
+        Message::SessionRenameInputBackspace => {
+            if let Some(ref mut input) = model.sessions.rename_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::SessionsRename(new_name) => {
+            model.popup = None;
+            model.sessions.rename_input = None;
+
+            let Some(selected_session) = model.sessions.get_selected_session() else {
+                return vec![Message::ShowError("No session selected".to_string())];
+            };
+            let old_name = selected_session.name.clone();
+            let new_name = new_name.trim().to_string();
+            if new_name == old_name {
+                return vec![];
+            }
+            if model.sessions.sessions.iter().any(|s| s.name == new_name) {
+                return vec![Message::ShowError(format!(
+                    "Session '{}' already exists",
+                    new_name
+                ))];
+            }
+            if let Some(message) = locked_session_error(&old_name) {
+                return vec![message];
+            }
+
+            // A current session that was never saved has no file to rename yet
+            if selected_session.state != crate::tui::model::session::SessionState::CurrentNeverSaved
+            {
+                if let Err(e) = crate::session::Session::rename(&old_name, &new_name) {
+                    return vec![Message::ShowError(format!(
+                        "Failed to rename session: {}",
+                        e
+                    ))];
+                }
+            }
+
+            model.sessions.apply_rename(&old_name, &new_name);
+            // The current session's lock follows it to the new file name
+            if model.sessions.current_lock.is_some()
+                && model.sessions.current_session_name.as_deref() == Some(new_name.as_str())
+            {
+                if let Ok(Acquire::Acquired(lock)) = crate::session::Session::file_path(&new_name)
+                    .and_then(|path| FileLock::acquire(&path))
+                {
+                    model.sessions.set_lock(Some(lock), false);
+                }
+            }
+            vec![
+                Message::SessionsRefresh,
+                Message::ShowSuccess(format!("Renamed session '{}' to '{}'", old_name, new_name)),
+            ]
+        }
+
+        Message::SessionsStartEditDetails => {
+            let Some(selected_session) = model.sessions.get_selected_session() else {
+                return vec![Message::ShowError("No session selected".to_string())];
+            };
+            model.sessions.details_input = Some(SessionDetailsInput {
+                session: selected_session.name.clone(),
+                tags: selected_session.tags.join(", "),
+                description: selected_session.description.clone(),
+                editing_description: false,
+            });
+            model.popup = Some(Popup::SessionDetails);
+            vec![]
+        }
+
+        Message::SessionDetailsInputChar(c) => {
+            if let Some(ref mut input) = model.sessions.details_input {
+                input.focused_mut().push(c);
+            }
+            vec![]
+        }
+
+        Message::SessionDetailsInputBackspace => {
+            if let Some(ref mut input) = model.sessions.details_input {
+                input.focused_mut().pop();
+            }
+            vec![]
+        }
+
+        Message::SessionDetailsToggleField => {
+            if let Some(ref mut input) = model.sessions.details_input {
+                input.editing_description = !input.editing_description;
+            }
+            vec![]
+        }
+
+        Message::SessionsSaveDetails => {
+            model.popup = None;
+            let Some(input) = model.sessions.details_input.take() else {
+                return vec![];
+            };
+            let tags = parse_tags(&input.tags);
+            let description = input.description.trim().to_string();
+
+            // A current session that was never saved gets its details when it is first saved
+            let saved = model
+                .sessions
+                .sessions
+                .iter()
+                .any(|s| s.name == input.session && s.last_saved.is_some());
+            if saved {
+                if let Err(e) = crate::session::Session::set_details(
+                    &input.session,
+                    tags.clone(),
+                    description.clone(),
+                ) {
+                    return vec![Message::ShowError(format!(
+                        "Failed to update session: {}",
+                        e
+                    ))];
+                }
+            }
+            if model.sessions.current_session_name.as_deref() == Some(input.session.as_str()) {
+                model.sessions.set_current_details(tags, description);
+            }
+
+            vec![
+                Message::SessionsRefresh,
+                Message::ShowSuccess(format!("Updated details of session '{}'", input.session)),
+            ]
+        }
+
+        Message::LogsPrevious => {
+            model.logs.scroll(-1);
+            vec![]
+        }
+
+        Message::LogsNext => {
+            model.logs.scroll(1);
+            vec![]
+        }
+
+        Message::LogsJump(jump) => {
+            model.logs.jump(jump);
+            vec![]
+        }
+
+        Message::LogsCycleLevel => {
+            model.logs.cycle_level();
+            vec![]
+        }
+
+        Message::LogsToggleFollow => {
+            model.logs.toggle_follow();
+            vec![]
+        }
+
+        Message::LogsStartSearch => {
+            model.logs.search_input = Some(model.logs.search.clone());
+            model.popup = Some(Popup::LogSearch);
+            vec![]
+        }
+
+        Message::LogSearchInputChar(c) => {
+            if let Some(ref mut input) = model.logs.search_input {
+                input.push(c);
+            }
+            vec![]
+        }
+
+        Message::LogSearchInputBackspace => {
+            if let Some(ref mut input) = model.logs.search_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::LogsSetSearch(search) => {
+            model.popup = None;
+            model.logs.search_input = None;
+            model.logs.set_search(search);
+            vec![]
+        }
+
+        Message::SessionsStartSearch => {
+            model.sessions.search_input = Some(model.sessions.search.clone());
+            model.popup = Some(Popup::SessionSearch);
+            vec![]
+        }
+
+        Message::SessionSearchInputChar(c) => {
+            if let Some(ref mut input) = model.sessions.search_input {
+                input.push(c);
+            }
+            vec![]
+        }
+
+        Message::SessionSearchInputBackspace => {
+            if let Some(ref mut input) = model.sessions.search_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::SessionsSetSearch(search) => {
+            model.popup = None;
+            model.sessions.search_input = None;
+            model.sessions.set_search(search);
+            vec![]
+        }
+
+        Message::SessionExportAsPack => {
+            let names = model.sessions.bulk_targets();
+            match names.as_slice() {
+                [] => vec![Message::ShowError("No saved session selected".to_string())],
+                [name] => match export_session_as_pack(name) {
+                    // Success is indicated by the pack appearing in the Packs tab
+                    Ok(_) => vec![Message::PacksRefresh],
+                    Err(e) => vec![Message::ShowError(e)],
+                },
+                _ => {
+                    let mut exported = 0;
+                    let mut failures = Vec::new();
+                    for name in &names {
+                        match export_session_as_pack(name) {
+                            Ok(_) => exported += 1,
+                            Err(e) => failures.push(format!("{}: {}", name, e)),
+                        }
+                    }
+                    let mut messages = vec![Message::PacksRefresh];
+                    if failures.is_empty() {
+                        messages.push(Message::ShowSuccess(format!(
+                            "Exported {} session(s) as query packs",
+                            exported
+                        )));
+                    } else {
+                        messages.push(Message::ShowError(format!(
+                            "Exported {} of {} session(s):\n{}",
+                            exported,
+                            names.len(),
+                            failures.join("\n")
+                        )));
+                    }
+                    messages
+                }
+            }
+        }
+
+        // === Fuzzy finder ===
+        Message::FinderOpen => {
+            // Query names are only known once every pack is parsed
+            model.packs.load_all_packs();
+            model.finder = Some(FinderModel::new(&model.packs, &model.sessions));
+            model.popup = Some(Popup::Finder);
+            vec![]
+        }
+
+        Message::FinderInputChar(c) => {
+            if let Some(finder) = model.finder.as_mut() {
+                finder.push_char(c);
+            }
+            vec![]
+        }
+
+        Message::FinderInputBackspace => {
+            if let Some(finder) = model.finder.as_mut() {
+                finder.pop_char();
+            }
+            vec![]
+        }
+
+        Message::FinderNavigate(delta) => {
+            if let Some(finder) = model.finder.as_mut() {
+                finder.navigate(delta);
+            }
+            vec![]
+        }
+
+        Message::FinderSelect(load) => {
+            let Some(target) = model.finder.as_ref().and_then(|f| f.selected_target()) else {
+                return vec![];
+            };
+            model.finder = None;
+            model.popup = None;
+
+            match target {
+                FinderTarget::Pack(pack_idx) | FinderTarget::PackQuery(pack_idx, _) => {
+                    // A tag filter hiding the pack would leave the selection invisible
+                    if !model.packs.visible_indices().contains(&pack_idx) {
+                        model.packs.set_tag_filter(Vec::new());
+                    }
+                    model.packs.table_state.select(Some(pack_idx));
+                    match (target, load) {
+                        (FinderTarget::PackQuery(_, query_idx), true) => {
+                            load_pack_query(model, query_idx)
+                        }
+                        (_, true) => load_pack_query(model, 0),
+                        _ => vec![Message::SwitchTab(Tab::Packs)],
+                    }
+                }
+                FinderTarget::Session(session_idx) => {
+                    if !model.sessions.visible_indices().contains(&session_idx) {
+                        model.sessions.set_search(String::new());
+                    }
+                    model.sessions.table_state.select(Some(session_idx));
+                    let mut messages = vec![Message::SwitchTab(Tab::Sessions)];
+                    if load {
+                        messages.push(Message::SessionsLoad);
+                    }
+                    messages
+                }
+            }
+        }
+
+        // === Query Packs ===
+        Message::PacksPrevious => {
+            model.packs.previous();
+            vec![]
+        }
+
+        Message::PacksNext => {
+            model.packs.next();
+            vec![]
+        }
+
+        Message::PacksJump(jump) => {
+            model.packs.jump(jump);
+            vec![]
+        }
+
+        Message::PacksNextQuery => {
+            // The query list needs the pack loaded
             if let Err(e) = model.packs.load_selected_pack() {
                 return vec![Message::ShowError(format!("Failed to load pack: {}", e))];
             }
+            model.packs.next_query();
+            vec![]
+        }
+
+        Message::PacksPreviousQuery => {
+            model.packs.previous_query();
+            vec![]
+        }
+
+        Message::PacksClearQuery => {
+            model.packs.clear_query_selection();
+            vec![]
+        }
+
+        Message::PacksRefresh => {
+            model.packs.refresh();
+            vec![]
+        }
+
+        Message::PacksStartRename => {
+            let Some(entry) = model.packs.get_selected_entry() else {
+                return vec![Message::ShowError("No pack selected".to_string())];
+            };
+            if entry.read_only {
+                return vec![Message::ShowError(format!(
+                    "Pack library '{}' is read-only",
+                    entry.source
+                ))];
+            }
+            let name = match &entry.pack {
+                Some(pack) => pack.name.clone(),
+                None => crate::query_pack::QueryPack::load_from_file(&entry.path)
+                    .map(|pack| pack.name)
+                    .unwrap_or_else(|_| entry.get_display_name()),
+            };
+            model.packs.rename_input = Some(name);
+            model.popup = Some(Popup::PackRename);
+            vec![]
+        }
+
+        Message::PackRenameInputChar(c) => {
+            if let Some(ref mut input) = model.packs.rename_input {
+                input.push(c);
+            }
+            vec![]
+        }
+
+        Message::PackRenameInputBackspace => {
+            if let Some(ref mut input) = model.packs.rename_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::PacksRename(new_name) => {
+            model.popup = None;
+            model.packs.rename_input = None;
+
+            let Some(entry) = model.packs.get_selected_entry() else {
+                return vec![Message::ShowError("No pack selected".to_string())];
+            };
+            if entry.read_only {
+                return vec![Message::ShowError(format!(
+                    "Pack library '{}' is read-only",
+                    entry.source
+                ))];
+            }
+            let old_path = entry.path.clone();
+            let old_relative_path = entry.relative_path.clone();
+
+            let new_name = new_name.trim().to_string();
+            let file_stem = paths::sanitize_file_name(&new_name);
+            if file_stem.is_empty() {
+                return vec![Message::ShowError("Pack name cannot be empty".to_string())];
+            }
+
+            // The file follows the new name, keeping its folder and format
+            let extension = old_path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("yaml");
+            let new_path = old_path.with_file_name(format!("{}.{}", file_stem, extension));
+            if new_path != old_path && new_path.exists() {
+                return vec![Message::ShowError(format!(
+                    "Pack already exists: {}",
+                    new_path.display()
+                ))];
+            }
+
+            let mut pack = match crate::query_pack::QueryPack::load_from_file(&old_path) {
+                Ok(pack) => pack,
+                Err(e) => return vec![Message::ShowError(format!("Failed to load pack: {}", e))],
+            };
+            pack.name = new_name.clone();
+            if let Err(e) = pack.save_to_file(&new_path) {
+                return vec![Message::ShowError(format!("Failed to rename pack: {}", e))];
+            }
+            if new_path != old_path {
+                if let Err(e) = std::fs::remove_file(&old_path) {
+                    return vec![Message::ShowError(format!(
+                        "Failed to remove old pack file: {}",
+                        e
+                    ))];
+                }
+            }
+
+            // Keep editor buffers and the session's pack origin pointing at the pack
+            model.query.rename_pack(&old_path, &new_path, &new_name);
+            if model.sessions.current_pack_origin.as_deref() == Some(old_relative_path.as_str()) {
+                let new_relative_path = std::path::Path::new(&old_relative_path)
+                    .with_file_name(new_path.file_name().unwrap_or_default())
+                    .to_string_lossy()
+                    .to_string();
+                model.sessions.set_pack_origin(Some(new_relative_path));
+                model.sessions.mark_dirty();
+            }
+
+            model.packs.refresh();
+            if let Some(idx) = model.packs.packs.iter().position(|e| e.path == new_path) {
+                model.packs.table_state.select(Some(idx));
+            }
+            vec![Message::ShowSuccess(format!(
+                "Renamed pack to '{}'",
+                new_name
+            ))]
+        }
+
+        Message::PacksPull => {
+            // The git pull runs asynchronously in the main loop
+            vec![]
+        }
+
+        Message::PacksPulled(changes) => {
+            model.packs.refresh();
+            if changes.is_empty() {
+                return vec![Message::ShowInfo("Pack library is up to date".to_string())];
+            }
+            let summary = changes.iter().map(ToString::to_string).collect::<Vec<_>>();
+            if summary.len() > PULL_TOAST_CHANGES {
+                return vec![Message::ShowInfo(format!(
+                    "Pulled {} pack change(s):\n{}",
+                    changes.len(),
+                    summary.join("\n")
+                ))];
+            }
+            vec![Message::ShowSuccess(format!(
+                "Pulled {} pack change(s): {}",
+                changes.len(),
+                summary.join(", ")
+            ))]
+        }
+
+        Message::PacksStartSearch => {
+            // Searching needs every pack's contents
+            model.packs.load_all_packs();
+            model.packs.search = Some(PackSearch::default());
+            model.popup = Some(Popup::PackSearch);
+            vec![]
+        }
+
+        Message::PackSearchInputChar(_)
+        | Message::PackSearchInputBackspace
+        | Message::PackSearchToggleRegex => {
+            let Some(search) = &mut model.packs.search else {
+                return vec![];
+            };
+            match message {
+                Message::PackSearchInputChar(c) => search.input.push(c),
+                Message::PackSearchInputBackspace => {
+                    search.input.pop();
+                }
+                _ => search.regex = !search.regex,
+            }
+            // Pack contents are in memory, so results follow the input as it's typed
+            let (input, regex) = (search.input.clone(), search.regex);
+            let (results, error) = if input.is_empty() {
+                (Vec::new(), None)
+            } else {
+                match crate::result_search::matcher(&input, regex) {
+                    Ok(matcher) => (model.packs.search_contents(&matcher), None),
+                    Err(e) => (Vec::new(), Some(e.to_string())),
+                }
+            };
+            if let Some(search) = &mut model.packs.search {
+                search.results = results;
+                search.selected = 0;
+                search.error = error;
+            }
+            vec![]
+        }
+
+        Message::PackSearchNavigate(delta) => {
+            if let Some(search) = &mut model.packs.search {
+                search.navigate(delta);
+            }
+            vec![]
+        }
+
+        Message::PackSearchOpen => {
+            let Some(found) = model
+                .packs
+                .search
+                .as_ref()
+                .and_then(PackSearch::selected_match)
+                .cloned()
+            else {
+                return vec![];
+            };
+            model.packs.search = None;
+            model.popup = None;
+            model.packs.select_pack_query(found.pack, found.query);
+            vec![]
+        }
+
+        Message::PacksStartTagFilter => {
+            model.packs.tag_input = Some(model.packs.tag_filter.join(", "));
+            model.popup = Some(Popup::PackTagFilter);
+            vec![]
+        }
+
+        Message::PackTagInputChar(c) => {
+            if let Some(ref mut input) = model.packs.tag_input {
+                input.push(c);
+            }
+            vec![]
+        }
 
-            // Now get the loaded pack and extract query
-            if let Some(entry) = model.packs.get_selected_entry() {
-                if let Some(pack) = &entry.pack {
-                    let queries = pack.get_queries();
-                    if let Some(first_query) = queries.first() {
-                        // Set the query text in the editor
-                        model.query.textarea.select_all();
-                        let len = model.query.textarea.yank_text().len();
-                        model.query.textarea.delete_str(len);
-                        model.query.textarea.insert_str(&first_query.query);
-
-                        // Set pack context for navigation
-                        model.query.pack_context = Some(crate::tui::model::query::PackContext {
-                            pack_name: pack.name.clone(),
-                            pack_path: entry.relative_path.clone(),
-                            queries: queries.clone(),
-                            current_index: 0,
-                        });
+        Message::PackTagInputBackspace => {
+            if let Some(ref mut input) = model.packs.tag_input {
+                input.pop();
+            }
+            vec![]
+        }
 
-                        // Switch to Query tab
-                        vec![Message::SwitchTab(Tab::Query)]
-                    } else {
-                        vec![Message::ShowError("Pack contains no queries".to_string())]
-                    }
-                } else {
-                    vec![Message::ShowError(
-                        "Failed to load pack details".to_string(),
-                    )]
-                }
+        Message::PacksSetTagFilter(input) => {
+            model.popup = None;
+            model.packs.tag_input = None;
+            let tags = input
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            model.packs.set_tag_filter(tags);
+            vec![]
+        }
+
+        Message::PacksLoadDetails => {
+            // Lazy load the selected pack
+            if let Err(e) = model.packs.load_selected_pack() {
+                vec![Message::ShowError(format!("Failed to load pack: {}", e))]
             } else {
-                vec![Message::ShowError("No pack selected".to_string())]
+                vec![]
             }
         }
 
+        Message::PacksLoadQuery => {
+            let query_index = model.packs.selected_query().unwrap_or(0);
+            load_pack_query(model, query_index)
+        }
+
         Message::PacksExecute => {
+            // Offer to resume if a previous run of this pack didn't finish
+            let Some(entry) = model.packs.get_selected_entry() else {
+                return vec![Message::ShowError("No pack selected".to_string())];
+            };
+            // Running a single query is a one-off test, not a resumable pack run
+            if model.packs.selected_query().is_some() {
+                return vec![Message::PacksRun {
+                    resume: false,
+                    confirmed: false,
+                }];
+            }
+            // A run still in progress is incomplete too, but isn't resumable
+            let incomplete =
+                crate::run_manifest::RunManifest::find_incomplete(&entry.path).filter(|manifest| {
+                    !model
+                        .packs
+                        .active_runs
+                        .iter()
+                        .any(|run| run.manifest.path() == manifest.path())
+                });
+            match incomplete {
+                Some(manifest) => {
+                    model.packs.resume_candidate = Some(manifest);
+                    model.popup = Some(Popup::ResumePackRun);
+                    vec![]
+                }
+                None => vec![Message::PacksRun {
+                    resume: false,
+                    confirmed: false,
+                }],
+            }
+        }
+
+        Message::PacksRun { resume, confirmed } => {
+            model.popup = None;
+            model.packs.pending_confirmation = None;
+            let resume_manifest = model.packs.resume_candidate.take().filter(|_| resume);
+
             // First ensure the pack is loaded
             if let Err(e) = model.packs.load_selected_pack() {
                 return vec![Message::ShowError(format!("Failed to load pack: {}", e))];
             }
 
             // Now execute the pack
-            if let Some(entry) = model.packs.get_selected_entry() {
+            if let Some(entry) = model.packs.get_selected_entry().cloned() {
                 if let Some(pack) = &entry.pack {
-                    let selected_workspaces: Vec<_> = model
-                        .workspaces
-                        .workspaces
-                        .iter()
-                        .filter(|ws| ws.selected)
-                        .map(|ws| ws.workspace.clone())
-                        .collect();
+                    let (selected_workspaces, failing) = model.workspaces.workspaces_for_run(
+                        model.settings.skip_failing_workspaces,
+                        model.settings.failure_streak,
+                    );
 
                     if selected_workspaces.is_empty() {
+                        if !failing.is_empty() {
+                            return vec![no_workspaces_error(&failing)];
+                        }
                         return vec![Message::ShowError(
                             "No workspaces selected. Go to Workspaces tab and select some."
                                 .to_string(),
                         )];
                     }
 
-                    let queries = pack.get_queries();
+                    // A highlighted query runs on its own; otherwise, with a tag filter
+                    // active, only the matching queries run
+                    let single_query = model
+                        .packs
+                        .selected_query()
+                        .and_then(|index| pack.get_queries().into_iter().nth(index));
+                    let queries = match &single_query {
+                        Some(query) => vec![query.clone()],
+                        None => pack.queries_with_tags(&model.packs.tag_filter),
+                    };
                     if queries.is_empty() {
                         return vec![Message::ShowError("Pack contains no queries".to_string())];
                     }
 
                     // Get base settings from pack or use current settings
-                    let base_settings = pack.settings.clone().unwrap_or_else(|| QuerySettings {
-                        job_name: "query".to_string(), // Will be overridden per query
-                        export_csv: model.settings.export_csv,
-                        export_json: model.settings.export_json,
-                        parse_dynamics: model.settings.parse_dynamics,
-                        output_folder: model.settings.output_folder.clone().into(),
-                    });
+                    let mut base_settings =
+                        pack.settings.clone().unwrap_or_else(|| QuerySettings {
+                            job_name: "query".to_string(), // Will be overridden per query
+                            export_csv: model.settings.export_csv,
+                            export_json: model.settings.export_json,
+                            export_ndjson: false,
+                            export_parquet: false,
+                            parse_dynamics: model.settings.parse_dynamics,
+                            output_folder: model.settings.output_folder.clone().into(),
+                            timeout_secs: None,
+                            retry_count: None,
+                            max_rows: None,
+                            sample_rows: None,
+                            timespan: None,
+                            estimate_cost: model.settings.estimate_cost,
+                            csv_metadata: model.settings.csv_metadata,
+                            sqlite_path: model.settings.sqlite_path_setting(),
+                            keep_partial_results: model.settings.keep_partial_results,
+                            extract_iocs: model.settings.extract_iocs,
+                            skip_empty_output: model.settings.skip_empty_output,
+                            blob_storage: None,
+                            ingestion: None,
+                        });
+                    // Fall back to the global row limit if the pack doesn't set one
+                    if base_settings.max_rows.is_none() {
+                        base_settings.max_rows = model.settings.max_rows_limit();
+                    }
+
+                    // Every run is previewed first; high-impact runs say why they need a `y`
+                    if !confirmed {
+                        let reason = pack.confirmation_reason(
+                            queries.len(),
+                            selected_workspaces.len(),
+                            model.packs.confirm_threshold,
+                        );
+                        let subscriptions: std::collections::HashSet<&str> = selected_workspaces
+                            .iter()
+                            .map(|ws| ws.subscription_id.as_str())
+                            .collect();
+                        model.packs.pending_confirmation = Some(RunConfirmation {
+                            pack_name: pack.name.clone(),
+                            reason,
+                            queries: queries.iter().map(|query| query.name.clone()).collect(),
+                            workspaces: selected_workspaces
+                                .iter()
+                                .map(|ws| ws.name.clone())
+                                .collect(),
+                            subscriptions: subscriptions.len(),
+                            timespan: pack.default_timespan.clone(),
+                            concurrency: crate::scheduler::QueryScheduler::estimated_concurrency(
+                                model.settings.max_concurrency,
+                                model.settings.max_concurrency_per_workspace,
+                                selected_workspaces.len(),
+                                queries.len(),
+                            ),
+                            output_folder: base_settings.output_folder.clone(),
+                            outputs: base_settings.output_summary(),
+                            resume: resume_manifest.is_some(),
+                        });
+                        model.packs.resume_candidate = resume_manifest;
+                        model.popup = Some(Popup::ConfirmPackRun);
+                        return vec![];
+                    }
+
+                    // Track progress in a run manifest so an interrupted run can be resumed
+                    // (single-query runs aren't tracked, so they never offer a resume)
+                    let mut manifest = match resume_manifest {
+                        Some(manifest) => Some(manifest),
+                        None if single_query.is_some() => None,
+                        None => {
+                            match crate::run_manifest::RunManifest::new(&pack.name, &entry.path) {
+                                Ok(manifest) => Some(manifest),
+                                Err(e) => {
+                                    return vec![Message::ShowError(format!(
+                                        "Failed to create run manifest: {}",
+                                        e
+                                    ))]
+                                }
+                            }
+                        }
+                    };
+                    let mut manifest_jobs = std::collections::HashMap::new();
+                    let mut skipped = 0;
+                    let mut duplicates = 0;
 
                     // Create jobs for all queries x workspaces
                     // Collect job IDs for tracking completion
@@ -1081,31 +3416,107 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
                     for pack_query in &queries {
                         // Create unique settings for each query with sanitized name
-                        let query_job_name = sanitize_filename(&pack_query.name);
+                        let query_job_name = paths::sanitize_file_name(&pack_query.name);
                         let mut query_settings = base_settings.clone();
                         query_settings.job_name = query_job_name;
 
                         for workspace in &selected_workspaces {
-                            // Create a better preview for KQL queries (200 chars to show more context)
-                            let query_preview = pack_query.query.chars().take(200).collect();
-
+                            // Apply query and workspace timeout/retry overrides
                             let retry_context = crate::tui::model::jobs::RetryContext {
                                 workspace: workspace.clone(),
                                 query: pack_query.query.clone(),
-                                settings: query_settings.clone(),
+                                settings: pack.resolve_settings(
+                                    &query_settings,
+                                    pack_query,
+                                    workspace,
+                                ),
                             };
 
+                            if model.jobs.has_duplicate(&retry_context) {
+                                duplicates += 1;
+                                if model.settings.duplicate_jobs == DuplicateJobs::Skip {
+                                    continue;
+                                }
+                            }
+
+                            if let Some(manifest) = &mut manifest {
+                                manifest.ensure_entry(
+                                    &pack_query.name,
+                                    &workspace.workspace_id,
+                                    &workspace.name,
+                                );
+                                if manifest.is_completed(&pack_query.name, &workspace.workspace_id)
+                                {
+                                    skipped += 1;
+                                    continue;
+                                }
+                            }
+
+                            // Create a better preview for KQL queries (200 chars to show more context)
+                            let query_preview = pack_query.query.chars().take(200).collect();
+
                             // Capture the job ID for this job
                             let job_id = model.jobs.add_job_with_context(
                                 workspace.name.clone(),
                                 query_preview,
                                 retry_context.clone(),
                             );
+                            model.jobs.set_query_info(
+                                job_id,
+                                pack_query.name.clone(),
+                                pack_query.description.clone(),
+                            );
+                            model.jobs.set_severity(job_id, pack_query.severity);
+                            model.jobs.set_priority(
+                                job_id,
+                                JobPriority::from_severity(pack_query.severity),
+                            );
 
+                            manifest_jobs.insert(job_id, pack_query.name.clone());
                             job_ids.push((job_id, retry_context));
                         }
                     }
 
+                    if let Some(mut manifest) = manifest {
+                        if let Err(e) = manifest.save() {
+                            log::warn!("Failed to save run manifest: {}", e);
+                        }
+                        model
+                            .packs
+                            .active_runs
+                            .push(crate::tui::model::packs::ActivePackRun {
+                                summary: crate::webhook::RunSummary::new(&manifest.pack_name),
+                                manifest,
+                                jobs: manifest_jobs,
+                                webhook: pack.webhook.clone(),
+                                ioc_folder: (base_settings.extract_iocs
+                                    || model.settings.extract_iocs)
+                                    .then(|| base_settings.output_folder.clone()),
+                            });
+                    }
+
+                    let duplicate_note = match model.settings.duplicate_jobs {
+                        _ if duplicates == 0 => String::new(),
+                        DuplicateJobs::Skip => {
+                            format!(", {} duplicate(s) skipped", duplicates)
+                        }
+                        DuplicateJobs::Warn => {
+                            format!(", {} repeat earlier jobs", duplicates)
+                        }
+                        DuplicateJobs::Allow => String::new(),
+                    };
+
+                    if job_ids.is_empty() {
+                        return vec![Message::ShowInfo(if skipped > 0 {
+                            format!(
+                                "All {} query/workspace pairs already completed in the previous run{}",
+                                skipped, duplicate_note
+                            )
+                        } else {
+                            format!("No jobs started{}", duplicate_note)
+                        })];
+                    }
+
                     // Track pack origin for session
                     model
                         .sessions
@@ -1114,103 +3525,37 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
                     // Mark session as dirty
                     model.sessions.mark_dirty();
 
-                    // Execute each job individually to preserve per-query settings
-                    // (QueryJobBuilder applies a single settings to all jobs, losing our sanitized names)
-                    let client = model.client.clone();
-                    let update_tx = model.job_update_tx.clone();
-
                     log::info!("Spawning {} tasks for pack execution", job_ids.len());
-
-                    // Create semaphore to limit concurrent query execution
-                    // This prevents resource exhaustion with large packs across many workspaces
-                    const MAX_CONCURRENT_QUERIES: usize = 15;
-                    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_QUERIES));
-
-                    // Spawn individual tasks for each job using stable job IDs
                     for (job_id, retry_ctx) in job_ids {
-                        let client = client.clone();
-                        let tx = update_tx.clone();
-                        let semaphore = semaphore.clone();
-
-                        log::debug!("Spawning task for job ID {}", job_id);
-
-                        tokio::spawn(async move {
-                            // Acquire semaphore permit before executing query
-                            let _permit = semaphore.acquire().await.expect("Semaphore closed");
-                            log::debug!("Job {} acquired semaphore permit, executing", job_id);
-
-                            // Clone retry_ctx for error cases (will be moved into builder)
-                            let retry_ctx_for_errors = retry_ctx.clone();
-
-                            let results = QueryJobBuilder::new()
-                                .workspaces(vec![retry_ctx.workspace])
-                                .queries(vec![retry_ctx.query])
-                                .settings(retry_ctx.settings)
-                                .execute(&client)
-                                .await;
-
-                            // Send completion message in ALL cases (success or failure)
-                            match results {
-                                Ok(mut results) if !results.is_empty() => {
-                                    let result = results.remove(0);
-                                    log::debug!(
-                                        "Job {} completed successfully, sending completion message",
-                                        job_id
-                                    );
-                                    let _ =
-                                        tx.send(crate::tui::model::JobUpdateMessage::Completed(
-                                            job_id, result,
-                                        ));
-                                }
-                                Ok(_) => {
-                                    // Empty results - shouldn't happen but handle it
-                                    log::error!("Job {} produced no results (empty vec), sending failed message", job_id);
-                                    // Create a failed result to update the UI
-                                    let failed_result = create_failed_result(
-                                        retry_ctx_for_errors,
-                                        "Query execution returned no results".to_string(),
-                                    );
-                                    let _ =
-                                        tx.send(crate::tui::model::JobUpdateMessage::Completed(
-                                            job_id,
-                                            failed_result,
-                                        ));
-                                }
-                                Err(e) => {
-                                    // Execution error - create failed result
-                                    log::error!(
-                                        "Job {} failed: {}, sending failed message",
-                                        job_id,
-                                        e
-                                    );
-                                    let failed_result =
-                                        create_failed_result(retry_ctx_for_errors, e.to_string());
-                                    let _ =
-                                        tx.send(crate::tui::model::JobUpdateMessage::Completed(
-                                            job_id,
-                                            failed_result,
-                                        ));
-                                }
-                            }
-                            // Permit is automatically released when _permit is dropped
-                        });
-                    }
-
-                    // Mark all newly created jobs as running
-                    for i in job_count_before..model.jobs.jobs.len() {
-                        if let Some(job) = model.jobs.jobs.get_mut(i) {
-                            job.status = crate::tui::model::jobs::JobStatus::Running;
-                        }
+                        spawn_scheduled_job(model, job_id, retry_ctx);
                     }
 
-                    vec![
-                        Message::SwitchTab(Tab::Jobs),
-                        Message::ShowError(format!(
-                            "Executing {} queries across {} workspaces",
+                    let summary = if skipped > 0 {
+                        format!(
+                            "Resuming pack run: {} job(s) queued, {} already completed{}",
+                            model.jobs.jobs.len() - job_count_before,
+                            skipped,
+                            duplicate_note
+                        )
+                    } else if let Some(query) = &single_query {
+                        format!(
+                            "Executing '{}' across {} workspaces{}",
+                            query.name,
+                            selected_workspaces.len(),
+                            duplicate_note
+                        )
+                    } else {
+                        format!(
+                            "Executing {} queries across {} workspaces{}",
                             queries.len(),
-                            selected_workspaces.len()
-                        )),
-                    ]
+                            selected_workspaces.len(),
+                            duplicate_note
+                        )
+                    };
+                    std::iter::once(Message::SwitchTab(Tab::Jobs))
+                        .chain(skipped_failing_notice(&failing))
+                        .chain([Message::ShowInfo(summary)])
+                        .collect()
                 } else {
                     vec![Message::ShowError(
                         "Failed to load pack details".to_string(),
@@ -1223,89 +3568,215 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
 
         Message::PacksSave => {
             // Check if there's a pack loaded in the query editor
-            if let Some(pack_context) = &model.query.pack_context {
-                // Clone necessary data to avoid borrow checker issues
-                let pack_path = pack_context.pack_path.clone();
-                let current_index = pack_context.current_index;
+            let Some(pack_context) = &model.query.pack_context else {
+                return vec![Message::ShowError(
+                    "No pack loaded. Load a pack from the Packs tab first.".to_string(),
+                )];
+            };
 
-                // Get the current query text from the editor
-                let current_query_text = model.query.get_text();
+            let mut pack = pack_context.pack.clone();
+            let text = model.query.get_text();
+            let changed = pack
+                .get_queries()
+                .get(pack_context.current_index)
+                .is_some_and(|query| query.query != text);
+            if !pack.set_query_text(pack_context.current_index, text) {
+                return vec![Message::ShowError("Invalid query index".to_string())];
+            }
+            let mut pack_name = pack.name.clone();
+            if changed && model.packs.changelog {
+                if let Some(query) = pack_context.queries.get(pack_context.current_index) {
+                    pack.record_change(&query.name, current_user());
+                    if let Some(version) = &pack.version {
+                        pack_name = format!("{} (v{})", pack_name, version);
+                    }
+                }
+            }
 
-                // Find the pack entry that matches the loaded pack
-                let pack_entry = model
-                    .packs
-                    .packs
-                    .iter_mut()
-                    .find(|entry| entry.relative_path == pack_path);
-
-                if let Some(entry) = pack_entry {
-                    // Ensure the pack is loaded
-                    if entry.pack.is_none() {
-                        if let Err(e) = crate::query_pack::QueryPack::load_from_file(&entry.path) {
-                            return vec![Message::ShowError(format!("Failed to load pack: {}", e))];
-                        }
+            // The file may have been renamed or moved since the pack was loaded
+            let mut messages = Vec::new();
+            let path = if pack_context.pack_path.is_file() {
+                pack_context.pack_path.clone()
+            } else {
+                let content_hash = pack_context.content_hash;
+                model.packs.refresh();
+                match content_hash.and_then(|hash| model.packs.find_by_content_hash(hash)) {
+                    Some(entry) => {
+                        messages.push(Message::ShowInfo(format!(
+                            "Pack file moved; saving to {}",
+                            entry.relative_path
+                        )));
+                        entry.path.clone()
+                    }
+                    None => {
+                        // The original is gone: offer to write the in-memory pack elsewhere
+                        return vec![
+                            Message::PacksStartSaveAs,
+                            Message::ShowInfo(
+                                "Pack file no longer exists; choose where to save it".to_string(),
+                            ),
+                        ];
                     }
+                }
+            };
 
-                    if let Some(pack) = &mut entry.pack {
-                        // Update the specific query in the pack
-                        let queries = pack.get_queries();
+            if let Some(entry) = model.packs.packs.iter().find(|entry| entry.path == path) {
+                if entry.read_only {
+                    return vec![Message::ShowError(format!(
+                        "Pack library '{}' is read-only",
+                        entry.source
+                    ))];
+                }
+            }
 
-                        if current_index >= queries.len() {
-                            return vec![Message::ShowError("Invalid query index".to_string())];
-                        }
+            match save_loaded_pack(model, pack, path) {
+                Ok(()) => {
+                    messages.push(Message::ShowSuccess(format!(
+                        "Saved changes to pack: {}",
+                        pack_name
+                    )));
+                    messages
+                }
+                Err(e) => vec![Message::ShowError(format!("Failed to save pack: {}", e))],
+            }
+        }
 
-                        // Reconstruct the pack with the updated query
-                        if let Some(pack_queries) = &mut pack.queries {
-                            // Multiple queries format
-                            if current_index < pack_queries.len() {
-                                pack_queries[current_index].query = current_query_text.clone();
-                            }
-                        } else if pack.query.is_some() && current_index == 0 {
-                            // Single query format
-                            pack.query = Some(current_query_text.clone());
-                        }
+        Message::PacksStartSaveAs => {
+            let Some(pack_context) = &model.query.pack_context else {
+                return vec![Message::ShowError(
+                    "No pack loaded. Load a pack from the Packs tab first.".to_string(),
+                )];
+            };
+            let file_name = pack_context
+                .pack_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            model.packs.save_as_input = Some(file_name);
+            model.popup = Some(Popup::PackSaveAs);
+            vec![]
+        }
 
-                        // Save the pack to disk
-                        let pack_name = pack.name.clone();
-                        match pack.save_to_file(&entry.path) {
-                            Ok(_) => {
-                                // Update the pack_context with the saved query
-                                if let Some(ctx) = &mut model.query.pack_context {
-                                    if current_index < ctx.queries.len() {
-                                        ctx.queries[current_index].query = current_query_text;
-                                    }
-                                }
+        Message::PackSaveAsInputChar(c) => {
+            if let Some(ref mut input) = model.packs.save_as_input {
+                input.push(c);
+            }
+            vec![]
+        }
 
-                                vec![Message::ShowSuccess(format!(
-                                    "Saved changes to pack: {}",
-                                    pack_name
-                                ))]
-                            }
-                            Err(e) => {
-                                vec![Message::ShowError(format!("Failed to save pack: {}", e))]
-                            }
-                        }
-                    } else {
-                        vec![Message::ShowError("Pack not loaded".to_string())]
-                    }
-                } else {
-                    vec![Message::ShowError("Pack not found in list".to_string())]
+        Message::PackSaveAsInputBackspace => {
+            if let Some(ref mut input) = model.packs.save_as_input {
+                input.pop();
+            }
+            vec![]
+        }
+
+        Message::PacksSaveAs(relative_path) => {
+            model.popup = None;
+            model.packs.save_as_input = None;
+
+            let Some(pack_context) = &model.query.pack_context else {
+                return vec![Message::ShowError("No pack loaded".to_string())];
+            };
+
+            let mut relative_path = relative_path.trim().to_string();
+            if relative_path.is_empty() {
+                return vec![Message::ShowError("Pack path cannot be empty".to_string())];
+            }
+            if std::path::Path::new(&relative_path).extension().is_none() {
+                relative_path.push_str(".yaml");
+            }
+
+            let mut pack = pack_context.pack.clone();
+            if !pack.set_query_text(pack_context.current_index, model.query.get_text()) {
+                return vec![Message::ShowError("Invalid query index".to_string())];
+            }
+
+            let output_path = match crate::query_pack::QueryPack::get_library_path(&relative_path) {
+                Ok(p) => p,
+                Err(e) => {
+                    return vec![Message::ShowError(format!(
+                        "Failed to get output path: {}",
+                        e
+                    ))]
                 }
-            } else {
-                vec![Message::ShowError(
-                    "No pack loaded. Load a pack from the Packs tab first.".to_string(),
-                )]
+            };
+
+            if output_path.exists() {
+                return vec![Message::ShowError(format!(
+                    "Pack already exists: {}",
+                    output_path.display()
+                ))];
+            }
+
+            if let Some(parent) = output_path.parent() {
+                if let Err(e) = std::fs::create_dir_all(parent) {
+                    return vec![Message::ShowError(format!(
+                        "Failed to create directory: {}",
+                        e
+                    ))];
+                }
+            }
+
+            match save_loaded_pack(model, pack, output_path) {
+                Ok(()) => {
+                    model.packs.refresh();
+                    vec![Message::ShowSuccess(format!(
+                        "Saved pack to {}",
+                        relative_path
+                    ))]
+                }
+                Err(e) => vec![Message::ShowError(format!("Failed to save pack: {}", e))],
             }
         }
 
         // === Popups ===
         Message::ShowError(msg) => {
+            model
+                .notifications
+                .push(NotificationLevel::Error, msg.clone());
             model.popup = Some(Popup::Error(msg));
             vec![]
         }
 
         Message::ShowSuccess(msg) => {
-            model.popup = Some(Popup::Success(msg));
+            model
+                .notifications
+                .push_toast(NotificationLevel::Success, msg);
+            vec![]
+        }
+
+        Message::ShowInfo(msg) => {
+            // Toasts are a single line, so reports that span lines get a popup instead
+            if msg.contains('\n') {
+                model
+                    .notifications
+                    .push(NotificationLevel::Info, msg.clone());
+                model.popup = Some(Popup::Info(msg));
+            } else {
+                model.notifications.push_toast(NotificationLevel::Info, msg);
+            }
+            vec![]
+        }
+
+        Message::NotificationsOpen => {
+            model.notifications.open();
+            model.popup = Some(Popup::Notifications);
+            vec![]
+        }
+
+        Message::NotificationsPrevious => {
+            model.notifications.previous();
+            vec![]
+        }
+
+        Message::NotificationsNext => {
+            model.notifications.next();
+            vec![]
+        }
+
+        Message::NotificationsClear => {
+            model.notifications.clear();
             vec![]
         }
 
@@ -1313,7 +3784,29 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
             model.popup = None;
             model.settings.editing = None;
             model.query.job_name_input = None;
+            model.query.watch_requested = false;
+            model.query.target_workspace = None;
+            model.query.parameter_input = None;
+            model.query.resolved_query = None;
             model.sessions.name_input = None;
+            model.sessions.rename_input = None;
+            model.sessions.details_input = None;
+            model.sessions.search_input = None;
+            model.sessions.pending_delete = None;
+            model.jobs.pack_name_input = None;
+            model.jobs.note_input = None;
+            model.jobs.post_filter = None;
+            model.jobs.chart = None;
+            model.packs.resume_candidate = None;
+            model.packs.pending_confirmation = None;
+            model.packs.save_as_input = None;
+            model.packs.rename_input = None;
+            model.packs.tag_input = None;
+            model.workspaces.subscription_picker = None;
+            model.workspaces.saved_searches = None;
+            model.jobs.column_picker = None;
+            model.jobs.result_search = None;
+            model.finder = None;
             vec![]
         }
 
@@ -1326,16 +3819,495 @@ pub fn update(model: &mut Model, message: Message) -> Vec<Message> {
         }
 
         Message::AuthFailed(error) => {
-            model.init_state = crate::tui::model::InitState::Failed;
+            model.init_state = crate::tui::model::InitState::Offline;
             vec![Message::ShowError(format!(
-                "Authentication failed: {}",
+                "Authentication failed: {}\n\nRunning offline: sessions, packs and saved results can still be browsed. Press F5 to re-authenticate.",
                 error
             ))]
         }
 
         Message::InitCompleted => {
             model.init_state = crate::tui::model::InitState::Ready;
+            model.auth_prompted = false;
+            vec![]
+        }
+
+        Message::ReAuthenticate => {
+            // Handled in main loop (clears tokens and re-validates in the background)
             vec![]
         }
+
+        Message::ReAuthCompleted => {
+            model.init_state = crate::tui::model::InitState::Ready;
+            model.auth_prompted = false;
+            vec![Message::ShowSuccess(
+                "Re-authenticated with Azure".to_string(),
+            )]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{FakeBackend, FakeCall};
+    use crate::error::KqlPanopticonError;
+    use crate::tui::model::jobs::JobStatus;
+    use crate::workspace::Workspace;
+
+    /// A writable pack in the local library, loaded lazily from `path`
+    fn pack_entry(
+        path: std::path::PathBuf,
+        relative_path: &str,
+    ) -> crate::tui::model::packs::PackEntry {
+        crate::tui::model::packs::PackEntry {
+            path,
+            pack: None,
+            relative_path: relative_path.to_string(),
+            source: "local".to_string(),
+            read_only: false,
+            load_error: None,
+        }
+    }
+
+    /// Run a query through `update` and wait for its jobs to finish
+    async fn run_query(model: &mut Model<FakeBackend>, query: &str, job_name: &str) {
+        model.query.set_text(query.to_string());
+        update(model, Message::ExecuteQuery(job_name.to_string()));
+        for _ in 0..200 {
+            model.process_job_updates();
+            if !model.jobs.has_pending_jobs() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("jobs did not finish");
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_with_fake_backend() {
+        let output = std::env::temp_dir().join(format!("kql-update-{}", std::process::id()));
+        let fake = FakeBackend::new();
+        fake.respond(
+            "SigninLogs",
+            FakeBackend::table(&["User"], vec![vec!["alice".into()], vec!["bob".into()]]),
+        )
+        .fail(
+            "SecurityEvent",
+            KqlPanopticonError::QueryExecutionFailed("Table not found".to_string()),
+        );
+
+        let mut model = Model::new(fake.clone());
+        model.settings.output_folder = output.to_string_lossy().into_owned();
+        model.workspaces.load_workspaces(vec![
+            Workspace::fixture("ws-eu"),
+            Workspace::fixture("ws-us"),
+        ]);
+        model.workspaces.select_all();
+
+        run_query(&mut model, "SigninLogs | take 2", "signins").await;
+        assert_eq!(model.jobs.jobs.len(), 2);
+        assert!(model
+            .jobs
+            .jobs
+            .iter()
+            .all(|job| job.status == JobStatus::Completed));
+
+        run_query(&mut model, "SecurityEvent | take 1", "events").await;
+        let failed = model
+            .jobs
+            .jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Failed)
+            .count();
+        assert_eq!(failed, 2);
+
+        let queried: Vec<String> = fake
+            .calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                FakeCall::Query { workspace_id, .. } => Some(workspace_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(queried.len(), 4);
+        assert!(queried.contains(&"ws-us-id".to_string()));
+
+        let _ = std::fs::remove_dir_all(&output);
+    }
+
+    #[tokio::test]
+    async fn test_queued_job_can_be_bumped() {
+        let output = std::env::temp_dir().join(format!("kql-priority-{}", std::process::id()));
+        let fake = FakeBackend::new();
+        fake.respond(
+            "SigninLogs",
+            FakeBackend::table(&["User"], vec![vec!["alice".into()]]),
+        );
+        let mut model = Model::new(fake);
+        model.settings.output_folder = output.to_string_lossy().into_owned();
+        model
+            .workspaces
+            .load_workspaces(vec![Workspace::fixture("ws-eu")]);
+        model.workspaces.select_all();
+
+        // Another job holds every slot, so the ad-hoc job waits in the queue
+        model.scheduler = crate::scheduler::QueryScheduler::new(1, 1);
+        let held = model
+            .scheduler
+            .acquire("ws-eu-id", &crate::scheduler::SharedPriority::default())
+            .await;
+        model.query.set_text("SigninLogs".to_string());
+        update(&mut model, Message::ExecuteQuery("signins".to_string()));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        model.process_job_updates();
+        let job = &model.jobs.jobs[0];
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.priority.get(), JobPriority::High);
+
+        model.jobs.table_state.select(Some(0));
+        let replies = update(&mut model, Message::JobsChangePriority(true));
+        assert!(matches!(replies.as_slice(), [Message::ShowSuccess(_)]));
+        assert_eq!(model.jobs.jobs[0].priority.get(), JobPriority::Urgent);
+
+        drop(held);
+        for _ in 0..200 {
+            model.process_job_updates();
+            if !model.jobs.has_pending_jobs() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(model.jobs.jobs[0].status, JobStatus::Completed);
+        let replies = update(&mut model, Message::JobsChangePriority(false));
+        assert!(matches!(replies.as_slice(), [Message::ShowError(_)]));
+
+        let _ = std::fs::remove_dir_all(&output);
+    }
+
+    #[tokio::test]
+    async fn test_result_search_jumps_to_job() {
+        let output = std::env::temp_dir().join(format!("kql-search-{}", std::process::id()));
+        let fake = FakeBackend::new();
+        fake.respond(
+            "SigninLogs",
+            FakeBackend::table(&["IPAddress"], vec![vec!["203.0.113.7".into()]]),
+        )
+        .respond(
+            "AuditLogs",
+            FakeBackend::table(&["IPAddress"], vec![vec!["198.51.100.1".into()]]),
+        );
+
+        let mut model = Model::new(fake);
+        model.settings.output_folder = output.to_string_lossy().into_owned();
+        model.workspaces.load_workspaces(vec![
+            Workspace::fixture("ws-eu"),
+            Workspace::fixture("ws-us"),
+        ]);
+        model.workspaces.select_all();
+        run_query(&mut model, "SigninLogs", "signins").await;
+        run_query(&mut model, "AuditLogs", "audit").await;
+
+        update(&mut model, Message::JobsStartResultSearch);
+        for c in "203.0.113.7".chars() {
+            update(&mut model, Message::ResultSearchInputChar(c));
+        }
+        update(&mut model, Message::ResultSearchSubmit);
+        let search = model.jobs.result_search.as_ref().unwrap();
+        let results = search.results.as_ref().unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|found| found.lines == 1));
+
+        let path = results[0].path.clone();
+        update(&mut model, Message::ResultSearchSubmit);
+        let Some(Popup::JobDetails(job_idx)) = model.popup else {
+            panic!("expected the job details popup");
+        };
+        assert_eq!(model.jobs.job_with_output(&path), Some(job_idx));
+        assert!(model.jobs.result_search.is_none());
+
+        let _ = std::fs::remove_dir_all(&output);
+    }
+
+    #[test]
+    fn test_read_only_refuses_execution() {
+        let fake = FakeBackend::new();
+        let mut model = Model::new(fake.clone());
+        model.read_only = true;
+        model
+            .workspaces
+            .load_workspaces(vec![Workspace::fixture("ws-eu")]);
+        model.workspaces.select_all();
+        model.query.set_text("SigninLogs".to_string());
+
+        for message in [
+            Message::QueryStartExecution,
+            Message::ExecuteQuery("signins".to_string()),
+            Message::PacksRun {
+                resume: false,
+                confirmed: true,
+            },
+        ] {
+            let replies = update(&mut model, message);
+            assert!(matches!(replies.as_slice(), [Message::ShowError(_)]));
+        }
+        assert!(model.popup.is_none());
+        assert!(model.jobs.jobs.is_empty());
+        assert!(fake.calls().is_empty());
+
+        // Drafting still works
+        update(&mut model, Message::QueryFormat);
+        update(&mut model, Message::QueryClear);
+        assert_eq!(model.query.get_text(), "");
+    }
+
+    #[test]
+    fn test_empty_setting_clears_optional_value() {
+        let mut model = Model::new(FakeBackend::new());
+        model.settings.sqlite_path = "results.db".to_string();
+        model.settings.query_timeout_secs = 60;
+
+        model.settings.selected_index = SettingsModel::SQLITE_PATH_INDEX;
+        model.settings.editing = Some("  ".to_string());
+        update(&mut model, Message::SettingsSave);
+        assert_eq!(model.settings.sqlite_path, "");
+
+        // Required settings keep their value
+        model.settings.selected_index = 1;
+        model.settings.editing = Some(String::new());
+        update(&mut model, Message::SettingsSave);
+        assert_eq!(model.settings.query_timeout_secs, 60);
+
+        model.settings.max_rows = 5000;
+        model.settings.selected_index = SettingsModel::MAX_ROWS_INDEX;
+        assert!(model.settings.save_edit(String::new()).is_ok());
+        assert_eq!(model.settings.max_rows_limit(), None);
+    }
+
+    #[test]
+    fn test_info_is_not_shown_as_error() {
+        let mut model = Model::new(FakeBackend::new());
+
+        update(
+            &mut model,
+            Message::ShowInfo("Pack library is up to date".to_string()),
+        );
+        assert!(model.popup.is_none());
+        assert!(model.notifications.active_toast().is_some());
+
+        update(
+            &mut model,
+            Message::ShowInfo("Pulled 4 pack change(s):\na\nb".to_string()),
+        );
+        assert!(matches!(model.popup, Some(Popup::Info(_))));
+        let latest = model.notifications.newest_first().next().unwrap();
+        assert!(matches!(latest.level, NotificationLevel::Info));
+    }
+
+    #[test]
+    fn test_cancelled_workspace_refresh_keeps_list() {
+        let mut model = Model::new(FakeBackend::new());
+        update(
+            &mut model,
+            Message::WorkspacesLoaded(vec![Workspace::fixture("prod")]),
+        );
+
+        update(&mut model, Message::WorkspacesRefresh);
+        assert!(model.workspaces.refreshing);
+        let replies = update(&mut model, Message::WorkspacesCancelRefresh);
+        assert!(!model.workspaces.refreshing);
+        assert!(matches!(replies.as_slice(), [Message::ShowInfo(_)]));
+
+        // A failure reported after cancelling is dropped
+        let replies = update(
+            &mut model,
+            Message::WorkspacesRefreshFailed("timed out".to_string()),
+        );
+        assert!(replies.is_empty());
+        assert_eq!(model.workspaces.workspaces.len(), 1);
+    }
+
+    #[test]
+    fn test_pack_run_is_previewed() {
+        let dir = std::env::temp_dir().join(format!("kql-confirm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wide.yaml");
+        std::fs::write(&path, "name: Wide\nrequires_confirmation: true\nquery: T").unwrap();
+
+        let mut model = Model::new(FakeBackend::new());
+        model.settings.output_folder = dir.to_string_lossy().into_owned();
+        model.workspaces.load_workspaces(vec![
+            Workspace::fixture("ws-eu"),
+            Workspace::fixture("ws-us"),
+        ]);
+        model.workspaces.select_all();
+        model.packs.packs = vec![pack_entry(path.clone(), "wide.yaml")];
+        model.packs.table_state.select(Some(0));
+
+        let run_message = || Message::PacksRun {
+            resume: false,
+            confirmed: false,
+        };
+        assert!(update(&mut model, run_message()).is_empty());
+        assert!(matches!(model.popup, Some(Popup::ConfirmPackRun)));
+        let confirmation = model.packs.pending_confirmation.as_ref().unwrap();
+        assert_eq!(confirmation.workspaces, vec!["ws-eu", "ws-us"]);
+        assert_eq!(confirmation.queries.len(), 1);
+        assert!(confirmation.reason.is_some());
+        assert_eq!(confirmation.concurrency, 2);
+        assert!(model.jobs.jobs.is_empty());
+
+        update(&mut model, Message::ClosePopup);
+        assert!(model.packs.pending_confirmation.is_none());
+        assert!(model.jobs.jobs.is_empty());
+
+        // Runs that don't need confirmation are still previewed
+        std::fs::write(&path, "name: Wide\nquery: T").unwrap();
+        model.packs.packs[0].pack = None;
+        update(&mut model, run_message());
+        let confirmation = model.packs.pending_confirmation.as_ref().unwrap();
+        assert!(confirmation.reason.is_none());
+        assert!(model.jobs.jobs.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_locked_pack_opens_read_only() {
+        let dir = std::env::temp_dir().join(format!("kql-locked-pack-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shared.yaml");
+        std::fs::write(&path, "name: Shared\nquery: SigninLogs").unwrap();
+
+        // Another instance, on another machine, has the pack open
+        let holder = crate::file_lock::LockInfo {
+            pid: 4242,
+            host: "analyst-laptop".to_string(),
+            acquired_at: String::new(),
+        };
+        std::fs::write(
+            FileLock::lock_path(&path),
+            serde_json::to_string(&holder).unwrap(),
+        )
+        .unwrap();
+
+        let mut model = Model::new(FakeBackend::new());
+        model.packs.packs = vec![pack_entry(path.clone(), "shared.yaml")];
+        model.packs.table_state.select(Some(0));
+
+        assert!(update(&mut model, Message::PacksLoadQuery).is_empty());
+        assert!(matches!(model.popup, Some(Popup::LockConflict)));
+        assert!(model.query.pack_context.is_none());
+
+        update(
+            &mut model,
+            Message::ResolveLockConflict { take_over: false },
+        );
+        assert!(model.query.pack_context.as_ref().unwrap().read_only);
+        model.query.set_text("SigninLogs | take 10".to_string());
+        let replies = update(&mut model, Message::PacksSave);
+        assert!(matches!(replies.as_slice(), [Message::ShowError(_)]));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name: Shared\nquery: SigninLogs"
+        );
+        assert_eq!(FileLock::holder(&path), Some(holder));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_pack_runs_highlighted_query_only() {
+        let dir = std::env::temp_dir().join(format!("kql-single-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("triage.yaml");
+        std::fs::write(
+            &path,
+            "name: Triage\nqueries:\n  - name: Signins\n    query: SigninLogs\n  - name: Audit\n    query: AuditLogs\n",
+        )
+        .unwrap();
+
+        let mut model = Model::new(FakeBackend::new());
+        model.settings.output_folder = dir.to_string_lossy().into_owned();
+        model.workspaces.load_workspaces(vec![
+            Workspace::fixture("ws-eu"),
+            Workspace::fixture("ws-us"),
+        ]);
+        model.workspaces.select_all();
+        model.packs.packs = vec![pack_entry(path.clone(), "triage.yaml")];
+        model.packs.table_state.select(Some(0));
+
+        // Right steps into the query list and stops at the last query
+        for _ in 0..3 {
+            update(&mut model, Message::PacksNextQuery);
+        }
+        assert_eq!(model.packs.selected_query(), Some(1));
+
+        // No resume prompt: the run is previewed straight away
+        for reply in update(&mut model, Message::PacksExecute) {
+            update(&mut model, reply);
+        }
+        let confirmation = model.packs.pending_confirmation.as_ref().unwrap();
+        assert_eq!(confirmation.queries, vec!["Audit"]);
+
+        update(
+            &mut model,
+            Message::PacksRun {
+                resume: false,
+                confirmed: true,
+            },
+        );
+        assert_eq!(model.jobs.jobs.len(), 2);
+        assert!(model
+            .jobs
+            .jobs
+            .iter()
+            .all(|job| job.query_name.as_deref() == Some("Audit")));
+        assert!(model.packs.active_runs.is_empty());
+
+        // Left from the first query goes back to the whole pack
+        update(&mut model, Message::PacksPreviousQuery);
+        update(&mut model, Message::PacksPreviousQuery);
+        assert_eq!(model.packs.selected_query(), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pack_search_finds_kql_lines() {
+        let dir = std::env::temp_dir().join(format!("kql-pack-search-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("network")).unwrap();
+        let path = dir.join("network/lateral.yaml");
+        std::fs::write(
+            &path,
+            "name: Lateral Movement\nqueries:\n  - name: Signins\n    query: SigninLogs\n  - name: SMB\n    query: |\n      DeviceNetworkEvents\n      | where RemotePort == 445\n",
+        )
+        .unwrap();
+
+        let mut model = Model::new(FakeBackend::new());
+        model.packs.packs = vec![
+            pack_entry(dir.join("missing.yaml"), "broken.yaml"),
+            pack_entry(path, "network/lateral.yaml"),
+        ];
+        model.packs.table_state.select(Some(0));
+
+        update(&mut model, Message::PacksStartSearch);
+        for c in "port == 445".chars() {
+            update(&mut model, Message::PackSearchInputChar(c));
+        }
+        let search = model.packs.search.as_ref().unwrap();
+        assert_eq!(search.results.len(), 1);
+        assert_eq!(
+            search.results[0].field,
+            crate::tui::model::packs::MatchField::Kql(2)
+        );
+        assert_eq!(search.results[0].text, "| where RemotePort == 445");
+
+        update(&mut model, Message::PackSearchOpen);
+        assert!(model.popup.is_none());
+        assert_eq!(model.packs.table_state.selected(), Some(1));
+        assert_eq!(model.packs.selected_query(), Some(1));
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
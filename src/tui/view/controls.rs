@@ -1,36 +1,48 @@
 use crate::tui::message::Tab;
 use ratatui::{
     layout::{Alignment, Rect},
+    style::{Color, Style},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
 /// Render the controls bar at the bottom
-pub fn render(f: &mut Frame, current_tab: Tab, area: Rect) {
+pub fn render(f: &mut Frame, current_tab: Tab, refreshing_workspaces: bool, area: Rect) {
     let controls = match current_tab {
         Tab::Settings => {
-            "1-6: Select Tab | Up/Down: Navigate | Enter: Edit | Tab: Next Tab | q: Quit"
+            "1-8: Select Tab | Up/Down: Navigate | Enter: Edit | a: Re-authenticate | Tab: Next Tab | q: Quit"
+        }
+        Tab::Workspaces if refreshing_workspaces => {
+            "Refreshing workspaces... | Esc: Cancel Refresh | Up/Down: Navigate | Space: Toggle | Tab: Next Tab | q: Quit"
         }
         Tab::Workspaces => {
-            "1-6: Select Tab | Up/Down: Navigate | Space: Toggle | a: Select All | n: Select None | r: Refresh | Tab: Next Tab | q: Quit"
+            "1-8: Select Tab | Up/Down: Navigate | PgUp/PgDn, g/G: Page, Top/Bottom | Space: Toggle | a: Select All | n: Select None | s: Subscriptions | o: Sort | e: Run Query Here | f: Saved Searches | r: Refresh | Tab: Next Tab | q: Quit"
         }
         Tab::Query => {
-            "1-6: Select Tab | i: INSERT mode | c: Clear | Ctrl+J: Execute | Tab: Next Tab | q: Quit"
+            "i: INSERT mode | v/V: Visual/Line | dd/yy: Delete/Yank Line | [count]j/k: Repeat | c: Clear | n: New Buffer | {/}: Prev/Next Buffer | X: Close Buffer | s: Split View | R: Inspect Rows | Ctrl+J: Execute | Ctrl+W: Watch | Tab/Shift+Tab: Switch Tab | q: Quit"
         }
         Tab::Jobs => {
-            "1-6: Select Tab | Up/Down: Navigate | Enter: View Details | r: Retry | c: Clear Completed | Tab: Next Tab | q: Quit"
+            "1-8: Select Tab | Up/Down: Navigate | PgUp/PgDn, g/G: Page, Top/Bottom | Enter: View Details | r: Retry | s: Stop Watch | Space: Mark | t: Triage | n: Note | f: Filter | o: Sort by Severity | +/-: Priority | v: Columns | /: Search Results | p: Export Marked as Pack | c: Clear Completed | Tab: Next Tab | q: Quit"
         }
         Tab::Sessions => {
-            "1-6: Select Tab | Up/Down: Navigate | s: Save | S: Save As | l: Load | d: Delete | p: Export as Pack | n: New | r: Refresh | Tab: Next Tab | q: Quit"
+            "1-8: Select Tab | Up/Down: Navigate | PgUp/PgDn, g/G: Page, Top/Bottom | s: Save | S: Save As | l: Load | d: Delete | Space: Mark | a/A: Mark All/None | D: Delete with Output | p: Export as Pack | R: Rename | e: Tags/Description | /: Search | n: New | r: Refresh | Ctrl+P: Find | Tab: Next Tab | q: Quit"
         }
         Tab::Packs => {
-            "1-6: Select Tab | Up/Down: Navigate | Enter: Load Query | e: Execute Pack | r: Refresh | Tab: Next Tab | q: Quit"
+            "1-8: Select Tab | Up/Down: Navigate | PgUp/PgDn, g/G: Page, Top/Bottom | Left/Right: Highlight Query | Enter: Load Query | e: Execute Pack/Query | s: Save | S: Save As | R: Rename | r: Refresh | p: Git Pull | t: Filter by Tag | /: Search | Ctrl+P: Find | Tab: Next Tab | q: Quit"
+        }
+        Tab::Dashboard => "1-8: Select Tab | m: Notifications | Ctrl+P: Find | Tab: Next Tab | q: Quit",
+        Tab::Logs => {
+            "1-8: Select Tab | Up/Down: Scroll | PgUp/PgDn, g/G: Page, Top/Bottom (follow) | f: Follow | l: Level | /: Search | Tab: Next Tab | q: Quit"
         }
     };
 
-    let paragraph = Paragraph::new(controls)
-        .block(Block::default().borders(Borders::ALL).title("Controls"))
-        .alignment(Alignment::Center);
+    // Without room for a border (short terminals), draw a single dimmed line
+    let paragraph = if area.height < 3 {
+        Paragraph::new(controls).style(Style::default().fg(Color::DarkGray))
+    } else {
+        Paragraph::new(controls).block(Block::default().borders(Borders::ALL).title("Controls"))
+    }
+    .alignment(Alignment::Center);
 
     f.render_widget(paragraph, area);
 }
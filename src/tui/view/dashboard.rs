@@ -0,0 +1,194 @@
+use crate::tui::model::dashboard::DashboardStats;
+use crate::tui::model::jobs::JobStatus;
+use crate::tui::model::Model;
+use crate::units::format_bytes;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Sparkline},
+    Frame,
+};
+
+/// Render the Dashboard tab
+pub fn render(f: &mut Frame, model: &Model, area: Rect) {
+    let stats = DashboardStats::from_jobs(&model.jobs.jobs);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Progress gauge
+            Constraint::Length(4), // Status summary
+            Constraint::Length(5), // Duration sparkline
+            Constraint::Min(0),    // Lists
+        ])
+        .split(area);
+
+    render_progress(f, &stats, chunks[0]);
+    render_summary(f, &stats, chunks[1]);
+    render_durations(f, &stats, chunks[2]);
+
+    let lists = super::split_panels(chunks[3], [34, 33, 33]);
+
+    let rows_items: Vec<ListItem> = stats
+        .rows_per_query
+        .iter()
+        .map(|query| {
+            let mut value = query.rows.to_string();
+            if let Some(severity) = query.hit_severity() {
+                value.push_str(&format!(" [{}]", severity.as_str()));
+            }
+            // Jobs without hits, e.g. "(12/40 no hits)"
+            if query.no_hits > 0 {
+                value.push_str(&format!(" ({}/{} no hits)", query.no_hits, query.jobs));
+            }
+            let color = match query.hit_severity() {
+                Some(severity) => super::severity_color(severity),
+                None if query.rows == 0 => Color::DarkGray,
+                None => Color::Green,
+            };
+            list_line(&query.name, value, color)
+        })
+        .collect();
+    render_list(f, "Rows per Query", rows_items, lists[0]);
+
+    let slow_items: Vec<ListItem> = stats
+        .slowest_workspaces
+        .iter()
+        .map(|(name, duration)| {
+            list_line(name, format!("{:.2}s", duration.as_secs_f64()), Color::Cyan)
+        })
+        .collect();
+    render_list(f, "Slowest Workspaces", slow_items, lists[1]);
+
+    let failure_items: Vec<ListItem> = stats
+        .recent_failures
+        .iter()
+        .map(|(workspace, error)| list_line(workspace, error.clone(), Color::Red))
+        .collect();
+    render_list(f, "Recent Failures", failure_items, lists[2]);
+}
+
+/// Render overall completion progress
+fn render_progress(f: &mut Frame, stats: &DashboardStats, area: Rect) {
+    let color = if stats.failed > 0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title("Progress"))
+        .gauge_style(Style::default().fg(color).bg(Color::Black))
+        .ratio(stats.progress_ratio())
+        .label(format!(
+            "{}/{} jobs finished",
+            stats.finished(),
+            stats.total
+        ));
+
+    f.render_widget(gauge, area);
+}
+
+/// Render job counts by status and output disk usage
+fn render_summary(f: &mut Frame, stats: &DashboardStats, area: Rect) {
+    let status_span = |status: JobStatus, count: usize| {
+        Span::styled(
+            format!("{}: {}   ", status.as_str(), count),
+            Style::default()
+                .fg(status.color())
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+
+    let mut size_line = vec![
+        Span::raw("Output on disk: "),
+        Span::styled(
+            format_bytes(stats.output_bytes),
+            Style::default().fg(Color::White),
+        ),
+    ];
+    if stats.estimated_jobs > 0 {
+        size_line.extend([
+            Span::raw("   Est. data scanned: "),
+            Span::styled(
+                format_bytes(stats.scanned_bytes),
+                Style::default().fg(Color::White),
+            ),
+            Span::raw(format!(" ({} jobs)", stats.estimated_jobs)),
+        ]);
+    }
+    if stats.cpu_jobs > 0 {
+        size_line.extend([
+            Span::raw("   Query CPU: "),
+            Span::styled(
+                format!("{:.2}s", stats.cpu_secs),
+                Style::default().fg(Color::White),
+            ),
+            Span::raw(format!(" ({} jobs)", stats.cpu_jobs)),
+        ]);
+    }
+
+    let lines = vec![
+        Line::from(vec![
+            Span::raw(format!("Total: {}   ", stats.total)),
+            status_span(JobStatus::Queued, stats.queued),
+            status_span(JobStatus::Running, stats.running),
+            status_span(JobStatus::Completed, stats.completed),
+            status_span(JobStatus::Failed, stats.failed),
+            Span::styled(
+                format!("NO HITS: {}", stats.no_hits),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(size_line),
+    ];
+
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Summary"));
+    f.render_widget(paragraph, area);
+}
+
+/// Render a sparkline of job durations over time
+fn render_durations(f: &mut Frame, stats: &DashboardStats, area: Rect) {
+    // Only show the most recent durations that fit in the available width
+    let width = area.width.saturating_sub(2) as usize;
+    let start = stats.duration_history.len().saturating_sub(width);
+    let data = &stats.duration_history[start..];
+
+    let max_ms = data.iter().copied().max().unwrap_or(0);
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Job Durations (max {:.2}s)",
+            max_ms as f64 / 1000.0
+        )))
+        .data(data)
+        .style(Style::default().fg(Color::Cyan));
+
+    f.render_widget(sparkline, area);
+}
+
+/// Build a two-part list line: label and value
+fn list_line(label: &str, value: String, color: Color) -> ListItem<'static> {
+    ListItem::new(Line::from(vec![
+        Span::raw(format!("{} ", label)),
+        Span::styled(value, Style::default().fg(color)),
+    ]))
+}
+
+/// Render a titled list, showing a placeholder when empty
+fn render_list(f: &mut Frame, title: &str, items: Vec<ListItem>, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title.to_string());
+
+    if items.is_empty() {
+        let paragraph = Paragraph::new("None")
+            .block(block)
+            .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    f.render_widget(List::new(items).block(block), area);
+}
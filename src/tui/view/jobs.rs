@@ -1,21 +1,31 @@
-use crate::tui::model::jobs::JobsModel;
+use crate::tui::model::jobs::{BatchProgress, JobColumn, JobState, JobsModel, TriageFilter};
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Row, Table},
+    widgets::{Block, Borders, Cell, Gauge, Row, Table},
     Frame,
 };
 
 /// Render the Jobs tab
 pub fn render(f: &mut Frame, model: &mut JobsModel, area: Rect) {
-    // Create header
-    let header = Row::new(vec![
-        "Status",
-        "Workspace",
-        "Query",
-        "Duration",
-        "Timestamp",
-    ])
+    // Progress gauge above the table while a batch of jobs is running
+    let area = match model.batch_progress() {
+        Some(progress) => {
+            let [gauge_area, table_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+            render_progress(f, &progress, gauge_area);
+            table_area
+        }
+        None => area,
+    };
+
+    // Create header (the mark column is always first; timestamps name their time zone)
+    let header = Row::new(
+        std::iter::once(String::new()).chain(model.columns.iter().map(|column| match column {
+            JobColumn::Timestamp => format!("{} ({})", column.title(), crate::time_zone::get()),
+            _ => column.title().to_string(),
+        })),
+    )
     .style(
         Style::default()
             .fg(Color::Yellow)
@@ -23,6 +33,9 @@ pub fn render(f: &mut Frame, model: &mut JobsModel, area: Rect) {
     )
     .bottom_margin(1);
 
+    // Only jobs passing the triage filter are shown
+    let visible = model.visible_indices();
+
     // Create rows
     // Pre-compute duration strings, status strings, and timestamp strings
     let duration_strings: Vec<String> = model
@@ -39,6 +52,17 @@ pub fn render(f: &mut Frame, model: &mut JobsModel, area: Rect) {
         .jobs
         .iter()
         .map(|job| {
+            // Active watches show a rolling row count instead of the plain status
+            if let Some(watch) = job
+                .watch
+                .as_ref()
+                .filter(|_| job.status == crate::tui::model::jobs::JobStatus::Running)
+            {
+                return format!(
+                    "WATCH +{} ({} total)",
+                    watch.last_new_rows, watch.total_rows
+                );
+            }
             // For failed jobs, show error description if available
             if job.status == crate::tui::model::jobs::JobStatus::Failed {
                 if let Some(ref error) = job.error {
@@ -46,6 +70,15 @@ pub fn render(f: &mut Frame, model: &mut JobsModel, area: Rect) {
                 } else {
                     job.status.as_str().to_string()
                 }
+            } else if job
+                .result
+                .as_ref()
+                .and_then(|r| r.result.as_ref().ok())
+                .is_some_and(|success| success.truncated)
+            {
+                format!("{} (TRUNCATED)", job.status.as_str())
+            } else if success(job).is_some_and(|s| s.row_count == 0) {
+                format!("{} (NO HITS)", job.status.as_str())
             } else {
                 job.status.as_str().to_string()
             }
@@ -63,37 +96,71 @@ pub fn render(f: &mut Frame, model: &mut JobsModel, area: Rect) {
         })
         .collect();
 
-    let rows: Vec<Row> = model
-        .jobs
+    let rows: Vec<Row> = visible
         .iter()
-        .enumerate()
-        .map(|(idx, job)| {
-            Row::new(vec![
-                status_strings[idx].as_str(),
-                job.workspace_name.as_str(),
-                job.query_preview.as_str(),
-                duration_strings[idx].as_str(),
-                timestamp_strings[idx].as_str(),
-            ])
-            .style(Style::default().fg(job.status.color()))
+        .map(|&idx| {
+            let job = &model.jobs[idx];
+            let mark = if model.is_marked(job.job_id) { "*" } else { "" };
+            // Triage verdict, with a marker when the job has a note
+            let triage = match (job.triage, job.note.is_empty()) {
+                (Some(status), true) => {
+                    Cell::from(status.as_str()).style(Style::default().fg(status.color()))
+                }
+                (Some(status), false) => Cell::from(format!("{} +", status.as_str()))
+                    .style(Style::default().fg(status.color())),
+                (None, true) => Cell::from("-"),
+                (None, false) => Cell::from("- +"),
+            };
+            let mut cells = vec![Cell::from(mark)];
+            for column in &model.columns {
+                cells.push(match column {
+                    JobColumn::Status => Cell::from(status_strings[idx].as_str()),
+                    JobColumn::Triage => triage.clone(),
+                    JobColumn::Workspace => Cell::from(job.workspace_name.as_str()),
+                    JobColumn::Query => Cell::from(job.query_preview.as_str()),
+                    JobColumn::QueryName => Cell::from(job.display_name().unwrap_or("-")),
+                    JobColumn::Severity => match job.severity {
+                        Some(severity) => Cell::from(severity.as_str())
+                            .style(Style::default().fg(super::severity_color(severity))),
+                        None => Cell::from("-"),
+                    },
+                    JobColumn::Priority => Cell::from(job.priority.get().as_str()),
+                    JobColumn::Rows => Cell::from(
+                        success(job)
+                            .map(|s| s.row_count.to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                    JobColumn::Duration => Cell::from(duration_strings[idx].as_str()),
+                    JobColumn::OutputSize => Cell::from(
+                        success(job)
+                            .map(|s| crate::units::format_bytes(s.file_size))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                    JobColumn::Timestamp => Cell::from(timestamp_strings[idx].as_str()),
+                });
+            }
+            // Hits from queries with a severity stand out in its color; jobs without hits fade
+            let color = match job.hit_severity() {
+                Some(severity) => super::severity_color(severity),
+                None if success(job).is_some_and(|s| s.row_count == 0) => Color::DarkGray,
+                None => job.status.color(),
+            };
+            Row::new(cells).style(Style::default().fg(color))
         })
         .collect();
 
     // Calculate column widths
-    let widths = [
-        ratatui::layout::Constraint::Length(28), // Status - fits "FAILED (Query Error)" etc.
-        ratatui::layout::Constraint::Percentage(20), // Workspace
-        ratatui::layout::Constraint::Percentage(30), // Query
-        ratatui::layout::Constraint::Length(10), // Duration
-        ratatui::layout::Constraint::Length(19), // Timestamp - "YYYY-MM-DD HH:MM:SS"
-    ];
+    let widths: Vec<Constraint> =
+        std::iter::once(Constraint::Length(1)) // Mark
+            .chain(model.columns.iter().map(|column| column_width(*column)))
+            .collect();
 
     let table = Table::new(rows, widths)
         .header(header)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Jobs ({})", model.jobs.len())),
+                .title(jobs_title(model, visible.len())),
         )
         .highlight_style(
             Style::default()
@@ -102,5 +169,67 @@ pub fn render(f: &mut Frame, model: &mut JobsModel, area: Rect) {
         )
         .highlight_symbol(">> ");
 
-    f.render_stateful_widget(table, area, &mut model.table_state);
+    // The rows borrow the model, so scroll a copy of the state and store it afterwards
+    let mut table_state = model.table_state.clone();
+    super::render_filtered_table(f, table, area, &mut table_state, &visible);
+    model.table_state = table_state;
+}
+
+/// Width of a table column
+fn column_width(column: JobColumn) -> Constraint {
+    match column {
+        JobColumn::Status => Constraint::Length(28), // Fits "FAILED (Query Error)" etc.
+        JobColumn::Triage => Constraint::Length(16), // Fits "False Positive +"
+        JobColumn::Workspace => Constraint::Percentage(20),
+        JobColumn::Query => Constraint::Percentage(30),
+        JobColumn::QueryName => Constraint::Percentage(15),
+        JobColumn::Severity => Constraint::Length(10),
+        JobColumn::Priority => Constraint::Length(8),
+        JobColumn::Rows => Constraint::Length(10),
+        JobColumn::Duration => Constraint::Length(10),
+        JobColumn::OutputSize => Constraint::Length(10),
+        JobColumn::Timestamp => Constraint::Length(19), // "YYYY-MM-DD HH:MM:SS"
+    }
+}
+
+fn success(job: &JobState) -> Option<&crate::query_job::JobSuccess> {
+    job.result.as_ref().and_then(|r| r.result.as_ref().ok())
+}
+
+/// Render the running batch's completion and estimated time remaining
+fn render_progress(f: &mut Frame, progress: &BatchProgress, area: Rect) {
+    let eta = match progress.remaining {
+        Some(remaining) => format!("~{} remaining", super::status::format_elapsed(remaining)),
+        None => "estimating time remaining".to_string(),
+    };
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(Color::Cyan).bg(Color::Black))
+        .ratio(progress.ratio())
+        .label(format!(
+            "{}/{} complete, {}",
+            progress.completed, progress.total, eta
+        ));
+    f.render_widget(gauge, area);
+}
+
+/// Build the table title with job, filter and mark counts
+fn jobs_title(model: &JobsModel, visible: usize) -> String {
+    let mut title = if model.triage_filter == TriageFilter::All {
+        format!("Jobs ({}", model.jobs.len())
+    } else {
+        format!(
+            "Jobs ({} of {}, filter: {}",
+            visible,
+            model.jobs.len(),
+            model.triage_filter.as_str()
+        )
+    };
+    if !model.marked.is_empty() {
+        title.push_str(&format!(", {} marked", model.marked.len()));
+    }
+    if model.sort_by_severity {
+        title.push_str(", by severity");
+    }
+    title.push(')');
+    title
 }
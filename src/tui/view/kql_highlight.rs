@@ -1,5 +1,6 @@
+use crate::tui::model::query::region_marker;
 use ratatui::{
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::Span,
 };
 
@@ -422,6 +423,9 @@ pub fn highlight_line(line: &str) -> Vec<Span<'_>> {
             TokenType::Type => Style::default().fg(Color::Cyan), // VS Code: #4EC9B0 (teal/cyan for types)
             TokenType::String => Style::default().fg(Color::LightRed), // VS Code: #CE9178 (peachy/salmon for strings)
             TokenType::Number => Style::default().fg(Color::LightGreen), // VS Code: #B5CEA8 (pale green for numbers)
+            TokenType::Comment if region_marker(token_str).is_some() => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD), // Section markers stand out from plain comments
             TokenType::Comment => Style::default().fg(Color::Green), // VS Code: #6A9955 (green for comments)
             TokenType::Pipe => Style::default().fg(Color::White), // Pipe/semicolon as white like other operators
             TokenType::Punctuation => Style::default().fg(Color::White), // VS Code: punctuation is typically white
@@ -447,6 +451,18 @@ pub fn highlight_line(line: &str) -> Vec<Span<'_>> {
     spans
 }
 
+/// Highlight the `//#region` line of a folded section, followed by a summary of what's hidden
+pub fn highlight_folded(line: &str, hidden_lines: usize) -> Vec<Span<'_>> {
+    let mut spans = highlight_line(line);
+    spans.push(Span::styled(
+        format!(" ⋯ {} lines folded", hidden_lines),
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC),
+    ));
+    spans
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -471,4 +487,11 @@ mod tests {
         let spans = highlight_line(line);
         assert!(!spans.is_empty());
     }
+
+    #[test]
+    fn test_folded_highlighting() {
+        let spans = highlight_folded("//#region Enrichment", 4);
+        assert!(spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans.last().unwrap().content, " ⋯ 4 lines folded");
+    }
 }
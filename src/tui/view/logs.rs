@@ -0,0 +1,67 @@
+use crate::tui::model::logs::LogsModel;
+use log::Level;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+/// Render the Logs tab
+pub fn render(f: &mut Frame, logs: &LogsModel, area: Rect) {
+    let visible = logs.visible();
+
+    let mut title = format!(
+        "Logs - {} of {} record(s) | level: {}",
+        visible.len(),
+        logs.total(),
+        logs.level_label()
+    );
+    if !logs.search.is_empty() {
+        title.push_str(&format!(" | search: '{}'", logs.search));
+    }
+    title.push_str(if logs.follow {
+        " | following"
+    } else {
+        " | paused (G or f to follow)"
+    });
+
+    let items: Vec<ListItem> = visible
+        .iter()
+        .map(|line| {
+            let target = line
+                .target
+                .strip_prefix("kql_panopticon::")
+                .unwrap_or(&line.target);
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} ", line.time),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:<5} ", line.level),
+                    Style::default().fg(level_color(line.level)),
+                ),
+                Span::styled(format!("{}: ", target), Style::default().fg(Color::Cyan)),
+                Span::raw(line.message.replace('\n', " ")),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(logs.selected_index(visible.len()));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Error => Color::Red,
+        Level::Warn => Color::Yellow,
+        Level::Info => Color::Green,
+        Level::Debug => Color::Blue,
+        Level::Trace => Color::DarkGray,
+    }
+}
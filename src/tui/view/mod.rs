@@ -1,33 +1,128 @@
 pub mod controls;
+pub mod dashboard;
 pub mod jobs;
 pub mod kql_highlight;
+pub mod logs;
 pub mod packs;
 pub mod popup;
 pub mod query;
 pub mod session;
 pub mod settings;
+pub mod status;
 pub mod syntax_textarea;
 pub mod tabs;
 pub mod workspaces;
 
+use crate::query_pack::Severity;
 use crate::tui::message::Tab;
-use crate::tui::model::Model;
+use crate::tui::model::{InitState, Model};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Paragraph, Table, TableState, Wrap},
     Frame,
 };
+use std::rc::Rc;
+
+/// Below this width, side-by-side panels stack and tab titles are shortened
+pub const NARROW_WIDTH: u16 = 80;
+
+/// Below this height, the tab and controls bars lose their borders
+pub const SHORT_HEIGHT: u16 = 24;
+
+/// Below this height, the controls bar is hidden entirely
+const TINY_HEIGHT: u16 = 14;
+
+/// Smallest terminal the UI will draw into
+const MIN_WIDTH: u16 = 30;
+const MIN_HEIGHT: u16 = 8;
+
+/// Check if an area is too narrow for side-by-side panels
+pub fn is_narrow(area: Rect) -> bool {
+    area.width < NARROW_WIDTH
+}
+
+/// Split an area into panels: side by side, or stacked top to bottom on narrow terminals
+pub fn split_panels<const N: usize>(area: Rect, percentages: [u16; N]) -> Rc<[Rect]> {
+    let direction = if is_narrow(area) {
+        Direction::Vertical
+    } else {
+        Direction::Horizontal
+    };
+    Layout::default()
+        .direction(direction)
+        .constraints(percentages.map(Constraint::Percentage))
+        .split(area)
+}
+
+/// Color for a pack query severity
+pub fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Info => Color::Gray,
+        Severity::Low => Color::Blue,
+        Severity::Medium => Color::Yellow,
+        Severity::High => Color::LightRed,
+        Severity::Critical => Color::Magenta,
+    }
+}
+
+/// Render a table showing only the `visible` rows of a list
+///
+/// `table_state` selects by index into the whole list; the table only sees visible rows, so
+/// the selection is translated into a row position and the scroll offset is kept.
+pub fn render_filtered_table(
+    f: &mut Frame,
+    table: Table,
+    area: Rect,
+    table_state: &mut TableState,
+    visible: &[usize],
+) {
+    let mut state = TableState::default()
+        .with_offset(table_state.offset())
+        .with_selected(
+            table_state
+                .selected()
+                .and_then(|selected| visible.iter().position(|&i| i == selected)),
+        );
+    f.render_stateful_widget(table, area, &mut state);
+    *table_state.offset_mut() = state.offset();
+}
 
 /// Main UI rendering function
 pub fn ui(f: &mut Frame, model: &mut Model) {
     let size = f.area();
 
-    // Main layout: top bar, content, bottom bar
+    // Re-validated on every frame, so growing the terminal restores the UI
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        let message = format!(
+            "Terminal too small ({}x{}). Please enlarge to at least {}x{}.",
+            size.width, size.height, MIN_WIDTH, MIN_HEIGHT
+        );
+        let paragraph = Paragraph::new(message)
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, size);
+        return;
+    }
+
+    // Short terminals drop the bar borders; tiny ones drop the status and controls bars
+    let short = size.height < SHORT_HEIGHT;
+    let bar_height = if short { 1 } else { 3 };
+    let (status_height, controls_height) = if size.height < TINY_HEIGHT {
+        (0, 0)
+    } else {
+        (1, bar_height)
+    };
+
+    // Main layout: top bar, content, status line, bottom bar
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Tab bar
-            Constraint::Min(0),    // Content
-            Constraint::Length(3), // Controls
+            Constraint::Length(bar_height),      // Tab bar
+            Constraint::Min(0),                  // Content
+            Constraint::Length(status_height),   // Status line
+            Constraint::Length(controls_height), // Controls
         ])
         .split(size);
 
@@ -35,7 +130,7 @@ pub fn ui(f: &mut Frame, model: &mut Model) {
     tabs::render(
         f,
         model.current_tab,
-        model.init_state,
+        model.init_state == InitState::Initializing || model.workspaces.refreshing,
         model.spinner_frame,
         chunks[0],
     );
@@ -43,15 +138,30 @@ pub fn ui(f: &mut Frame, model: &mut Model) {
     // Render content based on current tab
     match model.current_tab {
         Tab::Settings => settings::render(f, &mut model.settings, chunks[1]),
-        Tab::Workspaces => workspaces::render(f, &mut model.workspaces, chunks[1]),
+        Tab::Workspaces => workspaces::render(
+            f,
+            &mut model.workspaces,
+            model.settings.failure_streak,
+            chunks[1],
+        ),
         Tab::Query => query::render(f, &model.query, &model.jobs, chunks[1]),
         Tab::Jobs => jobs::render(f, &mut model.jobs, chunks[1]),
         Tab::Sessions => session::render(f, model, chunks[1]),
         Tab::Packs => packs::render(f, model, chunks[1]),
+        Tab::Dashboard => dashboard::render(f, model, chunks[1]),
+        Tab::Logs => logs::render(f, &model.logs, chunks[1]),
+    }
+
+    // Render status line and controls bar
+    if status_height > 0 {
+        status::render(f, model, chunks[2]);
+    }
+    if controls_height > 0 {
+        controls::render(f, model.current_tab, model.workspaces.refreshing, chunks[3]);
     }
 
-    // Render controls bar
-    controls::render(f, model.current_tab, chunks[2]);
+    // Render toast notification (non-blocking, under any popup)
+    popup::render_toast(f, &model.notifications);
 
     // Render popup if any
     if let Some(ref popup) = model.popup {
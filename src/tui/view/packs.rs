@@ -1,6 +1,10 @@
-use crate::tui::model::{packs::PacksModel, Model};
+use crate::query_pack::QueryPack;
+use crate::tui::model::{
+    packs::{PacksModel, SkippedFile},
+    Model,
+};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
@@ -9,14 +13,18 @@ use ratatui::{
 
 /// Render the Query Packs tab
 pub fn render(f: &mut Frame, model: &mut Model, area: Rect) {
-    // Split area: left side for pack list, right side for details
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
-        .split(area);
+    // Split area: pack list on the left (or top, on narrow terminals), details beside it
+    let chunks = super::split_panels(area, [40, 60]);
+
+    // Details share the list's border when side by side, so drop the shared edge
+    let details_borders = if super::is_narrow(area) {
+        Borders::ALL
+    } else {
+        Borders::TOP | Borders::RIGHT | Borders::BOTTOM
+    };
 
     render_pack_list(f, model, chunks[0]);
-    render_pack_details(f, &model.packs, chunks[1]);
+    render_pack_details(f, &model.packs, chunks[1], details_borders);
 }
 
 /// Render the list of query packs
@@ -28,7 +36,7 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
         .query
         .pack_context
         .as_ref()
-        .map(|ctx| ctx.pack_path.as_str());
+        .map(|ctx| ctx.pack_path.as_path());
 
     // Show loading or error state
     if packs_model.loading {
@@ -47,15 +55,31 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
         return;
     }
 
+    // Report library files that were skipped as non-packs below the list
+    let area = if packs_model.skipped.is_empty() {
+        area
+    } else {
+        let height = (packs_model.skipped.len() as u16 + 2)
+            .min(area.height / 3)
+            .max(3);
+        let [list_area, skipped_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).areas(area);
+        render_skipped_files(f, &packs_model.skipped, skipped_area);
+        list_area
+    };
+
     // Show empty state
     if packs_model.packs.is_empty() {
+        let library_path = QueryPack::get_library_path("")
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
         let empty_lines = vec![
             Line::from(""),
             Line::from("No query packs found"),
             Line::from(""),
             Line::from(vec![
                 Span::raw("Create packs in: "),
-                Span::styled("~/.kql-panopticon/packs/", Style::default().fg(Color::Cyan)),
+                Span::styled(library_path, Style::default().fg(Color::Cyan)),
             ]),
             Line::from(""),
             Line::from("Press 'r' to refresh"),
@@ -70,7 +94,7 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
     }
 
     // Create header
-    let header = Row::new(vec!["Pack", "Status", "Queries"])
+    let header = Row::new(vec!["Pack", "Source", "Status", "Queries"])
         .style(
             Style::default()
                 .fg(Color::Yellow)
@@ -78,11 +102,14 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
         )
         .bottom_margin(1);
 
+    // Only packs passing the tag filter are shown
+    let visible = packs_model.visible_indices();
+
     // Create rows
-    let rows: Vec<Row> = packs_model
-        .packs
+    let rows: Vec<Row> = visible
         .iter()
-        .map(|entry| {
+        .map(|&idx| {
+            let entry = &packs_model.packs[idx];
             let name = entry.get_display_name();
             let query_count = entry
                 .get_query_count()
@@ -98,7 +125,7 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
 
             // Check if this pack is currently loaded
             let is_loaded = loaded_pack_path
-                .map(|loaded| loaded == entry.relative_path)
+                .map(|loaded| loaded == entry.path)
                 .unwrap_or(false);
 
             let status = if is_loaded {
@@ -107,8 +134,15 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
                 Cell::from("")
             };
 
+            let source = if entry.read_only {
+                format!("{} (ro)", entry.source)
+            } else {
+                entry.source.clone()
+            };
+
             Row::new(vec![
                 Cell::from(name_with_indicator),
+                Cell::from(source).style(Style::default().fg(Color::Cyan)),
                 status,
                 Cell::from(query_count),
             ])
@@ -117,30 +151,66 @@ fn render_pack_list(f: &mut Frame, model: &mut Model, area: Rect) {
 
     // Calculate column widths
     let widths = [
-        Constraint::Percentage(55),
+        Constraint::Percentage(45),
         Constraint::Percentage(20),
-        Constraint::Percentage(25),
+        Constraint::Percentage(20),
+        Constraint::Percentage(15),
     ];
 
-    let table = Table::new(rows, widths)
-        .header(header)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(format!("Query Packs ({})", packs_model.pack_count())),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol(">> ");
+    let table =
+        Table::new(rows, widths)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(
+                if packs_model.tag_filter.is_empty() {
+                    format!("Query Packs ({})", packs_model.pack_count())
+                } else {
+                    format!(
+                        "Query Packs ({} of {}, tags: {})",
+                        visible.len(),
+                        packs_model.pack_count(),
+                        packs_model.tag_filter.join(", ")
+                    )
+                },
+            ))
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
 
-    f.render_stateful_widget(table, area, &mut packs_model.table_state);
+    super::render_filtered_table(f, table, area, &mut packs_model.table_state, &visible);
+}
+
+/// Render the library files that were skipped because they aren't query packs
+fn render_skipped_files(f: &mut Frame, skipped: &[SkippedFile], area: Rect) {
+    let lines: Vec<Line> = skipped
+        .iter()
+        .map(|file| {
+            Line::from(vec![
+                Span::styled(file.path.as_str(), Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format!(" - {}", file.reason),
+                    Style::default().fg(Color::Gray),
+                ),
+            ])
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(
+                "Skipped {} non-pack files (.kqlignore to hide)",
+                skipped.len()
+            ))
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(paragraph, area);
 }
 
 /// Render details for the selected pack
-fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
+fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect, borders: Borders) {
     let selected_entry = model.get_selected_entry();
 
     if selected_entry.is_none() {
@@ -150,11 +220,7 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
             Line::from(""),
             Line::from("Use Up/Down to select a pack"),
         ])
-        .block(
-            Block::default()
-                .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
-                .title("Pack Details"),
-        )
+        .block(Block::default().borders(borders).title("Pack Details"))
         .style(Style::default().fg(Color::Gray));
         f.render_widget(help_paragraph, area);
         return;
@@ -178,11 +244,7 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
                 Style::default().fg(Color::Gray),
             )),
         ])
-        .block(
-            Block::default()
-                .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
-                .title("Pack Details"),
-        )
+        .block(Block::default().borders(borders).title("Pack Details"))
         .wrap(Wrap { trim: true });
         f.render_widget(error_paragraph, area);
         return;
@@ -191,11 +253,7 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
     // Show loading state if pack not loaded yet
     if entry.pack.is_none() {
         let loading_paragraph = Paragraph::new("Press Enter to load pack details...")
-            .block(
-                Block::default()
-                    .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
-                    .title("Pack Details"),
-            )
+            .block(Block::default().borders(borders).title("Pack Details"))
             .style(Style::default().fg(Color::Yellow));
         f.render_widget(loading_paragraph, area);
         return;
@@ -208,6 +266,14 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
             Span::styled("Name: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw(&pack.name),
         ]),
+        Line::from(vec![
+            Span::styled("Source: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(&entry.source),
+            Span::styled(
+                if entry.read_only { " (read-only)" } else { "" },
+                Style::default().fg(Color::Gray),
+            ),
+        ]),
         Line::from(""),
     ];
 
@@ -239,6 +305,39 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
         ]));
     }
 
+    // Add pack tags if present
+    if !pack.tags.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Tags: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(pack.tags.join(", "), Style::default().fg(Color::Magenta)),
+        ]));
+    }
+
+    // Add scheduling metadata if present
+    if let Some(timespan) = &pack.default_timespan {
+        lines.push(Line::from(vec![
+            Span::styled("Timespan: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(timespan, Style::default().fg(Color::Cyan)),
+            Span::styled(
+                if pack.timespan().is_some() {
+                    " (applied to each query)"
+                } else {
+                    " (invalid, ignored)"
+                },
+                Style::default().fg(Color::Gray),
+            ),
+        ]));
+    }
+    if let Some(frequency) = &pack.recommended_frequency {
+        lines.push(Line::from(vec![
+            Span::styled(
+                "Recommended frequency: ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(frequency),
+        ]));
+    }
+
     lines.push(Line::from(""));
 
     // Add queries section
@@ -249,11 +348,33 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
     ]));
     lines.push(Line::from(""));
 
-    // List queries
+    // List queries, marking the highlighted one (Left/Right to move, Esc for the whole pack)
+    let selected_query = model.selected_query();
     for (i, query) in queries.iter().enumerate() {
+        let highlighted = selected_query == Some(i);
         lines.push(Line::from(vec![
-            Span::styled(format!("  {}. ", i + 1), Style::default().fg(Color::Yellow)),
-            Span::raw(&query.name),
+            Span::styled(
+                format!("{}{}. ", if highlighted { ">> " } else { "  " }, i + 1),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(
+                &query.name,
+                if highlighted {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                },
+            ),
+            Span::styled(
+                if query.tags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", query.tags.join(", "))
+                },
+                Style::default().fg(Color::Magenta),
+            ),
         ]));
 
         if let Some(description) = &query.description {
@@ -272,17 +393,27 @@ fn render_pack_details(f: &mut Frame, model: &PacksModel, area: Rect) {
         "Controls:",
         Style::default().add_modifier(Modifier::BOLD),
     )));
-    lines.push(Line::from("  Enter - Load first query into editor"));
+    lines.push(Line::from(
+        "  Left/Right - Highlight a query (Esc for the whole pack)",
+    ));
+    lines.push(Line::from(
+        "  Enter - Load highlighted (or first) query into editor",
+    ));
     lines.push(Line::from("  s - Save current query changes to pack"));
-    lines.push(Line::from("  e - Execute pack on selected workspaces"));
+    lines.push(Line::from("  S - Save loaded pack as a new file"));
+    lines.push(Line::from(if selected_query.is_some() {
+        "  e - Execute highlighted query on selected workspaces"
+    } else {
+        "  e - Execute pack on selected workspaces"
+    }));
     lines.push(Line::from("  r - Refresh pack list"));
+    lines.push(Line::from("  p - Pull pack updates from git remote"));
+    lines.push(Line::from(
+        "  t - Filter packs by tag (execute runs matching queries)",
+    ));
 
     let details_paragraph = Paragraph::new(lines)
-        .block(
-            Block::default()
-                .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
-                .title("Pack Details"),
-        )
+        .block(Block::default().borders(borders).title("Pack Details"))
         .wrap(Wrap { trim: true });
 
     f.render_widget(details_paragraph, area);
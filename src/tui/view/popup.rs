@@ -1,11 +1,27 @@
+use crate::retention::CleanPlan;
+use crate::run_manifest::RunManifest;
 use crate::tui::model::{
-    jobs::JobState, query::QueryModel, session::SessionModel, settings::SettingsModel, Model, Popup,
+    chart::{ChartStyle, JobChart},
+    finder::FinderModel,
+    jobs::{JobColumnPicker, JobError, JobState, JobsModel, PostFilterInput, ResultSearch},
+    logs::LogsModel,
+    notifications::NotificationsModel,
+    packs::{MatchField, PacksModel, RunConfirmation},
+    query::{ParameterInput, QueryModel, RunFormats},
+    row_inspector::RowInspector,
+    session::{SessionDetailsInput, SessionModel},
+    settings::SettingsModel,
+    workspaces::{SavedSearchBrowser, SubscriptionPicker},
+    LockConflict, Model, Popup,
 };
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    widgets::{
+        Bar, BarChart, BarGroup, Block, Borders, Clear, List, ListItem, ListState, Paragraph,
+        Sparkline, Wrap,
+    },
     Frame,
 };
 
@@ -15,20 +31,154 @@ const ERROR_POPUP_HEIGHT: u16 = 30;
 const SETTINGS_EDIT_POPUP_WIDTH: u16 = 60;
 const SETTINGS_EDIT_POPUP_HEIGHT: u16 = 25;
 const JOB_NAME_INPUT_POPUP_WIDTH: u16 = 50;
-const JOB_NAME_INPUT_POPUP_HEIGHT: u16 = 20;
+const JOB_NAME_INPUT_POPUP_HEIGHT: u16 = 30;
+const QUERY_PARAMETERS_POPUP_WIDTH: u16 = 60;
+const QUERY_PARAMETERS_POPUP_HEIGHT: u16 = 50;
 const SESSION_NAME_INPUT_POPUP_WIDTH: u16 = 50;
 const SESSION_NAME_INPUT_POPUP_HEIGHT: u16 = 20;
+const PACK_NAME_INPUT_POPUP_WIDTH: u16 = 50;
+const PACK_NAME_INPUT_POPUP_HEIGHT: u16 = 20;
+const SESSION_DETAILS_POPUP_WIDTH: u16 = 60;
+const SESSION_DETAILS_POPUP_HEIGHT: u16 = 25;
 const JOB_DETAILS_POPUP_WIDTH: u16 = 80;
 const JOB_DETAILS_POPUP_HEIGHT: u16 = 80;
+const SUBSCRIPTION_FILTER_POPUP_WIDTH: u16 = 70;
+const SUBSCRIPTION_FILTER_POPUP_HEIGHT: u16 = 70;
+const JOB_COLUMNS_POPUP_WIDTH: u16 = 50;
+const JOB_COLUMNS_POPUP_HEIGHT: u16 = 50;
+const FINDER_POPUP_WIDTH: u16 = 70;
+const FINDER_POPUP_HEIGHT: u16 = 60;
+const RESULT_SEARCH_POPUP_WIDTH: u16 = 80;
+const RESULT_SEARCH_POPUP_HEIGHT: u16 = 70;
+const SAVED_SEARCHES_POPUP_WIDTH: u16 = 80;
+const SAVED_SEARCHES_POPUP_HEIGHT: u16 = 80;
+const RESUME_PACK_RUN_POPUP_WIDTH: u16 = 60;
+const RESUME_PACK_RUN_POPUP_HEIGHT: u16 = 30;
+const CONFIRM_PACK_RUN_POPUP_WIDTH: u16 = 70;
+const CONFIRM_PACK_RUN_POPUP_HEIGHT: u16 = 70;
+/// Workspace names listed in the pack run preview before "+N more"
+const CONFIRM_PACK_RUN_WORKSPACES: usize = 10;
+/// Query names listed in the pack run preview before "+N more"
+const CONFIRM_PACK_RUN_QUERIES: usize = 12;
+const CONFIRM_REAUTHENTICATE_POPUP_WIDTH: u16 = 60;
+const CONFIRM_REAUTHENTICATE_POPUP_HEIGHT: u16 = 25;
+const JOB_CHART_POPUP_WIDTH: u16 = 90;
+const JOB_CHART_POPUP_HEIGHT: u16 = 80;
+const ROW_INSPECTOR_POPUP_WIDTH: u16 = 80;
+const ROW_INSPECTOR_POPUP_HEIGHT: u16 = 80;
+const REEXPORT_FORMAT_POPUP_WIDTH: u16 = 50;
+const REEXPORT_FORMAT_POPUP_HEIGHT: u16 = 25;
+const NOTIFICATIONS_POPUP_WIDTH: u16 = 80;
+const NOTIFICATIONS_POPUP_HEIGHT: u16 = 70;
+
+// Toast size (absolute cells)
+const TOAST_WIDTH: u16 = 50;
+const TOAST_HEIGHT: u16 = 4;
 
 /// Render a popup window
 pub fn render(f: &mut Frame, popup: &Popup, model: &Model) {
     match popup {
         Popup::Error(msg) => render_error(f, msg),
-        Popup::Success(msg) => render_success(f, msg),
+        Popup::Info(msg) => render_message(f, "Info", Color::Cyan, msg),
+        Popup::Notifications => render_notifications(f, &model.notifications),
+        Popup::LockConflict => {
+            if let Some(conflict) = &model.lock_conflict {
+                render_lock_conflict(f, conflict);
+            }
+        }
+        Popup::ResumePackRun => {
+            if let Some(manifest) = &model.packs.resume_candidate {
+                render_resume_pack_run(f, manifest);
+            }
+        }
+        Popup::ConfirmReauthenticate => render_confirm_reauthenticate(f),
+        Popup::ConfirmPackRun => {
+            if let Some(confirmation) = &model.packs.pending_confirmation {
+                render_confirm_pack_run(f, confirmation);
+            }
+        }
+        Popup::ReexportFormat(job_idx) => {
+            if let Some(job) = model.jobs.jobs.get(*job_idx) {
+                render_reexport_format(f, job);
+            }
+        }
+        Popup::ConfirmSessionsDelete => {
+            if let Some(plan) = &model.sessions.pending_delete {
+                render_confirm_sessions_delete(f, plan);
+            }
+        }
+        Popup::JobChart => {
+            if let Some(chart) = &model.jobs.chart {
+                render_job_chart(f, chart);
+            }
+        }
+        Popup::RowInspector => {
+            if let Some(inspector) = &model.jobs.row_inspector {
+                render_row_inspector(f, inspector);
+            }
+        }
         Popup::SettingsEdit => render_settings_edit(f, &model.settings),
         Popup::JobNameInput => render_job_name_input(f, &model.query),
+        Popup::QueryParameters => {
+            if let Some(input) = &model.query.parameter_input {
+                render_query_parameters(f, input);
+            }
+        }
         Popup::SessionNameInput => render_session_name_input(f, &model.sessions),
+        Popup::PackNameInput => render_pack_name_input(f, &model.jobs),
+        Popup::JobNoteInput => render_job_note_input(f, &model.jobs),
+        Popup::PostFilterInput => {
+            if let Some(input) = &model.jobs.post_filter {
+                render_post_filter_input(f, input);
+            }
+        }
+        Popup::PackSaveAs => render_pack_save_as(f, &model.packs),
+        Popup::SessionRename => render_rename_input(
+            f,
+            "Rename Session",
+            model.sessions.rename_input.as_deref(),
+            "Renames the session file and its stored name",
+        ),
+        Popup::SessionDetails => {
+            if let Some(input) = &model.sessions.details_input {
+                render_session_details(f, input);
+            }
+        }
+        Popup::SessionSearch => render_session_search(f, &model.sessions),
+        Popup::LogSearch => render_log_search(f, &model.logs),
+        Popup::PackRename => render_rename_input(
+            f,
+            "Rename Pack",
+            model.packs.rename_input.as_deref(),
+            "Sets the pack's name and renames its file to match",
+        ),
+        Popup::PackTagFilter => render_pack_tag_filter(f, &model.packs),
+        Popup::JobColumns => {
+            if let Some(picker) = &model.jobs.column_picker {
+                render_job_columns(f, picker);
+            }
+        }
+        Popup::SubscriptionFilter => {
+            if let Some(picker) = &model.workspaces.subscription_picker {
+                render_subscription_filter(f, picker);
+            }
+        }
+        Popup::SavedSearches => {
+            if let Some(browser) = &model.workspaces.saved_searches {
+                render_saved_searches(f, browser);
+            }
+        }
+        Popup::PackSearch => render_pack_search(f, &model.packs),
+        Popup::ResultSearch => {
+            if let Some(search) = &model.jobs.result_search {
+                render_result_search(f, search);
+            }
+        }
+        Popup::Finder => {
+            if let Some(finder) = &model.finder {
+                render_finder(f, finder);
+            }
+        }
         Popup::JobDetails(job_idx) => {
             if let Some(job) = model.jobs.jobs.get(*job_idx) {
                 render_job_details(f, job);
@@ -37,16 +187,877 @@ pub fn render(f: &mut Frame, popup: &Popup, model: &Model) {
     }
 }
 
-/// Render an error popup
-fn render_error(f: &mut Frame, msg: &str) {
-    let area = centered_rect(ERROR_POPUP_WIDTH, ERROR_POPUP_HEIGHT, f.area());
+/// Describe a job's query statistics as detail lines
+fn query_statistics_lines(
+    stats: &crate::client::QueryStatistics,
+    label_style: Style,
+    value_style: Style,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    let mut usage = Vec::new();
+    if let Some(cpu_secs) = stats.cpu_secs {
+        usage.push(format!("{:.2}s CPU", cpu_secs));
+    }
+    if let Some(execution_secs) = stats.execution_secs {
+        usage.push(format!("{:.2}s server time", execution_secs));
+    }
+    if let Some(peak_memory) = stats.peak_memory_bytes {
+        usage.push(format!(
+            "{} peak memory",
+            crate::units::format_bytes(peak_memory)
+        ));
+    }
+    if !usage.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("  Query stats: ", label_style),
+            Span::styled(usage.join(", "), value_style),
+        ]));
+    }
+
+    let mut scanned = Vec::new();
+    if let (Some(rows), Some(total)) = (stats.rows_scanned, stats.rows_total) {
+        scanned.push(format!("{} of {} rows", rows, total));
+    }
+    if let (Some(extents), Some(total)) = (stats.extents_scanned, stats.extents_total) {
+        scanned.push(format!("{} of {} extents", extents, total));
+    }
+    if !scanned.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("  Data scanned: ", label_style),
+            Span::styled(scanned.join(", "), value_style),
+        ]));
+    }
+
+    lines
+}
+
+/// Render an error popup
+fn render_error(f: &mut Frame, msg: &str) {
+    render_message(f, "Error", Color::Red, msg);
+}
+
+/// Render a plain message popup with the given title and colour
+fn render_message(f: &mut Frame, title: &str, color: Color, msg: &str) {
+    let area = centered_rect(ERROR_POPUP_WIDTH, ERROR_POPUP_HEIGHT, f.area());
+
+    let paragraph = Paragraph::new(msg)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title.to_string())
+                .style(Style::default().bg(Color::Black).fg(color)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the notification history popup (newest first)
+fn render_notifications(f: &mut Frame, notifications: &NotificationsModel) {
+    let area = centered_rect(
+        NOTIFICATIONS_POPUP_WIDTH,
+        NOTIFICATIONS_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let items: Vec<ListItem> = notifications
+        .newest_first()
+        .map(|n| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} ", n.timestamp.format("%H:%M:%S")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:<5} ", n.level.as_str()),
+                    Style::default()
+                        .fg(n.level.color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(n.message.replace('\n', " ")),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Notifications ({}) - Up/Down: Scroll | c: Clear | Esc: Close",
+                    notifications.entries.len()
+                ))
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    let mut list_state = notifications.list_state.clone();
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Render the prompt shown when a job fails with an authentication error
+fn render_confirm_reauthenticate(f: &mut Frame) {
+    let area = centered_rect(
+        CONFIRM_REAUTHENTICATE_POPUP_WIDTH,
+        CONFIRM_REAUTHENTICATE_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let text = "A job failed because Azure rejected the credentials.\n\nIf your `az login` session lapsed, log in again in another terminal first.\n\ny/Enter: Re-authenticate now (clears cached tokens)\nn/Esc: Not now";
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Re-authenticate?")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the format choice for re-exporting a job's results
+fn render_reexport_format(f: &mut Frame, job: &JobState) {
+    use crate::reexport::ResultFormat;
+    let area = centered_rect(
+        REEXPORT_FORMAT_POPUP_WIDTH,
+        REEXPORT_FORMAT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let source = job
+        .result
+        .as_ref()
+        .and_then(|r| r.result.as_ref().ok())
+        .map(|success| success.output_path.as_path());
+    let source_format = source.and_then(ResultFormat::of_path);
+    let mut text = format!(
+        "Convert {} without re-running the query.\n\n",
+        source
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    );
+    for (key, format) in [
+        ('c', ResultFormat::Csv),
+        ('j', ResultFormat::Json),
+        ('n', ResultFormat::Ndjson),
+    ] {
+        if source_format != Some(format) {
+            text.push_str(&format!("{}: {}\n", key, format.label()));
+        }
+    }
+    text.push_str("Esc: Cancel");
+
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Re-export Results")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the confirmation for deleting sessions with their output
+fn render_confirm_sessions_delete(f: &mut Frame, plan: &CleanPlan) {
+    let area = centered_rect(
+        RESUME_PACK_RUN_POPUP_WIDTH,
+        RESUME_PACK_RUN_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let freed = crate::units::format_bytes(plan.bytes);
+    let output = if plan.run_dirs.is_empty() {
+        format!("Output folders are kept. Frees {}.", freed)
+    } else {
+        format!(
+            "{} run folder(s) of output not used by other sessions will also be deleted, freeing {}.",
+            plan.run_dirs.len(),
+            freed
+        )
+    };
+    let text = format!(
+        "Delete {} session(s): {}\n\n{}\n\ny/Enter: Delete\nn/Esc: Cancel",
+        plan.sessions.len(),
+        plan.sessions.join(", "),
+        output
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Delete Sessions?")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the preview of a pack run awaiting confirmation
+fn render_confirm_pack_run(f: &mut Frame, confirmation: &RunConfirmation) {
+    let area = centered_rect(
+        CONFIRM_PACK_RUN_POPUP_WIDTH,
+        CONFIRM_PACK_RUN_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let workspaces = &confirmation.workspaces;
+    let mut shown = workspaces
+        .iter()
+        .take(CONFIRM_PACK_RUN_WORKSPACES)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if workspaces.len() > CONFIRM_PACK_RUN_WORKSPACES {
+        shown.push_str(&format!(
+            " (+{} more)",
+            workspaces.len() - CONFIRM_PACK_RUN_WORKSPACES
+        ));
+    }
+    let queries = &confirmation.queries;
+    let mut text = String::new();
+    if let Some(reason) = &confirmation.reason {
+        text.push_str(&format!("{}.\n\n", reason));
+    }
+    text.push_str(&format!(
+        "Pack: {}\nJobs: {} quer{} x {} workspace{} = {}\nConcurrency: up to {} at once\nSubscriptions: {}\nWorkspaces: {}\n",
+        confirmation.pack_name,
+        queries.len(),
+        if queries.len() == 1 { "y" } else { "ies" },
+        workspaces.len(),
+        if workspaces.len() == 1 { "" } else { "s" },
+        queries.len() * workspaces.len(),
+        confirmation.concurrency,
+        confirmation.subscriptions,
+        shown
+    ));
+    if let Some(timespan) = &confirmation.timespan {
+        text.push_str(&format!("Timespan: {}\n", timespan));
+    }
+    text.push_str(&format!(
+        "Output: {}\nWrites: {}\n",
+        confirmation.output_folder.display(),
+        if confirmation.outputs.is_empty() {
+            "nothing (no export format enabled)".to_string()
+        } else {
+            confirmation.outputs.join(", ")
+        }
+    ));
+    if confirmation.resume {
+        text.push_str("Resuming the previous run (completed pairs are skipped)\n");
+    }
+    text.push_str("\nQueries:\n");
+    for name in queries.iter().take(CONFIRM_PACK_RUN_QUERIES) {
+        text.push_str(&format!("  {}\n", name));
+    }
+    if queries.len() > CONFIRM_PACK_RUN_QUERIES {
+        text.push_str(&format!(
+            "  (+{} more)\n",
+            queries.len() - CONFIRM_PACK_RUN_QUERIES
+        ));
+    }
+    // A high-impact run needs the explicit key
+    text.push_str(if confirmation.reason.is_some() {
+        "\ny: Run\nn/Esc: Cancel"
+    } else {
+        "\ny/Enter: Run\nn/Esc: Cancel"
+    });
+
+    let (title, border) = match confirmation.reason {
+        Some(_) => ("Confirm Pack Run", Color::Yellow),
+        None => ("Run Pack", Color::Cyan),
+    };
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(border))
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the prompt to resume an incomplete pack run
+fn render_resume_pack_run(f: &mut Frame, manifest: &RunManifest) {
+    let area = centered_rect(
+        RESUME_PACK_RUN_POPUP_WIDTH,
+        RESUME_PACK_RUN_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let text = format!(
+        "A previous run of '{}' did not finish.\n\nStarted: {}\nCompleted: {}/{} query/workspace pairs\n\ny/Enter: Resume (skip completed pairs)\nn: Start a fresh run\nEsc: Cancel",
+        manifest.pack_name,
+        manifest.started_at,
+        manifest.completed_count(),
+        manifest.entries.len()
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Resume Pack Run?")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the prompt for a session or pack another instance has open
+fn render_lock_conflict(f: &mut Frame, conflict: &LockConflict) {
+    let area = centered_rect(
+        RESUME_PACK_RUN_POPUP_WIDTH,
+        RESUME_PACK_RUN_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let text = format!(
+        "{} is open in another instance:\n{}\n\nr: Open read-only (save under a new name)\nt: Take over (the other instance can no longer save it)\nEsc: Cancel",
+        conflict.file.describe(),
+        conflict.holder
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Open Elsewhere")
+                .style(Style::default().bg(Color::Black).fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render a job's results as a bar chart or sparkline (one panel per series)
+/// Render one result row, with dynamic columns as collapsible JSON trees
+fn render_row_inspector(f: &mut Frame, inspector: &RowInspector) {
+    let area = centered_rect(
+        ROW_INSPECTOR_POPUP_WIDTH,
+        ROW_INSPECTOR_POPUP_HEIGHT,
+        f.area(),
+    );
+    let of_total = if inspector.row_count() < inspector.total_rows {
+        format!(
+            " (first {} of {})",
+            inspector.row_count(),
+            inspector.total_rows
+        )
+    } else {
+        String::new()
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Row {} of {}{}: {} - n/p: Row | Enter: Expand/Collapse | Esc: Close",
+            inspector.row + 1,
+            inspector.row_count(),
+            of_total,
+            inspector.title
+        ))
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+
+    let key_style = Style::default().fg(Color::Rgb(255, 191, 0));
+    let summary_style = Style::default().fg(Color::DarkGray);
+    let value_style = Style::default().fg(Color::White);
+    let items: Vec<ListItem> = inspector
+        .lines()
+        .into_iter()
+        .map(|line| {
+            let marker = match (line.expandable, line.expanded) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                (false, _) => "  ",
+            };
+            let style = if line.expandable || line.value.starts_with(['{', '[']) {
+                summary_style
+            } else {
+                value_style
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{}{}", "  ".repeat(line.depth), marker)),
+                Span::styled(format!("{}: ", line.key), key_style),
+                Span::styled(line.value.replace('\n', " "), style),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default().with_selected(Some(inspector.cursor));
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_stateful_widget(list, inner, &mut state);
+}
+
+fn render_job_chart(f: &mut Frame, chart: &JobChart) {
+    let area = centered_rect(JOB_CHART_POPUP_WIDTH, JOB_CHART_POPUP_HEIGHT, f.area());
+    let data = &chart.data;
+
+    let style_name = match chart.style {
+        ChartStyle::Bar => "Bar",
+        ChartStyle::Sparkline => "Sparkline",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Chart: {} ({}) - t: Toggle Style | Esc: Close",
+            chart.title, style_name
+        ))
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    // Range line, then one panel per series
+    let mut constraints = vec![Constraint::Length(1)];
+    constraints.extend(data.series.iter().map(|_| Constraint::Min(3)));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+
+    let range = format!(
+        " {}: {} .. {} ({} points{})",
+        data.label_column,
+        data.labels.first().map(String::as_str).unwrap_or("-"),
+        data.labels.last().map(String::as_str).unwrap_or("-"),
+        data.labels.len(),
+        if data.is_time_series {
+            ", sorted by time"
+        } else {
+            ""
+        }
+    );
+    f.render_widget(
+        Paragraph::new(range).style(Style::default().fg(Color::DarkGray)),
+        chunks[0],
+    );
+
+    let colors = [Color::Cyan, Color::Magenta];
+    for (idx, series) in data.series.iter().enumerate() {
+        let panel = chunks[idx + 1];
+        let color = colors[idx % colors.len()];
+        let series_block = Block::default().borders(Borders::TOP).title(format!(
+            "{} (min {}, max {}, total {})",
+            series.name,
+            series.min(),
+            series.max(),
+            series.total()
+        ));
+        // Bars and sparklines are unsigned; negative values are drawn as zero
+        let values: Vec<u64> = series
+            .values
+            .iter()
+            .map(|v| v.max(0.0).round() as u64)
+            .collect();
+
+        match chart.style {
+            ChartStyle::Bar => {
+                let bar_width = (panel.width / values.len().max(1) as u16)
+                    .saturating_sub(1)
+                    .clamp(1, 12);
+                let bars: Vec<Bar> = data
+                    .labels
+                    .iter()
+                    .zip(&values)
+                    .map(|(label, value)| {
+                        Bar::default()
+                            .value(*value)
+                            .label(Line::from(label.as_str()))
+                            .style(Style::default().fg(color))
+                    })
+                    .collect();
+                let bar_chart = BarChart::default()
+                    .block(series_block)
+                    .data(BarGroup::default().bars(&bars))
+                    .bar_width(bar_width)
+                    .bar_gap(1);
+                f.render_widget(bar_chart, panel);
+            }
+            ChartStyle::Sparkline => {
+                // Show the most recent points that fit
+                let visible = values.len().saturating_sub(panel.width as usize);
+                let sparkline = Sparkline::default()
+                    .block(series_block)
+                    .data(&values[visible..])
+                    .style(Style::default().fg(color));
+                f.render_widget(sparkline, panel);
+            }
+        }
+    }
+}
+
+/// Render the active toast (if any) in the bottom-right corner, above the controls bar
+pub fn render_toast(f: &mut Frame, notifications: &NotificationsModel) {
+    let Some(toast) = notifications.active_toast() else {
+        return;
+    };
+
+    let screen = f.area();
+    let width = TOAST_WIDTH.min(screen.width.saturating_sub(2));
+    let height = TOAST_HEIGHT;
+    if width < 10 || screen.height < height + 6 {
+        return;
+    }
+    let area = Rect::new(
+        screen.width - width - 1,
+        screen.height - height - 3,
+        width,
+        height,
+    );
+
+    let paragraph = Paragraph::new(toast.message.as_str())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(toast.level.as_str())
+                .style(Style::default().bg(Color::Black).fg(toast.level.color())),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the settings edit popup
+fn render_settings_edit(f: &mut Frame, settings: &SettingsModel) {
+    let area = centered_rect(
+        SETTINGS_EDIT_POPUP_WIDTH,
+        SETTINGS_EDIT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = settings.editing.as_deref().unwrap_or("");
+    let text = format!(
+        "Edit {}\n\nValue: {}_\n\nPress Enter to save, Esc to cancel",
+        settings.get_selected_name(),
+        input
+    );
+
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Edit Setting")
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the job name input popup
+fn render_job_name_input(f: &mut Frame, query: &QueryModel) {
+    let area = centered_rect(
+        JOB_NAME_INPUT_POPUP_WIDTH,
+        JOB_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = query.job_name_input.as_deref().unwrap_or("");
+    let cursor = |field: usize| {
+        if query.job_name_focus == field {
+            "_"
+        } else {
+            ""
+        }
+    };
+
+    // Export format toggles for this run, the focused one highlighted
+    let mut toggles = vec![Span::raw("Export: ")];
+    for (i, label) in RunFormats::OPTIONS.iter().enumerate() {
+        let check = if query.run_formats.get(i) { "x" } else { " " };
+        let style = if query.format_focus() == Some(i) {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        toggles.push(Span::styled(format!("[{}] {}", check, label), style));
+        toggles.push(Span::raw("  "));
+    }
+
+    let text = vec![
+        Line::from(format!("Job Name: {}{}", input, cursor(0))),
+        Line::from(format!(
+            "Description (optional): {}{}",
+            query.description_input,
+            cursor(1)
+        )),
+        Line::from(""),
+        Line::from(toggles),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Tab: next field  Space: toggle  Enter: run  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(match (&query.target_workspace, query.watch_requested) {
+                (Some(workspace), _) => format!("Enter Job Name (on {})", workspace.name),
+                (None, true) => "Enter Job Name (Watch)".to_string(),
+                (None, false) => "Enter Job Name".to_string(),
+            })
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the query parameter popup
+fn render_query_parameters(f: &mut Frame, input: &ParameterInput) {
+    let area = centered_rect(
+        QUERY_PARAMETERS_POPUP_WIDTH,
+        QUERY_PARAMETERS_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let mut text = Vec::new();
+    for (i, (parameter, value)) in input.parameters.iter().zip(&input.values).enumerate() {
+        let focused = i == input.focus;
+        let style = if focused {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        let mut label = parameter.name.clone();
+        if let Some(kind) = &parameter.kind {
+            label.push_str(&format!(" ({})", kind));
+        }
+        let mut spans = vec![
+            Span::styled(format!("{}: ", label), style),
+            Span::raw(format!("{}{}", value, if focused { "_" } else { "" })),
+        ];
+        if value.is_empty() {
+            if let Some(default) = &parameter.default {
+                spans.push(Span::styled(
+                    format!("  default: {}", default),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        text.push(Line::from(spans));
+    }
+    text.push(Line::from(""));
+    if let Some(error) = &input.error {
+        text.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    text.push(Line::from(Span::styled(
+        "Tab: next parameter  Enter: run  Esc: cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let title = if input.watch {
+        "Query Parameters (Watch)"
+    } else {
+        "Query Parameters"
+    };
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the session name input popup
+fn render_session_name_input(f: &mut Frame, sessions: &SessionModel) {
+    let area = centered_rect(
+        SESSION_NAME_INPUT_POPUP_WIDTH,
+        SESSION_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = sessions.name_input.as_deref().unwrap_or("");
+    let text = format!(
+        "Session Name: {}_\n\nPress Enter to save, Esc to cancel",
+        input
+    );
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("New Session")
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the session tags and description input popup
+fn render_session_details(f: &mut Frame, input: &SessionDetailsInput) {
+    let area = centered_rect(
+        SESSION_DETAILS_POPUP_WIDTH,
+        SESSION_DETAILS_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    // The focused field shows the cursor
+    let field = |label: &str, value: &str, focused: bool| {
+        let style = if focused {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        Line::from(vec![
+            Span::styled(format!("{}: ", label), style),
+            Span::raw(format!("{}{}", value, if focused { "_" } else { "" })),
+        ])
+    };
+
+    let text = vec![
+        field("Tags", &input.tags, !input.editing_description),
+        Line::from(""),
+        field("Description", &input.description, input.editing_description),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Tags are comma-separated; both are searchable with /",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "Tab: next field  Enter: save  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Session Details: {}", input.session))
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the session list search input popup
+fn render_session_search(f: &mut Frame, sessions: &SessionModel) {
+    let area = centered_rect(
+        SESSION_NAME_INPUT_POPUP_WIDTH,
+        SESSION_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = sessions.search_input.as_deref().unwrap_or("");
+    let text = format!(
+        "Search: {}_\n\nMatches names, tags, pack origin and description (all terms must match; empty clears)\nPress Enter to apply, Esc to cancel",
+        input
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Search Sessions")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the search input for the Logs tab
+fn render_log_search(f: &mut Frame, logs: &LogsModel) {
+    let area = centered_rect(
+        SESSION_NAME_INPUT_POPUP_WIDTH,
+        SESSION_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = logs.search_input.as_deref().unwrap_or("");
+    let text = format!(
+        "Search: {}_\n\nMatches message and module text, case-insensitively (empty clears)\nPress Enter to apply, Esc to cancel",
+        input
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Search Logs")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the pack name input popup (export marked jobs)
+fn render_pack_name_input(f: &mut Frame, jobs: &JobsModel) {
+    let area = centered_rect(
+        PACK_NAME_INPUT_POPUP_WIDTH,
+        PACK_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = jobs.pack_name_input.as_deref().unwrap_or("");
+    let text = format!(
+        "Pack Name: {}_\n\n{} marked job(s) will be exported\nPress Enter to save, Esc to cancel",
+        input,
+        jobs.marked.len()
+    );
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Export Marked Jobs as Pack")
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the triage note input popup for the selected job
+fn render_job_note_input(f: &mut Frame, jobs: &JobsModel) {
+    let area = centered_rect(
+        PACK_NAME_INPUT_POPUP_WIDTH,
+        PACK_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
 
-    let paragraph = Paragraph::new(msg)
+    let input = jobs.note_input.as_deref().unwrap_or("");
+    let text = format!(
+        "Note: {}_\n\nSaved with the session (leave empty to clear)\nPress Enter to save, Esc to cancel",
+        input
+    );
+    let paragraph = Paragraph::new(text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Error")
-                .style(Style::default().bg(Color::Black).fg(Color::Red)),
+                .title("Job Note")
+                .style(Style::default().bg(Color::Black)),
         )
         .wrap(Wrap { trim: false });
 
@@ -54,16 +1065,25 @@ fn render_error(f: &mut Frame, msg: &str) {
     f.render_widget(paragraph, area);
 }
 
-/// Render a success popup
-fn render_success(f: &mut Frame, msg: &str) {
-    let area = centered_rect(ERROR_POPUP_WIDTH, ERROR_POPUP_HEIGHT, f.area());
+/// Render the post-filter expression input popup
+fn render_post_filter_input(f: &mut Frame, input: &PostFilterInput) {
+    let area = centered_rect(
+        PACK_NAME_INPUT_POPUP_WIDTH,
+        PACK_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
 
-    let paragraph = Paragraph::new(msg)
+    let text = format!(
+        "Filter: {}_\n\ne.g. Account != 'SYSTEM' and Computer contains dc\nUp/Down: Recall saved filters ({}) | Enter: Apply | Esc: Cancel",
+        input.text,
+        input.saved.len()
+    );
+    let paragraph = Paragraph::new(text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Success")
-                .style(Style::default().bg(Color::Black).fg(Color::Green)),
+                .title("Post-Filter Results")
+                .style(Style::default().bg(Color::Black)),
         )
         .wrap(Wrap { trim: false });
 
@@ -71,75 +1091,532 @@ fn render_success(f: &mut Frame, msg: &str) {
     f.render_widget(paragraph, area);
 }
 
-/// Render the settings edit popup
-fn render_settings_edit(f: &mut Frame, settings: &SettingsModel) {
+/// Render the pack list tag filter input popup
+fn render_pack_tag_filter(f: &mut Frame, packs: &PacksModel) {
     let area = centered_rect(
-        SETTINGS_EDIT_POPUP_WIDTH,
-        SETTINGS_EDIT_POPUP_HEIGHT,
+        PACK_NAME_INPUT_POPUP_WIDTH,
+        PACK_NAME_INPUT_POPUP_HEIGHT,
         f.area(),
     );
 
-    let input = settings.editing.as_deref().unwrap_or("");
+    let input = packs.tag_input.as_deref().unwrap_or("");
     let text = format!(
-        "Edit {}\n\nValue: {}_\n\nPress Enter to save, Esc to cancel",
-        settings.get_selected_name(),
+        "Tags: {}_\n\nComma-separated; packs with any of the tags are shown (empty clears)\nPress Enter to apply, Esc to cancel",
         input
     );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Filter Packs by Tag")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
 
-    let paragraph = Paragraph::new(text).block(
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the pack save-as path input popup
+fn render_pack_save_as(f: &mut Frame, packs: &PacksModel) {
+    let area = centered_rect(
+        PACK_NAME_INPUT_POPUP_WIDTH,
+        PACK_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let input = packs.save_as_input.as_deref().unwrap_or("");
+    let text = format!(
+        "Path: {}_\n\nRelative to the local pack library (.yaml added if no extension)\nPress Enter to save, Esc to cancel",
+        input
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Save Pack As")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render a name input popup for renaming a session or pack
+fn render_rename_input(f: &mut Frame, title: &str, input: Option<&str>, hint: &str) {
+    let area = centered_rect(
+        PACK_NAME_INPUT_POPUP_WIDTH,
+        PACK_NAME_INPUT_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let text = format!(
+        "New name: {}_\n\n{}\nPress Enter to rename, Esc to cancel",
+        input.unwrap_or(""),
+        hint
+    );
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the jobs table column picker popup
+fn render_job_columns(f: &mut Frame, picker: &JobColumnPicker) {
+    let area = centered_rect(JOB_COLUMNS_POPUP_WIDTH, JOB_COLUMNS_POPUP_HEIGHT, f.area());
+
+    let items: Vec<ListItem> = picker
+        .columns
+        .iter()
+        .map(|(column, shown)| {
+            let (checkbox, style) = if *shown {
+                ("[X]", Style::default().fg(Color::White))
+            } else {
+                ("[ ]", Style::default().fg(Color::DarkGray))
+            };
+            ListItem::new(format!("{} {}", checkbox, column.title())).style(style)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Jobs Columns ({}/{} shown)",
+                    picker.shown().len(),
+                    picker.columns.len()
+                ))
+                .title_bottom("↑↓:Navigate Space:Toggle J/K:Move Enter:Apply Esc:Cancel")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    let mut list_state = ListState::default().with_selected(Some(picker.selected));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Render the subscription filter picker popup
+fn render_subscription_filter(f: &mut Frame, picker: &SubscriptionPicker) {
+    let area = centered_rect(
+        SUBSCRIPTION_FILTER_POPUP_WIDTH,
+        SUBSCRIPTION_FILTER_POPUP_HEIGHT,
+        f.area(),
+    );
+
+    let items: Vec<ListItem> = picker
+        .subscriptions
+        .iter()
+        .map(|sub| {
+            let enabled = picker
+                .filter
+                .allows(&sub.subscription_id, &sub.display_name);
+            let checkbox = if enabled { "[X]" } else { "[ ]" };
+            let style = if enabled {
+                Style::default().fg(Color::White)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let mut spans = vec![
+                Span::styled(format!("{} {} ", checkbox, sub.display_name), style),
+                Span::styled(
+                    format!("({})", sub.subscription_id),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ];
+            if sub.is_delegated() {
+                spans.push(Span::styled(
+                    " [delegated]",
+                    Style::default().fg(Color::Magenta),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Subscriptions ({}/{} enabled)",
+                    picker.enabled_count(),
+                    picker.subscriptions.len()
+                ))
+                .title_bottom("↑↓:Navigate Space:Toggle Enter:Apply & Refresh Esc:Cancel")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    let mut list_state = picker.list_state.clone();
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Render the fuzzy finder: the search input above the ranked matches
+fn render_finder(f: &mut Frame, finder: &FinderModel) {
+    let area = centered_rect(FINDER_POPUP_WIDTH, FINDER_POPUP_HEIGHT, f.area());
+    let [input_area, list_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+
+    let input = Paragraph::new(format!("{}_", finder.input)).block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Edit Setting")
+            .title("Find pack, query or session")
             .style(Style::default().bg(Color::Black)),
     );
 
+    let items: Vec<ListItem> = finder
+        .results()
+        .map(|item| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<8}", item.target.kind()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(item.label.clone()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} match(es)", finder.matches.len()))
+                .title_bottom("↑↓:Navigate Enter:Go to Tab:Load Esc:Close")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    let mut list_state = ListState::default();
+    list_state.select((!finder.matches.is_empty()).then_some(finder.selected));
     f.render_widget(Clear, area);
-    f.render_widget(paragraph, area);
+    f.render_widget(input, input_area);
+    f.render_stateful_widget(list, list_area, &mut list_state);
 }
 
-/// Render the job name input popup
-fn render_job_name_input(f: &mut Frame, query: &QueryModel) {
+/// Render the search across job output files, with the matching lines of the selected file
+fn render_result_search(f: &mut Frame, search: &ResultSearch) {
     let area = centered_rect(
-        JOB_NAME_INPUT_POPUP_WIDTH,
-        JOB_NAME_INPUT_POPUP_HEIGHT,
+        RESULT_SEARCH_POPUP_WIDTH,
+        RESULT_SEARCH_POPUP_HEIGHT,
         f.area(),
     );
+    let [input_area, list_area, preview_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Percentage(50),
+        Constraint::Min(0),
+    ])
+    .areas(area);
 
-    let input = query.job_name_input.as_deref().unwrap_or("");
-    let text = format!("Job Name: {}_", input);
-    let paragraph = Paragraph::new(text).block(
+    let mode = if search.regex { "regex" } else { "text" };
+    let input = Paragraph::new(format!("{}_", search.input)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Search results ({}, case-insensitive)", mode))
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    let results = search.results.as_deref().unwrap_or_default();
+    let title = match (&search.error, &search.results) {
+        (Some(error), _) => error.clone(),
+        (None, None) => "Enter: Search".to_string(),
+        (None, Some(results)) => format!(
+            "{} file(s), {} matching line(s)",
+            results.len(),
+            results.iter().map(|found| found.lines).sum::<usize>()
+        ),
+    };
+    let items: Vec<ListItem> = results
+        .iter()
+        .map(|found| {
+            let file = found
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>6}  ", found.lines),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(format!("{}  ", found.workspace)),
+                Span::styled(file, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom("Tab:Text/Regex ↑↓:Navigate Enter:Search/Open job Esc:Close")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    let (preview_title, preview) = match search.selected_match() {
+        Some(found) => (
+            found.path.display().to_string(),
+            found
+                .samples
+                .iter()
+                .map(|(line, text)| {
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{:>6}: ", line),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::raw(text.clone()),
+                    ])
+                })
+                .collect(),
+        ),
+        None => ("Matches".to_string(), Vec::new()),
+    };
+    let preview = Paragraph::new(preview).block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Enter Job Name")
+            .title(preview_title)
             .style(Style::default().bg(Color::Black)),
     );
 
+    let mut list_state = ListState::default();
+    list_state.select((!results.is_empty()).then_some(search.selected));
     f.render_widget(Clear, area);
-    f.render_widget(paragraph, area);
+    f.render_widget(input, input_area);
+    f.render_stateful_widget(list, list_area, &mut list_state);
+    f.render_widget(preview, preview_area);
 }
 
-/// Render the session name input popup
-fn render_session_name_input(f: &mut Frame, sessions: &SessionModel) {
+/// Render the search across pack contents, with the KQL around the selected match
+fn render_pack_search(f: &mut Frame, packs: &PacksModel) {
+    let Some(search) = &packs.search else {
+        return;
+    };
     let area = centered_rect(
-        SESSION_NAME_INPUT_POPUP_WIDTH,
-        SESSION_NAME_INPUT_POPUP_HEIGHT,
+        RESULT_SEARCH_POPUP_WIDTH,
+        RESULT_SEARCH_POPUP_HEIGHT,
         f.area(),
     );
+    let [input_area, list_area, preview_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Percentage(50),
+        Constraint::Min(0),
+    ])
+    .areas(area);
 
-    let input = sessions.name_input.as_deref().unwrap_or("");
-    let text = format!(
-        "Session Name: {}_\n\nPress Enter to save, Esc to cancel",
-        input
+    let mode = if search.regex { "regex" } else { "text" };
+    let input = Paragraph::new(format!("{}_", search.input)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Search packs ({}, case-insensitive)", mode))
+            .style(Style::default().bg(Color::Black)),
     );
-    let paragraph = Paragraph::new(text).block(
+
+    let unreadable = packs
+        .packs
+        .iter()
+        .filter(|entry| entry.load_error.is_some())
+        .count();
+    let mut title = match &search.error {
+        Some(error) => error.clone(),
+        None => format!("{} match(es)", search.results.len()),
+    };
+    if unreadable > 0 {
+        title.push_str(&format!(" - {} pack(s) couldn't be parsed", unreadable));
+    }
+    let items: Vec<ListItem> = search
+        .results
+        .iter()
+        .map(|found| {
+            let entry = &packs.packs[found.pack];
+            let query = found
+                .query
+                .zip(entry.pack.as_ref())
+                .and_then(|(idx, pack)| pack.get_queries().into_iter().nth(idx))
+                .map(|query| format!(" › {}", query.name))
+                .unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>10}  ", found.field.label()),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(entry.relative_path.clone()),
+                Span::styled(format!("{}  ", query), Style::default().fg(Color::DarkGray)),
+                Span::raw(found.text.clone()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom("Tab:Text/Regex ↑↓:Navigate Enter:Select pack/query Esc:Close")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    // The selected query's KQL (or the pack description), scrolled to the matching line
+    let selected = search.selected_match();
+    let pack = selected.and_then(|found| packs.packs[found.pack].pack.as_ref());
+    let query = selected
+        .and_then(|found| found.query)
+        .zip(pack)
+        .and_then(|(idx, pack)| pack.get_queries().into_iter().nth(idx));
+    let (preview_title, text) = match (&query, pack) {
+        (Some(query), _) => (query.name.clone(), query.query.clone()),
+        (None, Some(pack)) => (
+            pack.name.clone(),
+            pack.description.clone().unwrap_or_default(),
+        ),
+        (None, None) => ("Matches".to_string(), String::new()),
+    };
+    let match_line = match selected.map(|found| found.field) {
+        Some(MatchField::Kql(line)) => Some(line - 1),
+        _ => None,
+    };
+    let lines: Vec<Line> = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let style = if Some(idx) == match_line {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>4}: ", idx + 1),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(line.to_string(), style),
+            ])
+        })
+        .collect();
+    let visible = preview_area.height.saturating_sub(2) as usize;
+    let scroll = match_line.map_or(0, |line| line.saturating_sub(visible / 2));
+    let preview = Paragraph::new(lines).scroll((scroll as u16, 0)).block(
         Block::default()
             .borders(Borders::ALL)
-            .title("New Session")
+            .title(preview_title)
             .style(Style::default().bg(Color::Black)),
     );
 
+    let mut list_state = ListState::default();
+    list_state.select((!search.results.is_empty()).then_some(search.selected));
     f.render_widget(Clear, area);
-    f.render_widget(paragraph, area);
+    f.render_widget(input, input_area);
+    f.render_stateful_widget(list, list_area, &mut list_state);
+    f.render_widget(preview, preview_area);
+}
+
+/// Render the saved search browser, with a preview of the selected query
+fn render_saved_searches(f: &mut Frame, browser: &SavedSearchBrowser) {
+    let area = centered_rect(
+        SAVED_SEARCHES_POPUP_WIDTH,
+        SAVED_SEARCHES_POPUP_HEIGHT,
+        f.area(),
+    );
+    let [list_area, preview_area] =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area);
+
+    let items: Vec<ListItem> = browser
+        .searches
+        .iter()
+        .map(|search| {
+            let mut spans = vec![
+                Span::styled(
+                    format!("[{}] ", search.category),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(search.name.clone()),
+            ];
+            if let Some(signature) = search.signature() {
+                spans.push(Span::styled(
+                    format!("  ƒ {}", signature),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Saved Searches - {} ({})",
+                    browser.workspace.name,
+                    browser.searches.len()
+                ))
+                .title_bottom("↑↓:Navigate Enter:Load into editor x:Export all as pack Esc:Close")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    let preview: Vec<Line> = browser
+        .selected()
+        .map(|search| {
+            search
+                .query
+                .lines()
+                .map(|line| Line::from(crate::tui::view::kql_highlight::highlight_line(line)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let preview = Paragraph::new(preview).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Query")
+            .style(Style::default().bg(Color::Black)),
+    );
+
+    let mut list_state = browser.list_state.clone();
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, list_area, &mut list_state);
+    f.render_widget(preview, preview_area);
 }
 
 /// Render the job details popup
@@ -148,8 +1625,10 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
     let area = centered_rect(JOB_DETAILS_POPUP_WIDTH, JOB_DETAILS_POPUP_HEIGHT, f.area());
 
     // Determine if job can be retried
-    let can_retry = matches!(job.status, JobStatus::Failed | JobStatus::Completed)
-        && job.retry_context.is_some();
+    let can_retry = matches!(
+        job.status,
+        JobStatus::Failed | JobStatus::Completed | JobStatus::CompletedWithWarnings
+    ) && job.retry_context.is_some();
 
     // Calculate max width for text wrapping (popup width - borders (2) - some margin (4))
     let max_text_width = area.width.saturating_sub(6) as usize;
@@ -171,6 +1650,51 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
         ),
     ]));
 
+    // Query name and description
+    if let Some(name) = &job.query_name {
+        lines.push(Line::from(vec![
+            Span::styled("  Name: ", label_style),
+            Span::styled(name.as_str(), value_style),
+        ]));
+    }
+    if let Some(description) = &job.description {
+        lines.push(Line::from(Span::styled("  Description:", label_style)));
+        for wrapped_line in wrap_text_with_indent(description, 4, max_text_width) {
+            lines.push(Line::from(Span::styled(wrapped_line, value_style)));
+        }
+    }
+
+    // Triage verdict and note
+    if let Some(triage) = job.triage {
+        lines.push(Line::from(vec![
+            Span::styled("  Triage: ", label_style),
+            Span::styled(triage.as_str(), Style::default().fg(triage.color())),
+        ]));
+    }
+    if !job.note.is_empty() {
+        lines.push(Line::from(Span::styled("  Note:", label_style)));
+        for wrapped_line in wrap_text_with_indent(&job.note, 4, max_text_width) {
+            lines.push(Line::from(Span::styled(wrapped_line, value_style)));
+        }
+    }
+
+    // Watch line (polling interval and rolling counts)
+    if let Some(ref watch) = job.watch {
+        lines.push(Line::from(vec![
+            Span::styled("  Watch: ", label_style),
+            Span::styled(
+                format!(
+                    "every {}s, {} run(s), +{} last poll, {} rows total",
+                    watch.interval.as_secs(),
+                    watch.runs,
+                    watch.last_new_rows,
+                    watch.total_rows
+                ),
+                value_style,
+            ),
+        ]));
+    }
+
     if let Some(ref result) = job.result {
         // Workspace line
         lines.push(Line::from(vec![
@@ -194,6 +1718,18 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
             lines.push(Line::from(Span::styled(wrapped_line, value_style)));
         }
 
+        // Timespan line (packs with a default timespan)
+        if let Some(timespan) = job
+            .retry_context
+            .as_ref()
+            .and_then(|ctx| ctx.settings.timespan.as_ref())
+        {
+            lines.push(Line::from(vec![
+                Span::styled("  Timespan: ", label_style),
+                Span::styled(timespan, value_style),
+            ]));
+        }
+
         // Duration line
         lines.push(Line::from(vec![
             Span::styled("  Duration: ", label_style),
@@ -212,15 +1748,66 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
         match &result.result {
             Ok(success) => {
                 // Rows line
-                lines.push(Line::from(vec![
+                let mut rows_line = vec![
                     Span::styled("  Rows: ", label_style),
                     Span::styled(success.row_count.to_string(), value_style),
-                ]));
+                ];
+                if success.page_count > 1 {
+                    rows_line.push(Span::styled(
+                        format!(" ({} pages)", success.page_count),
+                        value_style,
+                    ));
+                }
+                if success.truncated {
+                    // Truncation without a warning can only come from the row limit
+                    rows_line.push(Span::styled(
+                        if success.warnings.is_empty() {
+                            " (truncated: row limit reached)"
+                        } else {
+                            " (truncated)"
+                        },
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+                lines.push(Line::from(rows_line));
+
+                // Errors returned alongside the rows mean some data is missing
+                if !success.warnings.is_empty() {
+                    lines.push(Line::from(Span::styled(
+                        "  Partial results - the service reported:",
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    for warning in &success.warnings {
+                        for line in warning.lines() {
+                            lines.push(Line::from(Span::styled(
+                                format!("    {}", line),
+                                Style::default().fg(Color::Yellow),
+                            )));
+                        }
+                    }
+                }
+
+                if let Some(billed_bytes) = success.billed_bytes {
+                    lines.push(Line::from(vec![
+                        Span::styled("  Est. scanned: ", label_style),
+                        Span::styled(crate::units::format_bytes(billed_bytes), value_style),
+                    ]));
+                }
+
+                if let Some(stats) = &success.statistics {
+                    lines.extend(query_statistics_lines(stats, label_style, value_style));
+                }
 
                 // Output line
+                let output = if success.output_path.as_os_str().is_empty() && success.row_count == 0
+                {
+                    "(no rows, nothing written)".to_string()
+                } else {
+                    success.output_path.display().to_string()
+                };
                 lines.push(Line::from(vec![
                     Span::styled("  Output: ", label_style),
-                    Span::styled(success.output_path.display().to_string(), value_style),
+                    Span::styled(output, value_style),
                 ]));
 
                 // Size line
@@ -228,6 +1815,28 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
                     Span::styled("  Size: ", label_style),
                     Span::styled(format!("{} bytes", success.file_size), value_style),
                 ]));
+
+                // Blob Storage uploads of the result files
+                if !success.uploads.is_empty() {
+                    lines.push(Line::from(Span::styled("  Uploads:", label_style)));
+                    for upload in &success.uploads {
+                        let (status, detail, color) = match &upload.error {
+                            None => ("✓", upload.url.clone(), Color::Green),
+                            Some(error) => (
+                                "✗",
+                                format!("{}: {}", upload.file.display(), error),
+                                Color::Red,
+                            ),
+                        };
+                        let text = format!("{} {}", status, detail);
+                        for wrapped_line in wrap_text_with_indent(&text, 4, max_text_width) {
+                            lines.push(Line::from(Span::styled(
+                                wrapped_line,
+                                Style::default().fg(color),
+                            )));
+                        }
+                    }
+                }
             }
             Err(_) => {
                 // Use structured error if available, otherwise fallback to raw error
@@ -269,13 +1878,24 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
 
         // Check if error is retryable
         let (retry_text, retry_color) = if let Some(error) = &job.error {
-            if error.is_retryable() {
-                ("  Press 'r' to retry this job", Color::Yellow)
-            } else {
-                (
+            match error {
+                _ if error.is_retryable() => ("  Press 'r' to retry this job", Color::Yellow),
+                JobError::Network { .. } => (
+                    "  (Cannot retry: connection setup error - check TLS/proxy settings first)",
+                    Color::DarkGray,
+                ),
+                JobError::TableNotFound { .. } => (
+                    "  (Cannot retry: the table doesn't exist in this workspace)",
+                    Color::DarkGray,
+                ),
+                JobError::SemanticError { .. } => (
+                    "  (Cannot retry: semantic error - fix query first)",
+                    Color::DarkGray,
+                ),
+                _ => (
                     "  (Cannot retry: query syntax error - fix query first)",
                     Color::DarkGray,
-                )
+                ),
             }
         } else {
             // No error details - allow retry (backwards compatibility)
@@ -286,7 +1906,10 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
             retry_text,
             Style::default().fg(retry_color),
         )));
-    } else if matches!(job.status, JobStatus::Failed | JobStatus::Completed) {
+    } else if matches!(
+        job.status,
+        JobStatus::Failed | JobStatus::Completed | JobStatus::CompletedWithWarnings
+    ) {
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
             "  (Cannot retry: missing context)",
@@ -294,6 +1917,43 @@ fn render_job_details(f: &mut Frame, job: &JobState) {
         )));
     }
 
+    // Clipboard hint
+    if job.error.is_some() {
+        lines.push(Line::from(Span::styled(
+            "  Press 'y' to copy the error message",
+            Style::default().fg(Color::Yellow),
+        )));
+    } else if matches!(job.result.as_ref().map(|r| r.result.is_ok()), Some(true)) {
+        lines.push(Line::from(Span::styled(
+            "  Press 'y' to copy the output path",
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    // Output file and chart hints for successful results
+    if matches!(job.result.as_ref().map(|r| r.result.is_ok()), Some(true)) {
+        lines.push(Line::from(Span::styled(
+            "  Press 'o' to open the output file, 'f' to show it in the file manager",
+            Style::default().fg(Color::Yellow),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  Press 'g' to chart results (label column + 1-2 numeric columns)",
+            Style::default().fg(Color::Yellow),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  Press 'i' to inspect rows, with dynamic columns as JSON trees",
+            Style::default().fg(Color::Yellow),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  Press 'w' to filter results locally into a new file",
+            Style::default().fg(Color::Yellow),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  Press 'x' to re-export results to CSV, JSON or NDJSON",
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
     let paragraph = Paragraph::new(lines).block(
         Block::default()
             .borders(Borders::ALL)
@@ -374,6 +2034,18 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     const MIN_POPUP_WIDTH: u16 = 20;
     const MIN_POPUP_HEIGHT: u16 = 10;
 
+    // Small terminals get proportionally larger popups so their content still fits
+    let percent_x = if r.width < super::NARROW_WIDTH {
+        percent_x.max(90)
+    } else {
+        percent_x
+    };
+    let percent_y = if r.height < super::SHORT_HEIGHT {
+        percent_y.max(90)
+    } else {
+        percent_y
+    };
+
     // Calculate target dimensions based on percentages
     let target_width = (r.width * percent_x) / 100;
     let target_height = (r.height * percent_y) / 100;
@@ -1,22 +1,38 @@
 use crate::tui::model::{
     jobs::JobsModel,
-    query::{EditorMode, QueryModel},
+    query::{EditorMode, PreviewOutcome, QueryModel},
 };
 use crate::tui::view::syntax_textarea::SyntaxTextArea;
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap,
+    },
     Frame,
 };
 
+/// Widest a preview column is drawn
+const MAX_PREVIEW_COLUMN_WIDTH: u16 = 30;
+
 /// Render the Query tab
 pub fn render(f: &mut Frame, model: &QueryModel, jobs_model: &JobsModel, area: Rect) {
+    // In split view the latest run's result goes below the editor
+    let area = if model.split_view {
+        let [editor_area, result_area] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(area);
+        render_result_pane(f, model, jobs_model, result_area);
+        editor_area
+    } else {
+        area
+    };
+
     let mode_indicator = match model.mode {
         EditorMode::Normal => " [NORMAL] ",
         EditorMode::Insert => " [INSERT] ",
         EditorMode::Visual => " [VISUAL] ",
+        EditorMode::VisualLine => " [VISUAL LINE] ",
     };
 
     let mode_style = match model.mode {
@@ -26,7 +42,7 @@ pub fn render(f: &mut Frame, model: &QueryModel, jobs_model: &JobsModel, area: R
         EditorMode::Insert => Style::default()
             .fg(Color::Green)
             .add_modifier(Modifier::BOLD),
-        EditorMode::Visual => Style::default()
+        EditorMode::Visual | EditorMode::VisualLine => Style::default()
             .fg(Color::LightMagenta)
             .add_modifier(Modifier::BOLD),
     };
@@ -37,16 +53,27 @@ pub fn render(f: &mut Frame, model: &QueryModel, jobs_model: &JobsModel, area: R
             if model.pack_context.is_some() {
                 " | [:PREV ]:NEXT l:LOAD i:INSERT v:VISUAL ^J:EXECUTE"
             } else {
-                " | l:LOAD i:INSERT v:VISUAL ^J:EXECUTE ^U:UNDO ^R:REDO"
+                " | l:LOAD i:INSERT v:VISUAL =:FORMAT ^J:EXECUTE ^U:UNDO ^R:REDO"
             }
         }
         EditorMode::Insert => " | esc:NORMAL ^J:EXECUTE ^U:UNDO ^R:REDO",
-        EditorMode::Visual => " | y:YANK d:DELETE esc:NORMAL",
+        EditorMode::Visual | EditorMode::VisualLine => " | y:YANK d:DELETE esc:NORMAL",
     };
 
     // Build title with pack context if available
     let mut title_spans = vec![Span::raw("Query ")];
 
+    if model.buffer_count() > 1 {
+        title_spans.push(Span::styled(
+            format!(
+                "[Buffer {}/{}] ",
+                model.active_buffer() + 1,
+                model.buffer_count()
+            ),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
     if let Some(pack_context) = &model.pack_context {
         title_spans.push(Span::styled(
             format!("[Pack: {}] ", pack_context.display_string()),
@@ -60,7 +87,13 @@ pub fn render(f: &mut Frame, model: &QueryModel, jobs_model: &JobsModel, area: R
     let block = Block::default().borders(Borders::ALL).title(title_spans);
 
     // Render the textarea with syntax highlighting
-    let widget = SyntaxTextArea::new(&model.textarea).block(block);
+    let diagnostics = model.diagnostics();
+    let folds = model.visible_folds();
+    let widget = SyntaxTextArea::new(&model.textarea)
+        .block(block)
+        .diagnostics(&diagnostics)
+        .folds(&folds)
+        .line_selection(model.line_selection());
     f.render_widget(widget, area);
 
     // Render load panel if open
@@ -69,6 +102,94 @@ pub fn render(f: &mut Frame, model: &QueryModel, jobs_model: &JobsModel, area: R
     }
 }
 
+/// Render the split view pane with the result (or error) of the editor's latest run
+fn render_result_pane(f: &mut Frame, model: &QueryModel, jobs_model: &JobsModel, area: Rect) {
+    let run_jobs: Vec<_> = jobs_model
+        .jobs
+        .iter()
+        .filter(|job| model.run_job_ids.contains(&job.job_id))
+        .collect();
+    let finished = run_jobs.iter().filter(|job| job.result.is_some()).count();
+    let progress = if run_jobs.len() > 1 || finished < run_jobs.len() {
+        format!(" ({} of {} jobs finished)", finished, run_jobs.len())
+    } else {
+        String::new()
+    };
+
+    let Some(preview) = &model.result_preview else {
+        let text = if run_jobs.is_empty() {
+            "Run the query with Ctrl+J to see its result here"
+        } else {
+            "Running..."
+        };
+        let paragraph = Paragraph::new(text)
+            .style(Style::default().fg(Color::DarkGray))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Last Result{} | s:HIDE", progress)),
+            );
+        f.render_widget(paragraph, area);
+        return;
+    };
+
+    match &preview.outcome {
+        PreviewOutcome::Rows {
+            columns,
+            rows,
+            total_rows,
+        } => {
+            let shown = if rows.len() < *total_rows {
+                format!(", first {} shown", rows.len())
+            } else {
+                String::new()
+            };
+            let title = format!(
+                "Last Result: {} - {} rows{}{} | s:HIDE",
+                preview.workspace_name, total_rows, shown, progress
+            );
+
+            // Size columns to their content, up to a limit
+            let widths: Vec<Constraint> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let widest = rows
+                        .iter()
+                        .filter_map(|row| row.get(i))
+                        .chain(std::iter::once(column))
+                        .map(|cell| cell.chars().count())
+                        .max()
+                        .unwrap_or(0);
+                    Constraint::Length((widest as u16).min(MAX_PREVIEW_COLUMN_WIDTH))
+                })
+                .collect();
+            let header = Row::new(columns.iter().map(|c| Cell::from(c.as_str()))).style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+            let table_rows = rows
+                .iter()
+                .map(|row| Row::new(row.iter().map(|cell| Cell::from(cell.replace('\n', " ")))));
+            let table = Table::new(table_rows, widths)
+                .header(header)
+                .block(Block::default().borders(Borders::ALL).title(title));
+            f.render_widget(table, area);
+        }
+        PreviewOutcome::Error(message) => {
+            let paragraph = Paragraph::new(message.as_str())
+                .style(Style::default().fg(Color::Red))
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(format!(
+                    "Last Result: {} - failed{} | s:HIDE",
+                    preview.workspace_name, progress
+                )));
+            f.render_widget(paragraph, area);
+        }
+    }
+}
+
 /// Render the load query panel (right-aligned overlay)
 fn render_load_panel(
     f: &mut Frame,
@@ -1,8 +1,9 @@
 use crate::tui::model::Model;
+use crate::units::format_bytes;
 use ratatui::{
-    layout::{Constraint, Rect},
+    layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, Row, Table},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
@@ -10,17 +11,37 @@ use ratatui::{
 pub fn render(f: &mut Frame, model: &mut Model, area: Rect) {
     let selected_index = model.sessions.table_state.selected();
 
-    // Create table rows
-    let rows: Vec<Row> = model
-        .sessions
+    // The selected session's description, if it has one, goes below the table
+    let description = model
         .sessions
+        .get_selected_session()
+        .map(|session| session.description.clone())
+        .filter(|description| !description.is_empty());
+    let [table_area, description_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(if description.is_some() { 4 } else { 0 }),
+    ])
+    .areas(area);
+
+    // Only sessions matching the search are shown
+    let visible = model.sessions.visible_indices();
+
+    // Create table rows
+    let rows: Vec<Row> = visible
         .iter()
-        .enumerate()
-        .map(|(idx, session)| {
+        .map(|&idx| {
+            let session = &model.sessions.sessions[idx];
             let is_selected = Some(idx) == selected_index;
             let fg_color = session.state.color(is_selected);
 
-            let name_cell = Cell::from(session.name.as_str()).style(Style::default().fg(fg_color));
+            // Marked sessions are prefixed for bulk delete
+            let mark = if model.sessions.marked.contains(&session.name) {
+                "* "
+            } else {
+                "  "
+            };
+            let name_cell = Cell::from(format!("{}{}", mark, session.name))
+                .style(Style::default().fg(fg_color));
 
             let status_cell =
                 Cell::from(session.state.indicator()).style(Style::default().fg(fg_color));
@@ -32,7 +53,23 @@ pub fn render(f: &mut Frame, model: &mut Model, area: Rect) {
             let pack_origin = session.created_from_pack.as_deref().unwrap_or("-");
             let pack_cell = Cell::from(pack_origin).style(Style::default().fg(fg_color));
 
-            Row::new(vec![name_cell, status_cell, saved_cell, pack_cell])
+            let disk = session
+                .disk_usage
+                .map(format_bytes)
+                .unwrap_or_else(|| "-".to_string());
+            let disk_cell = Cell::from(disk).style(Style::default().fg(fg_color));
+
+            let tags_cell =
+                Cell::from(session.tags.join(", ")).style(Style::default().fg(Color::Cyan));
+
+            Row::new(vec![
+                name_cell,
+                status_cell,
+                saved_cell,
+                disk_cell,
+                tags_cell,
+                pack_cell,
+            ])
         })
         .collect();
 
@@ -53,6 +90,16 @@ pub fn render(f: &mut Frame, model: &mut Model, area: Rect) {
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         ),
+        Cell::from("Disk").style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Cell::from("Tags").style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
         Cell::from("Pack Origin").style(
             Style::default()
                 .fg(Color::Cyan)
@@ -64,16 +111,18 @@ pub fn render(f: &mut Frame, model: &mut Model, area: Rect) {
     let table = Table::new(
         rows,
         [
-            Constraint::Percentage(30),
-            Constraint::Percentage(25),
-            Constraint::Percentage(20),
-            Constraint::Percentage(25),
+            Constraint::Percentage(24),
+            Constraint::Percentage(14),
+            Constraint::Percentage(18),
+            Constraint::Percentage(8),
+            Constraint::Percentage(18),
+            Constraint::Percentage(18),
         ],
     )
     .header(header)
     .block(
         Block::default()
-            .title("Sessions")
+            .title(sessions_title(model))
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::White)),
     )
@@ -84,8 +133,40 @@ pub fn render(f: &mut Frame, model: &mut Model, area: Rect) {
     )
     .highlight_symbol(">> ");
 
-    // Render the table
-    f.render_stateful_widget(table, area, &mut model.sessions.table_state);
+    super::render_filtered_table(
+        f,
+        table,
+        table_area,
+        &mut model.sessions.table_state,
+        &visible,
+    );
+
+    if let Some(description) = description {
+        let paragraph = Paragraph::new(description)
+            .block(Block::default().borders(Borders::ALL).title("Description"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, description_area);
+    }
 
     // Note: Help text is shown in the control bar at the bottom of the screen
 }
+
+/// Title showing the session count and combined disk usage
+fn sessions_title(model: &Model) -> String {
+    let mut title = format!(
+        "Sessions ({}, {} on disk)",
+        model.sessions.sessions.len(),
+        format_bytes(model.sessions.total_disk_usage())
+    );
+    if !model.sessions.search.is_empty() {
+        title.push_str(&format!(
+            " - {} matching \"{}\"",
+            model.sessions.visible_indices().len(),
+            model.sessions.search
+        ));
+    }
+    if !model.sessions.marked.is_empty() {
+        title.push_str(&format!(" - {} marked", model.sessions.marked.len()));
+    }
+    title
+}
@@ -0,0 +1,186 @@
+use crate::tui::message::Tab;
+use crate::tui::model::{jobs::JobStatus, InitState, Model};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use std::time::{Duration, SystemTime};
+
+/// Render the one-line status bar above the controls
+pub fn render(f: &mut Frame, model: &Model, area: Rect) {
+    let mut segments = vec![
+        session_segment(model),
+        workspaces_segment(model),
+        jobs_segment(model),
+    ];
+    if let Some(pack) = pack_segment(model) {
+        segments.push(pack);
+    }
+    segments.push(auth_segment(model));
+    if model.read_only {
+        segments.push((
+            Tab::Settings,
+            Span::styled("READ-ONLY", Style::default().fg(Color::Yellow)),
+        ));
+    }
+    if model.current_tab == Tab::Query {
+        if let Some(lint) = lint_segment(model) {
+            segments.push(lint);
+        }
+    }
+
+    let separator = Span::styled(" | ", Style::default().fg(Color::DarkGray));
+    let mut spans = vec![Span::raw(" ")];
+    for (idx, (tab, span)) in segments.into_iter().enumerate() {
+        if idx > 0 {
+            spans.push(separator.clone());
+        }
+        // The segment belonging to the current tab stands out
+        let span = if tab == model.current_tab {
+            span.patch_style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            span
+        };
+        spans.push(span);
+    }
+
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// A status segment and the tab it describes
+type Segment = (Tab, Span<'static>);
+
+/// Current session name, with `*` when it has unsaved changes
+fn session_segment(model: &Model) -> Segment {
+    let sessions = &model.sessions;
+    let span = match &sessions.current_session_name {
+        Some(name) if sessions.read_only => Span::styled(
+            format!("Session: {} (read-only)", name),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Some(name) if sessions.has_unsaved_changes => Span::styled(
+            format!("Session: {}*", name),
+            Style::default().fg(Color::Yellow),
+        ),
+        Some(name) => Span::styled(
+            format!("Session: {}", name),
+            Style::default().fg(Color::Green),
+        ),
+        None => Span::styled("Session: (none)", Style::default().fg(Color::DarkGray)),
+    };
+    (Tab::Sessions, span)
+}
+
+/// Number of workspaces queries will run against
+fn workspaces_segment(model: &Model) -> Segment {
+    let selected = model.workspaces.selected_count();
+    let color = if selected == 0 {
+        Color::DarkGray
+    } else {
+        Color::White
+    };
+    (
+        Tab::Workspaces,
+        Span::styled(
+            format!("Workspaces: {} selected", selected),
+            Style::default().fg(color),
+        ),
+    )
+}
+
+/// Running and queued job counts
+fn jobs_segment(model: &Model) -> Segment {
+    let count = |status: JobStatus| {
+        model
+            .jobs
+            .jobs
+            .iter()
+            .filter(|job| job.status == status)
+            .count()
+    };
+    let (running, queued) = (count(JobStatus::Running), count(JobStatus::Queued));
+
+    let mut text = format!("Jobs: {} running", running);
+    if queued > 0 {
+        text.push_str(&format!(", {} queued", queued));
+    }
+    let color = if running + queued > 0 {
+        Color::Cyan
+    } else {
+        Color::DarkGray
+    };
+    (Tab::Jobs, Span::styled(text, Style::default().fg(color)))
+}
+
+/// Lint problem on the cursor line (or the first one), with a count of the others
+fn lint_segment(model: &Model) -> Option<Segment> {
+    let diagnostics = model.query.diagnostics();
+    let cursor_line = model.query.textarea.cursor().0 + 1;
+    let shown = diagnostics
+        .iter()
+        .find(|d| d.line == cursor_line)
+        .or(diagnostics.first())?;
+
+    let mut text = format!("Lint: {}", shown.message);
+    if diagnostics.len() > 1 {
+        text.push_str(&format!(" (+{} more)", diagnostics.len() - 1));
+    }
+    Some((
+        Tab::Query,
+        Span::styled(text, Style::default().fg(Color::Red)),
+    ))
+}
+
+/// Pack the query being edited came from, and its position in the pack
+fn pack_segment(model: &Model) -> Option<Segment> {
+    let ctx = model.query.pack_context.as_ref()?;
+    Some((
+        Tab::Packs,
+        Span::styled(
+            format!(
+                "Pack: {} ({}/{}){}",
+                ctx.pack_name,
+                ctx.current_index + 1,
+                ctx.queries.len(),
+                if ctx.read_only { " (read-only)" } else { "" }
+            ),
+            Style::default().fg(Color::Magenta),
+        ),
+    ))
+}
+
+/// Authentication state and time since it was last validated
+fn auth_segment(model: &Model) -> Segment {
+    let span = match model.init_state {
+        InitState::Initializing => {
+            Span::styled("Auth: checking...", Style::default().fg(Color::Yellow))
+        }
+        InitState::Offline => {
+            Span::styled("Auth: offline (F5: retry)", Style::default().fg(Color::Red))
+        }
+        InitState::Ready => match model.client.last_validated() {
+            Some(at) => {
+                let elapsed = SystemTime::now().duration_since(at).unwrap_or_default();
+                Span::styled(
+                    format!("Auth: OK ({} ago)", format_elapsed(elapsed)),
+                    Style::default().fg(Color::Green),
+                )
+            }
+            None => Span::styled("Auth: OK", Style::default().fg(Color::Green)),
+        },
+    };
+    (Tab::Settings, span)
+}
+
+/// Format an elapsed time coarsely (e.g. `45s`, `12m`, `3h`)
+pub(super) fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
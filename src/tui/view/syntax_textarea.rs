@@ -1,8 +1,10 @@
+use crate::pack_lint::Diagnostic;
+use crate::tui::model::query::{fold_regions, FoldRegion};
 use crate::tui::view::kql_highlight;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Widget},
 };
@@ -54,11 +56,10 @@ fn apply_selection_to_spans(
             let content_str = span.content.to_string();
             let mut chars_vec: Vec<char> = content_str.chars().collect();
 
-            let mut current_pos = char_pos;
             let mut current_str = String::new();
-            let mut in_selection = current_pos >= sel_start && current_pos < sel_end;
+            let mut in_selection = char_pos >= sel_start && char_pos < sel_end;
 
-            for ch in chars_vec.drain(..) {
+            for (current_pos, ch) in (char_pos..).zip(chars_vec.drain(..)) {
                 let next_in_selection = current_pos >= sel_start && current_pos < sel_end;
 
                 if next_in_selection != in_selection {
@@ -76,7 +77,6 @@ fn apply_selection_to_spans(
                 }
 
                 current_str.push(ch);
-                current_pos += 1;
             }
 
             // Flush remaining string
@@ -100,6 +100,9 @@ fn apply_selection_to_spans(
 pub struct SyntaxTextArea<'a> {
     textarea: &'a TextArea<'a>,
     block: Option<Block<'a>>,
+    diagnostics: &'a [Diagnostic],
+    folds: &'a [FoldRegion],
+    line_selection: Option<(usize, usize)>,
 }
 
 impl<'a> SyntaxTextArea<'a> {
@@ -107,6 +110,9 @@ impl<'a> SyntaxTextArea<'a> {
         Self {
             textarea,
             block: None,
+            diagnostics: &[],
+            folds: &[],
+            line_selection: None,
         }
     }
 
@@ -114,6 +120,24 @@ impl<'a> SyntaxTextArea<'a> {
         self.block = Some(block);
         self
     }
+
+    /// Mark the lines with lint problems in the gutter
+    pub fn diagnostics(mut self, diagnostics: &'a [Diagnostic]) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// Collapse these regions to their first line
+    pub fn folds(mut self, folds: &'a [FoldRegion]) -> Self {
+        self.folds = folds;
+        self
+    }
+
+    /// Highlight these rows (first, last) in full instead of the textarea's selection
+    pub fn line_selection(mut self, rows: Option<(usize, usize)>) -> Self {
+        self.line_selection = rows;
+        self
+    }
 }
 
 impl<'a> Widget for SyntaxTextArea<'a> {
@@ -134,12 +158,36 @@ impl<'a> Widget for SyntaxTextArea<'a> {
         let (cursor_row, cursor_col) = self.textarea.cursor();
 
         // Get selection range if any
-        let selection = self.textarea.selection_range();
+        let selection = match self.line_selection {
+            Some((first, last)) => Some(((first, 0), (last, usize::MAX))),
+            None => self.textarea.selection_range(),
+        };
+
+        // Lines to display: a folded region shows only its first line
+        let mut rows: Vec<(usize, Option<&FoldRegion>)> = Vec::new();
+        let mut idx = 0;
+        while idx < lines.len() {
+            match self.folds.iter().find(|region| region.start == idx) {
+                Some(region) => {
+                    rows.push((idx, Some(region)));
+                    idx = region.end + 1;
+                }
+                None => {
+                    rows.push((idx, None));
+                    idx += 1;
+                }
+            }
+        }
+        let region_starts: Vec<usize> = fold_regions(lines).iter().map(|r| r.start).collect();
 
         // Get the viewport offset (scroll position)
         let viewport_height = inner.height as usize;
-        let max_start = lines.len().saturating_sub(viewport_height);
-        let start_row = cursor_row
+        let cursor_display_row = rows
+            .iter()
+            .position(|(idx, _)| *idx == cursor_row)
+            .unwrap_or(0);
+        let max_start = rows.len().saturating_sub(viewport_height);
+        let start_row = cursor_display_row
             .saturating_sub(viewport_height / 2)
             .min(max_start);
 
@@ -148,44 +196,63 @@ impl<'a> Widget for SyntaxTextArea<'a> {
         let line_num_width = line_count.to_string().len().max(2) + 1; // +1 for space
 
         // Render each visible line with syntax highlighting
-        let mut y = inner.y;
-        for (idx, line_text) in lines
-            .iter()
-            .enumerate()
-            .skip(start_row)
-            .take(viewport_height)
+        for (y, &(idx, fold)) in (inner.y..).zip(rows.iter().skip(start_row).take(viewport_height))
         {
             if y >= inner.y + inner.height {
                 break;
             }
+            let line_text = &lines[idx];
+            let last_line = fold.map_or(idx, |region| region.end);
 
-            let line_num = format!("{:>width$} ", idx + 1, width = line_num_width - 1);
+            let line_num = format!("{:>width$}", idx + 1, width = line_num_width - 1);
 
-            // Create line number span
-            let mut spans = vec![Span::styled(line_num, Style::default().fg(Color::DarkGray))];
+            // Create line number span, with a marker in place of the gap for lint problems
+            // (including any hidden by a fold) or the start of a foldable region
+            let mut spans = if self
+                .diagnostics
+                .iter()
+                .any(|d| d.line > idx && d.line <= last_line + 1)
+            {
+                let marker = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+                vec![Span::styled(line_num, marker), Span::styled("●", marker)]
+            } else if region_starts.contains(&idx) {
+                vec![
+                    Span::styled(line_num, Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        if fold.is_some() { "▸" } else { "▾" },
+                        Style::default().fg(Color::Cyan),
+                    ),
+                ]
+            } else {
+                vec![Span::styled(
+                    format!("{} ", line_num),
+                    Style::default().fg(Color::DarkGray),
+                )]
+            };
 
             // Add syntax-highlighted content with selection overlay
-            let highlighted_spans =
-                if let Some(((start_row, start_col), (end_row, end_col))) = selection {
-                    // Check if this line is within the selection
-                    let is_selected_line = idx >= start_row && idx <= end_row;
-
-                    if is_selected_line {
-                        // Apply selection highlighting
-                        apply_selection_to_spans(
-                            kql_highlight::highlight_line(line_text),
-                            idx,
-                            start_row,
-                            start_col,
-                            end_row,
-                            end_col,
-                        )
-                    } else {
-                        kql_highlight::highlight_line(line_text)
-                    }
+            let highlighted_spans = if let Some(region) = fold {
+                kql_highlight::highlight_folded(line_text, region.end - region.start)
+            } else if let Some(((start_row, start_col), (end_row, end_col))) = selection {
+                // Check if this line is within the selection
+                let is_selected_line = idx >= start_row && idx <= end_row;
+
+                if is_selected_line {
+                    // Apply selection highlighting
+                    apply_selection_to_spans(
+                        kql_highlight::highlight_line(line_text),
+                        idx,
+                        start_row,
+                        start_col,
+                        end_row,
+                        end_col,
+                    )
                 } else {
                     kql_highlight::highlight_line(line_text)
-                };
+                }
+            } else {
+                kql_highlight::highlight_line(line_text)
+            };
 
             spans.extend(highlighted_spans);
 
@@ -218,8 +285,6 @@ impl<'a> Widget for SyntaxTextArea<'a> {
                     }
                 }
             }
-
-            y += 1;
         }
     }
 }
@@ -1,5 +1,4 @@
 use crate::tui::message::Tab;
-use crate::tui::model::InitState;
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -12,7 +11,7 @@ use ratatui::{
 pub fn render(
     f: &mut Frame,
     current_tab: Tab,
-    init_state: InitState,
+    workspaces_loading: bool,
     spinner_frame: usize,
     area: Rect,
 ) {
@@ -23,8 +22,11 @@ pub fn render(
         Tab::Settings,
         Tab::Jobs,
         Tab::Sessions,
+        Tab::Dashboard,
+        Tab::Logs,
     ];
     let spinner_chars = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    let narrow = super::is_narrow(area);
 
     let tab_spans: Vec<Span> = tabs
         .iter()
@@ -37,12 +39,18 @@ pub fn render(
                 Style::default().fg(Color::White)
             };
 
-            // Add spinner to Workspaces tab when initializing
-            let text = if *tab == Tab::Workspaces && init_state == InitState::Initializing {
+            let title = if narrow {
+                tab.short_str()
+            } else {
+                tab.as_str()
+            };
+
+            // Add spinner to Workspaces tab while workspaces load
+            let text = if *tab == Tab::Workspaces && workspaces_loading {
                 let spinner = spinner_chars[spinner_frame % spinner_chars.len()];
-                format!(" {} {} ", tab.as_str(), spinner)
+                format!(" {} {} ", title, spinner)
             } else {
-                format!(" {} ", tab.as_str())
+                format!(" {} ", title)
             };
 
             Span::styled(text, style)
@@ -50,11 +58,16 @@ pub fn render(
         .collect();
 
     let tabs_line = Line::from(tab_spans);
-    let tabs_paragraph = Paragraph::new(tabs_line).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("KQL Panopticon"),
-    );
+    // Without room for a border (short terminals), draw just the tab titles
+    let tabs_paragraph = if area.height < 3 {
+        Paragraph::new(tabs_line)
+    } else {
+        Paragraph::new(tabs_line).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("KQL Panopticon"),
+        )
+    };
 
     f.render_widget(tabs_paragraph, area);
 }
@@ -1,32 +1,58 @@
-use crate::tui::model::workspaces::WorkspacesModel;
+use crate::tui::model::workspaces::{WorkspaceSort, WorkspacesModel};
+use crate::workspace_health::WorkspaceStats;
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Row, Table},
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
     Frame,
 };
 
 /// Render the Workspaces tab
-pub fn render(f: &mut Frame, model: &mut WorkspacesModel, area: Rect) {
+/// Workspaces with at least `failure_streak` failures in a row are flagged.
+pub fn render(f: &mut Frame, model: &mut WorkspacesModel, failure_streak: u32, area: Rect) {
     // Create header
-    let header = Row::new(vec!["Selected", "Name", "Location"])
-        .style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )
-        .bottom_margin(1);
+    let header = Row::new(vec![
+        "Selected",
+        "Name",
+        "Subscription",
+        "Location",
+        "Access",
+        "Health",
+    ])
+    .style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )
+    .bottom_margin(1);
 
     // Create rows
     let rows: Vec<Row> = model
-        .workspaces
+        .display_order()
         .iter()
-        .map(|ws| {
+        .map(|&idx| {
+            let ws = &model.workspaces[idx];
             let checkbox = if ws.selected { "[X]" } else { "[ ]" };
+            let access = if ws.workspace.delegated {
+                Cell::from("Delegated").style(Style::default().fg(Color::Magenta))
+            } else {
+                Cell::from("Direct")
+            };
+            let stats = model.health.get(&ws.workspace.workspace_id);
+            let failing = stats.is_some_and(|stats| stats.is_failing(failure_streak));
+            let name = if failing {
+                Cell::from(format!("⚠ {}", ws.workspace.name))
+                    .style(Style::default().fg(Color::Red))
+            } else {
+                Cell::from(ws.workspace.name.as_str())
+            };
             Row::new(vec![
-                checkbox,
-                ws.workspace.name.as_str(),
-                ws.workspace.location.as_str(),
+                Cell::from(checkbox),
+                name,
+                Cell::from(ws.workspace.subscription_name.as_str()),
+                Cell::from(ws.workspace.location.as_str()),
+                access,
+                health_cell(stats, failing),
             ])
         })
         .collect();
@@ -34,17 +60,48 @@ pub fn render(f: &mut Frame, model: &mut WorkspacesModel, area: Rect) {
     // Calculate column widths
     let widths = [
         ratatui::layout::Constraint::Length(10),
-        ratatui::layout::Constraint::Percentage(45),
-        ratatui::layout::Constraint::Percentage(45),
+        ratatui::layout::Constraint::Percentage(30),
+        ratatui::layout::Constraint::Percentage(25),
+        ratatui::layout::Constraint::Percentage(12),
+        ratatui::layout::Constraint::Length(10),
+        ratatui::layout::Constraint::Length(16),
     ];
 
+    let delegated_count = model
+        .workspaces
+        .iter()
+        .filter(|ws| ws.workspace.delegated)
+        .count();
+    let mut title = format!("Workspaces ({} selected", model.selected_count());
+    if delegated_count > 0 {
+        title.push_str(&format!(", {} delegated", delegated_count));
+    }
+    let failing_count = model
+        .workspaces
+        .iter()
+        .filter(|ws| {
+            model
+                .health
+                .is_failing(&ws.workspace.workspace_id, failure_streak)
+        })
+        .count();
+    if failing_count > 0 {
+        title.push_str(&format!(", {} failing", failing_count));
+    }
+    if model.sort != WorkspaceSort::Discovery {
+        title.push_str(&format!(
+            ", sorted by {}",
+            model.sort.as_str().to_lowercase()
+        ));
+    }
+    title.push(')');
+    if model.refreshing {
+        title.push_str(" - refreshing (Esc to cancel)");
+    }
+
     let table = Table::new(rows, widths)
         .header(header)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(format!("Workspaces ({} selected)", model.selected_count())),
-        )
+        .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(
             Style::default()
                 .fg(Color::Yellow)
@@ -52,5 +109,27 @@ pub fn render(f: &mut Frame, model: &mut WorkspacesModel, area: Rect) {
         )
         .highlight_symbol(">> ");
 
-    f.render_stateful_widget(table, area, &mut model.table_state);
+    // The table sees rows in display order, so translate the selection into a row position
+    let mut state = TableState::default()
+        .with_offset(model.table_state.offset())
+        .with_selected(model.cursor_position());
+    f.render_stateful_widget(table, area, &mut state);
+    *model.table_state.offset_mut() = state.offset();
+}
+
+/// Failure streak or average latency of a workspace ("-" before any job has finished)
+fn health_cell(stats: Option<&WorkspaceStats>, failing: bool) -> Cell<'static> {
+    let Some(stats) = stats else {
+        return Cell::from("-");
+    };
+    if stats.consecutive_failures > 0 {
+        let color = if failing { Color::Red } else { Color::Yellow };
+        return Cell::from(format!("{} failed in a row", stats.consecutive_failures))
+            .style(Style::default().fg(color));
+    }
+    match stats.average_latency() {
+        Some(latency) => Cell::from(format!("OK, {:.1}s avg", latency.as_secs_f64()))
+            .style(Style::default().fg(Color::Green)),
+        None => Cell::from("-"),
+    }
 }
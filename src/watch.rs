@@ -0,0 +1,427 @@
+use crate::backend::QueryBackend;
+use crate::client::Table;
+use crate::error::{KqlPanopticonError, Result};
+use crate::output_index::OutputIndex;
+use crate::query_job::{format_csv_value, parse_dynamic_value, QuerySettings};
+use crate::query_params::code_chars;
+use crate::workspace::Workspace;
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use log::{debug, warn};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Notify;
+
+/// Column used as the watermark for incremental polling
+const WATERMARK_COLUMN: &str = "TimeGenerated";
+
+/// Progress report sent after each watch poll
+#[derive(Debug, Clone)]
+pub struct WatchTick {
+    /// Rows appended by this poll
+    pub new_rows: usize,
+    /// Rows appended since the watch started
+    pub total_rows: usize,
+    /// Number of polls completed
+    pub runs: u32,
+    /// Primary output file being appended to
+    pub output_path: PathBuf,
+    /// Combined size of the output files in bytes
+    pub file_size: u64,
+    /// Time taken by this poll
+    pub elapsed: Duration,
+    /// When this poll finished
+    pub timestamp: DateTime<FixedOffset>,
+    /// Error from this poll, if it failed (the watch keeps running)
+    pub error: Option<KqlPanopticonError>,
+}
+
+/// Re-runs a query on an interval, appending only rows newer than the last seen TimeGenerated
+pub struct WatchJob {
+    workspace: Workspace,
+    query: String,
+    settings: QuerySettings,
+    interval: Duration,
+    csv_path: Option<PathBuf>,
+    json_path: Option<PathBuf>,
+    header_written: bool,
+    watermark: Option<DateTime<Utc>>,
+    /// Hashes of the rows written with the watermark's TimeGenerated
+    seen_at_watermark: HashSet<u64>,
+    total_rows: usize,
+    runs: u32,
+}
+
+impl WatchJob {
+    /// Create a watch job writing to output_folder/subscription/workspace/timestamp/
+    /// CSV output is appended to `<job_name>.csv`, JSON output to `<job_name>.jsonl` (one row per line)
+    pub fn new(
+        workspace: Workspace,
+        query: String,
+        settings: QuerySettings,
+        interval: Duration,
+    ) -> Result<Self> {
+        if !settings.export_csv && !settings.export_json {
+            return Err(KqlPanopticonError::InvalidConfiguration(
+                "No export format enabled (CSV or JSON required)".to_string(),
+            ));
+        }
+
+        let output_dir = workspace.output_dir(&settings.output_folder).join(
+            crate::time_zone::now()
+                .format("%Y-%m-%d_%H-%M-%S")
+                .to_string(),
+        );
+        if let Err(e) = OutputIndex::record(&settings.output_folder, &workspace) {
+            warn!("Failed to update the output index: {}", e);
+        }
+
+        let csv_path = settings
+            .export_csv
+            .then(|| output_dir.join(format!("{}.csv", settings.job_name)));
+        let json_path = settings
+            .export_json
+            .then(|| output_dir.join(format!("{}.jsonl", settings.job_name)));
+
+        Ok(Self {
+            workspace,
+            query,
+            settings,
+            interval,
+            csv_path,
+            json_path,
+            header_written: false,
+            watermark: None,
+            seen_at_watermark: HashSet::new(),
+            total_rows: 0,
+            runs: 0,
+        })
+    }
+
+    /// Poll until `stop` is notified, reporting each poll through `on_tick`
+    /// Failed polls are reported and retried on the next interval
+    pub async fn run<B: QueryBackend, F>(mut self, client: B, stop: Arc<Notify>, mut on_tick: F)
+    where
+        F: FnMut(WatchTick),
+    {
+        loop {
+            let start = Instant::now();
+            let result = self.poll(&client).await;
+            self.runs += 1;
+
+            let (new_rows, error) = match result {
+                Ok(rows) => (rows, None),
+                Err(e) => {
+                    warn!(
+                        "Watch poll failed on workspace '{}': {}",
+                        self.workspace.name, e
+                    );
+                    (0, Some(e))
+                }
+            };
+            self.total_rows += new_rows;
+
+            on_tick(WatchTick {
+                new_rows,
+                total_rows: self.total_rows,
+                runs: self.runs,
+                output_path: self.primary_output_path(),
+                file_size: self.output_size().await,
+                elapsed: start.elapsed(),
+                timestamp: crate::time_zone::now(),
+                error,
+            });
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.interval) => {}
+                _ = stop.notified() => break,
+            }
+        }
+
+        debug!(
+            "Watch stopped on workspace '{}' after {} run(s)",
+            self.workspace.name, self.runs
+        );
+    }
+
+    /// The user's query, restricted to rows at or after the current watermark
+    ///
+    /// The filter goes right after the source of the query's last statement, so later
+    /// `take`/`summarize` steps only see new rows. Rows already written at the watermark
+    /// itself are dropped in `render_page`.
+    fn watched_query(&self) -> String {
+        let base = self.query.trim_end().trim_end_matches(';');
+        let Some(watermark) = self.watermark else {
+            return base.to_string();
+        };
+        let filter = format!(
+            "| where {} >= datetime({})",
+            WATERMARK_COLUMN,
+            watermark.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        );
+
+        let code = code_chars(base);
+        let statement_start = code
+            .iter()
+            .filter(|&&(_, c, depth)| c == ';' && depth == 0)
+            .map(|&(idx, _, _)| idx + 1)
+            .next_back()
+            .unwrap_or(0);
+        let first_pipe = code
+            .iter()
+            .find(|&&(idx, c, depth)| idx >= statement_start && c == '|' && depth == 0);
+        match first_pipe {
+            Some(&(idx, _, _)) => format!("{}{}\n{}", &base[..idx], filter, &base[idx..]),
+            None => format!("{}\n{}", base, filter),
+        }
+    }
+
+    /// Run the query once and append new rows, returning how many were written
+    ///
+    /// Every page is fetched before anything is written, so a failed page leaves the output
+    /// and the watermark as they were and the next poll fetches the same rows again.
+    async fn poll<B: QueryBackend>(&mut self, client: &B) -> Result<usize> {
+        let timeout = self.settings.effective_timeout(client);
+        let query = self.watched_query();
+
+        let mut response = client
+            .query_workspace(&self.workspace.workspace_id, &query, None, timeout)
+            .await?;
+
+        let mut page = Page {
+            latest: self.watermark,
+            ..Page::default()
+        };
+        loop {
+            if let Some(table) = response.tables.first() {
+                self.render_page(table, &mut page)?;
+            }
+            match response.next_link.take() {
+                Some(next_link) => {
+                    response = client.query_next_page(&next_link, timeout).await?;
+                }
+                None => break,
+            }
+        }
+
+        if page.header.is_some() || page.rows > 0 {
+            if let Some(path) = &self.csv_path {
+                let csv = page.header.unwrap_or_default() + &page.csv;
+                append_to_file(path, &csv).await?;
+            }
+            if let Some(path) = &self.json_path {
+                append_to_file(path, &page.json).await?;
+            }
+            self.header_written = true;
+        }
+
+        if page.latest != self.watermark {
+            self.seen_at_watermark.clear();
+        }
+        self.seen_at_watermark.extend(page.at_latest);
+        self.watermark = page.latest;
+        Ok(page.rows)
+    }
+
+    /// Render one page of rows into `page`, skipping rows written by an earlier poll
+    fn render_page(&self, table: &Table, page: &mut Page) -> Result<()> {
+        let watermark_idx = table
+            .columns
+            .iter()
+            .position(|c| c.name == WATERMARK_COLUMN)
+            .ok_or_else(|| {
+                KqlPanopticonError::QueryExecutionFailed(format!(
+                    "Watch mode requires the query to return a {} column",
+                    WATERMARK_COLUMN
+                ))
+            })?;
+
+        if !self.header_written && page.header.is_none() {
+            let headers: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+            page.header = Some(headers.join(",") + "\n");
+        }
+
+        for row in &table.rows {
+            let Some(values) = row.as_array() else {
+                continue;
+            };
+
+            if let Some(ts) = values
+                .get(watermark_idx)
+                .and_then(|v| v.as_str())
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            {
+                let ts = ts.with_timezone(&Utc);
+                let hash = row_hash(row);
+                if Some(ts) == self.watermark && self.seen_at_watermark.contains(&hash) {
+                    continue;
+                }
+                if page.latest.is_none_or(|l| ts > l) {
+                    page.latest = Some(ts);
+                    page.at_latest.clear();
+                }
+                if Some(ts) == page.latest {
+                    page.at_latest.push(hash);
+                }
+            }
+
+            if self.csv_path.is_some() {
+                let fields: Vec<String> = values.iter().map(format_csv_value).collect();
+                page.csv.push_str(&fields.join(","));
+                page.csv.push('\n');
+            }
+
+            if self.json_path.is_some() {
+                let mut object = serde_json::Map::new();
+                for (column, value) in table.columns.iter().zip(values) {
+                    let value = if self.settings.parse_dynamics && column.column_type == "dynamic" {
+                        parse_dynamic_value(value)
+                    } else {
+                        value.clone()
+                    };
+                    object.insert(column.name.clone(), value);
+                }
+                page.json
+                    .push_str(&serde_json::Value::Object(object).to_string());
+                page.json.push('\n');
+            }
+
+            page.rows += 1;
+        }
+
+        Ok(())
+    }
+
+    /// CSV output if enabled, otherwise JSON
+    fn primary_output_path(&self) -> PathBuf {
+        self.csv_path
+            .clone()
+            .or_else(|| self.json_path.clone())
+            .unwrap_or_default()
+    }
+
+    /// Combined size of the output files written so far
+    async fn output_size(&self) -> u64 {
+        let mut size = 0;
+        for path in self.csv_path.iter().chain(self.json_path.iter()) {
+            if let Ok(metadata) = fs::metadata(path).await {
+                size += metadata.len();
+            }
+        }
+        size
+    }
+}
+
+/// Rows fetched by one poll, rendered but not yet written
+#[derive(Default)]
+struct Page {
+    /// CSV header, if it hasn't been written yet
+    header: Option<String>,
+    csv: String,
+    json: String,
+    rows: usize,
+    /// Newest TimeGenerated seen, starting from the current watermark
+    latest: Option<DateTime<Utc>>,
+    /// Hashes of the rows at `latest`
+    at_latest: Vec<u64>,
+}
+
+/// Hash identifying a row, to recognise it when a later poll returns it again
+fn row_hash(row: &serde_json::Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    row.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Append text to a file, creating it (and its parent directories) if needed
+async fn append_to_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(contents.as_bytes()).await?;
+    // tokio writes in the background; wait for it so the rows are on disk when the tick reports them
+    file.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FakeBackend;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_poll_skips_written_rows_and_failed_pages() {
+        let dir = std::env::temp_dir().join(format!("kql-watch-{}", std::process::id()));
+        let settings = QuerySettings::new(&dir, "signins");
+        let query = "let since = ago(1h);\nSigninLogs\n| where TimeGenerated > since\n| take 100;";
+        let mut watch = WatchJob::new(
+            Workspace::fixture("ws"),
+            query.to_string(),
+            settings,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let columns = ["TimeGenerated", "User"];
+
+        // A failed second page writes nothing and keeps the watermark
+        let fake = FakeBackend::new();
+        fake.respond("SigninLogs", {
+            let mut first = FakeBackend::table(
+                &columns,
+                vec![vec![json!("2024-05-01T10:00:00Z"), json!("alice")]],
+            );
+            first["nextLink"] = json!("page-2");
+            first
+        });
+        assert!(watch.poll(&fake).await.is_err());
+        assert!(!watch.csv_path.as_ref().unwrap().exists());
+        assert_eq!(watch.watermark, None);
+
+        fake.respond_page(
+            "page-2",
+            FakeBackend::table(
+                &columns,
+                vec![vec![json!("2024-05-01T10:05:00Z"), json!("bob")]],
+            ),
+        );
+        assert_eq!(watch.poll(&fake).await.unwrap(), 2);
+
+        // The filter goes after the source; bob is returned again alongside a late row
+        assert_eq!(
+            watch.watched_query(),
+            "let since = ago(1h);\nSigninLogs\n| where TimeGenerated >= datetime(2024-05-01T10:05:00Z)\n| where TimeGenerated > since\n| take 100"
+        );
+        let fake = FakeBackend::new();
+        fake.respond(
+            "datetime(2024-05-01T10:05:00Z)",
+            FakeBackend::table(
+                &columns,
+                vec![
+                    vec![json!("2024-05-01T10:05:00Z"), json!("bob")],
+                    vec![json!("2024-05-01T10:05:00Z"), json!("carol")],
+                ],
+            ),
+        );
+        assert_eq!(watch.poll(&fake).await.unwrap(), 1);
+        assert_eq!(watch.poll(&fake).await.unwrap(), 0);
+
+        let csv = std::fs::read_to_string(watch.csv_path.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "TimeGenerated,User\n2024-05-01T10:00:00Z,alice\n2024-05-01T10:05:00Z,bob\n2024-05-01T10:05:00Z,carol\n"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+use crate::config::{NetworkConfig, PackOverride};
+use crate::error::{KqlPanopticonError, Result};
+use crate::query_job::QueryJobResult;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+/// How long a webhook POST may take before it is abandoned
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Failures listed in Slack and Teams messages (the generic payload lists all of them)
+const MAX_LISTED_FAILURES: usize = 10;
+
+/// Payload template for run notifications
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// The run summary as plain JSON
+    #[default]
+    Generic,
+    /// Slack incoming webhook (`text`)
+    Slack,
+    /// Microsoft Teams incoming webhook (MessageCard)
+    Teams,
+}
+
+/// Webhook settings (`webhook` in config.yaml, or in a pack to override them for that pack)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// URL the run summary is POSTed to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Payload template (default: generic)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<WebhookFormat>,
+    /// Only notify when at least one query failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only_on_failure: Option<bool>,
+    /// Set to false to turn notifications off (e.g. for one noisy pack)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+impl WebhookConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combine the global settings with a pack's overrides
+    /// Returns None if notifications are off or no URL is set.
+    pub fn resolve(global: &WebhookConfig, pack: Option<&WebhookConfig>) -> Option<Webhook> {
+        let settings = Self::layered(global, pack)?;
+        Some(Webhook {
+            url: settings.url?,
+            format: settings.format.unwrap_or_default(),
+            only_on_failure: settings.only_on_failure.unwrap_or(false),
+        })
+    }
+}
+
+impl PackOverride for WebhookConfig {
+    fn or(self, global: &Self) -> Self {
+        Self {
+            url: self.url.or_else(|| global.url.clone()),
+            format: self.format.or(global.format),
+            only_on_failure: self.only_on_failure.or(global.only_on_failure),
+            enabled: self.enabled.or(global.enabled),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+}
+
+/// An enabled webhook with its settings resolved
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    pub format: WebhookFormat,
+    pub only_on_failure: bool,
+}
+
+impl Webhook {
+    /// Check if a run with this summary should be reported
+    pub fn should_notify(&self, summary: &RunSummary) -> bool {
+        !self.only_on_failure || summary.failed > 0
+    }
+
+    /// POST the run summary (does nothing if `only_on_failure` is set and nothing failed)
+    pub async fn send(&self, summary: &RunSummary) -> Result<()> {
+        if !self.should_notify(summary) {
+            return Ok(());
+        }
+
+        let builder = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT);
+        let client = crate::client::configure_network(builder, &NetworkConfig::resolve())?
+            .build()
+            .map_err(KqlPanopticonError::from)?;
+        let response = client
+            .post(&self.url)
+            .json(&summary.payload(self.format))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            return Err(KqlPanopticonError::Other(format!(
+                "Webhook returned HTTP {}: {}",
+                status, body
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A query that failed on a workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct RunFailure {
+    pub query: String,
+    pub workspace: String,
+    pub error: String,
+}
+
+/// A result file written by a run
+#[derive(Debug, Clone)]
+pub struct RunOutput {
+    pub query: String,
+    pub workspace: String,
+    pub path: std::path::PathBuf,
+}
+
+/// Outcome of a pack run or a batch of session jobs
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// Pack or session name
+    pub source: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Rows returned by the successful queries
+    pub rows: usize,
+    pub failures: Vec<RunFailure>,
+    /// RFC 3339 time the last result was added
+    pub finished_at: String,
+    /// Result files of the successful queries (for post-processing; not sent)
+    #[serde(skip)]
+    pub outputs: Vec<RunOutput>,
+}
+
+impl RunSummary {
+    /// Start an empty summary for a pack or session
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            succeeded: 0,
+            failed: 0,
+            rows: 0,
+            failures: Vec::new(),
+            finished_at: crate::time_zone::now().to_rfc3339(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Count one query/workspace result
+    pub fn add(&mut self, query_name: &str, result: &QueryJobResult) {
+        self.finished_at = crate::time_zone::now().to_rfc3339();
+        match &result.result {
+            Ok(success) => {
+                self.succeeded += 1;
+                self.rows += success.row_count;
+                if !success.output_path.as_os_str().is_empty() {
+                    self.outputs.push(RunOutput {
+                        query: query_name.to_string(),
+                        workspace: result.workspace_name.clone(),
+                        path: success.output_path.clone(),
+                    });
+                }
+            }
+            Err(e) => {
+                self.failed += 1;
+                self.failures.push(RunFailure {
+                    query: query_name.to_string(),
+                    workspace: result.workspace_name.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    /// One-line description of the outcome
+    pub fn headline(&self) -> String {
+        let total = self.succeeded + self.failed;
+        if self.failed == 0 {
+            format!(
+                "{}: all {} queries succeeded ({} rows)",
+                self.source, total, self.rows
+            )
+        } else {
+            format!(
+                "{}: {} of {} queries failed ({} rows from the rest)",
+                self.source, self.failed, total, self.rows
+            )
+        }
+    }
+
+    /// Build the request body for a payload template
+    pub fn payload(&self, format: WebhookFormat) -> serde_json::Value {
+        let failure_lines: Vec<String> = self
+            .failures
+            .iter()
+            .take(MAX_LISTED_FAILURES)
+            .map(|f| format!("{} on {}: {}", f.query, f.workspace, f.error))
+            .chain(
+                (self.failures.len() > MAX_LISTED_FAILURES)
+                    .then(|| format!("...and {} more", self.failures.len() - MAX_LISTED_FAILURES)),
+            )
+            .collect();
+
+        match format {
+            WebhookFormat::Generic => json!({
+                "event": "run_completed",
+                "status": if self.failed == 0 { "succeeded" } else { "failed" },
+                "summary": self,
+            }),
+            WebhookFormat::Slack => {
+                let mut text = format!(
+                    "{} *{}*",
+                    if self.failed == 0 {
+                        ":white_check_mark:"
+                    } else {
+                        ":x:"
+                    },
+                    self.headline()
+                );
+                for line in &failure_lines {
+                    text.push_str(&format!("\n• {}", line));
+                }
+                json!({ "text": text })
+            }
+            WebhookFormat::Teams => json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": self.headline(),
+                "themeColor": if self.failed == 0 { "2EB886" } else { "D13438" },
+                "title": self.headline(),
+                "text": failure_lines
+                    .iter()
+                    .map(|line| format!("- {}", line))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_pack_overrides() {
+        let global = WebhookConfig {
+            url: Some("https://hooks.example/global".to_string()),
+            format: Some(WebhookFormat::Slack),
+            ..Default::default()
+        };
+        let webhook = WebhookConfig::resolve(&global, None).unwrap();
+        assert_eq!(webhook.url, "https://hooks.example/global");
+        assert_eq!(webhook.format, WebhookFormat::Slack);
+        assert!(!webhook.only_on_failure);
+
+        let pack = WebhookConfig {
+            format: Some(WebhookFormat::Teams),
+            only_on_failure: Some(true),
+            ..Default::default()
+        };
+        let webhook = WebhookConfig::resolve(&global, Some(&pack)).unwrap();
+        assert_eq!(webhook.url, "https://hooks.example/global");
+        assert_eq!(webhook.format, WebhookFormat::Teams);
+        assert!(webhook.only_on_failure);
+
+        let muted = WebhookConfig {
+            enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(WebhookConfig::resolve(&global, Some(&muted)).is_none());
+        assert!(WebhookConfig::resolve(&WebhookConfig::default(), None).is_none());
+    }
+
+    #[test]
+    fn test_payload_templates() {
+        let mut summary = RunSummary::new("Failed logons");
+        summary.succeeded = 3;
+        summary.rows = 42;
+        summary.failed = 1;
+        summary.failures.push(RunFailure {
+            query: "brute-force".to_string(),
+            workspace: "ws-eu".to_string(),
+            error: "timeout".to_string(),
+        });
+
+        let slack = summary.payload(WebhookFormat::Slack);
+        assert_eq!(
+            slack["text"],
+            ":x: *Failed logons: 1 of 4 queries failed (42 rows from the rest)*\n• brute-force on ws-eu: timeout"
+        );
+
+        let teams = summary.payload(WebhookFormat::Teams);
+        assert_eq!(teams["@type"], "MessageCard");
+        assert_eq!(teams["themeColor"], "D13438");
+
+        let generic = summary.payload(WebhookFormat::Generic);
+        assert_eq!(generic["status"], "failed");
+        assert_eq!(generic["summary"]["failures"][0]["workspace"], "ws-eu");
+    }
+}
@@ -1,4 +1,12 @@
+use crate::client::Subscription;
+use crate::config::SubscriptionFilter;
+use crate::error::Result;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default time-to-live for the workspace discovery cache (1 hour)
+pub const DEFAULT_WORKSPACE_CACHE_TTL_SECS: u64 = 3600;
 
 /// Represents a Log Analytics workspace
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,11 +29,15 @@ pub struct Workspace {
     /// The resource group name
     pub resource_group: String,
 
-    /// The tenant ID (for Lighthouse support)
+    /// The tenant that owns the workspace (the customer tenant for Lighthouse delegations)
     pub tenant_id: String,
 
     /// The subscription display name
     pub subscription_name: String,
+
+    /// Whether access comes from an Azure Lighthouse delegation
+    #[serde(default)]
+    pub delegated: bool,
 }
 
 impl Workspace {
@@ -43,6 +55,32 @@ impl Workspace {
             .collect()
     }
 
+    /// Folder for this workspace's runs: `<output_folder>/<subscription>/<workspace>_<id>`
+    pub fn output_dir(&self, output_folder: &Path) -> PathBuf {
+        self.output_folder_key()
+            .split('/')
+            .fold(output_folder.to_path_buf(), |dir, part| dir.join(part))
+    }
+
+    /// This workspace's folder relative to the output folder, as `<subscription>/<workspace>_<id>`
+    /// Names are normalized, so the first 8 characters of the workspace GUID keep workspaces
+    /// whose subscription and workspace names collide after normalizing from sharing a folder.
+    pub fn output_folder_key(&self) -> String {
+        let id: String = Self::normalize_name(&self.workspace_id)
+            .chars()
+            .take(8)
+            .collect();
+        let mut workspace = Self::normalize_name(&self.name);
+        if !id.is_empty() {
+            workspace = format!("{}_{}", workspace, id);
+        }
+        format!(
+            "{}/{}",
+            Self::normalize_name(&self.subscription_name),
+            workspace
+        )
+    }
+
     /// Extract resource group name from resource ID
     /// Resource ID format: /subscriptions/{sub}/resourceGroups/{rg}/providers/Microsoft.OperationalInsights/workspaces/{name}
     pub fn extract_resource_group(resource_id: &str) -> Option<String> {
@@ -57,6 +95,25 @@ impl Workspace {
 
         None
     }
+
+    /// Workspace in subscription "sub" (Production), resource group "rg", for tests
+    #[cfg(test)]
+    pub(crate) fn fixture(name: &str) -> Self {
+        Self {
+            workspace_id: format!("{}-id", name),
+            resource_id: format!(
+                "/subscriptions/sub/resourceGroups/rg/providers/Microsoft.OperationalInsights/workspaces/{}",
+                name
+            ),
+            name: name.into(),
+            location: "westeurope".into(),
+            subscription_id: "sub".into(),
+            resource_group: "rg".into(),
+            tenant_id: "tenant".into(),
+            subscription_name: "Production".into(),
+            delegated: false,
+        }
+    }
 }
 
 /// Response from Azure Management API when listing workspaces
@@ -80,15 +137,8 @@ pub(crate) struct WorkspaceProperties {
     pub customer_id: String,
 }
 
-impl From<(WorkspaceResource, String, String, String)> for Workspace {
-    fn from(
-        (resource, subscription_id, tenant_id, subscription_name): (
-            WorkspaceResource,
-            String,
-            String,
-            String,
-        ),
-    ) -> Self {
+impl From<(WorkspaceResource, &Subscription)> for Workspace {
+    fn from((resource, subscription): (WorkspaceResource, &Subscription)) -> Self {
         let resource_group = Workspace::extract_resource_group(&resource.id)
             .unwrap_or_else(|| "unknown".to_string());
 
@@ -97,10 +147,91 @@ impl From<(WorkspaceResource, String, String, String)> for Workspace {
             resource_id: resource.id,
             name: resource.name,
             location: resource.location,
-            subscription_id,
+            subscription_id: subscription.subscription_id.clone(),
             resource_group,
-            tenant_id,
-            subscription_name,
+            tenant_id: subscription.workspace_tenant_id().to_string(),
+            subscription_name: subscription.display_name.clone(),
+            delegated: subscription.is_delegated(),
+        }
+    }
+}
+
+/// On-disk cache of discovered workspaces (<data_dir>/cache/workspaces.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceCache {
+    /// When the workspaces were enumerated (RFC3339)
+    pub fetched_at: String,
+    /// Subscription filter in effect when the workspaces were enumerated
+    #[serde(default)]
+    pub filter: SubscriptionFilter,
+    /// Tenant the Azure CLI was signed in to when the workspaces were enumerated
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Resource Manager endpoint of the cloud the workspaces were enumerated in
+    #[serde(default)]
+    pub management: Option<String>,
+    /// Cached workspaces
+    pub workspaces: Vec<Workspace>,
+}
+
+impl WorkspaceCache {
+    /// Create a new cache entry stamped with the current time
+    pub fn new(
+        workspaces: Vec<Workspace>,
+        filter: SubscriptionFilter,
+        tenant_id: String,
+        management: String,
+    ) -> Self {
+        Self {
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            filter,
+            tenant_id: Some(tenant_id),
+            management: Some(management),
+            workspaces,
+        }
+    }
+
+    /// Get the cache file path
+    pub fn path() -> Result<PathBuf> {
+        Ok(crate::paths::data_dir()?
+            .join("cache")
+            .join("workspaces.json"))
+    }
+
+    /// Load the cache from disk (returns None if missing or unreadable)
+    pub fn load() -> Option<Self> {
+        let path = Self::path().ok()?;
+        let json = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Save the cache to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Age of the cache, or None if the timestamp can't be parsed
+    pub fn age(&self) -> Option<Duration> {
+        let fetched_at = chrono::DateTime::parse_from_rfc3339(&self.fetched_at).ok()?;
+        (chrono::Utc::now() - fetched_at.with_timezone(&chrono::Utc))
+            .to_std()
+            .ok()
+    }
+
+    /// Check if the cache is still within its TTL (a TTL of zero disables caching)
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        !ttl.is_zero() && !self.workspaces.is_empty() && self.age().is_some_and(|age| age < ttl)
+    }
+
+    /// Check if the cache was written for this filter, signed-in tenant and cloud
+    pub fn matches(&self, filter: &SubscriptionFilter, tenant_id: &str, management: &str) -> bool {
+        self.filter == *filter
+            && self.tenant_id.as_deref() == Some(tenant_id)
+            && self.management.as_deref() == Some(management)
     }
 }
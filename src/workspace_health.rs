@@ -0,0 +1,174 @@
+use crate::error::Result;
+use crate::query_job::QueryJobResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Consecutive failures after which a workspace is flagged as failing
+pub const DEFAULT_FAILURE_STREAK: u32 = 3;
+
+/// Query outcomes on one workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceStats {
+    pub workspace_name: String,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    pub successes: u32,
+    pub failures: u32,
+    /// Combined duration of the successful queries
+    pub total_success_millis: u64,
+    /// RFC 3339 time of the last success
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl WorkspaceStats {
+    /// Average duration of the successful queries
+    pub fn average_latency(&self) -> Option<Duration> {
+        (self.successes > 0)
+            .then(|| Duration::from_millis(self.total_success_millis / self.successes as u64))
+    }
+
+    /// Check if the workspace has failed at least `streak` times in a row
+    pub fn is_failing(&self, streak: u32) -> bool {
+        streak > 0 && self.consecutive_failures >= streak
+    }
+}
+
+/// Per-workspace query statistics, keyed by workspace ID
+///
+/// Optionally persisted in `<data_dir>/workspace_health.json` so streaks carry across sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceHealth {
+    pub workspaces: BTreeMap<String, WorkspaceStats>,
+}
+
+impl WorkspaceHealth {
+    /// Path of the persisted statistics
+    pub fn path() -> Result<PathBuf> {
+        Ok(crate::paths::data_dir()?.join("workspace_health.json"))
+    }
+
+    /// Load the persisted statistics (empty if there are none)
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, workspace_id: &str) -> Option<&WorkspaceStats> {
+        self.workspaces.get(workspace_id)
+    }
+
+    /// Check if a workspace has failed at least `streak` times in a row
+    pub fn is_failing(&self, workspace_id: &str, streak: u32) -> bool {
+        self.get(workspace_id)
+            .is_some_and(|stats| stats.is_failing(streak))
+    }
+
+    /// Count a finished job
+    /// Queries Azure rejected (syntax or semantic errors) say nothing about the workspace and
+    /// are left out.
+    pub fn record(&mut self, result: &QueryJobResult) {
+        if result.result.as_ref().is_err_and(|e| e.is_query_rejected()) {
+            return;
+        }
+
+        let stats = self
+            .workspaces
+            .entry(result.workspace_id.clone())
+            .or_default();
+        stats.workspace_name = result.workspace_name.clone();
+        match &result.result {
+            Ok(_) => {
+                stats.successes += 1;
+                stats.consecutive_failures = 0;
+                stats.total_success_millis += result.elapsed.as_millis() as u64;
+                stats.last_success = Some(result.timestamp.to_rfc3339());
+            }
+            Err(e) => {
+                stats.failures += 1;
+                stats.consecutive_failures += 1;
+                stats.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KqlPanopticonError;
+
+    fn result(outcome: std::result::Result<(), KqlPanopticonError>, millis: u64) -> QueryJobResult {
+        QueryJobResult {
+            workspace_id: "ws-1".to_string(),
+            workspace_name: "ws-eu".to_string(),
+            query: "SigninLogs".to_string(),
+            result: outcome.map(|_| crate::query_job::JobSuccess {
+                row_count: 1,
+                page_count: 1,
+                output_path: PathBuf::new(),
+                file_size: 0,
+                truncated: false,
+                billed_bytes: None,
+                warnings: Vec::new(),
+                statistics: None,
+                uploads: Vec::new(),
+            }),
+            elapsed: Duration::from_millis(millis),
+            timestamp: crate::time_zone::now(),
+        }
+    }
+
+    #[test]
+    fn test_failure_streaks() {
+        let mut health = WorkspaceHealth::default();
+        health.record(&result(Ok(()), 1000));
+        health.record(&result(Ok(()), 3000));
+        for _ in 0..3 {
+            health.record(&result(
+                Err(KqlPanopticonError::QueryExecutionFailed(
+                    "timeout".to_string(),
+                )),
+                0,
+            ));
+        }
+        // A rejected query neither breaks nor extends the streak
+        health.record(&result(
+            Err(KqlPanopticonError::AzureApiError {
+                status: 400,
+                message: "bad query".to_string(),
+                code: Some("SyntaxError".to_string()),
+            }),
+            0,
+        ));
+
+        let stats = health.get("ws-1").unwrap();
+        assert_eq!(stats.consecutive_failures, 3);
+        assert_eq!(stats.failures, 3);
+        assert_eq!(stats.average_latency(), Some(Duration::from_secs(2)));
+        assert!(health.is_failing("ws-1", DEFAULT_FAILURE_STREAK));
+        assert!(!health.is_failing("ws-1", 0));
+
+        health.record(&result(Ok(()), 2000));
+        assert!(!health.is_failing("ws-1", DEFAULT_FAILURE_STREAK));
+        assert!(health.get("ws-1").unwrap().last_success.is_some());
+    }
+}